@@ -0,0 +1,190 @@
+use crate::http;
+use crate::json::{parse_json, JsonValue};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Timelike, Utc};
+
+const AW_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// What the activity watchers recorded on one day, in local time.
+pub struct DayActivity
+{
+    /// Stretches spent at the computer, from the AFK bucket, sorted and not overlapping.
+    pub active: Vec<(NaiveDateTime, NaiveDateTime)>,
+    /// Focused windows, from the window bucket.
+    pub windows: Vec<WindowEvent>,
+}
+
+pub struct WindowEvent
+{
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub app: String,
+    pub title: String,
+}
+
+impl DayActivity
+{
+    #[must_use]
+    pub fn get_active_time_within(&self, start: NaiveDateTime, end: NaiveDateTime) -> TimeDelta
+    {
+        self.active.iter().map(|(active_start, active_end)| get_overlap((*active_start, *active_end), (start, end))).sum()
+    }
+
+    /// From the first to the last active moment within `start` and `end`.
+    #[must_use]
+    pub fn get_active_span_within(&self, start: NaiveDateTime, end: NaiveDateTime) -> Option<(NaiveDateTime, NaiveDateTime)>
+    {
+        let mut spans = self
+            .active
+            .iter()
+            .map(|(active_start, active_end)| ((*active_start).max(start), (*active_end).min(end)))
+            .filter(|(span_start, span_end)| span_start < span_end);
+
+        let first = spans.next()?;
+
+        Some(spans.fold(first, |(span_start, _), (_, span_end)| (span_start, span_end)))
+    }
+
+    /// The window that was in focus the longest within `start` and `end`.
+    #[must_use]
+    pub fn get_main_window_within(&self, start: NaiveDateTime, end: NaiveDateTime) -> Option<&WindowEvent>
+    {
+        // Titles change all the time, so the app is picked first.
+        let mut app_times: Vec<(&str, TimeDelta)> = Vec::new();
+
+        for window in &self.windows
+        {
+            let overlap = get_overlap((window.start, window.end), (start, end));
+
+            match app_times.iter_mut().find(|(app, _)| *app == window.app)
+            {
+                Some((_, time)) => *time += overlap,
+                None => app_times.push((&window.app, overlap)),
+            }
+        }
+
+        let (main_app, _) = app_times.into_iter().filter(|(_, time)| *time > TimeDelta::zero()).max_by_key(|(_, time)| *time)?;
+
+        self.windows
+            .iter()
+            .filter(|window| window.app == main_app)
+            .max_by_key(|window| get_overlap((window.start, window.end), (start, end)))
+    }
+}
+
+/// Reads the AFK and window buckets of `date` from the aw-server at `url`. Without a window bucket only the
+/// active time is known.
+///
+/// # Errors
+///
+/// If the aw-server can't be reached, answers with an error or has no AFK bucket.
+pub fn fetch_day_activity(url: &str, date: NaiveDate) -> Result<DayActivity, String>
+{
+    let buckets = request(&format!("{url}/api/0/buckets/"))?;
+
+    let afk_bucket = find_bucket(&buckets, "afkstatus").ok_or("ACTIVITYWATCH HAS NO AFK BUCKET")?;
+    let window_bucket = find_bucket(&buckets, "currentwindow");
+
+    let day_start = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    let range = format!("start={}&end={}", to_aw_time(day_start), to_aw_time(day_start + TimeDelta::days(1)));
+
+    let mut active = get_events(url, &afk_bucket, &range)?
+        .iter()
+        .filter(|event| event.get("data").and_then(|data| data.get("status")).and_then(JsonValue::as_str) == Some("not-afk"))
+        .filter_map(get_event_span)
+        .collect::<Vec<(NaiveDateTime, NaiveDateTime)>>();
+
+    active.sort();
+    active.dedup_by(|(next_start, next_end), (_, end)| {
+        let overlaps = *next_start <= *end;
+
+        if overlaps
+        {
+            *end = (*end).max(*next_end);
+        }
+
+        overlaps
+    });
+
+    let windows = match window_bucket
+    {
+        Some(window_bucket) => get_events(url, &window_bucket, &range)?
+            .iter()
+            .filter_map(|event| {
+                let (start, end) = get_event_span(event)?;
+                let window_data = event.get("data")?;
+
+                Some(WindowEvent {
+                    start,
+                    end,
+                    app: window_data.get("app").and_then(JsonValue::as_str).unwrap_or_default().to_string(),
+                    title: window_data.get("title").and_then(JsonValue::as_str).unwrap_or_default().to_string(),
+                })
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    Ok(DayActivity {
+        active,
+        windows,
+    })
+}
+
+/// The id of the most recently updated bucket of `bucket_type`, there is one per machine.
+fn find_bucket(buckets: &JsonValue, bucket_type: &str) -> Option<String>
+{
+    buckets
+        .as_object()?
+        .iter()
+        .filter(|(_, bucket)| bucket.get("type").and_then(JsonValue::as_str) == Some(bucket_type))
+        .max_by_key(|(_, bucket)| bucket.get("last_updated").and_then(JsonValue::as_str).unwrap_or_default().to_string())
+        .map(|(id, _)| id.clone())
+}
+
+fn get_events(url: &str, bucket: &str, range: &str) -> Result<Vec<JsonValue>, String>
+{
+    match request(&format!("{url}/api/0/buckets/{bucket}/events?{range}&limit=-1"))?
+    {
+        JsonValue::Array(events) => Ok(events),
+        _ => Err(String::from("ACTIVITYWATCH SENT INVALID EVENTS")),
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn get_event_span(event: &JsonValue) -> Option<(NaiveDateTime, NaiveDateTime)>
+{
+    let start = DateTime::parse_from_rfc3339(event.get("timestamp")?.as_str()?).ok()?.with_timezone(&Local).naive_local();
+    let duration = TimeDelta::milliseconds((event.get("duration")?.as_f64()? * 1000.0) as i64);
+
+    let start = start.with_nanosecond(0)?;
+
+    Some((start, start + duration))
+}
+
+fn get_overlap(first: (NaiveDateTime, NaiveDateTime), second: (NaiveDateTime, NaiveDateTime)) -> TimeDelta
+{
+    (first.1.min(second.1) - first.0.max(second.0)).max(TimeDelta::zero())
+}
+
+/// The aw-server keeps times in UTC.
+fn to_aw_time(time: NaiveDateTime) -> String
+{
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .map_or_else(|| time.and_utc(), |time| time.with_timezone(&Utc))
+        .format(AW_DATE_FORMAT)
+        .to_string()
+}
+
+fn request(url: &str) -> Result<JsonValue, String>
+{
+    let (status, body) = http::request("GET", url, &[], None)?;
+
+    match status
+    {
+        200..=299 => parse_json(&body).ok_or_else(|| String::from("ACTIVITYWATCH SENT INVALID JSON")),
+        0 => Err(String::from("ACTIVITYWATCH UNREACHABLE")),
+        status => Err(format!("ACTIVITYWATCH ERROR {status}")),
+    }
+}
@@ -1,15 +1,73 @@
-use crate::app_state::{CommandState, SessionField};
-use crate::database_handler::DatabaseHandler;
-use crate::io::Out;
-use crate::session::Session;
-use chrono::{Datelike, Timelike};
-use chrono::{Local, NaiveDateTime};
+use crate::activitywatch::{fetch_day_activity, DayActivity};
+use crate::app_state::{
+    CommandState, ConfirmOpen, ListRow, OverlapOrigin, SessionEditState, SessionField,
+    SessionFieldEditState, SessionInputState, SessionModifyState, SettingsField, SortColumn, StatsPeriod, UnsavedEdit,
+};
+use crate::clipboard::copy_to_clipboard;
+use crate::clock::{Clock, SystemClock};
+use crate::config::{
+    set_config_value, ClockFormat, Config, ConfirmAction, DateLabels, DateOrder, Goal, GoalPeriod, RoundingMode, WeekNumbers,
+};
+use crate::crypto::Cipher;
+use crate::daemon::request_daemon;
+use crate::day_off::DayOff;
+use crate::database_handler::{is_valid_workspace_name, AuditEntry, DatabaseHandler, RejectedLine, DATE_FORMAT, VALUE_SEPARATOR};
+use crate::editor::{edit_in_editor, format_session_text, parse_session_text};
+use crate::exporter::{export_daily_notes, export_icalendar, ExportForm};
+use crate::hooks::HookEvent;
+use crate::importer::{import_file, CsvMapping, ImportSummary, ImportedFile, ImportedSessions};
+use crate::issues::Issue;
+use crate::json::JsonValue;
+use crate::logger::set_log_level;
+use crate::manual_entry::ManualEntry;
+use crate::notes_editor::NotesEditor;
+use crate::notifications::send_notification;
+use crate::remote;
+use crate::remote::{RemoteStorage, SessionChange};
+use crate::report::{get_flex_balance, get_flex_time};
+use crate::server::{session_from_json, JSON_DATE_FORMAT};
+use crate::session::{format_duration, format_duration_short, Session};
+use crate::sync;
+use crate::sync::SyncTarget;
+use crate::text::get_fuzzy_match_rank;
+use crate::text_input::TextInput;
+use crate::time_input::{get_week_start, parse_duration, parse_start_input, parse_time, INPUT_DATE_FORMAT, INPUT_TIME_FORMAT};
+use crate::toggl;
+use crate::toggl::TogglAccount;
+use crate::tui::colors::{load_themes, Theme};
+use crate::tui::io::{BufferBackend, ClickArea, ClickTarget, Key, Out, Vector2};
+use chrono::Datelike;
+use chrono::{Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Weekday};
+use log::{debug, info, warn};
+use std::cmp;
+use std::env;
+use std::path::PathBuf;
+use std::slice;
+use std::thread::JoinHandle;
 
+const MAX_DESCRIPTION_SUGGESTIONS: usize = 6;
+/// What the settings offer for the shortest session kept without asking, in seconds, 0 for never asking.
+const MIN_DURATION_CHOICES: [i64; 6] = [0, 30, 60, 120, 300, 600];
+const ROUNDING_MINUTES_CHOICES: [i64; 7] = [1, 5, 6, 10, 15, 30, 60];
+/// How far the settings move the workday start and end.
+const SETTINGS_TIME_STEP: TimeDelta = TimeDelta::minutes(30);
+/// First line of the unsaved edit file, telling what the second line holds.
+const UNSAVED_DESCRIPTION: &str = "description";
+const UNSAVED_SESSION: &str = "session";
+/// How long the footer tells what the last action did.
+const STATUS_MESSAGE_DURATION: TimeDelta = TimeDelta::seconds(4);
+/// Shown for changes a read-only instance refuses. Only new, continue and end are handed to the daemon.
+const READ_ONLY_STATUS: &str = "READ-ONLY WHILE THE DAEMON RUNS";
+
+#[allow(clippy::struct_excessive_bools)]
 pub struct AppManager
 {
     pub version: String,
+    pub config: Config,
     pub renderer: Out,
+    pub clock: Box<dyn Clock>,
     database_handler: DatabaseHandler,
+    passphrase: Option<String>,
     value_separator: char,
     date_format: String,
     pub running: bool,
@@ -21,21 +79,124 @@ pub struct AppManager
     selected_tag_index: usize,
     pub sessions: Vec<Session>,
     pub state: CommandState,
-    pub description_buffer: String,
-    pub tag_buffer: String,
+    pub description_buffer: TextInput,
+    pub selected_suggestion_index: Option<usize>,
+    pub assigned_issues: Vec<Issue>,
+    pub git_branch: Option<String>,
+    /// True while a daemon owns the database, the running session is then started and ended through it.
+    pub daemon_connected: bool,
+    hook_threads: Vec<JoinHandle<()>>,
+    /// Start of the session and of the idle time last notified about, so each is notified about once.
+    notified_session_start: Option<NaiveDateTime>,
+    notified_idle_start: Option<NaiveDateTime>,
+    pub tag_filter: TextInput,
+    pub tag_buffer: TextInput,
+    pub start_buffer: String,
+    pub timer_buffer: String,
+    /// Id of the session started with a timer and how long it was meant to run, counted down while it runs.
+    pub session_timer: Option<(u64, TimeDelta)>,
+    notified_timer_session_id: Option<u64>,
+    /// The running session the user was asked about ending at the auto stop time, so they are asked once.
+    auto_stop_asked_session_id: Option<u64>,
     pub session_edit_buffer: Option<Session>,
+    /// The edit a previous run left unsaved, offered for restoring when the workspace opens.
+    pub unsaved_edit: Option<UnsavedEdit>,
+    /// What was last written to the unsaved edit file, so it is only written again when the edit changes.
+    journaled_edit_lines: Vec<String>,
+    pub manual_entry: ManualEntry,
+    pub notes_editor: Option<NotesEditor>,
+    pub pending_session: Option<Session>,
+    pub overlapping_session_indices: Vec<usize>,
+    pub overlap_adjust_failed: bool,
+    pub session_edit_error: Option<String>,
+    pub backups: Vec<String>,
+    pub selected_backup_index: usize,
+    pub audit_entries: Vec<AuditEntry>,
+    pub database_file_sizes: Vec<(String, u64)>,
+    pub sync_message: Option<String>,
+    /// Outcome of the last copy to the clipboard, shown until the next key press.
+    pub clipboard_message: Option<String>,
+    /// What the last action did and when, shown in the footer for `STATUS_MESSAGE_DURATION`.
+    status_message: Option<(String, NaiveDateTime)>,
+    pub running_sessions_elsewhere: Vec<(String, Session)>,
+    pub exported_running_session: Option<Session>,
+    pub selected_audit_index: usize,
+    pub workspaces: Vec<String>,
+    pub selected_workspace_index: usize,
+    pub workspace_name_buffer: String,
+    pub workspace_error: Option<String>,
+    pub rejected_lines: Vec<RejectedLine>,
+    pub import_path_buffer: String,
+    pub import_error: Option<String>,
+    pub import_summary: Option<ImportSummary>,
+    pub csv_mapping: Option<CsvMapping>,
+    pub export_form: ExportForm,
+    pub marked_session_ids: Vec<u64>,
+    pub collapsed_days: Vec<NaiveDate>,
+    pub calendar_date: NaiveDate,
+    /// Days marked as not worked, in no particular order.
+    pub days_off: Vec<(NaiveDate, DayOff)>,
+    pub stats_period: StatsPeriod,
+    pub stats_date: NaiveDate,
+    /// Outcome of writing the stats period to the daily notes, shown in the stats view.
+    pub daily_note_message: Option<String>,
+    pub report_week_start: NaiveDate,
+    pub gaps_date: NaiveDate,
+    pub selected_gap_index: usize,
+    pub selected_template_index: usize,
+    /// What the activity watchers recorded on the gaps day, or why the aw-server couldn't be asked.
+    pub day_activity: Option<Result<DayActivity, String>>,
+    pub help_open: bool,
+    pub debug_log_open: bool,
+    pub themes: Vec<Theme>,
+    pub selected_theme_index: usize,
+    pub theme_errors: Vec<String>,
+    /// The config as it was when the settings were opened, restored when they are cancelled.
+    pub settings_backup: Option<Config>,
+    pub list_sort: Option<(SortColumn, bool)>,
+    pub click_areas: Vec<ClickArea>,
 }
 
 impl AppManager
 {
-    pub fn new() -> Self
+    /// Opens `workspace` with the passphrase and key from `DatabaseHandler::unlock` if it is encrypted. The passphrase
+    /// is kept to open other workspaces encrypted with it.
+    #[must_use]
+    pub fn new(workspace: &str, passphrase: Option<String>, cipher: Option<Cipher>) -> Self
+    {
+        Self::with_parts(DatabaseHandler::new(workspace, cipher), passphrase, Out::new(), Box::new(SystemClock))
+    }
+
+    /// Opens `workspace` in memory with the default config, see `DatabaseHandler::new_ephemeral`.
+    #[must_use]
+    pub fn new_ephemeral(workspace: &str) -> Self
+    {
+        Self::with_parts(DatabaseHandler::new_ephemeral(workspace), None, Out::new(), Box::new(SystemClock))
+    }
+
+    /// Opens `workspace` like `new` without taking over the terminal, for commands changing sessions from the command
+    /// line.
+    #[must_use]
+    pub fn new_headless(workspace: &str, passphrase: Option<String>, cipher: Option<Cipher>) -> Self
+    {
+        let renderer = Out::with_backend(Box::new(BufferBackend::new(Vector2::new(80, 20))));
+
+        Self::with_parts(DatabaseHandler::new(workspace, cipher), passphrase, renderer, Box::new(SystemClock))
+    }
+
+    /// Works on `database_handler`, draws through `renderer` and takes the time from `clock`, e.g. on an ephemeral
+    /// database into a buffer at a fixed time in tests.
+    pub fn with_parts(database_handler: DatabaseHandler, passphrase: Option<String>, renderer: Out, clock: Box<dyn Clock>) -> Self
     {
         let mut manager = AppManager {
             version: "0.4.6".to_string(),
-            renderer: Out::new(),
-            database_handler: DatabaseHandler::new(),
-            value_separator: ';',
-            date_format: "%d-%m-%Y %H:%M:%S".to_string(),
+            config: Config::new(),
+            renderer,
+            clock,
+            database_handler,
+            passphrase,
+            value_separator: VALUE_SEPARATOR,
+            date_format: DATE_FORMAT.to_string(),
             running: true,
             tags: Vec::new(),
             temp_tag_index: 0,
@@ -45,258 +206,3618 @@ impl AppManager
             selected_tag_index: 0,
             sessions: Vec::new(),
             state: CommandState::Idle,
-            description_buffer: String::new(),
-            tag_buffer: String::new(),
+            description_buffer: TextInput::new(),
+            selected_suggestion_index: None,
+            assigned_issues: Vec::new(),
+            git_branch: None,
+            daemon_connected: false,
+            hook_threads: Vec::new(),
+            notified_session_start: None,
+            notified_idle_start: None,
+            tag_filter: TextInput::new(),
+            tag_buffer: TextInput::new(),
+            start_buffer: String::new(),
+            timer_buffer: String::new(),
+            session_timer: None,
+            notified_timer_session_id: None,
+            auto_stop_asked_session_id: None,
             session_edit_buffer: None,
+            unsaved_edit: None,
+            journaled_edit_lines: Vec::new(),
+            manual_entry: ManualEntry::new(),
+            notes_editor: None,
+            pending_session: None,
+            overlapping_session_indices: Vec::new(),
+            overlap_adjust_failed: false,
+            session_edit_error: None,
+            backups: Vec::new(),
+            selected_backup_index: 0,
+            audit_entries: Vec::new(),
+            database_file_sizes: Vec::new(),
+            sync_message: None,
+            clipboard_message: None,
+            status_message: None,
+            running_sessions_elsewhere: Vec::new(),
+            exported_running_session: None,
+            selected_audit_index: 0,
+            workspaces: Vec::new(),
+            selected_workspace_index: 0,
+            workspace_name_buffer: String::new(),
+            workspace_error: None,
+            rejected_lines: Vec::new(),
+            import_path_buffer: String::new(),
+            import_error: None,
+            import_summary: None,
+            csv_mapping: None,
+            export_form: ExportForm::new(),
+            marked_session_ids: Vec::new(),
+            collapsed_days: Vec::new(),
+            calendar_date: NaiveDate::default(),
+            days_off: Vec::new(),
+            stats_period: StatsPeriod::Week,
+            daily_note_message: None,
+            stats_date: NaiveDate::default(),
+            report_week_start: NaiveDate::default(),
+            gaps_date: NaiveDate::default(),
+            selected_gap_index: 0,
+            selected_template_index: 0,
+            day_activity: None,
+            help_open: false,
+            debug_log_open: false,
+            themes: Vec::new(),
+            selected_theme_index: 0,
+            theme_errors: Vec::new(),
+            settings_backup: None,
+            list_sort: None,
+            click_areas: Vec::new(),
+        };
+
+        manager.load_config();
+        manager.renderer.set_colors_enabled(env::var_os("NO_COLOR").is_none_or(|no_color| no_color.is_empty()));
+
+        manager.pull_from_sync_remote();
+        manager.pull_from_remote_storage();
+        manager.pull_from_toggl();
+        manager.load_database();
+        manager.fetch_assigned_issues();
+
+        manager
+    }
+
+    /// Reads the global config with the open workspace's overrides on top and applies its appearance settings.
+    fn load_config(&mut self)
+    {
+        let workspace_config_path = self.database_handler.get_workspace_config_file_path();
+
+        // Ephemeral databases don't read anything from disk, the config included.
+        self.config = if self.is_ephemeral()
+        {
+            Config::new()
+        }
+        else
+        {
+            Config::load(&self.database_handler.get_config_file_path(), workspace_config_path.as_deref())
         };
 
-        if let Some(sessions) = manager.database_handler.import_sessions(manager.value_separator, &manager.date_format)
+        set_log_level(self.config.log_level);
+
+        if let Some(error) = &self.config.error
         {
-            manager.sessions = sessions;
+            warn!("Config of workspace {} not applied: {error}", self.get_workspace());
+        }
 
-            if let Some(tags) = manager.database_handler.import_tags()
-            {
-                manager.tags = tags;
+        self.reload_themes();
+        self.renderer.set_ascii(self.config.ascii);
+    }
 
-                let last_used_tag = &manager.sessions.last().unwrap().tag;
-                let tag_index = manager.get_index_of_tag(last_used_tag);
+    /// Reads the sessions and tags of the open workspace, replacing whatever was loaded before.
+    fn load_database(&mut self)
+    {
+        self.merge_conflict_copies();
 
-                manager.set_selected_tag_index(tag_index);
-            }
+        let (sessions, rejected_lines) = self.database_handler.import_sessions(self.value_separator, &self.date_format);
+
+        self.sessions = sessions.unwrap_or_default();
+        self.tags = self.database_handler.import_tags().unwrap_or_default();
+        self.days_off = self.database_handler.import_days_off(self.value_separator);
+        self.rejected_lines.clear();
+
+        info!("Loaded {} sessions and {} tags of workspace {}", self.sessions.len(), self.tags.len(), self.get_workspace());
+
+        // A mark left by a previous run belongs to a session that was lost with it.
+        self.database_handler.export_running_session(None, self.value_separator, &self.date_format);
+        self.exported_running_session = None;
+        self.running_sessions_elsewhere =
+            self.database_handler.import_running_sessions_elsewhere(self.value_separator, &self.date_format);
+
+        self.daemon_connected = self.is_read_only() && request_daemon(self.get_workspace(), "GET", "/status", None).is_some();
+        self.take_running_session_from_daemon();
+
+        self.state = if self.is_read_only() && !self.daemon_connected
+        {
+            CommandState::Locked
         }
+        else if rejected_lines.is_empty()
+        {
+            CommandState::Idle
+        }
+        else
+        {
+            warn!("{} lines of workspace {} could not be read", rejected_lines.len(), self.get_workspace());
+            self.rejected_lines = rejected_lines;
+            CommandState::ImportReport
+        };
 
-        manager
+        self.select_initial_tag();
+
+        // A read-only instance would find the edit the instance owning the database is making right now.
+        self.journaled_edit_lines = if self.is_read_only()
+        {
+            Vec::new()
+        }
+        else
+        {
+            self.database_handler.import_unsaved_edit()
+        };
+        self.unsaved_edit = self.parse_unsaved_edit(&self.journaled_edit_lines);
+
+        if self.unsaved_edit.is_some() && matches!(self.state, CommandState::Idle)
+        {
+            info!("Found an unsaved edit of workspace {}", self.get_workspace());
+            self.state = CommandState::RestoreEdit;
+        }
     }
 
-    pub fn increment_selected_session_field(&mut self)
+    fn parse_unsaved_edit(&self, lines: &[String]) -> Option<UnsavedEdit>
     {
-        if let Some(session_buffer) = &self.session_edit_buffer
+        match lines
         {
-            self.selected_session_field = match self.selected_session_field
+            [kind, description] if kind == UNSAVED_DESCRIPTION => Some(UnsavedEdit::Description(description.clone())),
+            [kind, session_string] if kind == UNSAVED_SESSION =>
             {
-                SessionField::Date(_) => SessionField::Description(session_buffer.description.clone()),
-                SessionField::Description(_) => SessionField::Tag(session_buffer.tag.clone()),
-                SessionField::Tag(_) => SessionField::Start(session_buffer.start),
-                SessionField::Start(_) | SessionField::End(_) => SessionField::End(session_buffer.end),
-                SessionField::None => SessionField::None,
+                let (sessions, _) =
+                    DatabaseHandler::parse_sessions(slice::from_ref(session_string), self.value_separator, &self.date_format);
+
+                sessions?.pop().map(UnsavedEdit::Session)
             }
+            _ => None,
         }
     }
 
-    pub fn decrement_selected_session_field(&mut self)
+    /// The description being typed for a new session or the stored session being edited, if there are changes to lose.
+    fn get_unsaved_edit(&self) -> Option<UnsavedEdit>
     {
-        if let Some(session_buffer) = &self.session_edit_buffer
+        match &self.state
         {
-            self.selected_session_field = match self.selected_session_field
+            CommandState::New(_) if !self.description_buffer.trim().is_empty() =>
+            {
+                Some(UnsavedEdit::Description(self.description_buffer.to_string()))
+            }
+            CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(_) | SessionEditState::Confirm))
+            | CommandState::Overlap(OverlapOrigin::Edit) =>
             {
-                SessionField::Date(_) | SessionField::Description(_) => SessionField::Date(session_buffer.start),
-                SessionField::Tag(_) => SessionField::Description(session_buffer.description.clone()),
-                SessionField::Start(_) => SessionField::Tag(session_buffer.tag.clone()),
-                SessionField::End(_) => SessionField::Start(session_buffer.start),
-                SessionField::None => SessionField::None,
+                // Running sessions are lost with a crash, and so is what was changed about them.
+                self.session_edit_buffer
+                    .clone()
+                    .filter(|session| !session.is_running() && self.session_buffer_has_pending_changes())
+                    .map(UnsavedEdit::Session)
             }
+            _ => None,
         }
     }
 
-    pub fn get_selected_session_field_index(&self) -> usize
+    /// Writes the edit being made to the database folder, or removes it once there is none, so a run that stops
+    /// unexpectedly can offer it on the next start.
+    pub fn journal_unsaved_edit(&mut self)
     {
-        match self.selected_session_field
+        // The edit found on start is kept until it is restored or discarded.
+        if matches!(self.state, CommandState::RestoreEdit)
         {
-            SessionField::None | SessionField::Date(_) => 0,
-            SessionField::Description(_) => 1,
-            SessionField::Tag(_) => 2,
-            SessionField::Start(_) => 3,
-            SessionField::End(_) => 4,
+            return;
         }
-    }
 
-    pub fn get_index_of_tag(&self, tag: &String) -> usize
-    {
-        self.tags.iter().position(|t| t.eq(tag)).expect("Failed to retrieve tag index.")
+        let lines = match self.get_unsaved_edit()
+        {
+            Some(UnsavedEdit::Description(description)) => vec![String::from(UNSAVED_DESCRIPTION), description],
+            Some(UnsavedEdit::Session(session)) =>
+            {
+                vec![String::from(UNSAVED_SESSION), session.construct_db_string(self.value_separator, &self.date_format)]
+            }
+            None => Vec::new(),
+        };
+
+        if lines == self.journaled_edit_lines
+        {
+            return;
+        }
+
+        match self.database_handler.export_unsaved_edit(&lines)
+        {
+            Ok(()) => self.journaled_edit_lines = lines,
+            Err(error) => warn!("Keeping the unsaved edit failed: {error}"),
+        }
     }
 
-    pub fn try_start_new_session(&mut self)
+    /// Picks the edit found on start up where it was left. Returns false if the session it changed is gone.
+    pub fn restore_unsaved_edit(&mut self) -> bool
     {
-        self.description_buffer = self.description_buffer.trim().to_string();
-
-        if let Some(selected_tag) = self.tags.get(self.get_selected_tag_index())
-            && !self.description_buffer.is_empty()
+        match self.unsaved_edit.take()
         {
-            let start = self.get_current_time();
+            Some(UnsavedEdit::Description(description)) =>
+            {
+                self.description_buffer.set(&description);
+                self.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
+            }
+            Some(UnsavedEdit::Session(session)) =>
+            {
+                if self.database_handler.load_sessions_since(session.start.date())
+                {
+                    self.reload_sessions();
+                }
 
-            self.sessions.push(Session::from(&self.description_buffer, selected_tag, start, None));
+                let Some(session_index) = self.sessions.iter().position(|stored_session| stored_session.id == session.id)
+                else
+                {
+                    self.discard_unsaved_edit();
+                    return false;
+                };
 
-            self.description_buffer.clear();
+                self.selected_session_index = session_index;
+                self.copy_selected_session_to_buffer();
+                self.temp_tag_index = self.get_index_of_tag(&session.tag);
+                self.session_edit_buffer = Some(session);
+                self.state =
+                    CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(SessionFieldEditState::Browse)));
+            }
+            None => self.state = CommandState::Idle,
         }
+
+        true
+    }
+
+    /// Forgets the edit found on start.
+    pub fn discard_unsaved_edit(&mut self)
+    {
+        self.unsaved_edit = None;
+        self.state = CommandState::Idle;
+        self.journal_unsaved_edit();
     }
 
-    fn get_current_time(&self) -> NaiveDateTime
+    /// Selects the tag remembered from the last run, falling back to the configured default tag, which is created
+    /// when missing.
+    fn select_initial_tag(&mut self)
     {
-        let now = Local::now();
-        let date = now.date_naive();
-        let time = now.time();
+        if let Some(default_tag) = self.config.default_tag.clone()
+        {
+            self.store_tag(&default_tag);
+        }
 
-        let year = date.year();
-        let month = date.month();
-        let day = date.day();
+        let last_used_tag = self.database_handler.import_last_used_tag();
 
-        let hour = time.hour();
-        let minute = time.minute();
-        let second = time.second();
+        self.selected_tag_index = [last_used_tag.as_ref(), self.config.default_tag.as_ref()]
+            .into_iter()
+            .flatten()
+            .find_map(|tag| self.tags.iter().position(|existing_tag| existing_tag == tag))
+            .unwrap_or(0);
+    }
 
-        let formatted_start = format!("{day}-{month}-{year} {hour}:{minute}:{second}");
+    /// Moves to the next field shown in the list, staying put if every field after it is hidden.
+    pub fn increment_selected_session_field(&mut self)
+    {
+        self.step_selected_session_field(true, false);
+    }
 
-        NaiveDateTime::parse_from_str(&formatted_start, &self.date_format).expect("Failed to construct time.")
+    pub fn decrement_selected_session_field(&mut self)
+    {
+        self.step_selected_session_field(false, false);
     }
 
-    pub fn try_store_tag(&mut self)
+    /// Moves to the next or previous field the way Tab and Shift+Tab do, wrapping around at either end.
+    pub fn cycle_selected_session_field(&mut self, forward: bool)
     {
-        self.tag_buffer = self.tag_buffer.trim().to_string();
+        self.step_selected_session_field(forward, true);
+    }
 
-        if self.tag_buffer.is_empty() || self.tags.iter().any(|tag| tag.eq(&self.tag_buffer))
+    fn step_selected_session_field(&mut self, forward: bool, wrap: bool)
+    {
+        let Some(session_buffer) = &self.session_edit_buffer
+        else
         {
             return;
+        };
+
+        let initial_field = self.selected_session_field.clone();
+        let initial_index = self.get_selected_session_field_index();
+
+        loop
+        {
+            let field_index = self.get_selected_session_field_index();
+
+            self.selected_session_field = if forward
+            {
+                match self.selected_session_field
+                {
+                    SessionField::Date(_) => SessionField::Description(session_buffer.description.clone().into()),
+                    SessionField::Description(_) => SessionField::Tag(session_buffer.tag.clone()),
+                    SessionField::Tag(_) => SessionField::Start(session_buffer.start),
+                    SessionField::End(_) if wrap => SessionField::Date(session_buffer.start),
+                    SessionField::Start(_) | SessionField::End(_) => SessionField::End(session_buffer.end),
+                    SessionField::None => SessionField::None,
+                }
+            }
+            else
+            {
+                match self.selected_session_field
+                {
+                    SessionField::Date(_) if wrap => SessionField::End(session_buffer.end),
+                    SessionField::Date(_) | SessionField::Description(_) => SessionField::Date(session_buffer.start),
+                    SessionField::Tag(_) => SessionField::Description(session_buffer.description.clone().into()),
+                    SessionField::Start(_) => SessionField::Tag(session_buffer.tag.clone()),
+                    SessionField::End(_) => SessionField::Start(session_buffer.start),
+                    SessionField::None => SessionField::None,
+                }
+            };
+
+            if self.is_session_field_visible(self.get_selected_session_field_index())
+            {
+                return;
+            }
+
+            let index = self.get_selected_session_field_index();
+
+            if index == field_index || (wrap && index == initial_index)
+            {
+                self.selected_session_field = initial_field;
+                return;
+            }
         }
+    }
 
-        self.tags.push(self.tag_buffer.clone());
-        self.database_handler.export_tag(&self.tag_buffer).expect("Failed to export tag.");
-        self.set_selected_tag_index(self.tags.len() - 1);
-        self.tag_buffer.clear();
+    fn is_session_field_visible(&self, field_index: usize) -> bool
+    {
+        self.config.columns.iter().any(|column| column.get_session_field_index() == Some(field_index))
     }
 
-    pub fn set_selected_tag_index(&mut self, index: usize)
+    /// Hours, minutes and seconds can be edited, or just hours and minutes when seconds are hidden.
+    pub fn get_time_segment_count(&self) -> usize
     {
-        self.selected_tag_index = index;
+        if self.config.show_seconds
+        {
+            3
+        }
+        else
+        {
+            2
+        }
     }
 
-    pub fn get_selected_tag_index(&self) -> usize
+    pub fn get_selected_session_field_index(&self) -> usize
     {
-        self.selected_tag_index
+        match self.selected_session_field
+        {
+            SessionField::None | SessionField::Date(_) => 0,
+            SessionField::Description(_) => 1,
+            SessionField::Tag(_) => 2,
+            SessionField::Start(_) => 3,
+            SessionField::End(_) => 4,
+        }
     }
 
-    pub fn is_last_session_still_running(&self) -> bool
+    /// Tag indices in dropdown order with the number of sessions using each tag. The most recently used tags come
+    /// first, ties go to the more used one and unused tags stay in the order they were created. While a tag filter is
+    /// typed only matching tags are listed, prefix matches before substring matches before scattered ones.
+    pub fn get_tag_dropdown_entries(&self) -> Vec<(usize, usize)>
     {
-        if let Some(last_session) = self.sessions.last()
+        let mut entries = self
+            .tags
+            .iter()
+            .enumerate()
+            .filter(|(_, tag)| get_fuzzy_match_rank(tag, &self.tag_filter).is_some())
+            .map(|(tag_index, tag)| {
+                let use_count = self.sessions.iter().filter(|session| session.tag == *tag).count();
+                let last_use = self.sessions.iter().rposition(|session| session.tag == *tag);
+
+                (tag_index, use_count, last_use)
+            })
+            .collect::<Vec<(usize, usize, Option<usize>)>>();
+
+        entries.sort_by(|a, b| b.2.cmp(&a.2).then(b.1.cmp(&a.1)).then(a.0.cmp(&b.0)));
+
+        if !self.tag_filter.is_empty()
         {
-            return last_session.is_running();
+            entries.sort_by_key(|(tag_index, _, _)| get_fuzzy_match_rank(&self.tags[*tag_index], &self.tag_filter));
         }
 
-        false
+        entries.into_iter().map(|(tag_index, use_count, _)| (tag_index, use_count)).collect()
     }
 
-    pub fn end_running_session(&mut self)
+    /// Moves the dropdown selection to the best match when the selected tag no longer passes the filter. Returns
+    /// whether any tag matches.
+    pub fn snap_tag_selection_to_filter(&mut self) -> bool
     {
-        let end = self.get_current_time();
+        let entries = self.get_tag_dropdown_entries();
 
-        if let Some(last_session) = self.sessions.last_mut()
-            && last_session.is_running()
+        if !entries.iter().any(|(tag_index, _)| *tag_index == self.temp_tag_index)
+            && let Some((tag_index, _)) = entries.first()
         {
-            last_session.end = Some(end);
-            let session_string = last_session.construct_db_string(self.value_separator, &self.date_format);
-
-            self.database_handler.export_session(&session_string).expect("Error exporting session.");
+            self.temp_tag_index = *tag_index;
         }
+
+        !entries.is_empty()
     }
 
-    pub fn delete_selected_session(&mut self)
+    /// Moves the dropdown selection one entry up or down in dropdown order.
+    pub fn move_tag_selection(&mut self, up: bool)
     {
-        if self.sessions.is_empty()
+        let entries = self.get_tag_dropdown_entries();
+
+        let Some(position) = entries.iter().position(|(tag_index, _)| *tag_index == self.temp_tag_index)
+        else
         {
+            if let Some((tag_index, _)) = entries.first()
+            {
+                self.temp_tag_index = *tag_index;
+            }
             return;
-        }
+        };
 
-        if let Some(session) = self.sessions.get(self.selected_session_index)
-            && !session.is_running()
+        let next_position = if up
         {
-            self.database_handler.delete_session(self.selected_session_index);
+            position.saturating_sub(1)
         }
+        else
+        {
+            cmp::min(position + 1, entries.len() - 1)
+        };
 
-        self.sessions.remove(self.selected_session_index);
+        self.temp_tag_index = entries[next_position].0;
     }
 
-    pub fn start_new_session_based_on_selected(&mut self)
+    /// The position of `tag` among the stored tags.
+    ///
+    /// # Panics
+    ///
+    /// If `tag` isn't stored.
+    pub fn get_index_of_tag(&self, tag: &String) -> usize
     {
-        if self.is_last_session_still_running()
-        {
-            self.end_running_session();
-        }
+        self.tags.iter().position(|t| t.eq(tag)).expect("Failed to retrieve tag index.")
+    }
 
-        if let Some(session) = self.sessions.get(self.selected_session_index)
+    pub fn try_start_new_session(&mut self)
+    {
+        self.description_buffer = self.description_buffer.trim().into();
+
+        if let Some(selected_tag) = self.tags.get(self.get_selected_tag_index())
+            && !self.description_buffer.is_empty()
+            && let Some(start) = self.get_new_session_start()
+            && self.is_new_session_timer_valid()
         {
-            if session.is_running()
+            let timer = self.get_new_session_timer();
+
+            let mut session = Session::from(&self.description_buffer, selected_tag, start, None);
+            session.id = self.get_next_session_id();
+
+            if self.daemon_connected
             {
-                return;
-            }
+                let request = JsonValue::Object(vec![
+                    (String::from("description"), JsonValue::String(session.description.clone())),
+                    (String::from("tag"), JsonValue::String(session.tag.clone())),
+                    (String::from("start"), JsonValue::String(session.start.format(JSON_DATE_FORMAT).to_string())),
+                ]);
 
-            let description = &session.description;
-            let tag_index = self.get_index_of_tag(&session.tag);
+                if self.send_to_daemon("/start", &request)
+                {
+                    self.run_hooks(HookEvent::Start, &session);
+                }
 
-            self.description_buffer = description.clone();
-            self.set_selected_tag_index(tag_index);
+                self.take_running_session_from_daemon();
+            }
+            else
+            {
+                self.run_hooks(HookEvent::Start, &session);
+                debug!("Started session {} at {}", session.id, session.start);
+                self.sessions.push(session);
+            }
 
-            self.try_start_new_session();
+            if let Some(timer) = timer
+                && let Some(running_session) = self.sessions.last().filter(|session| session.is_running())
+            {
+                self.session_timer = Some((running_session.id, timer));
+            }
+
+            self.description_buffer.clear();
+            self.start_buffer.clear();
+            self.timer_buffer.clear();
         }
     }
 
-    pub fn session_buffer_has_pending_changes(&self) -> bool
+    /// Earlier descriptions containing the typed text, each with the tag it was used with. The checked out git branch
+    /// comes first, then descriptions starting with the text, the most used and most recent ones, and matching issues
+    /// assigned to the user.
+    pub fn get_description_suggestions(&self) -> Vec<(String, String)>
     {
-        if let Some(selected_session) = self.sessions.get(self.selected_session_index)
+        let query = self.description_buffer.trim().to_lowercase();
+
+        // The checked out branch comes first, already before anything is typed.
+        let branch_suggestion = self
+            .git_branch
+            .as_ref()
+            .filter(|branch| branch.to_lowercase().contains(&query))
+            .map(|branch| (branch.clone(), self.config.git_tag.clone().unwrap_or_default()));
+
+        if query.is_empty()
         {
-            if let Some(edited_session) = self.session_edit_buffer.clone()
+            return branch_suggestion.into_iter().collect();
+        }
+
+        // Description, tag, use count and index of the latest use.
+        let mut suggestions: Vec<(&str, &str, usize, usize)> = Vec::new();
+
+        for (session_index, session) in self.sessions.iter().enumerate()
+        {
+            if !session.description.to_lowercase().contains(&query)
             {
-                !selected_session.eq(&edited_session)
+                continue;
             }
-            else
+
+            match suggestions.iter_mut().find(|(description, tag, ..)| *description == session.description && *tag == session.tag)
             {
-                false
+                Some(suggestion) =>
+                {
+                    suggestion.2 += 1;
+                    suggestion.3 = session_index;
+                }
+                None => suggestions.push((&session.description, &session.tag, 1, session_index)),
             }
         }
-        else
-        {
-            false
-        }
-    }
 
-    pub fn apply_changes_to_session(&mut self)
-    {
-        if let Some(selected_session) = self.sessions.get_mut(self.selected_session_index)
-            && let Some(edited_session) = self.session_edit_buffer.clone()
+        suggestions.sort_by(|a, b| {
+            let a_is_prefix = a.0.to_lowercase().starts_with(&query);
+            let b_is_prefix = b.0.to_lowercase().starts_with(&query);
+
+            b_is_prefix.cmp(&a_is_prefix).then(b.2.cmp(&a.2)).then(b.3.cmp(&a.3))
+        });
+
+        let mut suggestions = branch_suggestion
+            .into_iter()
+            .chain(
+                suggestions
+                    .into_iter()
+                    .filter(|(description, ..)| Some(*description) != self.git_branch.as_deref())
+                    .map(|(description, tag, ..)| (description.to_string(), tag.to_string())),
+            )
+            .take(MAX_DESCRIPTION_SUGGESTIONS)
+            .collect::<Vec<(String, String)>>();
+
+        // Assigned issues fill the remaining rows, without a tag of their own.
+        let issue_tracker = self.config.get_issue_tracker();
+
+        for issue in &self.assigned_issues
         {
-            selected_session.description = edited_session.description;
-            selected_session.tag = edited_session.tag;
-            selected_session.start = edited_session.start;
-            selected_session.end = edited_session.end;
+            let description = format!("{} {}", issue_tracker.get_short_reference(&issue.reference), issue.title);
 
-            if !selected_session.is_running()
+            if suggestions.len() < MAX_DESCRIPTION_SUGGESTIONS
+                && description.to_lowercase().contains(&query)
+                && !suggestions.iter().any(|(existing_description, _)| *existing_description == description)
             {
-                self.database_handler
-                    .export_all_sessions(&self.sessions, self.value_separator, &self.date_format)
-                    .expect("Failed to export all sessions to db.");
+                suggestions.push((description, String::new()));
             }
         }
+
+        suggestions
     }
 
-    pub fn store_modified_field_to_session_buffer(&mut self)
+    pub fn move_suggestion_selection(&mut self, up: bool)
     {
-        if let Some(selected_session) = self.session_edit_buffer.as_mut()
+        let suggestion_count = self.get_description_suggestions().len();
+
+        self.selected_suggestion_index = match (self.selected_suggestion_index, up)
         {
-            selected_session.set_field(&self.selected_session_field);
-        }
+            _ if suggestion_count == 0 => None,
+            (None, false) => Some(0),
+            (None | Some(0), true) => None,
+            (Some(index), true) => Some(index - 1),
+            (Some(index), false) => Some(cmp::min(index + 1, suggestion_count - 1)),
+        };
     }
 
-    pub fn copy_selected_session_to_buffer(&mut self)
+    /// Takes over the highlighted suggestion's description and tag. Returns false if none is highlighted.
+    pub fn apply_selected_suggestion(&mut self) -> bool
     {
-        if let Some(selected_session) = self.sessions.get(self.selected_session_index)
+        let Some((description, tag)) =
+            self.selected_suggestion_index.and_then(|index| self.get_description_suggestions().get(index).cloned())
+        else
         {
-            self.session_edit_buffer = Some(selected_session.clone());
-            self.selected_session_field = SessionField::Date(selected_session.start);
+            return false;
+        };
 
-            self.temp_tag_index = self.get_index_of_tag(&selected_session.tag);
+        self.description_buffer.set(&description);
+        self.selected_suggestion_index = None;
+
+        if let Some(tag_index) = self.tags.iter().position(|existing_tag| *existing_tag == tag)
+        {
+            self.set_selected_tag_index(tag_index);
         }
+
+        true
     }
 
-    pub fn clear_session_edit_buffer(&mut self)
+    /// Fills the new session with the template at `index`, creating its tag if needed. The start is left at now.
+    pub fn apply_template(&mut self, index: usize) -> bool
     {
-        self.session_edit_buffer = None;
+        let Some(template) = self.config.templates.get(index).cloned()
+        else
+        {
+            return false;
+        };
+
+        self.description_buffer.set(&template.description);
+        self.store_tag(&template.tag);
+        self.set_selected_tag_index(self.get_index_of_tag(&template.tag));
+        self.start_buffer.clear();
+        self.timer_buffer = template.target.map(|target| format!("{}m", target.num_minutes())).unwrap_or_default();
+        self.selected_suggestion_index = None;
+
+        true
+    }
+
+    /// Gives a new session without a description the one of the first template using the selected tag, along with its
+    /// timer if none is set.
+    pub fn apply_tag_template(&mut self)
+    {
+        let Some(tag) = self.tags.get(self.selected_tag_index)
+        else
+        {
+            return;
+        };
+
+        let Some(template) = self.config.templates.iter().find(|template| template.tag == *tag)
+        else
+        {
+            return;
+        };
+
+        if !self.description_buffer.trim().is_empty()
+        {
+            return;
+        }
+
+        self.description_buffer.set(&template.description);
+
+        if let Some(target) = template.target
+            && self.timer_buffer.trim().is_empty()
+        {
+            self.timer_buffer = format!("{}m", target.num_minutes());
+        }
+    }
+
+    fn get_next_session_id(&self) -> u64
+    {
+        let loaded_max_session_id = self.sessions.iter().map(|session| session.id).max().unwrap_or(0);
+        let unloaded_max_session_id = self.database_handler.get_unloaded_max_session_id(self.value_separator, &self.date_format);
+        let last_session_id = self.database_handler.get_last_session_id(self.value_separator);
+
+        loaded_max_session_id.max(unloaded_max_session_id).max(last_session_id) + 1
+    }
+
+    /// How long the new session is meant to run, `None` for an open-ended one or while the timer input is invalid.
+    pub fn get_new_session_timer(&self) -> Option<TimeDelta>
+    {
+        parse_duration(self.timer_buffer.trim())
+    }
+
+    pub fn is_new_session_timer_valid(&self) -> bool
+    {
+        self.timer_buffer.trim().is_empty() || self.get_new_session_timer().is_some()
+    }
+
+    /// Time left until the timer of the running session elapses, negative once it has. `None` without a timer.
+    pub fn get_running_session_time_left(&self) -> Option<TimeDelta>
+    {
+        let running_session = self.sessions.last().filter(|session| session.is_running())?;
+        let (_, timer) = self.session_timer.filter(|(session_id, _)| *session_id == running_session.id)?;
+
+        Some(timer - (self.get_current_time() - running_session.start))
+    }
+
+    pub fn get_new_session_start(&self) -> Option<NaiveDateTime>
+    {
+        let now = self.get_current_time();
+
+        if self.start_buffer.trim().is_empty()
+        {
+            return Some(now);
+        }
+
+        parse_start_input(&self.start_buffer, now)
+    }
+
+    /// The date as shown in the date column, "Today", "Yesterday" or a weekday of the current week if the config asks
+    /// for it.
+    pub fn get_date_label(&self, date: NaiveDate) -> String
+    {
+        let today = self.get_today();
+        let relative = self.config.date_labels != DateLabels::Absolute;
+
+        if relative && date == today
+        {
+            String::from("Today")
+        }
+        else if relative && date + TimeDelta::days(1) == today
+        {
+            String::from("Yesterday")
+        }
+        else if self.config.date_labels == DateLabels::Weekdays
+            && date >= get_week_start(today, self.config.week_start)
+            && date < today
+        {
+            date.format("%A").to_string()
+        }
+        else
+        {
+            date.format(self.get_date_format()).to_string()
+        }
+    }
+
+    /// Character between the values of a stored session line.
+    #[must_use]
+    pub fn get_value_separator(&self) -> char
+    {
+        self.value_separator
+    }
+
+    /// Format of shown dates in the configured order, e.g. `14 Jan 26`.
+    pub fn get_date_format(&self) -> &'static str
+    {
+        match self.config.date_order
+        {
+            DateOrder::DayMonthYear => "%d %b %y",
+            DateOrder::MonthDayYear => "%b %d %y",
+            DateOrder::YearMonthDay => "%y %b %d",
+        }
+    }
+
+    /// Format of the day headers of the list, e.g. `Wed 14 Jan`, without the year.
+    pub fn get_day_format(&self) -> &'static str
+    {
+        match self.config.date_order
+        {
+            DateOrder::DayMonthYear => "%a %d %b",
+            DateOrder::MonthDayYear | DateOrder::YearMonthDay => "%a %b %d",
+        }
+    }
+
+    /// Format of shown times on the configured clock, with seconds if the columns show them.
+    pub fn get_time_format(&self) -> &'static str
+    {
+        match (self.config.clock_format, self.config.show_seconds)
+        {
+            (ClockFormat::TwentyFourHour, true) => "%H:%M:%S",
+            (ClockFormat::TwentyFourHour, false) => "%H:%M",
+            (ClockFormat::TwelveHour, true) => "%I:%M:%S %p",
+            (ClockFormat::TwelveHour, false) => "%I:%M %p",
+        }
+    }
+
+    pub fn get_day_total(&self, date: NaiveDate) -> TimeDelta
+    {
+        let now = self.get_current_time();
+        let day_start = date.and_time(NaiveTime::MIN);
+        let day_end = day_start + TimeDelta::days(1);
+
+        self.sessions.iter().map(|session| session.get_duration_within(day_start, day_end, now)).sum()
+    }
+
+    pub fn open_calendar(&mut self)
+    {
+        self.load_all_sessions();
+        self.calendar_date = self.get_current_time().date();
+    }
+
+    pub fn move_calendar_selection(&mut self, days: i64)
+    {
+        self.calendar_date += TimeDelta::days(days);
+    }
+
+    /// Shifts the selected day by whole months, clamping to the last day of shorter months.
+    pub fn move_calendar_month(&mut self, forward: bool)
+    {
+        let shifted = if forward
+        {
+            self.calendar_date.checked_add_months(Months::new(1))
+        }
+        else
+        {
+            self.calendar_date.checked_sub_months(Months::new(1))
+        };
+
+        if let Some(shifted) = shifted
+        {
+            self.calendar_date = shifted;
+        }
+    }
+
+    /// Shows only the selected calendar day in the session list and selects its latest session, to edit it. Returns false
+    /// if the day has no sessions or the database is read-only.
+    pub fn open_selected_calendar_day(&mut self) -> bool
+    {
+        if self.refuse_read_only_change()
+        {
+            return false;
+        }
+
+        let Some(session_index) = self.sessions.iter().rposition(|session| session.start.date() == self.calendar_date)
+        else
+        {
+            return false;
+        };
+
+        self.collapsed_days =
+            self.sessions.iter().map(|session| session.start.date()).filter(|date| *date != self.calendar_date).collect();
+        self.collapsed_days.dedup();
+        self.selected_session_index = session_index;
+
+        true
+    }
+
+    pub fn get_day_off(&self, date: NaiveDate) -> Option<DayOff>
+    {
+        self.days_off.iter().find(|(day_off_date, _)| *day_off_date == date).map(|(_, day_off)| *day_off)
+    }
+
+    /// Marks the selected calendar day as the next kind of day off, or unmarks it after the last.
+    pub fn cycle_selected_calendar_day_off(&mut self)
+    {
+        if self.refuse_read_only_change()
+        {
+            return;
+        }
+
+        let date = self.calendar_date;
+        let day_off = DayOff::get_next(self.get_day_off(date));
+
+        self.days_off.retain(|(day_off_date, _)| *day_off_date != date);
+        self.days_off.extend(day_off.map(|day_off| (date, day_off)));
+        self.days_off.sort_by_key(|(day_off_date, _)| *day_off_date);
+
+        if let Err(error) = self.database_handler.export_days_off(&self.days_off, self.value_separator)
+        {
+            warn!("Storing the days off failed: {error}");
+            self.show_status("DAYS OFF NOT WRITABLE");
+        }
+    }
+
+    /// When `date` is worked according to the schedule, `None` if it is off or marked as a day off.
+    pub fn get_working_hours(&self, date: NaiveDate) -> Option<(NaiveDateTime, NaiveDateTime)>
+    {
+        if self.get_day_off(date).is_some()
+        {
+            return None;
+        }
+
+        let (start, end) = self.config.get_working_hours(date.weekday())?;
+
+        Some((date.and_time(start), date.and_time(end)))
+    }
+
+    /// How long `date` is meant to be worked, nothing on days off.
+    pub fn get_scheduled_time(&self, date: NaiveDate) -> TimeDelta
+    {
+        if self.get_day_off(date).is_some()
+        {
+            return TimeDelta::zero();
+        }
+
+        self.config.get_scheduled_time(date.weekday())
+    }
+
+    pub fn open_stats(&mut self)
+    {
+        self.load_all_sessions();
+        self.stats_date = self.get_today();
+        self.daily_note_message = None;
+    }
+
+    pub fn open_database_stats(&mut self)
+    {
+        self.load_all_sessions();
+        self.database_file_sizes = self.database_handler.get_file_sizes();
+    }
+
+    /// Steps the stats period back or forward by its own length.
+    pub fn move_stats_period(&mut self, forward: bool)
+    {
+        let months = match self.stats_period
+        {
+            StatsPeriod::Day | StatsPeriod::Week | StatsPeriod::All => 0,
+            StatsPeriod::Month => 1,
+            StatsPeriod::Year => 12,
+        };
+        let days = match self.stats_period
+        {
+            StatsPeriod::Day => 1,
+            StatsPeriod::Week => 7,
+            StatsPeriod::Month | StatsPeriod::Year | StatsPeriod::All => 0,
+        };
+
+        let shifted = if forward
+        {
+            self.stats_date.checked_add_months(Months::new(months)).map(|date| date + TimeDelta::days(days))
+        }
+        else
+        {
+            self.stats_date.checked_sub_months(Months::new(months)).map(|date| date - TimeDelta::days(days))
+        };
+
+        if let Some(shifted) = shifted
+        {
+            self.stats_date = shifted;
+        }
+    }
+
+    /// First and last day of the selected stats period, or `None` for all time.
+    pub fn get_stats_range(&self) -> Option<(NaiveDate, NaiveDate)>
+    {
+        let date = self.stats_date;
+
+        let (first, last) = match self.stats_period
+        {
+            StatsPeriod::Day => (date, date),
+            StatsPeriod::Week =>
+            {
+                let first = get_week_start(date, self.config.week_start);
+                (first, first + TimeDelta::days(6))
+            }
+            StatsPeriod::Month =>
+            {
+                let first = date.with_day(1)?;
+                (first, first.checked_add_months(Months::new(1))? - TimeDelta::days(1))
+            }
+            StatsPeriod::Year =>
+            {
+                let first = date.with_ordinal(1)?;
+                (first, first.with_month(12)?.with_day(31)?)
+            }
+            StatsPeriod::All => return None,
+        };
+
+        Some((first, last))
+    }
+
+    /// Writes every day of the stats period into its Obsidian daily note.
+    pub fn write_daily_notes(&mut self)
+    {
+        let Some(daily_note) = &self.config.obsidian_daily_note
+        else
+        {
+            self.daily_note_message = Some(String::from("NO DAILY NOTE IN CONFIG"));
+            return;
+        };
+
+        let (from, to) = self.get_stats_range().unwrap_or((NaiveDate::MIN, NaiveDate::MAX));
+
+        self.daily_note_message = Some(
+            match export_daily_notes(&self.sessions, from, to, daily_note, &self.config.obsidian_template, self.config.rounding)
+            {
+                Ok(1) => String::from("WROTE 1 DAILY NOTE"),
+                Ok(note_count) => format!("WROTE {note_count} DAILY NOTES"),
+                Err(error) => error,
+            },
+        );
+    }
+
+    /// Tracked time per tag within the stats period, largest first.
+    pub fn get_tag_totals(&self) -> Vec<(String, TimeDelta)>
+    {
+        let (from, to) = self.get_stats_bounds();
+
+        self.get_tag_totals_within(from, to)
+    }
+
+    /// Tracked time within the stats period per issue the descriptions refer to, largest first, with the address of the
+    /// issue.
+    pub fn get_issue_totals(&self) -> Vec<(String, String, TimeDelta)>
+    {
+        let (from, to) = self.get_stats_bounds();
+        let now = self.get_current_time();
+        let issue_tracker = self.config.get_issue_tracker();
+        let mut issue_totals: Vec<(String, String, TimeDelta)> = Vec::new();
+
+        for session in &self.sessions
+        {
+            let duration = self.config.rounding.apply(session.get_duration_within(from, to, now));
+
+            let Some((reference, url)) = issue_tracker.find_issue(&session.description).filter(|_| !duration.is_zero())
+            else
+            {
+                continue;
+            };
+
+            match issue_totals.iter_mut().find(|(existing_reference, ..)| *existing_reference == reference)
+            {
+                Some((.., total)) => *total += duration,
+                None => issue_totals.push((reference, url, duration)),
+            }
+        }
+
+        issue_totals.sort_by_key(|(.., total)| cmp::Reverse(*total));
+
+        issue_totals
+    }
+
+    /// The stats period as `[from, to)`.
+    fn get_stats_bounds(&self) -> (NaiveDateTime, NaiveDateTime)
+    {
+        match self.get_stats_range()
+        {
+            Some((first, last)) => (first.and_time(NaiveTime::MIN), (last + TimeDelta::days(1)).and_time(NaiveTime::MIN)),
+            None => (NaiveDateTime::MIN, NaiveDateTime::MAX),
+        }
+    }
+
+    /// Tracked time per tag within `[from, to)`, largest first.
+    pub fn get_tag_totals_within(&self, from: NaiveDateTime, to: NaiveDateTime) -> Vec<(String, TimeDelta)>
+    {
+        let now = self.get_current_time();
+        let mut tag_totals: Vec<(String, TimeDelta)> = Vec::new();
+
+        for session in &self.sessions
+        {
+            let duration = self.config.rounding.apply(session.get_duration_within(from, to, now));
+
+            if duration.is_zero()
+            {
+                continue;
+            }
+
+            match tag_totals.iter_mut().find(|(tag, _)| *tag == session.tag)
+            {
+                Some((_, total)) => *total += duration,
+                None => tag_totals.push((session.tag.clone(), duration)),
+            }
+        }
+
+        tag_totals.sort_by_key(|(_, total)| cmp::Reverse(*total));
+
+        tag_totals
+    }
+
+    /// ISO week number of the week starting on `week_start`, `None` unless the config asks for week numbers. Weeks
+    /// starting on Sunday take the number of the Monday after.
+    pub fn get_week_number(&self, week_start: NaiveDate) -> Option<u32>
+    {
+        let monday = week_start + TimeDelta::days(i64::from(Weekday::Mon.days_since(self.config.week_start)));
+
+        (self.config.week_numbers == WeekNumbers::Iso).then(|| monday.iso_week().week())
+    }
+
+    pub fn open_week_report(&mut self)
+    {
+        self.load_all_sessions();
+        self.report_week_start = get_week_start(self.get_today(), self.config.week_start);
+    }
+
+    /// Overtime of the report week and the balance at its end, counting the days that are over since the balance start.
+    /// `None` without a balance start.
+    pub fn get_week_flex_time(&self) -> Option<(TimeDelta, TimeDelta)>
+    {
+        let now = self.get_current_time();
+        let week_end = self.report_week_start + TimeDelta::days(6);
+        let balance = get_flex_balance(&self.sessions, &self.config, &self.days_off, week_end, now)?;
+
+        let from = self.config.balance_start.map_or(self.report_week_start, |balance_start| balance_start.max(self.report_week_start));
+        let to = week_end.min(now.date() - TimeDelta::days(1));
+
+        Some((get_flex_time(&self.sessions, &self.config, &self.days_off, from, to, now), balance))
+    }
+
+    /// Per-tag totals of the report week next to the week before it, ordered by the report week's totals.
+    pub fn get_week_comparison(&self) -> Vec<(String, TimeDelta, TimeDelta)>
+    {
+        let week_start = self.report_week_start.and_time(NaiveTime::MIN);
+        let previous_week_start = week_start - TimeDelta::days(7);
+        let week_end = week_start + TimeDelta::days(7);
+
+        let current_totals = self.get_tag_totals_within(week_start, week_end);
+        let previous_totals = self.get_tag_totals_within(previous_week_start, week_start);
+
+        let mut comparison = current_totals
+            .iter()
+            .map(|(tag, current)| {
+                let previous = previous_totals
+                    .iter()
+                    .find(|(previous_tag, _)| previous_tag == tag)
+                    .map_or(TimeDelta::zero(), |(_, total)| *total);
+
+                (tag.clone(), *current, previous)
+            })
+            .collect::<Vec<(String, TimeDelta, TimeDelta)>>();
+
+        for (tag, previous) in previous_totals
+        {
+            if !comparison.iter().any(|(compared_tag, ..)| *compared_tag == tag)
+            {
+                comparison.push((tag, TimeDelta::zero(), previous));
+            }
+        }
+
+        comparison
+    }
+
+    /// Each configured goal with the time tracked for its tag in the current day or week and the target for it. Days off
+    /// and days the schedule leaves free have nothing to do for daily goals, and days off take their share of the week's
+    /// scheduled time off weekly targets.
+    pub fn get_goal_progress(&self) -> Vec<(&Goal, TimeDelta, TimeDelta)>
+    {
+        let today = self.get_today();
+        let week_start = get_week_start(today, self.config.week_start);
+
+        let day_totals =
+            self.get_tag_totals_within(today.and_time(NaiveTime::MIN), (today + TimeDelta::days(1)).and_time(NaiveTime::MIN));
+        let week_totals = self
+            .get_tag_totals_within(week_start.and_time(NaiveTime::MIN), (week_start + TimeDelta::days(7)).and_time(NaiveTime::MIN));
+        let week_dates = (0..7).map(|day_index| week_start + TimeDelta::days(day_index)).collect::<Vec<NaiveDate>>();
+        let week_scheduled_seconds =
+            week_dates.iter().map(|date| self.config.get_scheduled_time(date.weekday()).num_seconds()).sum::<i64>();
+        let week_worked_seconds = week_dates.iter().map(|date| self.get_scheduled_time(*date).num_seconds()).sum::<i64>();
+
+        self.config
+            .goals
+            .iter()
+            .map(|goal| {
+                let totals = match goal.period
+                {
+                    GoalPeriod::Day => &day_totals,
+                    GoalPeriod::Week => &week_totals,
+                };
+                let tracked = totals.iter().find(|(tag, _)| *tag == goal.tag).map_or(TimeDelta::zero(), |(_, total)| *total);
+                let target = match goal.period
+                {
+                    GoalPeriod::Day if self.get_scheduled_time(today).is_zero() => TimeDelta::zero(),
+                    GoalPeriod::Day => goal.target,
+                    GoalPeriod::Week if week_scheduled_seconds > 0 =>
+                    {
+                        TimeDelta::seconds(goal.target.num_seconds() * week_worked_seconds / week_scheduled_seconds)
+                    }
+                    GoalPeriod::Week => goal.target,
+                };
+
+                (goal, tracked, target)
+            })
+            .collect()
+    }
+
+    pub fn get_today(&self) -> NaiveDate
+    {
+        self.get_current_time().date()
+    }
+
+    /// Time tracked since midnight, including the running session and the part of sessions that started yesterday.
+    pub fn get_today_total(&self) -> TimeDelta
+    {
+        self.get_day_total(self.get_today())
+    }
+
+    /// Groups the sessions by start date, newest first, with a header row per day holding the day's total.
+    pub fn get_list_rows(&self) -> Vec<ListRow>
+    {
+        let mut days: Vec<(NaiveDate, Vec<usize>)> = Vec::new();
+
+        for (session_index, session) in self.sessions.iter().enumerate().rev()
+        {
+            let date = session.start.date();
+
+            match days.last_mut()
+            {
+                Some((day, session_indices)) if *day == date => session_indices.push(session_index),
+                _ => days.push((date, vec![session_index])),
+            }
+        }
+
+        let mut rows = Vec::new();
+
+        for (date, mut session_indices) in days
+        {
+            let collapsed = self.collapsed_days.contains(&date);
+            let latest_session_index = session_indices[0];
+
+            if let Some((column, descending)) = self.list_sort
+            {
+                session_indices.sort_by(|a, b| {
+                    let ordering = self.compare_sessions(&self.sessions[*a], &self.sessions[*b], column);
+                    if descending
+                    {
+                        ordering.reverse()
+                    }
+                    else
+                    {
+                        ordering
+                    }
+                });
+            }
+
+            rows.push(ListRow::Day {
+                date,
+                total: self.get_day_total(date),
+                collapsed,
+                latest_session_index,
+            });
+
+            if !collapsed
+            {
+                rows.extend(session_indices.into_iter().map(ListRow::Session));
+            }
+        }
+
+        rows
+    }
+
+    /// The session list as shown, as plain text with a line per day and session. Collapsed days only show their
+    /// total.
+    pub fn get_list_text(&self) -> String
+    {
+        let now = self.get_current_time();
+
+        self.get_list_rows()
+            .into_iter()
+            .map(|list_row| match list_row
+            {
+                ListRow::Day {
+                    date,
+                    total,
+                    ..
+                } => format!("{} \u{2014} {}", date.format(self.get_day_format()), format_duration_short(total)),
+                ListRow::Session(session_index) => format!("  {}", self.sessions[session_index].get_summary_text(now)),
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// The selected session as plain text, headed by its date.
+    pub fn get_selected_session_text(&self) -> Option<String>
+    {
+        let session = self.sessions.get(self.selected_session_index)?;
+
+        Some(format!("{}  {}", session.start.format("%a %d %b"), session.get_summary_text(self.get_current_time())))
+    }
+
+    /// Puts `text` on the system clipboard and notes how it went for the list title.
+    pub fn copy_to_clipboard(&mut self, text: &str)
+    {
+        self.clipboard_message = match copy_to_clipboard(text)
+        {
+            Ok(()) => Some(format!("COPIED {} LINES", text.lines().count())),
+            Err(error) =>
+            {
+                warn!("Copying to the clipboard failed: {error}");
+                Some(error)
+            }
+        };
+    }
+
+    /// Tells in the footer what an action did, e.g. "Session saved", replacing the message of the one before.
+    pub fn show_status(&mut self, message: impl Into<String>)
+    {
+        self.status_message = Some((message.into(), self.get_current_time()));
+    }
+
+    /// The message of the last action, until it is older than `STATUS_MESSAGE_DURATION`.
+    pub fn get_status_message(&self) -> Option<&str>
+    {
+        self.status_message
+            .as_ref()
+            .filter(|(_, shown_at)| self.get_current_time() - *shown_at < STATUS_MESSAGE_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
+    fn compare_sessions(&self, a: &Session, b: &Session, column: SortColumn) -> cmp::Ordering
+    {
+        let now = self.get_current_time();
+
+        match column
+        {
+            SortColumn::Description => a.description.to_lowercase().cmp(&b.description.to_lowercase()),
+            SortColumn::Tag => a.tag.to_lowercase().cmp(&b.tag.to_lowercase()),
+            SortColumn::Project => a.get_project().map(str::to_lowercase).cmp(&b.get_project().map(str::to_lowercase)),
+            SortColumn::Start => a.start.cmp(&b.start),
+            SortColumn::End => a.end.unwrap_or(now).cmp(&b.end.unwrap_or(now)),
+            SortColumn::Duration => (a.end.unwrap_or(now) - a.start).cmp(&(b.end.unwrap_or(now) - b.start)),
+        }
+    }
+
+    /// Sorts by the column, flipping the direction if it is already sorted by it. `None` restores the default order.
+    pub fn toggle_list_sort(&mut self, column: Option<SortColumn>)
+    {
+        self.list_sort = match (column, self.list_sort)
+        {
+            (None, _) => None,
+            (Some(column), Some((sorted_column, descending))) if column == sorted_column => Some((column, !descending)),
+            (Some(column), _) => Some((column, false)),
+        };
+    }
+
+    pub fn add_click_area(&mut self, x: u16, y: u16, width: u16, target: ClickTarget)
+    {
+        self.click_areas.push(ClickArea {
+            x,
+            y,
+            width,
+            target,
+        });
+    }
+
+    /// Resolves a left click against the areas of the last frame, topmost first. Clicks on buttons come back as their
+    /// key so they go through the regular key handling.
+    pub fn handle_click(&mut self, x: u16, y: u16) -> Option<Key>
+    {
+        let target = self.click_areas.iter().rev().find(|area| area.y == y && x >= area.x && x < area.x + area.width)?.target;
+
+        match target
+        {
+            ClickTarget::Key(key) => return Some(key),
+            ClickTarget::Row(row_index) => self.select_list_row(row_index),
+            ClickTarget::Column(column) => self.toggle_list_sort(column),
+        }
+
+        None
+    }
+
+    /// Selects the session of a clicked row, opening the list for editing if nothing was selected yet. Clicking a day
+    /// header folds or unfolds the day.
+    fn select_list_row(&mut self, row_index: usize)
+    {
+        match self.get_list_rows().get(row_index).copied()
+        {
+            Some(ListRow::Session(session_index)) =>
+            {
+                self.selected_session_index = session_index;
+
+                if let CommandState::Idle = self.state
+                    && !self.refuse_read_only_change()
+                {
+                    self.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Browse));
+                }
+            }
+            Some(ListRow::Day {
+                date,
+                ..
+            }) =>
+            {
+                self.toggle_day_collapsed(date);
+            }
+            None =>
+            {}
+        }
+    }
+
+    /// The row showing the selected session, which is its day header if the day is collapsed.
+    pub fn get_selected_row_index(&self, rows: &[ListRow]) -> Option<usize>
+    {
+        let selected_date = self.sessions.get(self.selected_session_index)?.start.date();
+
+        rows.iter().position(|row| match row
+        {
+            ListRow::Day {
+                date,
+                collapsed,
+                ..
+            } => *collapsed && *date == selected_date,
+            ListRow::Session(session_index) => *session_index == self.selected_session_index,
+        })
+    }
+
+    /// Moves the selection to the next visible session row, treating a collapsed day as a single row.
+    pub fn move_session_selection(&mut self, up: bool)
+    {
+        let rows = self.get_list_rows();
+
+        let Some(selected_row_index) = self.get_selected_row_index(&rows)
+        else
+        {
+            return;
+        };
+
+        let next_session_index = |row: &ListRow| match row
+        {
+            ListRow::Session(session_index) => Some(*session_index),
+            ListRow::Day {
+                collapsed: true,
+                latest_session_index,
+                ..
+            } => Some(*latest_session_index),
+            ListRow::Day {
+                collapsed: false,
+                ..
+            } => None,
+        };
+
+        let next_session_index = if up
+        {
+            rows[..selected_row_index].iter().rev().find_map(next_session_index)
+        }
+        else
+        {
+            rows[selected_row_index + 1..].iter().find_map(next_session_index)
+        };
+
+        if let Some(next_session_index) = next_session_index
+        {
+            self.selected_session_index = next_session_index;
+        }
+        // Moving past the oldest loaded session brings in the next page of the history.
+        else if !up && self.load_older_sessions()
+        {
+            self.move_session_selection(up);
+        }
+    }
+
+    pub fn is_selected_day_collapsed(&self) -> bool
+    {
+        self.sessions.get(self.selected_session_index).is_some_and(|session| self.collapsed_days.contains(&session.start.date()))
+    }
+
+    pub fn toggle_selected_day_collapsed(&mut self)
+    {
+        if let Some(date) = self.sessions.get(self.selected_session_index).map(|session| session.start.date())
+        {
+            self.toggle_day_collapsed(date);
+        }
+    }
+
+    fn toggle_day_collapsed(&mut self, date: NaiveDate)
+    {
+        if let Some(position) = self.collapsed_days.iter().position(|collapsed_day| *collapsed_day == date)
+        {
+            self.collapsed_days.remove(position);
+        }
+        else
+        {
+            self.collapsed_days.push(date);
+        }
+    }
+
+    /// Collapses every day except today, or expands everything if any day is collapsed.
+    pub fn toggle_all_days_collapsed(&mut self)
+    {
+        if !self.collapsed_days.is_empty()
+        {
+            self.collapsed_days.clear();
+            return;
+        }
+
+        let today = self.get_current_time().date();
+
+        for session in &self.sessions
+        {
+            let date = session.start.date();
+
+            if date != today && !self.collapsed_days.contains(&date)
+            {
+                self.collapsed_days.push(date);
+            }
+        }
+    }
+
+    pub fn get_running_session_elapsed(&self) -> Option<TimeDelta>
+    {
+        let running_session = self.sessions.last().filter(|session| session.is_running())?;
+
+        Some(self.get_current_time() - running_session.start)
+    }
+
+    /// The terminal title from the `terminal_title` template while a session runs, the app's name otherwise. `None` if
+    /// the title is left alone.
+    pub fn get_terminal_title(&self) -> Option<String>
+    {
+        let template = self.config.terminal_title.as_ref()?;
+
+        let Some((running_session, elapsed)) = self.sessions.last().zip(self.get_running_session_elapsed())
+        else
+        {
+            return Some(String::from("time-tracker"));
+        };
+
+        let title = template
+            .replace("{elapsed}", &format_duration(elapsed))
+            .replace("{description}", &running_session.description)
+            .replace("{tag}", &running_session.tag);
+
+        Some(title.trim().to_string())
+    }
+
+    pub fn get_current_time(&self) -> NaiveDateTime
+    {
+        self.clock.now()
+    }
+
+    pub fn try_store_tag(&mut self)
+    {
+        self.tag_buffer = self.tag_buffer.trim().into();
+
+        if !self.store_tag(&self.tag_buffer.to_string())
+        {
+            return;
+        }
+
+        self.set_selected_tag_index(self.tags.len() - 1);
+        self.tag_buffer.clear();
+    }
+
+    fn store_tag(&mut self, tag: &String) -> bool
+    {
+        if tag.is_empty() || self.tags.iter().any(|existing_tag| existing_tag.eq(tag))
+        {
+            return false;
+        }
+
+        self.tags.push(tag.clone());
+        self.database_handler.export_tag(tag).expect("Failed to export tag.");
+
+        true
+    }
+
+    /// Selects the tag for new sessions and remembers it for the next start.
+    pub fn set_selected_tag_index(&mut self, index: usize)
+    {
+        self.selected_tag_index = index;
+
+        if let Some(tag) = self.tags.get(index)
+        {
+            self.database_handler.export_last_used_tag(tag);
+        }
+    }
+
+    pub fn get_selected_tag_index(&self) -> usize
+    {
+        self.selected_tag_index
+    }
+
+    pub fn is_last_session_still_running(&self) -> bool
+    {
+        if let Some(last_session) = self.sessions.last()
+        {
+            return last_session.is_running();
+        }
+
+        false
+    }
+
+    pub fn end_running_session(&mut self)
+    {
+        let end = self.get_current_time();
+
+        self.end_running_session_at(end);
+    }
+
+    /// Ends the running session at `end`, or at its start if `end` is before it, through the daemon when one runs it.
+    ///
+    /// # Panics
+    ///
+    /// If the ended session can't be stored.
+    pub fn end_running_session_at(&mut self, end: NaiveDateTime)
+    {
+        if self.daemon_connected
+            && let Some(mut session) = self.sessions.last().filter(|session| session.is_running()).cloned()
+        {
+            session.end = Some(cmp::max(end, session.start));
+            let request = JsonValue::Object(vec![(
+                String::from("end"),
+                JsonValue::String(session.end.unwrap_or(end).format(JSON_DATE_FORMAT).to_string()),
+            )]);
+
+            if self.send_to_daemon("/stop", &request)
+            {
+                self.run_hooks(HookEvent::End, &session);
+                self.show_status("Session ended");
+            }
+
+            self.take_running_session_from_daemon();
+            return;
+        }
+
+        if let Some(last_session) = self.sessions.last_mut()
+            && last_session.is_running()
+        {
+            last_session.end = Some(cmp::max(end, last_session.start));
+            let session = last_session.clone();
+            let session_string = session.construct_db_string(self.value_separator, &self.date_format);
+
+            self.database_handler
+                .export_session(&session_string, self.value_separator, &self.date_format)
+                .expect("Error exporting session.");
+            self.send_to_remote_storage(&[SessionChange::Create(session_string)]);
+            self.run_hooks(HookEvent::End, &session);
+            self.show_status("Session ended");
+            debug!("Ended session {} at {}", session.id, end);
+        }
+    }
+
+    /// Sends a change of the running session to the daemon. Returns false if it was refused, with the reason in the
+    /// sync message.
+    fn send_to_daemon(&mut self, path: &str, request: &JsonValue) -> bool
+    {
+        match request_daemon(self.get_workspace(), "POST", path, Some(&request.to_string()))
+        {
+            Some((200..=299, _)) => true,
+            Some((status, answer)) =>
+            {
+                self.sync_message = Some(answer.get("error").and_then(JsonValue::as_str).unwrap_or("DAEMON ERROR").to_string());
+                warn!("Daemon refused {path} with {status}: {answer}");
+                false
+            }
+            None =>
+            {
+                self.sync_message = Some(String::from("DAEMON STOPPED"));
+                warn!("Daemon did not answer {path}");
+                false
+            }
+        }
+    }
+
+    /// Picks up sessions the daemon started or ended for another client, like the `--serve` API.
+    pub fn refresh_from_daemon(&mut self)
+    {
+        if self.daemon_connected && matches!(self.state, CommandState::Idle)
+        {
+            self.take_running_session_from_daemon();
+        }
+    }
+
+    /// Shows the session running in the daemon as the running session, reading the stored sessions again when it
+    /// changed, as the daemon has stored the one before.
+    fn take_running_session_from_daemon(&mut self)
+    {
+        if !self.daemon_connected
+        {
+            return;
+        }
+
+        let Some((_, status)) = request_daemon(self.get_workspace(), "GET", "/status", None)
+        else
+        {
+            warn!("Lost the connection to the daemon of workspace {}", self.get_workspace());
+            self.daemon_connected = false;
+            self.sync_message = Some(String::from("DAEMON STOPPED"));
+            self.sessions.pop_if(|session| session.is_running());
+            return;
+        };
+
+        let daemon_session = status.get("running").and_then(session_from_json);
+        let running_session = self.sessions.last().filter(|session| session.is_running());
+
+        let is_unchanged = match (&daemon_session, running_session)
+        {
+            (Some(daemon_session), Some(running_session)) =>
+            {
+                daemon_session.start == running_session.start
+                    && daemon_session.description == running_session.description
+                    && daemon_session.tag == running_session.tag
+            }
+            (None, None) => true,
+            _ => false,
+        };
+
+        if is_unchanged
+        {
+            return;
+        }
+
+        self.sessions.pop_if(|session| session.is_running());
+        self.reload_sessions_keeping_selection();
+
+        if let Some(mut daemon_session) = daemon_session
+        {
+            daemon_session.id = self.get_next_session_id();
+            self.sessions.push(daemon_session);
+        }
+
+        if let Some(tags) = self.database_handler.import_tags()
+        {
+            self.tags = tags;
+        }
+    }
+
+    fn run_hooks(&mut self, event: HookEvent, session: &Session)
+    {
+        self.hook_threads.retain(|hook_thread| !hook_thread.is_finished());
+
+        if let Some(hook_thread) = self.config.get_hooks().run(event, session, self.get_current_time())
+        {
+            self.hook_threads.push(hook_thread);
+        }
+    }
+
+    /// Notifies about a session running longer than configured, or about no session running during working hours for
+    /// longer than configured.
+    pub fn send_due_notifications(&mut self)
+    {
+        let now = self.get_current_time();
+
+        if let Some(running_session) = self.sessions.last().filter(|session| session.is_running())
+        {
+            if self.get_running_session_time_left().is_some_and(|time_left| time_left <= TimeDelta::zero())
+                && self.notified_timer_session_id != Some(running_session.id)
+            {
+                send_notification("Timer elapsed", &format!("Time is up for {}", running_session.description));
+                self.notified_timer_session_id = Some(running_session.id);
+            }
+
+            if let Some(long_session) = self.config.notify_long_session
+                && now - running_session.start >= long_session
+                && self.notified_session_start != Some(running_session.start)
+            {
+                send_notification(
+                    "Session still running",
+                    &format!(
+                        "{} has been running for {}",
+                        running_session.description,
+                        format_duration_short(now - running_session.start)
+                    ),
+                );
+                self.notified_session_start = Some(running_session.start);
+            }
+
+            return;
+        }
+
+        let (Some(idle), Some(idle_start)) = (self.config.notify_idle, self.get_idle_start())
+        else
+        {
+            return;
+        };
+
+        if now - idle_start >= idle && self.notified_idle_start != Some(idle_start)
+        {
+            send_notification("No session running", &format!("Nothing tracked for {}", format_duration_short(now - idle_start)));
+            self.notified_idle_start = Some(idle_start);
+        }
+    }
+
+    /// The first auto stop time the running session ran past, `None` while it hasn't reached one or without one.
+    pub fn get_auto_stop_end(&self) -> Option<NaiveDateTime>
+    {
+        let auto_stop = self.config.auto_stop?;
+        let running_session = self.sessions.last().filter(|session| session.is_running())?;
+
+        let mut auto_stop_end = running_session.start.date().and_time(auto_stop);
+        if auto_stop_end <= running_session.start
+        {
+            auto_stop_end += TimeDelta::days(1);
+        }
+
+        (self.get_current_time() >= auto_stop_end).then_some(auto_stop_end)
+    }
+
+    /// Ends the running session as of the auto stop time once it ran past it. Returns true if the user wants to be
+    /// asked first instead, which happens once per session.
+    pub fn apply_auto_stop(&mut self) -> bool
+    {
+        let Some(auto_stop_end) = self.get_auto_stop_end().filter(|_| !self.is_read_only() || self.daemon_connected)
+        else
+        {
+            return false;
+        };
+
+        if self.config.auto_stop_ask
+        {
+            let running_session_id = self.sessions.last().map(|session| session.id);
+            let ask = self.auto_stop_asked_session_id != running_session_id;
+            self.auto_stop_asked_session_id = running_session_id;
+
+            return ask;
+        }
+
+        if let Some(running_session) = self.sessions.last()
+        {
+            info!("Auto stopping session {} at {}", running_session.id, auto_stop_end);
+            send_notification(
+                "Session ended",
+                &format!("{} was still running at {}", running_session.description, auto_stop_end.format("%H:%M")),
+            );
+        }
+
+        self.end_running_session_at(auto_stop_end);
+        false
+    }
+
+    /// Since when nothing has been tracked during today's working hours, `None` while a session runs, outside them or on
+    /// a day that isn't worked.
+    pub fn get_idle_start(&self) -> Option<NaiveDateTime>
+    {
+        let now = self.get_current_time();
+        let (working_hours_start, working_hours_end) = self.get_working_hours(now.date())?;
+
+        if self.sessions.last().is_some_and(Session::is_running) || now < working_hours_start || now >= working_hours_end
+        {
+            return None;
+        }
+
+        Some(
+            self.sessions
+                .iter()
+                .filter_map(|session| session.end)
+                .max()
+                .map_or(working_hours_start, |end| end.max(working_hours_start)),
+        )
+    }
+
+    /// Lets the hooks still running finish before the app exits, as that would cut them off.
+    pub fn wait_for_hooks(&mut self)
+    {
+        for hook_thread in self.hook_threads.drain(..)
+        {
+            let _ = hook_thread.join();
+        }
+    }
+
+    /// Throws the running session away. It was never written to the database, so nothing else needs cleaning up.
+    /// True if the running session hasn't reached the minimum duration yet, so ending it likely was a mistake.
+    pub fn is_running_session_too_short(&self) -> bool
+    {
+        self.config
+            .min_session_duration
+            .zip(self.get_running_session_elapsed())
+            .is_some_and(|(min_duration, elapsed)| elapsed < min_duration)
+    }
+
+    pub fn discard_running_session(&mut self)
+    {
+        if self.sessions.pop_if(|session| session.is_running()).is_some()
+        {
+            self.show_status("Session discarded");
+        }
+
+        self.selected_session_index = self.sessions.len().saturating_sub(1);
+    }
+
+    pub fn delete_selected_session(&mut self)
+    {
+        if self.sessions.is_empty()
+        {
+            return;
+        }
+
+        if let Some(session) = self.sessions.get(self.selected_session_index)
+            && !session.is_running()
+        {
+            let session_id = session.id;
+
+            self.database_handler.delete_session(session_id, self.value_separator, &self.date_format);
+            self.database_handler.record_audit_entry(&session.construct_db_string(self.value_separator, &self.date_format), None);
+            self.send_to_remote_storage(&[SessionChange::Delete(session_id)]);
+        }
+
+        self.sessions.remove(self.selected_session_index);
+        self.show_status("Session deleted");
+    }
+
+    /// Copies the running session into the edit buffer so its description and tag can be changed while it keeps
+    /// running. Returns false if nothing is running.
+    pub fn open_running_session_edit(&mut self) -> bool
+    {
+        let Some(running_session) = self.sessions.last().filter(|session| session.is_running()).cloned()
+        else
+        {
+            return false;
+        };
+
+        self.temp_tag_index = self.tags.iter().position(|tag| *tag == running_session.tag).unwrap_or(0);
+        self.session_edit_buffer = Some(running_session);
+
+        true
+    }
+
+    /// Writes the edited description and tag back to the running session. Like the rest of the running session it only
+    /// lives in memory and is stored once the session ends.
+    pub fn apply_running_session_edit(&mut self)
+    {
+        let Some(edited_session) = self.session_edit_buffer.take()
+        else
+        {
+            return;
+        };
+
+        if let Some(running_session) = self.sessions.last_mut().filter(|session| session.is_running())
+        {
+            running_session.description = edited_session.description.trim().to_string();
+
+            if let Some(tag) = self.tags.get(self.temp_tag_index)
+            {
+                running_session.tag.clone_from(tag);
+            }
+
+            self.show_status("Session saved");
+        }
+    }
+
+    /// Starts marking sessions at the latest one. Returns false if there are no sessions to mark.
+    pub fn open_selection(&mut self) -> bool
+    {
+        let Some(last_session_index) = self.sessions.len().checked_sub(1)
+        else
+        {
+            return false;
+        };
+
+        self.marked_session_ids.clear();
+        self.selected_session_index = last_session_index;
+
+        true
+    }
+
+    pub fn is_session_marked(&self, session: &Session) -> bool
+    {
+        self.marked_session_ids.contains(&session.id)
+    }
+
+    /// Marks or unmarks the selected session. The running session can't be marked, deleting or retagging it would
+    /// pull it away from under the timer.
+    pub fn toggle_selected_session_mark(&mut self)
+    {
+        let Some(session) = self.sessions.get(self.selected_session_index)
+        else
+        {
+            return;
+        };
+
+        if session.is_running()
+        {
+            self.show_status("THE RUNNING SESSION CAN'T BE MARKED");
+            return;
+        }
+
+        let session_id = session.id;
+
+        if let Some(position) = self.marked_session_ids.iter().position(|id| *id == session_id)
+        {
+            self.marked_session_ids.remove(position);
+        }
+        else
+        {
+            self.marked_session_ids.push(session_id);
+        }
+    }
+
+    /// Removes all marked sessions, rewriting the database once. A running session is kept.
+    ///
+    /// # Panics
+    ///
+    /// If the sessions can't be written.
+    pub fn delete_marked_sessions(&mut self)
+    {
+        self.reload_before_rewrite();
+
+        let marked_session_ids = std::mem::take(&mut self.marked_session_ids);
+        let mut changes = Vec::new();
+
+        for session in self.sessions.iter().filter(|session| marked_session_ids.contains(&session.id) && !session.is_running())
+        {
+            self.database_handler.record_audit_entry(&session.construct_db_string(self.value_separator, &self.date_format), None);
+            changes.push(SessionChange::Delete(session.id));
+        }
+
+        let session_count = self.sessions.len();
+        self.sessions.retain(|session| session.is_running() || !marked_session_ids.contains(&session.id));
+        self.selected_session_index = self.sessions.len().saturating_sub(1);
+
+        self.database_handler
+            .export_all_sessions(&self.sessions, self.value_separator, &self.date_format)
+            .expect("Failed to export all sessions to db.");
+        self.send_to_remote_storage(&changes);
+        self.show_status(format!("Deleted {}", format_session_count(session_count - self.sessions.len())));
+    }
+
+    /// Gives all marked sessions `tag`, rewriting the database once. A running session keeps its tag.
+    ///
+    /// # Panics
+    ///
+    /// If the sessions can't be written.
+    pub fn retag_marked_sessions(&mut self, tag: &str)
+    {
+        self.reload_before_rewrite();
+
+        let marked_session_ids = std::mem::take(&mut self.marked_session_ids);
+        let mut changes = Vec::new();
+        let mut retagged_count = 0;
+
+        for session in self.sessions.iter_mut().filter(|session| marked_session_ids.contains(&session.id) && !session.is_running())
+        {
+            if session.tag != tag
+            {
+                let old_session_string = session.construct_db_string(self.value_separator, &self.date_format);
+                let mut new_session = session.clone();
+                new_session.tag = tag.to_string();
+                let new_session_string = new_session.construct_db_string(self.value_separator, &self.date_format);
+
+                self.database_handler.record_audit_entry(&old_session_string, Some(&new_session_string));
+                changes.push(SessionChange::Update(new_session_string));
+            }
+
+            session.tag = tag.to_string();
+            retagged_count += 1;
+        }
+
+        self.database_handler
+            .export_all_sessions(&self.sessions, self.value_separator, &self.date_format)
+            .expect("Failed to export all sessions to db.");
+        self.send_to_remote_storage(&changes);
+        self.show_status(format!("Retagged {} as {tag}", format_session_count(retagged_count)));
+    }
+
+    /// Selects the most recently finished session, skipping a running one. Returns false if there is none.
+    pub fn select_last_finished_session(&mut self) -> bool
+    {
+        let Some(session_index) = self.sessions.iter().rposition(|session| !session.is_running())
+        else
+        {
+            return false;
+        };
+
+        self.selected_session_index = session_index;
+
+        true
+    }
+
+    pub fn start_new_session_based_on_selected(&mut self)
+    {
+        if self.is_last_session_still_running()
+        {
+            self.end_running_session();
+        }
+
+        if let Some(session) = self.sessions.get(self.selected_session_index)
+        {
+            if session.is_running()
+            {
+                return;
+            }
+
+            let description = &session.description;
+            let tag_index = self.get_index_of_tag(&session.tag);
+
+            self.description_buffer.set(description);
+            self.start_buffer.clear();
+            self.timer_buffer.clear();
+            self.set_selected_tag_index(tag_index);
+
+            self.try_start_new_session();
+        }
+    }
+
+    pub fn session_buffer_has_pending_changes(&self) -> bool
+    {
+        if let Some(selected_session) = self.sessions.get(self.selected_session_index)
+        {
+            if let Some(edited_session) = self.session_edit_buffer.clone()
+            {
+                !selected_session.eq(&edited_session)
+            }
+            else
+            {
+                false
+            }
+        }
+        else
+        {
+            false
+        }
+    }
+
+    pub fn apply_changes_to_session(&mut self) -> bool
+    {
+        if let Some(edited_session) = &self.session_edit_buffer
+            && !edited_session.has_valid_times()
+        {
+            self.session_edit_error = Some(String::from("END IS BEFORE START"));
+
+            return false;
+        }
+
+        if let Some(selected_session) = self.sessions.get_mut(self.selected_session_index)
+            && let Some(edited_session) = self.session_edit_buffer.clone()
+        {
+            let old_session_string = (!selected_session.is_running())
+                .then(|| selected_session.construct_db_string(self.value_separator, &self.date_format));
+
+            selected_session.description = edited_session.description;
+            selected_session.tag = edited_session.tag;
+            selected_session.start = edited_session.start;
+            selected_session.end = edited_session.end;
+
+            if !selected_session.is_running()
+            {
+                let session_string = selected_session.construct_db_string(self.value_separator, &self.date_format);
+
+                self.database_handler.update_session(&session_string, self.value_separator, &self.date_format);
+
+                if let Some(old_session_string) = old_session_string.filter(|old_session_string| *old_session_string != session_string)
+                {
+                    self.database_handler.record_audit_entry(&old_session_string, Some(&session_string));
+                    self.send_to_remote_storage(&[SessionChange::Update(session_string)]);
+                }
+            }
+
+            self.show_status("Session saved");
+        }
+
+        true
+    }
+
+    pub fn store_modified_field_to_session_buffer(&mut self) -> bool
+    {
+        if let Some(selected_session) = self.session_edit_buffer.as_mut()
+        {
+            let mut modified_session = selected_session.clone();
+            modified_session.set_field(&self.selected_session_field);
+
+            if !modified_session.has_valid_times()
+            {
+                self.session_edit_error = Some(String::from("END IS BEFORE START"));
+
+                return false;
+            }
+
+            *selected_session = modified_session;
+        }
+
+        self.session_edit_error = None;
+
+        true
+    }
+
+    pub fn open_notes_editor(&mut self)
+    {
+        self.notes_editor = self.sessions.get(self.selected_session_index).map(|session| NotesEditor::new(&session.notes));
+    }
+
+    /// Stores the edited notes with the selected session and closes the editor.
+    pub fn save_notes(&mut self)
+    {
+        if let Some(notes) = self.notes_editor.take().map(|notes_editor| notes_editor.get_text())
+        {
+            self.change_selected_session(|session| session.notes = notes);
+            self.show_status("Notes saved");
+        }
+    }
+
+    /// Opens the description and notes of the selected session in the user's editor, with the app suspended until it
+    /// is closed, and stores what was saved.
+    pub fn edit_selected_session_in_editor(&mut self)
+    {
+        let Some(session) = self.sessions.get(self.selected_session_index)
+        else
+        {
+            return;
+        };
+
+        let text = format_session_text(&session.description, &session.notes);
+
+        match self.renderer.suspend_while(|| edit_in_editor(&text))
+        {
+            Ok(edited_text) => match parse_session_text(&edited_text)
+            {
+                Some((description, notes)) =>
+                {
+                    self.session_edit_error = None;
+                    let description = description.replace(self.value_separator, ",");
+
+                    self.change_selected_session(|session| {
+                        session.description = description;
+                        session.notes = notes;
+                    });
+                }
+                None => self.session_edit_error = Some(String::from("DESCRIPTION IS EMPTY")),
+            },
+            Err(error) =>
+            {
+                warn!("Editing session in the editor failed: {error}");
+                self.session_edit_error = Some(error);
+            }
+        }
+    }
+
+    /// Applies `change` to the selected session and stores it, noting the change in the audit log. Running sessions
+    /// are only changed in memory, they are stored when they end.
+    fn change_selected_session(&mut self, change: impl FnOnce(&mut Session))
+    {
+        let Some(selected_session) = self.sessions.get_mut(self.selected_session_index)
+        else
+        {
+            return;
+        };
+
+        if selected_session.is_running()
+        {
+            change(selected_session);
+            return;
+        }
+
+        let old_session_string = selected_session.construct_db_string(self.value_separator, &self.date_format);
+        change(selected_session);
+        let session_string = selected_session.construct_db_string(self.value_separator, &self.date_format);
+
+        if session_string != old_session_string
+        {
+            self.database_handler.update_session(&session_string, self.value_separator, &self.date_format);
+            self.database_handler.record_audit_entry(&old_session_string, Some(&session_string));
+            self.send_to_remote_storage(&[SessionChange::Update(session_string)]);
+        }
+    }
+
+    pub fn copy_selected_session_to_buffer(&mut self)
+    {
+        if let Some(selected_session) = self.sessions.get(self.selected_session_index)
+        {
+            self.session_edit_buffer = Some(selected_session.clone());
+            self.selected_session_field = SessionField::Date(selected_session.start);
+
+            self.temp_tag_index = self.get_index_of_tag(&selected_session.tag);
+        }
+
+        if !self.is_session_field_visible(0)
+        {
+            self.increment_selected_session_field();
+        }
+    }
+
+    pub fn open_gaps(&mut self)
+    {
+        self.load_all_sessions();
+        self.gaps_date = self.get_today();
+        self.selected_gap_index = 0;
+        self.load_day_activity();
+    }
+
+    pub fn move_gaps_date(&mut self, days: i64)
+    {
+        self.gaps_date += TimeDelta::days(days);
+        self.selected_gap_index = 0;
+        self.load_day_activity();
+    }
+
+    fn load_day_activity(&mut self)
+    {
+        self.day_activity = self.config.activitywatch_url.as_ref().map(|url| fetch_day_activity(url, self.gaps_date));
+    }
+
+    /// The active part of a gap that is long enough to be offered as a session, with the window used the most in it.
+    pub fn get_gap_activity_block(&self, start: NaiveDateTime, end: NaiveDateTime) -> Option<(NaiveDateTime, NaiveDateTime, String)>
+    {
+        let Some(Ok(day_activity)) = &self.day_activity
+        else
+        {
+            return None;
+        };
+
+        let (block_start, block_end) = day_activity.get_active_span_within(start, end)?;
+
+        if day_activity.get_active_time_within(start, end) < self.config.activitywatch_min_block
+        {
+            return None;
+        }
+
+        let description = day_activity
+            .get_main_window_within(block_start, block_end)
+            .map(|window| {
+                if window.title.trim().is_empty()
+                {
+                    window.app.clone()
+                }
+                else
+                {
+                    window.title.clone()
+                }
+            })
+            .unwrap_or_default();
+
+        Some((block_start, block_end, description))
+    }
+
+    /// Untracked stretches of at least a minute within the working hours of the gaps day. Today is only checked up
+    /// to now, days that aren't worked have none.
+    pub fn get_gaps(&self) -> Vec<(NaiveDateTime, NaiveDateTime)>
+    {
+        let Some((day_start, day_end)) = self.get_working_hours(self.gaps_date)
+        else
+        {
+            return Vec::new();
+        };
+
+        let now = self.get_current_time();
+        let day_end = day_end.min(now);
+
+        let mut busy = self
+            .sessions
+            .iter()
+            .map(|session| (session.start.max(day_start), session.end.unwrap_or(now).min(day_end)))
+            .filter(|(start, end)| start < end)
+            .collect::<Vec<(NaiveDateTime, NaiveDateTime)>>();
+
+        busy.sort();
+
+        let mut gaps = Vec::new();
+        let mut gap_start = day_start;
+
+        for (start, end) in busy.into_iter().chain([(day_end, day_end)])
+        {
+            if start - gap_start >= TimeDelta::minutes(1)
+            {
+                gaps.push((gap_start, start));
+            }
+
+            gap_start = gap_start.max(end);
+        }
+
+        gaps
+    }
+
+    /// Prefills the manual entry form with the selected gap, or with its active block and main window if the activity
+    /// watchers saw enough activity in it.
+    pub fn open_manual_entry_for_selected_gap(&mut self) -> bool
+    {
+        if self.refuse_read_only_change()
+        {
+            return false;
+        }
+
+        let Some((start, end)) = self.get_gaps().get(self.selected_gap_index).copied()
+        else
+        {
+            return false;
+        };
+
+        let (start, end, description) = self.get_gap_activity_block(start, end).unwrap_or((start, end, String::new()));
+
+        self.open_manual_entry();
+        // The separator would split the stored line.
+        self.manual_entry.description = description.replace(self.value_separator, ",");
+        self.manual_entry.date = format!("{}", start.format(INPUT_DATE_FORMAT));
+        self.manual_entry.start = format!("{}", start.format(INPUT_TIME_FORMAT));
+        self.manual_entry.end = format!("{}", end.format(INPUT_TIME_FORMAT));
+
+        true
+    }
+
+    /// Fills the manual entry form with the selected session on today's date, so it can be adjusted before saving
+    /// instead of starting right away like a copy.
+    pub fn open_manual_entry_for_selected_session(&mut self)
+    {
+        let Some(session) = self.sessions.get(self.selected_session_index).cloned()
+        else
+        {
+            return;
+        };
+
+        self.open_manual_entry();
+        self.manual_entry.description = session.description;
+        self.manual_entry.start = format!("{}", session.start.format(INPUT_TIME_FORMAT));
+
+        if let Some(end) = session.end
+        {
+            self.manual_entry.end = format!("{}", end.format(INPUT_TIME_FORMAT));
+        }
+
+        if let Some(tag_index) = self.tags.iter().position(|tag| *tag == session.tag)
+        {
+            self.manual_entry.tag_index = tag_index;
+        }
+    }
+
+    pub fn open_manual_entry(&mut self)
+    {
+        let now = self.get_current_time();
+        self.manual_entry.reset(self.get_selected_tag_index(), now);
+    }
+
+    pub fn stage_manual_session(&mut self) -> bool
+    {
+        match self.manual_entry.to_session(&self.tags)
+        {
+            Ok(session) =>
+            {
+                self.manual_entry.error = None;
+                self.stage_pending_session(session, None);
+
+                true
+            }
+            Err(error) =>
+            {
+                self.manual_entry.error = Some(error);
+
+                false
+            }
+        }
+    }
+
+    /// Stores a finished session given on the command line, going through the manual entry form so it is checked the
+    /// same way. New tags are created, without a tag the last used one is taken. Nothing is stored if the session is
+    /// invalid or overlaps others. Returns the stored session.
+    ///
+    /// # Errors
+    ///
+    /// The message of the first invalid field, or of the overlap.
+    ///
+    /// # Panics
+    ///
+    /// If the session can't be stored.
+    pub fn add_session(
+        &mut self,
+        description: &str,
+        tag: Option<&str>,
+        date: NaiveDate,
+        start: &str,
+        end: &str,
+    ) -> Result<Session, String>
+    {
+        let tag = self.get_command_tag(tag);
+        // A new tag is only stored along with the session.
+        let mut tags = self.tags.clone();
+        tags.extend(tag.clone().filter(|tag| !self.tags.contains(tag)));
+
+        self.open_manual_entry();
+        self.manual_entry.description = description.replace(self.value_separator, ",");
+        self.manual_entry.tag_index =
+            tag.and_then(|tag| tags.iter().position(|existing_tag| *existing_tag == tag)).unwrap_or(tags.len());
+        self.manual_entry.date = format!("{}", date.format(INPUT_DATE_FORMAT));
+        self.manual_entry.start = start.to_string();
+        self.manual_entry.end = end.to_string();
+
+        let session = self.manual_entry.to_session(&tags)?;
+        self.stage_pending_session(session.clone(), None);
+
+        if self.pending_session_has_overlaps()
+        {
+            self.pending_session = None;
+            self.overlapping_session_indices.clear();
+
+            return Err("OVERLAPS EXISTING SESSIONS".to_string());
+        }
+
+        self.store_tag(&session.tag);
+        self.add_pending_session();
+
+        Ok(self.sessions.iter().max_by_key(|session| session.id).cloned().expect("Failed to find the added session."))
+    }
+
+    /// Starts a session given on the command line, ending the running one as it starts. `start` is a time of today or
+    /// an offset into the past, like in the new session popup. New tags are created, without a tag the last used one is
+    /// taken. Returns the started session.
+    ///
+    /// # Errors
+    ///
+    /// If there is no tag or description, or the start can't be read or is before the running session.
+    pub fn start_session(&mut self, description: &str, tag: Option<&str>, start: Option<&str>) -> Result<Session, String>
+    {
+        let Some(tag) = self.get_command_tag(tag)
+        else
+        {
+            return Err("NO TAG SELECTED".to_string());
+        };
+
+        self.description_buffer.set(&description.trim().replace(self.value_separator, ","));
+        self.start_buffer = start.unwrap_or_default().to_string();
+        self.timer_buffer.clear();
+
+        if self.description_buffer.is_empty()
+        {
+            return Err("DESCRIPTION IS EMPTY".to_string());
+        }
+
+        let Some(start) = self.get_new_session_start()
+        else
+        {
+            return Err("INVALID START (HH:MM OR -15m)".to_string());
+        };
+
+        if let Some(running_session) = self.sessions.last().filter(|session| session.is_running())
+        {
+            if start < running_session.start
+            {
+                return Err("START IS BEFORE THE RUNNING SESSION".to_string());
+            }
+
+            self.end_running_session_at(start);
+        }
+
+        self.store_tag(&tag);
+        self.set_selected_tag_index(self.get_index_of_tag(&tag));
+        self.try_start_new_session();
+        self.export_running_session_changes();
+
+        self.sessions.last().filter(|session| session.is_running()).cloned().ok_or_else(|| "SESSION NOT STARTED".to_string())
+    }
+
+    /// The tag named on the command line, cleaned up to be stored, or the last used tag without one.
+    fn get_command_tag(&self, tag: Option<&str>) -> Option<String>
+    {
+        match tag
+        {
+            Some(tag) => Some(tag.trim().replace(self.value_separator, ",")).filter(|tag| !tag.is_empty()),
+            None => self.tags.get(self.get_selected_tag_index()).cloned(),
+        }
+    }
+
+    /// Changes the latest stored session from the command line, keeping what is `None`. The end is a time on the day
+    /// the session started, or on the day after if it is before the start. Returns the changed session.
+    ///
+    /// # Errors
+    ///
+    /// If there is no finished session, a new description or tag is empty, the end can't be read or equals the start, or
+    /// the amended session overlaps another.
+    pub fn amend_last_session(&mut self, description: Option<&str>, tag: Option<&str>, end: Option<&str>) -> Result<Session, String>
+    {
+        let Some(index) = self
+            .sessions
+            .iter()
+            .enumerate()
+            .filter(|(_, session)| !session.is_running())
+            .max_by_key(|(_, session)| session.start)
+            .map(|(index, _)| index)
+        else
+        {
+            return Err("NO SESSION TO AMEND".to_string());
+        };
+
+        let mut amended_session = self.sessions[index].clone();
+
+        if let Some(description) = description.map(str::trim)
+        {
+            if description.is_empty()
+            {
+                return Err("DESCRIPTION IS EMPTY".to_string());
+            }
+
+            amended_session.description = description.replace(self.value_separator, ",");
+        }
+
+        if let Some(end) = end
+        {
+            let Some(end) = parse_time(end).map(|time| amended_session.start.date().and_time(time))
+            else
+            {
+                return Err("INVALID END TIME (HH:MM)".to_string());
+            };
+
+            if end == amended_session.start
+            {
+                return Err("END MUST DIFFER FROM START".to_string());
+            }
+
+            // An end time before the start time means the session ran past midnight.
+            amended_session.end = Some(
+                if end < amended_session.start
+                {
+                    end + TimeDelta::days(1)
+                }
+                else
+                {
+                    end
+                },
+            );
+        }
+
+        if !self.get_overlapping_session_indices(&amended_session, Some(index)).is_empty()
+        {
+            return Err("AMENDED SESSION OVERLAPS ANOTHER".to_string());
+        }
+
+        if let Some(tag) = tag
+        {
+            let Some(tag) = self.get_command_tag(Some(tag))
+            else
+            {
+                return Err("TAG IS EMPTY".to_string());
+            };
+
+            self.store_tag(&tag);
+            amended_session.tag = tag;
+        }
+
+        self.selected_session_index = index;
+        self.change_selected_session(|session| *session = amended_session.clone());
+
+        Ok(amended_session)
+    }
+
+    pub fn stage_edited_session(&mut self)
+    {
+        if let Some(edited_session) = self.session_edit_buffer.clone()
+        {
+            self.stage_pending_session(edited_session, Some(self.selected_session_index));
+        }
+    }
+
+    fn stage_pending_session(&mut self, session: Session, mut ignored_index: Option<usize>)
+    {
+        // Overlaps with sessions further back than the loaded history have to be found too.
+        if self.database_handler.load_sessions_since(session.start.date())
+        {
+            self.reload_sessions_keeping_selection();
+            ignored_index = ignored_index.map(|_| self.selected_session_index);
+        }
+
+        self.overlapping_session_indices = self.get_overlapping_session_indices(&session, ignored_index);
+        self.overlap_adjust_failed = false;
+        self.pending_session = Some(session);
+    }
+
+    pub fn get_overlapping_session_indices(&self, session: &Session, ignored_index: Option<usize>) -> Vec<usize>
+    {
+        let now = self.get_current_time();
+
+        self.sessions
+            .iter()
+            .enumerate()
+            .filter(|(index, other)| Some(*index) != ignored_index && session.overlaps(other, now))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn pending_session_has_overlaps(&self) -> bool
+    {
+        !self.overlapping_session_indices.is_empty()
+    }
+
+    /// Trims the pending session so it no longer overlaps any of the conflicting sessions.
+    /// Returns false if nothing would be left of it.
+    pub fn adjust_pending_session(&mut self) -> bool
+    {
+        let now = self.get_current_time();
+
+        let Some(mut session) = self.pending_session.clone()
+        else
+        {
+            return false;
+        };
+
+        for index in &self.overlapping_session_indices
+        {
+            let other = &self.sessions[*index];
+            let other_end = other.end.unwrap_or(now);
+
+            if other.start <= session.start
+            {
+                session.start = cmp::max(session.start, other_end);
+            }
+            else if let Some(end) = session.end
+            {
+                session.end = Some(cmp::min(end, other.start));
+            }
+        }
+
+        if session.end.is_some_and(|end| end <= session.start)
+        {
+            self.overlap_adjust_failed = true;
+
+            return false;
+        }
+
+        self.pending_session = Some(session);
+
+        true
+    }
+
+    /// Stores the session staged in the manual entry form or for a duplicate under a new id.
+    ///
+    /// # Panics
+    ///
+    /// If the session can't be stored.
+    pub fn add_pending_session(&mut self)
+    {
+        if let Some(mut session) = self.pending_session.take()
+        {
+            session.id = self.get_next_session_id();
+
+            let session_string = session.construct_db_string(self.value_separator, &self.date_format);
+            self.database_handler
+                .export_session(&session_string, self.value_separator, &self.date_format)
+                .expect("Error exporting session.");
+            self.send_to_remote_storage(&[SessionChange::Create(session_string)]);
+
+            self.insert_session(session);
+
+            self.set_selected_tag_index(self.manual_entry.tag_index);
+        }
+
+        self.overlapping_session_indices.clear();
+    }
+
+    pub fn apply_pending_session_to_edit_buffer(&mut self)
+    {
+        if let Some(session) = self.pending_session.take()
+        {
+            self.session_edit_buffer = Some(session);
+        }
+
+        self.overlapping_session_indices.clear();
+    }
+
+    fn insert_session(&mut self, session: Session)
+    {
+        let insert_index = self.sessions.iter().position(|s| s.is_running() || s.start > session.start).unwrap_or(self.sessions.len());
+
+        self.sessions.insert(insert_index, session);
+    }
+
+    pub fn try_import_file(&mut self) -> bool
+    {
+        match import_file(&self.import_path_buffer)
+        {
+            Ok(ImportedFile::Sessions(imported_sessions)) =>
+            {
+                self.import_summary = Some(self.merge_imported_sessions(imported_sessions));
+                self.import_error = None;
+
+                true
+            }
+            Ok(ImportedFile::Csv(csv_mapping)) =>
+            {
+                self.csv_mapping = Some(csv_mapping);
+                self.import_error = None;
+
+                true
+            }
+            Err(error) =>
+            {
+                self.import_error = Some(error);
+
+                false
+            }
+        }
+    }
+
+    pub fn import_mapped_csv(&mut self)
+    {
+        if let Some(csv_mapping) = self.csv_mapping.take()
+        {
+            self.import_summary = Some(self.merge_imported_sessions(csv_mapping.to_imported_sessions()));
+        }
+    }
+
+    /// Adds imported sessions that aren't already tracked (same start, end and description), creating missing tags.
+    fn merge_imported_sessions(&mut self, imported_sessions: ImportedSessions) -> ImportSummary
+    {
+        self.load_all_sessions();
+        self.reload_before_rewrite();
+
+        let mut summary = ImportSummary {
+            imported: 0,
+            duplicates: 0,
+            invalid: imported_sessions.invalid_entries,
+        };
+        let mut changes = Vec::new();
+
+        for mut session in imported_sessions.sessions
+        {
+            session.description = session.description.replace(self.value_separator, ",");
+            session.tag = session.tag.replace(self.value_separator, ",");
+
+            let is_duplicate = self.sessions.iter().any(|existing| {
+                existing.start == session.start && existing.end == session.end && existing.description == session.description
+            });
+
+            if is_duplicate
+            {
+                summary.duplicates += 1;
+                continue;
+            }
+
+            self.store_tag(&session.tag);
+
+            session.id = self.get_next_session_id();
+            changes.push(SessionChange::Create(session.construct_db_string(self.value_separator, &self.date_format)));
+            self.insert_session(session);
+
+            summary.imported += 1;
+        }
+
+        if summary.imported > 0
+        {
+            self.database_handler
+                .export_all_sessions(&self.sessions, self.value_separator, &self.date_format)
+                .expect("Failed to export all sessions to db.");
+            self.send_to_remote_storage(&changes);
+        }
+
+        summary
+    }
+
+    pub fn open_export_form(&mut self)
+    {
+        self.load_all_sessions();
+        let now = self.get_current_time();
+        self.export_form.reset(now);
+    }
+
+    /// Opens the export form for the marked sessions, with the range covering all of them.
+    pub fn open_export_form_for_marked(&mut self)
+    {
+        self.open_export_form();
+        self.export_form.session_ids = Some(self.marked_session_ids.clone());
+
+        let marked_dates = self.sessions.iter().filter(|session| self.is_session_marked(session)).map(|session| session.start.date());
+
+        if let (Some(from), Some(to)) = (marked_dates.clone().min(), marked_dates.max())
+        {
+            self.export_form.from = format!("{}", from.format(INPUT_DATE_FORMAT));
+            self.export_form.to = format!("{}", to.format(INPUT_DATE_FORMAT));
+        }
+    }
+
+    pub fn try_export_sessions(&mut self) -> bool
+    {
+        let sessions = match &self.export_form.session_ids
+        {
+            Some(session_ids) => self.sessions.iter().filter(|session| session_ids.contains(&session.id)).cloned().collect(),
+            None => self.sessions.clone(),
+        };
+
+        let result = self.export_form.get_range().and_then(|(from, to)| {
+            export_icalendar(&sessions, from, to, &self.export_form.path, self.config.rounding, &self.config.get_issue_tracker())
+        });
+
+        match result
+        {
+            Ok(exported_session_count) =>
+            {
+                self.show_status(format!(
+                    "Exported {} to {}",
+                    format_session_count(exported_session_count),
+                    self.export_form.path.trim()
+                ));
+                self.export_form.error = None;
+
+                true
+            }
+            Err(error) =>
+            {
+                self.export_form.error = Some(error);
+
+                false
+            }
+        }
+    }
+
+    pub fn get_workspace(&self) -> &str
+    {
+        self.database_handler.get_workspace()
+    }
+
+    pub fn open_workspaces(&mut self)
+    {
+        self.workspaces = self.database_handler.list_workspaces();
+        self.selected_workspace_index = self.workspaces.iter().position(|workspace| workspace == self.get_workspace()).unwrap_or(0);
+        self.workspace_name_buffer.clear();
+        self.workspace_error = None;
+    }
+
+    pub fn switch_to_selected_workspace(&mut self) -> bool
+    {
+        match self.workspaces.get(self.selected_workspace_index)
+        {
+            Some(workspace) => self.switch_workspace(&workspace.clone()),
+            None => false,
+        }
+    }
+
+    /// Creates the typed workspace and opens it.
+    pub fn create_workspace(&mut self) -> bool
+    {
+        let workspace = self.workspace_name_buffer.trim().to_string();
+
+        if !is_valid_workspace_name(&workspace)
+        {
+            self.workspace_error = Some(String::from("INVALID NAME"));
+            return false;
+        }
+
+        self.switch_workspace(&workspace)
+    }
+
+    /// Closes the current workspace and opens `workspace` in its place, creating it if needed. Refused while a session
+    /// is running, since that session only exists in memory, and for encrypted workspaces that don't share the
+    /// passphrase given on launch.
+    fn switch_workspace(&mut self, workspace: &str) -> bool
+    {
+        if self.is_last_session_still_running() && !self.daemon_connected
+        {
+            self.workspace_error = Some(String::from("END THE RUNNING SESSION FIRST"));
+            return false;
+        }
+
+        let cipher = self.passphrase.as_deref().and_then(|passphrase| DatabaseHandler::unlock(workspace, passphrase));
+
+        if !self.is_ephemeral() && DatabaseHandler::is_encrypted(workspace) && cipher.is_none()
+        {
+            self.workspace_error = Some(format!("ENCRYPTED, START WITH -w {workspace}"));
+            return false;
+        }
+
+        if workspace != self.get_workspace()
+        {
+            info!("Switching from workspace {} to {workspace}", self.get_workspace());
+
+            let push_error = self.sync_with_toggl().or_else(|| self.push_to_sync_remote());
+
+            self.database_handler = self.database_handler.open_workspace(workspace, cipher);
+            self.load_config();
+
+            self.pull_from_sync_remote();
+            self.pull_from_remote_storage();
+            self.pull_from_toggl();
+            self.sync_message = push_error.or(self.sync_message.take());
+
+            let is_synced = self.get_sync_target().is_some() || self.get_remote_storage().is_some() || self.get_toggl_account().is_some();
+
+            if is_synced && self.sync_message.is_none()
+            {
+                self.show_status(format!("Synced {workspace}"));
+            }
+        }
+
+        self.selected_session_index = 0;
+        self.marked_session_ids.clear();
+        self.collapsed_days.clear();
+        self.workspace_error = None;
+        self.load_database();
+        self.fetch_assigned_issues();
+
+        true
+    }
+
+    /// The remote and branch the open workspace is synced with, `None` if sync is off or another instance has the
+    /// database open.
+    fn get_sync_target(&self) -> Option<SyncTarget>
+    {
+        let remote = self.config.sync_remote.as_ref().filter(|_| !self.is_read_only())?;
+
+        Some(SyncTarget::new(remote, &self.config.sync_branch, self.get_workspace()))
+    }
+
+    /// Merges the sessions from the sync remote into the workspace, before they are read.
+    fn pull_from_sync_remote(&mut self)
+    {
+        self.sync_message = self
+            .get_sync_target()
+            .and_then(|target| sync::pull(&self.database_handler, &target, self.value_separator, &self.date_format).err())
+            .inspect(|error| warn!("Pulling from the sync remote failed: {error}"));
+    }
+
+    /// Sends the workspace to the sync remote when closing it. Returns what went wrong, if anything.
+    pub fn push_to_sync_remote(&mut self) -> Option<String>
+    {
+        self.get_sync_target()
+            .and_then(|target| sync::push(&self.database_handler, &target, self.value_separator, &self.date_format).err())
+            .inspect(|error| warn!("Pushing to the sync remote failed: {error}"))
+    }
+
+    /// The server the open workspace's sessions are kept on, `None` if there is none or another instance has the
+    /// database open.
+    fn get_remote_storage(&self) -> Option<RemoteStorage>
+    {
+        let url = self.config.remote_url.as_ref().filter(|_| !self.is_read_only())?;
+
+        Some(RemoteStorage::new(url, self.config.remote_token.as_deref(), self.get_workspace()))
+    }
+
+    /// Replaces the stored sessions with those of the remote storage, before they are read.
+    fn pull_from_remote_storage(&mut self)
+    {
+        if let Some(storage) = self.get_remote_storage()
+            && let Err(error) = remote::pull(&self.database_handler, &storage, self.value_separator, &self.date_format)
+        {
+            warn!("Pulling from the remote storage failed: {error}");
+            self.sync_message = Some(error);
+        }
+    }
+
+    /// Sends changes of stored sessions to the remote storage, keeping those it doesn't accept for later.
+    fn send_to_remote_storage(&mut self, changes: &[SessionChange])
+    {
+        if let Some(storage) = self.get_remote_storage()
+        {
+            self.sync_message = remote::push(&self.database_handler, &storage, changes, self.value_separator)
+                .err()
+                .inspect(|error| warn!("Sending {} changes to the remote storage failed: {error}", changes.len()));
+        }
+    }
+
+    /// The Toggl account the open workspace is synced with, `None` if there is none or another instance has the
+    /// database open.
+    fn get_toggl_account(&self) -> Option<TogglAccount>
+    {
+        let token = self.config.toggl_token.as_ref().filter(|_| !self.is_read_only())?;
+
+        Some(TogglAccount {
+            token: token.clone(),
+            workspace_id: self.config.toggl_workspace_id,
+            projects: self.config.toggl_projects.clone(),
+        })
+    }
+
+    /// Brings in the entries made on Toggl, before the sessions are read.
+    fn pull_from_toggl(&mut self)
+    {
+        if let Some(error) = self.sync_with_toggl()
+        {
+            self.sync_message = Some(error);
+        }
+    }
+
+    /// Exchanges new sessions and entries with Toggl. Returns what went wrong, if anything.
+    pub fn sync_with_toggl(&mut self) -> Option<String>
+    {
+        self.get_toggl_account()
+            .and_then(|account| toggl::sync(&self.database_handler, &account, self.value_separator, &self.date_format).err())
+            .inspect(|error| warn!("Syncing with Toggl failed: {error}"))
+    }
+
+    /// Lists the open issues assigned to the user for the description suggestions.
+    /// Looks up the branch of the configured project folder, done when a new session is started so switching branches
+    /// is picked up.
+    pub fn detect_git_branch(&mut self)
+    {
+        self.git_branch = self.config.git_directory.as_deref().and_then(sync::get_current_branch);
+    }
+
+    fn fetch_assigned_issues(&mut self)
+    {
+        match self.config.get_issue_tracker().fetch_assigned_issues()
+        {
+            Ok(issues) => self.assigned_issues = issues,
+            Err(error) =>
+            {
+                warn!("Fetching assigned issues failed: {error}");
+                self.assigned_issues.clear();
+                self.sync_message = Some(error);
+            }
+        }
+    }
+
+    pub fn refresh_backups(&mut self)
+    {
+        self.backups = self.database_handler.list_backups();
+        self.selected_backup_index = 0;
+    }
+
+    pub fn open_audit_log(&mut self)
+    {
+        self.audit_entries = self.database_handler.import_audit_entries(self.value_separator, &self.date_format);
+        self.selected_audit_index = 0;
+    }
+
+    pub fn get_backup_label(&self, backup_name: &str) -> String
+    {
+        let timestamp = DatabaseHandler::get_backup_timestamp(backup_name)
+            .map_or(backup_name.to_string(), |timestamp| format!("{}", timestamp.format("%d %b %y %H:%M:%S")));
+        let session_count = self.database_handler.count_backup_sessions(backup_name);
+
+        format!("{timestamp}  {session_count} sessions")
+    }
+
+    /// Replaces the sessions with those of the selected backup.
+    ///
+    /// # Panics
+    ///
+    /// If the backup can't be read or the sessions can't be written.
+    pub fn restore_selected_backup(&mut self)
+    {
+        if let Some(backup_name) = self.backups.get(self.selected_backup_index).cloned()
+        {
+            self.database_handler
+                .restore_backup(&backup_name, self.value_separator, &self.date_format)
+                .expect("Failed to restore backup.");
+            self.reload_sessions();
+            self.show_status(format!("Restored {backup_name}"));
+        }
+    }
+
+    /// Picks up changes other programs made to the database files. Only done while idle, so nothing
+    /// being edited gets replaced underneath the user.
+    pub fn reload_on_external_changes(&mut self)
+    {
+        if !matches!(self.state, CommandState::Idle) || !self.database_handler.has_external_changes()
+        {
+            return;
+        }
+
+        self.merge_conflict_copies();
+        self.reload_sessions();
+        self.running_sessions_elsewhere =
+            self.database_handler.import_running_sessions_elsewhere(self.value_separator, &self.date_format);
+
+        if let Some(tags) = self.database_handler.import_tags()
+        {
+            self.tags = tags;
+        }
+
+        self.days_off = self.database_handler.import_days_off(self.value_separator);
+
+        if self.selected_tag_index >= self.tags.len()
+        {
+            self.selected_tag_index = 0;
+        }
+
+        if !self.rejected_lines.is_empty() && !self.is_read_only()
+        {
+            self.state = CommandState::ImportReport;
+        }
+    }
+
+    /// Merges the copies a sync service made of files changed on two machines at once and reports how many there were.
+    fn merge_conflict_copies(&mut self)
+    {
+        let merged_count = self.database_handler.merge_conflict_copies(self.value_separator, &self.date_format);
+
+        if merged_count > 0
+        {
+            self.sync_message = Some(format!("MERGED {merged_count} CONFLICT COPIES"));
+        }
+    }
+
+    /// Keeps the mark of the session running on this machine in step with the list, for other machines sharing the
+    /// database folder.
+    pub fn export_running_session_changes(&mut self)
+    {
+        let running_session = self.sessions.last().filter(|session| session.is_running());
+
+        if running_session != self.exported_running_session.as_ref()
+        {
+            self.database_handler.export_running_session(running_session, self.value_separator, &self.date_format);
+            self.exported_running_session = running_session.cloned();
+        }
+    }
+
+    /// Loads the next page of older sessions. Returns false once the whole history is loaded.
+    pub fn load_older_sessions(&mut self) -> bool
+    {
+        if !self.database_handler.load_older_sessions()
+        {
+            return false;
+        }
+
+        self.reload_sessions_keeping_selection();
+
+        true
+    }
+
+    /// Loads the whole history, for views that summarize more than the recent sessions.
+    pub fn load_all_sessions(&mut self)
+    {
+        if self.database_handler.load_all_sessions()
+        {
+            self.reload_sessions_keeping_selection();
+        }
+    }
+
+    pub fn has_unloaded_sessions(&self) -> bool
+    {
+        self.database_handler.has_unloaded_sessions()
+    }
+
+    /// Reloads after more of the history was loaded. Older sessions only add to the front of the list, so the selected
+    /// session keeps its distance to the end.
+    fn reload_sessions_keeping_selection(&mut self)
+    {
+        let selected_distance_to_end = self.sessions.len().saturating_sub(self.selected_session_index);
+
+        self.reload_sessions();
+
+        self.selected_session_index = self.sessions.len().saturating_sub(selected_distance_to_end);
+    }
+
+    /// Picks up changes other programs made to the sessions before all of them are written, which would replace those
+    /// changes.
+    fn reload_before_rewrite(&mut self)
+    {
+        if self.database_handler.has_external_changes()
+        {
+            self.reload_sessions_keeping_selection();
+        }
+    }
+
+    /// Re-reads the sessions from the database, keeping a running session that was not stored yet.
+    fn reload_sessions(&mut self)
+    {
+        let running_session = self.sessions.pop_if(|session| session.is_running());
+
+        let (sessions, rejected_lines) = self.database_handler.import_sessions(self.value_separator, &self.date_format);
+
+        self.sessions = sessions.unwrap_or_default();
+        self.rejected_lines = rejected_lines;
+
+        if let Some(mut running_session) = running_session
+        {
+            running_session.id = self.get_next_session_id();
+            self.sessions.push(running_session);
+        }
+
+        self.selected_session_index = 0;
+    }
+
+    pub fn is_read_only(&self) -> bool
+    {
+        self.database_handler.is_read_only()
+    }
+
+    /// Says why a change can't be made if the database is read-only and returns true then. The read-only database
+    /// would drop the change without a word.
+    pub fn refuse_read_only_change(&mut self) -> bool
+    {
+        if self.is_read_only()
+        {
+            self.show_status(READ_ONLY_STATUS);
+        }
+
+        self.is_read_only()
+    }
+
+    pub fn is_ephemeral(&self) -> bool
+    {
+        self.database_handler.is_ephemeral()
+    }
+
+    pub fn get_quarantine_file_name(&self) -> &str
+    {
+        self.database_handler.get_quarantine_file_name()
+    }
+
+    pub fn get_config_file_path(&self) -> PathBuf
+    {
+        self.database_handler.get_config_file_path()
+    }
+
+    pub fn get_themes_path(&self) -> PathBuf
+    {
+        self.database_handler.get_themes_path()
+    }
+
+    /// Reads the themes again, so new or changed theme files show up, and applies the one from the config.
+    pub fn reload_themes(&mut self)
+    {
+        let themes_path = self.database_handler.get_themes_path();
+        let (themes, mut errors) = load_themes(Some(themes_path.as_path()).filter(|_| !self.is_ephemeral()));
+
+        self.themes = themes;
+
+        if let Some(index) = self.themes.iter().position(|theme| theme.name == self.config.theme)
+        {
+            self.selected_theme_index = index;
+        }
+        else
+        {
+            errors.push(format!("UNKNOWN THEME {}", self.config.theme.to_uppercase()));
+            self.selected_theme_index = 0;
+        }
+
+        self.theme_errors = errors;
+        self.apply_selected_theme();
+    }
+
+    pub fn cycle_theme(&mut self, forward: bool)
+    {
+        if self.themes.is_empty()
+        {
+            return;
+        }
+
+        self.selected_theme_index = if forward
+        {
+            (self.selected_theme_index + 1) % self.themes.len()
+        }
+        else
+        {
+            (self.selected_theme_index + self.themes.len() - 1) % self.themes.len()
+        };
+
+        self.apply_selected_theme();
+    }
+
+    fn apply_selected_theme(&mut self)
+    {
+        let theme = self.themes.get(self.selected_theme_index).cloned().unwrap_or_default();
+        self.renderer.set_theme(theme);
+    }
+
+    pub fn open_settings(&mut self)
+    {
+        self.reload_themes();
+        self.settings_backup = Some(self.config.clone());
+    }
+
+    /// Changes a setting to its next or previous value. The app shows it right away, `save_settings` stores it.
+    pub fn change_setting(&mut self, field: SettingsField, forward: bool)
+    {
+        let step = if forward
+        {
+            SETTINGS_TIME_STEP
+        }
+        else
+        {
+            -SETTINGS_TIME_STEP
+        };
+
+        match field
+        {
+            SettingsField::Theme => self.cycle_theme(forward),
+            SettingsField::Clock =>
+            {
+                self.config.clock_format =
+                    get_cycled(&[ClockFormat::TwentyFourHour, ClockFormat::TwelveHour], self.config.clock_format, forward);
+            }
+            SettingsField::DateOrder =>
+            {
+                let date_orders = [DateOrder::DayMonthYear, DateOrder::MonthDayYear, DateOrder::YearMonthDay];
+                self.config.date_order = get_cycled(&date_orders, self.config.date_order, forward);
+            }
+            SettingsField::Dates =>
+            {
+                let date_labels = [DateLabels::Absolute, DateLabels::Relative, DateLabels::Weekdays];
+                self.config.date_labels = get_cycled(&date_labels, self.config.date_labels, forward);
+            }
+            SettingsField::Seconds => self.config.show_seconds = !self.config.show_seconds,
+            SettingsField::WeekStart =>
+            {
+                self.config.week_start = get_cycled(&[Weekday::Mon, Weekday::Sun], self.config.week_start, forward);
+            }
+            SettingsField::Confirm(action) =>
+            {
+                if self.config.asks_to(action)
+                {
+                    self.config.unconfirmed_actions.push(action);
+                }
+                else
+                {
+                    self.config.unconfirmed_actions.retain(|unconfirmed_action| *unconfirmed_action != action);
+                }
+            }
+            SettingsField::AutoStopAsk => self.config.auto_stop_ask = !self.config.auto_stop_ask,
+            SettingsField::MinDuration =>
+            {
+                let min_durations =
+                    MIN_DURATION_CHOICES.map(|seconds| Some(TimeDelta::seconds(seconds)).filter(|duration| !duration.is_zero()));
+                self.config.min_session_duration = get_cycled(&min_durations, self.config.min_session_duration, forward);
+            }
+            SettingsField::RoundingMode =>
+            {
+                let rounding_modes = [RoundingMode::None, RoundingMode::Nearest, RoundingMode::Up, RoundingMode::Down];
+                self.config.rounding.mode = get_cycled(&rounding_modes, self.config.rounding.mode, forward);
+            }
+            SettingsField::RoundingMinutes =>
+            {
+                self.config.rounding.minutes = get_cycled(&ROUNDING_MINUTES_CHOICES, self.config.rounding.minutes, forward);
+            }
+            // The workday stays within the day and doesn't end before it starts.
+            SettingsField::WorkingHoursStart =>
+            {
+                let (start, wrapped) = self.config.working_hours_start.overflowing_add_signed(step);
+
+                if wrapped == 0 && start < self.config.working_hours_end
+                {
+                    self.config.working_hours_start = start;
+                }
+            }
+            SettingsField::WorkingHoursEnd =>
+            {
+                let (end, wrapped) = self.config.working_hours_end.overflowing_add_signed(step);
+
+                if wrapped == 0 && end > self.config.working_hours_start
+                {
+                    self.config.working_hours_end = end;
+                }
+            }
+        }
+    }
+
+    /// The value of a setting as shown in the settings.
+    pub fn get_setting_text(&self, field: SettingsField) -> String
+    {
+        let (_, _, value) = self.get_setting_entry(field);
+
+        match value.as_str()
+        {
+            "true" => String::from("yes"),
+            "false" => String::from("no"),
+            "\"\"" => String::from("never"),
+            value => value.trim_matches('"').to_string(),
+        }
+    }
+
+    /// The table, key and TOML value of a setting in the config file.
+    fn get_setting_entry(&self, field: SettingsField) -> (&'static str, &'static str, String)
+    {
+        let quote = |value: &str| format!("\"{value}\"");
+        let format_time = |time: NaiveTime| quote(&time.format(INPUT_TIME_FORMAT).to_string());
+
+        match field
+        {
+            SettingsField::Theme =>
+            {
+                let theme_name =
+                    self.themes.get(self.selected_theme_index).map_or(self.config.theme.as_str(), |theme| theme.name.as_str());
+                ("appearance", "theme", quote(theme_name))
+            }
+            SettingsField::Clock => (
+                "appearance",
+                "clock",
+                quote(match self.config.clock_format
+                {
+                    ClockFormat::TwentyFourHour => "24h",
+                    ClockFormat::TwelveHour => "12h",
+                }),
+            ),
+            SettingsField::DateOrder => (
+                "appearance",
+                "date_order",
+                quote(match self.config.date_order
+                {
+                    DateOrder::DayMonthYear => "dmy",
+                    DateOrder::MonthDayYear => "mdy",
+                    DateOrder::YearMonthDay => "ymd",
+                }),
+            ),
+            SettingsField::Dates => (
+                "columns",
+                "dates",
+                quote(match self.config.date_labels
+                {
+                    DateLabels::Absolute => "absolute",
+                    DateLabels::Relative => "relative",
+                    DateLabels::Weekdays => "weekdays",
+                }),
+            ),
+            SettingsField::Seconds => ("columns", "seconds", self.config.show_seconds.to_string()),
+            SettingsField::WeekStart => (
+                "calendar",
+                "week_start",
+                quote(
+                    if self.config.week_start == Weekday::Sun
+                    {
+                        "sunday"
+                    }
+                    else
+                    {
+                        "monday"
+                    },
+                ),
+            ),
+            SettingsField::Confirm(action) => ("confirmations", action.get_key(), self.config.asks_to(action).to_string()),
+            SettingsField::AutoStopAsk => ("working_hours", "auto_stop_ask", self.config.auto_stop_ask.to_string()),
+            SettingsField::MinDuration =>
+            {
+                let min_duration = self.config.min_session_duration.map_or(String::new(), |duration| {
+                    if duration.num_seconds() % 60 == 0
+                    {
+                        format!("{}m", duration.num_minutes())
+                    }
+                    else
+                    {
+                        format!("{}s", duration.num_seconds())
+                    }
+                });
+
+                ("sessions", "min_duration", quote(&min_duration))
+            }
+            SettingsField::RoundingMode => (
+                "rounding",
+                "mode",
+                quote(match self.config.rounding.mode
+                {
+                    RoundingMode::None => "none",
+                    RoundingMode::Nearest => "nearest",
+                    RoundingMode::Up => "up",
+                    RoundingMode::Down => "down",
+                }),
+            ),
+            SettingsField::RoundingMinutes => ("rounding", "minutes", self.config.rounding.minutes.to_string()),
+            SettingsField::WorkingHoursStart => ("working_hours", "start", format_time(self.config.working_hours_start)),
+            SettingsField::WorkingHoursEnd => ("working_hours", "end", format_time(self.config.working_hours_end)),
+        }
+    }
+
+    /// Stores the changed settings in the workspace config file if the workspace has one, otherwise in the global one,
+    /// keeping the comments and everything else in it.
+    pub fn save_settings(&mut self) -> bool
+    {
+        let Some(backup) = self.settings_backup.take()
+        else
+        {
+            return true;
+        };
+
+        if let Some(theme) = self.themes.get(self.selected_theme_index)
+        {
+            self.config.theme.clone_from(&theme.name);
+        }
+
+        if self.is_ephemeral()
+        {
+            return true;
+        }
+
+        let config_path = self.get_settings_file_path();
+        let new_config = self.config.clone();
+        self.config = backup;
+        let old_entries = SettingsField::ALL.map(|field| self.get_setting_entry(field));
+        self.config = new_config;
+
+        let changed_entries =
+            SettingsField::ALL.map(|field| self.get_setting_entry(field)).into_iter().filter(|entry| !old_entries.contains(entry));
+
+        changed_entries.fold(true, |saved, (table, key, value)| set_config_value(&config_path, table, key, &value) && saved)
+    }
+
+    /// Does `action` without asking from now on, for when a question is answered with "yes, don't ask again".
+    pub fn stop_confirming(&mut self, action: ConfirmAction)
+    {
+        if !self.config.asks_to(action)
+        {
+            return;
+        }
+
+        self.config.unconfirmed_actions.push(action);
+
+        if !self.is_ephemeral() && !set_config_value(&self.get_settings_file_path(), "confirmations", action.get_key(), "false")
+        {
+            self.sync_message = Some(String::from("SETTINGS NOT SAVED"));
+        }
+    }
+
+    /// The workspace config file if the workspace has one, otherwise the global one.
+    fn get_settings_file_path(&self) -> PathBuf
+    {
+        self.database_handler
+            .get_workspace_config_file_path()
+            .filter(|workspace_config_path| workspace_config_path.exists())
+            .unwrap_or_else(|| self.database_handler.get_config_file_path())
+    }
+
+    /// Goes back to the settings as they were when opened.
+    pub fn cancel_settings(&mut self)
+    {
+        if let Some(backup) = self.settings_backup.take()
+        {
+            self.config = backup;
+        }
+
+        self.reload_themes();
+    }
+
+    pub fn clear_session_edit_buffer(&mut self)
+    {
+        self.session_edit_buffer = None;
+        self.session_edit_error = None;
+    }
+}
+
+/// The value after `current` in `values`, or before it, wrapping around. Values that are not among them go to the first.
+fn get_cycled<T: PartialEq + Copy>(values: &[T], current: T, forward: bool) -> T
+{
+    let Some(index) = values.iter().position(|value| *value == current)
+    else
+    {
+        return values[0];
+    };
+
+    if forward
+    {
+        values[(index + 1) % values.len()]
+    }
+    else
+    {
+        values[(index + values.len() - 1) % values.len()]
+    }
+}
+
+/// "1 session" or the count with "sessions", for status messages.
+fn format_session_count(count: usize) -> String
+{
+    if count == 1
+    {
+        String::from("1 session")
+    }
+    else
+    {
+        format!("{count} sessions")
     }
 }
@@ -1,4 +1,7 @@
-use chrono::NaiveDateTime;
+use crate::config::ConfirmAction;
+use crate::session::Session;
+use crate::text_input::TextInput;
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
 use std::fmt::{Display, Formatter};
 
 #[derive(Clone)]
@@ -6,16 +9,82 @@ pub enum CommandState
 {
     Idle,
     New(SessionInputState),
+    Add(ManualEntryField),
+    EditRunning(RunningEditField),
+    Overlap(OverlapOrigin),
+    Backups(ConfirmOpen),
+    AuditLog,
+    Workspaces(WorkspaceState),
+    ImportReport,
+    Import(ImportState),
+    Export(ExportState),
+    Calendar,
+    Stats,
+    DatabaseStats,
+    Goals,
+    Templates,
+    WeekReport,
+    Focus,
+    Gaps,
+    Settings(SettingsField),
+    Locked,
     Modify(SessionModifyState),
     End,
+    /// Asks whether to end the running session as of the auto stop time it ran past.
+    AutoStop,
+    /// Asks whether to discard the running session instead of ending it, as it is shorter than the minimum duration.
+    DiscardShort,
+    Discard,
+    /// Asks whether to pick up the edit a run that stopped unexpectedly left unsaved.
+    RestoreEdit,
     Quitting,
 }
 
+/// An edit that is not saved yet, kept on disk while it is made so it survives a crash.
+#[derive(Clone)]
+pub enum UnsavedEdit
+{
+    /// The description typed for a new session.
+    Description(String),
+    /// A stored session with the changes made to it so far.
+    Session(Session),
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum SessionInputState
 {
     Description(ConfirmOpen),
     Tag(TagInputState),
+    Start,
+    Timer,
+}
+
+impl SessionInputState
+{
+    /// The field Tab moves to in the new session popup, coming back around to the description after the timer.
+    #[must_use]
+    pub fn next(self) -> Self
+    {
+        match self
+        {
+            SessionInputState::Description(_) => SessionInputState::Tag(TagInputState::Select),
+            SessionInputState::Tag(_) => SessionInputState::Start,
+            SessionInputState::Start => SessionInputState::Timer,
+            SessionInputState::Timer => SessionInputState::Description(ConfirmOpen::No),
+        }
+    }
+
+    #[must_use]
+    pub fn previous(self) -> Self
+    {
+        match self
+        {
+            SessionInputState::Description(_) => SessionInputState::Timer,
+            SessionInputState::Tag(_) => SessionInputState::Description(ConfirmOpen::No),
+            SessionInputState::Start => SessionInputState::Tag(TagInputState::Select),
+            SessionInputState::Timer => SessionInputState::Start,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -23,13 +92,27 @@ pub enum SessionModifyState
 {
     Edit(SessionEditState),
     Continue(ConfirmOpen),
+    Duplicate,
     Delete(ConfirmOpen),
+    Select(SelectState),
+}
+
+/// Marking several sessions and applying one action to all of them.
+#[derive(PartialEq, Copy, Clone)]
+pub enum SelectState
+{
+    Browse,
+    Delete,
+    Retag(ConfirmOpen),
 }
 
 #[derive(Clone)]
 pub enum SessionEditState
 {
     Browse,
+    Details,
+    /// Editing the notes of the session, asking whether to keep them when leaving with changes.
+    Notes(ConfirmOpen),
     EditFields(SessionFieldEditState),
     Confirm,
 }
@@ -45,11 +128,11 @@ pub enum SessionFieldEditState
 pub enum SessionField
 {
     Date(NaiveDateTime),
-    Description(String),
+    Description(TextInput),
     Tag(String),
     Start(NaiveDateTime),
     End(Option<NaiveDateTime>),
-    None
+    None,
 }
 
 #[derive(PartialEq, Copy, Clone)]
@@ -57,9 +140,401 @@ pub enum TagInputState
 {
     Select,
     New,
+    #[allow(dead_code)]
     Delete(ConfirmOpen),
 }
 
+#[derive(PartialEq, Copy, Clone)]
+pub enum ManualEntryField
+{
+    Description,
+    Tag,
+    Date,
+    Start,
+    End,
+}
+
+impl ManualEntryField
+{
+    #[must_use]
+    pub fn next(self) -> Self
+    {
+        match self
+        {
+            ManualEntryField::Description => ManualEntryField::Tag,
+            ManualEntryField::Tag => ManualEntryField::Date,
+            ManualEntryField::Date => ManualEntryField::Start,
+            ManualEntryField::Start => ManualEntryField::End,
+            ManualEntryField::End => ManualEntryField::Description,
+        }
+    }
+
+    #[must_use]
+    pub fn previous(self) -> Self
+    {
+        match self
+        {
+            ManualEntryField::Description => ManualEntryField::End,
+            ManualEntryField::Tag => ManualEntryField::Description,
+            ManualEntryField::Date => ManualEntryField::Tag,
+            ManualEntryField::Start => ManualEntryField::Date,
+            ManualEntryField::End => ManualEntryField::Start,
+        }
+    }
+}
+
+/// Fields of the running session that can be changed without ending it.
+#[derive(PartialEq, Copy, Clone)]
+pub enum RunningEditField
+{
+    Description,
+    Tag,
+}
+
+impl RunningEditField
+{
+    #[must_use]
+    pub fn next(self) -> Self
+    {
+        match self
+        {
+            RunningEditField::Description => RunningEditField::Tag,
+            RunningEditField::Tag => RunningEditField::Description,
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ImportState
+{
+    Path,
+    Mapping(CsvMappingField),
+    Summary,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum CsvMappingField
+{
+    Description,
+    Tag,
+    Start,
+    End,
+    DateFormat,
+}
+
+impl CsvMappingField
+{
+    #[must_use]
+    pub fn next(self) -> Self
+    {
+        match self
+        {
+            CsvMappingField::Description => CsvMappingField::Tag,
+            CsvMappingField::Tag => CsvMappingField::Start,
+            CsvMappingField::Start => CsvMappingField::End,
+            CsvMappingField::End => CsvMappingField::DateFormat,
+            CsvMappingField::DateFormat => CsvMappingField::Description,
+        }
+    }
+
+    #[must_use]
+    pub fn previous(self) -> Self
+    {
+        match self
+        {
+            CsvMappingField::Description => CsvMappingField::DateFormat,
+            CsvMappingField::Tag => CsvMappingField::Description,
+            CsvMappingField::Start => CsvMappingField::Tag,
+            CsvMappingField::End => CsvMappingField::Start,
+            CsvMappingField::DateFormat => CsvMappingField::End,
+        }
+    }
+}
+
+/// Rows of the settings popup, from top to bottom.
+#[derive(PartialEq, Copy, Clone)]
+pub enum SettingsField
+{
+    Theme,
+    Clock,
+    DateOrder,
+    Dates,
+    Seconds,
+    WeekStart,
+    Confirm(ConfirmAction),
+    AutoStopAsk,
+    MinDuration,
+    RoundingMode,
+    RoundingMinutes,
+    WorkingHoursStart,
+    WorkingHoursEnd,
+}
+
+impl SettingsField
+{
+    pub const ALL: [SettingsField; 18] = [
+        SettingsField::Theme,
+        SettingsField::Clock,
+        SettingsField::DateOrder,
+        SettingsField::Dates,
+        SettingsField::Seconds,
+        SettingsField::WeekStart,
+        SettingsField::Confirm(ConfirmAction::Delete),
+        SettingsField::Confirm(ConfirmAction::Discard),
+        SettingsField::Confirm(ConfirmAction::End),
+        SettingsField::Confirm(ConfirmAction::Retag),
+        SettingsField::Confirm(ConfirmAction::Restore),
+        SettingsField::Confirm(ConfirmAction::Quit),
+        SettingsField::AutoStopAsk,
+        SettingsField::MinDuration,
+        SettingsField::RoundingMode,
+        SettingsField::RoundingMinutes,
+        SettingsField::WorkingHoursStart,
+        SettingsField::WorkingHoursEnd,
+    ];
+
+    #[must_use]
+    pub fn next(self) -> Self
+    {
+        let index = Self::ALL.iter().position(|field| *field == self).unwrap_or_default();
+
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    #[must_use]
+    pub fn previous(self) -> Self
+    {
+        let index = Self::ALL.iter().position(|field| *field == self).unwrap_or_default();
+
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    #[must_use]
+    pub fn get_label(self) -> &'static str
+    {
+        match self
+        {
+            SettingsField::Theme => "THEME",
+            SettingsField::Clock => "CLOCK",
+            SettingsField::DateOrder => "DATE ORDER",
+            SettingsField::Dates => "DATES",
+            SettingsField::Seconds => "SECONDS",
+            SettingsField::WeekStart => "WEEK START",
+            SettingsField::Confirm(action) => match action
+            {
+                ConfirmAction::Delete => "ASK TO DELETE",
+                ConfirmAction::Discard => "ASK TO DISCARD",
+                ConfirmAction::End => "ASK TO END",
+                ConfirmAction::Retag => "ASK TO RETAG",
+                ConfirmAction::Restore => "ASK TO RESTORE",
+                ConfirmAction::Quit => "ASK TO QUIT",
+            },
+            SettingsField::AutoStopAsk => "ASK TO AUTO STOP",
+            SettingsField::MinDuration => "ASK TO DISCARD UNDER",
+            SettingsField::RoundingMode => "ROUNDING",
+            SettingsField::RoundingMinutes => "ROUND TO MINUTES",
+            SettingsField::WorkingHoursStart => "WORKDAY START",
+            SettingsField::WorkingHoursEnd => "WORKDAY END",
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ExportState
+{
+    Form(ExportField),
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ExportField
+{
+    From,
+    To,
+    Path,
+}
+
+impl ExportField
+{
+    #[must_use]
+    pub fn next(self) -> Self
+    {
+        match self
+        {
+            ExportField::From => ExportField::To,
+            ExportField::To => ExportField::Path,
+            ExportField::Path => ExportField::From,
+        }
+    }
+
+    #[must_use]
+    pub fn previous(self) -> Self
+    {
+        match self
+        {
+            ExportField::From => ExportField::Path,
+            ExportField::To => ExportField::From,
+            ExportField::Path => ExportField::To,
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum OverlapOrigin
+{
+    Add,
+    Edit,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum StatsPeriod
+{
+    Day,
+    Week,
+    Month,
+    Year,
+    All,
+}
+
+impl StatsPeriod
+{
+    #[must_use]
+    pub fn next(self) -> Self
+    {
+        match self
+        {
+            StatsPeriod::Day => StatsPeriod::Week,
+            StatsPeriod::Week => StatsPeriod::Month,
+            StatsPeriod::Month => StatsPeriod::Year,
+            StatsPeriod::Year => StatsPeriod::All,
+            StatsPeriod::All => StatsPeriod::Day,
+        }
+    }
+
+    #[must_use]
+    pub fn previous(self) -> Self
+    {
+        match self
+        {
+            StatsPeriod::Day => StatsPeriod::All,
+            StatsPeriod::Week => StatsPeriod::Day,
+            StatsPeriod::Month => StatsPeriod::Week,
+            StatsPeriod::Year => StatsPeriod::Month,
+            StatsPeriod::All => StatsPeriod::Year,
+        }
+    }
+}
+
+/// A row of the session list: either a day header or a session, newest first.
+#[derive(PartialEq, Copy, Clone)]
+pub enum ListRow
+{
+    Day
+    {
+        date: NaiveDate,
+        total: TimeDelta,
+        collapsed: bool,
+        latest_session_index: usize,
+    },
+    Session(usize),
+}
+
+/// Session list columns that can be sorted by clicking their header. Sorting happens within each day.
+#[derive(PartialEq, Copy, Clone)]
+pub enum SortColumn
+{
+    Description,
+    Tag,
+    Project,
+    Start,
+    End,
+    Duration,
+}
+
+/// Session list columns that can be shown, hidden and reordered through the config.
+#[derive(PartialEq, Copy, Clone)]
+pub enum ListColumn
+{
+    Date,
+    Description,
+    Tag,
+    Project,
+    Start,
+    End,
+    Duration,
+}
+
+impl ListColumn
+{
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self>
+    {
+        match name
+        {
+            "date" => Some(ListColumn::Date),
+            "description" => Some(ListColumn::Description),
+            "tag" => Some(ListColumn::Tag),
+            "project" => Some(ListColumn::Project),
+            "start" => Some(ListColumn::Start),
+            "end" => Some(ListColumn::End),
+            "duration" => Some(ListColumn::Duration),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn get_title(self) -> &'static str
+    {
+        match self
+        {
+            ListColumn::Date => "Date",
+            ListColumn::Description => "Description",
+            ListColumn::Tag => "Tag",
+            ListColumn::Project => "Project",
+            ListColumn::Start => "Start",
+            ListColumn::End => "End",
+            ListColumn::Duration => "Duration",
+        }
+    }
+
+    #[must_use]
+    pub fn get_sort_column(self) -> Option<SortColumn>
+    {
+        match self
+        {
+            ListColumn::Date => None,
+            ListColumn::Description => Some(SortColumn::Description),
+            ListColumn::Tag => Some(SortColumn::Tag),
+            ListColumn::Project => Some(SortColumn::Project),
+            ListColumn::Start => Some(SortColumn::Start),
+            ListColumn::End => Some(SortColumn::End),
+            ListColumn::Duration => Some(SortColumn::Duration),
+        }
+    }
+
+    /// Index of the editable session field shown in this column, matching `get_selected_session_field_index`.
+    #[must_use]
+    pub fn get_session_field_index(self) -> Option<usize>
+    {
+        match self
+        {
+            ListColumn::Date => Some(0),
+            ListColumn::Description => Some(1),
+            ListColumn::Tag => Some(2),
+            ListColumn::Start => Some(3),
+            ListColumn::End => Some(4),
+            ListColumn::Project | ListColumn::Duration => None,
+        }
+    }
+}
+
+/// Picking a workspace to switch to or naming a new one.
+#[derive(PartialEq, Copy, Clone)]
+pub enum WorkspaceState
+{
+    Browse,
+    New,
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum ConfirmOpen
 {
@@ -67,8 +542,34 @@ pub enum ConfirmOpen
     No,
 }
 
+impl CommandState
+{
+    /// States where printable keys are typed into a text field instead of triggering commands.
+    #[must_use]
+    pub fn is_text_input(&self) -> bool
+    {
+        matches!(
+            self,
+            CommandState::New(
+                SessionInputState::Description(ConfirmOpen::No)
+                    | SessionInputState::Tag(TagInputState::Select | TagInputState::New)
+                    | SessionInputState::Start
+                    | SessionInputState::Timer
+            ) | CommandState::Add(_)
+                | CommandState::EditRunning(RunningEditField::Description)
+                | CommandState::Workspaces(WorkspaceState::New)
+                | CommandState::Import(ImportState::Path)
+                | CommandState::Export(ExportState::Form(_))
+                | CommandState::Modify(SessionModifyState::Edit(
+                    SessionEditState::EditFields(SessionFieldEditState::Editing) | SessionEditState::Notes(ConfirmOpen::No)
+                ))
+        )
+    }
+}
+
 impl Display for CommandState
 {
+    #[allow(clippy::too_many_lines)]
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result
     {
         match self
@@ -81,6 +582,82 @@ impl Display for CommandState
             {
                 write!(f, "Input: {}", input_field)
             }
+            CommandState::Add(_) =>
+            {
+                write!(f, "Add")
+            }
+            CommandState::EditRunning(_) =>
+            {
+                write!(f, "Edit Running")
+            }
+            CommandState::Overlap(_) =>
+            {
+                write!(f, "Overlap")
+            }
+            CommandState::Backups(_) =>
+            {
+                write!(f, "Backups")
+            }
+            CommandState::AuditLog =>
+            {
+                write!(f, "Audit Log")
+            }
+            CommandState::Workspaces(_) =>
+            {
+                write!(f, "Workspaces")
+            }
+            CommandState::ImportReport =>
+            {
+                write!(f, "Import Report")
+            }
+            CommandState::Import(_) =>
+            {
+                write!(f, "Import")
+            }
+            CommandState::Export(_) =>
+            {
+                write!(f, "Export")
+            }
+            CommandState::Calendar =>
+            {
+                write!(f, "Calendar")
+            }
+            CommandState::Stats =>
+            {
+                write!(f, "Stats")
+            }
+            CommandState::DatabaseStats =>
+            {
+                write!(f, "Database")
+            }
+            CommandState::Goals =>
+            {
+                write!(f, "Goals")
+            }
+            CommandState::Templates =>
+            {
+                write!(f, "Templates")
+            }
+            CommandState::WeekReport =>
+            {
+                write!(f, "Week Report")
+            }
+            CommandState::Focus =>
+            {
+                write!(f, "Focus")
+            }
+            CommandState::Gaps =>
+            {
+                write!(f, "Gaps")
+            }
+            CommandState::Settings(_) =>
+            {
+                write!(f, "Settings")
+            }
+            CommandState::Locked =>
+            {
+                write!(f, "Locked")
+            }
             CommandState::Modify(_) =>
             {
                 write!(f, "Delete")
@@ -89,6 +666,22 @@ impl Display for CommandState
             {
                 write!(f, "End")
             }
+            CommandState::AutoStop =>
+            {
+                write!(f, "Auto Stop")
+            }
+            CommandState::DiscardShort =>
+            {
+                write!(f, "Discard Short")
+            }
+            CommandState::Discard =>
+            {
+                write!(f, "Discard")
+            }
+            CommandState::RestoreEdit =>
+            {
+                write!(f, "Restore Edit")
+            }
             CommandState::Quitting =>
             {
                 write!(f, "Quitting")
@@ -110,6 +703,14 @@ impl Display for SessionInputState
             {
                 write!(f, "Tag: {}", tag_edit_state)
             }
+            SessionInputState::Start =>
+            {
+                write!(f, "Start")
+            }
+            SessionInputState::Timer =>
+            {
+                write!(f, "Timer")
+            }
         }
     }
 }
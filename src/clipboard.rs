@@ -0,0 +1,54 @@
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Puts `text` on the system clipboard through pbcopy on macOS, clip on Windows and wl-copy, xclip or xsel elsewhere,
+/// whichever is installed first.
+///
+/// # Errors
+///
+/// If none of the tools is installed or the one found fails.
+pub fn copy_to_clipboard(text: &str) -> Result<(), String>
+{
+    let tools: Vec<(&str, &[&str])> = if cfg!(target_os = "macos")
+    {
+        vec![("pbcopy", &[])]
+    }
+    else if cfg!(windows)
+    {
+        vec![("clip", &[])]
+    }
+    else
+    {
+        let mut tools: Vec<(&str, &[&str])> = vec![("xclip", &["-selection", "clipboard"]), ("xsel", &["--clipboard", "--input"])];
+
+        if env::var_os("WAYLAND_DISPLAY").is_some()
+        {
+            tools.insert(0, ("wl-copy", &[]));
+        }
+
+        tools
+    };
+
+    for (program, args) in tools
+    {
+        let Ok(mut child) = Command::new(program).args(args).stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).spawn()
+        else
+        {
+            continue;
+        };
+
+        if let Some(mut stdin) = child.stdin.take()
+        {
+            let _ = stdin.write_all(text.as_bytes());
+        }
+
+        return match child.wait()
+        {
+            Ok(status) if status.success() => Ok(()),
+            _ => Err(String::from("CLIPBOARD TOOL FAILED")),
+        };
+    }
+
+    Err(String::from("NO CLIPBOARD TOOL FOUND"))
+}
@@ -0,0 +1,59 @@
+use chrono::{Local, NaiveDateTime, TimeDelta, Timelike};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Where the app gets the current time from.
+pub trait Clock
+{
+    /// Local time, to the second as sessions are stored.
+    fn now(&self) -> NaiveDateTime;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock
+{
+    fn now(&self) -> NaiveDateTime
+    {
+        let now = Local::now().naive_local();
+
+        now.with_nanosecond(0).unwrap_or(now)
+    }
+}
+
+/// Stands still at the time it is set to, for tests. Clones share the time, one can be kept to move the clock of the
+/// app owning the other.
+#[derive(Clone)]
+pub struct MockClock
+{
+    now: Rc<Cell<NaiveDateTime>>,
+}
+
+impl MockClock
+{
+    #[must_use]
+    pub fn new(now: NaiveDateTime) -> Self
+    {
+        MockClock {
+            now: Rc::new(Cell::new(now)),
+        }
+    }
+
+    pub fn set(&self, now: NaiveDateTime)
+    {
+        self.now.set(now);
+    }
+
+    pub fn advance(&self, duration: TimeDelta)
+    {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for MockClock
+{
+    fn now(&self) -> NaiveDateTime
+    {
+        self.now.get()
+    }
+}
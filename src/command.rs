@@ -0,0 +1,195 @@
+//! The commands changing sessions from outside the app, `add`, `amend`, `start` and `stop`, shared by the command line
+//! and `batch`.
+
+use crate::app_manager::AppManager;
+use crate::exit_code;
+use crate::session::Session;
+use crate::time_input::parse_date_input;
+
+/// Writes are skipped while another instance has the database open, which commands would not show otherwise.
+pub const READ_ONLY_MESSAGE: &str = "The workspace is open in another instance, nothing was changed.";
+
+/// Runs a command changing sessions, `add`, `amend` and `stop` as given on the command line, `start` only in a batch
+/// that also stops it, since a session started there ends with the process. `arguments` start with the command name. Returns the changed
+/// session, or the message and exit code to fail with, in which case nothing is stored.
+///
+/// # Errors
+///
+/// With an exit code of `exit_code`, e.g. `USAGE` for an invalid command line.
+pub fn run_session_command(app_manager: &mut AppManager, arguments: &[String]) -> Result<Session, (String, i32)>
+{
+    let command = arguments.first().map_or("", String::as_str);
+    let description = arguments.get(1).filter(|description| !description.starts_with('-')).cloned();
+    let [tag, date, start, end] = ["--tag", "--date", "--start", "--end"].map(|name| get_option(arguments, name));
+
+    if !matches!(command, "add" | "amend" | "start" | "stop")
+    {
+        return Err((format!("Unknown command {command}, a batch takes start, stop, add and amend."), exit_code::USAGE));
+    }
+
+    // A running daemon stores the sessions it runs itself, the app has its running session to itself.
+    if app_manager.is_read_only() && !(app_manager.daemon_connected && matches!(command, "start" | "stop"))
+    {
+        return Err((READ_ONLY_MESSAGE.to_string(), exit_code::DATABASE_ERROR));
+    }
+
+    let session = match command
+    {
+        "add" =>
+        {
+            let Some(description) = description
+            else
+            {
+                return Err((
+                    "add needs a description, e.g. time-tracker add \"Review\" --start 09:00 --end 10:30.".to_string(),
+                    exit_code::USAGE,
+                ));
+            };
+
+            let today = app_manager.get_today();
+            let Some(date) = date.map_or(Some(today), |date| parse_date_input(&date, today))
+            else
+            {
+                return Err(("--date needs today, yesterday or a date like 2026-01-31.".to_string(), exit_code::USAGE));
+            };
+
+            app_manager
+                .add_session(&description, tag.as_deref(), date, &start.unwrap_or_default(), &end.unwrap_or_default())
+                .map_err(|error| (format!("Not added: {error}"), exit_code::FAILURE))?
+        }
+        "amend" =>
+        {
+            let description = description.filter(|description| !description.is_empty());
+
+            if description.is_none() && tag.is_none() && end.is_none()
+            {
+                return Err(("amend needs a description, --tag or --end.".to_string(), exit_code::USAGE));
+            }
+
+            app_manager
+                .amend_last_session(description.as_deref(), tag.as_deref(), end.as_deref())
+                .map_err(|error| (format!("Not amended: {error}"), exit_code::FAILURE))?
+        }
+        "start" =>
+        {
+            let Some(description) = description
+            else
+            {
+                return Err(("start needs a description, e.g. start \"Review\" --tag work.".to_string(), exit_code::USAGE));
+            };
+
+            app_manager
+                .start_session(&description, tag.as_deref(), start.as_deref())
+                .map_err(|error| (format!("Not started: {error}"), exit_code::FAILURE))?
+        }
+        _ =>
+        {
+            let Some(running_session) = app_manager.sessions.last().filter(|session| session.is_running()).cloned()
+            else
+            {
+                return Err(("No session is running.".to_string(), exit_code::NOTHING_TO_STOP));
+            };
+
+            app_manager.end_running_session();
+            app_manager.export_running_session_changes();
+
+            app_manager
+                .sessions
+                .iter()
+                .rev()
+                .find(|session| session.start == running_session.start)
+                .cloned()
+                .unwrap_or(running_session)
+        }
+    };
+
+    Ok(session)
+}
+
+/// The argument following `name` among `arguments`, like `get_argument_value` for a command read from a batch.
+fn get_option(arguments: &[String], name: &str) -> Option<String>
+{
+    let index = arguments.iter().position(|argument| argument == name)?;
+
+    Some(arguments.get(index + 1).cloned().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::database_handler::{DatabaseHandler, DATE_FORMAT};
+    use crate::tui::io::{BufferBackend, Out, Vector2};
+    use chrono::NaiveDateTime;
+
+    /// The app at 18:00 on Wednesday 14 January 2026, on an empty in-memory workspace.
+    fn open_app(workspace: &str) -> AppManager
+    {
+        let clock = MockClock::new(NaiveDateTime::parse_from_str("14-01-2026 18:00:00", DATE_FORMAT).expect("Invalid test time."));
+        let renderer = Out::with_backend(Box::new(BufferBackend::new(Vector2::new(100, 30))));
+
+        AppManager::with_parts(DatabaseHandler::new_ephemeral(workspace), None, renderer, Box::new(clock))
+    }
+
+    fn run(app_manager: &mut AppManager, line: &[&str]) -> Result<Session, (String, i32)>
+    {
+        run_session_command(app_manager, &line.iter().map(ToString::to_string).collect::<Vec<String>>())
+    }
+
+    #[test]
+    fn added_sessions_can_be_amended()
+    {
+        let mut app_manager = open_app("command-add");
+
+        let line = ["add", "Review", "--tag", "work", "--date", "yesterday", "--start", "09:00", "--end", "10:30"];
+
+        let session = run(&mut app_manager, &line).expect("Adding failed.");
+        assert_eq!(session.start, NaiveDateTime::parse_from_str("13-01-2026 09:00:00", DATE_FORMAT).expect("Invalid test time."));
+
+        let session = run(&mut app_manager, &["amend", "--tag", "meeting"]).expect("Amending failed.");
+        assert_eq!((session.description.as_str(), session.tag.as_str()), ("Review", "meeting"));
+        assert_eq!(app_manager.sessions.len(), 1);
+    }
+
+    #[test]
+    fn invalid_command_lines_are_usage_errors()
+    {
+        let mut app_manager = open_app("command-usage");
+
+        for line in [&["remove", "Review"][..], &["add", "--start", "09:00"], &["add", "Review", "--date", "someday"], &["amend"]]
+        {
+            let Err((_, exit_code)) = run(&mut app_manager, line)
+            else
+            {
+                panic!("{line:?} should have failed.");
+            };
+
+            assert_eq!(exit_code, exit_code::USAGE, "{line:?}");
+        }
+
+        assert!(app_manager.sessions.is_empty());
+    }
+
+    #[test]
+    fn stop_without_a_running_session_fails()
+    {
+        let mut app_manager = open_app("command-stop");
+
+        assert_eq!(run(&mut app_manager, &["stop"]).map_err(|(_, exit_code)| exit_code).err(), Some(exit_code::NOTHING_TO_STOP));
+
+        run(&mut app_manager, &["start", "Review", "--tag", "work", "--start", "17:00"]).expect("Starting failed.");
+        let session = run(&mut app_manager, &["stop"]).expect("Stopping failed.");
+        assert_eq!((session.description.as_str(), session.end), ("Review", Some(app_manager.get_current_time())));
+    }
+
+    #[test]
+    fn options_take_the_argument_after_them()
+    {
+        let arguments = ["add", "Review", "--tag", "work", "--end"].map(String::from);
+
+        assert_eq!(get_option(&arguments, "--tag").as_deref(), Some("work"));
+        assert_eq!(get_option(&arguments, "--end").as_deref(), Some(""));
+        assert_eq!(get_option(&arguments, "--start"), None);
+    }
+}
@@ -0,0 +1,1258 @@
+use crate::app_state::ListColumn;
+use crate::exporter::DEFAULT_DAILY_NOTE_TEMPLATE;
+use crate::hooks::Hooks;
+use crate::issues::{IssueProvider, IssueTracker};
+use crate::time_input::{parse_duration, parse_time};
+use crate::toml::{get_table, parse_toml, TomlValue};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{NaiveDate, NaiveTime, TimeDelta, Weekday};
+use log::LevelFilter;
+use std::fs;
+use std::path::Path;
+
+const DEFAULT_TERMINAL_TITLE: &str = "{elapsed} {description}";
+
+/// Theme used until another one is picked in the settings.
+pub const DEFAULT_THEME_NAME: &str = "dark";
+
+const DEFAULT_CONFIG: &str = r#"# time-tracker configuration
+# A workspace can override any of these settings with its own config.toml in database/workspaces/<name>.
+
+[appearance]
+# "dark", "light" or the name of a .toml file in the database themes folder.
+theme = "dark"
+# Draws frames with plain ASCII characters instead of box drawing characters.
+ascii = false
+# "24h" or "12h" for times like 09:30 PM. Only changes what is shown, the sessions files keep 24 hour times.
+clock = "24h"
+# Order of day, month and year in shown dates, "dmy", "mdy" or "ymd".
+date_order = "dmy"
+# Terminal window title while a session runs, to see the timer in a background tab or the taskbar. {elapsed} is how
+# long it has been running, {description} and {tag} are those of the session. Leave empty to keep the title as it is.
+terminal_title = "{elapsed} {description}"
+
+[columns]
+# Session list columns from left to right. Leave out the ones you don't need, description is required.
+# "project" shows the part of the tag before the first ":", e.g. "acme" for "acme:meetings".
+visible = ["date", "description", "tag", "start", "end", "duration"]
+# Shows seconds in the start, end and duration columns.
+seconds = true
+# How the date column shows dates: "absolute", "relative" for "Today" and "Yesterday" instead of their date, or
+# "weekdays" to also show the weekday for the other days of the current week.
+dates = "absolute"
+
+[tags]
+# Tag selected for new sessions until another one is used, e.g. "work". Created if it does not exist yet.
+default = ""
+
+[sessions]
+# Ending a session shorter than this asks whether to discard it instead, e.g. "60s". Leave empty to always keep it.
+min_duration = "1m"
+
+[confirmations]
+# Actions asking before they are done. Set one to false to do it right away, e.g. for "d" and Enter to delete the
+# selected session. Answering a question with "Y" sets it to false as well.
+# Deleting a session or the marked ones.
+delete = true
+# Discarding the running session.
+discard = true
+# Ending the running session.
+end = true
+# Retagging the marked sessions.
+retag = true
+# Restoring a backup.
+restore = true
+# Quitting, which ends the running session unless the daemon keeps it.
+quit = true
+
+[goals]
+# Hour targets per tag, per day or per week, e.g.
+# "project-x" = "20h/week"
+# work = "6h/day"
+
+[templates]
+# Sessions started with one key from the templates popup ([T]), as "description / tag" or with the time it is meant to
+# take, "description / tag / duration". Picking the tag of a template for a new session without a description fills
+# in its description. E.g.
+# standup = "Daily standup / meetings / 15m"
+
+[working_hours]
+# Used by the gap report to find untracked time, from Monday to Friday unless [schedule] says otherwise.
+start = "09:00"
+end = "18:00"
+# Ends a session still running at this time of day, as of that time, e.g. "20:00". Leave empty to let sessions run.
+auto_stop = ""
+# Asks once before ending it instead.
+auto_stop_ask = false
+# Keeps an overtime balance of the time tracked beyond the [schedule] from this day on, e.g. "2026-01-01", shown in the
+# week report and `report`. Leave empty to keep none.
+balance_start = ""
+
+[schedule]
+# Working hours per weekday, used by the gap report, the idle reminder and goals. A day is worked for a duration from
+# the working hours start like "6h", between two times like "08:00-14:00", or is "off". Days left out are worked during
+# the working hours from Monday to Friday and off on weekends, e.g.
+# friday = "6h"
+# saturday = "10:00-14:00"
+
+[calendar]
+# First day of the week in the calendar, the week report, weekly goals and stats, "monday" or "sunday".
+week_start = "monday"
+# "iso" shows ISO 8601 week numbers in the calendar and the week report, "none" leaves them out.
+week_numbers = "none"
+
+[rounding]
+# Rounds every session in reports and exports, the stored sessions stay untouched.
+# mode is "none", "nearest", "up" (for billing) or "down".
+mode = "none"
+minutes = 15
+
+[sync]
+# Keeps the sessions in a git repository in the workspace folder and merges them with this remote on start and quit,
+# e.g. "git@example.com:me/time-tracking.git". Leave empty to keep them on this machine only. The remote has to be
+# reachable without a password prompt, through an ssh agent or a credential helper.
+remote = ""
+# Named workspaces use "<branch>-<workspace>".
+branch = "main"
+
+[remote]
+# Keeps the sessions on a self-hosted server that is the source of truth for all machines, e.g.
+# "https://time.example.com/api". Leave empty to keep them on this machine only. Talks to the server through curl:
+# GET <url>/sessions lists the sessions on start, one per line as in the sessions files, POST <url>/sessions creates
+# one and PUT or DELETE <url>/sessions/<id> update and delete one. Named workspaces use
+# "<url>/workspaces/<workspace>/sessions". Changes the server doesn't accept are sent again later.
+url = ""
+# Sent as "Authorization: Bearer <token>", leave empty to send none.
+token = ""
+
+[toggl]
+# Sends the sessions of the last two weeks to Toggl Track as time entries and brings in the entries made there, on
+# start and quit. The API token is on the Toggl profile page. Leave empty to not use Toggl.
+token = ""
+# The Toggl workspace, 0 for the default one of the account.
+workspace_id = 0
+
+[toggl_projects]
+# Toggl project ids per tag, e.g.
+# "client:acme" = 123456789
+# Entries of other projects get their first Toggl tag as tag, or "toggl" if they have none.
+
+[issues]
+# Descriptions can refer to an issue by its address, "owner/repo#12", or a bare #12 for an issue of the repository
+# below. The link goes into exports and reports.
+# provider is "github" or "gitlab".
+provider = "github"
+# Address of a self-hosted instance, e.g. "https://gitlab.example.com". Leave empty for github.com or gitlab.com.
+url = ""
+repository = ""
+# With a token the open issues assigned to you are suggested while typing a description.
+token = ""
+
+[notifications]
+# Desktop notifications through notify-send (osascript on macOS). Leave a duration empty to not be notified.
+# When a session has been running this long, e.g. "2h".
+long_session = ""
+# When no session has been running for this long during working hours, e.g. "30m".
+idle = ""
+
+[activitywatch]
+# Shows the time ActivityWatch saw you at the computer next to the gaps in the untracked time view, e.g.
+# "http://localhost:5600". Leave empty to not ask ActivityWatch.
+url = ""
+# Active stretches at least this long are offered as sessions, named after the window used the most.
+min_block = "15m"
+
+[hooks]
+# Shell commands run when a session starts or ends, e.g. "notify-send \"$TT_DESCRIPTION\"". They get the session in
+# the TT_EVENT, TT_DESCRIPTION, TT_TAG, TT_START, TT_END and TT_DURATION (in seconds) environment variables.
+start = ""
+end = ""
+# Receives both events as a JSON POST, e.g. {"event": "start", "session": {"description": ...}}.
+url = ""
+
+[git]
+# Suggests the branch checked out in this project folder as the description of new sessions, e.g. "~/code/app".
+# Leave empty to not look for a branch.
+directory = ""
+# Tag suggested with the branch, e.g. "dev". Leave empty to keep the selected tag.
+tag = ""
+
+[obsidian]
+# The daily note each day of the stats view is written to with [x], with the date as in strftime, e.g.
+# "~/vault/Daily/%Y-%m-%d.md". Leave empty to not write daily notes.
+daily_note = ""
+# What goes into the note. {sessions} is a table of the day's sessions, {tags} a list of the time per tag, {total}
+# the time of the whole day and {date} the day. Writing the same day again replaces what was written before.
+template = "**Time tracked**\n\n{sessions}\n\n{tags}\n\nTotal: **{total}**"
+
+[logging]
+# What goes into time-tracker.log in the database folder and the debug overlay (F12): "off", "error", "warn", "info",
+# "debug" or "trace".
+level = "info"
+"#;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum GoalPeriod
+{
+    Day,
+    Week,
+}
+
+#[derive(Clone)]
+pub struct Goal
+{
+    pub tag: String,
+    pub target: TimeDelta,
+    pub period: GoalPeriod,
+}
+
+/// A session that can be started as it is, named by its key in `[templates]`.
+#[derive(Clone)]
+pub struct SessionTemplate
+{
+    pub name: String,
+    pub description: String,
+    pub tag: String,
+    /// Set as the timer of the session.
+    pub target: Option<TimeDelta>,
+}
+
+/// How a weekday is worked according to `[schedule]`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum WorkingDay
+{
+    Off,
+    /// Worked for this long from the start of the working hours.
+    Hours(TimeDelta),
+    Span(NaiveTime, NaiveTime),
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum WeekNumbers
+{
+    None,
+    /// ISO 8601 weeks, starting on Monday with week 1 holding the year's first Thursday.
+    Iso,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ClockFormat
+{
+    TwentyFourHour,
+    /// With AM and PM.
+    TwelveHour,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum DateOrder
+{
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum DateLabels
+{
+    Absolute,
+    /// "Today" and "Yesterday".
+    Relative,
+    /// Like `Relative`, with the weekday for the other days of the current week.
+    Weekdays,
+}
+
+/// Actions asking before they are done, unless turned off in `[confirmations]`.
+#[derive(PartialEq, Copy, Clone)]
+pub enum ConfirmAction
+{
+    Delete,
+    Discard,
+    End,
+    Retag,
+    Restore,
+    Quit,
+}
+
+impl ConfirmAction
+{
+    pub const ALL: [ConfirmAction; 6] = [
+        ConfirmAction::Delete,
+        ConfirmAction::Discard,
+        ConfirmAction::End,
+        ConfirmAction::Retag,
+        ConfirmAction::Restore,
+        ConfirmAction::Quit,
+    ];
+
+    /// The key of the action in `[confirmations]`.
+    #[must_use]
+    pub fn get_key(self) -> &'static str
+    {
+        match self
+        {
+            ConfirmAction::Delete => "delete",
+            ConfirmAction::Discard => "discard",
+            ConfirmAction::End => "end",
+            ConfirmAction::Retag => "retag",
+            ConfirmAction::Restore => "restore",
+            ConfirmAction::Quit => "quit",
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum RoundingMode
+{
+    None,
+    Nearest,
+    Up,
+    Down,
+}
+
+#[derive(Copy, Clone)]
+pub struct Rounding
+{
+    pub mode: RoundingMode,
+    pub minutes: i64,
+}
+
+impl Rounding
+{
+    /// Rounds a session duration to a multiple of `minutes`.
+    #[must_use]
+    pub fn apply(&self, duration: TimeDelta) -> TimeDelta
+    {
+        let step = self.minutes * 60;
+
+        if self.mode == RoundingMode::None || step <= 0
+        {
+            return duration;
+        }
+
+        let seconds = duration.num_seconds();
+        let rounded = match self.mode
+        {
+            RoundingMode::Nearest => (seconds + step / 2) / step * step,
+            RoundingMode::Up => (seconds + step - 1) / step * step,
+            RoundingMode::Down | RoundingMode::None => seconds / step * step,
+        };
+
+        TimeDelta::seconds(rounded)
+    }
+}
+
+#[derive(Clone)]
+pub struct Config
+{
+    pub goals: Vec<Goal>,
+    pub templates: Vec<SessionTemplate>,
+    pub working_hours_start: NaiveTime,
+    pub working_hours_end: NaiveTime,
+    /// Indexed by the days from Monday, `None` for weekdays left out of `[schedule]`.
+    pub schedule: [Option<WorkingDay>; 7],
+    pub auto_stop: Option<NaiveTime>,
+    pub auto_stop_ask: bool,
+    /// First day counted towards the overtime balance, `None` to keep no balance.
+    pub balance_start: Option<NaiveDate>,
+    pub week_start: Weekday,
+    pub week_numbers: WeekNumbers,
+    pub rounding: Rounding,
+    pub theme: String,
+    pub ascii: bool,
+    pub clock_format: ClockFormat,
+    pub date_order: DateOrder,
+    /// `None` leaves the terminal title alone.
+    pub terminal_title: Option<String>,
+    pub columns: Vec<ListColumn>,
+    pub show_seconds: bool,
+    pub date_labels: DateLabels,
+    pub default_tag: Option<String>,
+    pub min_session_duration: Option<TimeDelta>,
+    /// Actions done without asking first.
+    pub unconfirmed_actions: Vec<ConfirmAction>,
+    pub sync_remote: Option<String>,
+    pub sync_branch: String,
+    pub remote_url: Option<String>,
+    pub remote_token: Option<String>,
+    pub toggl_token: Option<String>,
+    pub toggl_workspace_id: Option<u64>,
+    pub toggl_projects: Vec<(String, u64)>,
+    pub issues_provider: IssueProvider,
+    pub issues_url: Option<String>,
+    pub issues_repository: Option<String>,
+    pub issues_token: Option<String>,
+    pub notify_long_session: Option<TimeDelta>,
+    pub notify_idle: Option<TimeDelta>,
+    pub activitywatch_url: Option<String>,
+    pub activitywatch_min_block: TimeDelta,
+    pub hook_start_command: Option<String>,
+    pub hook_end_command: Option<String>,
+    pub hook_url: Option<String>,
+    pub git_directory: Option<String>,
+    pub git_tag: Option<String>,
+    pub obsidian_daily_note: Option<String>,
+    pub obsidian_template: String,
+    pub log_level: LevelFilter,
+    pub error: Option<String>,
+}
+
+impl Default for Config
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl Config
+{
+    #[must_use]
+    pub fn new() -> Self
+    {
+        Config {
+            goals: Vec::new(),
+            templates: Vec::new(),
+            working_hours_start: NaiveTime::from_hms_opt(9, 0, 0).unwrap_or_default(),
+            working_hours_end: NaiveTime::from_hms_opt(18, 0, 0).unwrap_or_default(),
+            schedule: [None; 7],
+            auto_stop: None,
+            auto_stop_ask: false,
+            balance_start: None,
+            week_start: Weekday::Mon,
+            week_numbers: WeekNumbers::None,
+            rounding: Rounding {
+                mode: RoundingMode::None,
+                minutes: 15,
+            },
+            theme: DEFAULT_THEME_NAME.to_string(),
+            ascii: false,
+            clock_format: ClockFormat::TwentyFourHour,
+            date_order: DateOrder::DayMonthYear,
+            terminal_title: Some(String::from(DEFAULT_TERMINAL_TITLE)),
+            columns: vec![
+                ListColumn::Date,
+                ListColumn::Description,
+                ListColumn::Tag,
+                ListColumn::Start,
+                ListColumn::End,
+                ListColumn::Duration,
+            ],
+            show_seconds: true,
+            date_labels: DateLabels::Absolute,
+            default_tag: None,
+            min_session_duration: Some(TimeDelta::minutes(1)),
+            unconfirmed_actions: Vec::new(),
+            sync_remote: None,
+            sync_branch: String::from("main"),
+            remote_url: None,
+            remote_token: None,
+            toggl_token: None,
+            toggl_workspace_id: None,
+            toggl_projects: Vec::new(),
+            issues_provider: IssueProvider::GitHub,
+            issues_url: None,
+            issues_repository: None,
+            issues_token: None,
+            notify_long_session: None,
+            notify_idle: None,
+            activitywatch_url: None,
+            activitywatch_min_block: TimeDelta::minutes(15),
+            hook_start_command: None,
+            hook_end_command: None,
+            hook_url: None,
+            git_directory: None,
+            git_tag: None,
+            obsidian_daily_note: None,
+            obsidian_template: String::from(DEFAULT_DAILY_NOTE_TEMPLATE),
+            log_level: LevelFilter::Info,
+            error: None,
+        }
+    }
+
+    /// True if `action` asks before it is done.
+    #[must_use]
+    pub fn asks_to(&self, action: ConfirmAction) -> bool
+    {
+        !self.unconfirmed_actions.contains(&action)
+    }
+
+    /// When `weekday` is worked, `None` if it is off. Weekdays left out of `[schedule]` are worked during the working
+    /// hours from Monday to Friday.
+    #[must_use]
+    pub fn get_working_hours(&self, weekday: Weekday) -> Option<(NaiveTime, NaiveTime)>
+    {
+        match self.schedule[weekday.num_days_from_monday() as usize]
+        {
+            None if weekday.number_from_monday() <= 5 => Some((self.working_hours_start, self.working_hours_end)),
+            None | Some(WorkingDay::Off) => None,
+            Some(WorkingDay::Hours(duration)) =>
+            {
+                let (end, wrapped) = self.working_hours_start.overflowing_add_signed(duration);
+                let end = if wrapped == 0
+                {
+                    end
+                }
+                else
+                {
+                    NaiveTime::from_hms_opt(23, 59, 59).unwrap_or_default()
+                };
+
+                Some((self.working_hours_start, end))
+            }
+            Some(WorkingDay::Span(start, end)) => Some((start, end)),
+        }
+    }
+
+    /// How long `weekday` is worked.
+    #[must_use]
+    pub fn get_scheduled_time(&self, weekday: Weekday) -> TimeDelta
+    {
+        match self.schedule[weekday.num_days_from_monday() as usize]
+        {
+            Some(WorkingDay::Hours(duration)) => duration,
+            _ => self.get_working_hours(weekday).map_or(TimeDelta::zero(), |(start, end)| end - start),
+        }
+    }
+
+    #[must_use]
+    pub fn get_hooks(&self) -> Hooks
+    {
+        Hooks {
+            start_command: self.hook_start_command.clone(),
+            end_command: self.hook_end_command.clone(),
+            url: self.hook_url.clone(),
+        }
+    }
+
+    #[must_use]
+    pub fn get_issue_tracker(&self) -> IssueTracker
+    {
+        IssueTracker {
+            provider: self.issues_provider,
+            url: self.issues_url.clone(),
+            repository: self.issues_repository.clone(),
+            token: self.issues_token.clone(),
+        }
+    }
+
+    /// Reads the config file, writing a commented template first if there is none, then layers the workspace config
+    /// over it when there is one. An invalid global file keeps the defaults, an invalid workspace file keeps the global
+    /// settings, and either reports the problem in `error`.
+    #[must_use]
+    pub fn load(path: &Path, workspace_path: Option<&Path>) -> Self
+    {
+        let mut config = Config::new();
+
+        if !path.exists()
+        {
+            fs::write(path, DEFAULT_CONFIG).ok();
+        }
+
+        let Ok(content) = fs::read_to_string(path)
+        else
+        {
+            return config;
+        };
+
+        if let Err(error) = config.apply(&content)
+        {
+            config = Config::new();
+            config.error = Some(error);
+
+            return config;
+        }
+
+        if let Some(workspace_content) = workspace_path.and_then(|workspace_path| fs::read_to_string(workspace_path).ok())
+            && let Err(error) = config.apply(&workspace_content)
+        {
+            config = Config::new();
+            config.apply(&content).ok();
+            config.error = Some(format!("WORKSPACE {error}"));
+        }
+
+        config
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn apply(&mut self, content: &str) -> Result<(), String>
+    {
+        let tables = parse_toml(content)?;
+
+        if let Some(appearance) = get_table(&tables, "appearance")
+        {
+            for (key, value) in &appearance.entries
+            {
+                match key.as_str()
+                {
+                    "theme" => self.theme = value.as_str().ok_or("INVALID THEME NAME".to_string())?.to_string(),
+                    "ascii" => self.ascii = value.as_bool().ok_or("INVALID ASCII SWITCH".to_string())?,
+                    "clock" =>
+                    {
+                        self.clock_format = match value.as_str()
+                        {
+                            Some("24h") => ClockFormat::TwentyFourHour,
+                            Some("12h") => ClockFormat::TwelveHour,
+                            _ => return Err("INVALID CLOCK".to_string()),
+                        };
+                    }
+                    "date_order" =>
+                    {
+                        self.date_order = match value.as_str()
+                        {
+                            Some("dmy") => DateOrder::DayMonthYear,
+                            Some("mdy") => DateOrder::MonthDayYear,
+                            Some("ymd") => DateOrder::YearMonthDay,
+                            _ => return Err("INVALID DATE ORDER".to_string()),
+                        };
+                    }
+                    "terminal_title" =>
+                    {
+                        let terminal_title = value.as_str().ok_or("INVALID TERMINAL TITLE".to_string())?;
+                        self.terminal_title = Some(terminal_title.to_string()).filter(|title| !title.trim().is_empty());
+                    }
+                    _ => return Err(format!("UNKNOWN APPEARANCE KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(columns) = get_table(&tables, "columns")
+        {
+            for (key, value) in &columns.entries
+            {
+                match key.as_str()
+                {
+                    "visible" => self.columns = parse_columns(value).ok_or("INVALID COLUMNS".to_string())?,
+                    "seconds" => self.show_seconds = value.as_bool().ok_or("INVALID SECONDS SWITCH".to_string())?,
+                    "dates" =>
+                    {
+                        self.date_labels = match value.as_str()
+                        {
+                            Some("absolute") => DateLabels::Absolute,
+                            Some("relative") => DateLabels::Relative,
+                            Some("weekdays") => DateLabels::Weekdays,
+                            _ => return Err("INVALID DATES MODE".to_string()),
+                        };
+                    }
+                    _ => return Err(format!("UNKNOWN COLUMNS KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(tags) = get_table(&tables, "tags")
+        {
+            for (key, value) in &tags.entries
+            {
+                match key.as_str()
+                {
+                    "default" =>
+                    {
+                        let default_tag = value.as_str().ok_or("INVALID DEFAULT TAG".to_string())?.trim();
+                        self.default_tag = Some(default_tag.to_string()).filter(|tag| !tag.is_empty());
+                    }
+                    _ => return Err(format!("UNKNOWN TAGS KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(sessions) = get_table(&tables, "sessions")
+        {
+            for (key, value) in &sessions.entries
+            {
+                match key.as_str()
+                {
+                    "min_duration" =>
+                    {
+                        self.min_session_duration = match value.as_str().map(str::trim)
+                        {
+                            Some("") => None,
+                            min_duration => Some(min_duration.and_then(parse_duration).ok_or("INVALID MIN DURATION".to_string())?),
+                        };
+                    }
+                    _ => return Err(format!("UNKNOWN SESSIONS KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(confirmations) = get_table(&tables, "confirmations")
+        {
+            for (key, value) in &confirmations.entries
+            {
+                let Some(action) = ConfirmAction::ALL.into_iter().find(|action| action.get_key() == key)
+                else
+                {
+                    return Err(format!("UNKNOWN CONFIRMATIONS KEY {}", key.to_uppercase()));
+                };
+
+                let asks = value.as_bool().ok_or_else(|| format!("INVALID CONFIRMATION {}", key.to_uppercase()))?;
+
+                self.unconfirmed_actions.retain(|unconfirmed_action| *unconfirmed_action != action);
+
+                if !asks
+                {
+                    self.unconfirmed_actions.push(action);
+                }
+            }
+        }
+
+        if let Some(goals) = get_table(&tables, "goals")
+        {
+            // A workspace listing goals replaces the global ones instead of adding to them.
+            self.goals.clear();
+
+            for (tag, value) in &goals.entries
+            {
+                let goal = value.as_str().and_then(|value| parse_goal(tag, value));
+                self.goals.push(goal.ok_or_else(|| format!("INVALID GOAL FOR {}", tag.to_uppercase()))?);
+            }
+        }
+
+        if let Some(templates) = get_table(&tables, "templates")
+        {
+            // Replaced by a workspace like goals, a project has its own recurring sessions.
+            self.templates.clear();
+
+            for (name, value) in &templates.entries
+            {
+                let template = value.as_str().and_then(|value| parse_template(name, value));
+                self.templates.push(template.ok_or_else(|| format!("INVALID TEMPLATE {}", name.to_uppercase()))?);
+            }
+        }
+
+        if let Some(working_hours) = get_table(&tables, "working_hours")
+        {
+            for (key, value) in &working_hours.entries
+            {
+                let invalid_error = || format!("INVALID WORKING HOURS {}", key.to_uppercase().replace('_', " "));
+
+                match key.as_str()
+                {
+                    "start" => self.working_hours_start = value.as_str().and_then(parse_time).ok_or_else(invalid_error)?,
+                    "end" => self.working_hours_end = value.as_str().and_then(parse_time).ok_or_else(invalid_error)?,
+                    "auto_stop" =>
+                    {
+                        self.auto_stop = match value.as_str().map(str::trim)
+                        {
+                            Some("") => None,
+                            auto_stop => Some(auto_stop.and_then(parse_time).ok_or_else(invalid_error)?),
+                        };
+                    }
+                    "auto_stop_ask" => self.auto_stop_ask = value.as_bool().ok_or_else(invalid_error)?,
+                    "balance_start" =>
+                    {
+                        self.balance_start = match value.as_str().map(str::trim)
+                        {
+                            Some("") => None,
+                            balance_start => Some(
+                                balance_start
+                                    .and_then(|balance_start| NaiveDate::parse_from_str(balance_start, "%Y-%m-%d").ok())
+                                    .ok_or_else(invalid_error)?,
+                            ),
+                        };
+                    }
+                    _ => return Err(format!("UNKNOWN WORKING HOURS KEY {}", key.to_uppercase())),
+                }
+            }
+
+            if self.working_hours_end <= self.working_hours_start
+            {
+                return Err("WORKING HOURS END BEFORE START".to_string());
+            }
+        }
+
+        if let Some(schedule) = get_table(&tables, "schedule")
+        {
+            for (key, value) in &schedule.entries
+            {
+                let weekday = key.parse::<Weekday>().map_err(|_| format!("UNKNOWN SCHEDULE DAY {}", key.to_uppercase()))?;
+                let working_day = value.as_str().and_then(parse_working_day);
+
+                self.schedule[weekday.num_days_from_monday() as usize] =
+                    Some(working_day.ok_or_else(|| format!("INVALID SCHEDULE FOR {}", key.to_uppercase()))?);
+            }
+        }
+
+        if let Some(calendar) = get_table(&tables, "calendar")
+        {
+            for (key, value) in &calendar.entries
+            {
+                match key.as_str()
+                {
+                    "week_start" =>
+                    {
+                        self.week_start = match value.as_str()
+                        {
+                            Some("monday") => Weekday::Mon,
+                            Some("sunday") => Weekday::Sun,
+                            _ => return Err("INVALID WEEK START".to_string()),
+                        };
+                    }
+                    "week_numbers" =>
+                    {
+                        self.week_numbers = match value.as_str()
+                        {
+                            Some("none") => WeekNumbers::None,
+                            Some("iso") => WeekNumbers::Iso,
+                            _ => return Err("INVALID WEEK NUMBERS".to_string()),
+                        };
+                    }
+                    _ => return Err(format!("UNKNOWN CALENDAR KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(rounding) = get_table(&tables, "rounding")
+        {
+            for (key, value) in &rounding.entries
+            {
+                match key.as_str()
+                {
+                    "mode" =>
+                    {
+                        self.rounding.mode = match value.as_str()
+                        {
+                            Some("none") => RoundingMode::None,
+                            Some("nearest") => RoundingMode::Nearest,
+                            Some("up") => RoundingMode::Up,
+                            Some("down") => RoundingMode::Down,
+                            _ => return Err("INVALID ROUNDING MODE".to_string()),
+                        };
+                    }
+                    "minutes" =>
+                    {
+                        self.rounding.minutes =
+                            value.as_integer().filter(|minutes| *minutes > 0).ok_or("INVALID ROUNDING MINUTES".to_string())?;
+                    }
+                    _ => return Err(format!("UNKNOWN ROUNDING KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(sync) = get_table(&tables, "sync")
+        {
+            for (key, value) in &sync.entries
+            {
+                match key.as_str()
+                {
+                    "remote" =>
+                    {
+                        let remote = value.as_str().ok_or("INVALID SYNC REMOTE".to_string())?.trim();
+                        self.sync_remote = Some(remote.to_string()).filter(|remote| !remote.is_empty());
+                    }
+                    "branch" =>
+                    {
+                        let branch = value.as_str().map(str::trim).filter(|branch| !branch.is_empty());
+                        self.sync_branch = branch.ok_or("INVALID SYNC BRANCH".to_string())?.to_string();
+                    }
+                    _ => return Err(format!("UNKNOWN SYNC KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(remote) = get_table(&tables, "remote")
+        {
+            for (key, value) in &remote.entries
+            {
+                match key.as_str()
+                {
+                    "url" =>
+                    {
+                        let url = value
+                            .as_str()
+                            .map(str::trim)
+                            .filter(|url| url.is_empty() || url.starts_with("http://") || url.starts_with("https://"));
+                        self.remote_url = Some(url.ok_or("INVALID REMOTE URL".to_string())?.to_string()).filter(|url| !url.is_empty());
+                    }
+                    "token" =>
+                    {
+                        let token = value.as_str().ok_or("INVALID REMOTE TOKEN".to_string())?.trim();
+                        self.remote_token = Some(token.to_string()).filter(|token| !token.is_empty());
+                    }
+                    _ => return Err(format!("UNKNOWN REMOTE KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(toggl) = get_table(&tables, "toggl")
+        {
+            for (key, value) in &toggl.entries
+            {
+                match key.as_str()
+                {
+                    "token" =>
+                    {
+                        let token = value.as_str().ok_or("INVALID TOGGL TOKEN".to_string())?.trim();
+                        self.toggl_token = Some(token.to_string()).filter(|token| !token.is_empty());
+                    }
+                    "workspace_id" =>
+                    {
+                        let workspace_id = value.as_integer().and_then(|id| u64::try_from(id).ok());
+                        self.toggl_workspace_id =
+                            Some(workspace_id.ok_or("INVALID TOGGL WORKSPACE".to_string())?).filter(|id| *id > 0);
+                    }
+                    _ => return Err(format!("UNKNOWN TOGGL KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(toggl_projects) = get_table(&tables, "toggl_projects")
+        {
+            // A workspace mapping projects replaces the global mapping, like goals.
+            self.toggl_projects.clear();
+
+            for (tag, value) in &toggl_projects.entries
+            {
+                let project_id = value.as_integer().and_then(|id| u64::try_from(id).ok()).filter(|id| *id > 0);
+                self.toggl_projects
+                    .push((tag.clone(), project_id.ok_or_else(|| format!("INVALID TOGGL PROJECT FOR {}", tag.to_uppercase()))?));
+            }
+        }
+
+        if let Some(issues) = get_table(&tables, "issues")
+        {
+            for (key, value) in &issues.entries
+            {
+                match key.as_str()
+                {
+                    "provider" =>
+                    {
+                        self.issues_provider = match value.as_str()
+                        {
+                            Some("github") => IssueProvider::GitHub,
+                            Some("gitlab") => IssueProvider::GitLab,
+                            _ => return Err("INVALID ISSUES PROVIDER".to_string()),
+                        };
+                    }
+                    "url" =>
+                    {
+                        let url = value
+                            .as_str()
+                            .map(|url| url.trim().trim_end_matches('/'))
+                            .filter(|url| url.is_empty() || url.starts_with("http://") || url.starts_with("https://"));
+                        self.issues_url = Some(url.ok_or("INVALID ISSUES URL".to_string())?.to_string()).filter(|url| !url.is_empty());
+                    }
+                    "repository" =>
+                    {
+                        let repository =
+                            value.as_str().map(str::trim).filter(|repository| repository.is_empty() || repository.contains('/'));
+                        self.issues_repository = Some(repository.ok_or("INVALID ISSUES REPOSITORY".to_string())?.to_string())
+                            .filter(|repository| !repository.is_empty());
+                    }
+                    "token" =>
+                    {
+                        let token = value.as_str().ok_or("INVALID ISSUES TOKEN".to_string())?.trim();
+                        self.issues_token = Some(token.to_string()).filter(|token| !token.is_empty());
+                    }
+                    _ => return Err(format!("UNKNOWN ISSUES KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(notifications) = get_table(&tables, "notifications")
+        {
+            for (key, value) in &notifications.entries
+            {
+                let duration = value
+                    .as_str()
+                    .map(str::trim)
+                    .and_then(|duration| {
+                        if duration.is_empty()
+                        {
+                            Some(None)
+                        }
+                        else
+                        {
+                            parse_duration(duration).map(Some)
+                        }
+                    })
+                    .ok_or_else(|| format!("INVALID {} NOTIFICATION", key.to_uppercase().replace('_', " ")))?;
+
+                match key.as_str()
+                {
+                    "long_session" => self.notify_long_session = duration,
+                    "idle" => self.notify_idle = duration,
+                    _ => return Err(format!("UNKNOWN NOTIFICATIONS KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(activitywatch) = get_table(&tables, "activitywatch")
+        {
+            for (key, value) in &activitywatch.entries
+            {
+                match key.as_str()
+                {
+                    "url" =>
+                    {
+                        let url = value
+                            .as_str()
+                            .map(|url| url.trim().trim_end_matches('/'))
+                            .filter(|url| url.is_empty() || url.starts_with("http://") || url.starts_with("https://"));
+                        self.activitywatch_url =
+                            Some(url.ok_or("INVALID ACTIVITYWATCH URL".to_string())?.to_string()).filter(|url| !url.is_empty());
+                    }
+                    "min_block" =>
+                    {
+                        self.activitywatch_min_block = value
+                            .as_str()
+                            .and_then(|duration| parse_duration(duration.trim()))
+                            .ok_or("INVALID ACTIVITYWATCH MIN BLOCK".to_string())?;
+                    }
+                    _ => return Err(format!("UNKNOWN ACTIVITYWATCH KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(hooks) = get_table(&tables, "hooks")
+        {
+            for (key, value) in &hooks.entries
+            {
+                match key.as_str()
+                {
+                    "start" | "end" =>
+                    {
+                        let command = value.as_str().ok_or_else(|| format!("INVALID {} HOOK", key.to_uppercase()))?.trim().to_string();
+                        let command = Some(command).filter(|command| !command.is_empty());
+
+                        if key == "start"
+                        {
+                            self.hook_start_command = command;
+                        }
+                        else
+                        {
+                            self.hook_end_command = command;
+                        }
+                    }
+                    "url" =>
+                    {
+                        let url = value
+                            .as_str()
+                            .map(str::trim)
+                            .filter(|url| url.is_empty() || url.starts_with("http://") || url.starts_with("https://"));
+                        self.hook_url = Some(url.ok_or("INVALID HOOK URL".to_string())?.to_string()).filter(|url| !url.is_empty());
+                    }
+                    _ => return Err(format!("UNKNOWN HOOKS KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(git) = get_table(&tables, "git")
+        {
+            for (key, value) in &git.entries
+            {
+                match key.as_str()
+                {
+                    "directory" =>
+                    {
+                        let directory = value.as_str().ok_or("INVALID GIT DIRECTORY".to_string())?.trim();
+                        self.git_directory = Some(directory.to_string()).filter(|directory| !directory.is_empty());
+                    }
+                    "tag" =>
+                    {
+                        let tag = value.as_str().ok_or("INVALID GIT TAG".to_string())?.trim();
+                        self.git_tag = Some(tag.to_string()).filter(|tag| !tag.is_empty());
+                    }
+                    _ => return Err(format!("UNKNOWN GIT KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(obsidian) = get_table(&tables, "obsidian")
+        {
+            for (key, value) in &obsidian.entries
+            {
+                match key.as_str()
+                {
+                    "daily_note" =>
+                    {
+                        let daily_note = value
+                            .as_str()
+                            .map(str::trim)
+                            .filter(|daily_note| StrftimeItems::new(daily_note).all(|item| item != Item::Error));
+                        self.obsidian_daily_note = Some(daily_note.ok_or("INVALID OBSIDIAN DAILY NOTE".to_string())?.to_string())
+                            .filter(|daily_note| !daily_note.is_empty());
+                    }
+                    "template" =>
+                    {
+                        self.obsidian_template = value.as_str().ok_or("INVALID OBSIDIAN TEMPLATE".to_string())?.to_string();
+                    }
+                    _ => return Err(format!("UNKNOWN OBSIDIAN KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        if let Some(logging) = get_table(&tables, "logging")
+        {
+            for (key, value) in &logging.entries
+            {
+                match key.as_str()
+                {
+                    "level" =>
+                    {
+                        self.log_level = value.as_str().and_then(|level| level.parse().ok()).ok_or("INVALID LOG LEVEL".to_string())?;
+                    }
+                    _ => return Err(format!("UNKNOWN LOGGING KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Sets `key` in the `[table]` section of the config file to the TOML `value`, keeping everything else including
+/// comments. Missing keys and sections are appended.
+#[must_use]
+pub fn set_config_value(path: &Path, table: &str, key: &str, value: &str) -> bool
+{
+    let content = fs::read_to_string(path).unwrap_or_default();
+
+    fs::write(path, set_toml_value(&content, table, key, value)).is_ok()
+}
+
+/// Returns `content` with `key` in the `[table]` section set to the TOML `value`, see `set_config_value`.
+pub fn set_toml_value(content: &str, table: &str, key: &str, value: &str) -> String
+{
+    let mut lines = content.lines().map(str::to_string).collect::<Vec<String>>();
+    let entry = format!("{key} = {value}");
+
+    let header = format!("[{table}]");
+    let Some(table_start) = lines.iter().position(|line| line.trim() == header)
+    else
+    {
+        if lines.last().is_some_and(|line| !line.trim().is_empty())
+        {
+            lines.push(String::new());
+        }
+
+        lines.push(header);
+        lines.push(entry);
+
+        return lines.join("\n") + "\n";
+    };
+
+    let table_end = lines
+        .iter()
+        .skip(table_start + 1)
+        .position(|line| line.trim().starts_with('['))
+        .map_or(lines.len(), |end| table_start + 1 + end);
+
+    let existing_line = (table_start + 1..table_end).find(|index| {
+        let line = lines[*index].trim();
+        !line.starts_with('#') && line.split_once('=').is_some_and(|(line_key, _)| line_key.trim().trim_matches('"') == key)
+    });
+
+    if let Some(index) = existing_line
+    {
+        lines[index] = entry;
+    }
+    else
+    {
+        let insert_at = (table_start + 1..table_end)
+            .rev()
+            .find(|index| !lines[*index].trim().is_empty())
+            .map_or(table_start + 1, |index| index + 1);
+        lines.insert(insert_at, entry);
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Reads the column list, rejecting unknown names, duplicates and lists without the description column, which takes up
+/// the remaining width.
+fn parse_columns(value: &TomlValue) -> Option<Vec<ListColumn>>
+{
+    let TomlValue::Array(names) = value
+    else
+    {
+        return None;
+    };
+
+    let mut columns = Vec::new();
+
+    for name in names
+    {
+        let column = ListColumn::from_name(&name.as_str()?.to_lowercase())?;
+
+        if columns.contains(&column)
+        {
+            return None;
+        }
+
+        columns.push(column);
+    }
+
+    columns.contains(&ListColumn::Description).then_some(columns)
+}
+
+/// Reads targets like `20h/week` or `1h30m/day`.
+fn parse_goal(tag: &str, value: &str) -> Option<Goal>
+{
+    let (target, period) = value.split_once('/')?;
+
+    let period = match period.trim().to_lowercase().as_str()
+    {
+        "day" => GoalPeriod::Day,
+        "week" => GoalPeriod::Week,
+        _ => return None,
+    };
+
+    Some(Goal {
+        tag: tag.to_string(),
+        target: parse_duration(target.trim())?,
+        period,
+    })
+}
+
+/// Reads templates like `Daily standup / meetings / 15m`, the duration being optional.
+fn parse_template(name: &str, value: &str) -> Option<SessionTemplate>
+{
+    let parts = value.split('/').map(str::trim).collect::<Vec<&str>>();
+
+    let (description, tag, target) = match parts.as_slice()
+    {
+        [description, tag] => (description, tag, None),
+        [description, tag, target] => (description, tag, Some(parse_duration(target)?)),
+        _ => return None,
+    };
+
+    if description.is_empty() || tag.is_empty()
+    {
+        return None;
+    }
+
+    Some(SessionTemplate {
+        name: name.to_string(),
+        description: (*description).to_string(),
+        tag: (*tag).to_string(),
+        target,
+    })
+}
+
+/// Reads a day of the schedule like `6h`, `08:00-14:00` or `off`. Days worked for no time are off.
+fn parse_working_day(value: &str) -> Option<WorkingDay>
+{
+    let value = value.trim();
+
+    if value == "off"
+    {
+        return Some(WorkingDay::Off);
+    }
+
+    if let Some((start, end)) = value.split_once('-')
+    {
+        let (start, end) = (parse_time(start.trim())?, parse_time(end.trim())?);
+
+        return (start < end).then_some(WorkingDay::Span(start, end));
+    }
+
+    let duration = parse_duration(value).filter(|duration| *duration <= TimeDelta::days(1))?;
+
+    if duration.is_zero()
+    {
+        Some(WorkingDay::Off)
+    }
+    else
+    {
+        Some(WorkingDay::Hours(duration))
+    }
+}
@@ -0,0 +1,170 @@
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use sha2::Sha256;
+use std::io;
+
+pub const SALT_LENGTH: usize = 16;
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16;
+const PBKDF2_ITERATIONS: u32 = 100_000;
+
+/// ChaCha20-Poly1305 (RFC 8439) with a key derived from a passphrase by PBKDF2-HMAC-SHA256.
+#[derive(Clone)]
+pub struct Cipher
+{
+    key: [u8; KEY_LENGTH],
+}
+
+impl Cipher
+{
+    #[must_use]
+    pub fn from_passphrase(passphrase: &str, salt: &[u8]) -> Self
+    {
+        Cipher {
+            key: pbkdf2_sha256(passphrase.as_bytes(), salt, PBKDF2_ITERATIONS),
+        }
+    }
+
+    /// Encrypts under a fresh random nonce, returned as nonce, ciphertext and tag. `associated_data` is authenticated
+    /// but not stored, so decrypting needs the same value.
+    ///
+    /// # Errors
+    ///
+    /// If the operating system has no random bytes for the nonce, or the plaintext is longer than one nonce can cover.
+    pub fn encrypt(&self, plaintext: &[u8], associated_data: &[u8]) -> io::Result<Vec<u8>>
+    {
+        let mut nonce = [0; NONCE_LENGTH];
+        getrandom::fill(&mut nonce)?;
+
+        self.seal(&nonce, plaintext, associated_data)
+    }
+
+    fn seal(&self, nonce: &[u8; NONCE_LENGTH], plaintext: &[u8], associated_data: &[u8]) -> io::Result<Vec<u8>>
+    {
+        let payload = Payload {
+            msg: plaintext,
+            aad: associated_data,
+        };
+        let ciphertext = ChaCha20Poly1305::new(&self.key.into())
+            .encrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| io::Error::other("Too much data to encrypt under one nonce."))?;
+
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    /// The plaintext of `encrypt`'s output, or `None` if the passphrase is wrong or the data was changed.
+    #[must_use]
+    pub fn decrypt(&self, sealed: &[u8], associated_data: &[u8]) -> Option<Vec<u8>>
+    {
+        if sealed.len() < NONCE_LENGTH + TAG_LENGTH
+        {
+            return None;
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: associated_data,
+        };
+
+        ChaCha20Poly1305::new(&self.key.into()).decrypt(Nonce::from_slice(nonce), payload).ok()
+    }
+}
+
+/// Bytes for salts and nonces from the random source of the operating system.
+///
+/// # Errors
+///
+/// If the operating system has no random bytes to give.
+pub fn get_random_bytes(length: usize) -> io::Result<Vec<u8>>
+{
+    let mut bytes = vec![0; length];
+    getrandom::fill(&mut bytes)?;
+
+    Ok(bytes)
+}
+
+fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32) -> [u8; KEY_LENGTH]
+{
+    let mut derived_key = [0; KEY_LENGTH];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, iterations, &mut derived_key);
+
+    derived_key
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn from_hex(hex: &str) -> Vec<u8>
+    {
+        let hex = hex.split_whitespace().collect::<String>();
+
+        (0..hex.len()).step_by(2).map(|index| u8::from_str_radix(&hex[index..index + 2], 16).expect("Invalid hex.")).collect()
+    }
+
+    /// RFC 8439, section 2.8.2.
+    #[test]
+    fn chacha20_poly1305_matches_rfc_8439()
+    {
+        let cipher = Cipher {
+            key: from_hex("808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f").try_into().expect("Invalid key."),
+        };
+        let nonce = from_hex("070000004041424344454647").try_into().expect("Invalid nonce.");
+        let associated_data = from_hex("50515253c0c1c2c3c4c5c6c7");
+        let plaintext =
+            b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+        let expected = from_hex(
+            "070000004041424344454647
+             d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d63dbea45e8ca9671282fafb69da92728b
+             1a71de0a9e060b2905d6a5b67ecd3b3692ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc
+             3ff4def08e4b7a9de576d26586cec64b6116
+             1ae10b594f09e26a7e902ecbd0600691",
+        );
+
+        let sealed = cipher.seal(&nonce, plaintext, &associated_data).expect("Encrypting failed.");
+        assert_eq!(sealed, expected);
+        assert_eq!(cipher.decrypt(&sealed, &associated_data).as_deref(), Some(&plaintext[..]));
+
+        let mut tampered = sealed;
+        tampered[20] ^= 1;
+        assert_eq!(cipher.decrypt(&tampered, &associated_data), None);
+        assert_eq!(cipher.decrypt(&expected, b"other data"), None);
+    }
+
+    /// The inputs of RFC 6070 with SHA-256 instead of SHA-1, and the vectors of RFC 7914, section 11.
+    #[test]
+    fn pbkdf2_sha256_matches_known_vectors()
+    {
+        for (password, salt, iterations, expected) in [
+            ("password", "salt", 1, "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b"),
+            ("password", "salt", 2, "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43"),
+            ("password", "salt", 4096, "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"),
+            (
+                "passwordPASSWORDpassword",
+                "saltSALTsaltSALTsaltSALTsaltSALTsalt",
+                4096,
+                "348c89dbcbd32b2f32d814b8116e84cf2b17347ebc1800181c4e2a1fb8dd53e1",
+            ),
+            ("passwd", "salt", 1, "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"),
+            ("Password", "NaCl", 80_000, "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56"),
+        ]
+        {
+            assert_eq!(pbkdf2_sha256(password.as_bytes(), salt.as_bytes(), iterations).to_vec(), from_hex(expected));
+        }
+    }
+
+    #[test]
+    fn sealed_data_opens_with_the_same_passphrase_only()
+    {
+        let salt = get_random_bytes(SALT_LENGTH).expect("No random bytes.");
+        let sealed = Cipher::from_passphrase("correct horse", &salt).encrypt(b"sessions", b"magic").expect("Encrypting failed.");
+
+        assert_eq!(Cipher::from_passphrase("correct horse", &salt).decrypt(&sealed, b"magic").as_deref(), Some(&b"sessions"[..]));
+        assert_eq!(Cipher::from_passphrase("wrong horse", &salt).decrypt(&sealed, b"magic"), None);
+        assert_ne!(get_random_bytes(NONCE_LENGTH).ok(), get_random_bytes(NONCE_LENGTH).ok());
+    }
+}
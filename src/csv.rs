@@ -0,0 +1,65 @@
+/// Splits CSV content into rows of fields, following RFC 4180 quoting: fields may be wrapped in double quotes,
+/// quoted fields may contain separators and line breaks, and a doubled quote inside them is a literal quote.
+#[must_use]
+pub fn parse_csv(content: &str, separator: char) -> Vec<Vec<String>>
+{
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut characters = content.chars().peekable();
+
+    while let Some(character) = characters.next()
+    {
+        if in_quotes
+        {
+            match character
+            {
+                '"' if characters.peek() == Some(&'"') =>
+                {
+                    field.push('"');
+                    characters.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(character),
+            }
+
+            continue;
+        }
+
+        match character
+        {
+            '"' => in_quotes = true,
+            '\r' =>
+            {}
+            '\n' =>
+            {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ if character == separator => row.push(std::mem::take(&mut field)),
+            _ => field.push(character),
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty()
+    {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows.retain(|row| row.iter().any(|field| !field.trim().is_empty()));
+
+    rows
+}
+
+/// Picks the most common of the usual separators on the first line.
+#[must_use]
+pub fn detect_separator(content: &str) -> char
+{
+    let first_line = content.lines().next().unwrap_or_default();
+
+    [',', ';', '\t'].into_iter().max_by_key(|separator| first_line.matches(*separator).count()).unwrap_or(',')
+}
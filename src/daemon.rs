@@ -0,0 +1,109 @@
+use crate::database_handler::DatabaseHandler;
+#[cfg(unix)]
+use crate::database_handler::{DATE_FORMAT, VALUE_SEPARATOR};
+use crate::exit_code;
+#[cfg(unix)]
+use crate::json::parse_json;
+use crate::json::JsonValue;
+#[cfg(unix)]
+use crate::server::answer_request;
+#[cfg(unix)]
+use std::io::{Read, Write};
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::time::Duration;
+#[cfg(unix)]
+use std::{fs, process};
+
+#[cfg(unix)]
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Keeps the database and the running session of a workspace until the process is killed, answering the HTTP API of
+/// `--serve` on a socket in the workspace folder that only the user can open. The app and the `status` command hand
+/// their work to it while it runs, so nothing else writes to the database.
+#[cfg(unix)]
+pub fn run_daemon(database_handler: &DatabaseHandler)
+{
+    if database_handler.is_read_only()
+    {
+        eprintln!("The database is open in another instance, close it before starting the daemon.");
+        process::exit(exit_code::DATABASE_ERROR);
+    }
+
+    let socket_path = DatabaseHandler::get_daemon_socket_path(database_handler.get_workspace());
+
+    // What is left of a daemon that was killed.
+    let _ = fs::remove_file(&socket_path);
+
+    let listener = match UnixListener::bind(&socket_path)
+    {
+        Ok(listener) => listener,
+        Err(error) =>
+        {
+            eprintln!("Can't listen on {}: {error}", socket_path.display());
+            process::exit(exit_code::FAILURE);
+        }
+    };
+
+    let _ = fs::set_permissions(&socket_path, fs::Permissions::from_mode(0o600));
+
+    println!("Daemon of the {} workspace listening on {}", database_handler.get_workspace(), socket_path.display());
+
+    let mut running_session = database_handler.import_own_running_session(VALUE_SEPARATOR, DATE_FORMAT);
+
+    for stream in listener.incoming().filter_map(Result::ok)
+    {
+        let _ = stream.set_read_timeout(Some(REQUEST_TIMEOUT));
+
+        answer_request(database_handler, &mut running_session, stream, None);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn run_daemon(_database_handler: &DatabaseHandler)
+{
+    eprintln!("The daemon needs unix sockets, which this system doesn't have.");
+    std::process::exit(exit_code::FAILURE);
+}
+
+/// Sends a request to the daemon of `workspace` and returns the status code with the answer, `None` if no daemon is
+/// running.
+#[cfg(unix)]
+#[must_use]
+pub fn request_daemon(workspace: &str, method: &str, path: &str, body: Option<&str>) -> Option<(u16, JsonValue)>
+{
+    let mut stream = UnixStream::connect(DatabaseHandler::get_daemon_socket_path(workspace)).ok()?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT)).ok()?;
+
+    let body = body.unwrap_or_default();
+
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\nHost: daemon\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+    .ok()?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok()?;
+
+    parse_response(&response)
+}
+
+#[cfg(not(unix))]
+pub fn request_daemon(_workspace: &str, _method: &str, _path: &str, _body: Option<&str>) -> Option<(u16, JsonValue)>
+{
+    None
+}
+
+#[cfg(unix)]
+fn parse_response(response: &str) -> Option<(u16, JsonValue)>
+{
+    let (head, body) = response.split_once("\r\n\r\n")?;
+    let status = head.split_whitespace().nth(1)?.parse::<u16>().ok()?;
+
+    Some((status, parse_json(body)?))
+}
@@ -1,223 +1,2366 @@
-use crate::session::Session;
-use chrono::NaiveDateTime;
+use crate::config::set_toml_value;
+use crate::crypto::{get_random_bytes, Cipher, SALT_LENGTH};
+use crate::day_off::DayOff;
+use crate::logger::LOG_FILE_NAME;
+use crate::session::{escape_notes, unescape_notes, Session};
+use crate::storage::Storage;
+use crate::toml::{get_table, parse_toml, to_toml_string};
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeDelta};
+use log::{debug, info, warn};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env;
 use std::env::current_exe;
 use std::fs;
 use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::slice;
+use std::time::SystemTime;
+
+/// Down to the millisecond, so backups taken one after another don't replace each other. Names still sort by age.
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S%3f";
+/// Backups of older versions only went down to the second.
+const LEGACY_BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+const AUDIT_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// Separates the timestamp and the old and new session line of an audit entry. Tabs in the lines become spaces.
+const AUDIT_SEPARATOR: char = '\t';
+const DAY_OFF_DATE_FORMAT: &str = "%Y-%m-%d";
+/// Sessions are stored in one file per month of their start, named like `sessions-2024-05.txt`.
+const MONTH_FILE_PREFIX: &str = "sessions-";
+const MONTH_FORMAT: &str = "%Y-%m";
+/// Sessions loaded at startup and with every step further back in the history. Whole months are loaded, so this is a
+/// lower bound.
+const SESSIONS_PAGE_SIZE: usize = 500;
+const WORKSPACES_FOLDER_NAME: &str = "workspaces";
+/// Names the database folder next to the binary, which is used as long as it exists, and the folders of the app in the
+/// user's data and config folders.
+const PORTABLE_FOLDER_NAME: &str = "database";
+const APP_FOLDER_NAME: &str = "time-tracker";
+/// Holds the database folder chosen on the first run, in the user's config folder so it is found wherever the binary is.
+const LOCATION_FILE_NAME: &str = "database-location.txt";
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+/// Every machine marks the session running on it in a file of its own, named like `running-laptop.txt`.
+const RUNNING_FILE_PREFIX: &str = "running-";
+pub const ENCRYPTION_FILE_NAME: &str = "encryption.key";
+const DAEMON_SOCKET_FILE_NAME: &str = "daemon.sock";
+/// Starts every encrypted database file, telling it apart from a plain text one.
+const ENCRYPTED_FILE_MAGIC: &[u8] = b"TTENC1";
+/// Stored encrypted next to the salt, so a wrong passphrase is noticed before any file is read.
+const ENCRYPTION_CHECK: &[u8] = b"time-tracker";
+/// Journal records after which they are folded into the sessions file.
+const JOURNAL_COMPACTION_THRESHOLD: usize = 100;
+/// Prefixes of the journal records. Added and edited records carry the whole session line, deleted ones only the id.
+pub const JOURNAL_ADD: char = '+';
+pub const JOURNAL_EDIT: char = '~';
+pub const JOURNAL_DELETE: char = '-';
+
+/// The workspace stored directly in the database folder, as it was before workspaces existed.
+pub const DEFAULT_WORKSPACE: &str = "default";
+/// Separates the values of a stored session line.
+pub const VALUE_SEPARATOR: char = ';';
+/// Format of the stored start and end, the date and the time of day being stored as separate values.
+pub const DATE_FORMAT: &str = "%d-%m-%Y %H:%M:%S";
+
+/// Modification time and size of a database file, used to notice changes made by other programs.
+type FileStamp = Option<(SystemTime, u64)>;
 
 pub struct DatabaseHandler
 {
+    root_path: String,
     database_path: String,
+    workspace: String,
+    /// The single sessions file used before sessions were split by month. Its sessions move to the month files on
+    /// the next start.
     sessions_file_name: String,
+    journal_file_name: String,
     tags_file_name: String,
+    quarantine_file_name: String,
+    audit_file_name: String,
+    outbox_file_name: String,
+    toggl_file_name: String,
+    days_off_file_name: String,
+    /// Holds the highest id a session was ever stored with, so ids of deleted sessions are not handed out again.
+    last_session_id_file_name: String,
+    unsaved_edit_file_name: String,
+    machine_name: String,
+    config_file_name: String,
+    state_file_name: String,
+    backups_folder_name: String,
+    themes_folder_name: String,
+    max_backups: usize,
+    lock_file: Option<File>,
+    storage: Storage,
+    cipher: Option<Cipher>,
+    known_file_stamps: RefCell<HashMap<String, FileStamp>>,
+    /// Month files older than this are not loaded, `None` once the whole history is.
+    oldest_loaded_month: RefCell<Option<String>>,
+    /// Highest session id in the months that are not loaded, read once new ids are needed.
+    unloaded_max_session_id: Cell<Option<u64>>,
+}
+
+pub struct RejectedLine
+{
+    pub line_number: usize,
+    pub content: String,
+}
+
+/// A change to a stored session as recorded in the audit file. `new_session` is `None` for deleted sessions.
+pub struct AuditEntry
+{
+    pub timestamp: NaiveDateTime,
+    pub old_session: Session,
+    pub new_session: Option<Session>,
 }
 
 impl DatabaseHandler
 {
-    pub fn new() -> Self
+    /// Opens the sessions and tags of `workspace`, using `cipher` from `unlock` if the workspace is encrypted.
+    #[must_use]
+    pub fn new(workspace: &str, cipher: Option<Cipher>) -> Self
     {
-        let current_exe = current_exe().expect("Failed to retrieve executable path.");
-        let current_path = current_exe.parent().expect("Failed to retrieve executable parent folder.");
-        let database_path = current_path.join("database");
+        Self::with_storage(workspace, cipher, Storage::disk())
+    }
+
+    /// Opens `workspace` in memory, starting out empty. Nothing is read from or written to disk.
+    #[must_use]
+    pub fn new_ephemeral(workspace: &str) -> Self
+    {
+        Self::with_storage(workspace, None, Storage::memory())
+    }
+
+    /// Opens another workspace where this one is stored, so ephemeral workspaces stay in the same memory.
+    #[must_use]
+    pub fn open_workspace(&self, workspace: &str, cipher: Option<Cipher>) -> Self
+    {
+        Self::with_storage(workspace, cipher, self.storage.clone())
+    }
+
+    fn with_storage(workspace: &str, cipher: Option<Cipher>, storage: Storage) -> Self
+    {
+        let (root_path, database_path) = Self::get_paths(workspace);
 
-        let handler = DatabaseHandler {
+        let mut handler = DatabaseHandler {
+            root_path: String::from(root_path.to_str().expect("Failed to parse db path string.")),
             database_path: String::from(database_path.to_str().expect("Failed to parse db path string.")),
+            workspace: workspace.to_string(),
             sessions_file_name: String::from("sessions.txt"),
+            journal_file_name: String::from("journal.txt"),
             tags_file_name: String::from("tags.txt"),
+            quarantine_file_name: String::from("quarantine.txt"),
+            audit_file_name: String::from("audit.txt"),
+            outbox_file_name: String::from("outbox.txt"),
+            toggl_file_name: String::from("toggl.txt"),
+            days_off_file_name: String::from("days-off.txt"),
+            last_session_id_file_name: String::from("last-session-id.txt"),
+            unsaved_edit_file_name: String::from("unsaved-edit.txt"),
+            machine_name: Self::get_machine_name(),
+            config_file_name: String::from(CONFIG_FILE_NAME),
+            state_file_name: String::from("state.toml"),
+            backups_folder_name: String::from("backups"),
+            themes_folder_name: String::from("themes"),
+            max_backups: 10,
+            lock_file: None,
+            storage,
+            cipher,
+            known_file_stamps: RefCell::new(HashMap::new()),
+            oldest_loaded_month: RefCell::new(None),
+            unloaded_max_session_id: Cell::new(None),
         };
 
         handler.try_create_data_path_and_files().expect("Error while creating database.");
 
+        // Memory is not shared with other instances.
+        if !handler.storage.is_memory()
+        {
+            handler.lock_file = handler.try_lock_database();
+        }
+
+        handler.oldest_loaded_month.replace(handler.get_page_start_month(None));
+
+        debug!("Opened workspace {} at {}", handler.workspace, handler.database_path);
+
+        if handler.is_read_only()
+        {
+            info!("Workspace {} is open in another instance, opened it read-only", handler.workspace);
+        }
+
         handler
     }
 
-    fn try_create_data_path_and_files(&self) -> Result<(), Box<dyn std::error::Error>>
+    /// The host name, reduced to characters that are safe in a file name.
+    fn get_machine_name() -> String
     {
-        let database_path = Path::new(&self.database_path);
-        let sessions_path = database_path.join(&self.sessions_file_name);
-        let tags_path = database_path.join(&self.tags_file_name);
+        let host_name = env::var("COMPUTERNAME")
+            .or_else(|_| env::var("HOSTNAME"))
+            .ok()
+            .or_else(|| Command::new("hostname").output().ok().map(|output| String::from_utf8_lossy(&output.stdout).to_string()))
+            .unwrap_or_default();
 
-        if !database_path.exists()
-        {
-            fs::create_dir(database_path)?;
-        }
+        let machine_name = host_name.trim().chars().filter(|character| character.is_ascii_alphanumeric() || "-_".contains(*character));
 
-        if !sessions_path.exists()
-        {
-            File::create(sessions_path)?;
-        }
+        Some(machine_name.collect::<String>()).filter(|machine_name| !machine_name.is_empty()).unwrap_or(String::from("unknown"))
+    }
 
-        if !tags_path.exists()
+    /// The database folder and the folder of `workspace` in it. Config and themes are shared by all workspaces and stay
+    /// in the database folder, the default workspace keeps its files next to them.
+    fn get_paths(workspace: &str) -> (PathBuf, PathBuf)
+    {
+        let root_path = Self::find_root_path().unwrap_or_else(Self::get_default_root_path);
+        let database_path = if workspace == DEFAULT_WORKSPACE
         {
-            File::create(tags_path)?;
+            root_path.clone()
         }
+        else
+        {
+            root_path.join(WORKSPACES_FOLDER_NAME).join(workspace)
+        };
 
-        Ok(())
+        (root_path, database_path)
     }
 
-    pub fn export_session(&self, session_string: &String) -> Result<(), Box<dyn std::error::Error>>
+    /// The database folder, `None` before the first run. A `database` folder next to the binary is used as long as it
+    /// exists, as it was before the folder could be chosen, then the folder chosen on the first run.
+    pub fn find_root_path() -> Option<PathBuf>
     {
-        let database_path = Path::new(&self.database_path);
-        let sessions_path = database_path.join(&self.sessions_file_name);
+        let portable_path = Self::get_portable_root_path();
 
-        if let Ok(mut sessions_db) = OpenOptions::new().append(true).open(sessions_path)
+        if portable_path.is_dir()
         {
-            sessions_db.write_fmt(format_args!("\n{}", session_string))?;
+            return Some(portable_path);
         }
 
-        self.remove_empty_lines(&self.sessions_file_name);
+        let location = Self::get_location_file_path().and_then(|location_path| fs::read_to_string(location_path).ok());
 
-        Ok(())
+        if let Some(location) = location.as_deref().map(str::trim).filter(|location| !location.is_empty())
+        {
+            return Some(PathBuf::from(location));
+        }
+
+        Some(Self::get_default_root_path()).filter(|default_path| default_path.is_dir())
     }
 
-    pub fn export_all_sessions(
-        &self,
-        sessions: &Vec<Session>,
-        value_separator: char,
-        date_format: &str,
-    ) -> Result<(), Box<dyn std::error::Error>>
+    /// Where the database goes unless another folder is chosen: the app's folder in the user's data folder, or next to
+    /// the binary on systems without one.
+    pub fn get_default_root_path() -> PathBuf
     {
-        let database_path = Path::new(&self.database_path);
-        let sessions_path = database_path.join(&self.sessions_file_name);
+        get_user_folder("XDG_DATA_HOME", ".local/share").unwrap_or_else(Self::get_portable_root_path)
+    }
 
-        if let Ok(mut sessions_db) = OpenOptions::new().write(true).truncate(true).open(sessions_path)
+    /// Keeps the database in `root_path` from now on, creating the folder. Other folders than the default one are
+    /// remembered in the user's config folder.
+    ///
+    /// # Errors
+    ///
+    /// If the folder can't be created, or a folder other than the default one can't be remembered.
+    pub fn set_root_path(root_path: &Path) -> io::Result<()>
+    {
+        fs::create_dir_all(root_path)?;
+
+        let location_path = Self::get_location_file_path();
+
+        if root_path == Self::get_default_root_path()
         {
-            for session in sessions
+            if let Some(location_path) = location_path.filter(|location_path| location_path.exists())
             {
-                let session_string = session.construct_db_string(value_separator, date_format);
-                sessions_db.write_fmt(format_args!("\n{}", session_string))?;
+                fs::remove_file(location_path)?;
             }
+
+            return Ok(());
         }
 
-        self.remove_empty_lines(&self.sessions_file_name);
+        let Some(location_path) = location_path
+        else
+        {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "No config folder to remember the database folder in."));
+        };
 
-        Ok(())
+        if let Some(config_folder) = location_path.parent()
+        {
+            fs::create_dir_all(config_folder)?;
+        }
+
+        fs::write(location_path, format!("{}\n", root_path.display()))
     }
 
-    pub fn export_tag(&self, tag: &String) -> Result<(), Box<dyn std::error::Error>>
+    fn get_portable_root_path() -> PathBuf
     {
-        let database_path = Path::new(&self.database_path);
-        let tags_path = database_path.join(&self.tags_file_name);
+        let current_exe = current_exe().expect("Failed to retrieve executable path.");
+        let current_path = current_exe.parent().expect("Failed to retrieve executable parent folder.");
+
+        current_path.join(PORTABLE_FOLDER_NAME)
+    }
+
+    fn get_location_file_path() -> Option<PathBuf>
+    {
+        get_user_folder("XDG_CONFIG_HOME", ".config").map(|config_folder| config_folder.join(LOCATION_FILE_NAME))
+    }
+
+    /// The log file of all workspaces, in the database folder.
+    #[must_use]
+    pub fn get_log_file_path() -> PathBuf
+    {
+        Self::get_paths(DEFAULT_WORKSPACE).0.join(LOG_FILE_NAME)
+    }
+
+    /// Where the daemon of `workspace` listens, known without opening the database so asking it stays quick.
+    #[must_use]
+    pub fn get_daemon_socket_path(workspace: &str) -> PathBuf
+    {
+        Self::get_paths(workspace).1.join(DAEMON_SOCKET_FILE_NAME)
+    }
+
+    /// True if the workspace's files are encrypted, so a passphrase has to be asked for before opening it.
+    #[must_use]
+    pub fn is_encrypted(workspace: &str) -> bool
+    {
+        Self::get_paths(workspace).1.join(ENCRYPTION_FILE_NAME).exists()
+    }
+
+    /// The key of an encrypted workspace, `None` if the passphrase is wrong.
+    #[must_use]
+    pub fn unlock(workspace: &str, passphrase: &str) -> Option<Cipher>
+    {
+        let content = fs::read(Self::get_paths(workspace).1.join(ENCRYPTION_FILE_NAME)).ok()?;
 
-        if let Ok(mut tags) = OpenOptions::new().append(true).open(tags_path)
+        if content.len() < SALT_LENGTH
         {
-            tags.write_fmt(format_args!("\n{}", tag))?;
+            return None;
         }
 
-        self.remove_empty_lines(&self.tags_file_name);
+        let (salt, check) = content.split_at(SALT_LENGTH);
+        let cipher = Cipher::from_passphrase(passphrase, salt);
 
-        Ok(())
+        (cipher.decrypt(check, ENCRYPTED_FILE_MAGIC)? == ENCRYPTION_CHECK).then_some(cipher)
     }
 
-    pub fn import_sessions(&self, value_separator: char, format: &str) -> Option<Vec<Session>>
+    /// Encrypts every file of the workspace the app reads sessions, tags, links, marks or edits from, backups included,
+    /// with a new passphrase, or stores them as plain text again for `None`.
+    ///
+    /// # Errors
+    ///
+    /// If a file can't be read with the current key or can't be written. Files are only replaced once all of them are
+    /// converted, so nothing changes then.
+    pub fn set_passphrase(&mut self, passphrase: Option<&str>) -> Result<(), Box<dyn std::error::Error>>
     {
+        if self.is_read_only()
+        {
+            return Err("The database is in use by another instance.".into());
+        }
+
         let database_path = Path::new(&self.database_path);
-        let sessions_path = database_path.join(&self.sessions_file_name);
+        let backups_path = database_path.join(&self.backups_folder_name);
+        let encryption_path = database_path.join(ENCRYPTION_FILE_NAME);
+
+        let mut file_paths = [
+            &self.sessions_file_name,
+            &self.journal_file_name,
+            &self.tags_file_name,
+            &self.quarantine_file_name,
+            &self.audit_file_name,
+            &self.outbox_file_name,
+            &self.toggl_file_name,
+            &self.days_off_file_name,
+            &self.last_session_id_file_name,
+            &self.unsaved_edit_file_name,
+        ]
+        .map(|file_name| database_path.join(file_name))
+        .to_vec();
+        file_paths.extend(self.list_running_files().iter().map(|file_name| database_path.join(file_name)));
+        file_paths.extend(self.list_session_months().iter().map(|month| database_path.join(Self::get_month_file_name(month))));
+        file_paths.extend(self.list_backups().iter().map(|backup_name| backups_path.join(backup_name)));
+
+        let mut files = Vec::new();
+
+        for path in file_paths
+        {
+            match self.read_lines(&path)
+            {
+                Ok(lines) => files.push((lines, path)),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => (),
+                Err(error) => return Err(error.into()),
+            }
+        }
 
-        if let Ok(sessions) = OpenOptions::new().read(true).open(sessions_path)
+        let new_key = match passphrase
         {
-            let lines = BufReader::new(sessions).lines().map_while(Result::ok).filter(|x| !x.is_empty()).collect::<Vec<String>>();
+            Some(passphrase) =>
+            {
+                let salt = get_random_bytes(SALT_LENGTH)?;
+                let cipher = Cipher::from_passphrase(passphrase, &salt);
+                let key_content = [salt, cipher.encrypt(ENCRYPTION_CHECK, ENCRYPTED_FILE_MAGIC)?].concat();
+
+                Some((cipher, key_content))
+            }
+            None => None,
+        };
+        let new_cipher = new_key.as_ref().map(|(cipher, _)| cipher);
 
-            return self.parse_sessions(lines, value_separator, format);
+        // Every file is converted into its temp file first, so an interrupted run up to here leaves the workspace as it
+        // was. Only the renames are left once the key file changes: a new key is written before them, the key file of
+        // a workspace that is decrypted again is removed after them.
+        for (lines, path) in &files
+        {
+            self.storage.write(&Self::get_temp_path(path), &Self::encode_lines(lines, new_cipher)?)?;
         }
 
-        None
+        if let Some((_, key_content)) = &new_key
+        {
+            self.storage.write(&encryption_path, key_content)?;
+        }
+
+        for (_, path) in &files
+        {
+            self.storage.rename(&Self::get_temp_path(path), path)?;
+        }
+
+        if new_key.is_none() && self.storage.exists(&encryption_path)
+        {
+            self.storage.remove_file(&encryption_path)?;
+        }
+
+        self.cipher = new_key.map(|(cipher, _)| cipher);
+        self.remember_written_file_stamps(
+            &files.iter().filter_map(|(_, path)| Some(path.file_name()?.to_string_lossy().into_owned())).collect::<Vec<String>>(),
+        );
+
+        Ok(())
     }
 
-    pub fn parse_sessions(&self, sessions: Vec<String>, value_separator: char, format: &str) -> Option<Vec<Session>>
+    /// Lines of a database file, decrypted if the file is stored encrypted. Fails with `InvalidData` for files encrypted
+    /// with another key, or any key while the workspace isn't encrypted.
+    fn read_lines(&self, path: &Path) -> io::Result<Vec<String>>
     {
-        let mut parsed_sessions = Vec::new();
-        for session_string in sessions
+        let content = self.storage.read(path)?;
+
+        self.decode_lines(&content).ok_or_else(|| {
+            warn!("{} can't be decrypted", path.display());
+
+            io::Error::new(io::ErrorKind::InvalidData, format!("{} can't be decrypted", path.display()))
+        })
+    }
+
+    /// Lines of the content of a database file, `None` if it is encrypted with another key.
+    fn decode_lines(&self, content: &[u8]) -> Option<Vec<String>>
+    {
+        let content = match content.strip_prefix(ENCRYPTED_FILE_MAGIC)
         {
-            let session_split = session_string.split(value_separator).collect::<Vec<&str>>();
+            Some(sealed) => self.cipher.as_ref()?.decrypt(sealed, ENCRYPTED_FILE_MAGIC)?,
+            None => content.to_vec(),
+        };
 
-            let date = session_split[0];
-            let description = session_split[1];
-            let tag = session_split[2];
-            let start = session_split[3];
-            let end = session_split[4];
+        Some(String::from_utf8_lossy(&content).lines().map(str::to_string).collect())
+    }
 
-            let start_string = format!("{date} {start}");
-            let end_string = format!("{date} {end}");
+    /// Replaces a database file by way of a temp file, encrypting it if the database is encrypted.
+    fn write_lines(&self, path: &Path, lines: &[String]) -> io::Result<()>
+    {
+        let temp_path = Self::get_temp_path(path);
 
-            let start_date = NaiveDateTime::parse_from_str(&start_string, format).expect("Error parsing start date.");
-            let end_date = NaiveDateTime::parse_from_str(&end_string, format).expect("Error parsing end date.");
+        self.storage.write(&temp_path, &Self::encode_lines(lines, self.cipher.as_ref())?)?;
+        self.storage.rename(&temp_path, path)
+    }
 
-            let session = Session::from(description, tag, start_date, Some(end_date));
+    /// The content of a database file with `lines`, encrypted with `cipher` if there is one.
+    fn encode_lines(lines: &[String], cipher: Option<&Cipher>) -> io::Result<Vec<u8>>
+    {
+        let content = lines.iter().flat_map(|line| [line.as_str(), "\n"]).collect::<String>().into_bytes();
 
-            parsed_sessions.push(session);
+        match cipher
+        {
+            Some(cipher) => Ok([ENCRYPTED_FILE_MAGIC, &cipher.encrypt(&content, ENCRYPTED_FILE_MAGIC)?].concat()),
+            None => Ok(content),
         }
+    }
 
-        if parsed_sessions.is_empty()
+    fn get_temp_path(path: &Path) -> PathBuf
+    {
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".temp");
+
+        PathBuf::from(temp_path)
+    }
+
+    /// Adds lines to the end of a database file. Encrypted files have to be rewritten as a whole.
+    fn append_lines(&self, path: &Path, lines: &[String]) -> io::Result<()>
+    {
+        if self.cipher.is_some()
         {
-            return None;
+            let mut content = self.read_lines(path).unwrap_or_default();
+            content.extend_from_slice(lines);
+
+            return self.write_lines(path, &content);
         }
 
-        Some(parsed_sessions)
+        let content = lines.iter().fold(String::new(), |content, line| content + "\n" + line);
+
+        self.storage.append(path, content.as_bytes())
     }
 
-    pub fn import_tags(&self) -> Option<Vec<String>>
+    fn try_create_data_path_and_files(&self) -> Result<(), Box<dyn std::error::Error>>
     {
         let database_path = Path::new(&self.database_path);
         let tags_path = database_path.join(&self.tags_file_name);
+        let backups_path = database_path.join(&self.backups_folder_name);
+        let themes_path = Path::new(&self.root_path).join(&self.themes_folder_name);
 
-        if let Ok(tags) = OpenOptions::new().read(true).open(tags_path)
+        for folder_path in [database_path, &backups_path, &themes_path]
         {
-            let tags = BufReader::new(tags).lines().map_while(Result::ok).filter(|x| !x.is_empty()).collect::<Vec<String>>();
+            if !self.storage.exists(folder_path)
+            {
+                self.storage.create_dir_all(folder_path)?;
+            }
+        }
 
-            return Some(tags);
+        if !self.storage.exists(&tags_path)
+        {
+            self.storage.write(&tags_path, &[])?;
         }
 
-        None
+        Ok(())
     }
 
-    fn remove_empty_lines(&self, file_name: &String)
+    /// Takes an advisory lock on the database folder so a second instance can't rewrite the files concurrently.
+    /// The lock is held for as long as the returned file is open.
+    fn try_lock_database(&self) -> Option<File>
+    {
+        let lock_path = Path::new(&self.database_path).join(".lock");
+        let lock_file = OpenOptions::new().create(true).truncate(false).write(true).open(lock_path).ok()?;
+
+        lock_file.try_lock().ok()?;
+
+        Some(lock_file)
+    }
+
+    /// True when another instance holds the database lock. All writes are skipped in that case.
+    pub fn is_read_only(&self) -> bool
+    {
+        self.lock_file.is_none() && !self.storage.is_memory()
+    }
+
+    /// True if the database only lives in memory, see `new_ephemeral`.
+    pub fn is_ephemeral(&self) -> bool
+    {
+        self.storage.is_memory()
+    }
+
+    /// Adds `session_string` to the journal.
+    ///
+    /// # Errors
+    ///
+    /// If the journal can't be written.
+    pub fn export_session(
+        &self,
+        session_string: &str,
+        value_separator: char,
+        date_format: &str,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    {
+        self.append_journal_record(&format!("{JOURNAL_ADD}{session_string}"), value_separator, date_format)?;
+
+        Ok(())
+    }
+
+    /// Rewrites the sessions files of the loaded months with `sessions`, emptying the journal. The sessions are backed
+    /// up first.
+    ///
+    /// # Errors
+    ///
+    /// If a sessions file can't be written, or can't be read to keep what it holds.
+    pub fn export_all_sessions(
+        &self,
+        sessions: &[Session],
+        value_separator: char,
+        date_format: &str,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    {
+        if self.is_read_only()
+        {
+            return Ok(());
+        }
+
+        self.create_backup(value_separator, date_format);
+
+        let session_ids = sessions.iter().map(|session| session.id).collect::<HashSet<u64>>();
+
+        // Only the loaded months are replaced, sessions of older months are kept as they are stored.
+        let mut session_strings = self.read_session_lines(value_separator, date_format, |month| !self.is_month_loaded(month));
+        session_strings.retain(|line| Self::get_session_line_id(line, value_separator).is_none_or(|id| !session_ids.contains(&id)));
+        session_strings.extend(
+            sessions.iter().filter(|s| !s.is_running()).map(|session| session.construct_db_string(value_separator, date_format)),
+        );
+
+        self.write_sessions_files(&session_strings, value_separator, date_format)?;
+
+        Ok(())
+    }
+
+    /// Stores `session_strings` in one file per month and empties the journal. Only the month files whose content
+    /// changed are rewritten. The journal is only emptied once the month
+    /// files are in place, and replaying it again is harmless.
+    fn write_sessions_files(&self, session_strings: &[String], value_separator: char, date_format: &str) -> io::Result<()>
     {
         let database_path = Path::new(&self.database_path);
-        let file_path = database_path.join(file_name);
-        let temp_path = format!("{file_name}.temp");
+        let journal_path = database_path.join(&self.journal_file_name);
+        let unsharded_sessions_path = database_path.join(&self.sessions_file_name);
+
+        self.unloaded_max_session_id.set(None);
+        self.remember_session_ids(
+            session_strings.iter().filter_map(|session_string| Self::get_session_line_id(session_string, value_separator)),
+            value_separator,
+        )?;
+
+        let mut months = BTreeMap::<String, Vec<String>>::new();
+        let mut rejected_lines = Vec::new();
+        let mut written_file_names = vec![self.journal_file_name.clone()];
 
-        if let Ok(file) = OpenOptions::new().read(true).open(file_path.clone())
+        for session_string in session_strings.iter().filter(|line| !line.is_empty())
         {
-            let entries = BufReader::new(file).lines().map_while(Result::ok).filter(|x| !x.is_empty()).collect::<Vec<String>>();
+            match Self::parse_session_line(session_string, value_separator, date_format)
+            {
+                Some(session) =>
+                {
+                    months.entry(session.start.format(MONTH_FORMAT).to_string()).or_default().push(session_string.clone());
+                }
+                None => rejected_lines.push(session_string.clone()),
+            }
+        }
 
-            if !entries.is_empty()
-                && let Ok(mut temp_file) = OpenOptions::new().truncate(true).write(true).create_new(true).open(temp_path.clone())
+        if !rejected_lines.is_empty()
+        {
+            self.append_lines(&database_path.join(&self.quarantine_file_name), &rejected_lines)?;
+        }
+
+        for (month, month_session_strings) in &months
+        {
+            let month_file_name = Self::get_month_file_name(month);
+            let month_path = database_path.join(&month_file_name);
+
+            match self.read_lines(&month_path)
             {
-                for entry in entries
+                Ok(lines) if lines == *month_session_strings => (),
+                // The sessions of a file that can't be decrypted were never read, replacing it would lose them.
+                Err(error) if error.kind() == io::ErrorKind::InvalidData => return Err(error),
+                _ =>
                 {
-                    temp_file.write_fmt(format_args!("{}\n", entry)).expect("Failed to write to temp file.");
+                    self.write_lines(&month_path, month_session_strings)?;
+                    written_file_names.push(month_file_name);
                 }
+            }
+        }
+
+        for month in self.list_session_months().iter().filter(|month| !months.contains_key(*month))
+        {
+            let month_file_name = Self::get_month_file_name(month);
+            let month_path = database_path.join(&month_file_name);
 
-                fs::rename(&temp_path, &file_path).expect("Failed renaming after removing empty lines.");
+            if let Err(error) = self.read_lines(&month_path)
+                && error.kind() == io::ErrorKind::InvalidData
+            {
+                return Err(error);
             }
+
+            self.storage.remove_file(&month_path)?;
+            written_file_names.push(month_file_name);
+        }
+
+        if self.storage.exists(&unsharded_sessions_path)
+        {
+            self.storage.remove_file(&unsharded_sessions_path)?;
+            written_file_names.push(self.sessions_file_name.clone());
         }
+
+        self.write_lines(&journal_path, &[])?;
+
+        self.remember_written_file_stamps(&written_file_names);
+
+        Ok(())
     }
 
-    pub fn delete_session(&self, session_index: usize)
+    /// Gives sessions that share their id with a different session a new one and drops exact copies. Both happen when the
+    /// files of two machines are merged, as each numbers its new sessions on its own. The sessions are backed up before
+    /// they are rewritten.
+    ///
+    /// # Errors
+    ///
+    /// If the sessions can't be written.
+    pub fn resolve_duplicate_session_ids(&self, value_separator: char, date_format: &str) -> io::Result<()>
     {
-        let database_path = Path::new(&self.database_path);
-        let sessions_path = database_path.join(&self.sessions_file_name);
+        let session_strings = self
+            .get_session_file_paths()
+            .iter()
+            .filter_map(|path| self.read_lines(path).ok())
+            .flatten()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<String>>();
 
-        let temp_sessions_path = database_path.join("sessions.txt.temp");
+        let mut next_id = session_strings
+            .iter()
+            .filter_map(|line| Self::get_session_line_id(line, value_separator))
+            .max()
+            .unwrap_or(0)
+            .max(self.get_last_session_id(value_separator))
+            + 1;
+        let mut unique_session_strings = Vec::with_capacity(session_strings.len());
+        let mut seen_session_strings = HashSet::new();
+        let mut used_ids = HashSet::new();
 
-        if let Ok(sessions) = OpenOptions::new().read(true).open(sessions_path.clone())
+        for session_string in &session_strings
         {
-            let mut session_entries = BufReader::new(sessions).lines().map_while(Result::ok).collect::<Vec<String>>();
-
-            session_entries.remove(session_index);
+            if !seen_session_strings.insert(session_string.as_str())
+            {
+                continue;
+            }
 
-            if let Ok(mut temp_sessions) =
-                OpenOptions::new().truncate(true).write(true).create_new(true).open(temp_sessions_path.clone())
+            match (
+                Self::get_session_line_id(session_string, value_separator),
+                Self::parse_session_line(session_string, value_separator, date_format),
+            )
             {
-                for entry in session_entries
+                (Some(session_id), Some(mut session)) if !used_ids.insert(session_id) =>
                 {
-                    temp_sessions.write_fmt(format_args!("{}\n", entry)).expect("Failed to delete session from database.");
-                }
+                    session.id = next_id;
+                    used_ids.insert(next_id);
+                    next_id += 1;
 
-                fs::rename(&temp_sessions_path, &sessions_path).expect("Failed to rename new database.");
+                    unique_session_strings.push(session.construct_db_string(value_separator, date_format));
+                }
+                _ => unique_session_strings.push(session_string.clone()),
             }
         }
+
+        if unique_session_strings != session_strings
+        {
+            self.create_backup(value_separator, date_format);
+            self.write_sessions_files(&unique_session_strings, value_separator, date_format)?;
+        }
+
+        Ok(())
+    }
+
+    /// The single sessions file of older versions followed by the month files, oldest first.
+    fn get_session_file_paths(&self) -> Vec<PathBuf>
+    {
+        let database_path = Path::new(&self.database_path);
+
+        let mut session_file_paths = vec![database_path.join(&self.sessions_file_name)];
+        session_file_paths.extend(self.list_session_months().iter().map(|month| database_path.join(Self::get_month_file_name(month))));
+
+        session_file_paths
+    }
+
+    fn get_month_file_name(month: &str) -> String
+    {
+        format!("{MONTH_FILE_PREFIX}{month}.txt")
+    }
+
+    /// The months that have a sessions file, oldest first, formatted like `2024-05`.
+    pub fn list_session_months(&self) -> Vec<String>
+    {
+        let mut months = self
+            .storage
+            .list_names(Path::new(&self.database_path))
+            .map(|file_names| {
+                file_names
+                    .into_iter()
+                    .filter_map(|file_name| {
+                        let month = file_name.strip_prefix(MONTH_FILE_PREFIX)?.strip_suffix(".txt")?;
+
+                        NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d").ok().map(|_| month.to_string())
+                    })
+                    .collect::<Vec<String>>()
+            })
+            .unwrap_or_default();
+
+        months.sort();
+
+        months
+    }
+
+    /// Records an added, edited or deleted session at the end of the journal instead of rewriting a sessions file.
+    /// Once the journal grows past `JOURNAL_COMPACTION_THRESHOLD` records it is folded into the sessions files.
+    fn append_journal_record(&self, record: &str, value_separator: char, date_format: &str) -> io::Result<()>
+    {
+        if self.is_read_only()
+        {
+            return Ok(());
+        }
+
+        let journal_path = Path::new(&self.database_path).join(&self.journal_file_name);
+
+        self.remember_session_ids(Self::get_record_session_id(record, value_separator), value_separator)?;
+        self.append_lines(&journal_path, &[record.to_string()])?;
+
+        if self.count_journal_records() >= JOURNAL_COMPACTION_THRESHOLD
+        {
+            self.compact_journal(value_separator, date_format)?;
+        }
+
+        self.remember_written_file_stamps(slice::from_ref(&self.journal_file_name));
+
+        Ok(())
+    }
+
+    /// The id of the session a journal record adds, edits or deletes.
+    fn get_record_session_id(record: &str, value_separator: char) -> Option<u64>
+    {
+        match record.strip_prefix(JOURNAL_DELETE)
+        {
+            Some(session_id) => session_id.parse().ok(),
+            None => Self::get_session_line_id(record, value_separator),
+        }
+    }
+
+    /// The highest id a session was ever stored with. Ids only go up, so a new session never gets the id of a deleted
+    /// one, which could still be linked to a Toggl entry. Workspaces of older versions start out from their stored ids.
+    pub fn get_last_session_id(&self, value_separator: char) -> u64
+    {
+        let database_path = Path::new(&self.database_path);
+
+        if let Some(last_session_id) =
+            self.read_lines(&database_path.join(&self.last_session_id_file_name)).ok().and_then(|lines| lines.first()?.parse().ok())
+        {
+            return last_session_id;
+        }
+
+        let mut file_paths = self.get_session_file_paths();
+        file_paths.push(database_path.join(&self.journal_file_name));
+
+        file_paths
+            .iter()
+            .filter_map(|path| self.read_lines(path).ok())
+            .flatten()
+            .filter_map(|line| Self::get_record_session_id(&line, value_separator))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Raises the last session id to the highest of `session_ids`. Called before the files change, so a workspace of an
+    /// older version starts out from the ids it stored before.
+    fn remember_session_ids(&self, session_ids: impl IntoIterator<Item = u64>, value_separator: char) -> io::Result<()>
+    {
+        let last_session_id_path = Path::new(&self.database_path).join(&self.last_session_id_file_name);
+        let last_session_id = self.get_last_session_id(value_separator);
+        let max_session_id = session_ids.into_iter().max().unwrap_or(0).max(last_session_id);
+
+        if max_session_id == last_session_id && self.storage.exists(&last_session_id_path)
+        {
+            return Ok(());
+        }
+
+        self.write_lines(&last_session_id_path, &[max_session_id.to_string()])
+    }
+
+    fn count_journal_records(&self) -> usize
+    {
+        let journal_path = Path::new(&self.database_path).join(&self.journal_file_name);
+
+        self.read_lines(&journal_path).unwrap_or_default().iter().filter(|line| !line.is_empty()).count()
+    }
+
+    /// Folds the journal into the sessions files, backing them up first unless that was already done today.
+    ///
+    /// # Errors
+    ///
+    /// If the sessions files can't be written, the journal is kept then.
+    pub fn compact_journal(&self, value_separator: char, date_format: &str) -> io::Result<()>
+    {
+        self.create_daily_backup(value_separator, date_format);
+
+        let session_strings = self.read_session_lines(value_separator, date_format, |_| true);
+
+        self.write_sessions_files(&session_strings, value_separator, date_format)
+    }
+
+    /// The lines of the sessions files of the months `is_month_included` accepts, with the journal replayed over them.
+    /// Lines of an unknown month, like those of the single sessions file of older versions, are passed as `None`.
+    /// Every line and record replaces, adds or removes the line with its session id, so a session left in two files or
+    /// a record replayed twice by an interrupted compaction still shows up once.
+    fn read_session_lines(
+        &self,
+        value_separator: char,
+        date_format: &str,
+        is_month_included: impl Fn(Option<&str>) -> bool,
+    ) -> Vec<String>
+    {
+        let database_path = Path::new(&self.database_path);
+        let journal_path = database_path.join(&self.journal_file_name);
+
+        let mut session_file_paths = Vec::new();
+
+        if is_month_included(None)
+        {
+            session_file_paths.push(database_path.join(&self.sessions_file_name));
+        }
+
+        session_file_paths.extend(
+            self.list_session_months()
+                .iter()
+                .filter(|month| is_month_included(Some(month)))
+                .map(|month| database_path.join(Self::get_month_file_name(month))),
+        );
+
+        let mut session_strings = Vec::<Option<String>>::new();
+        let mut session_indices = HashMap::<u64, usize>::new();
+
+        for session_string in session_file_paths.iter().filter_map(|path| self.read_lines(path).ok()).flatten()
+        {
+            Self::upsert_session_line(&mut session_strings, &mut session_indices, &session_string, value_separator);
+        }
+
+        for record in self.read_lines(&journal_path).unwrap_or_default()
+        {
+            let mut characters = record.chars();
+            let operation = characters.next();
+            let content = characters.as_str();
+
+            match operation
+            {
+                Some(JOURNAL_ADD | JOURNAL_EDIT)
+                    if is_month_included(Self::get_session_line_month(content, value_separator, date_format).as_deref()) =>
+                {
+                    Self::upsert_session_line(&mut session_strings, &mut session_indices, content, value_separator);
+                }
+                // Sessions moved to a month that isn't included are dropped like deleted ones.
+                Some(JOURNAL_ADD | JOURNAL_EDIT) =>
+                {
+                    if let Some(session_index) =
+                        Self::get_session_line_id(content, value_separator).and_then(|session_id| session_indices.get(&session_id))
+                    {
+                        session_strings[*session_index] = None;
+                    }
+                }
+                Some(JOURNAL_DELETE) =>
+                {
+                    if let Some(session_index) = content.parse::<u64>().ok().and_then(|session_id| session_indices.get(&session_id))
+                    {
+                        session_strings[*session_index] = None;
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        session_strings.into_iter().flatten().collect()
+    }
+
+    /// The stored lines of the whole history with the journal replayed, without parsing or repairing anything.
+    pub fn import_all_session_lines(&self, value_separator: char, date_format: &str) -> Vec<String>
+    {
+        self.read_session_lines(value_separator, date_format, |_| true)
+    }
+
+    fn get_session_line_month(session_string: &str, value_separator: char, date_format: &str) -> Option<String>
+    {
+        Self::parse_session_line(session_string, value_separator, date_format)
+            .map(|session| session.start.format(MONTH_FORMAT).to_string())
+    }
+
+    /// True for lines of a loaded month. Lines of an unknown month count as loaded, they are sorted out once parsed.
+    fn is_month_loaded(&self, month: Option<&str>) -> bool
+    {
+        match (month, self.oldest_loaded_month.borrow().as_deref())
+        {
+            (Some(month), Some(oldest_loaded_month)) => month >= oldest_loaded_month,
+            _ => true,
+        }
+    }
+
+    /// The oldest month to load so at least `SESSIONS_PAGE_SIZE` more sessions than those from `before` on are loaded,
+    /// `None` if that takes the whole history. Sessions of older versions that are not split by month yet are always
+    /// loaded as a whole.
+    fn get_page_start_month(&self, before: Option<&str>) -> Option<String>
+    {
+        let database_path = Path::new(&self.database_path);
+
+        if self.storage.exists(&database_path.join(&self.sessions_file_name))
+        {
+            return None;
+        }
+
+        let months = self.list_session_months();
+        let mut session_count = 0;
+
+        for (index, month) in months.iter().enumerate().rev().filter(|(_, month)| before.is_none_or(|before| month.as_str() < before))
+        {
+            session_count += self
+                .read_lines(&database_path.join(Self::get_month_file_name(month)))
+                .map_or(0, |lines| lines.iter().filter(|line| !line.is_empty()).count());
+
+            if session_count >= SESSIONS_PAGE_SIZE
+            {
+                return (index > 0).then(|| month.clone());
+            }
+        }
+
+        None
+    }
+
+    /// True while older months of the history are not loaded yet.
+    pub fn has_unloaded_sessions(&self) -> bool
+    {
+        self.oldest_loaded_month.borrow().is_some()
+    }
+
+    /// Extends the loaded history by another page of older sessions. Returns false if everything is loaded already.
+    pub fn load_older_sessions(&self) -> bool
+    {
+        let Some(oldest_loaded_month) = self.oldest_loaded_month.borrow().clone()
+        else
+        {
+            return false;
+        };
+
+        self.oldest_loaded_month.replace(self.get_page_start_month(Some(&oldest_loaded_month)));
+
+        true
+    }
+
+    /// Extends the loaded history back to the month of `date`. Returns false if it was loaded already.
+    pub fn load_sessions_since(&self, date: NaiveDate) -> bool
+    {
+        let month = date.format(MONTH_FORMAT).to_string();
+
+        if self.is_month_loaded(Some(&month))
+        {
+            return false;
+        }
+
+        let is_oldest_month = self.list_session_months().first().is_none_or(|oldest_month| *oldest_month >= month);
+        self.oldest_loaded_month.replace((!is_oldest_month).then_some(month));
+
+        true
+    }
+
+    /// Loads the whole history, which reports need. Returns false if it was loaded already.
+    pub fn load_all_sessions(&self) -> bool
+    {
+        self.oldest_loaded_month.take().is_some()
+    }
+
+    /// The highest session id among the sessions that are not loaded, so new sessions never reuse one of their ids.
+    pub fn get_unloaded_max_session_id(&self, value_separator: char, date_format: &str) -> u64
+    {
+        if !self.has_unloaded_sessions()
+        {
+            return 0;
+        }
+
+        if let Some(max_session_id) = self.unloaded_max_session_id.get()
+        {
+            return max_session_id;
+        }
+
+        let max_session_id = self
+            .read_session_lines(value_separator, date_format, |month| !self.is_month_loaded(month))
+            .iter()
+            .filter_map(|line| Self::get_session_line_id(line, value_separator))
+            .max()
+            .unwrap_or(0);
+
+        self.unloaded_max_session_id.set(Some(max_session_id));
+
+        max_session_id
+    }
+
+    fn upsert_session_line(
+        session_strings: &mut Vec<Option<String>>,
+        session_indices: &mut HashMap<u64, usize>,
+        session_string: &str,
+        value_separator: char,
+    )
+    {
+        let session_id = Self::get_session_line_id(session_string, value_separator);
+
+        if let Some(session_index) = session_id.and_then(|session_id| session_indices.get(&session_id))
+        {
+            session_strings[*session_index] = Some(session_string.to_string());
+        }
+        else
+        {
+            if let Some(session_id) = session_id
+            {
+                session_indices.insert(session_id, session_strings.len());
+            }
+
+            session_strings.push(Some(session_string.to_string()));
+        }
+    }
+
+    /// Adds `tag` to the tags file.
+    ///
+    /// # Errors
+    ///
+    /// If the tags file can't be written.
+    pub fn export_tag(&self, tag: &String) -> Result<(), Box<dyn std::error::Error>>
+    {
+        if self.is_read_only()
+        {
+            return Ok(());
+        }
+
+        let database_path = Path::new(&self.database_path);
+        let tags_path = database_path.join(&self.tags_file_name);
+
+        self.append_lines(&tags_path, slice::from_ref(tag))?;
+
+        self.remove_empty_lines(&self.tags_file_name);
+        self.remember_written_file_stamps(slice::from_ref(&self.tags_file_name));
+
+        Ok(())
+    }
+
+    /// The sessions of the loaded months with the lines that couldn't be read, `None` for the sessions if there is no
+    /// sessions file yet.
+    ///
+    /// # Panics
+    ///
+    /// If lines that couldn't be read can't be moved to the quarantine file.
+    pub fn import_sessions(&self, value_separator: char, format: &str) -> (Option<Vec<Session>>, Vec<RejectedLine>)
+    {
+        let lines = self.read_session_lines(value_separator, format, |month| self.is_month_loaded(month));
+
+        let has_missing_ids =
+            lines.iter().filter(|x| !x.is_empty()).any(|line| Self::get_session_line_id(line, value_separator).is_none());
+        let is_unsharded = self.storage.exists(&Path::new(&self.database_path).join(&self.sessions_file_name));
+
+        let (sessions, rejected_lines) = Self::parse_sessions(&lines, value_separator, format);
+
+        self.remember_file_stamps();
+
+        if !rejected_lines.is_empty()
+        {
+            self.quarantine_lines(&rejected_lines).expect("Failed to quarantine malformed sessions.");
+        }
+
+        // Sessions stored before ids existed get theirs assigned once and written back, so they stay stable.
+        // Malformed lines are dropped from the files the same way, after being quarantined above, and sessions still in
+        // the single sessions file of older versions are moved to the month files.
+        if has_missing_ids
+            || is_unsharded
+            || !rejected_lines.is_empty()
+            || self.count_journal_records() >= JOURNAL_COMPACTION_THRESHOLD
+        {
+            self.export_all_sessions(sessions.as_deref().unwrap_or_default(), value_separator, format)
+                .expect("Failed to store repaired sessions.");
+        }
+
+        (sessions, rejected_lines)
+    }
+
+    /// Parses the lines of a sessions file. Lines that can't be parsed are returned with their 1-based line number
+    /// instead of aborting the whole import.
+    #[must_use]
+    pub fn parse_sessions(lines: &[String], value_separator: char, format: &str) -> (Option<Vec<Session>>, Vec<RejectedLine>)
+    {
+        let mut parsed_sessions = Vec::new();
+        let mut rejected_lines = Vec::new();
+
+        for (line_index, session_string) in lines.iter().enumerate()
+        {
+            if session_string.trim().is_empty()
+            {
+                continue;
+            }
+
+            if let Some(session) = Self::parse_session_line(session_string, value_separator, format)
+            {
+                parsed_sessions.push(session);
+            }
+            else
+            {
+                rejected_lines.push(RejectedLine {
+                    line_number: line_index + 1,
+                    content: session_string.clone(),
+                });
+            }
+        }
+
+        let next_id = parsed_sessions.iter().map(|session| session.id).max().unwrap_or(0) + 1;
+
+        for (id, session) in (next_id..).zip(parsed_sessions.iter_mut().filter(|session| session.id == 0))
+        {
+            session.id = id;
+        }
+
+        parsed_sessions.sort_by_key(|session| session.start);
+
+        if parsed_sessions.is_empty()
+        {
+            return (None, rejected_lines);
+        }
+
+        (Some(parsed_sessions), rejected_lines)
+    }
+
+    fn parse_session_line(session_string: &str, value_separator: char, format: &str) -> Option<Session>
+    {
+        let session_split = session_string.split(value_separator).collect::<Vec<&str>>();
+
+        let date = session_split.first()?;
+        let description = session_split.get(1)?;
+        let tag = session_split.get(2)?;
+        let start = session_split.get(3)?;
+        let end = session_split.get(4)?;
+
+        // Lines written before end dates were stored only carry the start date.
+        let stored_end_date = session_split.get(5).copied().filter(|end_date| !end_date.is_empty());
+
+        let start_string = format!("{date} {start}");
+        let end_string = format!("{} {end}", stored_end_date.unwrap_or(date));
+
+        let start_date = NaiveDateTime::parse_from_str(&start_string, format).ok()?;
+        let mut end_date = NaiveDateTime::parse_from_str(&end_string, format).ok()?;
+
+        if stored_end_date.is_none() && end_date < start_date
+        {
+            end_date += TimeDelta::days(1);
+        }
+
+        if description.trim().is_empty() || tag.trim().is_empty() || end_date < start_date
+        {
+            return None;
+        }
+
+        let mut session = Session::from(description, tag, start_date, Some(end_date));
+        session.id = Self::get_session_line_id(session_string, value_separator).unwrap_or(0);
+        session.notes = session_split.get(7).map(|notes| unescape_notes(notes, value_separator)).unwrap_or_default();
+
+        Some(session)
+    }
+
+    /// Moves lines that couldn't be read to the quarantine file, where they can be fixed by hand.
+    ///
+    /// # Errors
+    ///
+    /// If the quarantine file can't be written.
+    pub fn quarantine_lines(&self, rejected_lines: &[RejectedLine]) -> Result<(), Box<dyn std::error::Error>>
+    {
+        if self.is_read_only()
+        {
+            return Ok(());
+        }
+
+        let quarantine_path = Path::new(&self.database_path).join(&self.quarantine_file_name);
+        let lines = rejected_lines.iter().map(|rejected_line| rejected_line.content.clone()).collect::<Vec<String>>();
+
+        self.append_lines(&quarantine_path, &lines)?;
+        self.remove_empty_lines(&self.quarantine_file_name);
+
+        warn!("Moved {} unreadable lines to {}", lines.len(), quarantine_path.display());
+
+        Ok(())
+    }
+
+    pub fn get_quarantine_file_name(&self) -> &str
+    {
+        &self.quarantine_file_name
+    }
+
+    pub fn get_config_file_path(&self) -> PathBuf
+    {
+        Path::new(&self.root_path).join(&self.config_file_name)
+    }
+
+    /// The config file of a named workspace, layered over the global one. The default workspace has none.
+    pub fn get_workspace_config_file_path(&self) -> Option<PathBuf>
+    {
+        (self.workspace != DEFAULT_WORKSPACE).then(|| Path::new(&self.database_path).join(&self.config_file_name))
+    }
+
+    pub fn get_themes_path(&self) -> PathBuf
+    {
+        Path::new(&self.root_path).join(&self.themes_folder_name)
+    }
+
+    /// The folder holding the files of the open workspace.
+    pub fn get_database_path(&self) -> &Path
+    {
+        Path::new(&self.database_path)
+    }
+
+    /// Replaces a file of the workspace with the lines either `ours` or `theirs` added to or kept from `base`, in the order
+    /// of `ours` followed by the lines only `theirs` has. A missing version counts as an empty file and the file is
+    /// removed if no line is left. Returns false if a version can't be decrypted or the file can't be written.
+    pub fn merge_file_versions(&self, file_name: &str, base: Option<&[u8]>, ours: Option<&[u8]>, theirs: Option<&[u8]>) -> bool
+    {
+        let [Some(base), Some(ours), Some(theirs)] =
+            [base, ours, theirs].map(|content| content.map_or(Some(Vec::new()), |content| self.decode_lines(content)))
+        else
+        {
+            return false;
+        };
+
+        let base = base.iter().collect::<HashSet<&String>>();
+        let (ours_set, theirs_set) = (ours.iter().collect::<HashSet<&String>>(), theirs.iter().collect::<HashSet<&String>>());
+
+        // A line of the base that one side removed stays removed, even if the other side kept it.
+        let mut merged_lines = ours.iter().filter(|line| !base.contains(line) || theirs_set.contains(line)).collect::<Vec<&String>>();
+        merged_lines.extend(theirs.iter().filter(|line| !ours_set.contains(line) && !base.contains(line)));
+
+        let merged_lines = merged_lines.into_iter().filter(|line| !line.is_empty()).cloned().collect::<Vec<String>>();
+        let path = Path::new(&self.database_path).join(file_name);
+
+        let result = if !merged_lines.is_empty()
+        {
+            self.write_lines(&path, &merged_lines)
+        }
+        else if self.storage.exists(&path)
+        {
+            self.storage.remove_file(&path)
+        }
+        else
+        {
+            Ok(())
+        };
+
+        result.is_ok()
+    }
+
+    /// Copies of database files a sync service like Dropbox or Syncthing made when two machines changed them at once,
+    /// like `sessions-2024-05 (conflicted copy 2024-05-14).txt` or `tags.sync-conflict-20240514-093012-ABCD123.txt`,
+    /// each with the name of the file it is a copy of.
+    fn list_conflict_copies(&self) -> Vec<(String, String)>
+    {
+        let Ok(entry_names) = self.storage.list_names(Path::new(&self.database_path))
+        else
+        {
+            return Vec::new();
+        };
+
+        let file_names = [
+            &self.sessions_file_name,
+            &self.journal_file_name,
+            &self.tags_file_name,
+            &self.quarantine_file_name,
+            &self.audit_file_name,
+            &self.toggl_file_name,
+            &self.days_off_file_name,
+        ];
+
+        let mut conflict_copies = entry_names
+            .into_iter()
+            .filter(|file_name| file_name.strip_suffix(".txt").is_some_and(|name| name.to_lowercase().contains("conflict")))
+            .filter_map(|copy_name| {
+                let stem = copy_name.split([' ', '.']).next()?;
+                let original_name = format!("{stem}.txt");
+
+                let is_month_file = stem
+                    .strip_prefix(MONTH_FILE_PREFIX)
+                    .is_some_and(|month| NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d").is_ok());
+
+                (is_month_file || file_names.contains(&&original_name)).then_some((copy_name, original_name))
+            })
+            .collect::<Vec<(String, String)>>();
+
+        conflict_copies.sort();
+
+        conflict_copies
+    }
+
+    /// Folds the conflict copies back into the files they were made of, keeping the lines of both and removing the
+    /// copies. Sessions that were added on both machines under the same id are given a new one. Copies that can't be
+    /// read, like those encrypted with another passphrase, are left in place. The sessions are backed up first. Returns
+    /// how many were merged.
+    ///
+    /// # Panics
+    ///
+    /// If the merged sessions can't be written.
+    pub fn merge_conflict_copies(&self, value_separator: char, date_format: &str) -> usize
+    {
+        let conflict_copies = self.list_conflict_copies();
+
+        if conflict_copies.is_empty() || self.is_read_only()
+        {
+            return 0;
+        }
+
+        self.create_backup(value_separator, date_format);
+
+        let database_path = Path::new(&self.database_path);
+        let mut merged_count = 0;
+        let mut written_file_names = Vec::new();
+
+        // Session files are merged with the journal folded into them, and replaying the journal of the other machine
+        // has to wait for the ids to be unique.
+        self.compact_journal(value_separator, date_format).expect("Failed to write sessions.");
+
+        let (journal_copies, file_copies): (Vec<_>, Vec<_>) =
+            conflict_copies.iter().partition(|(_, original_name)| *original_name == self.journal_file_name);
+
+        for (copy_name, original_name) in file_copies
+        {
+            let [ours, theirs] = [original_name, copy_name].map(|file_name| self.storage.read(&database_path.join(file_name)).ok());
+
+            if self.merge_file_versions(original_name, None, ours.as_deref(), theirs.as_deref())
+                && self.storage.remove_file(&database_path.join(copy_name)).is_ok()
+            {
+                merged_count += 1;
+                written_file_names.extend([original_name.clone(), copy_name.clone()]);
+            }
+        }
+
+        self.resolve_duplicate_session_ids(value_separator, date_format).expect("Failed to write sessions.");
+
+        for (copy_name, _) in journal_copies
+        {
+            let Some(records) = self.storage.read(&database_path.join(copy_name)).ok().and_then(|content| self.decode_lines(&content))
+            else
+            {
+                continue;
+            };
+
+            self.replay_journal_copy(&records, value_separator, date_format).expect("Failed to write sessions.");
+            self.storage.remove_file(&database_path.join(copy_name)).expect("Failed to remove conflict copy.");
+            merged_count += 1;
+            written_file_names.push(copy_name.clone());
+        }
+
+        self.remember_written_file_stamps(&written_file_names);
+
+        info!("Merged {merged_count} of {} conflict copies", conflict_copies.len());
+
+        merged_count
+    }
+
+    /// Applies the journal records of another machine. Sessions it added under an id that is taken by a different
+    /// session here get a new one, and its later records of them follow along.
+    fn replay_journal_copy(&self, records: &[String], value_separator: char, date_format: &str) -> io::Result<()>
+    {
+        let mut session_strings = HashMap::<u64, String>::new();
+
+        for session_string in self.get_session_file_paths().iter().filter_map(|path| self.read_lines(path).ok()).flatten()
+        {
+            if let Some(session_id) = Self::get_session_line_id(&session_string, value_separator)
+            {
+                session_strings.insert(session_id, session_string);
+            }
+        }
+
+        // Deleted sessions keep their ids as well, see `get_last_session_id`.
+        let mut next_id = session_strings.keys().max().copied().unwrap_or(0).max(self.get_last_session_id(value_separator)) + 1;
+        let mut new_ids = HashMap::<u64, u64>::new();
+        let mut replayed_records = Vec::new();
+
+        for record in records.iter().filter(|record| !record.is_empty())
+        {
+            let mut characters = record.chars();
+            let operation = characters.next();
+            let content = characters.as_str();
+
+            if operation == Some(JOURNAL_DELETE)
+            {
+                let session_id = content.parse::<u64>().ok().map(|session_id| *new_ids.get(&session_id).unwrap_or(&session_id));
+                replayed_records.extend(session_id.map(|session_id| format!("{JOURNAL_DELETE}{session_id}")));
+
+                continue;
+            }
+
+            let (Some(operation), Some(session_id), Some(mut session)) = (
+                operation,
+                Self::get_session_line_id(content, value_separator),
+                Self::parse_session_line(content, value_separator, date_format),
+            )
+            else
+            {
+                replayed_records.push(record.clone());
+                continue;
+            };
+
+            let is_taken = operation == JOURNAL_ADD
+                && !new_ids.contains_key(&session_id)
+                && session_strings.get(&session_id).is_some_and(|session_string| session_string != content);
+
+            if is_taken
+            {
+                new_ids.insert(session_id, next_id);
+                next_id += 1;
+            }
+
+            session.id = *new_ids.get(&session_id).unwrap_or(&session_id);
+
+            let session_string = session.construct_db_string(value_separator, date_format);
+            session_strings.insert(session.id, session_string.clone());
+            replayed_records.push(format!("{operation}{session_string}"));
+        }
+
+        let journal_path = Path::new(&self.database_path).join(&self.journal_file_name);
+
+        self.append_lines(&journal_path, &replayed_records)?;
+        self.compact_journal(value_separator, date_format)
+    }
+
+    fn list_running_files(&self) -> Vec<String>
+    {
+        self.storage
+            .list_names(Path::new(&self.database_path))
+            .map(|file_names| {
+                file_names
+                    .into_iter()
+                    .filter(|file_name| {
+                        file_name.strip_prefix(RUNNING_FILE_PREFIX).and_then(|name| name.strip_suffix(".txt")).is_some()
+                    })
+                    .collect::<Vec<String>>()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Marks `running_session` as running on this machine, or clears the mark for `None`. Running sessions are only
+    /// stored once they end, so this is how other machines sharing the database folder learn about them.
+    ///
+    /// # Panics
+    ///
+    /// If the mark can't be written.
+    pub fn export_running_session(&self, running_session: Option<&Session>, value_separator: char, date_format: &str)
+    {
+        if self.is_read_only()
+        {
+            return;
+        }
+
+        let running_file_name = format!("{RUNNING_FILE_PREFIX}{}.txt", self.machine_name);
+        let running_path = Path::new(&self.database_path).join(&running_file_name);
+
+        match running_session
+        {
+            Some(session) =>
+            {
+                let line = [
+                    session.start.format(date_format).to_string(),
+                    session.description.clone(),
+                    session.tag.clone(),
+                    escape_notes(&session.notes, value_separator),
+                ]
+                .join(&value_separator.to_string());
+
+                self.write_lines(&running_path, &[line]).expect("Failed to write running session.");
+            }
+            None if self.storage.exists(&running_path) =>
+            {
+                self.storage.remove_file(&running_path).expect("Failed to remove running session.");
+            }
+            None => (),
+        }
+
+        self.remember_written_file_stamps(&[running_file_name]);
+    }
+
+    /// The sessions other machines marked as running on them, with the name of the machine.
+    pub fn import_running_sessions_elsewhere(&self, value_separator: char, date_format: &str) -> Vec<(String, Session)>
+    {
+        let own_file_name = format!("{RUNNING_FILE_PREFIX}{}.txt", self.machine_name);
+
+        self.list_running_files()
+            .into_iter()
+            .filter(|file_name| *file_name != own_file_name)
+            .filter_map(|file_name| {
+                let session = self.read_running_file(&file_name, value_separator, date_format)?;
+                let machine_name = file_name.strip_prefix(RUNNING_FILE_PREFIX)?.strip_suffix(".txt")?.to_string();
+
+                Some((machine_name, session))
+            })
+            .collect()
+    }
+
+    /// The session this machine marked as running, left behind by a run that ended without stopping it.
+    pub fn import_own_running_session(&self, value_separator: char, date_format: &str) -> Option<Session>
+    {
+        self.read_running_file(&format!("{RUNNING_FILE_PREFIX}{}.txt", self.machine_name), value_separator, date_format)
+    }
+
+    fn read_running_file(&self, file_name: &str, value_separator: char, date_format: &str) -> Option<Session>
+    {
+        let lines = self.decode_lines(&self.storage.read(&Path::new(&self.database_path).join(file_name)).ok()?)?;
+        let mut values = lines.first()?.split(value_separator);
+
+        let start = NaiveDateTime::parse_from_str(values.next()?, date_format).ok()?;
+        let mut session = Session::from(values.next()?, values.next()?, start, None);
+        session.notes = values.next().map(|notes| unescape_notes(notes, value_separator)).unwrap_or_default();
+
+        Some(session)
+    }
+
+    /// Forgets which months were loaded and what the files looked like, after they were replaced from outside.
+    pub fn reset_loaded_months(&self)
+    {
+        self.oldest_loaded_month.replace(self.get_page_start_month(None));
+        self.unloaded_max_session_id.set(None);
+        self.remember_file_stamps();
+    }
+
+    pub fn get_workspace(&self) -> &str
+    {
+        &self.workspace
+    }
+
+    /// The default workspace followed by the named ones in alphabetical order.
+    pub fn list_workspaces(&self) -> Vec<String>
+    {
+        let workspaces_path = Path::new(&self.root_path).join(WORKSPACES_FOLDER_NAME);
+
+        let mut workspaces = self
+            .storage
+            .list_names(&workspaces_path)
+            .map(|names| {
+                names
+                    .into_iter()
+                    .filter(|name| self.storage.is_dir(&workspaces_path.join(name)))
+                    .filter(|name| is_valid_workspace_name(name))
+                    .collect::<Vec<String>>()
+            })
+            .unwrap_or_default();
+
+        workspaces.sort();
+        workspaces.insert(0, DEFAULT_WORKSPACE.to_string());
+
+        workspaces
+    }
+
+    #[must_use]
+    pub fn get_session_line_id(session_string: &str, value_separator: char) -> Option<u64>
+    {
+        session_string.split(value_separator).nth(6).and_then(|id| id.parse::<u64>().ok()).filter(|id| *id > 0)
+    }
+
+    pub fn import_tags(&self) -> Option<Vec<String>>
+    {
+        let database_path = Path::new(&self.database_path);
+        let tags_path = database_path.join(&self.tags_file_name);
+
+        if let Ok(tags) = self.read_lines(&tags_path)
+        {
+            let tags = tags.into_iter().filter(|x| !x.is_empty()).collect::<Vec<String>>();
+
+            self.remember_file_stamps();
+
+            return Some(tags);
+        }
+
+        None
+    }
+
+    /// The tag of the last started session, kept in the state file so it survives restarts.
+    pub fn import_last_used_tag(&self) -> Option<String>
+    {
+        let state_path = Path::new(&self.database_path).join(&self.state_file_name);
+        let tables = parse_toml(&self.storage.read_to_string(&state_path).ok()?).ok()?;
+
+        get_table(&tables, "tags")?.entries.iter().find(|(key, _)| key == "last_used")?.1.as_str().map(str::to_string)
+    }
+
+    pub fn export_last_used_tag(&self, tag: &str)
+    {
+        if self.is_read_only() || self.import_last_used_tag().is_some_and(|last_used_tag| last_used_tag == tag)
+        {
+            return;
+        }
+
+        let state_path = Path::new(&self.database_path).join(&self.state_file_name);
+        let state = self.storage.read_to_string(&state_path).unwrap_or_default();
+
+        let _ = self.storage.write(&state_path, set_toml_value(&state, "tags", "last_used", &to_toml_string(tag)).as_bytes());
+    }
+
+    /// Conflict copies and the running marks of other machines count as well, so they are noticed as they show up.
+    fn get_file_stamps(&self) -> HashMap<String, FileStamp>
+    {
+        let database_path = Path::new(&self.database_path);
+
+        let mut file_names = vec![self.sessions_file_name.clone(), self.journal_file_name.clone(), self.tags_file_name.clone()];
+        file_names.extend(self.list_session_months().iter().map(|month| Self::get_month_file_name(month)));
+        file_names.extend(self.list_conflict_copies().into_iter().map(|(copy_name, _)| copy_name));
+        file_names.extend(self.list_running_files());
+
+        file_names
+            .into_iter()
+            .map(|file_name| {
+                let file_stamp = self.storage.get_modified_and_size(&database_path.join(&file_name));
+
+                (file_name, file_stamp)
+            })
+            .collect()
+    }
+
+    /// Takes all files as they are now as read, after they were read.
+    fn remember_file_stamps(&self)
+    {
+        self.known_file_stamps.replace(self.get_file_stamps());
+    }
+
+    /// Takes the files named in `file_names` as they are now as read, after this handler wrote or removed them. The
+    /// other files keep their stamps, so a change made to them from outside in the meantime is still noticed.
+    fn remember_written_file_stamps(&self, file_names: &[String])
+    {
+        let file_stamps = self.get_file_stamps();
+        let mut known_file_stamps = self.known_file_stamps.borrow_mut();
+
+        for file_name in file_names
+        {
+            match file_stamps.get(file_name)
+            {
+                Some(file_stamp) => known_file_stamps.insert(file_name.clone(), *file_stamp),
+                None => known_file_stamps.remove(file_name),
+            };
+        }
+    }
+
+    /// True if a sessions, journal or tags file changed since this handler last read or wrote it.
+    pub fn has_external_changes(&self) -> bool
+    {
+        self.get_file_stamps() != *self.known_file_stamps.borrow()
+    }
+
+    fn remove_empty_lines(&self, file_name: &String)
+    {
+        let database_path = Path::new(&self.database_path);
+        let file_path = database_path.join(file_name);
+
+        if let Ok(lines) = self.read_lines(&file_path)
+        {
+            let entries = lines.into_iter().filter(|x| !x.is_empty()).collect::<Vec<String>>();
+
+            if !entries.is_empty()
+            {
+                self.write_lines(&file_path, &entries).expect("Failed renaming after removing empty lines.");
+            }
+        }
+    }
+
+    /// Adds the deletion of the session with `session_id` to the journal.
+    ///
+    /// # Panics
+    ///
+    /// If the journal can't be written.
+    pub fn delete_session(&self, session_id: u64, value_separator: char, date_format: &str)
+    {
+        self.append_journal_record(&format!("{JOURNAL_DELETE}{session_id}"), value_separator, date_format)
+            .expect("Failed to write journal.");
+    }
+
+    /// Adds the edit of a session to the journal, `session_string` replacing the stored session with its id.
+    ///
+    /// # Panics
+    ///
+    /// If the journal can't be written.
+    pub fn update_session(&self, session_string: &str, value_separator: char, date_format: &str)
+    {
+        self.append_journal_record(&format!("{JOURNAL_EDIT}{session_string}"), value_separator, date_format)
+            .expect("Failed to write journal.");
+    }
+
+    /// Backs up the sessions unless the newest backup was taken today. Called when the journal is compacted, so the
+    /// whole history is copied once a day instead of every `JOURNAL_COMPACTION_THRESHOLD` records.
+    pub fn create_daily_backup(&self, value_separator: char, date_format: &str)
+    {
+        let newest_backup_time = self.list_backups().first().and_then(|backup_name| Self::get_backup_timestamp(backup_name));
+
+        if newest_backup_time.is_none_or(|backup_time| backup_time.date() < Local::now().date_naive())
+        {
+            self.create_backup(value_separator, date_format);
+        }
+    }
+
+    /// Stores the sessions, with the journal replayed, in the backups folder, keeping only the newest `max_backups`
+    /// copies. Nothing is stored for a workspace without sessions.
+    fn create_backup(&self, value_separator: char, date_format: &str)
+    {
+        if self.is_read_only()
+        {
+            return;
+        }
+
+        let database_path = Path::new(&self.database_path);
+        let backups_path = database_path.join(&self.backups_folder_name);
+
+        let get_backup_name = |time: NaiveDateTime| format!("sessions-{}.txt", time.format(BACKUP_TIMESTAMP_FORMAT));
+        let mut backup_time = Local::now().naive_local();
+
+        // A backup of the same millisecond is kept, the new one counts as a millisecond later.
+        while self.storage.exists(&backups_path.join(get_backup_name(backup_time)))
+        {
+            backup_time += TimeDelta::milliseconds(1);
+        }
+
+        let backup_name = get_backup_name(backup_time);
+
+        let session_strings = self.read_session_lines(value_separator, date_format, |_| true);
+
+        if session_strings.is_empty()
+        {
+            return;
+        }
+
+        if let Err(error) = self
+            .storage
+            .create_dir_all(&backups_path)
+            .and_then(|()| self.write_lines(&backups_path.join(&backup_name), &session_strings))
+        {
+            warn!("Writing backup {backup_name} failed: {error}");
+            return;
+        }
+
+        for outdated_backup in self.list_backups().iter().skip(self.max_backups)
+        {
+            let _ = self.storage.remove_file(&backups_path.join(outdated_backup));
+        }
+    }
+
+    /// Returns the file names of all backups, newest first.
+    pub fn list_backups(&self) -> Vec<String>
+    {
+        let backups_path = Path::new(&self.database_path).join(&self.backups_folder_name);
+
+        let Ok(file_names) = self.storage.list_names(&backups_path)
+        else
+        {
+            return Vec::new();
+        };
+
+        let mut backups = file_names.into_iter().filter(|name| Self::get_backup_timestamp(name).is_some()).collect::<Vec<String>>();
+
+        backups.sort_unstable_by(|a, b| b.cmp(a));
+
+        backups
+    }
+
+    #[must_use]
+    pub fn get_backup_timestamp(backup_name: &str) -> Option<NaiveDateTime>
+    {
+        let timestamp = backup_name.strip_prefix("sessions-")?.strip_suffix(".txt")?;
+
+        NaiveDateTime::parse_from_str(timestamp, BACKUP_TIMESTAMP_FORMAT)
+            .or_else(|_| NaiveDateTime::parse_from_str(timestamp, LEGACY_BACKUP_TIMESTAMP_FORMAT))
+            .ok()
+    }
+
+    /// Size in bytes of the files in the database folder that exist. The month files and the backups are each summed up
+    /// into one entry labeled with their count.
+    pub fn get_file_sizes(&self) -> Vec<(String, u64)>
+    {
+        let database_path = Path::new(&self.database_path);
+        let backups_path = database_path.join(&self.backups_folder_name);
+        let get_size = |path: PathBuf| self.storage.get_modified_and_size(&path).map(|(_, size)| size);
+
+        let mut file_sizes = Vec::new();
+
+        let months = self.list_session_months();
+
+        if !months.is_empty()
+        {
+            let size = months.iter().filter_map(|month| get_size(database_path.join(Self::get_month_file_name(month)))).sum();
+            file_sizes.push((format!("{MONTH_FILE_PREFIX}*.txt ({})", months.len()), size));
+        }
+
+        for file_name in [
+            &self.sessions_file_name,
+            &self.journal_file_name,
+            &self.tags_file_name,
+            &self.quarantine_file_name,
+            &self.audit_file_name,
+        ]
+        {
+            if let Some(size) = get_size(database_path.join(file_name))
+            {
+                file_sizes.push((file_name.clone(), size));
+            }
+        }
+
+        let backups = self.list_backups();
+
+        if !backups.is_empty()
+        {
+            let size = backups.iter().filter_map(|backup_name| get_size(backups_path.join(backup_name))).sum();
+            file_sizes.push((format!("{}/ ({})", self.backups_folder_name, backups.len()), size));
+        }
+
+        file_sizes
+    }
+
+    /// Appends an edit, or a deletion for `None`, of a stored session to the audit file.
+    ///
+    /// # Panics
+    ///
+    /// If the audit file can't be written.
+    pub fn record_audit_entry(&self, old_session_string: &str, new_session_string: Option<&str>)
+    {
+        if self.is_read_only()
+        {
+            return;
+        }
+
+        let audit_path = Path::new(&self.database_path).join(&self.audit_file_name);
+        let timestamp = Local::now().format(AUDIT_TIMESTAMP_FORMAT);
+        let [old_session_string, new_session_string] =
+            [Some(old_session_string), new_session_string].map(|line| line.unwrap_or_default().replace(AUDIT_SEPARATOR, " "));
+        let entry = format!("{timestamp}{AUDIT_SEPARATOR}{old_session_string}{AUDIT_SEPARATOR}{new_session_string}");
+
+        self.append_lines(&audit_path, &[entry]).expect("Failed to write audit file.");
+    }
+
+    /// Changes the remote storage didn't accept yet, as journal records in the order they were made.
+    pub fn import_remote_outbox(&self) -> Vec<String>
+    {
+        let outbox_path = Path::new(&self.database_path).join(&self.outbox_file_name);
+
+        self.read_lines(&outbox_path).unwrap_or_default().into_iter().filter(|line| !line.is_empty()).collect()
+    }
+
+    /// Replaces the changes waiting for the remote storage, removing the file once there are none left.
+    ///
+    /// # Errors
+    ///
+    /// If the outbox can't be written or removed.
+    pub fn export_remote_outbox(&self, records: &[String]) -> io::Result<()>
+    {
+        let outbox_path = Path::new(&self.database_path).join(&self.outbox_file_name);
+
+        if records.is_empty()
+        {
+            return match self.storage.remove_file(&outbox_path)
+            {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+                _ => Ok(()),
+            };
+        }
+
+        self.write_lines(&outbox_path, records)
+    }
+
+    /// The edit a previous run was making when it stopped, as written by `export_unsaved_edit`.
+    pub fn import_unsaved_edit(&self) -> Vec<String>
+    {
+        let unsaved_edit_path = Path::new(&self.database_path).join(&self.unsaved_edit_file_name);
+
+        self.read_lines(&unsaved_edit_path).unwrap_or_default()
+    }
+
+    /// Keeps the edit being made, removing the file once there is none.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be written or removed.
+    pub fn export_unsaved_edit(&self, lines: &[String]) -> io::Result<()>
+    {
+        if self.is_read_only()
+        {
+            return Ok(());
+        }
+
+        let unsaved_edit_path = Path::new(&self.database_path).join(&self.unsaved_edit_file_name);
+
+        if lines.is_empty()
+        {
+            return match self.storage.remove_file(&unsaved_edit_path)
+            {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+                _ => Ok(()),
+            };
+        }
+
+        self.write_lines(&unsaved_edit_path, lines)
+    }
+
+    /// Pairs of session id and Toggl time entry id for the sessions that are on Toggl, whichever side they came from.
+    pub fn import_toggl_links(&self, value_separator: char) -> Vec<(u64, u64)>
+    {
+        let toggl_path = Path::new(&self.database_path).join(&self.toggl_file_name);
+
+        self.read_lines(&toggl_path)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|line| {
+                let (session_id, entry_id) = line.split_once(value_separator)?;
+
+                Some((session_id.parse().ok()?, entry_id.parse().ok()?))
+            })
+            .collect()
+    }
+
+    /// Replaces the links between sessions and Toggl entries.
+    ///
+    /// # Errors
+    ///
+    /// If the links file can't be written.
+    pub fn export_toggl_links(&self, links: &[(u64, u64)], value_separator: char) -> io::Result<()>
+    {
+        if self.is_read_only()
+        {
+            return Ok(());
+        }
+
+        let toggl_path = Path::new(&self.database_path).join(&self.toggl_file_name);
+        let link_strings =
+            links.iter().map(|(session_id, entry_id)| format!("{session_id}{value_separator}{entry_id}")).collect::<Vec<String>>();
+
+        self.write_lines(&toggl_path, &link_strings)
+    }
+
+    /// The days marked as not worked. Lines that can't be read are skipped.
+    pub fn import_days_off(&self, value_separator: char) -> Vec<(NaiveDate, DayOff)>
+    {
+        let days_off_path = Path::new(&self.database_path).join(&self.days_off_file_name);
+
+        self.read_lines(&days_off_path)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|line| {
+                let (date, day_off) = line.split_once(value_separator)?;
+
+                Some((NaiveDate::parse_from_str(date, DAY_OFF_DATE_FORMAT).ok()?, DayOff::parse(day_off)?))
+            })
+            .collect()
+    }
+
+    /// Replaces the days marked as not worked.
+    ///
+    /// # Errors
+    ///
+    /// If the days off file can't be written.
+    pub fn export_days_off(&self, days_off: &[(NaiveDate, DayOff)], value_separator: char) -> io::Result<()>
+    {
+        if self.is_read_only()
+        {
+            return Ok(());
+        }
+
+        let days_off_path = Path::new(&self.database_path).join(&self.days_off_file_name);
+        let day_off_strings = days_off
+            .iter()
+            .map(|(date, day_off)| format!("{}{value_separator}{day_off}", date.format(DAY_OFF_DATE_FORMAT)))
+            .collect::<Vec<String>>();
+
+        self.write_lines(&days_off_path, &day_off_strings)
+    }
+
+    /// The recorded changes, newest first. Entries that can't be read are skipped.
+    pub fn import_audit_entries(&self, value_separator: char, date_format: &str) -> Vec<AuditEntry>
+    {
+        let audit_path = Path::new(&self.database_path).join(&self.audit_file_name);
+
+        let mut entries = self
+            .read_lines(&audit_path)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|line| {
+                let mut values = line.split(AUDIT_SEPARATOR);
+
+                let timestamp = NaiveDateTime::parse_from_str(values.next()?, AUDIT_TIMESTAMP_FORMAT).ok()?;
+                let old_session = Self::parse_session_line(values.next()?, value_separator, date_format)?;
+                let new_session = values.next().and_then(|line| Self::parse_session_line(line, value_separator, date_format));
+
+                Some(AuditEntry {
+                    timestamp,
+                    old_session,
+                    new_session,
+                })
+            })
+            .collect::<Vec<AuditEntry>>();
+
+        entries.reverse();
+
+        entries
+    }
+
+    pub fn count_backup_sessions(&self, backup_name: &str) -> usize
+    {
+        let backup_path = Path::new(&self.database_path).join(&self.backups_folder_name).join(backup_name);
+
+        self.read_lines(&backup_path).map_or(0, |lines| lines.iter().filter(|line| !line.is_empty()).count())
+    }
+
+    /// Replaces the sessions with the given backup. The current state is backed up first so a restore can be undone.
+    ///
+    /// # Errors
+    ///
+    /// If the backup can't be read or the sessions can't be written.
+    pub fn restore_backup(&self, backup_name: &str, value_separator: char, date_format: &str)
+        -> Result<(), Box<dyn std::error::Error>>
+    {
+        if self.is_read_only()
+        {
+            return Ok(());
+        }
+
+        let backup_path = Path::new(&self.database_path).join(&self.backups_folder_name).join(backup_name);
+        let backup = self.read_lines(&backup_path)?;
+
+        self.create_backup(value_separator, date_format);
+        self.write_sessions_files(&backup, value_separator, date_format)?;
+
+        info!("Restored {} sessions from backup {backup_name}", backup.len());
+
+        Ok(())
+    }
+}
+
+/// Workspace names become folder names, so only letters, digits, '-' and '_' are allowed.
+#[must_use]
+pub fn is_valid_workspace_name(name: &str) -> bool
+{
+    !name.is_empty() && name.chars().all(|character| character.is_alphanumeric() || character == '-' || character == '_')
+}
+
+/// The app's folder in a folder of the user: the one in the XDG `variable`, `fallback` in the home folder without it,
+/// or the roaming application data on Windows.
+fn get_user_folder(variable: &str, fallback: &str) -> Option<PathBuf>
+{
+    let user_folder = env::var_os(variable)
+        .filter(|folder| !folder.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(fallback)))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+
+    Some(user_folder.join(APP_FOLDER_NAME))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn store_session(database_handler: &DatabaseHandler, session_string: &str)
+    {
+        database_handler.export_session(session_string, VALUE_SEPARATOR, DATE_FORMAT).expect("Storing session failed.");
+    }
+
+    /// Writes `lines` to `file_name` in the database folder, as another machine or a sync service would.
+    fn write_file(database_handler: &DatabaseHandler, file_name: &str, lines: &[impl ToString])
+    {
+        let lines = lines.iter().map(ToString::to_string).collect::<Vec<String>>();
+
+        database_handler.write_lines(&Path::new(&database_handler.database_path).join(file_name), &lines).expect("Writing failed.");
+    }
+
+    fn read_file(database_handler: &DatabaseHandler, file_name: &str) -> Vec<String>
+    {
+        database_handler.read_lines(&Path::new(&database_handler.database_path).join(file_name)).unwrap_or_default()
+    }
+
+    fn file_exists(database_handler: &DatabaseHandler, file_name: &str) -> bool
+    {
+        database_handler.storage.exists(&Path::new(&database_handler.database_path).join(file_name))
+    }
+
+    /// A stored line of an hour long session on `date`, given like `12-01-2026`.
+    fn get_session_line(session_id: u64, date: &str, description: &str) -> String
+    {
+        format!("{date};{description};work;09:00:00;10:00:00;{date};{session_id};")
+    }
+
+    /// The ids of the loaded sessions, lowest first.
+    fn get_session_ids(database_handler: &DatabaseHandler) -> Vec<u64>
+    {
+        let (sessions, _) = database_handler.import_sessions(VALUE_SEPARATOR, DATE_FORMAT);
+        let mut session_ids = sessions.unwrap_or_default().iter().map(|session| session.id).collect::<Vec<u64>>();
+        session_ids.sort_unstable();
+
+        session_ids
+    }
+
+    fn get_descriptions(database_handler: &DatabaseHandler) -> Vec<String>
+    {
+        let (sessions, _) = database_handler.import_sessions(VALUE_SEPARATOR, DATE_FORMAT);
+
+        sessions.unwrap_or_default().into_iter().map(|session| session.description).collect()
+    }
+
+    /// Another run of the app on the same memory, which reads everything from the files again.
+    fn reopen(database_handler: &DatabaseHandler) -> DatabaseHandler
+    {
+        database_handler.open_workspace(database_handler.get_workspace(), None)
+    }
+
+    #[test]
+    fn journal_replays_adds_edits_and_deletes()
+    {
+        let database_handler = DatabaseHandler::new_ephemeral("journal-replay");
+        store_session(&database_handler, &get_session_line(1, "12-01-2026", "Plan the sprint"));
+        store_session(&database_handler, &get_session_line(2, "12-01-2026", "Review"));
+        store_session(&database_handler, &get_session_line(3, "13-01-2026", "Write the report"));
+        database_handler.update_session(&get_session_line(1, "12-01-2026", "Plan the release"), VALUE_SEPARATOR, DATE_FORMAT);
+        database_handler.delete_session(2, VALUE_SEPARATOR, DATE_FORMAT);
+
+        assert_eq!(database_handler.count_journal_records(), 5);
+        assert!(database_handler.list_session_months().is_empty());
+        assert_eq!(get_descriptions(&reopen(&database_handler)), ["Plan the release", "Write the report"]);
+    }
+
+    #[test]
+    fn journal_is_compacted_at_one_hundred_records()
+    {
+        let database_handler = DatabaseHandler::new_ephemeral("journal-compaction");
+
+        for session_id in 1..JOURNAL_COMPACTION_THRESHOLD as u64
+        {
+            store_session(&database_handler, &get_session_line(session_id, &format!("{:02}-01-2026", session_id % 28 + 1), "Work"));
+        }
+
+        assert_eq!(database_handler.count_journal_records(), JOURNAL_COMPACTION_THRESHOLD - 1);
+        assert!(database_handler.list_session_months().is_empty());
+
+        store_session(&database_handler, &get_session_line(100, "02-02-2026", "Work"));
+
+        assert_eq!(database_handler.count_journal_records(), 0);
+        assert_eq!(database_handler.list_session_months(), ["2026-01", "2026-02"]);
+        assert_eq!(read_file(&database_handler, "sessions-2026-01.txt").len(), 99);
+        assert_eq!(get_session_ids(&reopen(&database_handler)), (1..=100).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn interrupted_compaction_replays_the_journal_once()
+    {
+        let database_handler = DatabaseHandler::new_ephemeral("interrupted-compaction");
+        store_session(&database_handler, &get_session_line(1, "12-01-2026", "Plan the sprint"));
+        store_session(&database_handler, &get_session_line(2, "12-01-2026", "Review"));
+        database_handler.update_session(&get_session_line(1, "12-01-2026", "Plan the release"), VALUE_SEPARATOR, DATE_FORMAT);
+        database_handler.delete_session(2, VALUE_SEPARATOR, DATE_FORMAT);
+
+        // The month file was written, but the app stopped before the journal was emptied.
+        let journal = read_file(&database_handler, "journal.txt");
+        database_handler.compact_journal(VALUE_SEPARATOR, DATE_FORMAT).expect("Compacting failed.");
+        write_file(&database_handler, "journal.txt", &journal);
+
+        let database_handler = reopen(&database_handler);
+        assert_eq!(get_descriptions(&database_handler), ["Plan the release"]);
+
+        database_handler.compact_journal(VALUE_SEPARATOR, DATE_FORMAT).expect("Compacting failed.");
+        assert_eq!(read_file(&database_handler, "sessions-2026-01.txt"), [get_session_line(1, "12-01-2026", "Plan the release")]);
+    }
+
+    #[test]
+    fn edit_moves_a_session_to_the_file_of_its_new_month()
+    {
+        let database_handler = DatabaseHandler::new_ephemeral("edit-other-month");
+        store_session(&database_handler, &get_session_line(1, "30-01-2026", "Plan the sprint"));
+        store_session(&database_handler, &get_session_line(2, "31-01-2026", "Review"));
+        database_handler.compact_journal(VALUE_SEPARATOR, DATE_FORMAT).expect("Compacting failed.");
+
+        database_handler.update_session(&get_session_line(2, "02-02-2026", "Review"), VALUE_SEPARATOR, DATE_FORMAT);
+        assert_eq!(get_descriptions(&reopen(&database_handler)), ["Plan the sprint", "Review"]);
+
+        database_handler.compact_journal(VALUE_SEPARATOR, DATE_FORMAT).expect("Compacting failed.");
+        assert_eq!(read_file(&database_handler, "sessions-2026-01.txt"), [get_session_line(1, "30-01-2026", "Plan the sprint")]);
+        assert_eq!(read_file(&database_handler, "sessions-2026-02.txt"), [get_session_line(2, "02-02-2026", "Review")]);
+        assert_eq!(get_session_ids(&reopen(&database_handler)), [1, 2]);
+    }
+
+    #[test]
+    fn sessions_of_older_versions_are_split_by_month()
+    {
+        let database_handler = DatabaseHandler::new_ephemeral("monthly-sharding");
+        let session_lines = [
+            get_session_line(1, "31-12-2025", "Year review"),
+            get_session_line(2, "12-01-2026", "Plan the sprint"),
+            get_session_line(3, "13-01-2026", "Review"),
+            get_session_line(4, "02-02-2026", "Write the report"),
+        ];
+        write_file(&database_handler, "sessions.txt", &session_lines);
+
+        let database_handler = reopen(&database_handler);
+        assert_eq!(get_session_ids(&database_handler), [1, 2, 3, 4]);
+
+        assert!(!file_exists(&database_handler, "sessions.txt"));
+        assert_eq!(database_handler.list_session_months(), ["2025-12", "2026-01", "2026-02"]);
+        assert_eq!(read_file(&database_handler, "sessions-2025-12.txt"), session_lines[..1]);
+        assert_eq!(read_file(&database_handler, "sessions-2026-01.txt"), session_lines[1..3]);
+        assert_eq!(read_file(&database_handler, "sessions-2026-02.txt"), session_lines[3..]);
+        assert_eq!(get_session_ids(&reopen(&database_handler)), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn history_is_loaded_in_pages_of_five_hundred_sessions()
+    {
+        let database_handler = DatabaseHandler::new_ephemeral("lazy-paging");
+
+        // Twelve months of 100 sessions each, the newest page takes the last five of them.
+        for month in 1..=12_u64
+        {
+            let session_lines = (1..=100)
+                .map(|index| get_session_line((month - 1) * 100 + index, &format!("{:02}-{month:02}-2025", index % 28 + 1), "Work"))
+                .collect::<Vec<String>>();
+
+            write_file(&database_handler, &format!("sessions-2025-{month:02}.txt"), &session_lines);
+        }
+
+        let database_handler = reopen(&database_handler);
+        assert_eq!(get_session_ids(&database_handler).len(), SESSIONS_PAGE_SIZE);
+        assert!(database_handler.has_unloaded_sessions());
+        assert_eq!(database_handler.get_unloaded_max_session_id(VALUE_SEPARATOR, DATE_FORMAT), 700);
+
+        assert!(database_handler.load_older_sessions());
+        assert_eq!(get_session_ids(&database_handler).len(), 2 * SESSIONS_PAGE_SIZE);
+
+        assert!(database_handler.load_older_sessions());
+        assert_eq!(get_session_ids(&database_handler), (1..=1200).collect::<Vec<u64>>());
+        assert!(!database_handler.has_unloaded_sessions());
+        assert!(!database_handler.load_older_sessions());
+    }
+
+    #[test]
+    fn conflict_copies_keep_the_lines_of_both_machines()
+    {
+        let database_handler = DatabaseHandler::new_ephemeral("conflict-copy-merge");
+        store_session(&database_handler, &get_session_line(1, "12-01-2026", "Plan the sprint"));
+        store_session(&database_handler, &get_session_line(2, "12-01-2026", "Review"));
+        database_handler.compact_journal(VALUE_SEPARATOR, DATE_FORMAT).expect("Compacting failed.");
+        database_handler.export_tag(&String::from("work")).expect("Storing tag failed.");
+
+        // The other machine has the first session as well and added another one under the id of the second.
+        let copied_lines = [
+            get_session_line(1, "12-01-2026", "Plan the sprint"),
+            get_session_line(2, "13-01-2026", "Write the report"),
+        ];
+        write_file(&database_handler, "sessions-2026-01 (conflicted copy).txt", &copied_lines);
+        write_file(&database_handler, "tags.sync-conflict-20260114-093012-ABCD123.txt", &["docs", "work"]);
+
+        assert_eq!(database_handler.merge_conflict_copies(VALUE_SEPARATOR, DATE_FORMAT), 2);
+        assert!(!file_exists(&database_handler, "sessions-2026-01 (conflicted copy).txt"));
+        assert!(!file_exists(&database_handler, "tags.sync-conflict-20260114-093012-ABCD123.txt"));
+        assert_eq!(read_file(&database_handler, "tags.txt"), ["work", "docs"]);
+
+        let database_handler = reopen(&database_handler);
+        assert_eq!(get_session_ids(&database_handler), [1, 2, 3]);
+        assert_eq!(get_descriptions(&database_handler), ["Plan the sprint", "Review", "Write the report"]);
+    }
+
+    #[test]
+    fn git_merge_keeps_additions_of_both_sides_and_removals_of_either()
+    {
+        let database_handler = DatabaseHandler::new_ephemeral("git-union-sync");
+        let [plan, review, report, retro] = [
+            get_session_line(1, "12-01-2026", "Plan the sprint"),
+            get_session_line(2, "12-01-2026", "Review"),
+            get_session_line(3, "13-01-2026", "Write the report"),
+            get_session_line(4, "14-01-2026", "Retro"),
+        ];
+        let base = format!("{plan}\n{review}\n");
+        let ours = format!("{plan}\n{review}\n{report}\n");
+        let theirs = format!("{plan}\n{retro}\n");
+
+        let file_name = "sessions-2026-01.txt";
+        assert!(database_handler.merge_file_versions(file_name, Some(base.as_bytes()), Some(ours.as_bytes()), Some(theirs.as_bytes())));
+        assert_eq!(read_file(&database_handler, file_name), [plan.clone(), report, retro]);
+        assert_eq!(get_descriptions(&reopen(&database_handler)), ["Plan the sprint", "Write the report", "Retro"]);
+
+        // Both sides removed what was left of the month.
+        assert!(database_handler.merge_file_versions(file_name, Some(plan.as_bytes()), None, None));
+        assert!(!file_exists(&database_handler, file_name));
+    }
+
+    #[test]
+    fn journal_copy_does_not_reuse_the_id_of_a_deleted_session()
+    {
+        let database_handler = DatabaseHandler::new_ephemeral("replay-deleted-id");
+        store_session(&database_handler, "12-01-2026;Plan the sprint;meeting;09:00:00;10:15:00;12-01-2026;1;");
+        store_session(&database_handler, "12-01-2026;Review;meeting;11:00:00;11:30:00;12-01-2026;2;");
+        database_handler.delete_session(2, VALUE_SEPARATOR, DATE_FORMAT);
+        database_handler.compact_journal(VALUE_SEPARATOR, DATE_FORMAT).expect("Compacting failed.");
+
+        let copied_record = "+13-01-2026;Write the report;docs;09:00:00;12:00:00;13-01-2026;1;";
+        write_file(&database_handler, "journal (conflicted copy).txt", &[copied_record]);
+
+        assert_eq!(database_handler.merge_conflict_copies(VALUE_SEPARATOR, DATE_FORMAT), 1);
+        assert_eq!(get_session_ids(&database_handler), [1, 3]);
     }
 }
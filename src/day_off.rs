@@ -0,0 +1,52 @@
+//! Days marked as not worked. They are left out of the gap report and the idle reminder, and goals don't expect time
+//! tracked on them.
+
+use std::fmt::{Display, Formatter};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DayOff
+{
+    Vacation,
+    Holiday,
+    Sick,
+}
+
+impl DayOff
+{
+    pub const ALL: [DayOff; 3] = [DayOff::Vacation, DayOff::Holiday, DayOff::Sick];
+
+    /// Reads the name written by `Display`.
+    #[must_use]
+    pub fn parse(name: &str) -> Option<DayOff>
+    {
+        DayOff::ALL.into_iter().find(|day_off| day_off.to_string() == name.trim())
+    }
+
+    /// What marking a day again turns `day_off` into, going through every kind before unmarking it with `None`.
+    #[must_use]
+    pub fn get_next(day_off: Option<DayOff>) -> Option<DayOff>
+    {
+        match day_off
+        {
+            None => Some(DayOff::Vacation),
+            Some(DayOff::Vacation) => Some(DayOff::Holiday),
+            Some(DayOff::Holiday) => Some(DayOff::Sick),
+            Some(DayOff::Sick) => None,
+        }
+    }
+}
+
+impl Display for DayOff
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    {
+        let name = match self
+        {
+            DayOff::Vacation => "vacation",
+            DayOff::Holiday => "holiday",
+            DayOff::Sick => "sick",
+        };
+
+        write!(f, "{name}")
+    }
+}
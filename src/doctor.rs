@@ -0,0 +1,332 @@
+use crate::database_handler::{DatabaseHandler, RejectedLine, DATE_FORMAT, VALUE_SEPARATOR};
+use crate::json::JsonValue;
+use crate::session::Session;
+use chrono::NaiveDateTime;
+use std::collections::HashSet;
+use std::io::stdin;
+
+const DATE_DISPLAY_FORMAT: &str = "%d %b %Y %H:%M";
+
+/// A problem found in the database, with the automatic fix for it if there is one.
+pub struct Finding
+{
+    pub description: String,
+    pub fix: Option<String>,
+}
+
+/// The database with every automatic fix applied.
+pub struct Repair
+{
+    pub sessions: Vec<Session>,
+    pub quarantined_lines: Vec<RejectedLine>,
+    pub missing_tags: Vec<String>,
+}
+
+/// Checks the stored sessions of a workspace, lists what is wrong with them and offers to fix it. Returns false if
+/// problems were left unfixed. With `json` the findings are printed as a JSON object for scripts, without offering
+/// the fixes.
+pub fn run_doctor(database_handler: &DatabaseHandler, json: bool) -> bool
+{
+    let lines = database_handler.import_all_session_lines(VALUE_SEPARATOR, DATE_FORMAT);
+    let tags = database_handler.import_tags().unwrap_or_default();
+
+    if json
+    {
+        let (findings, _) = diagnose(&lines, &tags);
+        let findings_json = findings
+            .iter()
+            .map(|finding| {
+                JsonValue::Object(vec![
+                    (String::from("description"), JsonValue::String(finding.description.clone())),
+                    (String::from("fix"), finding.fix.clone().map_or(JsonValue::Null, JsonValue::String)),
+                ])
+            })
+            .collect();
+
+        let report = JsonValue::Object(vec![
+            (String::from("workspace"), JsonValue::String(database_handler.get_workspace().to_string())),
+            (String::from("findings"), JsonValue::Array(findings_json)),
+        ]);
+
+        println!("{report}");
+
+        return findings.is_empty();
+    }
+
+    println!("Checking workspace {}...", database_handler.get_workspace());
+
+    let (findings, repair) = diagnose(&lines, &tags);
+
+    if findings.is_empty()
+    {
+        println!("No problems found.");
+        return true;
+    }
+
+    for (index, finding) in findings.iter().enumerate()
+    {
+        println!("{:>4}. {}", index + 1, finding.description);
+
+        match &finding.fix
+        {
+            Some(fix) => println!("      fix: {fix}"),
+            None => println!("      no automatic fix, edit it in the app"),
+        }
+    }
+
+    let fixable_count = findings.iter().filter(|finding| finding.fix.is_some()).count();
+
+    println!();
+    let noun = if findings.len() == 1
+    {
+        "problem"
+    }
+    else
+    {
+        "problems"
+    };
+    println!("{} {noun} found, {fixable_count} can be fixed automatically.", findings.len());
+
+    if fixable_count == 0
+    {
+        return false;
+    }
+
+    if database_handler.is_read_only()
+    {
+        println!("The database is in use by another instance, close it to apply the fixes.");
+        return false;
+    }
+
+    println!("Apply the fixes? A backup of the sessions is made first. [y/N]");
+
+    let mut answer = String::new();
+
+    if stdin().read_line(&mut answer).is_err() || !answer.trim().eq_ignore_ascii_case("y")
+    {
+        println!("Nothing was changed.");
+        return false;
+    }
+
+    apply_repair(database_handler, &repair);
+
+    println!("Applied {fixable_count} fixes.");
+
+    fixable_count == findings.len()
+}
+
+/// True if the stored sessions of a workspace have no problems, for `--doctor --quiet`.
+pub fn is_healthy(database_handler: &DatabaseHandler) -> bool
+{
+    let lines = database_handler.import_all_session_lines(VALUE_SEPARATOR, DATE_FORMAT);
+    let tags = database_handler.import_tags().unwrap_or_default();
+
+    diagnose(&lines, &tags).0.is_empty()
+}
+
+/// Finds malformed lines, sessions that end before they start, duplicates, overlaps and tags missing from the tags
+/// file, in that order, as each fix can resolve findings of the later checks.
+#[must_use]
+pub fn diagnose(lines: &[String], tags: &[String]) -> (Vec<Finding>, Repair)
+{
+    let mut findings = Vec::new();
+    let mut quarantined_lines = Vec::new();
+
+    let (sessions, rejected_lines) = DatabaseHandler::parse_sessions(lines, VALUE_SEPARATOR, DATE_FORMAT);
+    let mut sessions = sessions.unwrap_or_default();
+
+    for rejected_line in rejected_lines
+    {
+        if let Some(session) = parse_with_swapped_start_and_end(&rejected_line.content)
+        {
+            findings.push(Finding {
+                description: format!(
+                    "\"{}\" ends at {} before it starts at {}",
+                    session.description,
+                    session.start.format(DATE_DISPLAY_FORMAT),
+                    session.end.unwrap_or(session.start).format(DATE_DISPLAY_FORMAT)
+                ),
+                fix: Some(String::from("swap its start and end")),
+            });
+
+            sessions.push(session);
+        }
+        else
+        {
+            findings.push(Finding {
+                description: format!("Malformed line \"{}\"", rejected_line.content),
+                fix: Some(String::from("move it to the quarantine file")),
+            });
+
+            quarantined_lines.push(rejected_line);
+        }
+    }
+
+    assign_unique_ids(&mut sessions);
+    sessions.sort_by_key(|session| session.start);
+
+    remove_duplicates(&mut sessions, &mut findings);
+    trim_overlaps(&mut sessions, &mut findings);
+
+    let mut missing_tags = Vec::<String>::new();
+
+    for session in &sessions
+    {
+        if !tags.contains(&session.tag) && !missing_tags.contains(&session.tag)
+        {
+            findings.push(Finding {
+                description: format!("Tag \"{}\" is used but missing from the tags file", session.tag),
+                fix: Some(String::from("add it to the tags file")),
+            });
+
+            missing_tags.push(session.tag.clone());
+        }
+    }
+
+    let repair = Repair {
+        sessions,
+        quarantined_lines,
+        missing_tags,
+    };
+
+    (findings, repair)
+}
+
+/// Reads a line that was rejected only because its end lies before its start, with the two swapped.
+fn parse_with_swapped_start_and_end(line: &str) -> Option<Session>
+{
+    let mut values = line.split(VALUE_SEPARATOR).collect::<Vec<&str>>();
+
+    if values.len() < 6 || values[5].is_empty()
+    {
+        return None;
+    }
+
+    values.swap(0, 5);
+    values.swap(3, 4);
+
+    let (sessions, _) = DatabaseHandler::parse_sessions(&[values.join(&VALUE_SEPARATOR.to_string())], VALUE_SEPARATOR, DATE_FORMAT);
+    let mut session = sessions?.pop()?;
+
+    // The id was assigned by parsing the single line, only keep one that was stored.
+    if values.get(6).and_then(|id| id.parse::<u64>().ok()).is_none()
+    {
+        session.id = 0;
+    }
+
+    Some(session)
+}
+
+/// Gives sessions without an id, or with one already taken, the next free one.
+fn assign_unique_ids(sessions: &mut [Session])
+{
+    let mut next_id = sessions.iter().map(|session| session.id).max().unwrap_or(0) + 1;
+    let mut used_ids = HashSet::new();
+
+    for session in sessions
+    {
+        if session.id == 0 || !used_ids.insert(session.id)
+        {
+            session.id = next_id;
+            next_id += 1;
+        }
+    }
+}
+
+/// Removes sessions stored more than once, with the same description, tag, start and end. `sessions` has to be sorted
+/// by start.
+fn remove_duplicates(sessions: &mut Vec<Session>, findings: &mut Vec<Finding>)
+{
+    let mut unique_sessions = Vec::<Session>::with_capacity(sessions.len());
+
+    for session in sessions.drain(..)
+    {
+        let is_duplicate = unique_sessions
+            .iter()
+            .rev()
+            .take_while(|other| other.start == session.start)
+            .any(|other| other.end == session.end && other.description == session.description && other.tag == session.tag);
+
+        if is_duplicate
+        {
+            findings.push(Finding {
+                description: format!("\"{}\" at {} is stored twice", session.description, session.start.format(DATE_DISPLAY_FORMAT)),
+                fix: Some(String::from("remove the copy")),
+            });
+        }
+        else
+        {
+            unique_sessions.push(session);
+        }
+    }
+
+    *sessions = unique_sessions;
+}
+
+/// Moves the start of sessions that begin before an earlier one ended to that end. Sessions lying entirely within an
+/// earlier one are only reported. `sessions` has to be sorted by start.
+fn trim_overlaps(sessions: &mut [Session], findings: &mut Vec<Finding>)
+{
+    let mut latest: Option<(String, NaiveDateTime)> = None;
+
+    for session in sessions.iter_mut()
+    {
+        let Some(end) = session.end
+        else
+        {
+            continue;
+        };
+
+        if let Some((latest_description, latest_end)) = &latest
+            && session.start < *latest_end
+        {
+            let description = format!(
+                "\"{}\" at {} overlaps \"{}\"",
+                session.description,
+                session.start.format(DATE_DISPLAY_FORMAT),
+                latest_description
+            );
+
+            if end > *latest_end
+            {
+                findings.push(Finding {
+                    description,
+                    fix: Some(format!("start it at {}", latest_end.format(DATE_DISPLAY_FORMAT))),
+                });
+
+                session.start = *latest_end;
+            }
+            else
+            {
+                findings.push(Finding {
+                    description,
+                    fix: None,
+                });
+                continue;
+            }
+        }
+
+        if latest.as_ref().is_none_or(|(_, latest_end)| end > *latest_end)
+        {
+            latest = Some((session.description.clone(), end));
+        }
+    }
+}
+
+fn apply_repair(database_handler: &DatabaseHandler, repair: &Repair)
+{
+    // Everything is written back, so nothing may be left unloaded.
+    database_handler.load_all_sessions();
+
+    if !repair.quarantined_lines.is_empty()
+    {
+        database_handler.quarantine_lines(&repair.quarantined_lines).expect("Failed to quarantine malformed sessions.");
+    }
+
+    for tag in &repair.missing_tags
+    {
+        database_handler.export_tag(tag).expect("Failed to store tag.");
+    }
+
+    database_handler.export_all_sessions(&repair.sessions, VALUE_SEPARATOR, DATE_FORMAT).expect("Failed to store repaired sessions.");
+}
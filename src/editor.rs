@@ -0,0 +1,107 @@
+use crate::crypto::get_random_bytes;
+use std::env;
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Used when neither `$VISUAL` nor `$EDITOR` is set.
+const FALLBACK_EDITOR: &str = "vi";
+
+/// Opens `text` in the user's editor and returns what was saved. The terminal has to be handed over while it runs,
+/// see `Out::suspend_while`. The editor variable may carry arguments, e.g. "code --wait".
+///
+/// # Errors
+///
+/// If the temporary file can't be written, or the editor isn't found or fails.
+pub fn edit_in_editor(text: &str) -> Result<String, String>
+{
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or(FALLBACK_EDITOR.to_string());
+
+    let folder = PrivateFolder::create().map_err(|_| String::from("CANNOT WRITE TEMP FILE"))?;
+    // Markdown, so editors highlight notes written as lists.
+    let path = folder.write_file("session.md", text).map_err(|_| String::from("CANNOT WRITE TEMP FILE"))?;
+
+    let status = Command::new("sh").arg("-c").arg(format!("{editor} \"$1\"")).arg("sh").arg(&path).status();
+    let edited = fs::read_to_string(&path);
+    drop(folder);
+
+    match status
+    {
+        Ok(status) if status.success() => edited.map_err(|_| String::from("CANNOT READ TEMP FILE")),
+        Ok(_) => Err(String::from("EDITOR FAILED")),
+        Err(_) => Err(String::from("EDITOR NOT FOUND")),
+    }
+}
+
+/// A folder in the temp folder only the user can open, for the plain text of sessions of encrypted workspaces too.
+/// Removed with everything in it when dropped.
+struct PrivateFolder
+{
+    path: PathBuf,
+}
+
+impl PrivateFolder
+{
+    /// Fails rather than reuse a folder that is already there, which someone else could have made.
+    fn create() -> io::Result<Self>
+    {
+        let suffix = u64::from_le_bytes(get_random_bytes(8)?.try_into().unwrap_or_default());
+        let path = env::temp_dir().join(format!("time-tracker-{suffix:016x}"));
+
+        let mut builder = DirBuilder::new();
+        #[cfg(unix)]
+        builder.mode(0o700);
+        builder.create(&path)?;
+
+        Ok(PrivateFolder {
+            path,
+        })
+    }
+
+    /// Writes `text` to a new file named `name` only the user can read, returning its path.
+    fn write_file(&self, name: &str, text: &str) -> io::Result<PathBuf>
+    {
+        let path = self.path.join(name);
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        options.open(&path)?.write_all(text.as_bytes())?;
+
+        Ok(path)
+    }
+}
+
+impl Drop for PrivateFolder
+{
+    fn drop(&mut self)
+    {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// The description on the first line, followed by a blank line and the notes, as the session is shown in the editor.
+#[must_use]
+pub fn format_session_text(description: &str, notes: &str) -> String
+{
+    format!("{description}\n\n{notes}")
+}
+
+/// Reads back what `format_session_text` wrote and the user changed: the first line is the description, the rest
+/// the notes. `None` if the description was removed.
+#[must_use]
+pub fn parse_session_text(text: &str) -> Option<(String, String)>
+{
+    let (description, notes) = text.trim_start().split_once('\n').unwrap_or((text.trim_start(), ""));
+    let description = description.trim();
+
+    (!description.is_empty()).then(|| (description.to_string(), notes.trim_start_matches(['\n', '\r']).trim_end().to_string()))
+}
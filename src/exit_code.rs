@@ -0,0 +1,16 @@
+//! Exit codes of the commands, kept stable so scripts and status bars can branch on them instead of parsing the
+//! output. `--quiet` leaves the exit code as the only answer.
+
+/// The command did what it was asked, `status` found a running session.
+pub const SUCCESS: i32 = 0;
+/// The command failed, e.g. a session to add overlaps others, a sync failed or the doctor left problems unfixed.
+pub const FAILURE: i32 = 1;
+/// The command line is invalid, nothing was done.
+pub const USAGE: i32 = 2;
+/// `status` found no running session on this machine.
+pub const NOT_RUNNING: i32 = 3;
+/// `stop` found no running session on this machine.
+pub const NOTHING_TO_STOP: i32 = 4;
+/// The database can't be opened, because another instance has it open or the passphrase is wrong, or can't be
+/// written.
+pub const DATABASE_ERROR: i32 = 5;
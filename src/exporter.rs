@@ -0,0 +1,341 @@
+use crate::app_state::ExportField;
+use crate::config::Rounding;
+use crate::importer::expand_home;
+use crate::issues::IssueTracker;
+use crate::session::{format_duration_short, Session};
+use crate::time_input::INPUT_DATE_FORMAT;
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use std::path::Path;
+use std::{cmp, fs};
+
+const ICALENDAR_DATETIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+const ICALENDAR_LINE_LIMIT: usize = 75;
+const DEFAULT_EXPORT_PATH: &str = "~/sessions.ics";
+pub const DEFAULT_DAILY_NOTE_TEMPLATE: &str = "**Time tracked**\n\n{sessions}\n\n{tags}\n\nTotal: **{total}**";
+// Surround the summary in the note so writing the day again replaces it. Obsidian doesn't render them.
+const DAILY_NOTE_START_MARKER: &str = "<!-- time-tracker -->";
+const DAILY_NOTE_END_MARKER: &str = "<!-- /time-tracker -->";
+
+pub struct ExportForm
+{
+    pub from: String,
+    pub to: String,
+    pub path: String,
+    /// Limits the export to these sessions, used when exporting a marked selection.
+    pub session_ids: Option<Vec<u64>>,
+    pub error: Option<String>,
+}
+
+impl Default for ExportForm
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl ExportForm
+{
+    #[must_use]
+    pub fn new() -> Self
+    {
+        ExportForm {
+            from: String::new(),
+            to: String::new(),
+            path: DEFAULT_EXPORT_PATH.to_string(),
+            session_ids: None,
+            error: None,
+        }
+    }
+
+    /// Defaults the range to the current month up to today for all sessions, keeping the last used path.
+    pub fn reset(&mut self, now: NaiveDateTime)
+    {
+        let today = now.date();
+
+        self.from = format!("{}", today.with_day(1).unwrap_or(today).format(INPUT_DATE_FORMAT));
+        self.to = format!("{}", today.format(INPUT_DATE_FORMAT));
+        self.session_ids = None;
+        self.error = None;
+    }
+
+    pub fn get_text_field_mut(&mut self, field: ExportField) -> &mut String
+    {
+        match field
+        {
+            ExportField::From => &mut self.from,
+            ExportField::To => &mut self.to,
+            ExportField::Path => &mut self.path,
+        }
+    }
+
+    /// The days typed into the from and to fields.
+    ///
+    /// # Errors
+    ///
+    /// The message shown in the popup when a date can't be read or the range ends before it starts.
+    pub fn get_range(&self) -> Result<(NaiveDate, NaiveDate), String>
+    {
+        let Ok(from) = NaiveDate::parse_from_str(self.from.trim(), INPUT_DATE_FORMAT)
+        else
+        {
+            return Err("INVALID FROM DATE (DD-MM-YYYY)".to_string());
+        };
+
+        let Ok(to) = NaiveDate::parse_from_str(self.to.trim(), INPUT_DATE_FORMAT)
+        else
+        {
+            return Err("INVALID TO DATE (DD-MM-YYYY)".to_string());
+        };
+
+        if to < from
+        {
+            return Err("TO DATE IS BEFORE FROM DATE".to_string());
+        }
+
+        Ok((from, to))
+    }
+}
+
+/// Writes the finished sessions that start within the range (inclusive) as an iCalendar file, one event per session
+/// with the tag as its category, the notes as its description and the address of the issue it refers to, if any.
+/// Durations are rounded, keeping the start time. Returns the number of exported sessions.
+///
+/// # Errors
+///
+/// If the file can't be written.
+pub fn export_icalendar(
+    sessions: &[Session],
+    from: NaiveDate,
+    to: NaiveDate,
+    path: &str,
+    rounding: Rounding,
+    issue_tracker: &IssueTracker,
+) -> Result<usize, String>
+{
+    let path = expand_home(path.trim());
+
+    if path.is_empty()
+    {
+        return Err("PATH IS EMPTY".to_string());
+    }
+
+    let timestamp = Utc::now().format(ICALENDAR_DATETIME_FORMAT);
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//time-tracker//sessions//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    let mut exported = 0;
+
+    for session in sessions
+    {
+        let Some(end) = session.end
+        else
+        {
+            continue;
+        };
+
+        let end = session.start + rounding.apply(end - session.start);
+        let date = session.start.date();
+
+        if date < from || date > to
+        {
+            continue;
+        }
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:session-{}-{}@time-tracker", session.id, session.start.format(ICALENDAR_DATETIME_FORMAT)));
+        lines.push(format!("DTSTAMP:{timestamp}Z"));
+        lines.push(format!("DTSTART:{}", format_icalendar_datetime(session.start)));
+        lines.push(format!("DTEND:{}", format_icalendar_datetime(end)));
+        lines.push(format!("SUMMARY:{}", escape_icalendar_text(&session.description)));
+        lines.push(format!("CATEGORIES:{}", escape_icalendar_text(&session.tag)));
+
+        if !session.notes.is_empty()
+        {
+            lines.push(format!("DESCRIPTION:{}", escape_icalendar_text(&session.notes)));
+        }
+
+        if let Some((_, issue_url)) = issue_tracker.find_issue(&session.description)
+        {
+            lines.push(format!("URL:{issue_url}"));
+        }
+
+        lines.push("END:VEVENT".to_string());
+
+        exported += 1;
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    let content = lines.iter().map(|line| fold_icalendar_line(line)).collect::<String>();
+
+    fs::write(&path, content).map_err(|error| format!("CANNOT WRITE FILE: {error}"))?;
+
+    Ok(exported)
+}
+
+/// Sessions are stored in local time; calendars get UTC so they show up correctly in any timezone.
+fn format_icalendar_datetime(datetime: NaiveDateTime) -> String
+{
+    match Local.from_local_datetime(&datetime).earliest()
+    {
+        Some(local) => format!("{}Z", local.with_timezone(&Utc).format(ICALENDAR_DATETIME_FORMAT)),
+        None => format!("{}", datetime.format(ICALENDAR_DATETIME_FORMAT)),
+    }
+}
+
+fn escape_icalendar_text(text: &str) -> String
+{
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+/// Content lines longer than 75 octets are split, continuing on the next line after a single space.
+fn fold_icalendar_line(line: &str) -> String
+{
+    let mut folded = String::new();
+    let mut line_length = 0;
+
+    for character in line.chars()
+    {
+        if line_length + character.len_utf8() > ICALENDAR_LINE_LIMIT
+        {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+
+        folded.push(character);
+        line_length += character.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
+
+/// Writes a summary of every day within the range (inclusive) that has finished sessions into its daily note, the path
+/// being `daily_note` formatted with the date. The summary is `template` with the sessions table, the time per tag and
+/// the total filled in, appended to the note or replacing the one written before. Returns the number of notes written.
+///
+/// # Errors
+///
+/// If a note can't be read or written.
+pub fn export_daily_notes(
+    sessions: &[Session],
+    from: NaiveDate,
+    to: NaiveDate,
+    daily_note: &str,
+    template: &str,
+    rounding: Rounding,
+) -> Result<usize, String>
+{
+    let mut days: Vec<(NaiveDate, Vec<(&Session, TimeDelta)>)> = Vec::new();
+
+    for session in sessions
+    {
+        let Some(end) = session.end
+        else
+        {
+            continue;
+        };
+
+        let date = session.start.date();
+
+        if date < from || date > to
+        {
+            continue;
+        }
+
+        let duration = rounding.apply(end - session.start);
+
+        match days.iter_mut().find(|(day, _)| *day == date)
+        {
+            Some((_, day_sessions)) => day_sessions.push((session, duration)),
+            None => days.push((date, vec![(session, duration)])),
+        }
+    }
+
+    for (date, day_sessions) in &mut days
+    {
+        day_sessions.sort_by_key(|(session, _)| session.start);
+
+        let path = expand_home(&date.format(daily_note).to_string());
+        let summary = format_daily_summary(*date, day_sessions, template);
+
+        write_daily_note(Path::new(&path), &summary).map_err(|error| format!("CANNOT WRITE DAILY NOTE: {error}"))?;
+    }
+
+    Ok(days.len())
+}
+
+fn format_daily_summary(date: NaiveDate, day_sessions: &[(&Session, TimeDelta)], template: &str) -> String
+{
+    let escape_cell = |text: &str| text.replace('|', "\\|").replace('\n', "<br>");
+
+    let mut table =
+        vec![String::from("| Start | End | Duration | Tag | Description |"), String::from("| --- | --- | --- | --- | --- |")];
+    let mut tag_totals: Vec<(&str, TimeDelta)> = Vec::new();
+
+    for (session, duration) in day_sessions
+    {
+        table.push(format!(
+            "| {} | {} | {} | {} | {} |",
+            session.start.format("%H:%M"),
+            (session.start + *duration).format("%H:%M"),
+            format_duration_short(*duration),
+            escape_cell(&session.tag),
+            escape_cell(&if session.notes.is_empty()
+            {
+                session.description.clone()
+            }
+            else
+            {
+                format!("{}\n{}", session.description, session.notes)
+            })
+        ));
+
+        match tag_totals.iter_mut().find(|(tag, _)| *tag == session.tag)
+        {
+            Some((_, total)) => *total += *duration,
+            None => tag_totals.push((&session.tag, *duration)),
+        }
+    }
+
+    tag_totals.sort_by_key(|(_, total)| cmp::Reverse(*total));
+
+    let tags = tag_totals.iter().map(|(tag, total)| format!("- {tag}: {}", format_duration_short(*total))).collect::<Vec<String>>();
+    let total = day_sessions.iter().map(|(_, duration)| *duration).sum();
+
+    template
+        .replace("{sessions}", &table.join("\n"))
+        .replace("{tags}", &tags.join("\n"))
+        .replace("{total}", &format_duration_short(total))
+        .replace("{date}", &date.format(INPUT_DATE_FORMAT).to_string())
+}
+
+/// Replaces the summary between the markers, or appends it after a blank line. Creates the note and its folder if
+/// they don't exist yet.
+fn write_daily_note(path: &Path, summary: &str) -> std::io::Result<()>
+{
+    let content = fs::read_to_string(path).unwrap_or_default();
+    let block = format!("{DAILY_NOTE_START_MARKER}\n{summary}\n{DAILY_NOTE_END_MARKER}");
+
+    let existing_block = content.find(DAILY_NOTE_START_MARKER).and_then(|start| {
+        content[start..].find(DAILY_NOTE_END_MARKER).map(|length| (start, start + length + DAILY_NOTE_END_MARKER.len()))
+    });
+
+    let content = match existing_block
+    {
+        Some((start, end)) => format!("{}{block}{}", &content[..start], &content[end..]),
+        None if content.trim().is_empty() => format!("{block}\n"),
+        None => format!("{}\n\n{block}\n", content.trim_end()),
+    };
+
+    if let Some(folder) = path.parent().filter(|folder| !folder.as_os_str().is_empty())
+    {
+        fs::create_dir_all(folder)?;
+    }
+
+    fs::write(path, content)
+}
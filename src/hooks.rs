@@ -0,0 +1,102 @@
+use crate::http;
+use crate::json::JsonValue;
+use crate::server::{session_to_json, JSON_DATE_FORMAT};
+use crate::session::Session;
+use chrono::NaiveDateTime;
+use log::warn;
+use std::process::{Command, Stdio};
+use std::thread::{self, JoinHandle};
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum HookEvent
+{
+    Start,
+    End,
+}
+
+impl HookEvent
+{
+    fn get_name(self) -> &'static str
+    {
+        match self
+        {
+            HookEvent::Start => "start",
+            HookEvent::End => "end",
+        }
+    }
+}
+
+/// What runs when a session starts or ends, see the `[hooks]` section of the config.
+pub struct Hooks
+{
+    pub start_command: Option<String>,
+    pub end_command: Option<String>,
+    pub url: Option<String>,
+}
+
+impl Hooks
+{
+    /// Runs the command of `event` and posts it to the URL on a thread of its own, so a slow hook never holds up the
+    /// app. Hooks that fail are logged, not retried.
+    #[must_use]
+    pub fn run(&self, event: HookEvent, session: &Session, now: NaiveDateTime) -> Option<JoinHandle<()>>
+    {
+        let command = match event
+        {
+            HookEvent::Start => self.start_command.clone(),
+            HookEvent::End => self.end_command.clone(),
+        };
+
+        if command.is_none() && self.url.is_none()
+        {
+            return None;
+        }
+
+        let payload = JsonValue::Object(vec![
+            (String::from("event"), JsonValue::String(event.get_name().to_string())),
+            (String::from("session"), session_to_json(session, now)),
+        ])
+        .to_string();
+        let environment = [
+            ("TT_EVENT", event.get_name().to_string()),
+            ("TT_DESCRIPTION", session.description.clone()),
+            ("TT_TAG", session.tag.clone()),
+            ("TT_START", session.start.format(JSON_DATE_FORMAT).to_string()),
+            ("TT_END", session.end.map(|end| end.format(JSON_DATE_FORMAT).to_string()).unwrap_or_default()),
+            ("TT_DURATION", (session.end.unwrap_or(now) - session.start).num_seconds().to_string()),
+        ];
+        let url = self.url.clone();
+
+        Some(thread::spawn(move || {
+            if let Some(command) = command
+            {
+                // Waited for so it doesn't linger as a zombie.
+                match Command::new("sh")
+                    .arg("-c")
+                    .arg(&command)
+                    .envs(environment)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status()
+                {
+                    Ok(status) if status.success() =>
+                    {}
+                    Ok(status) => warn!("{} hook \"{command}\" failed with {status}", event.get_name()),
+                    Err(error) => warn!("{} hook \"{command}\" could not be run: {error}", event.get_name()),
+                }
+            }
+
+            if let Some(url) = url
+            {
+                match http::request("POST", &url, &[String::from("Content-Type: application/json")], Some(&payload))
+                {
+                    Ok((200..=299, _)) =>
+                    {}
+                    Ok((status, _)) => warn!("{} hook to {url} answered {status}", event.get_name()),
+                    Err(error) => warn!("{} hook to {url} failed: {error}", event.get_name()),
+                }
+            }
+        }))
+    }
+}
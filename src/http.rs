@@ -0,0 +1,114 @@
+use log::debug;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const REQUEST_TIMEOUT_SECONDS: &str = "10";
+
+/// Runs a request through curl and returns the status code, 0 if the server couldn't be reached, with the response
+/// body. The request is handed over on stdin so tokens don't show up in the process list.
+///
+/// # Errors
+///
+/// If curl can't be run.
+pub fn request(method: &str, url: &str, headers: &[String], body: Option<&str>) -> Result<(u16, String), String>
+{
+    let mut options = vec![format!("url = {}", quote(url)), format!("request = {}", quote(method))];
+    options.extend(headers.iter().map(|header| format!("header = {}", quote(header))));
+
+    if let Some(body) = body
+    {
+        options.push(format!("data-raw = {}", quote(body)));
+    }
+
+    let mut curl = Command::new("curl")
+        .args(["--config", "-", "--silent", "--max-time", REQUEST_TIMEOUT_SECONDS, "--write-out", "\n%{http_code}"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|_| String::from("CURL NOT FOUND"))?;
+
+    if let Some(mut stdin) = curl.stdin.take()
+    {
+        stdin.write_all((options.join("\n") + "\n").as_bytes()).map_err(|_| String::from("REQUEST FAILED"))?;
+    }
+
+    let output = curl.wait_with_output().map_err(|_| String::from("REQUEST FAILED"))?;
+    let output = String::from_utf8_lossy(&output.stdout);
+    let (body, status) = output.rsplit_once('\n').unwrap_or_default();
+    let status = status.trim().parse::<u16>().unwrap_or_default();
+
+    debug!("{method} {url} answered {status}");
+
+    Ok((status, body.to_string()))
+}
+
+/// Quotes a value for a curl config file, escaping line breaks and tabs so a value can't end its line and add options.
+fn quote(value: &str) -> String
+{
+    let mut quoted = String::from("\"");
+
+    for character in value.chars()
+    {
+        match character
+        {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\u{b}' => quoted.push_str("\\v"),
+            _ => quoted.push(character),
+        }
+    }
+
+    quoted.push('"');
+
+    quoted
+}
+
+/// The `Authorization` header for HTTP basic authentication.
+#[must_use]
+pub fn get_basic_authorization(user: &str, password: &str) -> String
+{
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let credentials = format!("{user}:{password}").into_bytes();
+    let mut encoded = String::new();
+
+    for chunk in credentials.chunks(3)
+    {
+        let bytes = [0, 1, 2].map(|index| chunk.get(index).copied().unwrap_or(0));
+        let bits = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+
+        for index in 0..4
+        {
+            encoded.push(
+                if index <= chunk.len()
+                {
+                    char::from(ALPHABET[(bits >> (18 - index * 6)) as usize & 63])
+                }
+                else
+                {
+                    '='
+                },
+            );
+        }
+    }
+
+    format!("Authorization: Basic {encoded}")
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn quoted_value_stays_on_its_line()
+    {
+        assert_eq!(quote("Bearer abc"), "\"Bearer abc\"");
+        assert_eq!(quote("a\"b\\c"), "\"a\\\"b\\\\c\"");
+        assert_eq!(quote("x\nurl = \"file:///etc/passwd\"\r\t"), "\"x\\nurl = \\\"file:///etc/passwd\\\"\\r\\t\"");
+    }
+}
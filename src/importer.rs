@@ -0,0 +1,489 @@
+use crate::app_state::CsvMappingField;
+use crate::csv::{detect_separator, parse_csv};
+use crate::json::{parse_json, JsonValue};
+use crate::session::Session;
+use crate::time_input::parse_time;
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::fs;
+
+pub struct ImportedSessions
+{
+    pub sessions: Vec<Session>,
+    pub invalid_entries: usize,
+}
+
+pub enum ImportedFile
+{
+    Sessions(ImportedSessions),
+    Csv(CsvMapping),
+}
+
+/// Column assignment for a CSV file in an unknown layout. The first row is treated as the header.
+pub struct CsvMapping
+{
+    pub rows: Vec<Vec<String>>,
+    pub description_column: usize,
+    pub tag_column: Option<usize>,
+    pub start_column: usize,
+    pub end_column: usize,
+    pub date_format_index: usize,
+}
+
+pub struct ImportSummary
+{
+    pub imported: usize,
+    pub duplicates: usize,
+    pub invalid: usize,
+}
+
+const TOGGL_DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%m/%d/%Y", "%d.%m.%Y"];
+const TIMECLOCK_DATE_FORMATS: [&str; 2] = ["%Y/%m/%d", "%Y-%m-%d"];
+const TIMEWARRIOR_DATETIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+/// Date formats offered for mapped CSV columns, with the label shown in the wizard.
+pub const CSV_DATE_FORMATS: [(&str, &str); 6] = [
+    ("%Y-%m-%d", "YYYY-MM-DD"),
+    ("%d-%m-%Y", "DD-MM-YYYY"),
+    ("%d.%m.%Y", "DD.MM.YYYY"),
+    ("%d/%m/%Y", "DD/MM/YYYY"),
+    ("%m/%d/%Y", "MM/DD/YYYY"),
+    ("%Y/%m/%d", "YYYY/MM/DD"),
+];
+const FALLBACK_TAG: &str = "imported";
+const FALLBACK_DESCRIPTION: &str = "(no description)";
+
+/// Reads a file exported by another time tracker, detecting its format from the content. CSV files in an unknown
+/// layout are returned for column mapping.
+///
+/// # Errors
+///
+/// If the file can't be read or its format isn't recognized.
+pub fn import_file(path: &str) -> Result<ImportedFile, String>
+{
+    let path = expand_home(path.trim());
+    let content = fs::read_to_string(&path).map_err(|error| format!("CANNOT READ FILE: {error}"))?;
+
+    if content.trim_start().starts_with('[')
+    {
+        return parse_timewarrior_export(&content).map(ImportedFile::Sessions);
+    }
+
+    if is_timeclock(&content)
+    {
+        return Ok(ImportedFile::Sessions(parse_timeclock(&content)));
+    }
+
+    let rows = parse_csv(&content, detect_separator(&content));
+
+    if let Some(header) = rows.first()
+        && is_toggl_header(header)
+    {
+        return Ok(ImportedFile::Sessions(parse_toggl_rows(&rows)));
+    }
+
+    if rows.len() < 2 || rows[0].len() < 2
+    {
+        return Err("UNKNOWN FILE FORMAT".to_string());
+    }
+
+    Ok(ImportedFile::Csv(CsvMapping::new(rows)))
+}
+
+#[must_use]
+pub fn expand_home(path: &str) -> String
+{
+    if let Some(rest) = path.strip_prefix("~/")
+        && let Ok(home) = std::env::var("HOME")
+    {
+        return format!("{home}/{rest}");
+    }
+
+    path.to_string()
+}
+
+fn is_toggl_header(header: &[String]) -> bool
+{
+    ["Description", "Start date", "Start time", "End date", "End time"].iter().all(|column| header.iter().any(|field| field == column))
+}
+
+/// Maps a Toggl Track detailed report export. The project becomes the tag; entries without a project fall back to
+/// their first Toggl tag.
+fn parse_toggl_rows(rows: &[Vec<String>]) -> ImportedSessions
+{
+    let header = &rows[0];
+    let column = |name: &str| header.iter().position(|field| field == name);
+
+    let description_column = column("Description");
+    let project_column = column("Project");
+    let tags_column = column("Tags");
+    let start_date_column = column("Start date");
+    let start_time_column = column("Start time");
+    let end_date_column = column("End date");
+    let end_time_column = column("End time");
+
+    let mut imported = ImportedSessions {
+        sessions: Vec::new(),
+        invalid_entries: 0,
+    };
+
+    for row in rows.iter().skip(1)
+    {
+        let field = |index: Option<usize>| index.and_then(|index| row.get(index)).map_or("", |field| field.trim());
+
+        let start = parse_toggl_datetime(field(start_date_column), field(start_time_column));
+        let end = parse_toggl_datetime(field(end_date_column), field(end_time_column));
+
+        let (Some(start), Some(end)) = (start, end)
+        else
+        {
+            imported.invalid_entries += 1;
+            continue;
+        };
+
+        if end < start
+        {
+            imported.invalid_entries += 1;
+            continue;
+        }
+
+        let description = match field(description_column)
+        {
+            "" => FALLBACK_DESCRIPTION,
+            description => description,
+        };
+
+        let project = field(project_column);
+        let first_tag = field(tags_column).split(',').map(str::trim).find(|tag| !tag.is_empty());
+
+        let tag = if project.is_empty()
+        {
+            first_tag.unwrap_or(FALLBACK_TAG)
+        }
+        else
+        {
+            project
+        };
+
+        imported.sessions.push(Session::from(description, tag, start, Some(end)));
+    }
+
+    imported
+}
+
+fn parse_toggl_datetime(date: &str, time: &str) -> Option<NaiveDateTime>
+{
+    let date = TOGGL_DATE_FORMATS.iter().find_map(|format| NaiveDate::parse_from_str(date, format).ok())?;
+    let time = parse_time(time)?;
+
+    Some(date.and_time(time))
+}
+
+/// Maps a `timew export` document. The first tag becomes the tag; the annotation, or the remaining tags if there is
+/// none, becomes the description. Still running intervals are counted as invalid.
+fn parse_timewarrior_export(content: &str) -> Result<ImportedSessions, String>
+{
+    let Some(JsonValue::Array(intervals)) = parse_json(content)
+    else
+    {
+        return Err("INVALID TIMEWARRIOR JSON".to_string());
+    };
+
+    let mut imported = ImportedSessions {
+        sessions: Vec::new(),
+        invalid_entries: 0,
+    };
+
+    for interval in &intervals
+    {
+        let start = interval.get("start").and_then(JsonValue::as_str).and_then(parse_timewarrior_datetime);
+        let end = interval.get("end").and_then(JsonValue::as_str).and_then(parse_timewarrior_datetime);
+
+        let (Some(start), Some(end)) = (start, end)
+        else
+        {
+            imported.invalid_entries += 1;
+            continue;
+        };
+
+        if end < start
+        {
+            imported.invalid_entries += 1;
+            continue;
+        }
+
+        let tags: Vec<&str> = interval
+            .get("tags")
+            .and_then(JsonValue::as_array)
+            .map(|tags| tags.iter().filter_map(JsonValue::as_str).map(str::trim).filter(|tag| !tag.is_empty()).collect())
+            .unwrap_or_default();
+
+        let tag = tags.first().copied().unwrap_or(FALLBACK_TAG);
+
+        let description = match interval.get("annotation").and_then(JsonValue::as_str).map(str::trim)
+        {
+            Some(annotation) if !annotation.is_empty() => annotation.to_string(),
+            _ if tags.len() > 1 => tags[1..].join(", "),
+            _ => FALLBACK_DESCRIPTION.to_string(),
+        };
+
+        imported.sessions.push(Session::from(&description, tag, start, Some(end)));
+    }
+
+    Ok(imported)
+}
+
+/// Timewarrior stores UTC timestamps; sessions are kept in local time.
+fn parse_timewarrior_datetime(datetime: &str) -> Option<NaiveDateTime>
+{
+    let utc = NaiveDateTime::parse_from_str(datetime, TIMEWARRIOR_DATETIME_FORMAT).ok()?;
+
+    Some(Utc.from_utc_datetime(&utc).with_timezone(&Local).naive_local())
+}
+
+fn is_timeclock(content: &str) -> bool
+{
+    content.lines().map(str::trim_start).any(|line| line.starts_with("i ") || line.starts_with("I "))
+}
+
+/// Maps a ledger/hledger timeclock file. Each clock-in line (`i DATE TIME ACCOUNT  DESCRIPTION`) is paired with the
+/// following clock-out line; the account becomes the tag. Unpaired lines are counted as invalid.
+fn parse_timeclock(content: &str) -> ImportedSessions
+{
+    let mut imported = ImportedSessions {
+        sessions: Vec::new(),
+        invalid_entries: 0,
+    };
+
+    let mut clock_in: Option<(NaiveDateTime, String, String)> = None;
+
+    for line in content.lines().map(str::trim)
+    {
+        if line.is_empty() || line.starts_with([';', '#', '*'])
+        {
+            continue;
+        }
+
+        let mut parts = line.splitn(4, ' ');
+        let code = parts.next().unwrap_or_default();
+        let datetime = parse_timeclock_datetime(parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+        let rest = parts.next().unwrap_or_default().trim();
+
+        match (code, datetime)
+        {
+            ("i" | "I", Some(start)) =>
+            {
+                if clock_in.is_some()
+                {
+                    imported.invalid_entries += 1;
+                }
+
+                let (account, description) = rest.split_once("  ").or_else(|| rest.split_once('\t')).unwrap_or((rest, ""));
+
+                clock_in = Some((start, account.trim().to_string(), description.trim().to_string()));
+            }
+            ("o" | "O", Some(end)) =>
+            {
+                let Some((start, account, description)) = clock_in.take()
+                else
+                {
+                    imported.invalid_entries += 1;
+                    continue;
+                };
+
+                if end < start
+                {
+                    imported.invalid_entries += 1;
+                    continue;
+                }
+
+                let tag = if account.is_empty()
+                {
+                    FALLBACK_TAG
+                }
+                else
+                {
+                    &account
+                };
+
+                let description = if description.is_empty()
+                {
+                    FALLBACK_DESCRIPTION
+                }
+                else
+                {
+                    &description
+                };
+
+                imported.sessions.push(Session::from(description, tag, start, Some(end)));
+            }
+            _ =>
+            {
+                imported.invalid_entries += 1;
+            }
+        }
+    }
+
+    if clock_in.is_some()
+    {
+        imported.invalid_entries += 1;
+    }
+
+    imported
+}
+
+fn parse_timeclock_datetime(date: &str, time: &str) -> Option<NaiveDateTime>
+{
+    let date = TIMECLOCK_DATE_FORMATS.iter().find_map(|format| NaiveDate::parse_from_str(date, format).ok())?;
+    let time = parse_time(time)?;
+
+    Some(date.and_time(time))
+}
+
+impl CsvMapping
+{
+    /// Guesses the columns from common header names, falling back to the first columns in order.
+    #[must_use]
+    pub fn new(rows: Vec<Vec<String>>) -> Self
+    {
+        let find_column =
+            |names: &[&str]| rows[0].iter().position(|field| names.iter().any(|name| field.trim().eq_ignore_ascii_case(name)));
+
+        let description_column = find_column(&["description", "task", "summary", "title", "name"]).unwrap_or(0);
+        let tag_column = find_column(&["tag", "tags", "project", "category", "activity"]);
+        let start_column = find_column(&["start", "from", "begin", "started"]).unwrap_or(1);
+        let end_column = find_column(&["end", "to", "stop", "finish", "ended"]).unwrap_or(start_column + 1);
+
+        let mut mapping = CsvMapping {
+            rows,
+            description_column,
+            tag_column,
+            start_column,
+            end_column,
+            date_format_index: 0,
+        };
+
+        mapping.end_column = mapping.end_column.min(mapping.get_column_count() - 1);
+        mapping.date_format_index = mapping.guess_date_format_index();
+
+        mapping
+    }
+
+    pub fn get_column_count(&self) -> usize
+    {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    #[must_use]
+    pub fn get_header(&self, column: usize) -> &str
+    {
+        self.rows[0].get(column).map_or("", |field| field.trim())
+    }
+
+    #[must_use]
+    pub fn get_sample(&self, column: usize) -> &str
+    {
+        self.rows.get(1).and_then(|row| row.get(column)).map_or("", |field| field.trim())
+    }
+
+    #[must_use]
+    pub fn get_date_format_label(&self) -> &'static str
+    {
+        CSV_DATE_FORMATS[self.date_format_index].1
+    }
+
+    /// Moves the selected field to the next or previous column; the tag can also be left unmapped.
+    pub fn cycle(&mut self, field: CsvMappingField, forward: bool)
+    {
+        let column_count = self.get_column_count();
+        let step = |column: usize, count: usize| {
+            if forward
+            {
+                (column + 1) % count
+            }
+            else
+            {
+                (column + count - 1) % count
+            }
+        };
+
+        match field
+        {
+            CsvMappingField::Description => self.description_column = step(self.description_column, column_count),
+            CsvMappingField::Start => self.start_column = step(self.start_column, column_count),
+            CsvMappingField::End => self.end_column = step(self.end_column, column_count),
+            CsvMappingField::DateFormat => self.date_format_index = step(self.date_format_index, CSV_DATE_FORMATS.len()),
+            CsvMappingField::Tag =>
+            {
+                // Column `column_count` stands for "no tag column".
+                let column = self.tag_column.unwrap_or(column_count);
+                let column = step(column, column_count + 1);
+
+                self.tag_column = (column < column_count).then_some(column);
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn to_imported_sessions(&self) -> ImportedSessions
+    {
+        let date_format = CSV_DATE_FORMATS[self.date_format_index].0;
+
+        let mut imported = ImportedSessions {
+            sessions: Vec::new(),
+            invalid_entries: 0,
+        };
+
+        for row in self.rows.iter().skip(1)
+        {
+            let field = |index: usize| row.get(index).map_or("", |field| field.trim());
+
+            let start = parse_csv_datetime(field(self.start_column), date_format);
+            let end = parse_csv_datetime(field(self.end_column), date_format);
+
+            let (Some(start), Some(end)) = (start, end)
+            else
+            {
+                imported.invalid_entries += 1;
+                continue;
+            };
+
+            if end < start
+            {
+                imported.invalid_entries += 1;
+                continue;
+            }
+
+            let description = match field(self.description_column)
+            {
+                "" => FALLBACK_DESCRIPTION,
+                description => description,
+            };
+
+            let tag = match self.tag_column.map_or("", field)
+            {
+                "" => FALLBACK_TAG,
+                tag => tag,
+            };
+
+            imported.sessions.push(Session::from(description, tag, start, Some(end)));
+        }
+
+        imported
+    }
+
+    fn guess_date_format_index(&self) -> usize
+    {
+        let sample = self.get_sample(self.start_column);
+
+        CSV_DATE_FORMATS.iter().position(|(format, _)| parse_csv_datetime(sample, format).is_some()).unwrap_or(0)
+    }
+}
+
+/// Accepts a date and a time separated by a space or `T`, e.g. `2024-05-01 09:30` or `2024-05-01T09:30:00`.
+fn parse_csv_datetime(datetime: &str, date_format: &str) -> Option<NaiveDateTime>
+{
+    let (date, time) = datetime.split_once([' ', 'T'])?;
+    let date = NaiveDate::parse_from_str(date.trim(), date_format).ok()?;
+    let time = parse_time(time.trim())?;
+
+    Some(date.and_time(time))
+}
@@ -0,0 +1,168 @@
+use crate::http;
+use crate::json::parse_json;
+
+const GITHUB_URL: &str = "https://github.com";
+const GITHUB_API_URL: &str = "https://api.github.com";
+const GITLAB_URL: &str = "https://gitlab.com";
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum IssueProvider
+{
+    GitHub,
+    GitLab,
+}
+
+/// Where the issues descriptions refer to live. Works without a token, which is only needed to list the issues assigned
+/// to the user.
+pub struct IssueTracker
+{
+    pub provider: IssueProvider,
+    /// Web address of a self-hosted instance, `None` for github.com or gitlab.com.
+    pub url: Option<String>,
+    /// The repository of bare `#12` references, as `owner/repo`.
+    pub repository: Option<String>,
+    pub token: Option<String>,
+}
+
+/// An open issue assigned to the user, offered while typing a description.
+pub struct Issue
+{
+    /// `owner/repo#12`
+    pub reference: String,
+    pub title: String,
+}
+
+impl IssueTracker
+{
+    /// The first issue `description` refers to, as `owner/repo#12`, with its web address. Takes the address of an issue,
+    /// `owner/repo#12` or `#12` for an issue of the configured repository.
+    #[must_use]
+    pub fn find_issue(&self, description: &str) -> Option<(String, String)>
+    {
+        let base_url = self.get_base_url();
+
+        description.split_whitespace().find_map(|word| {
+            let word = word.trim_matches(|character: char| matches!(character, ',' | '.' | ':' | '(' | ')' | '[' | ']'));
+
+            let (repository, number) = if let Some(path) = word.strip_prefix(&base_url).and_then(|path| path.strip_prefix('/'))
+            {
+                let (repository, number) = path.rsplit_once("/issues/")?;
+                (repository.trim_end_matches("/-").to_string(), number)
+            }
+            else
+            {
+                let (repository, number) = word.split_once('#')?;
+                let repository = if repository.is_empty()
+                {
+                    self.repository.clone()?
+                }
+                else
+                {
+                    repository.to_string()
+                };
+                (repository, number)
+            };
+
+            let is_valid_repository = repository.contains('/')
+                && repository.chars().all(|character| character.is_ascii_alphanumeric() || matches!(character, '/' | '-' | '_' | '.'));
+            let number = number.parse::<u64>().ok().filter(|number| *number > 0)?;
+
+            is_valid_repository.then(|| (format!("{repository}#{number}"), self.get_issue_url(&repository, number)))
+        })
+    }
+
+    /// How an issue is best written in a description: `#12` for issues of the configured repository.
+    #[must_use]
+    pub fn get_short_reference(&self, reference: &str) -> String
+    {
+        match (reference.split_once('#'), &self.repository)
+        {
+            (Some((repository, number)), Some(own_repository)) if repository == own_repository => format!("#{number}"),
+            _ => reference.to_string(),
+        }
+    }
+
+    /// The open issues assigned to the owner of the token.
+    ///
+    /// # Errors
+    ///
+    /// If the server can't be reached, refuses the token or answers with something other than issues.
+    pub fn fetch_assigned_issues(&self) -> Result<Vec<Issue>, String>
+    {
+        let Some(token) = &self.token
+        else
+        {
+            return Ok(Vec::new());
+        };
+
+        let (url, headers) = match self.provider
+        {
+            IssueProvider::GitHub =>
+            {
+                let api_url = self.url.as_ref().map_or_else(|| GITHUB_API_URL.to_string(), |url| format!("{url}/api/v3"));
+
+                (
+                    format!("{api_url}/issues?filter=assigned&state=open&per_page=100"),
+                    vec![format!("Authorization: Bearer {token}"), String::from("Accept: application/vnd.github+json")],
+                )
+            }
+            IssueProvider::GitLab => (
+                format!("{}/api/v4/issues?scope=assigned_to_me&state=opened&per_page=100", self.get_base_url()),
+                vec![format!("PRIVATE-TOKEN: {token}")],
+            ),
+        };
+
+        let (status, body) = http::request("GET", &url, &headers, None)?;
+
+        let issues = match status
+        {
+            200..=299 => parse_json(&body).ok_or("ISSUES SENT INVALID JSON")?,
+            0 => return Err(String::from("ISSUES UNREACHABLE")),
+            401 | 403 => return Err(String::from("ISSUES TOKEN REJECTED")),
+            status => return Err(format!("ISSUES ERROR {status}")),
+        };
+
+        Ok(issues
+            .as_array()
+            .unwrap_or_default()
+            .iter()
+            // GitHub lists pull requests as issues too.
+            .filter(|issue| issue.get("pull_request").is_none())
+            .filter_map(|issue| {
+                let reference = match self.provider
+                {
+                    IssueProvider::GitHub =>
+                    {
+                        format!("{}#{}", issue.get("repository")?.get("full_name")?.as_str()?, issue.get("number")?.as_i64()?)
+                    }
+                    IssueProvider::GitLab => issue.get("references")?.get("full")?.as_str()?.to_string(),
+                };
+
+                Some(Issue {
+                    reference,
+                    title: issue.get("title")?.as_str()?.to_string(),
+                })
+            })
+            .collect())
+    }
+
+    fn get_base_url(&self) -> String
+    {
+        let default_url = match self.provider
+        {
+            IssueProvider::GitHub => GITHUB_URL,
+            IssueProvider::GitLab => GITLAB_URL,
+        };
+
+        self.url.as_deref().unwrap_or(default_url).trim_end_matches('/').to_string()
+    }
+
+    fn get_issue_url(&self, repository: &str, number: u64) -> String
+    {
+        match self.provider
+        {
+            IssueProvider::GitHub => format!("{}/{repository}/issues/{number}", self.get_base_url()),
+            IssueProvider::GitLab => format!("{}/{repository}/-/issues/{number}", self.get_base_url()),
+        }
+    }
+}
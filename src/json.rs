@@ -0,0 +1,364 @@
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::str::Chars;
+
+pub enum JsonValue
+{
+    Null,
+    Bool(bool),
+    Number(f64),
+    /// Numbers without a fraction or exponent, like ids.
+    Integer(i64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue
+{
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&JsonValue>
+    {
+        match self
+        {
+            JsonValue::Object(members) => members.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str>
+    {
+        match self
+        {
+            JsonValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64>
+    {
+        match self
+        {
+            JsonValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_array(&self) -> Option<&[JsonValue]>
+    {
+        match self
+        {
+            JsonValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_object(&self) -> Option<&[(String, JsonValue)]>
+    {
+        match self
+        {
+            JsonValue::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    /// Any number, with or without a fraction.
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64>
+    {
+        match self
+        {
+            JsonValue::Number(value) => Some(*value),
+            JsonValue::Integer(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the value as compact JSON.
+impl Display for JsonValue
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(value) => write!(f, "{value}"),
+            JsonValue::Number(value) if value.is_finite() => write!(f, "{value}"),
+            JsonValue::Number(_) => write!(f, "null"),
+            JsonValue::Integer(value) => write!(f, "{value}"),
+            JsonValue::String(value) => write_string(f, value),
+            JsonValue::Array(values) =>
+            {
+                write!(f, "[")?;
+
+                for (index, value) in values.iter().enumerate()
+                {
+                    if index > 0
+                    {
+                        write!(f, ",")?;
+                    }
+
+                    write!(f, "{value}")?;
+                }
+
+                write!(f, "]")
+            }
+            JsonValue::Object(members) =>
+            {
+                write!(f, "{{")?;
+
+                for (index, (name, value)) in members.iter().enumerate()
+                {
+                    if index > 0
+                    {
+                        write!(f, ",")?;
+                    }
+
+                    write_string(f, name)?;
+                    write!(f, ":{value}")?;
+                }
+
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut Formatter<'_>, value: &str) -> std::fmt::Result
+{
+    write!(f, "\"")?;
+
+    for character in value.chars()
+    {
+        match character
+        {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            character if character.is_control() => write!(f, "\\u{:04x}", u32::from(character))?,
+            character => write!(f, "{character}")?,
+        }
+    }
+
+    write!(f, "\"")
+}
+
+/// Parses a complete JSON document. Returns `None` if the content isn't valid JSON.
+#[must_use]
+pub fn parse_json(content: &str) -> Option<JsonValue>
+{
+    let mut characters = content.chars().peekable();
+    let value = parse_value(&mut characters)?;
+
+    skip_whitespace(&mut characters);
+
+    if characters.next().is_some()
+    {
+        return None;
+    }
+
+    Some(value)
+}
+
+fn parse_value(characters: &mut Peekable<Chars>) -> Option<JsonValue>
+{
+    skip_whitespace(characters);
+
+    match characters.peek()?
+    {
+        '{' => parse_object(characters),
+        '[' => parse_array(characters),
+        '"' => parse_string(characters).map(JsonValue::String),
+        't' => parse_literal(characters, "true", JsonValue::Bool(true)),
+        'f' => parse_literal(characters, "false", JsonValue::Bool(false)),
+        'n' => parse_literal(characters, "null", JsonValue::Null),
+        _ => parse_number(characters),
+    }
+}
+
+fn parse_object(characters: &mut Peekable<Chars>) -> Option<JsonValue>
+{
+    characters.next();
+
+    let mut members = Vec::new();
+
+    skip_whitespace(characters);
+
+    if characters.peek() == Some(&'}')
+    {
+        characters.next();
+        return Some(JsonValue::Object(members));
+    }
+
+    loop
+    {
+        skip_whitespace(characters);
+
+        let name = parse_string(characters)?;
+
+        skip_whitespace(characters);
+
+        if characters.next()? != ':'
+        {
+            return None;
+        }
+
+        members.push((name, parse_value(characters)?));
+
+        skip_whitespace(characters);
+
+        match characters.next()?
+        {
+            ',' =>
+            {}
+            '}' => return Some(JsonValue::Object(members)),
+            _ => return None,
+        }
+    }
+}
+
+fn parse_array(characters: &mut Peekable<Chars>) -> Option<JsonValue>
+{
+    characters.next();
+
+    let mut values = Vec::new();
+
+    skip_whitespace(characters);
+
+    if characters.peek() == Some(&']')
+    {
+        characters.next();
+        return Some(JsonValue::Array(values));
+    }
+
+    loop
+    {
+        values.push(parse_value(characters)?);
+
+        skip_whitespace(characters);
+
+        match characters.next()?
+        {
+            ',' =>
+            {}
+            ']' => return Some(JsonValue::Array(values)),
+            _ => return None,
+        }
+    }
+}
+
+fn parse_string(characters: &mut Peekable<Chars>) -> Option<String>
+{
+    if characters.next()? != '"'
+    {
+        return None;
+    }
+
+    let mut value = String::new();
+
+    loop
+    {
+        match characters.next()?
+        {
+            '"' => return Some(value),
+            '\\' => match characters.next()?
+            {
+                '"' => value.push('"'),
+                '\\' => value.push('\\'),
+                '/' => value.push('/'),
+                'b' => value.push('\u{8}'),
+                'f' => value.push('\u{c}'),
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                'u' => value.push(parse_unicode_escape(characters)?),
+                _ => return None,
+            },
+            character => value.push(character),
+        }
+    }
+}
+
+/// Decodes the hex digits after `\u`, combining UTF-16 surrogate pairs into a single character.
+fn parse_unicode_escape(characters: &mut Peekable<Chars>) -> Option<char>
+{
+    let high = parse_hex_code_unit(characters)?;
+
+    if !(0xD800..0xDC00).contains(&high)
+    {
+        return char::from_u32(high);
+    }
+
+    if characters.next()? != '\\' || characters.next()? != 'u'
+    {
+        return None;
+    }
+
+    let low = parse_hex_code_unit(characters)?;
+
+    if !(0xDC00..0xE000).contains(&low)
+    {
+        return None;
+    }
+
+    char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+}
+
+fn parse_hex_code_unit(characters: &mut Peekable<Chars>) -> Option<u32>
+{
+    let mut code_unit = 0;
+
+    for _ in 0..4
+    {
+        code_unit = code_unit * 16 + characters.next()?.to_digit(16)?;
+    }
+
+    Some(code_unit)
+}
+
+fn parse_number(characters: &mut Peekable<Chars>) -> Option<JsonValue>
+{
+    let mut number = String::new();
+
+    while let Some(&character) = characters.peek()
+    {
+        if !(character.is_ascii_digit() || matches!(character, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            break;
+        }
+
+        number.push(character);
+        characters.next();
+    }
+
+    number.parse().map(JsonValue::Integer).or_else(|_| number.parse().map(JsonValue::Number)).ok()
+}
+
+fn parse_literal(characters: &mut Peekable<Chars>, literal: &str, value: JsonValue) -> Option<JsonValue>
+{
+    for expected in literal.chars()
+    {
+        if characters.next()? != expected
+        {
+            return None;
+        }
+    }
+
+    Some(value)
+}
+
+fn skip_whitespace(characters: &mut Peekable<Chars>)
+{
+    while characters.peek().is_some_and(|character| character.is_whitespace())
+    {
+        characters.next();
+    }
+}
@@ -0,0 +1,57 @@
+//! Sessions, their storage and everything computed from them, usable by any frontend. The types most frontends need
+//! are re-exported here: open a workspace with [`DatabaseHandler`], read its [`Session`]s and sum them up with
+//! [`report`].
+//!
+//! The terminal app is behind the `tui` feature, on by default. It only draws the state kept by
+//! `app_manager::AppManager` and feeds it events through `tui::events::update`; without the feature the crate has no
+//! terminal dependencies.
+
+pub use config::Config;
+pub use database_handler::DatabaseHandler;
+pub use report::{ReportFormat, ReportGrouping};
+pub use session::Session;
+pub use storage::Storage;
+
+pub mod activitywatch;
+#[cfg(feature = "tui")]
+pub mod app_manager;
+pub mod app_state;
+pub mod clipboard;
+pub mod clock;
+#[cfg(feature = "tui")]
+pub mod command;
+pub mod config;
+pub mod crypto;
+pub mod csv;
+pub mod daemon;
+pub mod database_handler;
+pub mod day_off;
+pub mod doctor;
+pub mod editor;
+pub mod exit_code;
+pub mod exporter;
+pub mod hooks;
+pub mod http;
+pub mod importer;
+pub mod issues;
+pub mod json;
+pub mod logger;
+pub mod manual_entry;
+pub mod notes_editor;
+pub mod notifications;
+pub mod onboarding;
+pub mod remote;
+pub mod report;
+pub mod server;
+pub mod session;
+pub mod sprites;
+pub mod status;
+pub mod storage;
+pub mod sync;
+pub mod text;
+pub mod text_input;
+pub mod time_input;
+pub mod toggl;
+pub mod toml;
+#[cfg(feature = "tui")]
+pub mod tui;
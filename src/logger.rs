@@ -0,0 +1,125 @@
+use chrono::Local;
+use log::{LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::panic;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Name of the log file in the database folder, shared by all workspaces.
+pub const LOG_FILE_NAME: &str = "time-tracker.log";
+/// Records kept in memory for the debug overlay.
+const MAX_RECENT_RECORDS: usize = 200;
+/// A larger log file is moved to `time-tracker.log.old` on start, replacing the previous one.
+const MAX_LOG_FILE_SIZE: u64 = 1024 * 1024;
+const LOG_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+static LOGGER: Logger = Logger {
+    file: Mutex::new(None),
+    recent_records: Mutex::new(VecDeque::new()),
+};
+
+/// Writes every record as one line to the log file and keeps the last ones for the debug overlay.
+struct Logger
+{
+    file: Mutex<Option<File>>,
+    recent_records: Mutex<VecDeque<String>>,
+}
+
+impl Log for Logger
+{
+    fn enabled(&self, metadata: &Metadata) -> bool
+    {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record)
+    {
+        if !self.enabled(record.metadata())
+        {
+            return;
+        }
+
+        let target = record.target().strip_prefix("time_tracker_core::").unwrap_or(record.target());
+        let line = format!("{} {:<5} {}: {}", Local::now().format(LOG_TIMESTAMP_FORMAT), record.level(), target, record.args());
+
+        if let Ok(mut file) = self.file.lock()
+            && let Some(file) = file.as_mut()
+        {
+            let _ = writeln!(file, "{line}");
+        }
+
+        if let Ok(mut recent_records) = self.recent_records.lock()
+        {
+            if recent_records.len() == MAX_RECENT_RECORDS
+            {
+                recent_records.pop_front();
+            }
+
+            recent_records.push_back(line);
+        }
+    }
+
+    fn flush(&self)
+    {
+        if let Ok(mut file) = self.file.lock()
+            && let Some(file) = file.as_mut()
+        {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Routes the `log` macros to `log_path`, or only to memory without one, e.g. for `--ephemeral`. Records up to info
+/// are kept until `set_log_level` applies the level from the config. Panics are logged as errors before the default
+/// panic message is printed.
+pub fn init_logging(log_path: Option<&Path>)
+{
+    if log::set_logger(&LOGGER).is_ok()
+    {
+        log::set_max_level(LevelFilter::Info);
+
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |panic_info| {
+            log::error!("{panic_info}");
+            log::logger().flush();
+            default_hook(panic_info);
+        }));
+    }
+
+    let Some(log_path) = log_path
+    else
+    {
+        return;
+    };
+
+    if fs::metadata(log_path).is_ok_and(|metadata| metadata.len() > MAX_LOG_FILE_SIZE)
+    {
+        let mut old_log_path = log_path.as_os_str().to_owned();
+        old_log_path.push(".old");
+        let _ = fs::rename(log_path, old_log_path);
+    }
+
+    if let Some(folder_path) = log_path.parent()
+    {
+        let _ = fs::create_dir_all(folder_path);
+    }
+
+    if let Ok(mut file) = LOGGER.file.lock()
+    {
+        *file = OpenOptions::new().create(true).append(true).open(log_path).ok();
+    }
+}
+
+pub fn set_log_level(level: LevelFilter)
+{
+    log::set_max_level(level);
+}
+
+/// The last records logged, oldest first.
+pub fn get_recent_records() -> Vec<String>
+{
+    LOGGER.recent_records.lock().map(|recent_records| recent_records.iter().cloned().collect()).unwrap_or_default()
+}
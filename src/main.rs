@@ -1,1318 +1,3450 @@
-use crate::app_manager::AppManager;
-use crate::app_state::*;
-use chrono::{NaiveDateTime, TimeDelta};
-use colors::*;
-use control_keys::*;
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
 use crossterm::event;
-use crossterm::event::KeyCode;
-use io::{ColorType, Out, Vector2};
-use sprites::*;
+use ratatui::layout::{Constraint, Layout};
 use std::cmp;
-use std::ops::Add;
+use std::env;
+use std::io;
+use std::io::IsTerminal;
+use std::process;
+use std::time::{Duration, Instant};
+use time_tracker_core::app_manager::AppManager;
+use time_tracker_core::app_state::*;
+use time_tracker_core::command::run_session_command;
+use time_tracker_core::config::GoalPeriod;
+use time_tracker_core::crypto::Cipher;
+use time_tracker_core::daemon::run_daemon;
+use time_tracker_core::database_handler::{is_valid_workspace_name, AuditEntry, DatabaseHandler, DEFAULT_WORKSPACE};
+use time_tracker_core::doctor::{is_healthy, run_doctor};
+use time_tracker_core::exit_code;
+use time_tracker_core::logger::{get_recent_records, init_logging};
+use time_tracker_core::onboarding::{apply_onboarding, ask_onboarding, Onboarding};
+use time_tracker_core::report::{
+    format_duration_delta, format_log, format_percentage_change, get_flex_time_text, get_log_text, get_report_text,
+};
+use time_tracker_core::server::{run_server, session_to_json, DEFAULT_PORT};
+use time_tracker_core::session::{format_duration, format_duration_short, format_duration_without_seconds, Session};
+use time_tracker_core::sprites::*;
+use time_tracker_core::status::{get_status_text, DEFAULT_STATUS_FORMAT};
+use time_tracker_core::text::{
+    ellipsize, get_display_width, get_tail_within_width, pad_to_width, split_arguments, truncate_to_width, wrap_to_width,
+};
+use time_tracker_core::text_input::TextInput;
+use time_tracker_core::tui::colors::*;
+use time_tracker_core::tui::control_keys::*;
+use time_tracker_core::tui::events::{import_file, tick, update};
+use time_tracker_core::tui::io::{read_passphrase, ClickTarget, ColorType, Out, Vector2};
+use time_tracker_core::tui::widgets::*;
+
+#[cfg(test)]
+mod snapshot_tests;
+
+/// Longest the loop waits for input before the tick work runs and the screen is drawn again, so clocks keep moving.
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+/// How long input that queued up, e.g. a paste, is handled before the screen is drawn again.
+const FRAME_BUDGET: Duration = Duration::from_millis(16);
+/// Smallest terminal the session list and popups are laid out for.
+const MIN_TERMINAL_WIDTH: u16 = 80;
+const MIN_TERMINAL_HEIGHT: u16 = 20;
+/// Width of the first list column, where the modification of the selected session is labeled.
+const COMMAND_COLUMN_WIDTH: u16 = 5;
+/// Issues listed below the tags in the stats, those with the most time.
+const MAX_STATS_ISSUES: usize = 5;
+const COMMANDS: [&str; 7] = ["status", "report", "log", "add", "amend", "stop", "batch"];
+/// Options followed by a value, which is not taken for the command when they come before it.
+const VALUE_OPTIONS: [&str; 12] =
+    ["--workspace", "-w", "--port", "--format", "--from", "--to", "--group-by", "--tag", "--date", "--limit", "--start", "--end"];
+/// Printed for a command line that names an unknown command.
+const USAGE: &str = "\
+Usage: time-tracker [--workspace <name>] [<command>] [--json] [--quiet]
+
+Without a command the app opens. The commands are:
+  status   the session running on this machine, for status bars
+  report   the tracked time per day, week, tag or issue
+  log      the stored sessions
+  add      a session that was not tracked
+  amend    fixes the latest stored session
+  stop     ends the session running on this machine
+  batch    runs the commands read from stdin, one per line
+
+Instead of a command: --encrypt, --decrypt, --doctor, --daemon, --serve [--port <port>] or --ephemeral.";
 
-mod app_state;
-mod colors;
-mod control_keys;
-mod database_handler;
-mod io;
-mod session;
-mod sprites;
+fn main()
+{
+    let workspace = get_workspace_argument();
+    let command = get_command();
+    // Every command prints JSON instead of text with it, see `print_session` for the sessions.
+    let json = has_argument("--json");
+    // Leaves the exit code as the only answer of a command, see `exit_code`.
+    let quiet = has_argument("--quiet") || has_argument("-q");
+
+    if let Some(command) = command.as_deref().filter(|command| !COMMANDS.contains(command))
+    {
+        eprintln!("Unknown command {command}.\n\n{USAGE}");
+        process::exit(exit_code::USAGE);
+    }
 
-mod app_manager;
+    if has_argument("--ephemeral")
+    {
+        init_logging(None);
+        run_app(AppManager::new_ephemeral(&workspace));
+        return;
+    }
 
-fn main()
+    // The first run of the app asks where to keep the database before anything is written, commands and scripts use
+    // the default folder.
+    let opens_app = command.is_none() && !["--encrypt", "--decrypt", "--doctor", "--daemon", "--serve"].into_iter().any(has_argument);
+    let onboarding = (opens_app && DatabaseHandler::find_root_path().is_none() && io::stdin().is_terminal()).then(run_onboarding);
+
+    init_logging(Some(&DatabaseHandler::get_log_file_path()));
+
+    // A running daemon answers without the passphrase.
+    if command.as_deref() == Some("status")
+    {
+        print_status(&workspace, json, quiet);
+        return;
+    }
+
+    let (passphrase, cipher) = unlock_workspace(&workspace).unzip();
+
+    if has_argument("--encrypt") || has_argument("--decrypt")
+    {
+        change_encryption(&workspace, cipher, has_argument("--encrypt"));
+        return;
+    }
+
+    if has_argument("--doctor")
+    {
+        let database_handler = DatabaseHandler::new(&workspace, cipher);
+        let is_healthy = if quiet
+        {
+            is_healthy(&database_handler)
+        }
+        else
+        {
+            run_doctor(&database_handler, json)
+        };
+
+        process::exit(
+            if is_healthy
+            {
+                exit_code::SUCCESS
+            }
+            else
+            {
+                exit_code::FAILURE
+            },
+        );
+    }
+
+    if command.as_deref() == Some("report")
+    {
+        print_report(&workspace, cipher, json, quiet);
+        return;
+    }
+
+    if command.as_deref() == Some("log")
+    {
+        print_log(&workspace, cipher, json, quiet);
+        return;
+    }
+
+    if matches!(command.as_deref(), Some("add" | "amend" | "stop"))
+    {
+        run_command(AppManager::new_headless(&workspace, passphrase, cipher), json, quiet);
+        return;
+    }
+
+    if command.as_deref() == Some("batch")
+    {
+        run_batch(AppManager::new_headless(&workspace, passphrase, cipher), json, quiet);
+        return;
+    }
+
+    if has_argument("--daemon")
+    {
+        run_daemon(&DatabaseHandler::new(&workspace, cipher));
+        return;
+    }
+
+    if has_argument("--serve")
+    {
+        run_server(&DatabaseHandler::new(&workspace, cipher), get_port_argument());
+        return;
+    }
+
+    let mut app_manager = AppManager::new(&workspace, passphrase, cipher);
+
+    if let Some(onboarding) = onboarding
+    {
+        finish_onboarding(&mut app_manager, onboarding);
+    }
+
+    run_app(app_manager);
+}
+
+/// Asks the first run questions and creates the database folder with the answers.
+fn run_onboarding() -> Onboarding
+{
+    let Some(onboarding) = ask_onboarding(&mut io::stdin().lock(), &mut io::stdout(), &DatabaseHandler::get_default_root_path())
+    else
+    {
+        eprintln!("Setup cancelled, nothing was created.");
+        process::exit(exit_code::FAILURE);
+    };
+
+    if let Err(error) = apply_onboarding(&onboarding)
+    {
+        eprintln!("Creating the database in {} failed: {error}", onboarding.database_path.display());
+        process::exit(exit_code::DATABASE_ERROR);
+    }
+
+    onboarding
+}
+
+/// Stores the tags chosen on the first run, selecting the first one, and imports the chosen file.
+fn finish_onboarding(app_manager: &mut AppManager, onboarding: Onboarding)
+{
+    for tag in onboarding.tags
+    {
+        app_manager.tag_buffer.set(&tag);
+        app_manager.try_store_tag();
+    }
+
+    if !app_manager.tags.is_empty()
+    {
+        app_manager.set_selected_tag_index(0);
+    }
+
+    if let Some(import_path) = onboarding.import_path
+    {
+        app_manager.import_path_buffer = import_path;
+        app_manager.state = CommandState::Import(ImportState::Path);
+        import_file(app_manager);
+    }
+}
+
+fn run_app(mut app_manager: AppManager)
 {
-    let mut app_manager = AppManager::new();
     app_manager.renderer.clear_screen();
 
-    while app_manager.running
+    let mut last_tick = Instant::now();
+
+    while app_manager.running
+    {
+        render(&mut app_manager);
+
+        app_manager.renderer.check_color_stacks();
+
+        if event::poll(TICK_INTERVAL.saturating_sub(last_tick.elapsed())).expect("Input Error")
+        {
+            let frame_start = Instant::now();
+
+            // Everything that queued up is handled before the next frame, as long as it fits the budget.
+            loop
+            {
+                update(&mut app_manager, &event::read().expect("Input Error"));
+
+                if !app_manager.running || frame_start.elapsed() >= FRAME_BUDGET || !event::poll(Duration::ZERO).expect("Input Error")
+                {
+                    break;
+                }
+            }
+        }
+
+        if last_tick.elapsed() >= TICK_INTERVAL
+        {
+            tick(&mut app_manager);
+            last_tick = Instant::now();
+        }
+    }
+
+    close_app(app_manager);
+}
+
+/// Syncs the changes and waits for the hooks, then restores the terminal to report what failed. Exits with 1 if
+/// anything did.
+fn close_app(mut app_manager: AppManager)
+{
+    // Before the push, so the table of sessions on Toggl goes along.
+    let toggl_error = app_manager.sync_with_toggl();
+    let sync_error = app_manager.push_to_sync_remote();
+
+    app_manager.wait_for_hooks();
+
+    // Restores the terminal before anything is printed.
+    drop(app_manager);
+
+    if let Some(error) = &toggl_error
+    {
+        eprintln!("Toggl sync failed: {error}");
+    }
+
+    if let Some(error) = &sync_error
+    {
+        eprintln!("Sync failed: {error}");
+    }
+
+    if toggl_error.is_some() || sync_error.is_some()
+    {
+        process::exit(exit_code::FAILURE);
+    }
+}
+
+/// `time-tracker status [--format <format>]`, exiting with `NOT_RUNNING` without a running session.
+fn print_status(workspace: &str, json: bool, quiet: bool)
+{
+    let format = if json
+    {
+        String::from("json")
+    }
+    else
+    {
+        get_format_argument()
+    };
+
+    let (text, is_running) = get_status_text(workspace, || unlock_workspace(workspace).map(|(_, cipher)| cipher), &format);
+    print_output(&format!("{text}\n"), quiet);
+
+    // Status bars hide the modules of commands that fail.
+    if !is_running && format != "waybar"
+    {
+        process::exit(exit_code::NOT_RUNNING);
+    }
+}
+
+/// `time-tracker report [--from <day>] [--to <day>] [--group-by <grouping>] [--format <format>]`.
+fn print_report(workspace: &str, cipher: Option<Cipher>, json: bool, quiet: bool)
+{
+    let [from, to, group_by, format] = ["--from", "--to", "--group-by", "--format"].map(get_argument_value);
+    let format = if json
+    {
+        Some(String::from("json"))
+    }
+    else
+    {
+        format
+    };
+
+    match get_report_text(workspace, cipher, from.as_deref(), to.as_deref(), group_by.as_deref(), format.as_deref())
+    {
+        Ok(text) => print_output(&text, quiet),
+        Err(error) =>
+        {
+            eprintln!("{error}");
+            process::exit(exit_code::USAGE);
+        }
+    }
+}
+
+/// `time-tracker log [--tag <tag>] [--from <day>] [--to <day>] [--date <day>] [--limit <count>]`.
+fn print_log(workspace: &str, cipher: Option<Cipher>, json: bool, quiet: bool)
+{
+    let [tag, from, to, date, limit] = ["--tag", "--from", "--to", "--date", "--limit"].map(get_argument_value);
+    // A single day is a range of one.
+    let (from, to) = (from.or_else(|| date.clone()), to.or(date));
+
+    match get_log_text(workspace, cipher, tag.as_deref(), from.as_deref(), to.as_deref(), limit.as_deref(), json)
+    {
+        Ok(text) => print_output(&text, quiet),
+        Err(error) =>
+        {
+            eprintln!("{error}");
+            process::exit(exit_code::USAGE);
+        }
+    }
+}
+
+/// `time-tracker add "<description>" [--tag <tag>] --start <time> --end <time> [--date <day>]` for sessions that were
+/// not tracked, on today's date unless `--date` names another day. `time-tracker amend ["<description>"] [--tag <tag>]
+/// [--end <time>]` fixes the latest stored session and `time-tracker stop` ends the session running on this machine.
+fn run_command(mut app_manager: AppManager, json: bool, quiet: bool)
+{
+    match run_session_command(&mut app_manager, &get_command_arguments())
+    {
+        Ok(session) => print_session(&session, app_manager.get_current_time(), json, quiet),
+        Err((message, exit_code)) => exit_command(app_manager, &message, exit_code),
+    }
+
+    close_app(app_manager);
+}
+
+/// `time-tracker batch` runs the commands read from stdin one per line, like `start "Review" --tag work`, printing each
+/// changed session. Blank lines and lines starting with `#` are skipped. Every command is stored completely or not at
+/// all; the first one failing ends the batch with its exit code, keeping what the lines before it stored. A session
+/// still running at the end is not stored and fails the batch with `USAGE`, unless a daemon runs it.
+fn run_batch(mut app_manager: AppManager, json: bool, quiet: bool)
+{
+    let mut start_line = 0;
+
+    for (index, line) in io::stdin().lines().enumerate()
+    {
+        let line = line.expect("Failed to read stdin.");
+
+        if line.trim().is_empty() || line.trim_start().starts_with('#')
+        {
+            continue;
+        }
+
+        let result = split_arguments(&line)
+            .ok_or_else(|| ("A quote is not closed.".to_string(), exit_code::USAGE))
+            .and_then(|arguments| run_session_command(&mut app_manager, &arguments));
+
+        match result
+        {
+            Ok(session) =>
+            {
+                if session.is_running()
+                {
+                    start_line = index + 1;
+                }
+
+                print_session(&session, app_manager.get_current_time(), json, quiet);
+            }
+            Err((message, exit_code)) =>
+            {
+                eprintln!("Line {}: {message}", index + 1);
+                end_batch(app_manager, start_line);
+                process::exit(exit_code);
+            }
+        }
+    }
+
+    if !end_batch(app_manager, start_line)
+    {
+        process::exit(exit_code::USAGE);
+    }
+}
+
+/// Closes the workspace after a batch. A session the batch started on `start_line` and left running is dropped rather
+/// than ended moments after it started, unless a daemon runs it, and false is returned then.
+fn end_batch(mut app_manager: AppManager, start_line: usize) -> bool
+{
+    let is_left_running = app_manager.is_last_session_still_running() && !app_manager.daemon_connected;
+
+    if is_left_running
+    {
+        eprintln!("Line {start_line}: the session is still running at the end of the batch and was not stored, stop it in the batch.");
+        app_manager.sessions.pop();
+        app_manager.export_running_session_changes();
+    }
+
+    close_app(app_manager);
+
+    !is_left_running
+}
+
+/// A session a command changed, as a line of `log` or as the session object of `--serve`.
+fn print_session(session: &Session, now: NaiveDateTime, json: bool, quiet: bool)
+{
+    if json
+    {
+        print_output(&format!("{}\n", session_to_json(session, now)), quiet);
+    }
+    else
+    {
+        print_output(&format_log(&[session], now), quiet);
+    }
+}
+
+/// What a command prints, nothing with `--quiet`.
+fn print_output(text: &str, quiet: bool)
+{
+    if !quiet
+    {
+        print!("{text}");
+    }
+}
+
+/// Closes the workspace before exiting, which skips destructors and would leave it locked.
+fn exit_command(app_manager: AppManager, message: &str, exit_code: i32) -> !
+{
+    drop(app_manager);
+    eprintln!("{message}");
+    process::exit(exit_code);
+}
+
+/// The command, the first argument that is neither an option nor the value of one, see `VALUE_OPTIONS`.
+fn get_command() -> Option<String>
+{
+    get_command_arguments().into_iter().next()
+}
+
+/// The command and the arguments following it.
+fn get_command_arguments() -> Vec<String>
+{
+    let arguments: Vec<String> = env::args().skip(1).collect();
+    let mut index = 0;
+
+    while let Some(argument) = arguments.get(index).filter(|argument| argument.starts_with('-'))
+    {
+        index += if VALUE_OPTIONS.contains(&argument.as_str())
+        {
+            2
+        }
+        else
+        {
+            1
+        };
+    }
+
+    arguments.get(index..).unwrap_or_default().to_vec()
+}
+
+/// The workspace named with `--workspace <name>` or `-w <name>`, the default one otherwise.
+fn get_workspace_argument() -> String
+{
+    let mut arguments = env::args().skip(1);
+
+    while let Some(argument) = arguments.next()
+    {
+        if argument == "--workspace" || argument == "-w"
+        {
+            let Some(workspace) = arguments.next().filter(|workspace| is_valid_workspace_name(workspace))
+            else
+            {
+                eprintln!("{argument} needs a workspace name made of letters, digits, '-' and '_'.");
+                process::exit(exit_code::USAGE);
+            };
+
+            return workspace;
+        }
+    }
+
+    DEFAULT_WORKSPACE.to_string()
+}
+
+/// The format named with `--format <format>` for `status`, `DEFAULT_STATUS_FORMAT` otherwise.
+fn get_format_argument() -> String
+{
+    let mut arguments = env::args().skip(1);
+
+    while let Some(argument) = arguments.next()
+    {
+        if argument == "--format"
+        {
+            let Some(format) = arguments.next()
+            else
+            {
+                eprintln!("--format needs text, json, waybar or a template like \"{{description}} {{elapsed}}\".");
+                process::exit(exit_code::USAGE);
+            };
+
+            return format;
+        }
+    }
+
+    String::from(DEFAULT_STATUS_FORMAT)
+}
+
+/// The port named with `--port <port>` for `--serve`, `DEFAULT_PORT` otherwise.
+fn get_port_argument() -> u16
+{
+    let mut arguments = env::args().skip(1);
+
+    while let Some(argument) = arguments.next()
+    {
+        if argument == "--port"
+        {
+            let Some(port) = arguments.next().and_then(|port| port.parse::<u16>().ok()).filter(|port| *port > 0)
+            else
+            {
+                eprintln!("--port needs a port number.");
+                process::exit(exit_code::USAGE);
+            };
+
+            return port;
+        }
+    }
+
+    DEFAULT_PORT
+}
+
+/// The argument following `name`, `None` if `name` is not given. A missing value reads as empty, which no option takes.
+fn get_argument_value(name: &str) -> Option<String>
+{
+    let mut arguments = env::args().skip(1);
+
+    arguments.by_ref().find(|argument| argument == name)?;
+
+    Some(arguments.next().unwrap_or_default())
+}
+
+fn has_argument(name: &str) -> bool
+{
+    env::args().skip(1).any(|argument| argument == name)
+}
+
+/// Asks for the passphrase of an encrypted workspace before anything is read from it, returning it with the key derived
+/// from it. Gives up after three wrong tries.
+fn unlock_workspace(workspace: &str) -> Option<(String, Cipher)>
+{
+    if !DatabaseHandler::is_encrypted(workspace)
+    {
+        return None;
+    }
+
+    for _ in 0..3
+    {
+        let Some(passphrase) = ask_passphrase("Passphrase: ")
+        else
+        {
+            process::exit(exit_code::DATABASE_ERROR);
+        };
+
+        if let Some(cipher) = DatabaseHandler::unlock(workspace, &passphrase)
+        {
+            return Some((passphrase, cipher));
+        }
+
+        eprintln!("Wrong passphrase.");
+    }
+
+    process::exit(exit_code::DATABASE_ERROR);
+}
+
+/// A passphrase typed in the terminal, `None` if cancelled. Exits with `DATABASE_ERROR` if there is no terminal to type
+/// it in.
+fn ask_passphrase(prompt: &str) -> Option<String>
+{
+    match read_passphrase(prompt)
+    {
+        Ok(passphrase) => passphrase,
+        Err(error) =>
+        {
+            eprintln!("The passphrase can't be read: {error}");
+            process::exit(exit_code::DATABASE_ERROR);
+        }
+    }
+}
+
+/// `--encrypt` asks for a new passphrase and encrypts the workspace with it, also to change the passphrase of an
+/// encrypted one. `--decrypt` stores the workspace as plain text again.
+fn change_encryption(workspace: &str, cipher: Option<Cipher>, encrypt: bool)
+{
+    let new_passphrase = if encrypt
+    {
+        let new_passphrase = ask_passphrase("New passphrase: ").unwrap_or_default();
+
+        if new_passphrase.is_empty() || ask_passphrase("Repeat passphrase: ").as_ref() != Some(&new_passphrase)
+        {
+            eprintln!("The passphrases are empty or differ, nothing was changed.");
+            process::exit(exit_code::FAILURE);
+        }
+
+        Some(new_passphrase)
+    }
+    else
+    {
+        None
+    };
+
+    let mut database_handler = DatabaseHandler::new(workspace, cipher);
+
+    if let Err(error) = database_handler.set_passphrase(new_passphrase.as_deref())
+    {
+        eprintln!("{error}");
+        process::exit(exit_code::DATABASE_ERROR);
+    }
+
+    if encrypt
+    {
+        println!("Workspace {workspace} is encrypted.");
+    }
+    else
+    {
+        println!("Workspace {workspace} is stored as plain text.");
+    }
+}
+
+/// Draws the screen of the current state: the session list with what the state shows over it, or the focus clock.
+fn render(app_manager: &mut AppManager)
+{
+    let terminal_size = app_manager.renderer.get_terminal_size();
+
+    if let Some(title) = app_manager.get_terminal_title()
+    {
+        app_manager.renderer.set_title(&title);
+    }
+
+    if terminal_size.x < MIN_TERMINAL_WIDTH || terminal_size.y < MIN_TERMINAL_HEIGHT
+    {
+        draw_terminal_too_small(app_manager, &terminal_size);
+        app_manager.renderer.render();
+        return;
+    }
+
+    app_manager.click_areas.clear();
+
+    if let CommandState::Focus = app_manager.state
+    {
+        draw_focus(app_manager);
+        draw_overlays(app_manager);
+        app_manager.renderer.render();
+        return;
+    }
+
+    // The last row is left to the control panel.
+    let [main_window, _] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)])
+        .areas(Rect::new(0, 0, terminal_size.x, terminal_size.y));
+
+    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_WHITE);
+    app_manager.renderer.push_color(ColorType::Background, COL_BG_MAIN);
+
+    draw_session_list(app_manager, main_window);
+    draw_state(app_manager, main_window);
+    draw_overlays(app_manager);
+    draw_version(app_manager);
+
+    app_manager.renderer.pop_color(ColorType::Foreground);
+    app_manager.renderer.pop_color(ColorType::Background);
+
+    draw_control_panel(app_manager);
+
+    app_manager.renderer.render();
+}
+
+/// The help and the debug log, drawn over every screen while they are open.
+fn draw_overlays(app_manager: &mut AppManager)
+{
+    if app_manager.help_open
+    {
+        draw_help(app_manager);
+    }
+
+    if app_manager.debug_log_open
+    {
+        draw_debug_log(app_manager);
+    }
+}
+
+/// True in the states that let the list be clicked: its rows to select them and its column titles to sort by them.
+fn is_list_interactive(state: &CommandState) -> bool
+{
+    matches!(
+        state,
+        CommandState::Idle
+            | CommandState::Modify(
+                SessionModifyState::Edit(SessionEditState::Browse)
+                    | SessionModifyState::Continue(ConfirmOpen::No)
+                    | SessionModifyState::Duplicate
+                    | SessionModifyState::Delete(ConfirmOpen::No)
+                    | SessionModifyState::Select(SelectState::Browse)
+            )
+    )
+}
+
+/// The session list in `main_window`: the frame with the column titles and dividers, the title, the rows and the
+/// summary of the day below them.
+fn draw_session_list(app_manager: &mut AppManager, main_window: Rect)
+{
+    let list_is_interactive = is_list_interactive(&app_manager.state);
+    let list_columns = get_list_columns(app_manager, main_window);
+
+    app_manager.renderer.push_color(ColorType::Foreground, COL_OUTLINE_MAIN);
+    draw_frame(&mut app_manager.renderer, &main_window);
+    draw_list_column_titles(app_manager, main_window, &list_columns, list_is_interactive);
+    app_manager.renderer.pop_color(ColorType::Foreground);
+
+    app_manager.renderer.push_color(ColorType::Foreground, COL_BG_MAIN);
+    app_manager.renderer.push_color(ColorType::Background, COL_OUTLINE_MAIN);
+    let title = get_list_title(app_manager);
+    draw_title(&mut app_manager.renderer, &title, &main_window);
+    app_manager.renderer.pop_color(ColorType::Foreground);
+    app_manager.renderer.pop_color(ColorType::Background);
+
+    draw_list_rows(app_manager, main_window, &list_columns, list_is_interactive);
+    draw_daily_summary(app_manager, &Vector2::new(main_window.width, main_window.height));
+}
+
+/// The configured columns of the list with the position and width of each, after the command column. The description
+/// column takes whatever width the other columns leave.
+fn get_list_columns(app_manager: &AppManager, main_window: Rect) -> Vec<(ListColumn, u16, u16)>
+{
+    let time_column_width = get_display_width(&NaiveTime::MIN.format(app_manager.get_time_format()).to_string()) as u16 + 2;
+
+    // Long tags must not push the tag and project columns into the description column.
+    let max_name_column_width = main_window.width.saturating_sub(70);
+    let tag_column_width = (app_manager.sessions.iter().map(|s| get_display_width(&s.tag)).max().unwrap_or(10) + 2) as u16;
+    let project_column_width =
+        (app_manager.sessions.iter().filter_map(Session::get_project).map(get_display_width).max().unwrap_or(0).max(7) + 2) as u16;
+
+    let get_column_width = |column: ListColumn| match column
+    {
+        ListColumn::Date => 11,
+        ListColumn::Description => 0,
+        ListColumn::Tag => cmp::min(tag_column_width, max_name_column_width),
+        ListColumn::Project => cmp::min(project_column_width, max_name_column_width),
+        ListColumn::Start | ListColumn::End => time_column_width,
+        ListColumn::Duration => 10,
+    };
+
+    let column_widths = [COMMAND_COLUMN_WIDTH]
+        .into_iter()
+        .chain(app_manager.config.columns.iter().map(|column| get_column_width(*column)))
+        .collect::<Vec<u16>>();
+    let header_row = Rect::new(main_window.x, main_window.y, main_window.width.saturating_sub(2), 1);
+
+    app_manager
+        .config
+        .columns
+        .iter()
+        .zip(layout_columns(header_row, &column_widths, 1).into_iter().skip(1))
+        .map(|(column, area)| (*column, area.x, area.width))
+        .collect()
+}
+
+/// The title of every column, with an arrow on the one the list is sorted by, and the dividers between them.
+fn draw_list_column_titles(
+    app_manager: &mut AppManager,
+    main_window: Rect,
+    list_columns: &[(ListColumn, u16, u16)],
+    list_is_interactive: bool,
+)
+{
+    let content = main_window.at(2, 1);
+
+    let mut dividers = vec![(0, "Cmd", None)];
+    dividers
+        .extend(list_columns.iter().map(|(column, column_pos, _)| (*column_pos, column.get_title(), Some(column.get_sort_column()))));
+
+    for (index, (column_pos, section_title, sort_column)) in dividers.iter().enumerate()
+    {
+        let section_title = match app_manager.list_sort
+        {
+            Some((sorted_column, descending)) if *sort_column == Some(Some(sorted_column)) =>
+            {
+                format!(
+                    "{} {}",
+                    section_title,
+                    if descending
+                    {
+                        ARROW_DOWN
+                    }
+                    else
+                    {
+                        ARROW_UP
+                    }
+                )
+            }
+            _ => (*section_title).to_string(),
+        };
+
+        if let Some(sort_column) = sort_column
+            && list_is_interactive
+        {
+            app_manager.add_click_area(
+                *column_pos + content.x,
+                content.y,
+                section_title.chars().count() as u16,
+                ClickTarget::Column(*sort_column),
+            );
+        }
+
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_HIGHLIGHT);
+        app_manager.renderer.draw_at(section_title, &Vector2::new(*column_pos + content.x, content.y));
+        app_manager.renderer.pop_color(ColorType::Foreground);
+
+        if index == 0
+        {
+            continue;
+        }
+
+        app_manager.renderer.draw_at(INTERSECT_T, &Vector2::new(*column_pos, main_window.y));
+
+        for row_index in main_window.y + 1..main_window.bottom() - 1
+        {
+            app_manager.renderer.draw_at(DIVIDER_V, &Vector2::new(*column_pos, row_index));
+        }
+
+        app_manager.renderer.draw_at(INTERSECT_B, &Vector2::new(*column_pos, main_window.bottom() - 1));
+    }
+}
+
+/// The title of the list, naming the workspace and what needs attention most, like a running daemon, a read-only
+/// database or a session running on another machine.
+fn get_list_title(app_manager: &AppManager) -> String
+{
+    let list_title = if app_manager.get_workspace() == DEFAULT_WORKSPACE
+    {
+        String::from("SESSIONS")
+    }
+    else
+    {
+        format!("SESSIONS: {}", app_manager.get_workspace().to_uppercase())
+    };
+
+    if let Some(message) = &app_manager.clipboard_message
+    {
+        format!("{list_title} [{message}]")
+    }
+    else if app_manager.daemon_connected
+    {
+        match &app_manager.sync_message
+        {
+            Some(message) => format!("{list_title} [DAEMON: {message}]"),
+            None => format!("{list_title} [DAEMON]"),
+        }
+    }
+    else if app_manager.is_read_only()
+    {
+        format!("{list_title} [READ-ONLY]")
+    }
+    else if app_manager.is_ephemeral()
+    {
+        format!("{list_title} [EPHEMERAL]")
+    }
+    else if let Some(error) = &app_manager.config.error
+    {
+        format!("{list_title} [{}]", error)
+    }
+    else if let Some((machine_name, _)) = app_manager.running_sessions_elsewhere.first()
+    {
+        let also = if app_manager.is_last_session_still_running()
+        {
+            "ALSO "
+        }
+        else
+        {
+            ""
+        };
+
+        format!("{list_title} [{also}RUNNING ON {}]", machine_name.to_uppercase())
+    }
+    else if let Some(message) = &app_manager.sync_message
+    {
+        format!("{list_title} [SYNC: {}]", message)
+    }
+    else if let CommandState::Modify(SessionModifyState::Select(_)) = app_manager.state
+    {
+        format!("{list_title} [{} MARKED]", app_manager.marked_session_ids.len())
+    }
+    else
+    {
+        list_title
+    }
+}
+
+/// The day headers and session rows of the list, one per line below the column titles.
+fn draw_list_rows(app_manager: &mut AppManager, main_window: Rect, list_columns: &[(ListColumn, u16, u16)], list_is_interactive: bool)
+{
+    let content = main_window.at(2, 1);
+    let main_window_size = Vector2::new(main_window.width, main_window.height);
+
+    let list_rows = app_manager.get_list_rows();
+    let selected_row_index = if let CommandState::Modify(_) = &app_manager.state
+    {
+        app_manager.get_selected_row_index(&list_rows)
+    }
+    else
+    {
+        None
+    };
+
+    for (row_index, list_row) in list_rows.iter().enumerate()
+    {
+        let entry_pos_y = content.y + 1 + row_index as u16;
+        let row_is_selected = selected_row_index == Some(row_index);
+
+        if list_is_interactive
+        {
+            app_manager.add_click_area(main_window.x + 1, entry_pos_y, main_window.width - 2, ClickTarget::Row(row_index));
+        }
+
+        if row_is_selected
+        {
+            app_manager.renderer.push_color(ColorType::Background, COL_TEXT_DIM);
+
+            let bg = " ".repeat(main_window.width as usize - 3);
+            app_manager.renderer.draw_at(bg, &Vector2::new(content.x, entry_pos_y));
+        }
+
+        match *list_row
+        {
+            ListRow::Day {
+                date,
+                total,
+                collapsed,
+                ..
+            } =>
+            {
+                draw_day_header(app_manager, &main_window_size, entry_pos_y, date, total, collapsed);
+            }
+            ListRow::Session(session_index) =>
+            {
+                let fields = list_columns
+                    .iter()
+                    .map(|(column, column_pos, column_width)| {
+                        (*column, Vector2::new(column_pos + content.x, entry_pos_y), column_width.saturating_sub(2) as usize)
+                    })
+                    .collect::<Vec<(ListColumn, Vector2, usize)>>();
+
+                draw_session_entry(app_manager, &fields, session_index, row_is_selected);
+
+                if let CommandState::Modify(SessionModifyState::Select(_)) = app_manager.state
+                    && app_manager.is_session_marked(&app_manager.sessions[session_index])
+                {
+                    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_HIGHLIGHT);
+                    app_manager.renderer.draw_at(MARK, &Vector2::new(main_window.x + COMMAND_COLUMN_WIDTH, entry_pos_y));
+                    app_manager.renderer.pop_color(ColorType::Foreground);
+                }
+            }
+        }
+
+        if row_is_selected
+        {
+            app_manager.renderer.pop_color(ColorType::Background);
+        }
+    }
+}
+
+/// What the current state shows over the session list: a popup, a view or the line of the session being modified.
+fn draw_state(app_manager: &mut AppManager, main_window: Rect)
+{
+    if let Some(question) = get_state_question(app_manager)
+    {
+        draw_yes_no_popup(app_manager, &question);
+        return;
+    }
+
+    match app_manager.state.clone()
+    {
+        CommandState::New(input_field) => draw_new_session_popup(app_manager, input_field),
+        CommandState::Add(selected_field) => draw_manual_entry_popup(app_manager, selected_field),
+        CommandState::EditRunning(selected_field) => draw_running_edit_popup(app_manager, selected_field),
+        CommandState::Overlap(_) => draw_overlap_popup(app_manager),
+        CommandState::ImportReport => draw_import_report_popup(app_manager),
+        CommandState::Calendar => draw_calendar(app_manager),
+        CommandState::Stats => draw_stats(app_manager),
+        CommandState::DatabaseStats => draw_database_stats(app_manager),
+        CommandState::Goals => draw_goals(app_manager),
+        CommandState::Templates => draw_templates(app_manager),
+        CommandState::WeekReport => draw_week_report(app_manager),
+        CommandState::Gaps => draw_gaps(app_manager),
+        CommandState::Settings(selected_field) => draw_settings(app_manager, selected_field),
+        CommandState::Locked => draw_locked_popup(app_manager),
+        CommandState::Export(ExportState::Form(selected_field)) => draw_export_popup(app_manager, selected_field),
+        CommandState::Import(ImportState::Path) => draw_import_path_popup(app_manager),
+        CommandState::Import(ImportState::Mapping(selected_field)) => draw_csv_mapping_popup(app_manager, selected_field),
+        CommandState::Import(ImportState::Summary) => draw_import_summary_popup(app_manager),
+        CommandState::Backups(confirm_open) =>
+        {
+            draw_backups_popup(app_manager);
+
+            if confirm_open == ConfirmOpen::Yes
+            {
+                draw_yes_no_popup(app_manager, "RESTORE BACKUP?");
+            }
+        }
+        CommandState::AuditLog => draw_audit_log(app_manager),
+        CommandState::Workspaces(workspace_state) => draw_workspaces_popup(app_manager, workspace_state),
+        CommandState::Modify(modify_state) => draw_modify_state(app_manager, modify_state, main_window),
+        CommandState::Idle
+        | CommandState::Focus
+        | CommandState::End
+        | CommandState::AutoStop
+        | CommandState::Discard
+        | CommandState::DiscardShort
+        | CommandState::RestoreEdit
+        | CommandState::Quitting =>
+        {}
+    }
+}
+
+/// The question of the states that only ask yes or no, `None` in the others.
+fn get_state_question(app_manager: &AppManager) -> Option<String>
+{
+    let question = match app_manager.state
+    {
+        CommandState::End => String::from("END SESSION?"),
+        CommandState::AutoStop =>
+        {
+            let auto_stop = app_manager.config.auto_stop.unwrap_or_default().format("%H:%M");
+            format!("STILL RUNNING AT {auto_stop}, END THEN?")
+        }
+        CommandState::Discard => String::from("DISCARD RUNNING SESSION?"),
+        CommandState::DiscardShort =>
+        {
+            let elapsed = format_duration(app_manager.get_running_session_elapsed().unwrap_or_default());
+            format!("ONLY {elapsed} LONG, DISCARD IT?")
+        }
+        CommandState::RestoreEdit => String::from("RESTORE UNSAVED EDIT?"),
+        CommandState::Quitting => String::from("REALLY QUIT?"),
+        _ => return None,
+    };
+
+    Some(question)
+}
+
+/// The popup for a new session with its description, tag and start side by side, and below them the suggestions,
+/// dropdown or cursor of the field being typed in.
+fn draw_new_session_popup(app_manager: &mut AppManager, input_field: SessionInputState)
+{
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let input_window = get_centered_area(&Vector2::new(terminal_size.x - 32, 3), &terminal_size);
+    let field_areas = Layout::horizontal([Constraint::Fill(2), Constraint::Fill(1), Constraint::Fill(1)]).areas(input_window);
+    let [description_area, tag_area, start_area] = field_areas;
+
+    open_popup(&mut app_manager.renderer, &input_window, "NEW SESSION");
+
+    for divider_area in [tag_area, start_area]
+    {
+        app_manager.renderer.draw_at(INTERSECT_T, &divider_area.at(0, 0));
+        app_manager.renderer.draw_at(DIVIDER_V, &divider_area.at(0, 1));
+        app_manager.renderer.draw_at(INTERSECT_B, &divider_area.at(0, 2));
+    }
+
+    let (description_text_pos, description_width) = draw_new_session_fields(app_manager, input_field, field_areas);
+
+    match input_field
+    {
+        SessionInputState::Description(ConfirmOpen::Yes) =>
+        {
+            draw_yes_no_popup(app_manager, "END RUNNING SESSION?");
+        }
+        SessionInputState::Description(ConfirmOpen::No) =>
+        {
+            draw_text_input(&mut app_manager.renderer, &app_manager.description_buffer, &description_text_pos, description_width);
+            draw_description_suggestions(app_manager, &input_window.at(0, 3), description_area.width + 1);
+        }
+        SessionInputState::Tag(edit_state) =>
+        {
+            let tag_input_pos = tag_area.at(2, 1);
+            let entries = app_manager.get_tag_dropdown_entries();
+
+            draw_tag_dropdown(
+                &mut app_manager.renderer,
+                &app_manager.tags,
+                &entries,
+                app_manager.temp_tag_index,
+                &tag_input_pos,
+                "TAG",
+                &app_manager.tag_filter,
+            );
+
+            if let TagInputState::New = edit_state
+            {
+                let new_tag_window = Rect::new(tag_input_pos.x + 2, tag_input_pos.y + 1, 32, 3);
+                draw_window(&mut app_manager.renderer, &new_tag_window, Some("NEW TAG"));
+                draw_text_input(&mut app_manager.renderer, &app_manager.tag_buffer, &new_tag_window.at(2, 1), 28);
+            }
+        }
+        SessionInputState::Start =>
+        {
+            let start_input_label = if app_manager.timer_buffer.is_empty()
+            {
+                "START "
+            }
+            else
+            {
+                ""
+            };
+            let cursor_offset = (start_input_label.len() + get_display_width(&app_manager.start_buffer)) as u16;
+
+            app_manager.renderer.draw_at(CURSOR, &start_area.at(2 + cursor_offset, 1));
+        }
+        SessionInputState::Timer =>
+        {
+            app_manager.renderer.draw(CURSOR);
+        }
+    }
+
+    close_popup(&mut app_manager.renderer);
+}
+
+/// The labels and values of the description, tag and start of a new session in `field_areas`, the start followed by
+/// the timer once one is set or typed. Returns where the description text starts and how wide it may be.
+fn draw_new_session_fields(app_manager: &mut AppManager, input_field: SessionInputState, field_areas: [Rect; 3]) -> (Vector2, usize)
+{
+    let [description_area, tag_area, start_area] = field_areas;
+    let description_input_label = "DESCRIPTION ";
+
+    // The timer shares the start field, it only shows once one is set or being typed and then takes the place of the
+    // start label.
+    let timer_is_shown = !app_manager.timer_buffer.is_empty() || input_field == SessionInputState::Timer;
+    let start_input_label = if timer_is_shown
+    {
+        ""
+    }
+    else
+    {
+        "START "
+    };
+
+    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+    app_manager.renderer.draw_at(description_input_label, &description_area.at(2, 1));
+    app_manager.renderer.pop_color(ColorType::Foreground);
+
+    let description_text_pos = description_area.at(2 + description_input_label.len() as u16, 1);
+    let description_width = description_area.right().saturating_sub(description_text_pos.x) as usize;
+
+    app_manager.renderer.draw(ellipsize(&app_manager.description_buffer, description_width));
+
+    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+    app_manager.renderer.draw_at("TAG ", &tag_area.at(2, 1));
+    app_manager.renderer.pop_color(ColorType::Foreground);
+
+    let no_tags_msg = "- empty -".to_string();
+    let selected_tag = app_manager.tags.get(app_manager.get_selected_tag_index()).unwrap_or(&no_tags_msg);
+
+    app_manager.renderer.draw(selected_tag);
+
+    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+    app_manager.renderer.draw_at(start_input_label, &start_area.at(2, 1));
+    app_manager.renderer.pop_color(ColorType::Foreground);
+
+    if app_manager.start_buffer.is_empty() && input_field != SessionInputState::Start
+    {
+        app_manager.renderer.draw("now");
+    }
+    else
+    {
+        let start_is_valid = app_manager.get_new_session_start().is_some();
+
+        draw_maybe_invalid(&mut app_manager.renderer, &app_manager.start_buffer, start_is_valid);
+    }
+
+    if timer_is_shown
+    {
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+        app_manager.renderer.draw(" FOR ");
+        app_manager.renderer.pop_color(ColorType::Foreground);
+
+        let timer_is_valid = app_manager.is_new_session_timer_valid();
+
+        draw_maybe_invalid(&mut app_manager.renderer, &app_manager.timer_buffer, timer_is_valid);
+    }
+
+    (description_text_pos, description_width)
+}
+
+/// Draws `text` where the cursor is, in red unless it is valid.
+fn draw_maybe_invalid(renderer: &mut Out, text: &str, is_valid: bool)
+{
+    if !is_valid
+    {
+        renderer.push_color(ColorType::Foreground, COL_TEXT_RED);
+    }
+
+    renderer.draw(text);
+
+    if !is_valid
+    {
+        renderer.pop_color(ColorType::Foreground);
+    }
+}
+
+/// The line of the session being modified, labeled with the modification, and the popup of the step it is at.
+fn draw_modify_state(app_manager: &mut AppManager, modify_state: SessionModifyState, main_window: Rect)
+{
+    let command_label = match modify_state
+    {
+        SessionModifyState::Edit(_) => "EDT",
+        SessionModifyState::Continue(_) => "CPY",
+        SessionModifyState::Duplicate => "DUP",
+        SessionModifyState::Select(_) => "SEL",
+        SessionModifyState::Delete(_) => "DEL",
+    };
+
+    draw_session_selection_line(app_manager, &main_window.at(2, 1), command_label);
+
+    let marked_count = app_manager.marked_session_ids.len();
+
+    match modify_state
+    {
+        SessionModifyState::Edit(edit_state) => draw_session_edit_state(app_manager, &edit_state, main_window),
+        SessionModifyState::Continue(ConfirmOpen::Yes) =>
+        {
+            let message = if app_manager.is_last_session_still_running()
+            {
+                "END RUNNING SESSION?"
+            }
+            else
+            {
+                "COPY AND START SESSION?"
+            };
+
+            draw_yes_no_popup(app_manager, message);
+        }
+        SessionModifyState::Select(SelectState::Delete) =>
+        {
+            draw_yes_no_popup(app_manager, &format!("DELETE {marked_count} SESSIONS?"));
+        }
+        SessionModifyState::Select(SelectState::Retag(ConfirmOpen::No)) => draw_retag_popup(app_manager),
+        SessionModifyState::Select(SelectState::Retag(ConfirmOpen::Yes)) =>
+        {
+            let tag = app_manager.tags[app_manager.temp_tag_index].to_uppercase();
+            draw_yes_no_popup(app_manager, &format!("RETAG {marked_count} SESSIONS AS {tag}?"));
+        }
+        SessionModifyState::Delete(ConfirmOpen::Yes) => draw_yes_no_popup(app_manager, "CONFIRM DELETE"),
+        SessionModifyState::Continue(ConfirmOpen::No)
+        | SessionModifyState::Duplicate
+        | SessionModifyState::Select(SelectState::Browse)
+        | SessionModifyState::Delete(ConfirmOpen::No) =>
+        {}
+    }
+}
+
+/// The error of the last edit on the bottom line of `main_window`, and the details, notes or question of the edit.
+fn draw_session_edit_state(app_manager: &mut AppManager, edit_state: &SessionEditState, main_window: Rect)
+{
+    if let Some(error) = app_manager.session_edit_error.clone()
+    {
+        app_manager.renderer.push_color(ColorType::Background, COL_TEXT_RED);
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_WHITE);
+        let error_line = Rect::new(main_window.x, main_window.bottom() - 1, main_window.width, 1);
+        draw_title(&mut app_manager.renderer, &error, &error_line);
+        app_manager.renderer.pop_color(ColorType::Foreground);
+        app_manager.renderer.pop_color(ColorType::Background);
+    }
+
+    match edit_state
+    {
+        SessionEditState::Details => draw_session_details(app_manager),
+        SessionEditState::Notes(confirm_open) =>
+        {
+            draw_notes_editor(app_manager);
+
+            if *confirm_open == ConfirmOpen::Yes
+            {
+                draw_yes_no_popup(app_manager, "SAVE NOTES?");
+            }
+        }
+        SessionEditState::Confirm => draw_yes_no_popup(app_manager, "ACCEPT CHANGES?"),
+        SessionEditState::Browse | SessionEditState::EditFields(_) =>
+        {}
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+/// Draws a session row, cutting every field to the width of its column.
+fn draw_session_entry(
+    app_manager: &mut AppManager,
+    fields: &[(ListColumn, Vector2, usize)],
+    session_index: usize,
+    session_is_selected: bool,
+)
+{
+    let session = if let CommandState::Modify(SessionModifyState::Edit(_)) = &app_manager.state
+        && let Some(session_buffer) = &app_manager.session_edit_buffer
+        && session_is_selected
+    {
+        session_buffer
+    }
+    else
+    {
+        &app_manager.sessions[session_index]
+    };
+
+    let show_seconds = app_manager.config.show_seconds;
+    let time_format = app_manager.get_time_format();
+
+    for (column, position, width) in fields
+    {
+        let width = *width;
+
+        let field = match column
+        {
+            ListColumn::Date => app_manager.get_date_label(session.start.date()),
+            ListColumn::Description => session.description.clone(),
+            ListColumn::Tag => session.tag.clone(),
+            ListColumn::Project => session.get_project().unwrap_or("-").to_string(),
+            ListColumn::Start => session.start.format(time_format).to_string(),
+            ListColumn::End => session.end.map_or(String::from("-"), |end| end.format(time_format).to_string()),
+            ListColumn::Duration => match session.end
+            {
+                Some(end) if show_seconds => format_duration(end - session.start),
+                Some(end) => format_duration_without_seconds(end - session.start),
+                None => String::from("Running"),
+            },
+        };
+
+        let session_field_is_selected =
+            session_is_selected && column.get_session_field_index() == Some(app_manager.get_selected_session_field_index());
+
+        if session_field_is_selected
+            && let CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(edit_field_state))) = &app_manager.state
+        {
+            let (bg_color, fg_color) = match edit_field_state
+            {
+                SessionFieldEditState::Browse => (COL_TEXT_HIGHLIGHT, COL_TEXT_BLACK),
+                SessionFieldEditState::Editing => (COL_TEXT_RED, COL_TEXT_WHITE),
+            };
+
+            app_manager.renderer.push_color(ColorType::Background, bg_color);
+            app_manager.renderer.push_color(ColorType::Foreground, fg_color);
+
+            match &app_manager.selected_session_field
+            {
+                SessionField::Date(date_buffer) =>
+                {
+                    app_manager.renderer.push_color(ColorType::Background, COL_TEXT_HIGHLIGHT);
+                    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_BLACK);
+
+                    let date = format!("{}", date_buffer.format("%d %b %y"));
+                    app_manager.renderer.draw_at(date, position);
+
+                    app_manager.renderer.pop_color(ColorType::Background);
+                    app_manager.renderer.pop_color(ColorType::Foreground);
+
+                    let (selected_date_segment, position_offset) = match app_manager.selected_datetime_segment
+                    {
+                        0 => (format!("{}", date_buffer.format("%d")), 0),
+                        1 => (format!("{}", date_buffer.format("%b")), 3),
+                        2 => (format!("{}", date_buffer.format("%y")), 7),
+                        _ => (String::new(), 0),
+                    };
+
+                    app_manager.renderer.draw_at(selected_date_segment, &Vector2::new(position.x + position_offset, position.y));
+                }
+                SessionField::Description(description_buffer) => match edit_field_state
+                {
+                    SessionFieldEditState::Browse =>
+                    {
+                        app_manager.renderer.draw_at(ellipsize(description_buffer, width), position);
+                    }
+                    SessionFieldEditState::Editing =>
+                    {
+                        draw_text_input(&mut app_manager.renderer, description_buffer, position, width);
+                    }
+                },
+                SessionField::Tag(tag_buffer) => match edit_field_state
+                {
+                    SessionFieldEditState::Browse =>
+                    {
+                        app_manager.renderer.draw_at(ellipsize(tag_buffer, width), position);
+                    }
+                    SessionFieldEditState::Editing =>
+                    {
+                        let entries = app_manager.get_tag_dropdown_entries();
+
+                        draw_tag_dropdown(
+                            &mut app_manager.renderer,
+                            &app_manager.tags,
+                            &entries,
+                            app_manager.temp_tag_index,
+                            position,
+                            "EDIT TAG",
+                            &app_manager.tag_filter,
+                        );
+                    }
+                },
+                SessionField::Start(start_buffer) =>
+                {
+                    render_edited_time(
+                        &mut app_manager.renderer,
+                        app_manager.selected_datetime_segment,
+                        start_buffer,
+                        position,
+                        time_format,
+                    );
+                }
+                SessionField::End(end_buffer) =>
+                {
+                    if let Some(end_buffer) = end_buffer
+                    {
+                        render_edited_time(
+                            &mut app_manager.renderer,
+                            app_manager.selected_datetime_segment,
+                            end_buffer,
+                            position,
+                            time_format,
+                        );
+                    }
+                    else
+                    {
+                        app_manager.renderer.draw_at(&field, position);
+                    }
+                }
+                SessionField::None =>
+                {}
+            }
+
+            app_manager.renderer.pop_color(ColorType::Background);
+            app_manager.renderer.pop_color(ColorType::Foreground);
+        }
+        else
+        {
+            let is_running_duration = *column == ListColumn::Duration && session.is_running();
+
+            if is_running_duration
+            {
+                app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED);
+            }
+
+            app_manager.renderer.draw_at(ellipsize(&field, width), position);
+
+            if is_running_duration
+            {
+                app_manager.renderer.pop_color(ColorType::Foreground);
+            }
+        }
+    }
+}
+
+/// Shows every field of the selected session, with the description wrapped instead of cut off.
+fn draw_session_details(app_manager: &mut AppManager)
+{
+    let Some(session) = app_manager.sessions.get(app_manager.selected_session_index)
+    else
+    {
+        return;
+    };
+
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup_width = cmp::min(terminal_size.x - 4, 72);
+    let label_width = 13;
+    let text_width = popup_width as usize - label_width - 4;
+
+    let time_format = app_manager.get_time_format();
+    let mut rows = vec![("DATE", session.start.format(app_manager.get_date_format()).to_string())];
+    rows.extend(wrap_to_width(&session.description, text_width).into_iter().enumerate().map(|(line_index, line)| {
+        let label = if line_index == 0
+        {
+            "DESCRIPTION"
+        }
+        else
+        {
+            ""
+        };
+        (label, line)
+    }));
+    rows.push(("TAG", session.tag.clone()));
+    rows.push(("START", session.start.format(time_format).to_string()));
+    rows.push(("END", session.end.map_or(String::from("-"), |end| end.format(time_format).to_string())));
+    rows.push(("DURATION", session.get_duration_string().unwrap_or(String::from("Running"))));
+
+    let note_lines = session.notes.split('\n').flat_map(|line| wrap_to_width(line, text_width)).filter(|_| !session.notes.is_empty());
+    rows.extend(note_lines.enumerate().map(|(line_index, line)| {
+        (
+            if line_index == 0
+            {
+                "NOTES"
+            }
+            else
+            {
+                ""
+            },
+            line,
+        )
+    }));
+
+    let visible_row_count = cmp::min(rows.len(), terminal_size.y as usize - 10);
+    let popup = get_centered_area(&Vector2::new(popup_width, visible_row_count as u16 + 4), &terminal_size);
+
+    open_popup(&mut app_manager.renderer, &popup, "SESSION");
+
+    for (row_index, (label, text)) in rows.iter().take(visible_row_count).enumerate()
+    {
+        let row_pos = popup.at(2, 1 + row_index as u16);
+
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+        app_manager.renderer.draw_at(format!("{:<pad$}", label, pad = label_width), &row_pos);
+        app_manager.renderer.pop_color(ColorType::Foreground);
+        app_manager.renderer.draw(format!(" {}", ellipsize(text, text_width)));
+    }
+
+    let divider_y = popup.y + popup.height - 3;
+    draw_divider(&mut app_manager.renderer, &popup, divider_y);
+
+    app_manager.renderer.draw_at(
+        "[ENTER] edit  [N] notes  [E] $EDITOR  [y] copy  [ESC] back",
+        &popup.at(2, popup.height - 2),
+    );
+
+    close_popup(&mut app_manager.renderer);
+}
+
+/// The notes of the selected session with the cursor, scrolled so the cursor stays in view.
+fn draw_notes_editor(app_manager: &mut AppManager)
+{
+    let (Some(notes_editor), Some(session)) =
+        (&app_manager.notes_editor, app_manager.sessions.get(app_manager.selected_session_index))
+    else
+    {
+        return;
+    };
+
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup = get_centered_area(&Vector2::new(cmp::min(terminal_size.x - 4, 72), cmp::min(terminal_size.y - 4, 18)), &terminal_size);
+    let text_width = popup.width as usize - 4;
+    let visible_line_count = popup.height as usize - 4;
+
+    let title = truncate_to_width(&format!("NOTES: {}", session.description), text_width);
+    let first_line = get_first_visible_row(notes_editor.cursor_line, visible_line_count);
+    let first_column = notes_editor.cursor_column.saturating_sub(text_width - 1);
+    let lines = notes_editor
+        .lines
+        .iter()
+        .enumerate()
+        .skip(first_line)
+        .take(visible_line_count)
+        .map(|(line_index, line)| (line_index, line.chars().collect::<Vec<char>>()))
+        .collect::<Vec<_>>();
+    let cursor = (notes_editor.cursor_line, notes_editor.cursor_column);
+
+    open_popup(&mut app_manager.renderer, &popup, &title);
+
+    for (row_index, (line_index, characters)) in lines.iter().enumerate()
+    {
+        let row_pos = popup.at(2, 1 + row_index as u16);
+        // Only the cursor line scrolls sideways, the others are cut off.
+        let column_offset = if *line_index == cursor.0
+        {
+            first_column
+        }
+        else
+        {
+            0
+        };
+        let visible_text = characters.iter().skip(column_offset).collect::<String>();
+
+        app_manager.renderer.draw_at(truncate_to_width(&visible_text, text_width), &row_pos);
+
+        if *line_index == cursor.0
+        {
+            let cursor_character = characters.get(cursor.1).copied().unwrap_or(' ');
+
+            app_manager.renderer.push_color(ColorType::Background, COL_TEXT_HIGHLIGHT);
+            app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_BLACK);
+            app_manager.renderer.draw_at(
+                cursor_character.to_string(),
+                &Vector2::new(
+                    row_pos.x
+                        + get_display_width(&characters[column_offset..cursor.1.min(characters.len())].iter().collect::<String>())
+                            as u16,
+                    row_pos.y,
+                ),
+            );
+            app_manager.renderer.pop_color(ColorType::Foreground);
+            app_manager.renderer.pop_color(ColorType::Background);
+        }
+    }
+
+    let divider_y = popup.y + popup.height - 3;
+    draw_divider(&mut app_manager.renderer, &popup, divider_y);
+
+    app_manager.renderer.draw_at("[ENTER] new line  [ESC] done", &popup.at(2, popup.height - 2));
+
+    close_popup(&mut app_manager.renderer);
+}
+
+/// Draws `time` in `time_format` with the segment being edited highlighted. The format starts with the hour, `%H` or
+/// `%I`, followed by minutes and seconds.
+fn render_edited_time(renderer: &mut Out, datetime_segment: usize, time: &NaiveDateTime, position: &Vector2, time_format: &str)
+{
+    renderer.push_color(ColorType::Background, COL_TEXT_HIGHLIGHT);
+    renderer.push_color(ColorType::Foreground, COL_TEXT_BLACK);
+
+    let date = format!("{}", time.format(time_format));
+    renderer.draw_at(date, position);
+
+    renderer.pop_color(ColorType::Background);
+    renderer.pop_color(ColorType::Foreground);
+
+    let (selected_date_segment, position_offset) = match datetime_segment
+    {
+        0 => (format!("{}", time.format(&time_format[..2])), 0),
+        1 => (format!("{}", time.format("%M")), 3),
+        2 => (format!("{}", time.format("%S")), 6),
+        _ => (String::new(), 0),
+    };
+
+    renderer.draw_at(selected_date_segment, &Vector2::new(position.x + position_offset, position.y));
+}
+
+/// Shows the version in the bottom right corner of the frame, or for a few seconds after an action what it did.
+fn draw_version(app_manager: &mut AppManager)
+{
+    let window_size = app_manager.renderer.get_terminal_size();
+    let (text, color) = match app_manager.get_status_message()
+    {
+        Some(message) => (format!(" {} ", truncate_to_width(message, window_size.x as usize / 2)), COL_TEXT_HIGHLIGHT),
+        None => (format!(" Version {} ", app_manager.version), COL_OUTLINE_MAIN),
+    };
+    let text_pos = Vector2::new(window_size.x - get_display_width(&text) as u16 - 2, window_size.y - 2);
+
+    app_manager.renderer.push_color(ColorType::Foreground, color);
+    app_manager.renderer.draw_at(text, &text_pos);
+    app_manager.renderer.pop_color(ColorType::Foreground);
+}
+
+fn draw_yes_no_popup(app_manager: &mut AppManager, title: &str)
+{
+    let popup = get_centered_area(&Vector2::new(40, 5), &app_manager.renderer.get_terminal_size());
+
+    open_popup(&mut app_manager.renderer, &popup, title);
+
+    let text_pos_y = popup.y + popup.height / 2;
+    let yes_pos = Vector2::new(popup.x + popup.width / 4 - 2, text_pos_y);
+    let no_pos = Vector2::new(popup.x + (popup.width / 4) * 3 - 2, text_pos_y);
+
+    app_manager.add_click_area(yes_pos.x, yes_pos.y, 5, ClickTarget::Key(KEY_YES));
+    app_manager.add_click_area(no_pos.x, no_pos.y, 4, ClickTarget::Key(KEY_NO));
+
+    app_manager.renderer.go_to_position(&yes_pos);
+    draw_key_hint(&mut app_manager.renderer, 'y', "es");
+    app_manager.renderer.go_to_position(&no_pos);
+    draw_key_hint(&mut app_manager.renderer, 'n', "o");
+
+    close_popup(&mut app_manager.renderer);
+}
+
+fn draw_manual_entry_popup(app_manager: &mut AppManager, selected_field: ManualEntryField)
+{
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup = get_centered_area(&Vector2::new(terminal_size.x - 32, 9), &terminal_size);
+
+    open_popup(&mut app_manager.renderer, &popup, "ADD SESSION");
+
+    let no_tags_msg = "- empty -".to_string();
+    let selected_tag = app_manager.tags.get(app_manager.manual_entry.tag_index).unwrap_or(&no_tags_msg);
+    let tag_text = if selected_field == ManualEntryField::Tag
+    {
+        format!("< {} >", selected_tag)
+    }
+    else
+    {
+        selected_tag.clone()
+    };
+
+    let rows = [
+        (ManualEntryField::Description, "DESCRIPTION", app_manager.manual_entry.description.clone()),
+        (ManualEntryField::Tag, "TAG", tag_text),
+        (ManualEntryField::Date, "DATE", app_manager.manual_entry.date.clone()),
+        (ManualEntryField::Start, "START", app_manager.manual_entry.start.clone()),
+        (ManualEntryField::End, "END", app_manager.manual_entry.end.clone()),
+    ];
+
+    let label_width = 14;
+
+    for (row_index, (field, label, text)) in rows.iter().enumerate()
+    {
+        let row_pos = popup.at(2, 1 + row_index as u16);
+        let is_selected = *field == selected_field;
+
+        draw_field_label(&mut app_manager.renderer, label, label_width, &row_pos, is_selected);
+
+        app_manager.renderer.draw(format!(" {}", text));
+
+        if is_selected && *field != ManualEntryField::Tag
+        {
+            app_manager.renderer.draw(CURSOR);
+        }
+    }
+
+    let divider_y = popup.y + popup.height - 3;
+    draw_divider(&mut app_manager.renderer, &popup, divider_y);
+
+    let message_pos = Vector2::new(popup.x + 2, divider_y + 1);
+
+    if let Some(error) = &app_manager.manual_entry.error
+    {
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED);
+        app_manager.renderer.draw_at(error, &message_pos);
+        app_manager.renderer.pop_color(ColorType::Foreground);
+    }
+    else
+    {
+        app_manager.renderer.draw_at("[TAB] next field  [ENTER] save  [ESC] cancel", &message_pos);
+    }
+
+    close_popup(&mut app_manager.renderer);
+}
+
+/// The description and tag of the running session, edited without stopping its timer.
+fn draw_running_edit_popup(app_manager: &mut AppManager, selected_field: RunningEditField)
+{
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup = get_centered_area(&Vector2::new(terminal_size.x - 32, 6), &terminal_size);
+
+    open_popup(&mut app_manager.renderer, &popup, "EDIT RUNNING SESSION");
+
+    let no_tags_msg = "- empty -".to_string();
+    let selected_tag = app_manager.tags.get(app_manager.temp_tag_index).unwrap_or(&no_tags_msg);
+    let tag_text = if selected_field == RunningEditField::Tag
+    {
+        format!("< {} >", selected_tag)
+    }
+    else
+    {
+        selected_tag.clone()
+    };
+
+    let description = app_manager.session_edit_buffer.as_ref().map(|session| session.description.clone()).unwrap_or_default();
+    let label_width = 14;
+    let text_width = popup.width as usize - label_width - 6;
+
+    let rows = [
+        (RunningEditField::Description, "DESCRIPTION", get_tail_within_width(&description, text_width)),
+        (RunningEditField::Tag, "TAG", tag_text),
+    ];
+
+    for (row_index, (field, label, text)) in rows.iter().enumerate()
+    {
+        let row_pos = popup.at(2, 1 + row_index as u16);
+        let is_selected = *field == selected_field;
+
+        draw_field_label(&mut app_manager.renderer, label, label_width, &row_pos, is_selected);
+
+        app_manager.renderer.draw(format!(" {}", text));
+
+        if is_selected && *field == RunningEditField::Description
+        {
+            app_manager.renderer.draw(CURSOR);
+        }
+    }
+
+    let divider_y = popup.y + popup.height - 3;
+    draw_divider(&mut app_manager.renderer, &popup, divider_y);
+
+    app_manager.renderer.draw_at("[TAB] next field  [ENTER] save  [ESC] cancel", &Vector2::new(popup.x + 2, divider_y + 1));
+
+    close_popup(&mut app_manager.renderer);
+}
+
+fn draw_overlap_popup(app_manager: &mut AppManager)
+{
+    let conflicts = app_manager
+        .overlapping_session_indices
+        .iter()
+        .map(|index| &app_manager.sessions[*index])
+        .map(|session| {
+            format!(
+                "{} {}-{} {}",
+                session.get_date_string(),
+                session.get_start_time_string(),
+                session.get_end_time_string().unwrap_or(String::from("now")),
+                session.description
+            )
+        })
+        .collect::<Vec<String>>();
+
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup = get_centered_area(&Vector2::new(cmp::min(terminal_size.x - 4, 64), conflicts.len() as u16 + 6), &terminal_size);
+    let text_width = popup.width as usize - 4;
+
+    open_popup(&mut app_manager.renderer, &popup, "OVERLAPS EXISTING SESSIONS");
+
+    for (row_index, conflict) in conflicts.iter().enumerate()
+    {
+        let row_pos = popup.at(2, 2 + row_index as u16);
+        app_manager.renderer.draw_at(truncate_to_width(conflict, text_width), &row_pos);
+    }
+
+    let options_pos = popup.at(2, popup.height - 2);
+
+    if app_manager.overlap_adjust_failed
+    {
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED);
+        app_manager.renderer.draw_at("CANNOT ADJUST, SESSION IS FULLY COVERED", &Vector2::new(options_pos.x, options_pos.y - 1));
+        app_manager.renderer.pop_color(ColorType::Foreground);
+    }
+
+    app_manager.renderer.go_to_position(&options_pos);
+
+    for (key, label) in [('y', "es, keep   "), ('a', "djust   "), ('n', "o, go back")]
+    {
+        draw_key_hint(&mut app_manager.renderer, key, label);
+    }
+
+    close_popup(&mut app_manager.renderer);
+}
+
+fn draw_export_popup(app_manager: &mut AppManager, selected_field: ExportField)
+{
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup = get_centered_area(&Vector2::new(terminal_size.x - 32, 7), &terminal_size);
+
+    open_popup(&mut app_manager.renderer, &popup, "EXPORT ICALENDAR");
+
+    let rows = [
+        (ExportField::From, "FROM", app_manager.export_form.from.clone()),
+        (ExportField::To, "TO", app_manager.export_form.to.clone()),
+        (ExportField::Path, "PATH", app_manager.export_form.path.clone()),
+    ];
+
+    let label_width = 6;
+
+    for (row_index, (field, label, text)) in rows.iter().enumerate()
+    {
+        let row_pos = popup.at(2, 1 + row_index as u16);
+        let is_selected = *field == selected_field;
+
+        draw_field_label(&mut app_manager.renderer, label, label_width, &row_pos, is_selected);
+
+        app_manager.renderer.draw(format!(" {}", text));
+
+        if is_selected
+        {
+            app_manager.renderer.draw(CURSOR);
+        }
+    }
+
+    let message_pos = popup.at(2, popup.height - 2);
+
+    if let Some(error) = &app_manager.export_form.error
+    {
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED);
+        app_manager.renderer.draw_at(error, &message_pos);
+        app_manager.renderer.pop_color(ColorType::Foreground);
+    }
+    else
+    {
+        app_manager.renderer.draw_at("[TAB] next field  [ENTER] export  [ESC] cancel", &message_pos);
+    }
+
+    close_popup(&mut app_manager.renderer);
+}
+
+fn draw_import_path_popup(app_manager: &mut AppManager)
+{
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup = get_centered_area(&Vector2::new(terminal_size.x - 32, 5), &terminal_size);
+
+    open_popup(&mut app_manager.renderer, &popup, "IMPORT FILE");
+
+    let path_label = "PATH ";
+    let path_pos = popup.at(2, 1);
+
+    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+    app_manager.renderer.draw_at(path_label, &path_pos);
+    app_manager.renderer.pop_color(ColorType::Foreground);
+    app_manager.renderer.draw(format!("{}{}", &app_manager.import_path_buffer, CURSOR));
+
+    let message_pos = popup.at(2, 3);
+
+    if let Some(error) = &app_manager.import_error
+    {
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED);
+        app_manager.renderer.draw_at(error, &message_pos);
+        app_manager.renderer.pop_color(ColorType::Foreground);
+    }
+    else
+    {
+        app_manager.renderer.draw_at("Toggl CSV, Timewarrior JSON, timeclock or any CSV file", &message_pos);
+    }
+
+    close_popup(&mut app_manager.renderer);
+}
+
+fn draw_csv_mapping_popup(app_manager: &mut AppManager, selected_field: CsvMappingField)
+{
+    let Some(csv_mapping) = &app_manager.csv_mapping
+    else
+    {
+        return;
+    };
+
+    let describe_column =
+        |column: usize| format!("{} {} (e.g. {})", column + 1, csv_mapping.get_header(column), csv_mapping.get_sample(column));
+
+    let rows = [
+        (CsvMappingField::Description, "DESCRIPTION", describe_column(csv_mapping.description_column)),
+        (CsvMappingField::Tag, "TAG", csv_mapping.tag_column.map_or(String::from("- none -"), describe_column)),
+        (CsvMappingField::Start, "START", describe_column(csv_mapping.start_column)),
+        (CsvMappingField::End, "END", describe_column(csv_mapping.end_column)),
+        (CsvMappingField::DateFormat, "DATE FORMAT", csv_mapping.get_date_format_label().to_string()),
+    ];
+
+    let preview_rows = csv_mapping
+        .rows
+        .iter()
+        .take(4)
+        .map(|row| row.iter().map(|field| field.trim()).collect::<Vec<&str>>().join(" | "))
+        .collect::<Vec<String>>();
+
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup = get_centered_area(&Vector2::new(terminal_size.x - 16, (rows.len() + preview_rows.len()) as u16 + 5), &terminal_size);
+    let text_width = popup.width as usize - 4;
+
+    open_popup(&mut app_manager.renderer, &popup, "MAP CSV COLUMNS");
+
+    for (row_index, preview_row) in preview_rows.iter().enumerate()
+    {
+        let preview_row = truncate_to_width(preview_row, text_width);
+        app_manager.renderer.draw_at(preview_row, &popup.at(2, 1 + row_index as u16));
+    }
+
+    let divider_y = popup.y + 1 + preview_rows.len() as u16;
+    draw_divider(&mut app_manager.renderer, &popup, divider_y);
+
+    let label_width = 14;
+
+    for (row_index, (field, label, text)) in rows.iter().enumerate()
+    {
+        let row_pos = Vector2::new(popup.x + 2, divider_y + 1 + row_index as u16);
+        let is_selected = *field == selected_field;
+
+        draw_field_label(&mut app_manager.renderer, label, label_width, &row_pos, is_selected);
+
+        let text = if is_selected
+        {
+            format!("< {} >", text)
+        }
+        else
+        {
+            text.clone()
+        };
+
+        app_manager.renderer.draw(format!(" {}", truncate_to_width(&text, text_width - label_width - 1)));
+    }
+
+    app_manager
+        .renderer
+        .draw_at("[LEFT/RIGHT] change  [ENTER] import  [ESC] back", &popup.at(2, popup.height - 2));
+
+    close_popup(&mut app_manager.renderer);
+}
+
+fn draw_import_summary_popup(app_manager: &mut AppManager)
+{
+    let Some(summary) = &app_manager.import_summary
+    else
+    {
+        return;
+    };
+
+    let rows = [
+        format!("Imported sessions   {}", summary.imported),
+        format!("Duplicates skipped  {}", summary.duplicates),
+        format!("Invalid entries     {}", summary.invalid),
+    ];
+
+    let popup = get_centered_area(&Vector2::new(40, rows.len() as u16 + 4), &app_manager.renderer.get_terminal_size());
+
+    open_popup(&mut app_manager.renderer, &popup, "IMPORT FINISHED");
+
+    for (row_index, row) in rows.iter().enumerate()
+    {
+        app_manager.renderer.draw_at(row, &popup.at(2, 1 + row_index as u16));
+    }
+
+    app_manager.renderer.draw_at("[ENTER] continue", &popup.at(2, popup.height - 2));
+
+    close_popup(&mut app_manager.renderer);
+}
+
+fn draw_locked_popup(app_manager: &mut AppManager)
+{
+    let popup = get_centered_area(&Vector2::new(48, 6), &app_manager.renderer.get_terminal_size());
+
+    open_popup(&mut app_manager.renderer, &popup, "ALREADY RUNNING");
+
+    app_manager.renderer.draw_at("Another instance is using this database.", &popup.at(2, 1));
+
+    let text_pos_y = popup.y + popup.height - 2;
+    let read_only_pos = Vector2::new(popup.x + popup.width / 4 - 4, text_pos_y);
+    let quit_pos = Vector2::new(popup.x + (popup.width / 4) * 3 - 4, text_pos_y);
+
+    app_manager.renderer.go_to_position(&read_only_pos);
+    draw_key_hint(&mut app_manager.renderer, 'r', "ead-only");
+    app_manager.renderer.go_to_position(&quit_pos);
+    draw_key_hint(&mut app_manager.renderer, 'q', "uit");
+
+    close_popup(&mut app_manager.renderer);
+}
+
+fn draw_import_report_popup(app_manager: &mut AppManager)
+{
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let max_rows = terminal_size.y.saturating_sub(12) as usize;
+
+    let mut rows = app_manager
+        .rejected_lines
+        .iter()
+        .take(max_rows)
+        .map(|rejected_line| format!("line {:>4}  {}", rejected_line.line_number, rejected_line.content))
+        .collect::<Vec<String>>();
+
+    if app_manager.rejected_lines.len() > max_rows
+    {
+        rows.push(format!("... and {} more", app_manager.rejected_lines.len() - max_rows));
+    }
+
+    let summary =
+        format!("{} malformed lines were moved to {}", app_manager.rejected_lines.len(), app_manager.get_quarantine_file_name());
+
+    let popup = get_centered_area(&Vector2::new(cmp::min(terminal_size.x - 4, 80), rows.len() as u16 + 6), &terminal_size);
+    let text_width = popup.width as usize - 4;
+
+    open_popup(&mut app_manager.renderer, &popup, "DATABASE REPAIRED");
+
+    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+    app_manager.renderer.draw_at(truncate_to_width(&summary, text_width), &popup.at(2, 1));
+    app_manager.renderer.pop_color(ColorType::Foreground);
+
+    for (row_index, row) in rows.iter().enumerate()
+    {
+        let row_pos = popup.at(2, 3 + row_index as u16);
+        app_manager.renderer.draw_at(truncate_to_width(row, text_width), &row_pos);
+    }
+
+    app_manager.renderer.draw_at("[ENTER] continue", &popup.at(2, popup.height - 2));
+
+    close_popup(&mut app_manager.renderer);
+}
+
+fn draw_backups_popup(app_manager: &mut AppManager)
+{
+    let no_backups_msg = "- no backups -".to_string();
+    let labels = if app_manager.backups.is_empty()
+    {
+        vec![no_backups_msg]
+    }
+    else
+    {
+        app_manager.backups.iter().map(|backup| app_manager.get_backup_label(backup)).collect::<Vec<String>>()
+    };
+
+    let longest_label = labels.iter().map(String::len).max().unwrap_or(0) as u16;
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup = get_centered_area(&Vector2::new(longest_label + 8, labels.len() as u16 + 2), &terminal_size);
+
+    open_popup(&mut app_manager.renderer, &popup, "BACKUPS");
+
+    let rows = labels.iter().map(|label| format!("{:<pad$}", label, pad = longest_label as usize + 1)).collect::<Vec<String>>();
+    let selected_index = Some(app_manager.selected_backup_index).filter(|_| !app_manager.backups.is_empty());
+    let list_area = Rect::new(popup.x + 2, popup.y + 1, popup.width - 4, rows.len() as u16);
+
+    draw_list(&mut app_manager.renderer, &rows, selected_index, list_area);
+
+    close_popup(&mut app_manager.renderer);
+}
+
+/// Lists the workspaces with the open one marked, plus the name input while a new one is typed.
+fn draw_workspaces_popup(app_manager: &mut AppManager, workspace_state: WorkspaceState)
+{
+    let title = match &app_manager.workspace_error
+    {
+        Some(error) => format!("WORKSPACES [{error}]"),
+        None => String::from("WORKSPACES"),
+    };
+
+    let longest_name = app_manager.workspaces.iter().map(|workspace| get_display_width(workspace)).max().unwrap_or(0);
+    let list_width = cmp::max(cmp::max(longest_name + 6, title.len() + 2), 28) as u16;
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup = get_centered_area(&Vector2::new(list_width + 4, app_manager.workspaces.len() as u16 + 2), &terminal_size);
+
+    open_popup(&mut app_manager.renderer, &popup, &title);
+
+    let rows = app_manager
+        .workspaces
+        .iter()
+        .map(|workspace| {
+            let open_mark = if workspace == app_manager.get_workspace()
+            {
+                MARK
+            }
+            else
+            {
+                ' '
+            };
+
+            format!("{} {}", open_mark, pad_to_width(workspace, list_width as usize - 6))
+        })
+        .collect::<Vec<String>>();
+    let list_area = Rect::new(popup.x + 2, popup.y + 1, list_width - 1, rows.len() as u16);
+
+    draw_list(&mut app_manager.renderer, &rows, Some(app_manager.selected_workspace_index), list_area);
+
+    if workspace_state == WorkspaceState::New
+    {
+        let input_window = Rect::new(popup.x + 2, popup.y + 1, popup.width - 4, 3);
+        draw_window(&mut app_manager.renderer, &input_window, Some("NEW WORKSPACE"));
+
+        let name = get_tail_within_width(&app_manager.workspace_name_buffer, input_window.width as usize - 5);
+        app_manager.renderer.draw_at(format!("{}{}", name, CURSOR), &input_window.at(2, 1));
+    }
+
+    close_popup(&mut app_manager.renderer);
+}
+
+/// Earlier descriptions matching the typed text, hanging below the description input.
+fn draw_description_suggestions(app_manager: &mut AppManager, position: &Vector2, width: u16)
+{
+    let suggestions = app_manager.get_description_suggestions();
+
+    if suggestions.is_empty()
+    {
+        return;
+    }
+
+    let dropdown = Rect::new(position.x, position.y, width, suggestions.len() as u16 + 2);
+    draw_window(&mut app_manager.renderer, &dropdown, None);
+
+    let text_width = width as usize - 6;
+
+    for (index, (description, tag)) in suggestions.iter().enumerate()
+    {
+        let selected_row = app_manager.selected_suggestion_index == Some(index);
+        let tag = ellipsize(tag, text_width / 3);
+        let description_width = text_width - get_display_width(&tag) - 1;
+        let description = pad_to_width(&ellipsize(description, description_width), description_width);
+
+        draw_selectable(&mut app_manager.renderer, selected_row, |renderer| {
+            renderer.draw_at(format!(" {} {} ", get_selection_arrow(selected_row), description), &dropdown.at(1, 1 + index as u16));
+            renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+            renderer.draw(format!("{tag} "));
+            renderer.pop_color(ColorType::Foreground);
+        });
+    }
+}
+
+/// Lists the tags in dropdown order with their usage counts, scrolling to keep the selected tag visible when there are
+/// more tags than rows below `position`.
+fn draw_tag_dropdown(
+    renderer: &mut Out,
+    tags: &[String],
+    entries: &[(usize, usize)],
+    selected_tag_index: usize,
+    position: &Vector2,
+    label: &str,
+    filter: &TextInput,
+)
+{
+    let terminal_size = renderer.get_terminal_size();
+
+    let title = if filter.is_empty()
+    {
+        label.to_string()
+    }
+    else
+    {
+        format!("{} /{}", label, filter)
+    };
+    let title = title.as_str();
+
+    if entries.is_empty()
+    {
+        let no_tags_msg = if filter.is_empty()
+        {
+            "- empty -"
+        }
+        else
+        {
+            "- no match -"
+        };
+        let dropdown_width = cmp::max(no_tags_msg.len(), get_display_width(title) + 2) as u16 + 4;
+        let dropdown = Rect::new(position.x, position.y, dropdown_width, 3);
+
+        draw_window(renderer, &dropdown, Some(title).filter(|_| !filter.is_empty()));
+        draw_filter_cursor(renderer, dropdown, label, filter);
+        renderer.draw_at(no_tags_msg, &dropdown.at(2, 1));
+
+        return;
+    }
+
+    let visible_row_count = cmp::max(cmp::min(entries.len(), terminal_size.y.saturating_sub(position.y + 4) as usize), 1);
+    let selected_position = entries.iter().position(|(tag_index, _)| *tag_index == selected_tag_index);
+
+    let longest_tag = entries.iter().map(|(tag_index, _)| get_display_width(&tags[*tag_index])).max().unwrap_or(0);
+    let count_width = entries.iter().map(|(_, use_count)| use_count.to_string().len()).max().unwrap_or(1);
+    let title_width = get_display_width(title) + 2;
+    let tag_width = cmp::max(longest_tag, title_width - cmp::min(title_width, count_width + 1));
+
+    let dropdown = Rect::new(position.x, position.y, (tag_width + count_width) as u16 + 9, visible_row_count as u16 + 2);
+    draw_window(renderer, &dropdown, Some(title));
+    draw_filter_cursor(renderer, dropdown, label, filter);
+
+    let rows = entries
+        .iter()
+        .map(|(tag_index, use_count)| format!("{} {:>count_width$} ", pad_to_width(&tags[*tag_index], tag_width), use_count))
+        .collect::<Vec<String>>();
+    let list_area = Rect::new(dropdown.x + 2, dropdown.y + 1, dropdown.width - 4, visible_row_count as u16);
+    let first_row = draw_list(renderer, &rows, selected_position, list_area);
+
+    draw_scroll_markers(renderer, &dropdown, first_row, visible_row_count, entries.len());
+}
+
+/// Marks where the filter typed into the title of a dropdown labeled `label` is edited, nothing while it is empty.
+fn draw_filter_cursor(renderer: &mut Out, dropdown: Rect, label: &str, filter: &TextInput)
+{
+    if filter.is_empty()
+    {
+        return;
+    }
+
+    let cursor_column = get_display_width(&format!("   {} /{}", label, filter.get_text_before_cursor())) as u16;
+
+    renderer.push_color(ColorType::Background, COL_TEXT_HIGHLIGHT);
+    renderer.push_color(ColorType::Foreground, COL_TEXT_BLACK);
+    renderer.draw_at(filter.get_char_at_cursor().unwrap_or(' '), &dropdown.at(cursor_column, 0));
+    renderer.pop_color(ColorType::Foreground);
+    renderer.pop_color(ColorType::Background);
+}
+
+/// Lists the tags to move the marked sessions to.
+fn draw_retag_popup(app_manager: &mut AppManager)
+{
+    let title = format!("RETAG {} SESSIONS", app_manager.marked_session_ids.len());
+    let longest_tag = app_manager.tags.iter().map(|tag| get_display_width(tag)).max().unwrap_or(0);
+    let longest_label = cmp::max(longest_tag, title.len()) as u16;
+
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup = get_centered_area(&Vector2::new(longest_label + 8, app_manager.tags.len() as u16 + 2), &terminal_size);
+
+    open_popup(&mut app_manager.renderer, &popup, &title);
+
+    let rows = app_manager.tags.iter().map(|tag| pad_to_width(tag, longest_label as usize + 1)).collect::<Vec<String>>();
+    let list_area = Rect::new(popup.x + 2, popup.y + 1, popup.width - 4, rows.len() as u16);
+
+    draw_list(&mut app_manager.renderer, &rows, Some(app_manager.temp_tag_index), list_area);
+
+    close_popup(&mut app_manager.renderer);
+}
+
+fn draw_daily_summary(app_manager: &mut AppManager, main_window_size: &Vector2)
+{
+    let today_total = format_duration(app_manager.get_today_total());
+
+    let summary = match app_manager.get_running_session_elapsed()
+    {
+        Some(running_elapsed) => format!("TODAY {} | RUNNING {}", today_total, format_duration(running_elapsed)),
+        None => format!("TODAY {}", today_total),
+    };
+
+    let time_left = app_manager.get_running_session_time_left();
+    let summary = match time_left
+    {
+        Some(time_left) if time_left > TimeDelta::zero() => format!("{summary} | LEFT {}", format_duration(time_left)),
+        Some(time_left) => format!("{summary} | TIME'S UP +{}", format_duration(-time_left)),
+        None => summary,
+    };
+
+    // Older sessions are loaded page by page, once the selection moves past the oldest loaded one.
+    let summary = if app_manager.has_unloaded_sessions()
+    {
+        format!("{summary} | OLDER {ARROW_DOWN}")
+    }
+    else
+    {
+        summary
+    };
+
+    let summary_line = Rect::new(0, main_window_size.y - 1, main_window_size.x, 1);
+    let summary_color = if time_left.is_some_and(|time_left| time_left <= TimeDelta::zero())
+    {
+        COL_TEXT_RED
+    }
+    else
+    {
+        COL_BG_MAIN
+    };
+
+    app_manager.renderer.push_color(ColorType::Foreground, summary_color);
+    app_manager.renderer.push_color(ColorType::Background, COL_OUTLINE_MAIN);
+    draw_title(&mut app_manager.renderer, &summary, &summary_line);
+    app_manager.renderer.pop_color(ColorType::Foreground);
+    app_manager.renderer.pop_color(ColorType::Background);
+}
+
+/// The selected month with a cell per day. Week numbers take a column of their own, narrowing the cells to still fit the
+/// smallest terminal.
+fn draw_calendar(app_manager: &mut AppManager)
+{
+    const WEEK_HEIGHT: u16 = 2;
+    const WEEK_NUMBER_WIDTH: u16 = 4;
+
+    let selected_date = app_manager.calendar_date;
+    let today = app_manager.get_today();
+    let week_start = app_manager.config.week_start;
+    let first_of_month = selected_date.with_day(1).unwrap_or(selected_date);
+    let leading_days = first_of_month.weekday().days_since(week_start) as u16;
+    let days_in_month =
+        (first_of_month.checked_add_months(Months::new(1)).unwrap_or(first_of_month) - first_of_month).num_days() as u16;
+    let week_count = (leading_days + days_in_month).div_ceil(7);
+    let show_week_numbers = app_manager.get_week_number(first_of_month).is_some();
+
+    let (cell_width, week_number_width) = if show_week_numbers
+    {
+        (10, WEEK_NUMBER_WIDTH)
+    }
+    else
+    {
+        (11, 0)
+    };
+
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup = get_centered_area(&Vector2::new(cell_width * 7 + week_number_width + 3, week_count * WEEK_HEIGHT + 5), &terminal_size);
+
+    open_popup(&mut app_manager.renderer, &popup, &format!("{}", selected_date.format("%B %Y")).to_uppercase());
+
+    let grid_pos = Vector2::new(popup.x + 2 + week_number_width, popup.y + 1);
+
+    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+
+    for weekday_index in 0..7
+    {
+        let weekday = (first_of_month - TimeDelta::days(i64::from(leading_days)) + TimeDelta::days(weekday_index)).format("%a");
+        app_manager.renderer.draw_at(weekday, &Vector2::new(grid_pos.x + weekday_index as u16 * cell_width, grid_pos.y));
+    }
+
+    for week_index in 0..week_count
+    {
+        let week_start_date = first_of_month + TimeDelta::days(i64::from(week_index * 7) - i64::from(leading_days));
+
+        if let Some(week_number) = app_manager.get_week_number(week_start_date)
+        {
+            let week_number_pos = Vector2::new(popup.x + 2, grid_pos.y + 1 + week_index * WEEK_HEIGHT);
+            app_manager.renderer.draw_at(format!("W{week_number:02}"), &week_number_pos);
+        }
+    }
+
+    app_manager.renderer.pop_color(ColorType::Foreground);
+
+    let mut month_total = TimeDelta::zero();
+
+    for day_offset in 0..days_in_month
+    {
+        let date = first_of_month + TimeDelta::days(i64::from(day_offset));
+        let day_total = app_manager.get_day_total(date);
+        month_total += day_total;
+
+        let cell_index = leading_days + day_offset;
+        let cell_pos = Vector2::new(grid_pos.x + (cell_index % 7) * cell_width, grid_pos.y + 1 + (cell_index / 7) * WEEK_HEIGHT);
+
+        let intensity = match day_total.num_hours()
+        {
+            _ if day_total.is_zero() => 0,
+            0..2 => 1,
+            2..4 => 2,
+            4..6 => 3,
+            _ => 4,
+        };
+
+        let day_off = app_manager.get_day_off(date);
+
+        let (background, foreground) = if date == selected_date
+        {
+            (COL_TEXT_HIGHLIGHT, COL_TEXT_BLACK)
+        }
+        else if day_off.is_some()
+        {
+            (COL_INTENSITY[intensity], COL_TEXT_DIM)
+        }
+        else if date == today
+        {
+            (COL_INTENSITY[intensity], COL_TEXT_HIGHLIGHT)
+        }
+        else
+        {
+            (COL_INTENSITY[intensity], COL_TEXT_WHITE)
+        };
+
+        let total_text = if let Some(day_off) = day_off
+        {
+            day_off.to_string()
+        }
+        else if day_total.is_zero()
+        {
+            String::new()
+        }
+        else
+        {
+            format_duration_short(day_total)
+        };
+
+        app_manager.renderer.push_color(ColorType::Background, background);
+        app_manager.renderer.push_color(ColorType::Foreground, foreground);
+        app_manager.renderer.draw_at(format!(" {:<pad$}", date.day(), pad = cell_width as usize - 2), &cell_pos);
+        app_manager
+            .renderer
+            .draw_at(format!(" {:<pad$}", total_text, pad = cell_width as usize - 2), &Vector2::new(cell_pos.x, cell_pos.y + 1));
+        app_manager.renderer.pop_color(ColorType::Foreground);
+        app_manager.renderer.pop_color(ColorType::Background);
+    }
+
+    let footer_pos = popup.at(2, popup.height - 2);
+    app_manager.renderer.draw_at(
+        format!("MONTH {}  [ARROWS] day  [PGUP/PGDN] month  [ENTER] open  [ESC] close", format_duration_short(month_total)),
+        &footer_pos,
+    );
+
+    close_popup(&mut app_manager.renderer);
+}
+
+fn draw_settings(app_manager: &mut AppManager, selected_field: SettingsField)
+{
+    let terminal_size = app_manager.renderer.get_terminal_size();
+
+    let rows = SettingsField::ALL.map(|field| (field, app_manager.get_setting_text(field)));
+    let errors = app_manager.theme_errors.clone();
+    let themes_path = format!("Themes folder: {}", app_manager.get_themes_path().display());
+
+    // The rows scroll to keep the selected one in view when they don't all fit.
+    let visible_row_count = cmp::min(rows.len(), (terminal_size.y as usize).saturating_sub(errors.len() + 10).max(1));
+    let selected_index = rows.iter().position(|(field, _)| *field == selected_field).unwrap_or_default();
+    let first_row_index = get_first_visible_row(selected_index, visible_row_count);
+
+    let popup_size = Vector2::new(cmp::min(terminal_size.x - 4, 72), (visible_row_count + errors.len()) as u16 + 6);
+    let popup = get_centered_area(&popup_size, &terminal_size);
+    let text_width = popup.width as usize - 4;
+    let label_width = 22;
+
+    open_popup(&mut app_manager.renderer, &popup, "SETTINGS");
+
+    for (row_index, (field, text)) in rows.iter().skip(first_row_index).take(visible_row_count).enumerate()
+    {
+        let row_pos = popup.at(2, 2 + row_index as u16);
+        let is_selected = *field == selected_field;
+
+        draw_field_label(&mut app_manager.renderer, field.get_label(), label_width, &row_pos, is_selected);
+
+        let text = if is_selected
+        {
+            format!("< {text} >")
+        }
+        else
+        {
+            text.clone()
+        };
+
+        app_manager.renderer.draw(format!(" {}", truncate_to_width(&text, text_width - label_width - 1)));
+    }
+
+    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED);
+    for (error_index, error) in errors.iter().enumerate()
+    {
+        let error = truncate_to_width(error, text_width);
+        app_manager
+            .renderer
+            .draw_at(error, &popup.at(2, 3 + (visible_row_count + error_index) as u16));
+    }
+    app_manager.renderer.pop_color(ColorType::Foreground);
+
+    // Ephemeral workspaces only have the built-in themes.
+    if !app_manager.is_ephemeral()
     {
-        render(&mut app_manager);
+        let themes_path = truncate_to_width(&themes_path, text_width);
+        app_manager.renderer.draw_at(themes_path, &popup.at(2, popup.height - 3));
+    }
 
-        app_manager.renderer.check_color_stacks();
+    app_manager.renderer.draw_at(
+        "[UP/DOWN] select  [LEFT/RIGHT] change  [ENTER] save  [ESC] cancel",
+        &popup.at(2, popup.height - 2),
+    );
 
-        update(&mut app_manager);
-    }
+    close_popup(&mut app_manager.renderer);
 }
 
-#[allow(clippy::too_many_lines)]
-fn render(app_manager: &mut AppManager)
+fn draw_gaps(app_manager: &mut AppManager)
 {
     let terminal_size = app_manager.renderer.get_terminal_size();
-    let main_window_size = Vector2::new(terminal_size.x, terminal_size.y - 1);
 
-    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_WHITE);
-    app_manager.renderer.push_color(ColorType::Background, COL_BG_MAIN);
+    let gaps = app_manager.get_gaps();
+    let untracked: TimeDelta = gaps.iter().map(|(start, end)| *end - *start).sum();
+    let visible_gap_count = cmp::min(gaps.len(), terminal_size.y.saturating_sub(12) as usize);
+    let first_visible_gap = get_first_visible_row(app_manager.selected_gap_index, visible_gap_count);
 
-    app_manager.renderer.push_color(ColorType::Foreground, COL_OUTLINE_MAIN);
-    draw_window(&mut app_manager.renderer, &main_window_size, &Vector2::new(0, 0));
-
-    let content_offset = Vector2::new(2, 1);
-
-    let command_column_width = 6;
-    let date_column_width = 12;
-    let timestamp_column_width = 10;
-
-    let tag_column_width = (app_manager.sessions.iter().map(|s| &s.tag).map(String::len).max().unwrap_or(10) + 2) as u16;
-
-    let command_column_pos = 0;
-    let date_column_pos = command_column_width;
-    let description_column_pos = date_column_pos + date_column_width;
-    let duration_column_pos = main_window_size.x - timestamp_column_width - 2;
-    let end_column_pos = duration_column_pos - timestamp_column_width - 1;
-    let start_column_pos = end_column_pos - timestamp_column_width - 1;
-    let tag_column_pos = start_column_pos - tag_column_width - 1;
-
-    let dividers = [
-        (command_column_pos, "Cmd"),
-        (date_column_pos, "Date"),
-        (description_column_pos, "Description"),
-        (duration_column_pos, "Duration"),
-        (end_column_pos, "End"),
-        (start_column_pos, "Start"),
-        (tag_column_pos, "Tag"),
-    ];
+    let title = format!("UNTRACKED TIME {}", app_manager.gaps_date.format("%a %d %b %Y")).to_uppercase();
 
-    for (index, (column_pos, section_title)) in dividers.iter().enumerate()
+    // Room for the active time and main window of each gap.
+    let popup_width = if app_manager.day_activity.is_some()
     {
-        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_HIGHLIGHT);
-        app_manager.renderer.draw_at(section_title, &Vector2::new(*column_pos + content_offset.x, content_offset.y));
-        app_manager.renderer.pop_color(ColorType::Foreground);
+        96
+    }
+    else
+    {
+        64
+    };
+    let popup_size = Vector2::new(cmp::min(terminal_size.x - 4, popup_width), cmp::max(visible_gap_count as u16, 1) + 6);
+    let popup = get_centered_area(&popup_size, &terminal_size);
 
-        if index == 0
+    open_popup(&mut app_manager.renderer, &popup, &title);
+
+    let activity_summary = match &app_manager.day_activity
+    {
+        Some(Ok(day_activity)) =>
         {
-            continue;
+            let active: TimeDelta = gaps.iter().map(|(start, end)| day_activity.get_active_time_within(*start, *end)).sum();
+            format!(", active while untracked {}", format_duration_short(active))
         }
+        Some(Err(error)) => format!(" [{error}]"),
+        None => String::new(),
+    };
+    let working_hours = match app_manager.get_working_hours(app_manager.gaps_date)
+    {
+        Some((start, end)) => format!("Working hours {}-{}", start.format("%H:%M"), end.format("%H:%M")),
+        None => String::from("No working hours"),
+    };
+    let working_hours = format!("{working_hours}, untracked {}{activity_summary}", format_duration_short(untracked));
 
-        app_manager.renderer.draw_at(INTERSECT_T, &Vector2::new(*column_pos, 0));
+    app_manager
+        .renderer
+        .draw_at(truncate_to_width(&working_hours, popup.width as usize - 4), &popup.at(2, 1));
+
+    if let Some(day_off) = app_manager.get_day_off(app_manager.gaps_date)
+    {
+        app_manager.renderer.draw_at(format!("No gaps, marked as {day_off}"), &popup.at(2, 2));
+    }
+    else if gaps.is_empty()
+    {
+        app_manager.renderer.draw_at("No gaps", &popup.at(2, 2));
+    }
 
-        for row_index in 1..main_window_size.y - 1
+    for (row_index, (gap_index, (start, end))) in gaps.iter().enumerate().skip(first_visible_gap).take(visible_gap_count).enumerate()
+    {
+        let activity_columns = match &app_manager.day_activity
         {
-            app_manager.renderer.draw_at(DIVIDER_V, &Vector2::new(*column_pos, row_index));
-        }
+            Some(Ok(day_activity)) =>
+            {
+                let active = day_activity.get_active_time_within(*start, *end);
+                let block = app_manager.get_gap_activity_block(*start, *end).map(|(.., description)| description);
 
-        app_manager.renderer.draw_at(INTERSECT_B, &Vector2::new(*column_pos, main_window_size.y - 1));
+                // Blocks worth a session are marked, named after their main window.
+                let marker = if block.is_some()
+                {
+                    ARROW
+                }
+                else
+                {
+                    ' '
+                };
+                format!("{marker} active {:>8}  {}", format_duration_short(active), block.unwrap_or_default())
+            }
+            _ => String::new(),
+        };
+        let row_width = popup.width as usize - 4;
+        let row = format!(
+            " {} - {}  {:>8} {activity_columns}",
+            start.format("%H:%M"),
+            end.format("%H:%M"),
+            format_duration_short(*end - *start)
+        );
+        let row = pad_to_width(&truncate_to_width(&row, row_width), row_width);
+        let row_pos = popup.at(2, 2 + row_index as u16);
+
+        draw_selectable(&mut app_manager.renderer, gap_index == app_manager.selected_gap_index, |renderer| {
+            renderer.draw_at(row, &row_pos);
+        });
     }
 
-    app_manager.renderer.pop_color(ColorType::Foreground);
+    app_manager
+        .renderer
+        .draw_at("[LEFT/RIGHT] day  [ENTER] fill gap  [ESC] close", &popup.at(2, popup.height - 2));
 
-    app_manager.renderer.push_color(ColorType::Foreground, COL_BG_MAIN);
-    app_manager.renderer.push_color(ColorType::Background, COL_OUTLINE_MAIN);
-    // app_manager.renderer.draw_at(" ".repeat(app_manager.renderer.get_terminal_size().x as usize), &Vector2::new(0, 0));
-    draw_window_title(&mut app_manager.renderer, "SESSIONS", &Vector2::new(0, 0));
-    app_manager.renderer.pop_color(ColorType::Foreground);
-    app_manager.renderer.pop_color(ColorType::Background);
+    close_popup(&mut app_manager.renderer);
+}
+
+/// One line per recorded change, newest first, with the selected change shown in full below the list.
+fn draw_audit_log(app_manager: &mut AppManager)
+{
+    let terminal_size = app_manager.renderer.get_terminal_size();
+
+    let entry_count = app_manager.audit_entries.len();
+    let visible_entry_count = cmp::min(entry_count, terminal_size.y.saturating_sub(14) as usize);
+    let first_visible_entry = get_first_visible_row(app_manager.selected_audit_index, visible_entry_count);
+
+    let popup_size = Vector2::new(cmp::min(terminal_size.x - 4, 100), cmp::max(visible_entry_count as u16, 1) + 7);
+    let popup = get_centered_area(&popup_size, &terminal_size);
+    let text_width = popup.width as usize - 4;
+
+    open_popup(&mut app_manager.renderer, &popup, &format!("HISTORY OF CHANGES ({entry_count})"));
 
-    for (session_index, offset) in (0..app_manager.sessions.len()).rev().enumerate()
+    if entry_count == 0
     {
-        let entry_pos_y = content_offset.y + 1 + offset as u16;
+        app_manager.renderer.draw_at("No edits or deletions yet", &popup.at(2, 1));
+    }
 
-        let row_is_selected = if let CommandState::Modify(_) = &app_manager.state
-        {
-            app_manager.sessions.len() - 1 - app_manager.selected_session_index == offset
-        }
-        else
+    for row_index in 0..visible_entry_count
+    {
+        let entry_index = first_visible_entry + row_index;
+        let row =
+            pad_to_width(&truncate_to_width(&get_audit_entry_label(&app_manager.audit_entries[entry_index]), text_width), text_width);
+        let row_pos = popup.at(2, 1 + row_index as u16);
+
+        draw_selectable(&mut app_manager.renderer, entry_index == app_manager.selected_audit_index, |renderer| {
+            renderer.draw_at(row, &row_pos);
+        });
+    }
+
+    if let Some(entry) = app_manager.audit_entries.get(app_manager.selected_audit_index)
+    {
+        let before = format!("before  {}", get_audit_session_label(&entry.old_session));
+        let after = match &entry.new_session
         {
-            false
+            Some(new_session) => format!("after   {}", get_audit_session_label(new_session)),
+            None => String::from("after   deleted"),
         };
 
-        if row_is_selected
-        {
-            app_manager.renderer.push_color(ColorType::Background, COL_TEXT_DIM);
+        let details_y = popup.y + popup.height - 5;
+        app_manager.renderer.draw_at(truncate_to_width(&before, text_width), &Vector2::new(popup.x + 2, details_y));
+        app_manager.renderer.draw_at(truncate_to_width(&after, text_width), &Vector2::new(popup.x + 2, details_y + 1));
+    }
 
-            let bg = " ".repeat(main_window_size.x as usize - 3);
-            app_manager.renderer.draw_at(bg, &Vector2::new(content_offset.x, entry_pos_y));
-        }
+    app_manager.renderer.draw_at("[UP/DOWN] select  [ESC] close", &popup.at(2, popup.height - 2));
 
-        let field_positions = [
-            Vector2::new(date_column_pos + content_offset.x, entry_pos_y),
-            Vector2::new(description_column_pos + content_offset.x, entry_pos_y),
-            Vector2::new(tag_column_pos + content_offset.x, entry_pos_y),
-            Vector2::new(start_column_pos + content_offset.x, entry_pos_y),
-            Vector2::new(end_column_pos + content_offset.x, entry_pos_y),
-            Vector2::new(duration_column_pos + content_offset.x, entry_pos_y),
-        ];
+    close_popup(&mut app_manager.renderer);
+}
 
-        draw_session_entry(app_manager, &field_positions, session_index, row_is_selected);
+/// When a change was made and which values of the session it changed.
+fn get_audit_entry_label(entry: &AuditEntry) -> String
+{
+    let timestamp = entry.timestamp.format("%d %b %y %H:%M");
+    let old_session = &entry.old_session;
 
-        if row_is_selected
-        {
-            app_manager.renderer.pop_color(ColorType::Background);
-        }
+    let Some(new_session) = &entry.new_session
+    else
+    {
+        return format!("{timestamp}  DELETED  {}", old_session.description);
+    };
+
+    let mut changes = Vec::new();
+
+    if new_session.description != old_session.description
+    {
+        changes.push(format!("description {} → {}", old_session.description, new_session.description));
     }
 
-    match app_manager.state.clone()
+    if new_session.tag != old_session.tag
     {
-        CommandState::Idle =>
-        {}
-        CommandState::New(input_field) =>
-        {
-            let input_field_size = Vector2::new(terminal_size.x - 32, 3);
-            let input_field_pos = Vector2::new((terminal_size.x - input_field_size.x) / 2, (terminal_size.y - input_field_size.y) / 2);
+        changes.push(format!("tag {} → {}", old_session.tag, new_session.tag));
+    }
 
-            app_manager.renderer.push_color(ColorType::Background, COL_BG_POPUP);
-            app_manager.renderer.push_color(ColorType::Foreground, COL_OUTLINE_POPUP);
+    if new_session.start != old_session.start
+    {
+        changes.push(format!("start {} → {}", old_session.start.format("%d %b %H:%M"), new_session.start.format("%d %b %H:%M")));
+    }
 
-            draw_window(&mut app_manager.renderer, &input_field_size, &input_field_pos);
-            draw_window_shadow(&mut app_manager.renderer, &input_field_size, &input_field_pos);
+    if new_session.end != old_session.end
+    {
+        let format_end = |session: &Session| session.end.map(|end| end.format("%d %b %H:%M").to_string()).unwrap_or_default();
+        changes.push(format!("end {} → {}", format_end(old_session), format_end(new_session)));
+    }
 
-            let input_field_half = input_field_pos.x + input_field_size.x / 2;
-            let title = "NEW SESSION";
+    format!("{timestamp}  EDITED   {}: {}", new_session.description, changes.join(", "))
+}
 
-            app_manager.renderer.push_color(ColorType::Background, COL_TEXT_BLACK);
-            app_manager.renderer.push_color(ColorType::Foreground, COL_BG_POPUP);
-            draw_window_title(&mut app_manager.renderer, title, &input_field_pos);
-            app_manager.renderer.pop_color(ColorType::Background);
-            app_manager.renderer.pop_color(ColorType::Foreground);
+fn get_audit_session_label(session: &Session) -> String
+{
+    let end = session.end.map(|end| end.format("%d %b %y %H:%M:%S").to_string()).unwrap_or_default();
 
-            app_manager.renderer.draw_at(INTERSECT_T, &Vector2::new(input_field_half, input_field_pos.y));
-            app_manager.renderer.draw_at(DIVIDER_V, &Vector2::new(input_field_half, input_field_pos.y + 1));
-            app_manager.renderer.draw_at(INTERSECT_B, &Vector2::new(input_field_half, input_field_pos.y + 2));
+    format!("{} → {}  {}  [{}]", session.start.format("%d %b %y %H:%M:%S"), end, session.description, session.tag)
+}
 
-            let text_pos_y = input_field_pos.y + 1;
-            let description_input_pos = Vector2::new(input_field_pos.x + 2, text_pos_y);
-            let tag_input_pos = Vector2::new(input_field_pos.x + input_field_size.x / 2 + 2, text_pos_y);
+fn draw_week_report(app_manager: &mut AppManager)
+{
+    let terminal_size = app_manager.renderer.get_terminal_size();
 
-            let description_input_label = "DESCRIPTION ";
-            let tag_input_label = "TAG ";
-            let no_tags_msg = "- empty -".to_string();
+    let mut comparison = app_manager.get_week_comparison();
+    let current_total: TimeDelta = comparison.iter().map(|(_, current, _)| *current).sum();
+    let previous_total: TimeDelta = comparison.iter().map(|(_, _, previous)| *previous).sum();
 
-            app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
-            app_manager.renderer.draw_at(description_input_label, &description_input_pos);
-            app_manager.renderer.pop_color(ColorType::Foreground);
+    comparison.truncate(terminal_size.y.saturating_sub(12) as usize);
 
-            app_manager.renderer.draw(&app_manager.description_buffer);
+    let flex_time = app_manager.get_week_flex_time();
+    let week_start = app_manager.report_week_start;
+    let title = match app_manager.get_week_number(week_start)
+    {
+        Some(week_number) => format!("WEEK {} OF {} VS PREVIOUS WEEK", week_number, week_start.format("%d %b %Y")),
+        None => format!("WEEK OF {} VS PREVIOUS WEEK", week_start.format("%d %b %Y")),
+    }
+    .to_uppercase();
 
-            app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
-            app_manager.renderer.draw_at(tag_input_label, &tag_input_pos);
-            app_manager.renderer.pop_color(ColorType::Foreground);
+    let flex_row_count = u16::from(flex_time.is_some());
+    let popup_size = Vector2::new(cmp::min(terminal_size.x - 4, 80), cmp::max(comparison.len() as u16, 1) + 6 + flex_row_count);
+    let popup = get_centered_area(&popup_size, &terminal_size);
 
-            let selected_tag = app_manager.tags.get(app_manager.get_selected_tag_index()).unwrap_or(&no_tags_msg);
+    open_popup(&mut app_manager.renderer, &popup, &title);
 
-            app_manager.renderer.draw(selected_tag);
+    let label_width = popup.width as usize - 48;
+    let row_format = |tag: &str, current: &str, previous: &str, delta: &str, percentage: &str| {
+        let tag = truncate_to_width(tag, label_width);
+        format!("{} {:>10} {:>10} {:>12} {:>8}", pad_to_width(&tag, label_width), current, previous, delta, percentage)
+    };
 
-            match input_field
-            {
-                SessionInputState::Description(confirm_end_previous) => match confirm_end_previous
-                {
-                    ConfirmOpen::Yes =>
-                    {
-                        draw_yes_no_popup(app_manager, "END RUNNING SESSION?");
-                    }
-                    ConfirmOpen::No =>
-                    {
-                        let cursor_pos_x =
-                            description_input_pos.x + (description_input_label.len() + app_manager.description_buffer.len()) as u16;
+    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+    app_manager
+        .renderer
+        .draw_at(row_format("TAG", "THIS WEEK", "LAST WEEK", "DELTA", "CHANGE"), &popup.at(2, 1));
+    app_manager.renderer.pop_color(ColorType::Foreground);
 
-                        app_manager.renderer.draw_at(CURSOR, &Vector2::new(cursor_pos_x, text_pos_y));
-                    }
-                },
-                SessionInputState::Tag(edit_state) =>
-                {
-                    let dropdown_title = "TAG";
-                    let tag_dropdown_pos = &tag_input_pos;
-                    let tag_dropdown_text_pos = Vector2::new(tag_dropdown_pos.x + 2, tag_dropdown_pos.y + 1);
+    if comparison.is_empty()
+    {
+        app_manager.renderer.draw_at("No sessions in either week", &popup.at(2, 2));
+    }
 
-                    if let Some(longest_tag_str) = app_manager.tags.iter().map(String::len).max()
-                    {
-                        let longest_tag_str = cmp::max(longest_tag_str, dropdown_title.len() + 2) as u16;
-                        let tag_dropdown_size = Vector2::new(longest_tag_str + 8, app_manager.tags.len() as u16 + 2);
-
-                        draw_window(&mut app_manager.renderer, &tag_dropdown_size, tag_dropdown_pos);
-                        draw_window_shadow(&mut app_manager.renderer, &tag_dropdown_size, tag_dropdown_pos);
-
-                        app_manager.renderer.push_color(ColorType::Background, COL_TEXT_BLACK);
-                        app_manager.renderer.push_color(ColorType::Foreground, COL_BG_POPUP);
-                        draw_window_title(&mut app_manager.renderer, dropdown_title, tag_dropdown_pos);
-                        app_manager.renderer.pop_color(ColorType::Background);
-                        app_manager.renderer.pop_color(ColorType::Foreground);
-
-                        for (index, tag) in app_manager.tags.iter().enumerate()
-                        {
-                            let selected_row = index == app_manager.temp_tag_index;
-
-                            let arrow = if selected_row
-                            {
-                                ARROW
-                            }
-                            else
-                            {
-                                ' '
-                            };
-
-                            if selected_row
-                            {
-                                app_manager.renderer.push_color(ColorType::Background, COL_TEXT_BLACK);
-                                app_manager.renderer.push_color(ColorType::Foreground, COL_BG_POPUP);
-                            }
-
-                            let right_pad = longest_tag_str as usize + 1;
-                            app_manager.renderer.draw_at(
-                                format!(" {} {:<pad$}", arrow, tag, pad = right_pad),
-                                &Vector2::new(tag_dropdown_text_pos.x, tag_dropdown_text_pos.y + index as u16),
-                            );
-
-                            if selected_row
-                            {
-                                app_manager.renderer.pop_color(ColorType::Background);
-                                app_manager.renderer.pop_color(ColorType::Foreground);
-                            }
-                        }
-                    }
-                    else
-                    {
-                        let tag_dropdown_size = Vector2::new(no_tags_msg.len() as u16 + 4, 3);
-                        draw_window(&mut app_manager.renderer, &tag_dropdown_size, tag_dropdown_pos);
-                        draw_window_shadow(&mut app_manager.renderer, &tag_dropdown_size, tag_dropdown_pos);
+    let total_row_y = popup.y + popup.height - 3 - flex_row_count;
+    let rows = comparison
+        .into_iter()
+        .enumerate()
+        .map(|(row_index, (tag, current, previous))| (popup.y + 2 + row_index as u16, tag, current, previous))
+        .chain([(total_row_y, String::from("TOTAL"), current_total, previous_total)]);
 
-                        app_manager.renderer.draw_at(&no_tags_msg, &tag_dropdown_text_pos);
-                    };
+    for (row_y, tag, current, previous) in rows
+    {
+        let row = row_format(
+            &tag,
+            &format_duration_short(current),
+            &format_duration_short(previous),
+            &format_duration_delta(current - previous),
+            &format_percentage_change(current, previous),
+        );
+
+        let color = if current >= previous
+        {
+            COL_BG_MAIN
+        }
+        else
+        {
+            COL_TEXT_RED_DARK
+        };
 
-                    match edit_state
-                    {
-                        TagInputState::Select =>
-                        {}
-                        TagInputState::New =>
-                        {
-                            let new_tag_title = "NEW TAG";
-                            let new_tag_window_pos = &tag_dropdown_text_pos;
-                            let new_tag_window_size = Vector2::new(32, 3);
-
-                            draw_window(&mut app_manager.renderer, &new_tag_window_size, new_tag_window_pos);
-                            draw_window_shadow(&mut app_manager.renderer, &new_tag_window_size, new_tag_window_pos);
-
-                            app_manager.renderer.push_color(ColorType::Background, COL_TEXT_BLACK);
-                            app_manager.renderer.push_color(ColorType::Foreground, COL_BG_POPUP);
-                            draw_window_title(&mut app_manager.renderer, new_tag_title, new_tag_window_pos);
-                            app_manager.renderer.pop_color(ColorType::Background);
-                            app_manager.renderer.pop_color(ColorType::Foreground);
-
-                            let new_tag_text_pos = Vector2::new(new_tag_window_pos.x + 2, new_tag_window_pos.y + 1);
-                            app_manager.renderer.draw_at(format!("{}{}", &app_manager.tag_buffer, CURSOR), &new_tag_text_pos);
-                        }
-                        TagInputState::Delete(_) =>
-                        {}
-                    }
-                }
-            }
+        app_manager.renderer.push_color(ColorType::Foreground, color);
+        app_manager.renderer.draw_at(row, &Vector2::new(popup.x + 2, row_y));
+        app_manager.renderer.pop_color(ColorType::Foreground);
+    }
 
-            app_manager.renderer.pop_color(ColorType::Background);
-            app_manager.renderer.pop_color(ColorType::Foreground);
+    if let Some((week_flex_time, balance)) = flex_time
+    {
+        let color = if balance >= TimeDelta::zero()
+        {
+            COL_BG_MAIN
         }
-        CommandState::Modify(session_edit_state) => match session_edit_state
+        else
         {
-            SessionModifyState::Edit(edit_state) =>
-            {
-                draw_session_selection_line(app_manager, &content_offset, "EDT");
+            COL_TEXT_RED_DARK
+        };
 
-                match edit_state
-                {
-                    SessionEditState::Browse =>
-                    {}
-                    SessionEditState::EditFields(field_state) => match field_state
-                    {
-                        SessionFieldEditState::Browse =>
-                        {}
-                        SessionFieldEditState::Editing =>
-                        {}
-                    },
-                    SessionEditState::Confirm =>
-                    {
-                        draw_yes_no_popup(app_manager, "ACCEPT CHANGES?");
-                    }
-                }
-            }
-            SessionModifyState::Continue(confirm_open) =>
-            {
-                draw_session_selection_line(app_manager, &content_offset, "CPY");
+        app_manager.renderer.push_color(ColorType::Foreground, color);
+        app_manager.renderer.draw_at(get_flex_time_text(week_flex_time, balance), &popup.at(2, popup.height - 3));
+        app_manager.renderer.pop_color(ColorType::Foreground);
+    }
 
-                match confirm_open
-                {
-                    ConfirmOpen::Yes =>
-                    {
-                        let message = if app_manager.is_last_session_still_running()
-                        {
-                            "END RUNNING SESSION?"
-                        }
-                        else
-                        {
-                            "COPY AND START SESSION?"
-                        };
-
-                        draw_yes_no_popup(app_manager, message);
-                    }
-                    ConfirmOpen::No =>
-                    {}
-                }
-            }
-            SessionModifyState::Delete(confirm_open) =>
-            {
-                draw_session_selection_line(app_manager, &content_offset, "DEL");
+    app_manager.renderer.draw_at(
+        "[LEFT/RIGHT] previous/next week  [y] copy  [ESC] close",
+        &popup.at(2, popup.height - 2),
+    );
 
-                match confirm_open
-                {
-                    ConfirmOpen::Yes =>
-                    {
-                        draw_yes_no_popup(app_manager, "CONFIRM DELETE");
-                    }
-                    ConfirmOpen::No =>
-                    {}
-                }
-            }
-        },
-        CommandState::End =>
-        {
-            draw_yes_no_popup(app_manager, "END SESSION?");
-        }
-        CommandState::Quitting =>
+    close_popup(&mut app_manager.renderer);
+}
+
+/// The running session alone on the screen, its elapsed time drawn in big digits. A session with a timer counts down
+/// instead, and up again in red once the time is up. Without a running session it shows today's total.
+fn draw_focus(app_manager: &mut AppManager)
+{
+    let terminal_size = app_manager.renderer.get_terminal_size();
+
+    let (description, subtitle, elapsed) = match app_manager.sessions.last().filter(|session| session.is_running())
+    {
+        Some(session) =>
         {
-            draw_yes_no_popup(app_manager, "REALLY QUIT?");
+            (session.description.clone(), session.tag.clone(), app_manager.get_running_session_elapsed().unwrap_or(TimeDelta::zero()))
         }
+        None => (String::from("No session running"), String::from("today"), app_manager.get_today_total()),
+    };
+
+    let time_left = app_manager.get_running_session_time_left();
+    let time_is_up = time_left.is_some_and(|time_left| time_left <= TimeDelta::zero());
+    let (subtitle, elapsed) = match time_left
+    {
+        Some(time_left) if time_is_up => (format!("{subtitle} | TIME'S UP"), -time_left),
+        Some(time_left) => (subtitle, time_left),
+        None => (subtitle, elapsed),
+    };
+    let clock_color = if time_is_up
+    {
+        COL_TEXT_RED
+    }
+    else
+    {
+        COL_TEXT_HIGHLIGHT
+    };
+
+    let elapsed_seconds = elapsed.num_seconds().max(0);
+    let clock_text = format!("{:02}:{:02}:{:02}", elapsed_seconds / 3600, elapsed_seconds / 60 % 60, elapsed_seconds % 60);
+    let clock_rows = (0..BIG_COLON.len())
+        .map(|row| {
+            clock_text
+                .chars()
+                .map(|character| match character.to_digit(10)
+                {
+                    Some(digit) => BIG_DIGITS[digit as usize][row],
+                    None => BIG_COLON[row],
+                })
+                .collect::<Vec<&str>>()
+                .join(" ")
+        })
+        .collect::<Vec<String>>();
+
+    let center_x = |text: &str| terminal_size.x.saturating_sub(get_display_width(text) as u16) / 2;
+    let clock_pos_y = terminal_size.y.saturating_sub(clock_rows.len() as u16) / 2;
+    let description = truncate_to_width(&description, terminal_size.x.saturating_sub(4) as usize);
+
+    app_manager.renderer.push_color(ColorType::Background, COL_BG_MAIN);
+    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_WHITE);
+
+    for row in 0..terminal_size.y
+    {
+        app_manager.renderer.draw_at(" ".repeat(terminal_size.x as usize), &Vector2::new(0, row));
     }
 
-    let version = format!("Version {}", &app_manager.version);
-    debug_draw(app_manager, &version);
+    app_manager.renderer.draw_at(&description, &Vector2::new(center_x(&description), clock_pos_y.saturating_sub(3)));
 
+    app_manager.renderer.push_color(ColorType::Foreground, clock_color);
+    for (row_index, clock_row) in clock_rows.iter().enumerate()
+    {
+        app_manager.renderer.draw_at(clock_row, &Vector2::new(center_x(clock_row), clock_pos_y + row_index as u16));
+    }
     app_manager.renderer.pop_color(ColorType::Foreground);
-    app_manager.renderer.pop_color(ColorType::Background);
 
-    draw_control_panel(app_manager);
+    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_DIM);
+    app_manager.renderer.draw_at(&subtitle, &Vector2::new(center_x(&subtitle), clock_pos_y + clock_rows.len() as u16 + 2));
+    app_manager.renderer.pop_color(ColorType::Foreground);
 
-    app_manager.renderer.render();
+    app_manager.renderer.pop_color(ColorType::Foreground);
+    app_manager.renderer.pop_color(ColorType::Background);
 }
 
-#[allow(clippy::too_many_lines)]
-fn update(app_manager: &mut AppManager)
+fn draw_templates(app_manager: &mut AppManager)
 {
-    if let Some(key) = get_user_key()
-    {
-        match app_manager.state.clone()
-        {
-            CommandState::Idle => match key
-            {
-                KEY_NEW =>
-                {
-                    app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
-                }
-                KEY_EDIT =>
-                {
-                    app_manager.selected_session_index = app_manager.sessions.len() - 1;
-                    app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Browse));
-                }
-                KEY_COPY =>
-                {
-                    app_manager.selected_session_index = app_manager.sessions.len() - 1;
-                    app_manager.state = CommandState::Modify(SessionModifyState::Continue(ConfirmOpen::No));
-                }
-                KEY_DELETE =>
-                {
-                    app_manager.selected_session_index = app_manager.sessions.len() - 1;
-                    app_manager.state = CommandState::Modify(SessionModifyState::Delete(ConfirmOpen::No));
-                }
-                KEY_END =>
-                {
-                    if app_manager.is_last_session_still_running()
-                    {
-                        app_manager.state = CommandState::End;
-                    }
-                }
-                KEY_QUIT =>
-                {
-                    app_manager.state = CommandState::Quitting;
-                }
-                _ =>
-                {}
-            },
-            CommandState::New(input_field) => match input_field
-            {
-                SessionInputState::Description(confirm_end_previous) => match confirm_end_previous
-                {
-                    ConfirmOpen::Yes =>
-                    {
-                        if key == KEY_YES
-                        {
-                            app_manager.end_running_session();
-                            app_manager.try_start_new_session();
-                            app_manager.state = CommandState::Idle;
-                        }
-                        else if key == KEY_NO || key == KEY_ESCAPE
-                        {
-                            app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
-                        }
-                    }
-                    ConfirmOpen::No => match key
-                    {
-                        KEY_ESCAPE =>
-                        {
-                            app_manager.state = CommandState::Idle;
-                        }
-                        KEY_BACKSPACE =>
-                        {
-                            app_manager.description_buffer.pop();
-                        }
-                        KEY_ENTER =>
-                        {
-                            if app_manager.is_last_session_still_running()
-                            {
-                                app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::Yes));
-                            }
-                            else
-                            {
-                                app_manager.try_start_new_session();
-                                app_manager.state = CommandState::Idle;
-                            }
-                        }
-                        KEY_TAB =>
-                        {
-                            app_manager.temp_tag_index = app_manager.get_selected_tag_index();
-                            app_manager.state = CommandState::New(SessionInputState::Tag(TagInputState::Select));
-                        }
-                        KeyCode::Char(character) =>
-                        {
-                            app_manager.description_buffer.push(character);
-                        }
-                        _ =>
-                        {}
-                    },
-                },
-                SessionInputState::Tag(edit_state) => match edit_state
-                {
-                    TagInputState::Select => match key
-                    {
-                        KEY_NEW =>
-                        {
-                            app_manager.state = CommandState::New(SessionInputState::Tag(TagInputState::New));
-                        }
-                        KEY_ESCAPE =>
-                        {
-                            app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
-                        }
-                        KEY_UP =>
-                        {
-                            if app_manager.temp_tag_index > 0
-                            {
-                                app_manager.temp_tag_index -= 1;
-                            }
-                        }
-                        KEY_DOWN =>
-                        {
-                            if app_manager.temp_tag_index + 1 < app_manager.tags.len()
-                            {
-                                app_manager.temp_tag_index += 1;
-                            }
-                        }
-                        KEY_ENTER =>
-                        {
-                            app_manager.set_selected_tag_index(app_manager.temp_tag_index);
-                            app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
-                        }
-                        _ =>
-                        {}
-                    },
-                    TagInputState::New => match key
-                    {
-                        KEY_ESCAPE =>
-                        {
-                            app_manager.state = CommandState::New(SessionInputState::Tag(TagInputState::Select));
-                        }
-                        KEY_BACKSPACE =>
-                        {
-                            app_manager.tag_buffer.pop();
-                        }
-                        KEY_ENTER =>
-                        {
-                            app_manager.try_store_tag();
-                            app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
-                        }
-                        KeyCode::Char(character) =>
-                        {
-                            app_manager.tag_buffer.push(character);
-                        }
-                        _ =>
-                        {}
-                    },
-                    TagInputState::Delete(_) =>
-                    {}
-                },
-            },
-            CommandState::Modify(session_modify_state) => match session_modify_state
-            {
-                SessionModifyState::Edit(edit_state) => match edit_state
-                {
-                    SessionEditState::Browse => match key
-                    {
-                        KEY_ESCAPE =>
-                        {
-                            app_manager.state = CommandState::Idle;
-                        }
-                        KEY_UP =>
-                        {
-                            if app_manager.selected_session_index + 1 < app_manager.sessions.len()
-                            {
-                                app_manager.selected_session_index += 1;
-                            }
-                        }
-                        KEY_DOWN =>
-                        {
-                            if app_manager.selected_session_index > 0
-                            {
-                                app_manager.selected_session_index -= 1;
-                            }
-                        }
-                        KEY_ENTER =>
-                        {
-                            app_manager.copy_selected_session_to_buffer();
-                            app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(
-                                SessionFieldEditState::Browse,
-                            )));
-                        }
-                        _ =>
-                        {}
-                    },
-                    SessionEditState::EditFields(state) => match state
-                    {
-                        SessionFieldEditState::Browse => match key
-                        {
-                            KEY_ESCAPE =>
-                            {
-                                if app_manager.session_buffer_has_pending_changes()
-                                {
-                                    app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Confirm));
-                                }
-                                else
-                                {
-                                    app_manager.clear_session_edit_buffer();
-                                    app_manager.selected_session_field = SessionField::None;
-                                    app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Browse));
-                                }
-                            }
-                            KEY_LEFT =>
-                            {
-                                app_manager.decrement_selected_session_field();
-                            }
-                            KEY_RIGHT =>
-                            {
-                                app_manager.increment_selected_session_field();
-                            }
-                            KEY_ENTER =>
-                            {
-                                app_manager.selected_datetime_segment = 0;
-
-                                let can_edit = if let SessionField::End(_) = app_manager.selected_session_field
-                                    && app_manager.is_last_session_still_running()
-                                {
-                                    false
-                                }
-                                else
-                                {
-                                    true
-                                };
-
-                                if can_edit
-                                {
-                                    app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(
-                                        SessionFieldEditState::Editing,
-                                    )));
-                                }
-                            }
-                            _ =>
-                            {}
-                        },
-                        SessionFieldEditState::Editing =>
-                        {
-                            match key
-                            {
-                                KEY_ESCAPE =>
-                                {
-                                    let session_edit_buffer = &app_manager.session_edit_buffer.as_ref().unwrap();
-                                    app_manager.temp_tag_index = app_manager.get_index_of_tag(&session_edit_buffer.tag);
-
-                                    match &mut app_manager.selected_session_field
-                                    {
-                                        SessionField::Date(date_buffer) =>
-                                        {
-                                            *date_buffer = session_edit_buffer.start;
-                                        }
-                                        SessionField::Description(description_buffer) =>
-                                        {
-                                            description_buffer.clone_from(&session_edit_buffer.description);
-                                        }
-                                        SessionField::Tag(tag_buffer) =>
-                                        {
-                                            tag_buffer.clone_from(&session_edit_buffer.tag);
-                                        }
-                                        SessionField::Start(start_time_buffer) =>
-                                        {
-                                            *start_time_buffer = session_edit_buffer.start;
-                                        }
-                                        SessionField::End(end_time_buffer) =>
-                                        {
-                                            *end_time_buffer = session_edit_buffer.end;
-                                        }
-                                        SessionField::None =>
-                                        {}
-                                    }
-
-                                    app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(
-                                        SessionFieldEditState::Browse,
-                                    )));
-                                }
-                                KEY_ENTER =>
-                                {
-                                    app_manager.store_modified_field_to_session_buffer();
-
-                                    app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(
-                                        SessionFieldEditState::Browse,
-                                    )));
-                                }
-                                _ =>
-                                {}
-                            }
-
-                            match &mut app_manager.selected_session_field
-                            {
-                                SessionField::Date(date_buffer) =>
-                                {
-                                    if let Some(new_date) = edit_date(key, app_manager.selected_datetime_segment, *date_buffer)
-                                    {
-                                        *date_buffer = new_date;
-                                    }
-
-                                    match key
-                                    {
-                                        KEY_LEFT =>
-                                        {
-                                            if app_manager.selected_datetime_segment > 0
-                                            {
-                                                app_manager.selected_datetime_segment -= 1;
-                                            }
-                                        }
-                                        KEY_RIGHT =>
-                                        {
-                                            if app_manager.selected_datetime_segment < 2
-                                            {
-                                                app_manager.selected_datetime_segment += 1;
-                                            }
-                                        }
-                                        _ =>
-                                        {}
-                                    }
-                                }
-                                SessionField::Description(description_buffer) => match key
-                                {
-                                    KEY_BACKSPACE =>
-                                    {
-                                        description_buffer.pop();
-                                    }
-                                    KeyCode::Char(character) =>
-                                    {
-                                        description_buffer.push(character);
-                                    }
-                                    _ =>
-                                    {}
-                                },
-
-                                SessionField::Tag(tag_buffer) => match key
-                                {
-                                    KEY_UP =>
-                                    {
-                                        if app_manager.temp_tag_index > 0
-                                        {
-                                            app_manager.temp_tag_index -= 1;
-                                        }
-
-                                        tag_buffer.clone_from(&app_manager.tags[app_manager.temp_tag_index]);
-                                    }
-                                    KEY_DOWN =>
-                                    {
-                                        if app_manager.temp_tag_index + 1 < app_manager.tags.len()
-                                        {
-                                            app_manager.temp_tag_index += 1;
-                                        }
-
-                                        tag_buffer.clone_from(&app_manager.tags[app_manager.temp_tag_index]);
-                                    }
-                                    _ =>
-                                    {}
-                                },
-                                SessionField::Start(start_buffer) =>
-                                {
-                                    if let Some(new_date) = edit_time(key, app_manager.selected_datetime_segment, *start_buffer)
-                                    {
-                                        *start_buffer = new_date;
-                                    }
-
-                                    match key
-                                    {
-                                        KEY_LEFT =>
-                                        {
-                                            if app_manager.selected_datetime_segment > 0
-                                            {
-                                                app_manager.selected_datetime_segment -= 1;
-                                            }
-                                        }
-                                        KEY_RIGHT =>
-                                        {
-                                            if app_manager.selected_datetime_segment < 2
-                                            {
-                                                app_manager.selected_datetime_segment += 1;
-                                            }
-                                        }
-                                        _ =>
-                                        {}
-                                    }
-                                }
-                                SessionField::End(end_buffer) =>
-                                {
-                                    if let Some(end_buffer) = end_buffer
-                                        && let Some(new_date) = edit_time(key, app_manager.selected_datetime_segment, *end_buffer)
-                                    {
-                                        *end_buffer = new_date;
-                                    }
-
-                                    match key
-                                    {
-                                        KEY_LEFT =>
-                                        {
-                                            if app_manager.selected_datetime_segment > 0
-                                            {
-                                                app_manager.selected_datetime_segment -= 1;
-                                            }
-                                        }
-                                        KEY_RIGHT =>
-                                        {
-                                            if app_manager.selected_datetime_segment < 2
-                                            {
-                                                app_manager.selected_datetime_segment += 1;
-                                            }
-                                        }
-                                        _ =>
-                                        {}
-                                    }
-                                }
-                                SessionField::None =>
-                                {}
-                            }
-                        }
-                    },
-                    SessionEditState::Confirm => match key
-                    {
-                        KEY_YES =>
-                        {
-                            app_manager.apply_changes_to_session();
-                            app_manager.clear_session_edit_buffer();
-                            app_manager.selected_session_field = SessionField::None;
-                            app_manager.state = CommandState::Idle;
-                        }
-                        KEY_NO =>
-                        {
-                            app_manager.clear_session_edit_buffer();
-                            app_manager.selected_session_field = SessionField::None;
-                            app_manager.state = CommandState::Idle;
-                        }
-                        KEY_ESCAPE =>
-                        {
-                            app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(
-                                SessionFieldEditState::Browse,
-                            )));
-                        }
-                        _ =>
-                        {}
-                    },
-                },
-                SessionModifyState::Continue(confirm_open) => match confirm_open
-                {
-                    ConfirmOpen::Yes =>
-                    {
-                        if key == KEY_YES
-                        {
-                            app_manager.start_new_session_based_on_selected();
-                            app_manager.state = CommandState::Idle;
-                        }
-                        else if key == KEY_NO || key == KEY_ESCAPE
-                        {
-                            app_manager.state = CommandState::Idle;
-                        }
-                    }
-                    ConfirmOpen::No => match key
-                    {
-                        KEY_ESCAPE =>
-                        {
-                            app_manager.state = CommandState::Idle;
-                        }
-                        KEY_UP =>
-                        {
-                            if app_manager.selected_session_index + 1 < app_manager.sessions.len()
-                            {
-                                app_manager.selected_session_index += 1;
-                            }
-                        }
-                        KEY_DOWN =>
-                        {
-                            if app_manager.selected_session_index > 0
-                            {
-                                app_manager.selected_session_index -= 1;
-                            }
-                        }
-                        KEY_ENTER =>
-                        {
-                            app_manager.state = CommandState::Modify(SessionModifyState::Continue(ConfirmOpen::Yes));
-                        }
-                        _ =>
-                        {}
-                    },
-                },
-                SessionModifyState::Delete(confirm_open) => match confirm_open
-                {
-                    ConfirmOpen::Yes =>
-                    {
-                        if key == KEY_YES
-                        {
-                            app_manager.delete_selected_session();
-                            app_manager.state = CommandState::Idle;
-                        }
-                        else if key == KEY_NO || key == KEY_ESCAPE
-                        {
-                            app_manager.state = CommandState::Idle;
-                        }
-                    }
-                    ConfirmOpen::No => match key
-                    {
-                        KEY_ESCAPE =>
-                        {
-                            app_manager.state = CommandState::Idle;
-                        }
-                        KEY_UP =>
-                        {
-                            if app_manager.selected_session_index + 1 < app_manager.sessions.len()
-                            {
-                                app_manager.selected_session_index += 1;
-                            }
-                        }
-                        KEY_DOWN =>
-                        {
-                            if app_manager.selected_session_index > 0
-                            {
-                                app_manager.selected_session_index -= 1;
-                            }
-                        }
-                        KEY_ENTER =>
-                        {
-                            app_manager.state = CommandState::Modify(SessionModifyState::Delete(ConfirmOpen::Yes));
-                        }
-                        _ =>
-                        {}
-                    },
-                },
-            },
-            CommandState::End =>
+    let templates = &app_manager.config.templates;
+    let description_width = templates.iter().map(|template| get_display_width(&template.description)).max().unwrap_or(0).min(40);
+    let tag_width = templates.iter().map(|template| get_display_width(&template.tag)).max().unwrap_or(0).min(20);
+    let rows = templates
+        .iter()
+        .enumerate()
+        .map(|(index, template)| {
+            let number = if index < 9
             {
-                if key == KEY_YES
-                {
-                    app_manager.end_running_session();
-                    app_manager.state = CommandState::Idle;
-                }
-                else if key == KEY_NO || key == KEY_ESCAPE
-                {
-                    app_manager.state = CommandState::Idle;
-                }
+                (index + 1).to_string()
             }
-            CommandState::Quitting =>
+            else
             {
-                if key == KEY_YES
-                {
-                    if app_manager.is_last_session_still_running()
-                    {
-                        app_manager.end_running_session();
-                    }
+                String::from(" ")
+            };
+            let target = template.target.map(format_duration_short).unwrap_or_default();
+
+            format!(
+                "{} {}  {}  {}",
+                number,
+                pad_to_width(&truncate_to_width(&template.description, description_width), description_width),
+                pad_to_width(&truncate_to_width(&template.tag, tag_width), tag_width),
+                target
+            )
+        })
+        .collect::<Vec<String>>();
 
-                    app_manager.running = false;
-                }
-                else if key == KEY_NO || key == KEY_ESCAPE
-                {
-                    app_manager.state = CommandState::Idle;
-                }
-            }
-        }
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup = get_centered_area(&Vector2::new(terminal_size.x - 16, cmp::max(rows.len() as u16, 2) + 4), &terminal_size);
+
+    open_popup(&mut app_manager.renderer, &popup, "TEMPLATES");
+
+    if rows.is_empty()
+    {
+        let config_path = app_manager.get_config_file_path();
+        app_manager
+            .renderer
+            .draw_at("No templates configured. Add them under [templates] in", &popup.at(2, 1));
+        app_manager.renderer.draw_at(config_path.display().to_string(), &popup.at(2, 2));
     }
+    else
+    {
+        let list_area = Rect::new(popup.x + 1, popup.y + 1, popup.width - 2, popup.height - 4);
+        draw_list(&mut app_manager.renderer, &rows, Some(app_manager.selected_template_index), list_area);
+    }
+
+    app_manager.renderer.draw_at("[ENTER] start  [1-9] start  [ESC] close", &popup.at(2, popup.height - 2));
+    close_popup(&mut app_manager.renderer);
 }
 
-fn edit_date(key: KeyCode, date_segment: usize, date: NaiveDateTime) -> Option<NaiveDateTime>
+fn draw_goals(app_manager: &mut AppManager)
 {
-    match key
-    {
-        KEY_UP => match date_segment
-        {
-            0 => date.checked_add_days(chrono::Days::new(1)),
-            1 => date.checked_add_months(chrono::Months::new(1)),
-            2 => date.checked_add_months(chrono::Months::new(12)),
-            _ => None,
-        },
-        KEY_DOWN => match date_segment
-        {
-            0 => date.checked_sub_days(chrono::Days::new(1)),
-            1 => date.checked_sub_months(chrono::Months::new(1)),
-            2 => date.checked_sub_months(chrono::Months::new(12)),
-            _ => None,
-        },
+    let rows = app_manager
+        .get_goal_progress()
+        .into_iter()
+        .map(|(goal, tracked, target)| {
+            let period = match goal.period
+            {
+                GoalPeriod::Day => "today",
+                GoalPeriod::Week => "this week",
+            };
+
+            (goal.tag.clone(), period, tracked, target)
+        })
+        .collect::<Vec<(String, &str, TimeDelta, TimeDelta)>>();
+
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup = get_centered_area(&Vector2::new(terminal_size.x - 16, cmp::max(rows.len() as u16, 2) + 4), &terminal_size);
 
-        _ => None,
+    open_popup(&mut app_manager.renderer, &popup, "GOALS");
+
+    if rows.is_empty()
+    {
+        let config_path = app_manager.get_config_file_path();
+        app_manager
+            .renderer
+            .draw_at("No goals configured. Add them under [goals] in", &popup.at(2, 1));
+        app_manager.renderer.draw_at(config_path.display().to_string(), &popup.at(2, 2));
     }
-}
 
-fn edit_time(key: KeyCode, date_segment: usize, time: NaiveDateTime) -> Option<NaiveDateTime>
-{
-    match key
+    let label_width = rows.iter().map(|(tag, ..)| get_display_width(tag)).max().unwrap_or(0).min(20);
+    let numbers_width = 31;
+    let status_width = 16;
+    let bar_width = (popup.width as usize).saturating_sub(label_width + numbers_width + status_width + 6);
+
+    for (row_index, (tag, period, tracked, target)) in rows.iter().enumerate()
     {
-        KEY_UP => match date_segment
+        let row_pos = popup.at(2, 1 + row_index as u16);
+        let tag = truncate_to_width(tag, label_width);
+
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+        app_manager.renderer.draw_at(pad_to_width(&tag, label_width), &row_pos);
+        app_manager.renderer.pop_color(ColorType::Foreground);
+
+        let numbers = format!("{} / {} {}", format_duration_short(*tracked), format_duration_short(*target), period);
+        app_manager.renderer.draw(format!(" {:<pad$} ", numbers, pad = numbers_width));
+
+        let filled = get_bar(cmp::min(tracked.num_seconds(), target.num_seconds()) as u64, target.num_seconds() as u64, bar_width);
+        let empty = BAR_EMPTY.to_string().repeat(bar_width.saturating_sub(filled.chars().count()));
+
+        app_manager.renderer.push_color(ColorType::Foreground, COL_BG_MAIN);
+        app_manager.renderer.draw(filled);
+        app_manager.renderer.pop_color(ColorType::Foreground);
+        app_manager.renderer.draw(empty);
+
+        let (status, status_color) = if target.is_zero()
         {
-            0 => time.checked_add_signed(TimeDelta::hours(1)),
-            1 => time.checked_add_signed(TimeDelta::minutes(1)),
-            2 => time.checked_add_signed(TimeDelta::seconds(1)),
-            _ => None,
-        },
-        KEY_DOWN => match date_segment
+            (String::from(" day off"), COL_BG_MAIN)
+        }
+        else if tracked >= target
+        {
+            (format!(" {} over", format_duration_short(*tracked - *target)), COL_BG_MAIN)
+        }
+        else
         {
-            0 => time.checked_sub_signed(TimeDelta::hours(1)),
-            1 => time.checked_sub_signed(TimeDelta::minutes(1)),
-            2 => time.checked_sub_signed(TimeDelta::seconds(1)),
-            _ => None,
-        },
+            (format!(" {} left", format_duration_short(*target - *tracked)), COL_TEXT_RED_DARK)
+        };
 
-        _ => None,
+        app_manager.renderer.push_color(ColorType::Foreground, status_color);
+        app_manager.renderer.draw(status);
+        app_manager.renderer.pop_color(ColorType::Foreground);
     }
+
+    app_manager.renderer.draw_at("[ESC] close", &popup.at(2, popup.height - 2));
+
+    close_popup(&mut app_manager.renderer);
 }
 
 #[allow(clippy::too_many_lines)]
-fn draw_session_entry(app_manager: &mut AppManager, field_positions: &[Vector2], session_index: usize, session_is_selected: bool)
+fn draw_stats(app_manager: &mut AppManager)
 {
-    let session = if let CommandState::Modify(SessionModifyState::Edit(_)) = &app_manager.state
-        && let Some(session_buffer) = &app_manager.session_edit_buffer
-        && session_is_selected
+    let terminal_size = app_manager.renderer.get_terminal_size();
+
+    let mut tag_totals = app_manager.get_tag_totals();
+    let total: TimeDelta = tag_totals.iter().map(|(_, duration)| *duration).sum();
+
+    let mut issue_totals = app_manager.get_issue_totals();
+    issue_totals.truncate(MAX_STATS_ISSUES);
+
+    // The issues get a blank row above them.
+    let issue_rows = if issue_totals.is_empty()
     {
-        session_buffer
+        0
     }
     else
     {
-        &app_manager.sessions[session_index]
+        issue_totals.len() + 1
     };
+    tag_totals.truncate((terminal_size.y as usize).saturating_sub(10 + issue_rows));
 
-    let start_date = session.get_date_string();
-    let description = &session.description;
-    let tag = &session.tag;
-    let start_time = session.get_start_time_string();
-    let end_time = session.get_end_time_string().unwrap_or(String::from("-"));
-    let duration = session.get_duration_string().unwrap_or(String::from("Running"));
-
-    let session_fields = [&start_date, description, tag, &start_time, &end_time, &duration];
-
-    for session_field_index in 0..session_fields.len()
+    let period_label = match app_manager.get_stats_range()
     {
-        let field = session_fields[session_field_index];
-        let position = &field_positions[session_field_index];
+        Some((first, last)) if first == last => format!("{}", first.format("%a %d %b %Y")),
+        Some((first, last)) => format!("{} - {}", first.format("%d %b %Y"), last.format("%d %b %Y")),
+        None => String::from("All time"),
+    };
 
-        let session_field_is_selected = session_is_selected && session_field_index == app_manager.get_selected_session_field_index();
+    let popup_size = Vector2::new(terminal_size.x - 16, cmp::max(tag_totals.len() as u16, 1) + issue_rows as u16 + 6);
+    let popup = get_centered_area(&popup_size, &terminal_size);
 
-        if session_field_is_selected
-            && let CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(edit_field_state))) = &app_manager.state
-        {
-            let (bg_color, fg_color) = match edit_field_state
-            {
-                SessionFieldEditState::Browse => (COL_TEXT_HIGHLIGHT, COL_TEXT_BLACK),
-                SessionFieldEditState::Editing => (COL_TEXT_RED, COL_TEXT_WHITE),
-            };
+    open_popup(&mut app_manager.renderer, &popup, "TIME PER TAG");
 
-            app_manager.renderer.push_color(ColorType::Background, bg_color);
-            app_manager.renderer.push_color(ColorType::Foreground, fg_color);
+    let periods = [
+        (StatsPeriod::Day, "DAY"),
+        (StatsPeriod::Week, "WEEK"),
+        (StatsPeriod::Month, "MONTH"),
+        (StatsPeriod::Year, "YEAR"),
+        (StatsPeriod::All, "ALL"),
+    ];
 
-            match &app_manager.selected_session_field
-            {
-                SessionField::Date(date_buffer) =>
-                {
-                    app_manager.renderer.push_color(ColorType::Background, COL_TEXT_HIGHLIGHT);
-                    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_BLACK);
+    app_manager.renderer.draw_at("", &popup.at(2, 1));
 
-                    let date = format!("{}", date_buffer.format("%d %b %y"));
-                    app_manager.renderer.draw_at(date, position);
+    for (period, label) in periods
+    {
+        draw_selectable(&mut app_manager.renderer, period == app_manager.stats_period, |renderer| {
+            renderer.draw(format!(" {} ", label));
+        });
+    }
 
-                    app_manager.renderer.pop_color(ColorType::Background);
-                    app_manager.renderer.pop_color(ColorType::Foreground);
+    app_manager.renderer.draw(format!("  {}  TOTAL {}", period_label, format_duration_short(total)));
 
-                    let (selected_date_segment, position_offset) = match app_manager.selected_datetime_segment
-                    {
-                        0 => (format!("{}", date_buffer.format("%d")), 0),
-                        1 => (format!("{}", date_buffer.format("%b")), 3),
-                        2 => (format!("{}", date_buffer.format("%y")), 7),
-                        _ => (String::new(), 0),
-                    };
+    let label_width = tag_totals.iter().map(|(tag, _)| get_display_width(tag)).max().unwrap_or(0).min(20);
+    let duration_width = 9;
+    let bar_width = (popup.width as usize).saturating_sub(label_width + duration_width + 7);
+    let longest_duration = tag_totals.first().map_or(0, |(_, duration)| duration.num_seconds() as u64);
 
-                    app_manager.renderer.draw_at(selected_date_segment, &Vector2::new(position.x + position_offset, position.y));
-                }
-                SessionField::Description(description_buffer) => match edit_field_state
-                {
-                    SessionFieldEditState::Browse =>
-                    {
-                        app_manager.renderer.draw_at(description_buffer, position);
-                    }
-                    SessionFieldEditState::Editing =>
-                    {
-                        app_manager.renderer.draw_at(description_buffer, position);
+    if tag_totals.is_empty()
+    {
+        app_manager.renderer.draw_at("No sessions in this period", &popup.at(2, 3));
+    }
 
-                        let cursor_pos_x = position.x + (description_buffer.len() + app_manager.description_buffer.len()) as u16;
+    for (row_index, (tag, duration)) in tag_totals.iter().enumerate()
+    {
+        let row_pos = popup.at(2, 3 + row_index as u16);
+        let tag = truncate_to_width(tag, label_width);
 
-                        app_manager.renderer.draw_at(CURSOR, &Vector2::new(cursor_pos_x, position.y));
-                    }
-                },
-                SessionField::Tag(tag_buffer) => match edit_field_state
-                {
-                    SessionFieldEditState::Browse =>
-                    {
-                        app_manager.renderer.draw_at(tag_buffer, position);
-                    }
-                    SessionFieldEditState::Editing =>
-                    {
-                        let dropdown_title = "EDIT TAG";
-                        let tag_dropdown_pos = position;
-                        let tag_dropdown_text_pos = Vector2::new(tag_dropdown_pos.x + 2, tag_dropdown_pos.y + 1);
-
-                        if let Some(longest_tag_str) = app_manager.tags.iter().map(String::len).max()
-                        {
-                            let longest_tag_str = cmp::max(longest_tag_str, dropdown_title.len() + 2) as u16;
-                            let tag_dropdown_size = Vector2::new(longest_tag_str + 8, app_manager.tags.len() as u16 + 2);
-
-                            draw_window(&mut app_manager.renderer, &tag_dropdown_size, tag_dropdown_pos);
-                            draw_window_shadow(&mut app_manager.renderer, &tag_dropdown_size, tag_dropdown_pos);
-
-                            draw_window_title(&mut app_manager.renderer, dropdown_title, tag_dropdown_pos);
-
-                            for (index, tag) in app_manager.tags.iter().enumerate()
-                            {
-                                let selected_row = index == app_manager.temp_tag_index;
-
-                                let arrow = if selected_row
-                                {
-                                    ARROW
-                                }
-                                else
-                                {
-                                    ' '
-                                };
-
-                                let right_pad = longest_tag_str as usize + 1;
-                                app_manager.renderer.draw_at(
-                                    format!(" {} {:<pad$}", arrow, tag, pad = right_pad),
-                                    &Vector2::new(tag_dropdown_text_pos.x, tag_dropdown_text_pos.y + index as u16),
-                                );
-                            }
-                        }
-                    }
-                },
-                SessionField::Start(start_buffer) =>
-                {
-                    render_edited_time(&mut app_manager.renderer, app_manager.selected_datetime_segment, start_buffer, position);
-                }
-                SessionField::End(end_buffer) =>
-                {
-                    if let Some(end_buffer) = end_buffer
-                    {
-                        render_edited_time(&mut app_manager.renderer, app_manager.selected_datetime_segment, end_buffer, position);
-                    }
-                    else
-                    {
-                        app_manager.renderer.draw_at(field, position);
-                    }
-                }
-                SessionField::None =>
-                {}
-            }
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+        app_manager.renderer.draw_at(pad_to_width(&tag, label_width), &row_pos);
+        app_manager.renderer.pop_color(ColorType::Foreground);
 
-            app_manager.renderer.pop_color(ColorType::Background);
-            app_manager.renderer.pop_color(ColorType::Foreground);
-        }
-        else
-        {
-            app_manager.renderer.draw_at(field, position);
-        }
+        app_manager.renderer.draw(format!(" {:>pad$} ", format_duration_short(*duration), pad = duration_width));
+
+        app_manager.renderer.push_color(ColorType::Foreground, COL_BG_MAIN);
+        app_manager.renderer.draw(get_bar(duration.num_seconds() as u64, longest_duration, bar_width));
+        app_manager.renderer.pop_color(ColorType::Foreground);
     }
 
-    if session.is_running()
+    let issues_top = popup.y + 4 + cmp::max(tag_totals.len() as u16, 1);
+    let reference_width = issue_totals.iter().map(|(reference, ..)| get_display_width(reference)).max().unwrap_or(0).min(30);
+    let url_width = (popup.width as usize).saturating_sub(reference_width + duration_width + 7);
+
+    for (row_index, (reference, url, duration)) in issue_totals.iter().enumerate()
     {
-        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED);
+        let row_pos = Vector2::new(popup.x + 2, issues_top + row_index as u16);
+
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+        app_manager.renderer.draw_at(pad_to_width(&truncate_to_width(reference, reference_width), reference_width), &row_pos);
+        app_manager.renderer.pop_color(ColorType::Foreground);
+
+        app_manager.renderer.draw(format!(" {:>pad$} ", format_duration_short(*duration), pad = duration_width));
+        app_manager.renderer.draw(ellipsize(url, url_width));
     }
-    app_manager.renderer.draw_at(duration, field_positions.last().unwrap());
-    if session.is_running()
+
+    app_manager.renderer.draw_at(
+        "[LEFT/RIGHT] period  [PGUP/PGDN] previous/next  [X] daily notes  [ESC] close",
+        &popup.at(2, popup.height - 2),
+    );
+
+    if let Some(message) = &app_manager.daily_note_message
     {
+        let message_width = get_display_width(message) as u16;
+
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+        app_manager
+            .renderer
+            .draw_at(message, &Vector2::new(popup.x + popup.width - message_width - 2, popup.y + popup.height - 2));
         app_manager.renderer.pop_color(ColorType::Foreground);
     }
+
+    close_popup(&mut app_manager.renderer);
 }
 
-fn render_edited_time(renderer: &mut Out, datetime_segment: usize, time: &NaiveDateTime, position: &Vector2)
+/// Totals over every stored session and the size of the database files.
+fn draw_database_stats(app_manager: &mut AppManager)
 {
-    renderer.push_color(ColorType::Background, COL_TEXT_HIGHLIGHT);
-    renderer.push_color(ColorType::Foreground, COL_TEXT_BLACK);
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let now = app_manager.get_current_time();
+
+    let sessions = &app_manager.sessions;
+    let finished_sessions =
+        sessions.iter().filter_map(|session| session.end.map(|end| end - session.start)).collect::<Vec<TimeDelta>>();
+    let total: TimeDelta = sessions.iter().map(|session| session.end.unwrap_or(now) - session.start).sum();
+    let finished_total: TimeDelta = finished_sessions.iter().copied().sum();
+    let average = i32::try_from(finished_sessions.len()).ok().filter(|count| *count > 0).map(|count| finished_total / count);
+    let first_start = sessions.iter().map(|session| session.start).min();
+    let last_end = sessions.iter().map(|session| session.end.unwrap_or(now)).max();
+
+    let mut used_tags = sessions.iter().map(|session| session.tag.as_str()).collect::<Vec<&str>>();
+    used_tags.sort_unstable();
+    used_tags.dedup();
+
+    let format_date =
+        |date: Option<NaiveDateTime>| date.map_or(String::from("-"), |date| date.format("%a %d %b %Y %H:%M").to_string());
+
+    let mut rows = vec![
+        (String::from("Sessions"), sessions.len().to_string()),
+        (String::from("First entry"), format_date(first_start)),
+        (String::from("Last entry"), format_date(last_end)),
+        (String::from("Tracked time"), format_duration_short(total)),
+        (String::from("Average session"), average.map_or(String::from("-"), format_duration)),
+        (String::from("Tags"), format!("{} ({} in use)", app_manager.tags.len(), used_tags.len())),
+        (String::new(), String::new()),
+    ];
 
-    let date = format!("{}", time.format("%H:%M:%S"));
-    renderer.draw_at(date, position);
+    let total_size = app_manager.database_file_sizes.iter().map(|(_, size)| *size).sum();
+    rows.extend(app_manager.database_file_sizes.iter().map(|(file_name, size)| (file_name.clone(), format_file_size(*size))));
+    rows.push((String::from("Total size"), format_file_size(total_size)));
 
-    renderer.pop_color(ColorType::Background);
-    renderer.pop_color(ColorType::Foreground);
+    let label_width = rows.iter().map(|(label, _)| get_display_width(label)).max().unwrap_or(0);
+    let value_width = rows.iter().map(|(_, value)| get_display_width(value)).max().unwrap_or(0);
 
-    let (selected_date_segment, position_offset) = match datetime_segment
+    let popup_size = Vector2::new(cmp::min(terminal_size.x - 4, (label_width + value_width + 7) as u16), rows.len() as u16 + 4);
+    let popup = get_centered_area(&popup_size, &terminal_size);
+    let text_width = popup.width as usize - 4;
+
+    open_popup(&mut app_manager.renderer, &popup, "DATABASE");
+
+    for (row_index, (label, value)) in rows.iter().enumerate()
     {
-        0 => (format!("{}", time.format("%H")), 0),
-        1 => (format!("{}", time.format("%M")), 3),
-        2 => (format!("{}", time.format("%S")), 6),
-        _ => (String::new(), 0),
-    };
+        let row_pos = popup.at(2, 1 + row_index as u16);
 
-    renderer.draw_at(selected_date_segment, &Vector2::new(position.x + position_offset, position.y));
-}
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+        app_manager.renderer.draw_at(pad_to_width(label, label_width + 2), &row_pos);
+        app_manager.renderer.pop_color(ColorType::Foreground);
 
-fn debug_draw(app_manager: &mut AppManager, message: &str)
-{
-    let formatted_msg = format!(" {message} ");
-    let window_size = app_manager.renderer.get_terminal_size();
-    let debug_pos = Vector2::new(window_size.x - formatted_msg.len() as u16 - 2, app_manager.renderer.get_terminal_size().y - 2);
+        app_manager.renderer.draw(truncate_to_width(value, text_width.saturating_sub(label_width + 2)));
+    }
 
-    app_manager.renderer.push_color(ColorType::Foreground, COL_OUTLINE_MAIN);
-    app_manager.renderer.draw_at(formatted_msg, &debug_pos);
-    app_manager.renderer.pop_color(ColorType::Foreground);
-}
+    app_manager.renderer.draw_at("[ESC] close", &popup.at(2, popup.height - 2));
 
-fn draw_window_title(renderer: &mut Out, title: &str, window_pos: &Vector2)
-{
-    const OFFSET: u16 = 2;
-    let title_pos = Vector2::new(window_pos.x + OFFSET, window_pos.y);
-    renderer.draw_at(format!(" {} ", title), &title_pos);
+    close_popup(&mut app_manager.renderer);
 }
 
-fn draw_window_shadow(renderer: &mut Out, window_size: &Vector2, window_pos: &Vector2)
+/// Bytes in the largest unit that keeps the number at least 1, e.g. `12.3 KB`.
+fn format_file_size(bytes: u64) -> String
 {
-    renderer.push_color(ColorType::Background, COL_WINDOW_SHADOW);
-    let shadow_bottom = " ".repeat(window_size.x as usize);
-    renderer.draw_at(shadow_bottom, &Vector2::new(window_pos.x + 1, window_pos.y + window_size.y));
+    let units = ["KB", "MB", "GB"];
 
-    for y in 1..=window_size.y
+    if bytes < 1024
     {
-        renderer.draw_at("  ", &Vector2::new(window_pos.x + window_size.x, window_pos.y + y));
+        return format!("{bytes} B");
     }
-    renderer.pop_color(ColorType::Background);
+
+    let mut divisor: u64 = 1024;
+    let mut unit_index = 0;
+
+    while bytes >= divisor * 1024 && unit_index + 1 < units.len()
+    {
+        divisor *= 1024;
+        unit_index += 1;
+    }
+
+    let tenths = bytes * 10 / divisor;
+
+    format!("{}.{} {}", tenths / 10, tenths % 10, units[unit_index])
 }
 
-fn draw_yes_no_popup(app_manager: &mut AppManager, title: &str)
+/// A horizontal bar scaled so that `maximum` fills `width` cells, with eighth-cell precision.
+fn get_bar(value: u64, maximum: u64, width: usize) -> String
 {
-    let confirm_popup_size = Vector2::new(40, 5);
-    let window_size = app_manager.renderer.get_terminal_size();
-    let confirm_popup_pos = Vector2::new((window_size.x - confirm_popup_size.x) / 2, (window_size.y - confirm_popup_size.y) / 2);
-    app_manager.renderer.push_color(ColorType::Background, COL_BG_POPUP);
-    app_manager.renderer.push_color(ColorType::Foreground, COL_OUTLINE_POPUP);
+    if maximum == 0
+    {
+        return String::new();
+    }
 
-    draw_window(&mut app_manager.renderer, &confirm_popup_size, &confirm_popup_pos);
-    draw_window_shadow(&mut app_manager.renderer, &confirm_popup_size, &confirm_popup_pos);
+    let eighths = (value * width as u64 * 8 / maximum) as usize;
+    let mut bar = BAR_BLOCKS[0].to_string().repeat(eighths / 8);
 
-    app_manager.renderer.push_color(ColorType::Background, COL_TEXT_BLACK);
-    app_manager.renderer.push_color(ColorType::Foreground, COL_BG_POPUP);
-    draw_window_title(&mut app_manager.renderer, title, &confirm_popup_pos);
-    app_manager.renderer.pop_color(ColorType::Background);
-    app_manager.renderer.pop_color(ColorType::Foreground);
+    if !eighths.is_multiple_of(8)
+    {
+        bar.push(BAR_BLOCKS[8 - eighths % 8]);
+    }
 
-    let text_pos_y = confirm_popup_pos.y + confirm_popup_size.y / 2;
-    let yes_pos = Vector2::new(confirm_popup_pos.x + confirm_popup_size.x / 4 - 2, text_pos_y);
-    let no_pos = Vector2::new(confirm_popup_pos.x + (confirm_popup_size.x / 4) * 3 - 2, text_pos_y);
+    bar
+}
 
-    app_manager.renderer.draw_at('[', &yes_pos);
-    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
-    app_manager.renderer.draw('y');
-    app_manager.renderer.pop_color(ColorType::Foreground);
-    app_manager.renderer.draw("]es");
-    app_manager.renderer.draw_at('[', &no_pos);
-    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
-    app_manager.renderer.draw('n');
+fn draw_day_header(
+    app_manager: &mut AppManager,
+    main_window_size: &Vector2,
+    pos_y: u16,
+    date: NaiveDate,
+    total: TimeDelta,
+    collapsed: bool,
+)
+{
+    let marker = if collapsed
+    {
+        ARROW
+    }
+    else
+    {
+        ARROW_DOWN
+    };
+
+    app_manager.renderer.push_color(ColorType::Foreground, COL_OUTLINE_MAIN);
+    app_manager.renderer.draw_at(INTERSECT_L, &Vector2::new(0, pos_y));
+    app_manager.renderer.draw(DIVIDER_H.to_string().repeat(main_window_size.x as usize - 2));
+    app_manager.renderer.draw(INTERSECT_R);
     app_manager.renderer.pop_color(ColorType::Foreground);
-    app_manager.renderer.draw("]o");
 
+    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_HIGHLIGHT);
+    app_manager.renderer.draw_at(
+        format!(" {} {} \u{2014} {} ", marker, date.format(app_manager.get_day_format()), format_duration_short(total)),
+        &Vector2::new(2, pos_y),
+    );
     app_manager.renderer.pop_color(ColorType::Foreground);
-    app_manager.renderer.pop_color(ColorType::Background);
 }
 
 fn draw_session_selection_line(app_manager: &mut AppManager, content_offset: &Vector2, command_label: &str)
 {
-    let row = (app_manager.sessions.len() - app_manager.selected_session_index - content_offset.y as usize) as u16;
+    let Some(row) = app_manager.get_selected_row_index(&app_manager.get_list_rows())
+    else
+    {
+        return;
+    };
+    let row = row as u16;
 
     app_manager.renderer.push_color(ColorType::Background, COL_TEXT_DIM);
     app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_HIGHLIGHT);
-    app_manager.renderer.draw_at(format!(" {}", command_label), &Vector2::new(content_offset.x - 1, 2 + row));
+    app_manager.renderer.draw_at(format!(" {}", command_label), &Vector2::new(content_offset.x - 1, content_offset.y + 1 + row));
     app_manager.renderer.pop_color(ColorType::Foreground);
     app_manager.renderer.pop_color(ColorType::Background);
 }
 
-fn draw_control_panel(app_manager: &mut AppManager)
+/// Lists the keys of the current state, merging keys that share a description into one line.
+fn draw_help(app_manager: &mut AppManager)
 {
-    let controls: Controls = get_controls();
-    let control_columns = controls.len() as u16;
+    let terminal_size = app_manager.renderer.get_terminal_size();
 
-    let window_size = app_manager.renderer.get_terminal_size();
-    let start_position = Vector2::new(0, window_size.y - 1);
-    let control_section_width = window_size.x / control_columns;
+    let mut lines: Vec<(String, String)> = Vec::new();
+
+    for control in get_state_controls(&app_manager.state)
+    {
+        let key = control.key.to_string();
+
+        if let Some((keys, _)) = lines.iter_mut().find(|(_, description)| *description == control.description)
+        {
+            keys.push('/');
+            keys.push_str(&key);
+        }
+        else
+        {
+            lines.push((key, control.description));
+        }
+    }
+
+    let key_width = lines.iter().map(|(keys, _)| keys.chars().count()).max().unwrap_or(0);
+    let text_width = lines.iter().map(|(_, description)| description.chars().count()).max().unwrap_or(0) + key_width + 4;
+    let title = format!("HELP: {}", app_manager.state).to_uppercase();
+
+    let popup_size = Vector2::new(
+        cmp::min(terminal_size.x.saturating_sub(4), cmp::max(text_width, title.chars().count() + 4) as u16 + 4),
+        cmp::min(terminal_size.y.saturating_sub(4), lines.len() as u16 + 4),
+    );
+    let popup = get_centered_area(&popup_size, &terminal_size);
+
+    open_popup(&mut app_manager.renderer, &popup, &title);
+
+    for (line_index, (keys, description)) in lines.iter().take(popup.height.saturating_sub(4) as usize).enumerate()
+    {
+        let position = popup.at(2, 2 + line_index as u16);
+
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+        app_manager.renderer.draw_at(format!("{keys:>key_width$}"), &position);
+        app_manager.renderer.pop_color(ColorType::Foreground);
+        app_manager.renderer.draw(format!("  {description}"));
+    }
+
+    close_popup(&mut app_manager.renderer);
+}
+
+/// The last log records over the lower half of the screen, newest at the bottom, with warnings and errors in red. Stays
+/// open while working with the app, so it can be watched while reproducing a problem.
+fn draw_debug_log(app_manager: &mut AppManager)
+{
+    let terminal_size = app_manager.renderer.get_terminal_size();
+    let popup_height = terminal_size.y / 2;
+    let popup = Rect::new(2, terminal_size.y.saturating_sub(popup_height + 2), terminal_size.x.saturating_sub(4), popup_height);
+    let text_width = popup.width.saturating_sub(4) as usize;
+
+    let title = if app_manager.is_ephemeral()
+    {
+        String::from("DEBUG LOG: IN MEMORY")
+    }
+    else
+    {
+        format!("DEBUG LOG: {}", DatabaseHandler::get_log_file_path().display())
+    };
+
+    let records = get_recent_records();
+    let visible_count = popup.height.saturating_sub(4) as usize;
 
-    let bg = " ".repeat(window_size.x as usize);
     app_manager.renderer.push_color(ColorType::Background, COL_BG_POPUP);
-    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_BLACK);
-    app_manager.renderer.draw_at(bg, &start_position);
+    app_manager.renderer.push_color(ColorType::Foreground, COL_OUTLINE_POPUP);
+
+    draw_frame(&mut app_manager.renderer, &popup);
+    draw_inverted_title(&mut app_manager.renderer, &truncate_to_width(&title, text_width), &popup);
+
+    if records.is_empty()
+    {
+        app_manager.renderer.draw_at("Nothing logged yet", &popup.at(2, 2));
+    }
 
-    for label_index in 0..control_columns
+    for (line_index, record) in records.iter().skip(records.len().saturating_sub(visible_count)).enumerate()
     {
-        if let Some(control_label) = controls.get(label_index as usize)
+        let position = popup.at(2, 2 + line_index as u16);
+        let is_problem = record.contains(" WARN ") || record.contains(" ERROR ");
+
+        if is_problem
         {
-            let position = Vector2::new(start_position.x + (control_section_width * label_index), start_position.y);
-            app_manager.renderer.draw_at('[', &position);
             app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
-            app_manager.renderer.draw(key_to_char(control_label.key));
+        }
+
+        app_manager.renderer.draw_at(truncate_to_width(record, text_width), &position);
+
+        if is_problem
+        {
             app_manager.renderer.pop_color(ColorType::Foreground);
-            app_manager.renderer.draw(format!("] {}", &control_label.description));
         }
     }
 
-    app_manager.renderer.pop_color(ColorType::Background);
     app_manager.renderer.pop_color(ColorType::Foreground);
+    app_manager.renderer.pop_color(ColorType::Background);
 }
 
-fn get_user_key() -> Option<KeyCode>
+/// Shows as many idle commands as fit into the bar, always keeping the help key as the last one.
+fn draw_control_panel(app_manager: &mut AppManager)
 {
-    let event = event::read().expect("Input Error");
+    let window_size = app_manager.renderer.get_terminal_size();
+    let start_position = Vector2::new(0, window_size.y - 1);
+
+    let mut controls: Controls = get_controls();
+    let get_label_width =
+        |control: &Control| (control.key.to_string().chars().count() + control.description.chars().count() + 3) as u16;
+    let get_labels_width = |controls: &Controls| controls.iter().map(|control| get_label_width(control) + 2).sum::<u16>();
 
-    if let Some(key_event) = event.as_key_press_event()
+    while controls.len() > 1 && get_labels_width(&controls) > window_size.x
     {
-        return Some(key_event.code);
+        controls.remove(controls.len() - 2);
     }
 
-    None
-}
+    let label_spacing = (window_size.x - get_labels_width(&controls)) / controls.len() as u16 + 2;
 
-fn draw_window(renderer: &mut Out, size: &Vector2, position: &Vector2)
-{
-    renderer.draw_at(CORNER_TL, position);
+    let bg = " ".repeat(window_size.x as usize);
+    app_manager.renderer.push_color(ColorType::Background, COL_BG_POPUP);
+    app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_BLACK);
+    app_manager.renderer.draw_at(bg, &start_position);
 
-    for _ in 0..size.x - 2
-    {
-        renderer.draw(FRAME_H);
-    }
-    renderer.draw(CORNER_TR);
+    let mut position = start_position;
 
-    for y in 1..size.y - 1
+    for control_label in &controls
     {
-        renderer.draw_at(FRAME_V, &Vector2::new(position.x, position.y + y));
-        for _ in 0..size.x - 2
+        if let CommandState::Idle = app_manager.state
         {
-            renderer.draw(' ');
+            app_manager.add_click_area(position.x, position.y, get_label_width(control_label), ClickTarget::Key(control_label.key));
         }
-        renderer.draw(FRAME_V);
-    }
 
-    renderer.draw_at(CORNER_BL, &Vector2::new(position.x, position.y + size.y - 1));
-    for _ in 0..size.x - 2
-    {
-        renderer.draw(FRAME_H);
+        app_manager.renderer.draw_at('[', &position);
+        app_manager.renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+        app_manager.renderer.draw(control_label.key.to_string());
+        app_manager.renderer.pop_color(ColorType::Foreground);
+        app_manager.renderer.draw(format!("] {}", &control_label.description));
+
+        position.x += get_label_width(control_label) + label_spacing;
     }
-    renderer.draw(CORNER_BR);
+
+    app_manager.renderer.pop_color(ColorType::Background);
+    app_manager.renderer.pop_color(ColorType::Foreground);
+}
+
+fn draw_terminal_too_small(app_manager: &mut AppManager, terminal_size: &Vector2)
+{
+    let message =
+        format!("Terminal too small: {}x{}, needs {MIN_TERMINAL_WIDTH}x{MIN_TERMINAL_HEIGHT}", terminal_size.x, terminal_size.y);
+    let message = truncate_to_width(&message, terminal_size.x as usize);
+    let message_pos = Vector2::new((terminal_size.x - message.chars().count() as u16) / 2, terminal_size.y / 2);
+
+    app_manager.renderer.clear_screen();
+    app_manager.renderer.draw_at(message, &message_pos);
 }
@@ -0,0 +1,115 @@
+use crate::app_state::ManualEntryField;
+use crate::session::Session;
+use crate::time_input::{parse_time, INPUT_DATE_FORMAT, INPUT_TIME_FORMAT};
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+
+pub struct ManualEntry
+{
+    pub description: String,
+    pub tag_index: usize,
+    pub date: String,
+    pub start: String,
+    pub end: String,
+    pub error: Option<String>,
+}
+
+impl Default for ManualEntry
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl ManualEntry
+{
+    #[must_use]
+    pub fn new() -> Self
+    {
+        ManualEntry {
+            description: String::new(),
+            tag_index: 0,
+            date: String::new(),
+            start: String::new(),
+            end: String::new(),
+            error: None,
+        }
+    }
+
+    pub fn reset(&mut self, tag_index: usize, now: NaiveDateTime)
+    {
+        self.description.clear();
+        self.tag_index = tag_index;
+        self.date = format!("{}", now.format(INPUT_DATE_FORMAT));
+        self.start = format!("{}", now.format(INPUT_TIME_FORMAT));
+        self.end = format!("{}", now.format(INPUT_TIME_FORMAT));
+        self.error = None;
+    }
+
+    pub fn get_text_field_mut(&mut self, field: ManualEntryField) -> Option<&mut String>
+    {
+        match field
+        {
+            ManualEntryField::Description => Some(&mut self.description),
+            ManualEntryField::Date => Some(&mut self.date),
+            ManualEntryField::Start => Some(&mut self.start),
+            ManualEntryField::End => Some(&mut self.end),
+            ManualEntryField::Tag => None,
+        }
+    }
+
+    /// The session filled into the form, with the tag at the selected index of `tags`.
+    ///
+    /// # Errors
+    ///
+    /// The message of the first field that is missing or can't be read, or of an end equal to the start.
+    pub fn to_session(&self, tags: &[String]) -> Result<Session, String>
+    {
+        let description = self.description.trim();
+
+        if description.is_empty()
+        {
+            return Err("DESCRIPTION IS EMPTY".to_string());
+        }
+
+        let Some(tag) = tags.get(self.tag_index)
+        else
+        {
+            return Err("NO TAG SELECTED".to_string());
+        };
+
+        let Ok(date) = NaiveDate::parse_from_str(self.date.trim(), INPUT_DATE_FORMAT)
+        else
+        {
+            return Err("INVALID DATE (DD-MM-YYYY)".to_string());
+        };
+
+        let Some(start) = parse_time(&self.start)
+        else
+        {
+            return Err("INVALID START TIME (HH:MM)".to_string());
+        };
+
+        let Some(end) = parse_time(&self.end)
+        else
+        {
+            return Err("INVALID END TIME (HH:MM)".to_string());
+        };
+
+        let start = date.and_time(start);
+        let mut end = date.and_time(end);
+
+        if end == start
+        {
+            return Err("END MUST DIFFER FROM START".to_string());
+        }
+
+        // An end time before the start time means the session ran past midnight.
+        if end < start
+        {
+            end += TimeDelta::days(1);
+        }
+
+        Ok(Session::from(description, tag, start, Some(end)))
+    }
+}
@@ -0,0 +1,171 @@
+use crate::text_input::get_word_start;
+
+/// Multi-line text being edited, with the cursor as a line and a character position within it.
+pub struct NotesEditor
+{
+    pub lines: Vec<String>,
+    pub cursor_line: usize,
+    pub cursor_column: usize,
+    original: String,
+}
+
+impl NotesEditor
+{
+    /// Starts editing `notes` with the cursor at the end.
+    pub fn new(notes: &str) -> Self
+    {
+        let lines = notes.split('\n').map(str::to_string).collect::<Vec<String>>();
+        let cursor_line = lines.len() - 1;
+        let cursor_column = lines[cursor_line].chars().count();
+
+        NotesEditor {
+            lines,
+            cursor_line,
+            cursor_column,
+            original: notes.to_string(),
+        }
+    }
+
+    /// The edited notes, without blank lines at the end.
+    #[must_use]
+    pub fn get_text(&self) -> String
+    {
+        self.lines.join("\n").trim_end().to_string()
+    }
+
+    #[must_use]
+    pub fn has_changes(&self) -> bool
+    {
+        self.get_text() != self.original
+    }
+
+    pub fn insert(&mut self, character: char)
+    {
+        let byte_index = self.get_cursor_byte_index();
+        self.lines[self.cursor_line].insert(byte_index, character);
+        self.cursor_column += 1;
+    }
+
+    pub fn insert_line_break(&mut self)
+    {
+        let byte_index = self.get_cursor_byte_index();
+        let rest = self.lines[self.cursor_line].split_off(byte_index);
+
+        self.lines.insert(self.cursor_line + 1, rest);
+        self.cursor_line += 1;
+        self.cursor_column = 0;
+    }
+
+    /// Removes the character before the cursor, joining the line with the one above at its start.
+    pub fn delete_before(&mut self)
+    {
+        if self.cursor_column > 0
+        {
+            self.cursor_column -= 1;
+            let byte_index = self.get_cursor_byte_index();
+            self.lines[self.cursor_line].remove(byte_index);
+        }
+        else if self.cursor_line > 0
+        {
+            let line = self.lines.remove(self.cursor_line);
+
+            self.cursor_line -= 1;
+            self.cursor_column = self.lines[self.cursor_line].chars().count();
+            self.lines[self.cursor_line].push_str(&line);
+        }
+    }
+
+    /// Removes the word before the cursor within the line. At the start of a line it is joined with the one above.
+    pub fn delete_word_before(&mut self)
+    {
+        if self.cursor_column == 0
+        {
+            self.delete_before();
+            return;
+        }
+
+        let characters = self.lines[self.cursor_line].chars().collect::<Vec<char>>();
+        let word_start = get_word_start(&characters, self.cursor_column);
+
+        self.lines[self.cursor_line] = characters[..word_start].iter().chain(&characters[self.cursor_column..]).collect();
+        self.cursor_column = word_start;
+    }
+
+    /// Removes the character under the cursor, joining the line below at the end of a line.
+    pub fn delete_after(&mut self)
+    {
+        if self.cursor_column < self.get_line_length()
+        {
+            let byte_index = self.get_cursor_byte_index();
+            self.lines[self.cursor_line].remove(byte_index);
+        }
+        else if self.cursor_line + 1 < self.lines.len()
+        {
+            let line = self.lines.remove(self.cursor_line + 1);
+            self.lines[self.cursor_line].push_str(&line);
+        }
+    }
+
+    pub fn move_left(&mut self)
+    {
+        if self.cursor_column > 0
+        {
+            self.cursor_column -= 1;
+        }
+        else if self.cursor_line > 0
+        {
+            self.cursor_line -= 1;
+            self.cursor_column = self.get_line_length();
+        }
+    }
+
+    pub fn move_right(&mut self)
+    {
+        if self.cursor_column < self.get_line_length()
+        {
+            self.cursor_column += 1;
+        }
+        else if self.cursor_line + 1 < self.lines.len()
+        {
+            self.cursor_line += 1;
+            self.cursor_column = 0;
+        }
+    }
+
+    /// Moves a line up or down, keeping the column where the line is long enough.
+    pub fn move_vertically(&mut self, up: bool)
+    {
+        if up && self.cursor_line > 0
+        {
+            self.cursor_line -= 1;
+        }
+        else if !up && self.cursor_line + 1 < self.lines.len()
+        {
+            self.cursor_line += 1;
+        }
+
+        self.cursor_column = self.cursor_column.min(self.get_line_length());
+    }
+
+    pub fn move_to_line_start(&mut self)
+    {
+        self.cursor_column = 0;
+    }
+
+    pub fn move_to_line_end(&mut self)
+    {
+        self.cursor_column = self.get_line_length();
+    }
+
+    fn get_line_length(&self) -> usize
+    {
+        self.lines[self.cursor_line].chars().count()
+    }
+
+    fn get_cursor_byte_index(&self) -> usize
+    {
+        let line = &self.lines[self.cursor_line];
+
+        line.char_indices().nth(self.cursor_column).map_or(line.len(), |(byte_index, _)| byte_index)
+    }
+}
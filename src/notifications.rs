@@ -0,0 +1,28 @@
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Shows a desktop notification through notify-send, or osascript on macOS. Does nothing where neither is available.
+pub fn send_notification(title: &str, body: &str)
+{
+    let mut command = if cfg!(target_os = "macos")
+    {
+        let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!("display notification {} with title {}", quote(body), quote(title)));
+        command
+    }
+    else
+    {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name", "time-tracker", title, body]);
+        command
+    };
+
+    command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+
+    // Waited for on a thread of its own so it doesn't linger as a zombie.
+    thread::spawn(move || {
+        let _ = command.status();
+    });
+}
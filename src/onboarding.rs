@@ -0,0 +1,144 @@
+//! The first run wizard. Before anything is written it asks where to keep the database, how to show times and dates,
+//! which tags to start with and whether to import sessions tracked elsewhere, so nothing lands next to the binary
+//! unasked.
+
+use crate::config::{set_config_value, Config};
+use crate::database_handler::{DatabaseHandler, CONFIG_FILE_NAME};
+use crate::importer::expand_home;
+use std::env;
+use std::io;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Values of the `clock` and `date_order` settings, the first being the default.
+const CLOCK_FORMATS: [&str; 2] = ["24h", "12h"];
+const DATE_ORDERS: [&str; 3] = ["dmy", "mdy", "ymd"];
+
+pub struct Onboarding
+{
+    pub database_path: PathBuf,
+    /// `None` when the chosen folder already holds a database, whose settings are kept.
+    pub clock_format: Option<String>,
+    pub date_order: Option<String>,
+    pub tags: Vec<String>,
+    /// A file exported by another time tracker, imported once the app is open.
+    pub import_path: Option<String>,
+}
+
+/// Asks the first run questions, suggesting `default_database_path` for the database. Every question shows its default
+/// in brackets, taken by an empty answer, and is asked again after an invalid one. `None` if the input ends before the
+/// last answer.
+pub fn ask_onboarding(input: &mut impl BufRead, output: &mut impl Write, default_database_path: &Path) -> Option<Onboarding>
+{
+    writeln!(output, "Welcome to time-tracker! A few questions before the database is created, Enter takes the default.").ok()?;
+
+    let database_path = ask(input, output, "Database folder", &default_database_path.display().to_string(), |answer| {
+        !get_absolute_path(answer).is_file()
+    })?;
+    let database_path = get_absolute_path(&database_path);
+
+    if database_path.join(CONFIG_FILE_NAME).is_file()
+    {
+        writeln!(output, "Found a database there, keeping its settings and tags.").ok()?;
+
+        return Some(Onboarding {
+            database_path,
+            clock_format: None,
+            date_order: None,
+            tags: Vec::new(),
+            import_path: None,
+        });
+    }
+
+    let clock_format = ask(input, output, "Clock, 24h or 12h", CLOCK_FORMATS[0], |answer| CLOCK_FORMATS.contains(&answer))?;
+    let date_order = ask(input, output, "Date order, dmy, mdy or ymd", DATE_ORDERS[0], |answer| DATE_ORDERS.contains(&answer))?;
+    let tags = ask(input, output, "Tags to start with, separated by commas", "", |_| true)?;
+    let import_path = ask(input, output, "File from Toggl, Timewarrior, a timeclock or a CSV to import", "", |answer| {
+        answer.is_empty() || Path::new(&expand_home(answer)).is_file()
+    })?;
+
+    Some(Onboarding {
+        database_path,
+        clock_format: Some(clock_format),
+        date_order: Some(date_order),
+        tags: tags.split(',').map(str::trim).filter(|tag| !tag.is_empty()).map(str::to_string).collect(),
+        import_path: Some(import_path).filter(|import_path| !import_path.is_empty()),
+    })
+}
+
+/// Creates the database folder and its config with the answers. Tags and the import are left to the app, which stores
+/// them like any other.
+///
+/// # Errors
+///
+/// If the folder or the config can't be written.
+pub fn apply_onboarding(onboarding: &Onboarding) -> io::Result<()>
+{
+    DatabaseHandler::set_root_path(&onboarding.database_path)?;
+
+    let config_path = onboarding.database_path.join(CONFIG_FILE_NAME);
+    // Writes the commented template the answers go into.
+    let _ = Config::load(&config_path, None);
+
+    let settings = [("clock", &onboarding.clock_format), ("date_order", &onboarding.date_order)];
+
+    for (key, value) in settings.iter().filter_map(|(key, value)| Some((key, value.as_ref()?)))
+    {
+        if !set_config_value(&config_path, "appearance", key, &format!("\"{value}\""))
+        {
+            return Err(io::Error::other(format!("Failed to write {}.", config_path.display())));
+        }
+    }
+
+    Ok(())
+}
+
+/// Asks until an answer is valid, an empty one meaning `default`. `None` if the input ends.
+fn ask(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+    default: &str,
+    is_valid: impl Fn(&str) -> bool,
+) -> Option<String>
+{
+    loop
+    {
+        write!(output, "{question} [{default}]: ").ok()?;
+        output.flush().ok()?;
+
+        let mut answer = String::new();
+
+        if input.read_line(&mut answer).ok()? == 0
+        {
+            writeln!(output).ok()?;
+            return None;
+        }
+
+        let answer = match answer.trim()
+        {
+            "" => default,
+            answer => answer,
+        };
+
+        if is_valid(answer)
+        {
+            return Some(answer.to_string());
+        }
+
+        writeln!(output, "Invalid answer.").ok()?;
+    }
+}
+
+/// The folder typed as an answer, with `~` for the home folder and relative to the working folder.
+fn get_absolute_path(answer: &str) -> PathBuf
+{
+    let path = PathBuf::from(expand_home(answer));
+
+    if path.is_absolute()
+    {
+        return path;
+    }
+
+    env::current_dir().map(|current_path| current_path.join(&path)).unwrap_or(path)
+}
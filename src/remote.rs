@@ -0,0 +1,228 @@
+use crate::database_handler::{DatabaseHandler, DEFAULT_WORKSPACE, JOURNAL_ADD, JOURNAL_DELETE, JOURNAL_EDIT};
+use crate::http;
+
+/// The HTTP API a workspace's sessions are kept on, see the `[remote]` section of the config for what it has to serve.
+pub struct RemoteStorage
+{
+    pub sessions_url: String,
+    pub token: Option<String>,
+}
+
+/// A change to a stored session, sent to the remote storage right after it was made. Changes the remote storage
+/// didn't accept wait in the outbox as journal records.
+pub enum SessionChange
+{
+    Create(String),
+    Update(String),
+    Delete(u64),
+}
+
+impl SessionChange
+{
+    fn to_record(&self) -> String
+    {
+        match self
+        {
+            SessionChange::Create(session_string) => format!("{JOURNAL_ADD}{session_string}"),
+            SessionChange::Update(session_string) => format!("{JOURNAL_EDIT}{session_string}"),
+            SessionChange::Delete(session_id) => format!("{JOURNAL_DELETE}{session_id}"),
+        }
+    }
+
+    fn from_record(record: &str) -> Option<Self>
+    {
+        let mut characters = record.chars();
+
+        match characters.next()?
+        {
+            JOURNAL_ADD => Some(SessionChange::Create(characters.as_str().to_string())),
+            JOURNAL_EDIT => Some(SessionChange::Update(characters.as_str().to_string())),
+            JOURNAL_DELETE => characters.as_str().parse::<u64>().ok().map(SessionChange::Delete),
+            _ => None,
+        }
+    }
+}
+
+impl RemoteStorage
+{
+    /// Named workspaces are kept under `<url>/workspaces/<workspace>`, as they all share the configured server.
+    pub fn new(url: &str, token: Option<&str>, workspace: &str) -> Self
+    {
+        let url = url.trim_end_matches('/');
+
+        let sessions_url = if workspace == DEFAULT_WORKSPACE
+        {
+            format!("{url}/sessions")
+        }
+        else
+        {
+            format!("{url}/workspaces/{workspace}/sessions")
+        };
+
+        RemoteStorage {
+            sessions_url,
+            token: token.map(str::to_string),
+        }
+    }
+
+    /// The stored lines of all sessions, in the format of the sessions file.
+    fn list_sessions(&self) -> Result<Vec<String>, String>
+    {
+        let body = self.request("GET", &self.sessions_url, None)?;
+
+        Ok(body.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect())
+    }
+
+    fn send_change(&self, change: &SessionChange, value_separator: char) -> Result<(), String>
+    {
+        match change
+        {
+            SessionChange::Create(session_string) => self.request("POST", &self.sessions_url, Some(session_string)).map(|_| ()),
+            SessionChange::Update(session_string) =>
+            {
+                let session_id =
+                    DatabaseHandler::get_session_line_id(session_string, value_separator).ok_or("INVALID SESSION IN OUTBOX")?;
+
+                match self.request("PUT", &format!("{}/{session_id}", self.sessions_url), Some(session_string))
+                {
+                    // Deleted from another machine in the meantime, the edit brings it back.
+                    Err(error) if error == "REMOTE SESSION NOT FOUND" =>
+                    {
+                        self.request("POST", &self.sessions_url, Some(session_string)).map(|_| ())
+                    }
+                    result => result.map(|_| ()),
+                }
+            }
+            SessionChange::Delete(session_id) =>
+            {
+                match self.request("DELETE", &format!("{}/{session_id}", self.sessions_url), None)
+                {
+                    // Already gone, deleted from another machine.
+                    Err(error) if error == "REMOTE SESSION NOT FOUND" => Ok(()),
+                    result => result.map(|_| ()),
+                }
+            }
+        }
+    }
+
+    /// Sends a request with the token and returns the response body.
+    fn request(&self, method: &str, url: &str, body: Option<&str>) -> Result<String, String>
+    {
+        let mut headers = Vec::new();
+
+        if let Some(token) = &self.token
+        {
+            headers.push(format!("Authorization: Bearer {token}"));
+        }
+
+        if body.is_some()
+        {
+            headers.push(String::from("Content-Type: text/plain; charset=utf-8"));
+        }
+
+        let (status, body) = http::request(method, url, &headers, body)?;
+
+        match status
+        {
+            200..=299 => Ok(body),
+            0 => Err(String::from("REMOTE UNREACHABLE")),
+            401 | 403 => Err(String::from("REMOTE TOKEN REJECTED")),
+            404 => Err(String::from("REMOTE SESSION NOT FOUND")),
+            status => Err(format!("REMOTE ERROR {status}")),
+        }
+    }
+}
+
+/// Sends the changes waiting in the outbox, then replaces the stored sessions with those of the remote storage, which
+/// is the source of truth. A remote storage without any sessions gets all of this machine's instead.
+///
+/// # Errors
+///
+/// If the remote storage can't be reached or sends invalid sessions, or the sessions can't be stored.
+pub fn pull(
+    database_handler: &DatabaseHandler,
+    storage: &RemoteStorage,
+    value_separator: char,
+    date_format: &str,
+) -> Result<(), String>
+{
+    let mut session_strings = storage.list_sessions()?;
+
+    if session_strings.is_empty()
+    {
+        // The outbox is part of what is sent anyway.
+        database_handler.export_remote_outbox(&[]).map_err(|_| String::from("OUTBOX NOT WRITABLE"))?;
+
+        let changes = database_handler
+            .import_all_session_lines(value_separator, date_format)
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .map(SessionChange::Create)
+            .collect::<Vec<SessionChange>>();
+
+        return push(database_handler, storage, &changes, value_separator);
+    }
+
+    if !database_handler.import_remote_outbox().is_empty()
+    {
+        push(database_handler, storage, &[], value_separator)?;
+        session_strings = storage.list_sessions()?;
+    }
+
+    let (sessions, rejected_lines) = DatabaseHandler::parse_sessions(&session_strings, value_separator, date_format);
+
+    if !rejected_lines.is_empty()
+    {
+        return Err(String::from("REMOTE SENT INVALID SESSIONS"));
+    }
+
+    database_handler.load_all_sessions();
+    database_handler
+        .export_all_sessions(&sessions.unwrap_or_default(), value_separator, date_format)
+        .map_err(|_| String::from("SESSIONS NOT WRITABLE"))?;
+    database_handler.reset_loaded_months();
+
+    Ok(())
+}
+
+/// Sends the changes in the outbox followed by `changes`, in order. Whatever couldn't be sent stays in the outbox for
+/// the next try.
+///
+/// # Errors
+///
+/// If a change can't be sent.
+pub fn push(
+    database_handler: &DatabaseHandler,
+    storage: &RemoteStorage,
+    changes: &[SessionChange],
+    value_separator: char,
+) -> Result<(), String>
+{
+    let mut records = database_handler.import_remote_outbox();
+
+    if records.is_empty() && changes.is_empty()
+    {
+        return Ok(());
+    }
+
+    records.extend(changes.iter().map(SessionChange::to_record));
+
+    let mut sent_count = 0;
+    let mut result = Ok(());
+
+    for record in &records
+    {
+        if let Some(change) = SessionChange::from_record(record)
+            && let Err(error) = storage.send_change(&change, value_separator)
+        {
+            result = Err(format!("{error}, {} CHANGES PENDING", records.len() - sent_count));
+            break;
+        }
+
+        sent_count += 1;
+    }
+
+    database_handler.export_remote_outbox(&records[sent_count..]).map_err(|_| String::from("OUTBOX NOT WRITABLE"))?;
+
+    result
+}
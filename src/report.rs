@@ -0,0 +1,616 @@
+use crate::clock::{Clock, SystemClock};
+use crate::config::{Config, Rounding};
+use crate::crypto::Cipher;
+use crate::database_handler::{DatabaseHandler, DATE_FORMAT, VALUE_SEPARATOR};
+use crate::day_off::DayOff;
+use crate::json::JsonValue;
+use crate::server::session_to_json;
+use crate::session::{format_duration, format_duration_short, Session};
+use crate::text::{get_display_width, pad_to_width};
+use crate::time_input::{get_week_start, parse_date_input};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use std::cmp;
+
+const REPORT_DATE_FORMAT: &str = "%Y-%m-%d";
+/// Sessions `log` prints without `--limit`.
+const DEFAULT_LOG_LIMIT: usize = 20;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ReportGrouping
+{
+    Tag,
+    Day,
+    Project,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ReportFormat
+{
+    Table,
+    Csv,
+    Json,
+    Markdown,
+}
+
+impl ReportGrouping
+{
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self>
+    {
+        match text
+        {
+            "tag" => Some(ReportGrouping::Tag),
+            "day" => Some(ReportGrouping::Day),
+            "project" => Some(ReportGrouping::Project),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn get_name(self) -> &'static str
+    {
+        match self
+        {
+            ReportGrouping::Tag => "tag",
+            ReportGrouping::Day => "day",
+            ReportGrouping::Project => "project",
+        }
+    }
+}
+
+impl ReportFormat
+{
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self>
+    {
+        match text
+        {
+            "table" => Some(ReportFormat::Table),
+            "csv" => Some(ReportFormat::Csv),
+            "json" => Some(ReportFormat::Json),
+            "md" => Some(ReportFormat::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// The tracked time between `--from` and `--to`, both days included, for scripts and mailed summaries. The range
+/// defaults to the current week up to today, like the week report of the app. The options are as given on the command
+/// line, `None` when missing, and an error explains the first invalid one.
+///
+/// # Errors
+///
+/// The message explaining the first invalid option.
+pub fn get_report_text(
+    workspace: &str,
+    cipher: Option<Cipher>,
+    from: Option<&str>,
+    to: Option<&str>,
+    group_by: Option<&str>,
+    format: Option<&str>,
+) -> Result<String, String>
+{
+    let database_handler = DatabaseHandler::new(workspace, cipher);
+    let config = Config::load(&database_handler.get_config_file_path(), database_handler.get_workspace_config_file_path().as_deref());
+    let now = SystemClock.now();
+
+    let to = to.map_or(Some(now.date()), |to| parse_date_input(to, now.date())).ok_or("--to needs a date like 2026-01-31.")?;
+    let from = from
+        .map_or(Some(get_week_start(to, config.week_start)), |from| parse_date_input(from, now.date()))
+        .ok_or("--from needs a date like 2026-01-31.")?;
+    let grouping = group_by.map_or(Some(ReportGrouping::Tag), ReportGrouping::parse).ok_or("--group-by needs tag, day or project.")?;
+    let format = format.map_or(Some(ReportFormat::Table), ReportFormat::parse).ok_or("--format needs table, csv, json or md.")?;
+
+    if to < from
+    {
+        return Err(String::from("--to is before --from."));
+    }
+
+    // A day earlier for the sessions running over midnight into the range, or into the overtime balance.
+    let since = config.balance_start.map_or(from, |balance_start| balance_start.min(from));
+    let sessions = import_sessions_since(&database_handler, since.pred_opt());
+    let rows = get_report_rows(&sessions, from, to, grouping, config.rounding, now);
+    let balance = get_flex_balance(&sessions, &config, &database_handler.import_days_off(VALUE_SEPARATOR), to, now);
+
+    Ok(format_report(&rows, from, to, grouping, format, balance))
+}
+
+/// The sessions with `tag` started between `from` and `to`, both days included, newest first and one line each like
+/// `git log --oneline`, or as a JSON array. Only the latest `limit` sessions are listed, `DEFAULT_LOG_LIMIT` without
+/// one. The options are as given on the command line, `None` when missing, and an error explains the first
+/// invalid one.
+///
+/// # Errors
+///
+/// The message explaining the first invalid option.
+pub fn get_log_text(
+    workspace: &str,
+    cipher: Option<Cipher>,
+    tag: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+    limit: Option<&str>,
+    json: bool,
+) -> Result<String, String>
+{
+    let now = SystemClock.now();
+    let from = from.map(|from| parse_date_input(from, now.date()).ok_or("--from needs a date like 2026-01-31.")).transpose()?;
+    let to = to.map(|to| parse_date_input(to, now.date()).ok_or("--to needs a date like 2026-01-31.")).transpose()?;
+    let limit = limit.map_or(Some(DEFAULT_LOG_LIMIT), |limit| limit.parse().ok()).ok_or("--limit needs a number of sessions.")?;
+
+    let database_handler = DatabaseHandler::new(workspace, cipher);
+    let sessions = import_sessions_since(&database_handler, from);
+
+    let sessions = get_log_sessions(&sessions, tag, from, to, limit);
+
+    if json
+    {
+        Ok(format_log_json(&sessions, now))
+    }
+    else
+    {
+        Ok(format_log(&sessions, now))
+    }
+}
+
+/// The stored sessions since `date`, the whole history without one, and the running session of this machine.
+fn import_sessions_since(database_handler: &DatabaseHandler, date: Option<NaiveDate>) -> Vec<Session>
+{
+    match date
+    {
+        Some(date) => database_handler.load_sessions_since(date),
+        None => database_handler.load_all_sessions(),
+    };
+
+    let (sessions, _) = database_handler.import_sessions(VALUE_SEPARATOR, DATE_FORMAT);
+    let mut sessions = sessions.unwrap_or_default();
+    sessions.extend(database_handler.import_own_running_session(VALUE_SEPARATOR, DATE_FORMAT));
+
+    sessions
+}
+
+/// Tracked time per group within `from` and `to`, both days included. Each session is rounded on its own within every
+/// group it counts towards, like on the report screens of the app. Days come in order, tags and projects with the most
+/// time first. Tags without a project are grouped as "-".
+#[must_use]
+pub fn get_report_rows(
+    sessions: &[Session],
+    from: NaiveDate,
+    to: NaiveDate,
+    grouping: ReportGrouping,
+    rounding: Rounding,
+    now: NaiveDateTime,
+) -> Vec<(String, TimeDelta)>
+{
+    let mut rows: Vec<(String, TimeDelta)> = Vec::new();
+    let mut add = |name: &str, duration: TimeDelta| {
+        if duration.is_zero()
+        {
+            return;
+        }
+
+        match rows.iter_mut().find(|(existing_name, _)| existing_name == name)
+        {
+            Some((_, total)) => *total += duration,
+            None => rows.push((name.to_string(), duration)),
+        }
+    };
+
+    if grouping == ReportGrouping::Day
+    {
+        for date in from.iter_days().take_while(|date| *date <= to)
+        {
+            let (day_start, day_end) = get_bounds(date, date);
+            let name = date.format(REPORT_DATE_FORMAT).to_string();
+
+            for session in sessions
+            {
+                add(&name, rounding.apply(session.get_duration_within(day_start, day_end, now)));
+            }
+        }
+
+        return rows;
+    }
+
+    let (range_start, range_end) = get_bounds(from, to);
+
+    for session in sessions
+    {
+        let name = match grouping
+        {
+            ReportGrouping::Project => session.get_project().unwrap_or("-"),
+            ReportGrouping::Tag | ReportGrouping::Day => &session.tag,
+        };
+
+        add(name, rounding.apply(session.get_duration_within(range_start, range_end, now)));
+    }
+
+    rows.sort_by(|(name, total), (other_name, other_total)| other_total.cmp(total).then_with(|| name.cmp(other_name)));
+
+    rows
+}
+
+/// Time tracked beyond the schedule from `from` to `to`, both days included, negative when less was tracked. Days off are
+/// scheduled for nothing, so time tracked on them is overtime. Each session is rounded on its own within every day, like
+/// in the day report.
+#[must_use]
+pub fn get_flex_time(
+    sessions: &[Session],
+    config: &Config,
+    days_off: &[(NaiveDate, DayOff)],
+    from: NaiveDate,
+    to: NaiveDate,
+    now: NaiveDateTime,
+) -> TimeDelta
+{
+    let mut tracked = TimeDelta::zero();
+
+    for session in sessions
+    {
+        let last_date = session.end.unwrap_or(now).date().min(to);
+
+        for date in session.start.date().max(from).iter_days().take_while(|date| *date <= last_date)
+        {
+            let (day_start, day_end) = get_bounds(date, date);
+            tracked += config.rounding.apply(session.get_duration_within(day_start, day_end, now));
+        }
+    }
+
+    let scheduled: TimeDelta = from
+        .iter_days()
+        .take_while(|date| *date <= to)
+        .filter(|date| !days_off.iter().any(|(day_off_date, _)| day_off_date == date))
+        .map(|date| config.get_scheduled_time(date.weekday()))
+        .sum();
+
+    tracked - scheduled
+}
+
+/// The overtime balance at the end of `date`, from the balance start of the config on. Days count once they are over,
+/// so today is left out. `None` without a balance start.
+#[must_use]
+pub fn get_flex_balance(
+    sessions: &[Session],
+    config: &Config,
+    days_off: &[(NaiveDate, DayOff)],
+    date: NaiveDate,
+    now: NaiveDateTime,
+) -> Option<TimeDelta>
+{
+    let balance_start = config.balance_start?;
+    let last_date = date.min(now.date().pred_opt()?);
+
+    Some(get_flex_time(sessions, config, days_off, balance_start, last_date, now))
+}
+
+/// The week report as plain text with the columns of the week report screen, sized to the longest tag. `comparison`
+/// holds the totals of each tag in the week starting on `week_start` and in the week before, `flex_time` the overtime of
+/// the week and the balance at its end.
+#[must_use]
+pub fn get_week_report_text(
+    week_start: NaiveDate,
+    comparison: Vec<(String, TimeDelta, TimeDelta)>,
+    flex_time: Option<(TimeDelta, TimeDelta)>,
+) -> String
+{
+    let current_total: TimeDelta = comparison.iter().map(|(_, current, _)| *current).sum();
+    let previous_total: TimeDelta = comparison.iter().map(|(_, _, previous)| *previous).sum();
+
+    let label_width = comparison.iter().map(|(tag, ..)| get_display_width(tag)).max().unwrap_or(0).max(5);
+    let row_format = |tag: &str, current: &str, previous: &str, delta: &str, percentage: &str| {
+        format!("{} {:>10} {:>10} {:>12} {:>8}", pad_to_width(tag, label_width), current, previous, delta, percentage)
+    };
+
+    let title = format!("Week of {} vs previous week", week_start.format("%d %b %Y"));
+    let header = row_format("TAG", "THIS WEEK", "LAST WEEK", "DELTA", "CHANGE");
+
+    let rows =
+        comparison.into_iter().chain([(String::from("TOTAL"), current_total, previous_total)]).map(|(tag, current, previous)| {
+            row_format(
+                &tag,
+                &format_duration_short(current),
+                &format_duration_short(previous),
+                &format_duration_delta(current - previous),
+                &format_percentage_change(current, previous),
+            )
+            .trim_end()
+            .to_string()
+        });
+
+    let flex_time = flex_time.map(|(week_flex_time, balance)| get_flex_time_text(week_flex_time, balance));
+
+    [title, header].into_iter().chain(rows).chain(flex_time).collect::<Vec<String>>().join("\n")
+}
+
+/// The overtime of a week and the balance at its end, below the week report.
+#[must_use]
+pub fn get_flex_time_text(week_flex_time: TimeDelta, balance: TimeDelta) -> String
+{
+    format!("Overtime {} this week, balance {}", format_duration_delta(week_flex_time), format_duration_delta(balance))
+}
+
+/// `delta` in the short duration format with a sign in front, `+` for none.
+#[must_use]
+pub fn format_duration_delta(delta: TimeDelta) -> String
+{
+    let sign = if delta < TimeDelta::zero()
+    {
+        '-'
+    }
+    else
+    {
+        '+'
+    };
+
+    format!("{}{}", sign, format_duration_short(delta.abs()))
+}
+
+/// How much `current` changed from `previous` in percent, "new" for time where there was none before.
+#[must_use]
+pub fn format_percentage_change(current: TimeDelta, previous: TimeDelta) -> String
+{
+    if previous.is_zero()
+    {
+        return if current.is_zero()
+        {
+            String::from("-")
+        }
+        else
+        {
+            String::from("new")
+        };
+    }
+
+    let change = (current - previous).num_seconds() * 100 / previous.num_seconds();
+
+    format!("{:+}%", change)
+}
+
+/// `duration` with a sign in front, `+` for none.
+#[must_use]
+pub fn format_signed_duration(duration: TimeDelta) -> String
+{
+    let sign = if duration < TimeDelta::zero()
+    {
+        '-'
+    }
+    else
+    {
+        '+'
+    };
+
+    format!("{}{}", sign, format_duration(duration.abs()))
+}
+
+/// The latest `limit` sessions with `tag` that started between `from` and `to`, newest first.
+#[must_use]
+pub fn get_log_sessions<'a>(
+    sessions: &'a [Session],
+    tag: Option<&str>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    limit: usize,
+) -> Vec<&'a Session>
+{
+    let mut sessions: Vec<&Session> = sessions
+        .iter()
+        .filter(|session| tag.is_none_or(|tag| session.tag == tag))
+        .filter(|session| from.is_none_or(|from| session.start.date() >= from) && to.is_none_or(|to| session.start.date() <= to))
+        .collect();
+
+    sessions.sort_by_key(|session| cmp::Reverse(session.start));
+    sessions.truncate(limit);
+
+    sessions
+}
+
+/// The sessions as a JSON array of the session objects `--serve` answers with, one line.
+#[must_use]
+pub fn format_log_json(sessions: &[&Session], now: NaiveDateTime) -> String
+{
+    format!("{}\n", JsonValue::Array(sessions.iter().map(|session| session_to_json(session, now)).collect()))
+}
+
+/// A line per session with the id, which a running session does not have yet, the day, the times, the duration, the
+/// tag and the description.
+#[must_use]
+pub fn format_log(sessions: &[&Session], now: NaiveDateTime) -> String
+{
+    let id_width = sessions.iter().map(|session| session.id.to_string().len()).max().unwrap_or(0);
+
+    sessions
+        .iter()
+        .map(|session| {
+            let id = if session.is_running()
+            {
+                String::from("*")
+            }
+            else
+            {
+                session.id.to_string()
+            };
+            let end = session.end.map_or(String::from("now  "), |end| end.format("%H:%M").to_string());
+            let duration = format_duration_short(session.end.unwrap_or(now) - session.start);
+
+            format!(
+                "{:>id_width$} {} {}-{} {:>7} {} {}",
+                id,
+                session.start.format(REPORT_DATE_FORMAT),
+                session.start.format("%H:%M"),
+                end,
+                duration,
+                session.tag,
+                session.description
+            )
+        })
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// `from` and `to` as `[from, to)` times, both days included.
+fn get_bounds(from: NaiveDate, to: NaiveDate) -> (NaiveDateTime, NaiveDateTime)
+{
+    (from.and_time(NaiveTime::MIN), to.succ_opt().unwrap_or(to).and_time(NaiveTime::MIN))
+}
+
+/// The rows as a text table, CSV with a header, a JSON object like the `/report` answer of `--serve` or a Markdown table.
+/// The table formats end with the total and the overtime `balance` when there is one, which CSV leaves out as it is not
+/// a row.
+#[must_use]
+pub fn format_report(
+    rows: &[(String, TimeDelta)],
+    from: NaiveDate,
+    to: NaiveDate,
+    grouping: ReportGrouping,
+    format: ReportFormat,
+    balance: Option<TimeDelta>,
+) -> String
+{
+    let total: TimeDelta = rows.iter().map(|(_, duration)| *duration).sum();
+    let name_header = grouping.get_name().to_uppercase();
+
+    match format
+    {
+        ReportFormat::Table =>
+        {
+            let balance_row = balance.map(|balance| (String::from("BALANCE"), format_signed_duration(balance)));
+            let time_rows = rows
+                .iter()
+                .chain([&(String::from("TOTAL"), total)])
+                .map(|(name, duration)| (name.clone(), format_duration(*duration)))
+                .chain(balance_row)
+                .collect::<Vec<(String, String)>>();
+
+            let name_width =
+                time_rows.iter().map(|(name, _)| get_display_width(name)).chain([name_header.len()]).max().unwrap_or(0);
+            let time_width = time_rows.iter().map(|(_, time)| time.len()).max().unwrap_or(0);
+            let mut lines = vec![format!("{}  {:>time_width$}", pad_to_width(&name_header, name_width), "TIME")];
+
+            for (name, time) in time_rows
+            {
+                lines.push(format!("{}  {:>time_width$}", pad_to_width(&name, name_width), time));
+            }
+
+            lines.join("\n") + "\n"
+        }
+        ReportFormat::Csv =>
+        {
+            let mut lines = vec![format!("{},duration,seconds", grouping.get_name())];
+
+            for (name, duration) in rows
+            {
+                lines.push(format!("{},{},{}", quote_csv_field(name), format_duration(*duration), duration.num_seconds()));
+            }
+
+            lines.join("\n") + "\n"
+        }
+        ReportFormat::Json =>
+        {
+            let rows = rows
+                .iter()
+                .map(|(name, duration)| {
+                    JsonValue::Object(vec![
+                        (grouping.get_name().to_string(), JsonValue::String(name.clone())),
+                        (String::from("seconds"), JsonValue::Integer(duration.num_seconds())),
+                    ])
+                })
+                .collect();
+
+            let mut members = vec![
+                (String::from("from"), JsonValue::String(from.format(REPORT_DATE_FORMAT).to_string())),
+                (String::from("to"), JsonValue::String(to.format(REPORT_DATE_FORMAT).to_string())),
+                (String::from("group_by"), JsonValue::String(grouping.get_name().to_string())),
+                (String::from("total_seconds"), JsonValue::Integer(total.num_seconds())),
+            ];
+            members.extend(balance.map(|balance| (String::from("balance_seconds"), JsonValue::Integer(balance.num_seconds()))));
+            members.push((String::from("rows"), JsonValue::Array(rows)));
+
+            let report = JsonValue::Object(members);
+
+            format!("{report}\n")
+        }
+        ReportFormat::Markdown =>
+        {
+            let mut lines = vec![format!("| {} | Time |", capitalize(grouping.get_name())), String::from("| --- | ---: |")];
+
+            for (name, duration) in rows
+            {
+                lines.push(format!("| {} | {} |", name.replace('|', "\\|"), format_duration(*duration)));
+            }
+
+            lines.push(format!("| **Total** | **{}** |", format_duration(total)));
+            lines.extend(balance.map(|balance| format!("| **Balance** | **{}** |", format_signed_duration(balance))));
+
+            lines.join("\n") + "\n"
+        }
+    }
+}
+
+fn capitalize(text: &str) -> String
+{
+    let mut chars = text.chars();
+
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+/// Quotes fields with the separator, quotes or line breaks in them.
+fn quote_csv_field(field: &str) -> String
+{
+    if field.contains([',', '"', '\n', '\r'])
+    {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    }
+    else
+    {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn week_report_text_compares_each_tag_with_the_week_before()
+    {
+        let week_start = NaiveDate::from_ymd_opt(2026, 1, 12).expect("Invalid test date.");
+        let comparison = vec![
+            (String::from("code"), TimeDelta::minutes(330), TimeDelta::hours(4)),
+            (String::from("meeting"), TimeDelta::hours(1), TimeDelta::zero()),
+        ];
+
+        let text = get_week_report_text(week_start, comparison, Some((TimeDelta::minutes(90), TimeDelta::minutes(-45))));
+
+        assert_eq!(
+            text.lines().collect::<Vec<&str>>(),
+            [
+                "Week of 12 Jan 2026 vs previous week",
+                "TAG      THIS WEEK  LAST WEEK        DELTA   CHANGE",
+                "code        5h 30m     4h 00m      +1h 30m     +37%",
+                "meeting     1h 00m     0h 00m      +1h 00m      new",
+                "TOTAL       6h 30m     4h 00m      +2h 30m     +62%",
+                "Overtime +1h 30m this week, balance -0h 45m",
+            ]
+        );
+    }
+
+    #[test]
+    fn week_report_text_leaves_out_overtime_without_a_balance()
+    {
+        let week_start = NaiveDate::from_ymd_opt(2026, 1, 12).expect("Invalid test date.");
+
+        let text = get_week_report_text(week_start, Vec::new(), None);
+
+        assert_eq!(text.lines().last(), Some("TOTAL     0h 00m     0h 00m      +0h 00m        -"));
+    }
+
+    #[test]
+    fn changes_are_signed()
+    {
+        assert_eq!(format_duration_delta(TimeDelta::minutes(-75)), "-1h 15m");
+        assert_eq!(format_duration_delta(TimeDelta::zero()), "+0h 00m");
+        assert_eq!(format_percentage_change(TimeDelta::hours(1), TimeDelta::hours(2)), "-50%");
+        assert_eq!(format_percentage_change(TimeDelta::zero(), TimeDelta::zero()), "-");
+    }
+}
@@ -0,0 +1,505 @@
+use crate::database_handler::{DatabaseHandler, DATE_FORMAT, VALUE_SEPARATOR};
+use crate::exit_code;
+use crate::json::{parse_json, JsonValue};
+use crate::session::Session;
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeDelta, Timelike};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::time::Duration;
+
+pub const DEFAULT_PORT: u16 = 7431;
+
+const MAX_BODY_LENGTH: usize = 64 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const QUERY_DATE_FORMAT: &str = "%Y-%m-%d";
+pub const JSON_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+struct Request
+{
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    host: Option<String>,
+    content_type: Option<String>,
+    body: String,
+}
+
+struct Response
+{
+    status: u16,
+    body: JsonValue,
+}
+
+impl Response
+{
+    fn ok(body: JsonValue) -> Self
+    {
+        Response {
+            status: 200,
+            body,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self
+    {
+        Response {
+            status,
+            body: JsonValue::Object(vec![(String::from("error"), JsonValue::String(message.to_string()))]),
+        }
+    }
+}
+
+/// Answers the HTTP API on localhost until the process is killed. The running session lives in the server, marked as
+/// running on this machine so it survives a restart and other instances know about it.
+pub fn run_server(database_handler: &DatabaseHandler, port: u16)
+{
+    let listener = match TcpListener::bind(("127.0.0.1", port))
+    {
+        Ok(listener) => listener,
+        Err(error) =>
+        {
+            eprintln!("Can't listen on port {port}: {error}");
+            std::process::exit(exit_code::FAILURE);
+        }
+    };
+
+    if database_handler.is_read_only()
+    {
+        eprintln!("The database is open in another instance, only reading is possible.");
+    }
+
+    println!("Serving the {} workspace on http://127.0.0.1:{port}", database_handler.get_workspace());
+
+    let mut running_session = database_handler.import_own_running_session(VALUE_SEPARATOR, DATE_FORMAT);
+
+    for stream in listener.incoming().filter_map(Result::ok)
+    {
+        let _ = stream.set_read_timeout(Some(REQUEST_TIMEOUT));
+
+        answer_request(database_handler, &mut running_session, stream, Some(port));
+    }
+}
+
+/// Reads one request from `stream` and writes the response. With a `port` the request has to name this machine as
+/// host, streams that never left it, like the daemon's socket, leave it out.
+pub fn answer_request(
+    database_handler: &DatabaseHandler,
+    running_session: &mut Option<Session>,
+    mut stream: impl Read + Write,
+    port: Option<u16>,
+)
+{
+    let response = match read_request(&mut stream)
+    {
+        Some(request) if port.is_some_and(|port| !is_local_host(request.host.as_deref(), port)) =>
+        {
+            Response::error(403, "UNKNOWN HOST")
+        }
+        Some(request) => handle_request(database_handler, running_session, &request),
+        None => Response::error(400, "INVALID REQUEST"),
+    };
+
+    write_response(stream, &response);
+}
+
+/// Requests for any other host come from a web page that had its domain pointed at this machine.
+fn is_local_host(host: Option<&str>, port: u16) -> bool
+{
+    host.is_some_and(|host| [format!("127.0.0.1:{port}"), format!("localhost:{port}")].iter().any(|local_host| local_host == host))
+}
+
+fn handle_request(database_handler: &DatabaseHandler, running_session: &mut Option<Session>, request: &Request) -> Response
+{
+    if request.method == "POST"
+    {
+        // Web pages can't send JSON to another site without asking first, which this server never allows.
+        if request.content_type.as_deref().is_none_or(|content_type| !content_type.starts_with("application/json"))
+        {
+            return Response::error(415, "CONTENT TYPE MUST BE APPLICATION/JSON");
+        }
+
+        if database_handler.is_read_only()
+        {
+            return Response::error(423, "DATABASE LOCKED");
+        }
+    }
+
+    match (request.method.as_str(), request.path.as_str())
+    {
+        ("GET", "/status") => get_status(database_handler, running_session.as_ref()),
+        ("GET", "/sessions") => get_sessions(database_handler, running_session.as_ref(), request),
+        ("GET", "/report") => get_report(database_handler, running_session.as_ref(), request),
+        ("GET", "/tags") => Response::ok(JsonValue::Array(
+            database_handler.import_tags().unwrap_or_default().into_iter().map(JsonValue::String).collect(),
+        )),
+        ("POST", "/start") => start_session(database_handler, running_session, request),
+        ("POST", "/stop") => stop_session(database_handler, running_session, Some(request)),
+        (_, "/status" | "/sessions" | "/report" | "/tags" | "/start" | "/stop") => Response::error(405, "METHOD NOT ALLOWED"),
+        _ => Response::error(404, "NOT FOUND"),
+    }
+}
+
+fn get_status(database_handler: &DatabaseHandler, running_session: Option<&Session>) -> Response
+{
+    Response::ok(get_status_json(database_handler, running_session))
+}
+
+/// The workspace, its running session and today's total, also printed by `status --format json`.
+pub fn get_status_json(database_handler: &DatabaseHandler, running_session: Option<&Session>) -> JsonValue
+{
+    let now = get_current_time();
+
+    JsonValue::Object(vec![
+        (String::from("workspace"), JsonValue::String(database_handler.get_workspace().to_string())),
+        (String::from("running"), running_session.map_or(JsonValue::Null, |session| session_to_json(session, now))),
+        (String::from("today_seconds"), JsonValue::Integer(get_today_total(database_handler, running_session, now).num_seconds())),
+        (String::from("read_only"), JsonValue::Bool(database_handler.is_read_only())),
+    ])
+}
+
+fn get_today_total(database_handler: &DatabaseHandler, running_session: Option<&Session>, now: NaiveDateTime) -> TimeDelta
+{
+    let today = now.date();
+    let sessions = import_sessions_since(database_handler, running_session, today);
+
+    get_total_within(&sessions, today, today, now)
+}
+
+/// The sessions started within `from` and `to`, both days included, today's by default.
+fn get_sessions(database_handler: &DatabaseHandler, running_session: Option<&Session>, request: &Request) -> Response
+{
+    let now = get_current_time();
+    let Some((from, to)) = get_date_range(request, now.date())
+    else
+    {
+        return Response::error(400, "INVALID DATE RANGE");
+    };
+
+    let sessions = import_sessions_since(database_handler, running_session, from)
+        .iter()
+        .filter(|session| (from..=to).contains(&session.start.date()))
+        .map(|session| session_to_json(session, now))
+        .collect();
+
+    Response::ok(JsonValue::Array(sessions))
+}
+
+/// Tracked time per tag within `from` and `to`, both days included, today's by default. Tags with the most time come
+/// first.
+fn get_report(database_handler: &DatabaseHandler, running_session: Option<&Session>, request: &Request) -> Response
+{
+    let now = get_current_time();
+    let Some((from, to)) = get_date_range(request, now.date())
+    else
+    {
+        return Response::error(400, "INVALID DATE RANGE");
+    };
+
+    let sessions = import_sessions_since(database_handler, running_session, from);
+    let mut tag_totals: Vec<(String, TimeDelta)> = Vec::new();
+
+    for session in &sessions
+    {
+        let duration = get_total_within(std::slice::from_ref(session), from, to, now);
+
+        if duration.is_zero()
+        {
+            continue;
+        }
+
+        match tag_totals.iter_mut().find(|(tag, _)| *tag == session.tag)
+        {
+            Some((_, total)) => *total += duration,
+            None => tag_totals.push((session.tag.clone(), duration)),
+        }
+    }
+
+    tag_totals.sort_by(|(tag, total), (other_tag, other_total)| other_total.cmp(total).then_with(|| tag.cmp(other_tag)));
+
+    let tags = tag_totals
+        .iter()
+        .map(|(tag, total)| {
+            JsonValue::Object(vec![
+                (String::from("tag"), JsonValue::String(tag.clone())),
+                (String::from("seconds"), JsonValue::Integer(total.num_seconds())),
+            ])
+        })
+        .collect();
+
+    Response::ok(JsonValue::Object(vec![
+        (String::from("from"), JsonValue::String(from.format(QUERY_DATE_FORMAT).to_string())),
+        (String::from("to"), JsonValue::String(to.format(QUERY_DATE_FORMAT).to_string())),
+        (String::from("total_seconds"), JsonValue::Integer(get_total_within(&sessions, from, to, now).num_seconds())),
+        (String::from("tags"), JsonValue::Array(tags)),
+    ]))
+}
+
+/// Starts a session with the description and tag of the request body, ending the running one first. Without a tag the
+/// last used one is taken, new tags are created. A `start` in the past backdates it.
+fn start_session(database_handler: &DatabaseHandler, running_session: &mut Option<Session>, request: &Request) -> Response
+{
+    let Some(body) = parse_json(&request.body)
+    else
+    {
+        return Response::error(400, "INVALID JSON");
+    };
+
+    // The separator would split the stored line.
+    let description = body.get("description").and_then(JsonValue::as_str).unwrap_or_default().trim().replace(VALUE_SEPARATOR, ",");
+    let tag = body
+        .get("tag")
+        .and_then(JsonValue::as_str)
+        .map(|tag| tag.trim().replace(VALUE_SEPARATOR, ","))
+        .filter(|tag| !tag.is_empty())
+        .or_else(|| database_handler.import_last_used_tag());
+
+    if description.is_empty()
+    {
+        return Response::error(400, "DESCRIPTION MISSING");
+    }
+
+    let Some(tag) = tag
+    else
+    {
+        return Response::error(400, "TAG MISSING");
+    };
+
+    let now = get_current_time();
+    let start = match get_time_member(&body, "start")
+    {
+        Ok(start) if start.is_none_or(|start| start <= now) => start.unwrap_or(now),
+        _ => return Response::error(400, "INVALID START"),
+    };
+
+    if running_session.is_some()
+    {
+        stop_session(database_handler, running_session, None);
+    }
+
+    if !database_handler.import_tags().unwrap_or_default().contains(&tag)
+    {
+        database_handler.export_tag(&tag).expect("Failed to export tag.");
+    }
+
+    database_handler.export_last_used_tag(&tag);
+
+    let session = Session::from(&description, &tag, start, None);
+
+    database_handler.export_running_session(Some(&session), VALUE_SEPARATOR, DATE_FORMAT);
+
+    let response = Response {
+        status: 201,
+        body: session_to_json(&session, now),
+    };
+
+    *running_session = Some(session);
+
+    response
+}
+
+/// Ends the running session, at the `end` of the request body if there is one, and stores it.
+fn stop_session(database_handler: &DatabaseHandler, running_session: &mut Option<Session>, request: Option<&Request>) -> Response
+{
+    let now = get_current_time();
+    // The body is optional here.
+    let body = request.map(|request| request.body.trim()).filter(|body| !body.is_empty());
+
+    let end = match body.map(|body| parse_json(body).ok_or(()).and_then(|body| get_time_member(&body, "end")))
+    {
+        None | Some(Ok(None)) => now,
+        Some(Ok(Some(end))) => end,
+        Some(Err(())) => return Response::error(400, "INVALID END"),
+    };
+
+    let Some(mut session) = running_session.take()
+    else
+    {
+        return Response::error(409, "NO SESSION RUNNING");
+    };
+
+    session.end = Some(end.min(now).max(session.start));
+    session.id = get_next_session_id(database_handler);
+
+    database_handler
+        .export_session(&session.construct_db_string(VALUE_SEPARATOR, DATE_FORMAT), VALUE_SEPARATOR, DATE_FORMAT)
+        .expect("Error exporting session.");
+    database_handler.export_running_session(None, VALUE_SEPARATOR, DATE_FORMAT);
+
+    Response::ok(session_to_json(&session, now))
+}
+
+/// A time member of a request body, `None` if it is missing or null.
+fn get_time_member(body: &JsonValue, name: &str) -> Result<Option<NaiveDateTime>, ()>
+{
+    match body.get(name)
+    {
+        None | Some(JsonValue::Null) => Ok(None),
+        Some(value) => value.as_str().and_then(|time| NaiveDateTime::parse_from_str(time, JSON_DATE_FORMAT).ok()).map(Some).ok_or(()),
+    }
+}
+
+fn get_next_session_id(database_handler: &DatabaseHandler) -> u64
+{
+    let (sessions, _) = database_handler.import_sessions(VALUE_SEPARATOR, DATE_FORMAT);
+    let loaded_max_session_id = sessions.unwrap_or_default().iter().map(|session| session.id).max().unwrap_or(0);
+
+    loaded_max_session_id
+        .max(database_handler.get_unloaded_max_session_id(VALUE_SEPARATOR, DATE_FORMAT))
+        .max(database_handler.get_last_session_id(VALUE_SEPARATOR))
+        + 1
+}
+
+/// The stored sessions of the months from `date` on, read again on every request as the app may have changed them,
+/// followed by the running one.
+fn import_sessions_since(database_handler: &DatabaseHandler, running_session: Option<&Session>, date: NaiveDate) -> Vec<Session>
+{
+    database_handler.load_sessions_since(date);
+
+    let (sessions, _) = database_handler.import_sessions(VALUE_SEPARATOR, DATE_FORMAT);
+    let mut sessions = sessions.unwrap_or_default();
+    sessions.extend(running_session.cloned());
+
+    sessions
+}
+
+fn get_total_within(sessions: &[Session], from: NaiveDate, to: NaiveDate, now: NaiveDateTime) -> TimeDelta
+{
+    let from = from.and_hms_opt(0, 0, 0).unwrap_or_default();
+    let to = to.succ_opt().unwrap_or(to).and_hms_opt(0, 0, 0).unwrap_or_default();
+
+    sessions.iter().map(|session| session.get_duration_within(from, to, now)).sum()
+}
+
+/// The `from` and `to` days of the query, each defaulting to `today`.
+fn get_date_range(request: &Request, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)>
+{
+    let [from, to] = ["from", "to"].map(|name| {
+        request.query.iter().find(|(key, _)| key == name).map(|(_, value)| NaiveDate::parse_from_str(value, QUERY_DATE_FORMAT))
+    });
+
+    let from = from.transpose().ok()?.unwrap_or(today);
+    let to = to.transpose().ok()?.unwrap_or(today.max(from));
+
+    (from <= to).then_some((from, to))
+}
+
+/// The running session of a `/status` answer.
+pub fn session_from_json(value: &JsonValue) -> Option<Session>
+{
+    let start = NaiveDateTime::parse_from_str(value.get("start")?.as_str()?, JSON_DATE_FORMAT).ok()?;
+
+    let mut session = Session::from(value.get("description")?.as_str()?, value.get("tag")?.as_str()?, start, None);
+    session.notes = value.get("notes").and_then(JsonValue::as_str).unwrap_or_default().to_string();
+
+    Some(session)
+}
+
+#[must_use]
+pub fn session_to_json(session: &Session, now: NaiveDateTime) -> JsonValue
+{
+    JsonValue::Object(vec![
+        (
+            String::from("id"),
+            if session.is_running()
+            {
+                JsonValue::Null
+            }
+            else
+            {
+                JsonValue::Integer(session.id.cast_signed())
+            },
+        ),
+        (String::from("description"), JsonValue::String(session.description.clone())),
+        (String::from("tag"), JsonValue::String(session.tag.clone())),
+        (String::from("notes"), JsonValue::String(session.notes.clone())),
+        (String::from("start"), JsonValue::String(session.start.format(JSON_DATE_FORMAT).to_string())),
+        (String::from("end"), session.end.map_or(JsonValue::Null, |end| JsonValue::String(end.format(JSON_DATE_FORMAT).to_string()))),
+        (String::from("duration_seconds"), JsonValue::Integer((session.end.unwrap_or(now) - session.start).num_seconds())),
+    ])
+}
+
+fn get_current_time() -> NaiveDateTime
+{
+    let now = Local::now().naive_local();
+
+    now.with_nanosecond(0).unwrap_or(now)
+}
+
+fn read_request(stream: impl Read) -> Option<Request>
+{
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut host = None;
+    let mut content_type = None;
+    let mut content_length = 0;
+
+    loop
+    {
+        let mut header = String::new();
+        reader.read_line(&mut header).ok()?;
+
+        let Some((name, value)) = header.trim_end().split_once(':')
+        else
+        {
+            break;
+        };
+
+        let value = value.trim().to_string();
+
+        match name.to_lowercase().as_str()
+        {
+            "host" => host = Some(value),
+            "content-type" => content_type = Some(value.to_lowercase()),
+            "content-length" => content_length = value.parse::<usize>().ok().filter(|length| *length <= MAX_BODY_LENGTH)?,
+            _ => (),
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(Request {
+        method,
+        path: path.trim_end_matches('/').to_string(),
+        query: query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+        host,
+        content_type,
+        body: String::from_utf8(body).ok()?,
+    })
+}
+
+fn write_response(mut stream: impl Write, response: &Response)
+{
+    let body = response.body.to_string();
+    let reason = match response.status
+    {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        415 => "Unsupported Media Type",
+        423 => "Locked",
+        _ => "",
+    };
+
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        response.status,
+        body.len()
+    );
+}
@@ -1,20 +1,27 @@
 use crate::app_state::SessionField;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, TimeDelta};
 use std::ops::Add;
 
 pub struct Session
 {
+    pub id: u64,
     pub description: String,
     pub tag: String,
     pub start: NaiveDateTime,
     pub end: Option<NaiveDateTime>,
+    /// Free text of any number of lines, empty for sessions without notes.
+    pub notes: String,
 }
 
 impl Clone for Session
 {
     fn clone(&self) -> Self
     {
-        Session::from(&self.description, &self.tag, self.start, self.end)
+        let mut session = Session::from(&self.description, &self.tag, self.start, self.end);
+        session.id = self.id;
+        session.notes.clone_from(&self.notes);
+
+        session
     }
 }
 
@@ -22,27 +29,69 @@ impl PartialEq for Session
 {
     fn eq(&self, other: &Self) -> bool
     {
-        self.description == other.description && self.tag == other.tag && self.start == other.start && self.end == other.end
+        self.id == other.id
+            && self.description == other.description
+            && self.tag == other.tag
+            && self.start == other.start
+            && self.end == other.end
+            && self.notes == other.notes
     }
 }
 
 impl Session
 {
+    #[must_use]
     pub fn from(description: &str, tag: &str, start: NaiveDateTime, end: Option<NaiveDateTime>) -> Session
     {
         Session {
+            id: 0,
             description: description.to_string(),
             tag: tag.to_string(),
             start,
             end,
+            notes: String::new(),
         }
     }
 
+    #[must_use]
     pub fn is_running(&self) -> bool
     {
         self.end.is_none()
     }
 
+    #[must_use]
+    pub fn has_valid_times(&self) -> bool
+    {
+        self.end.is_none_or(|end| end >= self.start)
+    }
+
+    /// A running session is treated as lasting until `now`.
+    #[must_use]
+    pub fn overlaps(&self, other: &Session, now: NaiveDateTime) -> bool
+    {
+        let end = self.end.unwrap_or(now);
+        let other_end = other.end.unwrap_or(now);
+
+        self.start < other_end && other.start < end
+    }
+
+    /// Time the session spent inside `[from, to)`, with a running session lasting until `now`.
+    #[must_use]
+    pub fn get_duration_within(&self, from: NaiveDateTime, to: NaiveDateTime, now: NaiveDateTime) -> TimeDelta
+    {
+        let start = self.start.max(from);
+        let end = self.end.unwrap_or(now).min(to);
+
+        if end > start
+        {
+            end - start
+        }
+        else
+        {
+            TimeDelta::zero()
+        }
+    }
+
     // pub fn get_field_as_string(&self, field: &SessionField) -> String
     // {
     //     match field
@@ -59,16 +108,26 @@ impl Session
     //     }
     // }
 
+    /// The part of the tag before the first ':', tags without one have no project.
+    #[must_use]
+    pub fn get_project(&self) -> Option<&str>
+    {
+        self.tag.split_once(':').map(|(project, _)| project)
+    }
+
+    #[must_use]
     pub fn get_date_string(&self) -> String
     {
         format!("{}", self.start.format("%d %b %y"))
     }
 
+    #[must_use]
     pub fn get_start_time_string(&self) -> String
     {
         format!("{}", self.start.format("%H:%M:%S"))
     }
 
+    #[must_use]
     pub fn get_end_time_string(&self) -> Option<String>
     {
         if let Some(end) = self.end
@@ -79,25 +138,30 @@ impl Session
         None
     }
 
+    #[must_use]
     pub fn get_duration_string(&self) -> Option<String>
     {
-        if let Some(end) = self.end
-        {
-            let duration = end - self.start;
-
-            let secs_per_minute: i64 = 60;
-            let secs_per_hour: i64 = 3600;
-
-            let hours = duration.num_hours();
-            let minutes = duration.num_minutes() - hours * secs_per_minute;
-            let seconds = duration.num_seconds() - hours * secs_per_hour - minutes * secs_per_minute;
+        self.end.map(|end| format_duration(end - self.start))
+    }
 
-            return Some(format!("{:02}:{:02}:{:02}", hours, minutes, seconds));
-        }
+    /// One line of plain text for pasting elsewhere, e.g. `09:00-10:30  1h 30m  work  Standup`, with the notes
+    /// indented below it. A running session runs until `now`.
+    #[must_use]
+    pub fn get_summary_text(&self, now: NaiveDateTime) -> String
+    {
+        let end = self.end.map_or(String::from("now"), |end| end.format("%H:%M").to_string());
+        let duration = format_duration_short(self.end.unwrap_or(now) - self.start);
+        let line = format!("{}-{}  {}  {}  {}", self.start.format("%H:%M"), end, duration, self.tag, self.description);
 
-        None
+        self.notes.lines().fold(line, |text, note_line| format!("{text}\n    {note_line}"))
     }
 
+    /// The session as a line of the sessions file.
+    ///
+    /// # Panics
+    ///
+    /// If the session is still running, as running sessions are only stored once they end.
+    #[must_use]
     pub fn construct_db_string(&self, separator: char, format: &str) -> String
     {
         let format_split = format.split(' ').collect::<Vec<&str>>();
@@ -110,9 +174,23 @@ impl Session
         let start = format!("{}", self.start.format(time_format));
 
         let end = self.end.expect("Cannot export ongoing session.");
+        let end_date = format!("{}", end.format(date_format));
         let end = format!("{}", end.format(time_format));
 
-        format!("{date}{separator}{description}{separator}{tag}{separator}{start}{separator}{end}{separator}")
+        let id = self.id;
+
+        let mut db_string = format!(
+            "{date}{separator}{description}{separator}{tag}{separator}{start}{separator}{end}{separator}{end_date}{separator}{id}{separator}"
+        );
+
+        // Lines of sessions without notes stay as older versions wrote them.
+        if !self.notes.is_empty()
+        {
+            db_string.push_str(&escape_notes(&self.notes, separator));
+            db_string.push(separator);
+        }
+
+        db_string
     }
 
     pub fn set_field(&mut self, field: &SessionField)
@@ -160,3 +238,89 @@ impl Session
         }
     }
 }
+
+/// Notes as one value of a session line: backslashes, line breaks and the separator become `\\`, `\n` and `\s`.
+#[must_use]
+pub fn escape_notes(notes: &str, separator: char) -> String
+{
+    let mut escaped = String::new();
+
+    for character in notes.chars()
+    {
+        match character
+        {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => (),
+            _ if character == separator => escaped.push_str("\\s"),
+            _ => escaped.push(character),
+        }
+    }
+
+    escaped
+}
+
+/// Reverses `escape_notes`. An unknown escape is kept as it is.
+#[must_use]
+pub fn unescape_notes(escaped: &str, separator: char) -> String
+{
+    let mut notes = String::new();
+    let mut characters = escaped.chars();
+
+    while let Some(character) = characters.next()
+    {
+        if character != '\\'
+        {
+            notes.push(character);
+            continue;
+        }
+
+        match characters.next()
+        {
+            Some('\\') => notes.push('\\'),
+            Some('n') => notes.push('\n'),
+            Some('s') => notes.push(separator),
+            Some(other) =>
+            {
+                notes.push('\\');
+                notes.push(other);
+            }
+            None => notes.push('\\'),
+        }
+    }
+
+    notes
+}
+
+#[must_use]
+pub fn format_duration(duration: TimeDelta) -> String
+{
+    let secs_per_minute: i64 = 60;
+    let secs_per_hour: i64 = 3600;
+
+    let hours = duration.num_hours();
+    let minutes = duration.num_minutes() - hours * secs_per_minute;
+    let seconds = duration.num_seconds() - hours * secs_per_hour - minutes * secs_per_minute;
+
+    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+}
+
+/// Formats a duration as `HH:MM`, for lists that hide seconds.
+#[must_use]
+pub fn format_duration_without_seconds(duration: TimeDelta) -> String
+{
+    let hours = duration.num_hours();
+    let minutes = duration.num_minutes() - hours * 60;
+
+    format!("{:02}:{:02}", hours, minutes)
+}
+
+/// Compact form for summaries, e.g. `6h 12m`.
+#[must_use]
+pub fn format_duration_short(duration: TimeDelta) -> String
+{
+    let hours = duration.num_hours();
+    let minutes = duration.num_minutes() - hours * 60;
+
+    format!("{}h {:02}m", hours, minutes)
+}
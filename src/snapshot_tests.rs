@@ -0,0 +1,213 @@
+//! Screens drawn into a buffer and compared with the text in tests/snapshots. Run with `UPDATE_SNAPSHOTS=1` to write
+//! the snapshots again after changing what a screen looks like.
+
+use super::*;
+use std::fs;
+use std::path::PathBuf;
+use time_tracker_core::clock::MockClock;
+use time_tracker_core::config::{ClockFormat, DateOrder};
+use time_tracker_core::database_handler::{DATE_FORMAT, VALUE_SEPARATOR};
+use time_tracker_core::tui::io::BufferBackend;
+
+const SCREEN_WIDTH: u16 = 100;
+const SCREEN_HEIGHT: u16 = 30;
+/// What the clock of every screen shows.
+const NOW: &str = "14-01-2026 10:30:00";
+
+/// The app on an empty workspace that only lives in memory.
+fn open_app(workspace: &str, screen_size: Vector2) -> (AppManager, BufferBackend)
+{
+    open_app_with_sessions(workspace, screen_size, &[])
+}
+
+/// The app on an in-memory workspace holding `session_lines` as stored in the sessions file.
+fn open_app_with_sessions(workspace: &str, screen_size: Vector2, session_lines: &[&str]) -> (AppManager, BufferBackend)
+{
+    let database_handler = DatabaseHandler::new_ephemeral(workspace);
+
+    for session_line in session_lines
+    {
+        database_handler.export_session(session_line, VALUE_SEPARATOR, DATE_FORMAT).expect("Storing session failed.");
+    }
+
+    let backend = BufferBackend::new(screen_size);
+    let clock = MockClock::new(NaiveDateTime::parse_from_str(NOW, "%d-%m-%Y %H:%M:%S").expect("Invalid snapshot time."));
+    let app_manager = AppManager::with_parts(database_handler, None, Out::with_backend(Box::new(backend.clone())), Box::new(clock));
+
+    (app_manager, backend)
+}
+
+fn render_screen(app_manager: &mut AppManager, backend: &BufferBackend) -> String
+{
+    app_manager.renderer.clear_screen();
+    render(app_manager);
+    app_manager.renderer.check_color_stacks();
+
+    backend.get_text()
+}
+
+fn assert_snapshot(name: &str, screen: &str)
+{
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("snapshots").join(format!("{name}.txt"));
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some()
+    {
+        fs::create_dir_all(path.parent().expect("Snapshot path has no folder.")).expect("Creating snapshot folder failed.");
+        fs::write(&path, screen).expect("Writing snapshot failed.");
+        return;
+    }
+
+    let Ok(snapshot) = fs::read_to_string(&path)
+    else
+    {
+        panic!("No snapshot at {}, run the tests with UPDATE_SNAPSHOTS=1 to create it.", path.display());
+    };
+
+    assert!(snapshot == screen, "{name} differs from its snapshot.\n--- snapshot\n{snapshot}\n--- screen\n{screen}");
+}
+
+#[test]
+fn empty_session_list()
+{
+    let (mut app_manager, backend) = open_app("snapshot-empty", Vector2::new(SCREEN_WIDTH, SCREEN_HEIGHT));
+
+    assert_snapshot("empty_session_list", &render_screen(&mut app_manager, &backend));
+}
+
+#[test]
+fn session_list()
+{
+    let (mut app_manager, backend) = open_app_with_sessions(
+        "snapshot-list",
+        Vector2::new(SCREEN_WIDTH, SCREEN_HEIGHT),
+        &[
+            "12-01-2026;Plan the sprint;meeting;09:00:00;10:15:00;12-01-2026;1;",
+            "12-01-2026;Fix login redirect;code;10:30:00;12:45:30;12-01-2026;2;",
+            "13-01-2026;Release notes;docs;23:00:00;01:30:00;14-01-2026;3;",
+        ],
+    );
+
+    assert_snapshot("session_list", &render_screen(&mut app_manager, &backend));
+}
+
+#[test]
+fn ascii_frames()
+{
+    let (mut app_manager, backend) = open_app("snapshot-ascii", Vector2::new(SCREEN_WIDTH, SCREEN_HEIGHT));
+    app_manager.renderer.set_ascii(true);
+
+    assert_snapshot("ascii_frames", &render_screen(&mut app_manager, &backend));
+}
+
+#[test]
+fn help_overlay()
+{
+    let (mut app_manager, backend) = open_app("snapshot-help", Vector2::new(SCREEN_WIDTH, SCREEN_HEIGHT));
+    app_manager.help_open = true;
+
+    assert_snapshot("help_overlay", &render_screen(&mut app_manager, &backend));
+}
+
+#[test]
+fn new_session_description()
+{
+    let (mut app_manager, backend) = open_app("snapshot-new", Vector2::new(SCREEN_WIDTH, SCREEN_HEIGHT));
+    app_manager.description_buffer = TextInput::from("Write release notes");
+    app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
+
+    assert_snapshot("new_session_description", &render_screen(&mut app_manager, &backend));
+}
+
+#[test]
+fn quit_confirmation()
+{
+    let (mut app_manager, backend) = open_app("snapshot-quit", Vector2::new(SCREEN_WIDTH, SCREEN_HEIGHT));
+    app_manager.state = CommandState::Quitting;
+
+    assert_snapshot("quit_confirmation", &render_screen(&mut app_manager, &backend));
+}
+
+#[test]
+fn terminal_too_small()
+{
+    let (mut app_manager, backend) = open_app("snapshot-small", Vector2::new(MIN_TERMINAL_WIDTH - 20, MIN_TERMINAL_HEIGHT - 5));
+
+    assert_snapshot("terminal_too_small", &render_screen(&mut app_manager, &backend));
+}
+
+#[test]
+fn session_notes()
+{
+    let (mut app_manager, backend) = open_app_with_sessions(
+        "snapshot-notes",
+        Vector2::new(SCREEN_WIDTH, SCREEN_HEIGHT),
+        &["12-01-2026;Plan the sprint;meeting;09:00:00;10:15:00;12-01-2026;1;Agenda\\nReview the backlog\\s then estimate;"],
+    );
+    app_manager.selected_session_index = 0;
+    app_manager.open_notes_editor();
+    app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Notes(ConfirmOpen::No)));
+
+    assert_snapshot("session_notes", &render_screen(&mut app_manager, &backend));
+}
+
+#[test]
+fn focus_clock()
+{
+    let (mut app_manager, backend) = open_app("snapshot-focus", Vector2::new(SCREEN_WIDTH, SCREEN_HEIGHT));
+    app_manager.tag_buffer = TextInput::from("code");
+    app_manager.try_store_tag();
+    app_manager.description_buffer = TextInput::from("Fix login redirect");
+    app_manager.try_start_new_session();
+
+    let running_session = app_manager.sessions.last_mut().expect("Session was not started.");
+    running_session.start = NaiveDateTime::parse_from_str("14-01-2026 08:47:12", "%d-%m-%Y %H:%M:%S").expect("Invalid snapshot time.");
+    app_manager.state = CommandState::Focus;
+
+    assert_snapshot("focus_clock", &render_screen(&mut app_manager, &backend));
+}
+
+#[test]
+fn twelve_hour_clock()
+{
+    let (mut app_manager, backend) = open_app_with_sessions(
+        "snapshot-12h",
+        Vector2::new(SCREEN_WIDTH, SCREEN_HEIGHT),
+        &[
+            "12-01-2026;Plan the sprint;meeting;09:00:00;10:15:00;12-01-2026;1;",
+            "13-01-2026;Release notes;docs;13:05:00;17:45:30;13-01-2026;2;",
+        ],
+    );
+    app_manager.config.clock_format = ClockFormat::TwelveHour;
+    app_manager.config.date_order = DateOrder::MonthDayYear;
+
+    assert_snapshot("twelve_hour_clock", &render_screen(&mut app_manager, &backend));
+}
+
+#[test]
+fn settings()
+{
+    let (mut app_manager, backend) = open_app("snapshot-settings", Vector2::new(SCREEN_WIDTH, SCREEN_HEIGHT));
+    app_manager.open_settings();
+    app_manager.change_setting(SettingsField::Clock, true);
+    app_manager.change_setting(SettingsField::WorkingHoursEnd, false);
+    app_manager.state = CommandState::Settings(SettingsField::Clock);
+
+    assert_snapshot("settings", &render_screen(&mut app_manager, &backend));
+}
+
+#[test]
+fn status_message()
+{
+    let (mut app_manager, backend) = open_app_with_sessions(
+        "snapshot-status",
+        Vector2::new(SCREEN_WIDTH, SCREEN_HEIGHT),
+        &[
+            "12-01-2026;Plan the sprint;meeting;09:00:00;10:15:00;12-01-2026;1;",
+            "12-01-2026;Fix login redirect;code;10:30:00;12:45:30;12-01-2026;2;",
+        ],
+    );
+    app_manager.selected_session_index = 0;
+    app_manager.delete_selected_session();
+
+    assert_snapshot("status_message", &render_screen(&mut app_manager, &backend));
+}
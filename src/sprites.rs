@@ -12,3 +12,46 @@ pub const DIVIDER_H: char = '─';
 pub const DIVIDER_V: char = '│';
 pub const CURSOR: char = '█';
 pub const ARROW: char = '▶';
+pub const ARROW_DOWN: char = '▼';
+pub const ARROW_UP: char = '▲';
+pub const ELLIPSIS: char = '…';
+pub const MARK: char = '●';
+/// Bar segments from a full block down to one eighth of a cell.
+pub const BAR_BLOCKS: [char; 8] = ['█', '▉', '▊', '▋', '▌', '▍', '▎', '▏'];
+pub const BAR_EMPTY: char = '░';
+
+/// Digits five rows high for the focus clock, drawn from full blocks so they fall back to `#` in ASCII mode.
+pub const BIG_DIGITS: [[&str; 5]; 10] = [
+    ["██████", "██  ██", "██  ██", "██  ██", "██████"],
+    ["  ██  ", "████  ", "  ██  ", "  ██  ", "██████"],
+    ["██████", "    ██", "██████", "██    ", "██████"],
+    ["██████", "    ██", "██████", "    ██", "██████"],
+    ["██  ██", "██  ██", "██████", "    ██", "    ██"],
+    ["██████", "██    ", "██████", "    ██", "██████"],
+    ["██████", "██    ", "██████", "██  ██", "██████"],
+    ["██████", "    ██", "    ██", "    ██", "    ██"],
+    ["██████", "██  ██", "██████", "██  ██", "██████"],
+    ["██████", "██  ██", "██████", "    ██", "██████"],
+];
+pub const BIG_COLON: [&str; 5] = ["  ", "██", "  ", "██", "  "];
+
+/// Plain ASCII stand-ins for the sprites above, for terminals and screen readers that can't handle box drawing.
+#[must_use]
+pub fn to_ascii(character: char) -> char
+{
+    match character
+    {
+        FRAME_H => '=',
+        FRAME_V | DIVIDER_V => '|',
+        CORNER_TL | CORNER_TR | CORNER_BR | CORNER_BL | INTERSECT_T | INTERSECT_B | INTERSECT_L | INTERSECT_R => '+',
+        DIVIDER_H | '\u{2014}' => '-',
+        ARROW => '>',
+        ARROW_DOWN => 'v',
+        ARROW_UP => '^',
+        ELLIPSIS => '~',
+        MARK => '*',
+        BAR_EMPTY => '.',
+        _ if BAR_BLOCKS.contains(&character) => '#',
+        _ => character,
+    }
+}
@@ -0,0 +1,80 @@
+use crate::crypto::Cipher;
+use crate::daemon::request_daemon;
+use crate::database_handler::{DatabaseHandler, DATE_FORMAT, VALUE_SEPARATOR};
+use crate::json::JsonValue;
+use crate::server::get_status_json;
+use crate::session::format_duration_short;
+use chrono::TimeDelta;
+
+pub const DEFAULT_STATUS_FORMAT: &str = "text";
+const TEXT_TEMPLATE: &str = "{description} [{tag}] {elapsed}";
+
+/// The running session of this machine as one line for status bars like waybar, polybar or tmux, and whether there is
+/// one. `format` is "text", "json" (the `/status` answer of `--serve`), "waybar" (its custom module JSON) or a template
+/// with `{description}`, `{tag}`, `{elapsed}` and `{today}`. Without a running session the text line is empty. A
+/// running daemon answers right away, the database is only read without one, with the key `unlock` gives for an
+/// encrypted workspace.
+pub fn get_status_text(workspace: &str, unlock: impl FnOnce() -> Option<Cipher>, format: &str) -> (String, bool)
+{
+    let status = request_daemon(workspace, "GET", "/status", None).filter(|(status_code, _)| *status_code == 200).map_or_else(
+        || {
+            let database_handler = DatabaseHandler::new(workspace, unlock());
+            let running_session = database_handler.import_own_running_session(VALUE_SEPARATOR, DATE_FORMAT);
+
+            get_status_json(&database_handler, running_session.as_ref())
+        },
+        |(_, status)| status,
+    );
+
+    let running_session = status.get("running").filter(|running_session| !matches!(running_session, JsonValue::Null));
+    let is_running = running_session.is_some();
+
+    if format == "json"
+    {
+        return (status.to_string(), is_running);
+    }
+
+    let get_duration =
+        |value: Option<&JsonValue>| format_duration_short(TimeDelta::seconds(value.and_then(JsonValue::as_i64).unwrap_or(0)));
+    let today = get_duration(status.get("today_seconds"));
+
+    let text = running_session.map_or_else(String::new, |session| {
+        let template = if format == "text" || format == "waybar"
+        {
+            TEXT_TEMPLATE
+        }
+        else
+        {
+            format
+        };
+
+        template
+            .replace("{description}", session.get("description").and_then(JsonValue::as_str).unwrap_or_default())
+            .replace("{tag}", session.get("tag").and_then(JsonValue::as_str).unwrap_or_default())
+            .replace("{elapsed}", &get_duration(session.get("duration_seconds")))
+            .replace("{today}", &today)
+    });
+
+    if format == "waybar"
+    {
+        let tooltip = format!("Today {today}");
+        let class = if is_running
+        {
+            "running"
+        }
+        else
+        {
+            "idle"
+        };
+
+        let waybar_json = JsonValue::Object(vec![
+            (String::from("text"), JsonValue::String(text)),
+            (String::from("tooltip"), JsonValue::String(tooltip)),
+            (String::from("class"), JsonValue::String(class.to_string())),
+        ]);
+
+        return (waybar_json.to_string(), is_running);
+    }
+
+    (text, is_running)
+}
@@ -0,0 +1,248 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
+
+/// Where the database files are kept: on disk, or for `--ephemeral` only in memory until the app quits. Paths are the
+/// same either way. Clones share the files.
+#[derive(Clone)]
+pub struct Storage
+{
+    /// Files and folders by path, `None` on disk.
+    memory: Option<Rc<RefCell<BTreeMap<PathBuf, MemoryEntry>>>>,
+}
+
+enum MemoryEntry
+{
+    File
+    {
+        content: Vec<u8>,
+        modified: SystemTime,
+    },
+    Folder,
+}
+
+impl Storage
+{
+    #[must_use]
+    pub fn disk() -> Self
+    {
+        Storage {
+            memory: None,
+        }
+    }
+
+    #[must_use]
+    pub fn memory() -> Self
+    {
+        Storage {
+            memory: Some(Rc::new(RefCell::new(BTreeMap::new()))),
+        }
+    }
+
+    #[must_use]
+    pub fn is_memory(&self) -> bool
+    {
+        self.memory.is_some()
+    }
+
+    /// The content of the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read, `NotFound` if there is none.
+    pub fn read(&self, path: &Path) -> io::Result<Vec<u8>>
+    {
+        let Some(memory) = &self.memory
+        else
+        {
+            return fs::read(path);
+        };
+
+        match memory.borrow().get(path)
+        {
+            Some(MemoryEntry::File {
+                content,
+                ..
+            }) => Ok(content.clone()),
+            _ => Err(io::ErrorKind::NotFound.into()),
+        }
+    }
+
+    /// The content of the file at `path` as text.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read, `InvalidData` if it isn't UTF-8.
+    pub fn read_to_string(&self, path: &Path) -> io::Result<String>
+    {
+        String::from_utf8(self.read(path)?).map_err(|_| io::ErrorKind::InvalidData.into())
+    }
+
+    /// Replaces the content of the file at `path`, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be written.
+    pub fn write(&self, path: &Path, content: &[u8]) -> io::Result<()>
+    {
+        let Some(memory) = &self.memory
+        else
+        {
+            return fs::write(path, content);
+        };
+
+        memory.borrow_mut().insert(
+            path.to_path_buf(),
+            MemoryEntry::File {
+                content: content.to_vec(),
+                modified: SystemTime::now(),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Adds to the end of the file, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be written.
+    pub fn append(&self, path: &Path, content: &[u8]) -> io::Result<()>
+    {
+        if self.memory.is_none()
+        {
+            return OpenOptions::new().create(true).append(true).open(path)?.write_all(content);
+        }
+
+        let mut appended = self.read(path).unwrap_or_default();
+        appended.extend_from_slice(content);
+
+        self.write(path, &appended)
+    }
+
+    /// Moves the file at `from` to `to`, replacing what is there.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be moved, `NotFound` if there is none.
+    pub fn rename(&self, from: &Path, to: &Path) -> io::Result<()>
+    {
+        let Some(memory) = &self.memory
+        else
+        {
+            return fs::rename(from, to);
+        };
+
+        let entry = memory.borrow_mut().remove(from).ok_or(io::Error::from(io::ErrorKind::NotFound))?;
+        memory.borrow_mut().insert(to.to_path_buf(), entry);
+
+        Ok(())
+    }
+
+    /// Removes the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be removed, `NotFound` if there is none.
+    pub fn remove_file(&self, path: &Path) -> io::Result<()>
+    {
+        let Some(memory) = &self.memory
+        else
+        {
+            return fs::remove_file(path);
+        };
+
+        match memory.borrow_mut().remove(path)
+        {
+            Some(_) => Ok(()),
+            None => Err(io::ErrorKind::NotFound.into()),
+        }
+    }
+
+    #[must_use]
+    pub fn exists(&self, path: &Path) -> bool
+    {
+        self.memory.as_ref().map_or_else(|| path.exists(), |memory| memory.borrow().contains_key(path))
+    }
+
+    #[must_use]
+    pub fn is_dir(&self, path: &Path) -> bool
+    {
+        self.memory.as_ref().map_or_else(|| path.is_dir(), |memory| matches!(memory.borrow().get(path), Some(MemoryEntry::Folder)))
+    }
+
+    /// Creates the folder at `path` with the folders it is in.
+    ///
+    /// # Errors
+    ///
+    /// If a folder can't be created.
+    pub fn create_dir_all(&self, path: &Path) -> io::Result<()>
+    {
+        let Some(memory) = &self.memory
+        else
+        {
+            return fs::create_dir_all(path);
+        };
+
+        for folder in path.ancestors()
+        {
+            memory.borrow_mut().entry(folder.to_path_buf()).or_insert(MemoryEntry::Folder);
+        }
+
+        Ok(())
+    }
+
+    /// Names of the files and folders directly in the folder at `path`, in no particular order.
+    ///
+    /// # Errors
+    ///
+    /// If the folder can't be read.
+    pub fn list_names(&self, path: &Path) -> io::Result<Vec<String>>
+    {
+        let Some(memory) = &self.memory
+        else
+        {
+            return Ok(fs::read_dir(path)?.filter_map(Result::ok).filter_map(|entry| entry.file_name().into_string().ok()).collect());
+        };
+
+        if !self.is_dir(path)
+        {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+
+        Ok(memory
+            .borrow()
+            .keys()
+            .filter(|entry_path| entry_path.parent() == Some(path))
+            .filter_map(|entry_path| entry_path.file_name()?.to_str().map(str::to_string))
+            .collect())
+    }
+
+    /// Modification time and size of the file at `path`.
+    #[must_use]
+    pub fn get_modified_and_size(&self, path: &Path) -> Option<(SystemTime, u64)>
+    {
+        let Some(memory) = &self.memory
+        else
+        {
+            let metadata = fs::metadata(path).ok()?;
+
+            return Some((metadata.modified().ok()?, metadata.len()));
+        };
+
+        match memory.borrow().get(path)?
+        {
+            MemoryEntry::File {
+                content,
+                modified,
+            } => Some((*modified, content.len() as u64)),
+            MemoryEntry::Folder => None,
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use crate::database_handler::{DatabaseHandler, DEFAULT_WORKSPACE, ENCRYPTION_FILE_NAME};
+use log::debug;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::{env, fs};
+
+const COMMIT_MESSAGE: &str = "Sync sessions";
+
+/// Where and under which branch a workspace is kept in git.
+pub struct SyncTarget
+{
+    pub remote: String,
+    pub branch: String,
+}
+
+impl SyncTarget
+{
+    /// Named workspaces get a branch of their own next to `branch`, as they all share the configured remote.
+    #[must_use]
+    pub fn new(remote: &str, branch: &str, workspace: &str) -> Self
+    {
+        let branch = if workspace == DEFAULT_WORKSPACE
+        {
+            branch.to_string()
+        }
+        else
+        {
+            format!("{branch}-{workspace}")
+        };
+
+        SyncTarget {
+            remote: remote.to_string(),
+            branch,
+        }
+    }
+}
+
+/// Commits the local changes and merges the remote branch into them. Files both machines changed are merged line by
+/// line, keeping what either side added, and sessions that ended up with the same id are renumbered afterwards.
+///
+/// # Errors
+///
+/// The output of the git command that failed.
+pub fn pull(database_handler: &DatabaseHandler, target: &SyncTarget, value_separator: char, date_format: &str) -> Result<(), String>
+{
+    database_handler.compact_journal(value_separator, date_format).map_err(|_| String::from("JOURNAL NOT WRITABLE"))?;
+
+    init_repository(database_handler)?;
+    commit_changes(database_handler)?;
+
+    let fetch = run_git(database_handler, &["fetch", &target.remote, &target.branch])?;
+
+    if !fetch.status.success()
+    {
+        // Nothing was pushed to the branch yet.
+        if String::from_utf8_lossy(&fetch.stderr).contains("couldn't find remote ref")
+        {
+            return Ok(());
+        }
+
+        return Err(String::from("FETCH FAILED"));
+    }
+
+    let head_before_merge = get_head(database_handler);
+
+    let merge = run_git_with_identity(database_handler, &["merge", "--no-edit", "--allow-unrelated-histories", "FETCH_HEAD"])?;
+
+    if !merge.status.success()
+    {
+        resolve_conflicts(database_handler)?;
+    }
+
+    if get_head(database_handler) != head_before_merge
+    {
+        database_handler
+            .resolve_duplicate_session_ids(value_separator, date_format)
+            .map_err(|_| String::from("SESSIONS NOT WRITABLE"))?;
+        commit_changes(database_handler)?;
+
+        database_handler.reset_loaded_months();
+    }
+
+    Ok(())
+}
+
+/// Pulls first, so the remote only ever moves forward, then pushes the result.
+///
+/// # Errors
+///
+/// The output of the git command that failed.
+pub fn push(database_handler: &DatabaseHandler, target: &SyncTarget, value_separator: char, date_format: &str) -> Result<(), String>
+{
+    pull(database_handler, target, value_separator, date_format)?;
+
+    let push = run_git(database_handler, &["push", &target.remote, &format!("HEAD:refs/heads/{}", target.branch)])?;
+
+    if !push.status.success()
+    {
+        return Err(String::from("PUSH FAILED"));
+    }
+
+    Ok(())
+}
+
+/// Turns the workspace folder into a git repository that only tracks the files shared between machines. The journal
+/// is compacted before every commit, backups, the lock and the remembered state stay local.
+fn init_repository(database_handler: &DatabaseHandler) -> Result<(), String>
+{
+    let database_path = database_handler.get_database_path();
+
+    if database_path.join(".git").exists()
+    {
+        return Ok(());
+    }
+
+    if !run_git(database_handler, &["init", "--quiet"])?.status.success()
+    {
+        return Err(String::from("GIT INIT FAILED"));
+    }
+
+    let ignored_files = [
+        "*",
+        "!.gitignore",
+        "!sessions*.txt",
+        "!tags.txt",
+        "!quarantine.txt",
+        "!audit.txt",
+        "!toggl.txt",
+        "!days-off.txt",
+        &format!("!{ENCRYPTION_FILE_NAME}"),
+    ];
+
+    fs::write(database_path.join(".gitignore"), ignored_files.join("\n") + "\n").map_err(|_| String::from("GIT INIT FAILED"))
+}
+
+fn commit_changes(database_handler: &DatabaseHandler) -> Result<(), String>
+{
+    run_git(database_handler, &["add", "--all"])?;
+
+    // Exits with 1 if something is staged.
+    if run_git(database_handler, &["diff", "--cached", "--quiet"])?.status.success()
+    {
+        return Ok(());
+    }
+
+    if !run_git_with_identity(database_handler, &["commit", "--quiet", "--message", COMMIT_MESSAGE])?.status.success()
+    {
+        return Err(String::from("COMMIT FAILED"));
+    }
+
+    Ok(())
+}
+
+/// Merges every conflicting file by its lines and concludes the merge. Aborts it if a file can't be merged that way,
+/// like the encryption key of a workspace encrypted on both machines with different passphrases.
+fn resolve_conflicts(database_handler: &DatabaseHandler) -> Result<(), String>
+{
+    let conflicts = run_git(database_handler, &["diff", "--name-only", "--diff-filter=U"])?;
+    let file_names = String::from_utf8_lossy(&conflicts.stdout).lines().map(str::to_string).collect::<Vec<String>>();
+
+    for file_name in &file_names
+    {
+        let [base, ours, theirs] = [1, 2, 3].map(|stage| {
+            run_git(database_handler, &["show", &format!(":{stage}:{file_name}")])
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| output.stdout)
+        });
+
+        let is_merged = file_name != ENCRYPTION_FILE_NAME
+            && database_handler.merge_file_versions(file_name, base.as_deref(), ours.as_deref(), theirs.as_deref())
+            && run_git(database_handler, &["add", "--all", "--", file_name])?.status.success();
+
+        if !is_merged
+        {
+            run_git(database_handler, &["merge", "--abort"])?;
+            return Err(format!("CONFLICT IN {}", file_name.to_uppercase()));
+        }
+    }
+
+    if file_names.is_empty() || !run_git_with_identity(database_handler, &["commit", "--quiet", "--no-edit"])?.status.success()
+    {
+        run_git(database_handler, &["merge", "--abort"])?;
+        return Err(String::from("MERGE FAILED"));
+    }
+
+    Ok(())
+}
+
+/// Runs a git command that creates commits as the git user of the machine, or under a generic name where none is set
+/// up.
+fn run_git_with_identity(database_handler: &DatabaseHandler, arguments: &[&str]) -> Result<Output, String>
+{
+    let mut identity_arguments = if run_git(database_handler, &["config", "user.email"])?.status.success()
+    {
+        Vec::new()
+    }
+    else
+    {
+        vec!["-c", "user.name=time-tracker", "-c", "user.email=time-tracker@localhost"]
+    };
+
+    identity_arguments.extend_from_slice(arguments);
+
+    run_git(database_handler, &identity_arguments)
+}
+
+fn get_head(database_handler: &DatabaseHandler) -> Option<Vec<u8>>
+{
+    run_git(database_handler, &["rev-parse", "--verify", "--quiet", "HEAD"]).ok().map(|output| output.stdout)
+}
+
+/// The branch checked out in the repository holding `directory`, `None` outside of a repository or on a detached
+/// head. A leading `~` stands for the home folder.
+#[must_use]
+pub fn get_current_branch(directory: &str) -> Option<String>
+{
+    let directory = match (directory.strip_prefix('~'), env::var_os("HOME"))
+    {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest.trim_start_matches('/')),
+        _ => PathBuf::from(directory),
+    };
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    (output.status.success() && !branch.is_empty()).then_some(branch)
+}
+
+/// Runs git in the workspace folder. It must not ask for credentials, as the terminal belongs to the app.
+fn run_git(database_handler: &DatabaseHandler, arguments: &[&str]) -> Result<Output, String>
+{
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(database_handler.get_database_path())
+        .args(arguments)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|_| String::from("GIT NOT FOUND"))?;
+
+    if !output.status.success()
+    {
+        debug!("git {} failed: {}", arguments.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(output)
+}
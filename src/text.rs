@@ -0,0 +1,286 @@
+use crate::sprites::ELLIPSIS;
+
+/// Terminal cell widths of text. Layout works in cells, not bytes or chars, so descriptions with CJK characters,
+/// emoji or combining accents line up with the columns.
+#[must_use]
+pub fn get_char_width(character: char) -> usize
+{
+    let code = u32::from(character);
+
+    let is_zero_width = character.is_control()
+        || matches!(
+            code,
+            0x0300..=0x036F
+                | 0x0483..=0x0489
+                | 0x0591..=0x05BD
+                | 0x0610..=0x061A
+                | 0x064B..=0x065F
+                | 0x1AB0..=0x1AFF
+                | 0x1DC0..=0x1DFF
+                | 0x200B..=0x200F
+                | 0x20D0..=0x20FF
+                | 0xFE00..=0xFE0F
+                | 0xFE20..=0xFE2F
+                | 0xE0100..=0xE01EF
+        );
+
+    if is_zero_width
+    {
+        return 0;
+    }
+
+    let is_wide = matches!(
+        code,
+        0x1100..=0x115F
+            | 0x231A..=0x231B
+            | 0x2329..=0x232A
+            | 0x23E9..=0x23EC
+            | 0x23F0
+            | 0x23F3
+            | 0x25FD..=0x25FE
+            | 0x2614..=0x2615
+            | 0x2648..=0x2653
+            | 0x267F
+            | 0x2693
+            | 0x26A1
+            | 0x26AA..=0x26AB
+            | 0x26BD..=0x26BE
+            | 0x26C4..=0x26C5
+            | 0x26CE
+            | 0x26D4
+            | 0x26EA
+            | 0x26F2..=0x26F5
+            | 0x26FA
+            | 0x26FD
+            | 0x2705
+            | 0x270A..=0x270B
+            | 0x2728
+            | 0x274C
+            | 0x274E
+            | 0x2753..=0x2755
+            | 0x2757
+            | 0x2795..=0x2797
+            | 0x27B0
+            | 0x27BF
+            | 0x2B1B..=0x2B1C
+            | 0x2B50
+            | 0x2B55
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xA960..=0xA97F
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE10..=0xFE19
+            | 0xFE30..=0xFE6F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x1F004
+            | 0x1F0CF
+            | 0x1F18E
+            | 0x1F191..=0x1F19A
+            | 0x1F200..=0x1F251
+            | 0x1F300..=0x1F64F
+            | 0x1F680..=0x1F6FF
+            | 0x1F7E0..=0x1F7EB
+            | 0x1F90C..=0x1F9FF
+            | 0x1FA70..=0x1FAFF
+            | 0x20000..=0x3FFFD
+    );
+
+    if is_wide
+    {
+        2
+    }
+    else
+    {
+        1
+    }
+}
+
+pub fn get_display_width(text: &str) -> usize
+{
+    text.chars().map(get_char_width).sum()
+}
+
+/// Cuts the text so it takes up at most `width` cells.
+#[must_use]
+pub fn truncate_to_width(text: &str, width: usize) -> String
+{
+    let mut truncated = String::new();
+    let mut truncated_width = 0;
+
+    for character in text.chars()
+    {
+        let character_width = get_char_width(character);
+
+        if truncated_width + character_width > width
+        {
+            break;
+        }
+
+        truncated.push(character);
+        truncated_width += character_width;
+    }
+
+    truncated
+}
+
+/// Cuts the text to `width` cells, marking the cut with an ellipsis.
+#[must_use]
+pub fn ellipsize(text: &str, width: usize) -> String
+{
+    if get_display_width(text) <= width
+    {
+        return text.to_string();
+    }
+
+    if width == 0
+    {
+        return String::new();
+    }
+
+    format!("{}{}", truncate_to_width(text, width - 1), ELLIPSIS)
+}
+
+/// The end of the text that fits into `width` cells, so the cursor stays visible while typing.
+#[must_use]
+pub fn get_tail_within_width(text: &str, width: usize) -> String
+{
+    let mut tail_start = text.len();
+    let mut tail_width = 0;
+
+    for (index, character) in text.char_indices().rev()
+    {
+        tail_width += get_char_width(character);
+
+        if tail_width > width
+        {
+            break;
+        }
+
+        tail_start = index;
+    }
+
+    text[tail_start..].to_string()
+}
+
+/// Breaks the text into lines of at most `width` cells, between words where possible.
+#[must_use]
+pub fn wrap_to_width(text: &str, width: usize) -> Vec<String>
+{
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split(' ')
+    {
+        let separator = usize::from(!line.is_empty());
+
+        if get_display_width(&line) + separator + get_display_width(word) <= width
+        {
+            if separator > 0
+            {
+                line.push(' ');
+            }
+            line.push_str(word);
+            continue;
+        }
+
+        if !line.is_empty()
+        {
+            lines.push(line);
+        }
+
+        let mut remaining = word.to_string();
+
+        while get_display_width(&remaining) > width && width > 0
+        {
+            let head = truncate_to_width(&remaining, width);
+            remaining = remaining[head.len()..].to_string();
+            lines.push(head);
+        }
+
+        line = remaining;
+    }
+
+    lines.push(line);
+    lines
+}
+
+/// Fills the text up with spaces to `width` cells, `format!("{:<width$}")` counts chars instead.
+#[must_use]
+pub fn pad_to_width(text: &str, width: usize) -> String
+{
+    format!("{}{}", text, " ".repeat(width.saturating_sub(get_display_width(text))))
+}
+
+/// How well `pattern` fuzzy-matches the text, ignoring case: 0 for a prefix, 1 for a substring and 2 when the pattern's
+/// characters only appear in order with gaps between them. `None` when it does not match at all.
+#[must_use]
+pub fn get_fuzzy_match_rank(text: &str, pattern: &str) -> Option<usize>
+{
+    let text = text.to_lowercase();
+    let pattern = pattern.to_lowercase();
+
+    if text.starts_with(&pattern)
+    {
+        return Some(0);
+    }
+
+    if text.contains(&pattern)
+    {
+        return Some(1);
+    }
+
+    let mut text_chars = text.chars();
+
+    pattern.chars().all(|pattern_char| text_chars.any(|text_char| text_char == pattern_char)).then_some(2)
+}
+
+/// Pasted text as one line to type into a field: line breaks and tabs become single spaces, other control characters
+/// are dropped and the ends are trimmed.
+pub fn get_pasted_line(text: &str) -> String
+{
+    let line = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect::<Vec<&str>>().join(" ");
+
+    line.replace('\t', " ").chars().filter(|character| !character.is_control()).collect()
+}
+
+/// Splits a command line into its arguments like a shell does: at whitespace outside quotes, with "double" or 'single'
+/// quotes around arguments containing spaces and `\` escaping the next character outside single quotes. `None` when a
+/// quote is left open or the line ends in a `\`.
+#[must_use]
+pub fn split_arguments(line: &str) -> Option<Vec<String>>
+{
+    let mut arguments = Vec::new();
+    let mut argument: Option<String> = None;
+    let mut quote = None;
+    let mut characters = line.chars();
+
+    while let Some(character) = characters.next()
+    {
+        match (quote, character)
+        {
+            (Some(quote_character), _) if character == quote_character => quote = None,
+            (Some('"') | None, '\\') => argument.get_or_insert_default().push(characters.next()?),
+            (Some(_), _) => argument.get_or_insert_default().push(character),
+            (None, '"' | '\'') =>
+            {
+                quote = Some(character);
+                argument.get_or_insert_default();
+            }
+            (None, _) if character.is_whitespace() => arguments.extend(argument.take()),
+            (None, _) => argument.get_or_insert_default().push(character),
+        }
+    }
+
+    if quote.is_some()
+    {
+        return None;
+    }
+
+    arguments.extend(argument);
+    Some(arguments)
+}
@@ -0,0 +1,178 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+
+/// A line of text being typed, with the cursor as a character position within it. Text put in from outside starts with
+/// the cursor at its end, where typing continues.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct TextInput
+{
+    text: String,
+    cursor: usize,
+}
+
+impl TextInput
+{
+    #[must_use]
+    pub fn new() -> Self
+    {
+        TextInput::default()
+    }
+
+    #[must_use]
+    pub fn get_cursor(&self) -> usize
+    {
+        self.cursor
+    }
+
+    /// The text left of the cursor, which decides where on screen the cursor goes.
+    #[must_use]
+    pub fn get_text_before_cursor(&self) -> &str
+    {
+        &self.text[..self.get_cursor_byte_index()]
+    }
+
+    #[must_use]
+    pub fn get_text_after_cursor(&self) -> &str
+    {
+        &self.text[self.get_cursor_byte_index()..]
+    }
+
+    /// `None` when the cursor is behind the last character.
+    #[must_use]
+    pub fn get_char_at_cursor(&self) -> Option<char>
+    {
+        self.get_text_after_cursor().chars().next()
+    }
+
+    /// Replaces the text, moving the cursor to its end.
+    pub fn set(&mut self, text: &str)
+    {
+        self.text = text.to_string();
+        self.cursor = text.chars().count();
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    pub fn insert(&mut self, character: char)
+    {
+        let byte_index = self.get_cursor_byte_index();
+        self.text.insert(byte_index, character);
+        self.cursor += 1;
+    }
+
+    pub fn delete_before(&mut self)
+    {
+        if self.cursor > 0
+        {
+            self.cursor -= 1;
+            let byte_index = self.get_cursor_byte_index();
+            self.text.remove(byte_index);
+        }
+    }
+
+    pub fn delete_after(&mut self)
+    {
+        if self.cursor < self.get_length()
+        {
+            let byte_index = self.get_cursor_byte_index();
+            self.text.remove(byte_index);
+        }
+    }
+
+    /// Removes the word before the cursor together with the spaces between it and the cursor.
+    pub fn delete_word_before(&mut self)
+    {
+        let characters = self.text.chars().collect::<Vec<char>>();
+        let word_start = get_word_start(&characters, self.cursor);
+
+        self.text = characters[..word_start].iter().chain(&characters[self.cursor..]).collect();
+        self.cursor = word_start;
+    }
+
+    pub fn move_left(&mut self)
+    {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self)
+    {
+        self.cursor = (self.cursor + 1).min(self.get_length());
+    }
+
+    pub fn move_to_start(&mut self)
+    {
+        self.cursor = 0;
+    }
+
+    pub fn move_to_end(&mut self)
+    {
+        self.cursor = self.get_length();
+    }
+
+    fn get_length(&self) -> usize
+    {
+        self.text.chars().count()
+    }
+
+    fn get_cursor_byte_index(&self) -> usize
+    {
+        self.text.char_indices().nth(self.cursor).map_or(self.text.len(), |(byte_index, _)| byte_index)
+    }
+}
+
+/// Where the word before `cursor` in `characters` starts, skipping whitespace between the word and the cursor first.
+#[must_use]
+pub fn get_word_start(characters: &[char], cursor: usize) -> usize
+{
+    let before_word = characters[..cursor].iter().rposition(|character| !character.is_whitespace()).map_or(0, |index| index + 1);
+
+    characters[..before_word].iter().rposition(|character| character.is_whitespace()).map_or(0, |index| index + 1)
+}
+
+impl Deref for TextInput
+{
+    type Target = str;
+
+    fn deref(&self) -> &str
+    {
+        &self.text
+    }
+}
+
+impl From<&str> for TextInput
+{
+    fn from(text: &str) -> Self
+    {
+        let mut input = TextInput::new();
+        input.set(text);
+        input
+    }
+}
+
+impl From<String> for TextInput
+{
+    fn from(text: String) -> Self
+    {
+        TextInput::from(text.as_str())
+    }
+}
+
+impl PartialEq<&str> for TextInput
+{
+    fn eq(&self, other: &&str) -> bool
+    {
+        self.text == *other
+    }
+}
+
+impl Display for TextInput
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    {
+        write!(f, "{}", self.text)
+    }
+}
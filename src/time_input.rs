@@ -0,0 +1,208 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Weekday};
+
+pub const INPUT_DATE_FORMAT: &str = "%d-%m-%Y";
+pub const INPUT_TIME_FORMAT: &str = "%H:%M";
+
+#[must_use]
+pub fn parse_time(time: &str) -> Option<NaiveTime>
+{
+    let time = time.trim();
+
+    NaiveTime::parse_from_str(time, "%H:%M:%S").or_else(|_| NaiveTime::parse_from_str(time, INPUT_TIME_FORMAT)).ok()
+}
+
+/// Parses a day given on the command line: "today", "yesterday", `2026-01-31` or `31-01-2026`.
+#[must_use]
+pub fn parse_date_input(input: &str, today: NaiveDate) -> Option<NaiveDate>
+{
+    match input.trim()
+    {
+        "today" => Some(today),
+        "yesterday" => today.pred_opt(),
+        input => NaiveDate::parse_from_str(input, "%Y-%m-%d").or_else(|_| NaiveDate::parse_from_str(input, INPUT_DATE_FORMAT)).ok(),
+    }
+}
+
+/// Parses a session start typed by the user, either as an offset into the past ("-15m", "1h30m"), as a clock time
+/// ("09:40") or as a day and a clock time ("yesterday 23:50", "2026-01-31 09:40"). A clock time later than now is one
+/// of yesterday, so "23:50" typed just after midnight starts before it. A day with a time in the future is rejected.
+#[must_use]
+pub fn parse_start_input(input: &str, now: NaiveDateTime) -> Option<NaiveDateTime>
+{
+    let input = input.trim();
+
+    let start = if let Some(time) = parse_time(input)
+    {
+        let start = now.date().and_time(time);
+
+        if start > now
+        {
+            start.checked_sub_signed(TimeDelta::days(1))?
+        }
+        else
+        {
+            start
+        }
+    }
+    else if let Some((date, time)) = input.rsplit_once(' ')
+    {
+        parse_date_input(date, now.date())?.and_time(parse_time(time)?)
+    }
+    else
+    {
+        now.checked_sub_signed(parse_duration(input.strip_prefix('-').unwrap_or(input))?)?
+    };
+
+    if start > now
+    {
+        return None;
+    }
+
+    Some(start)
+}
+
+/// Reads durations like `1h30m`, `45m` or `90` (minutes).
+#[must_use]
+pub fn parse_duration(duration: &str) -> Option<TimeDelta>
+{
+    let mut total = TimeDelta::zero();
+    let mut number = String::new();
+
+    for character in duration.chars()
+    {
+        if character.is_ascii_digit()
+        {
+            number.push(character);
+            continue;
+        }
+
+        let value = number.parse::<i64>().ok()?;
+        number.clear();
+
+        let delta = match character
+        {
+            'h' => TimeDelta::try_hours(value)?,
+            'm' => TimeDelta::try_minutes(value)?,
+            's' => TimeDelta::try_seconds(value)?,
+            _ => return None,
+        };
+        total = total.checked_add(&delta)?;
+    }
+
+    if !number.is_empty()
+    {
+        total = total.checked_add(&TimeDelta::try_minutes(number.parse::<i64>().ok()?)?)?;
+    }
+
+    if total.is_zero()
+    {
+        return None;
+    }
+
+    Some(total)
+}
+
+/// First day of the week containing `date`, for weeks starting on `week_start`.
+#[must_use]
+pub fn get_week_start(date: NaiveDate, week_start: Weekday) -> NaiveDate
+{
+    date - TimeDelta::days(i64::from(date.weekday().days_since(week_start)))
+}
+
+/// Which way a segment of a date or time is moved, later or earlier.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Step
+{
+    Forward,
+    Back,
+}
+
+/// `date` moved a day, month or year by `step`, depending on the `date_segment` being edited. `None` when it would
+/// leave the range of dates.
+#[must_use]
+pub fn edit_date(step: Step, date_segment: usize, date: NaiveDateTime) -> Option<NaiveDateTime>
+{
+    match step
+    {
+        Step::Forward => match date_segment
+        {
+            0 => date.checked_add_days(chrono::Days::new(1)),
+            1 => date.checked_add_months(chrono::Months::new(1)),
+            2 => date.checked_add_months(chrono::Months::new(12)),
+            _ => None,
+        },
+        Step::Back => match date_segment
+        {
+            0 => date.checked_sub_days(chrono::Days::new(1)),
+            1 => date.checked_sub_months(chrono::Months::new(1)),
+            2 => date.checked_sub_months(chrono::Months::new(12)),
+            _ => None,
+        },
+    }
+}
+
+/// `time` moved an hour, minute or second by `step`, depending on the `date_segment` being edited. `None` when it would
+/// leave the range of dates.
+#[must_use]
+pub fn edit_time(step: Step, date_segment: usize, time: NaiveDateTime) -> Option<NaiveDateTime>
+{
+    match step
+    {
+        Step::Forward => match date_segment
+        {
+            0 => time.checked_add_signed(TimeDelta::hours(1)),
+            1 => time.checked_add_signed(TimeDelta::minutes(1)),
+            2 => time.checked_add_signed(TimeDelta::seconds(1)),
+            _ => None,
+        },
+        Step::Back => match date_segment
+        {
+            0 => time.checked_sub_signed(TimeDelta::hours(1)),
+            1 => time.checked_sub_signed(TimeDelta::minutes(1)),
+            2 => time.checked_sub_signed(TimeDelta::seconds(1)),
+            _ => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn get_time(date: (i32, u32, u32), time: (u32, u32, u32)) -> NaiveDateTime
+    {
+        NaiveDate::from_ymd_opt(date.0, date.1, date.2)
+            .and_then(|date| date.and_hms_opt(time.0, time.1, time.2))
+            .expect("Invalid test time.")
+    }
+
+    #[test]
+    fn dates_step_by_day_month_and_year()
+    {
+        let leap_day = get_time((2024, 2, 29), (9, 30, 0));
+
+        assert_eq!(edit_date(Step::Forward, 0, leap_day), Some(get_time((2024, 3, 1), (9, 30, 0))));
+        assert_eq!(edit_date(Step::Back, 1, leap_day), Some(get_time((2024, 1, 29), (9, 30, 0))));
+        assert_eq!(edit_date(Step::Forward, 2, leap_day), Some(get_time((2025, 2, 28), (9, 30, 0))));
+        assert_eq!(edit_date(Step::Forward, 3, leap_day), None);
+    }
+
+    #[test]
+    fn times_step_by_hour_minute_and_second_across_days()
+    {
+        let late = get_time((2026, 1, 14), (23, 59, 59));
+
+        assert_eq!(edit_time(Step::Forward, 0, late), Some(get_time((2026, 1, 15), (0, 59, 59))));
+        assert_eq!(edit_time(Step::Forward, 2, late), Some(get_time((2026, 1, 15), (0, 0, 0))));
+        assert_eq!(edit_time(Step::Back, 1, get_time((2026, 1, 14), (0, 0, 30))), Some(get_time((2026, 1, 13), (23, 59, 30))));
+        assert_eq!(edit_time(Step::Back, 3, late), None);
+    }
+
+    #[test]
+    fn edits_stop_at_the_end_of_the_date_range()
+    {
+        assert_eq!(edit_date(Step::Forward, 2, NaiveDateTime::MAX), None);
+        assert_eq!(edit_time(Step::Back, 2, NaiveDateTime::MIN), None);
+    }
+}
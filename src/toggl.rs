@@ -0,0 +1,230 @@
+use crate::database_handler::DatabaseHandler;
+use crate::http;
+use crate::json::{parse_json, JsonValue};
+use crate::session::Session;
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta, TimeZone, Timelike, Utc};
+
+const API_URL: &str = "https://api.track.toggl.com/api/v9";
+/// How far back sessions are sent and entries brought in.
+const SYNC_DAYS: i64 = 14;
+/// Tag of entries without a mapped project or Toggl tags.
+const FALLBACK_TAG: &str = "toggl";
+const TOGGL_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// The Toggl account a workspace is synced with, with the Toggl project of each mapped tag.
+pub struct TogglAccount
+{
+    pub token: String,
+    pub workspace_id: Option<u64>,
+    pub projects: Vec<(String, u64)>,
+}
+
+/// Brings in the Toggl entries of the last `SYNC_DAYS` days that aren't sessions yet, then sends the finished sessions
+/// of that time that aren't on Toggl yet. Which session belongs to which entry is kept in a table, so nothing is
+/// brought in or sent twice. Returns how many entries were brought in.
+///
+/// # Errors
+///
+/// If Toggl can't be reached or refuses a request, or the links can't be stored.
+pub fn sync(
+    database_handler: &DatabaseHandler,
+    account: &TogglAccount,
+    value_separator: char,
+    date_format: &str,
+) -> Result<usize, String>
+{
+    let mut links = database_handler.import_toggl_links(value_separator);
+    let result = sync_entries(database_handler, account, &mut links, value_separator, date_format);
+
+    // What was linked before an error is kept as well.
+    database_handler.export_toggl_links(&links, value_separator).map_err(|_| String::from("TOGGL LINKS NOT WRITABLE"))?;
+
+    result
+}
+
+fn sync_entries(
+    database_handler: &DatabaseHandler,
+    account: &TogglAccount,
+    links: &mut Vec<(u64, u64)>,
+    value_separator: char,
+    date_format: &str,
+) -> Result<usize, String>
+{
+    let workspace_id = match account.workspace_id
+    {
+        Some(workspace_id) => workspace_id,
+        None => get_id(request(account, "GET", "/me", None)?.get("default_workspace_id")).ok_or("TOGGL WORKSPACE NOT FOUND")?,
+    };
+
+    let now = Local::now().naive_local();
+    let since = now - TimeDelta::days(SYNC_DAYS);
+
+    let session_lines = database_handler.import_all_session_lines(value_separator, date_format);
+    let mut sessions = DatabaseHandler::parse_sessions(&session_lines, value_separator, date_format).0.unwrap_or_default();
+    let mut next_session_id =
+        sessions.iter().map(|session| session.id).max().unwrap_or(0).max(database_handler.get_last_session_id(value_separator)) + 1;
+    let mut tags = database_handler.import_tags().unwrap_or_default();
+    let mut imported_count = 0;
+
+    let entries_path =
+        format!("/me/time_entries?start_date={}&end_date={}", to_toggl_time(since), to_toggl_time(now + TimeDelta::days(1)));
+    let entries = request(account, "GET", &entries_path, None)?;
+
+    for entry in entries.as_array().unwrap_or_default()
+    {
+        let Some(entry_id) = get_id(entry.get("id"))
+        else
+        {
+            continue;
+        };
+
+        if links.iter().any(|(_, linked_entry_id)| *linked_entry_id == entry_id)
+            || get_id(entry.get("workspace_id")) != Some(workspace_id)
+        {
+            continue;
+        }
+
+        let Some(mut session) = entry_to_session(entry, account, value_separator)
+        else
+        {
+            continue;
+        };
+
+        // Sent from another machine that doesn't share the table.
+        if let Some(existing_session) = sessions.iter().find(|existing_session| {
+            existing_session.start == session.start
+                && existing_session.end == session.end
+                && existing_session.description == session.description
+        })
+        {
+            links.push((existing_session.id, entry_id));
+            continue;
+        }
+
+        session.id = next_session_id;
+        next_session_id += 1;
+
+        if !tags.contains(&session.tag)
+        {
+            database_handler.export_tag(&session.tag).map_err(|_| String::from("TAGS NOT WRITABLE"))?;
+            tags.push(session.tag.clone());
+        }
+
+        database_handler
+            .export_session(&session.construct_db_string(value_separator, date_format), value_separator, date_format)
+            .map_err(|_| String::from("SESSIONS NOT WRITABLE"))?;
+
+        links.push((session.id, entry_id));
+        sessions.push(session);
+        imported_count += 1;
+    }
+
+    for session in get_unsent_sessions(&sessions, links, since)
+    {
+        let entry = session_to_entry(session, account, workspace_id);
+        let created_entry = request(account, "POST", &format!("/workspaces/{workspace_id}/time_entries"), Some(&entry.to_string()))?;
+
+        links.push((session.id, get_id(created_entry.get("id")).ok_or("TOGGL SENT NO ENTRY ID")?));
+    }
+
+    Ok(imported_count)
+}
+
+/// The finished sessions since `since` that aren't linked to a Toggl entry yet, which a sync sends.
+#[must_use]
+pub fn get_unsent_sessions<'a>(sessions: &'a [Session], links: &[(u64, u64)], since: NaiveDateTime) -> Vec<&'a Session>
+{
+    sessions
+        .iter()
+        .filter(|session| !session.is_running() && session.start >= since)
+        .filter(|session| !links.iter().any(|(session_id, _)| *session_id == session.id))
+        .collect()
+}
+
+/// The session of a finished entry, tagged with the tag mapped to its project, its first Toggl tag or `FALLBACK_TAG`.
+fn entry_to_session(entry: &JsonValue, account: &TogglAccount, value_separator: char) -> Option<Session>
+{
+    let start = from_toggl_time(entry.get("start")?.as_str()?)?;
+    let end = from_toggl_time(entry.get("stop")?.as_str()?)?;
+    let description = entry.get("description").and_then(JsonValue::as_str).unwrap_or_default();
+
+    let project_id = get_id(entry.get("project_id"));
+    let tag = account
+        .projects
+        .iter()
+        .find(|(_, mapped_project_id)| Some(*mapped_project_id) == project_id)
+        .map(|(tag, _)| tag.as_str())
+        .or_else(|| entry.get("tags")?.as_array()?.first()?.as_str())
+        .unwrap_or(FALLBACK_TAG);
+
+    // The separator would split the stored line.
+    let [description, tag] = [description, tag].map(|value| value.trim().replace(value_separator, ","));
+
+    (end >= start).then(|| Session::from(&description, &tag, start, Some(end)))
+}
+
+fn session_to_entry(session: &Session, account: &TogglAccount, workspace_id: u64) -> JsonValue
+{
+    let end = session.end.unwrap_or(session.start);
+    let workspace_id = i64::try_from(workspace_id).unwrap_or_default();
+
+    let mut members = vec![
+        (String::from("created_with"), JsonValue::String(String::from("time-tracker"))),
+        (String::from("description"), JsonValue::String(session.description.clone())),
+        (String::from("workspace_id"), JsonValue::Integer(workspace_id)),
+        (String::from("start"), JsonValue::String(to_toggl_time(session.start))),
+        (String::from("stop"), JsonValue::String(to_toggl_time(end))),
+        (String::from("duration"), JsonValue::Integer((end - session.start).num_seconds())),
+        (String::from("tags"), JsonValue::Array(vec![JsonValue::String(session.tag.clone())])),
+    ];
+
+    if let Some((_, project_id)) = account.projects.iter().find(|(tag, _)| *tag == session.tag)
+    {
+        members.push((String::from("project_id"), JsonValue::Integer(i64::try_from(*project_id).unwrap_or_default())));
+    }
+
+    JsonValue::Object(members)
+}
+
+fn get_id(value: Option<&JsonValue>) -> Option<u64>
+{
+    value.and_then(JsonValue::as_i64).and_then(|id| u64::try_from(id).ok())
+}
+
+/// Toggl keeps times in UTC, sessions in local time.
+fn to_toggl_time(time: NaiveDateTime) -> String
+{
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .map_or_else(|| time.and_utc(), |time| time.with_timezone(&Utc))
+        .format(TOGGL_DATE_FORMAT)
+        .to_string()
+}
+
+fn from_toggl_time(time: &str) -> Option<NaiveDateTime>
+{
+    let time = DateTime::parse_from_rfc3339(time).ok()?.with_timezone(&Local).naive_local();
+
+    time.with_nanosecond(0)
+}
+
+fn request(account: &TogglAccount, method: &str, path: &str, body: Option<&str>) -> Result<JsonValue, String>
+{
+    let mut headers = vec![http::get_basic_authorization(&account.token, "api_token")];
+
+    if body.is_some()
+    {
+        headers.push(String::from("Content-Type: application/json"));
+    }
+
+    let (status, body) = http::request(method, &format!("{API_URL}{path}"), &headers, body)?;
+
+    match status
+    {
+        200..=299 => parse_json(&body).ok_or_else(|| String::from("TOGGL SENT INVALID JSON")),
+        0 => Err(String::from("TOGGL UNREACHABLE")),
+        401 | 403 => Err(String::from("TOGGL TOKEN REJECTED")),
+        status => Err(format!("TOGGL ERROR {status}")),
+    }
+}
@@ -0,0 +1,233 @@
+/// Values of the TOML subset used by the config file: strings, numbers, booleans and flat arrays.
+#[allow(dead_code)]
+pub enum TomlValue
+{
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Array(Vec<TomlValue>),
+}
+
+impl TomlValue
+{
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str>
+    {
+        match self
+        {
+            TomlValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool>
+    {
+        match self
+        {
+            TomlValue::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_integer(&self) -> Option<i64>
+    {
+        match self
+        {
+            TomlValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// A `[name]` section with its keys in file order. Keys before the first header belong to the table named "".
+pub struct TomlTable
+{
+    pub name: String,
+    pub entries: Vec<(String, TomlValue)>,
+}
+
+/// Quotes the text as a TOML basic string.
+#[must_use]
+pub fn to_toml_string(value: &str) -> String
+{
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[must_use]
+pub fn get_table<'a>(tables: &'a [TomlTable], name: &str) -> Option<&'a TomlTable>
+{
+    tables.iter().find(|table| table.name == name)
+}
+
+/// Parses the subset of TOML the config needs. Errors name the offending line.
+///
+/// # Errors
+///
+/// The first line that isn't valid, with its number.
+pub fn parse_toml(content: &str) -> Result<Vec<TomlTable>, String>
+{
+    let mut tables = vec![TomlTable {
+        name: String::new(),
+        entries: Vec::new(),
+    }];
+
+    for (line_index, line) in content.lines().enumerate()
+    {
+        let line = strip_comment(line).trim();
+
+        if line.is_empty()
+        {
+            continue;
+        }
+
+        let error = || format!("INVALID CONFIG LINE {}", line_index + 1);
+
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']'))
+        {
+            tables.push(TomlTable {
+                name: parse_key(name.trim()).ok_or_else(error)?,
+                entries: Vec::new(),
+            });
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or_else(error)?;
+        let key = parse_key(key.trim()).ok_or_else(error)?;
+        let value = parse_value(value.trim()).ok_or_else(error)?;
+
+        if let Some(table) = tables.last_mut()
+        {
+            table.entries.push((key, value));
+        }
+    }
+
+    Ok(tables)
+}
+
+fn strip_comment(line: &str) -> &str
+{
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, character) in line.char_indices()
+    {
+        match character
+        {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..index],
+            _ =>
+            {}
+        }
+    }
+
+    line
+}
+
+fn parse_key(key: &str) -> Option<String>
+{
+    if key.starts_with('"')
+    {
+        return parse_string(key);
+    }
+
+    let is_bare_key =
+        !key.is_empty() && key.chars().all(|character| character.is_ascii_alphanumeric() || matches!(character, '_' | '-' | '.'));
+
+    is_bare_key.then(|| key.to_string())
+}
+
+fn parse_value(value: &str) -> Option<TomlValue>
+{
+    if value.starts_with('"')
+    {
+        return parse_string(value).map(TomlValue::String);
+    }
+
+    if let Some(items) = value.strip_prefix('[').and_then(|value| value.strip_suffix(']'))
+    {
+        return split_array_items(items)
+            .iter()
+            .map(|item| item.trim())
+            .filter(|item| !item.is_empty())
+            .map(parse_value)
+            .collect::<Option<Vec<TomlValue>>>()
+            .map(TomlValue::Array);
+    }
+
+    match value
+    {
+        "true" => return Some(TomlValue::Boolean(true)),
+        "false" => return Some(TomlValue::Boolean(false)),
+        _ =>
+        {}
+    }
+
+    let number = value.replace('_', "");
+
+    if let Ok(integer) = number.parse()
+    {
+        return Some(TomlValue::Integer(integer));
+    }
+
+    number.parse().ok().map(TomlValue::Float)
+}
+
+fn parse_string(value: &str) -> Option<String>
+{
+    let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+    let mut result = String::new();
+    let mut characters = inner.chars();
+
+    while let Some(character) = characters.next()
+    {
+        match character
+        {
+            '\\' => match characters.next()?
+            {
+                'n' => result.push('\n'),
+                't' => result.push('\t'),
+                '"' => result.push('"'),
+                '\\' => result.push('\\'),
+                _ => return None,
+            },
+            '"' => return None,
+            _ => result.push(character),
+        }
+    }
+
+    Some(result)
+}
+
+/// Splits array items on commas outside of strings.
+fn split_array_items(items: &str) -> Vec<&str>
+{
+    let mut result = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut item_start = 0;
+
+    for (index, character) in items.char_indices()
+    {
+        match character
+        {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            ',' if !in_string =>
+            {
+                result.push(&items[item_start..index]);
+                item_start = index + 1;
+            }
+            _ =>
+            {}
+        }
+    }
+
+    result.push(&items[item_start..]);
+    result
+}
@@ -0,0 +1,279 @@
+use crate::config::DEFAULT_THEME_NAME;
+use crate::toml::{parse_toml, TomlValue};
+use ratatui::style::Color;
+use std::fs;
+use std::path::Path;
+
+const BUILT_IN_THEMES: [(&str, &str); 2] =
+    [("dark", include_str!("../../themes/dark.toml")), ("light", include_str!("../../themes/light.toml"))];
+
+/// Palette slots the interface draws with. The actual colors come from the active theme.
+#[derive(PartialEq, Copy, Clone)]
+pub enum ThemeColor
+{
+    BackgroundMain,
+    OutlineMain,
+    BackgroundPopup,
+    OutlinePopup,
+    TextMain,
+    TextPopup,
+    WindowShadow,
+    TextHighlight,
+    TextDim,
+    TextAccent,
+    TextError,
+    Intensity(usize),
+}
+
+pub const COL_BG_MAIN: ThemeColor = ThemeColor::BackgroundMain;
+pub const COL_OUTLINE_MAIN: ThemeColor = ThemeColor::OutlineMain;
+pub const COL_BG_POPUP: ThemeColor = ThemeColor::BackgroundPopup;
+pub const COL_OUTLINE_POPUP: ThemeColor = ThemeColor::OutlinePopup;
+pub const COL_TEXT_WHITE: ThemeColor = ThemeColor::TextMain;
+pub const COL_TEXT_BLACK: ThemeColor = ThemeColor::TextPopup;
+pub const COL_WINDOW_SHADOW: ThemeColor = ThemeColor::WindowShadow;
+pub const COL_TEXT_HIGHLIGHT: ThemeColor = ThemeColor::TextHighlight;
+pub const COL_TEXT_DIM: ThemeColor = ThemeColor::TextDim;
+pub const COL_TEXT_RED_DARK: ThemeColor = ThemeColor::TextAccent;
+pub const COL_TEXT_RED: ThemeColor = ThemeColor::TextError;
+
+/// Calendar cell backgrounds from no tracked time to a full day.
+pub const COL_INTENSITY: [ThemeColor; 5] =
+    [ThemeColor::Intensity(0), ThemeColor::Intensity(1), ThemeColor::Intensity(2), ThemeColor::Intensity(3), ThemeColor::Intensity(4)];
+
+/// A color as written in a theme: an index into the 256-color palette or a `"#rrggbb"` value.
+#[derive(PartialEq, Copy, Clone)]
+pub enum ColorValue
+{
+    Ansi(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl ColorValue
+{
+    /// The closest palette index, for terminals without true color support.
+    #[must_use]
+    pub fn to_ansi(self) -> u8
+    {
+        match self
+        {
+            ColorValue::Ansi(ansi_value) => ansi_value,
+            ColorValue::Rgb(red, green, blue) => get_nearest_ansi_value(red, green, blue),
+        }
+    }
+
+    /// The color to draw with, as RGB only where the terminal supports true color.
+    #[must_use]
+    pub fn to_color(self, true_color: bool) -> Color
+    {
+        match self
+        {
+            ColorValue::Rgb(red, green, blue) if true_color => Color::Rgb(red, green, blue),
+            _ => Color::Indexed(self.to_ansi()),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Theme
+{
+    pub name: String,
+    background_main: ColorValue,
+    outline_main: ColorValue,
+    background_popup: ColorValue,
+    outline_popup: ColorValue,
+    text_main: ColorValue,
+    text_popup: ColorValue,
+    window_shadow: ColorValue,
+    text_highlight: ColorValue,
+    text_dim: ColorValue,
+    text_accent: ColorValue,
+    text_error: ColorValue,
+    intensity: [ColorValue; 5],
+}
+
+impl Theme
+{
+    #[must_use]
+    pub fn get(&self, color: ThemeColor) -> ColorValue
+    {
+        match color
+        {
+            ThemeColor::BackgroundMain => self.background_main,
+            ThemeColor::OutlineMain => self.outline_main,
+            ThemeColor::BackgroundPopup => self.background_popup,
+            ThemeColor::OutlinePopup => self.outline_popup,
+            ThemeColor::TextMain => self.text_main,
+            ThemeColor::TextPopup => self.text_popup,
+            ThemeColor::WindowShadow => self.window_shadow,
+            ThemeColor::TextHighlight => self.text_highlight,
+            ThemeColor::TextDim => self.text_dim,
+            ThemeColor::TextAccent => self.text_accent,
+            ThemeColor::TextError => self.text_error,
+            ThemeColor::Intensity(level) => self.intensity[level.min(self.intensity.len() - 1)],
+        }
+    }
+
+    /// Reads a theme file. Keys it leaves out keep the values of `base`.
+    fn parse(name: &str, content: &str, base: &Theme) -> Result<Theme, String>
+    {
+        let mut theme = base.clone();
+        theme.name = name.to_string();
+
+        for table in parse_toml(content)?
+        {
+            for (key, value) in &table.entries
+            {
+                let invalid_value = || format!("INVALID THEME VALUE {}", key.to_uppercase());
+
+                if key == "intensity"
+                {
+                    let TomlValue::Array(values) = value
+                    else
+                    {
+                        return Err(invalid_value());
+                    };
+
+                    let levels = values.iter().map(get_color_value).collect::<Option<Vec<ColorValue>>>().ok_or_else(invalid_value)?;
+                    theme.intensity = levels.try_into().map_err(|_| invalid_value())?;
+                    continue;
+                }
+
+                let color = get_color_value(value).ok_or_else(invalid_value)?;
+
+                match key.as_str()
+                {
+                    "background_main" => theme.background_main = color,
+                    "outline_main" => theme.outline_main = color,
+                    "background_popup" => theme.background_popup = color,
+                    "outline_popup" => theme.outline_popup = color,
+                    "text_main" => theme.text_main = color,
+                    "text_popup" => theme.text_popup = color,
+                    "window_shadow" => theme.window_shadow = color,
+                    "text_highlight" => theme.text_highlight = color,
+                    "text_dim" => theme.text_dim = color,
+                    "text_accent" => theme.text_accent = color,
+                    "text_error" => theme.text_error = color,
+                    _ => return Err(format!("UNKNOWN THEME KEY {}", key.to_uppercase())),
+                }
+            }
+        }
+
+        Ok(theme)
+    }
+}
+
+impl Default for Theme
+{
+    fn default() -> Self
+    {
+        Theme {
+            name: DEFAULT_THEME_NAME.to_string(),
+            background_main: ColorValue::Ansi(19),
+            outline_main: ColorValue::Ansi(87),
+            background_popup: ColorValue::Ansi(248),
+            outline_popup: ColorValue::Ansi(16),
+            text_main: ColorValue::Ansi(255),
+            text_popup: ColorValue::Ansi(16),
+            window_shadow: ColorValue::Ansi(16),
+            text_highlight: ColorValue::Ansi(226),
+            text_dim: ColorValue::Ansi(73),
+            text_accent: ColorValue::Ansi(124),
+            text_error: ColorValue::Ansi(160),
+            intensity: [ColorValue::Ansi(19), ColorValue::Ansi(22), ColorValue::Ansi(28), ColorValue::Ansi(34), ColorValue::Ansi(40)],
+        }
+    }
+}
+
+fn get_color_value(value: &TomlValue) -> Option<ColorValue>
+{
+    match value
+    {
+        TomlValue::Integer(value) => u8::try_from(*value).ok().map(ColorValue::Ansi),
+        TomlValue::String(value) =>
+        {
+            let hex = value.strip_prefix('#').filter(|hex| hex.len() == 6 && hex.is_ascii())?;
+            let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+
+            Some(ColorValue::Rgb(channel(0)?, channel(2)?, channel(4)?))
+        }
+        _ => None,
+    }
+}
+
+/// Picks the closest entry of the 6x6x6 color cube or the grayscale ramp of the 256-color palette.
+fn get_nearest_ansi_value(red: u8, green: u8, blue: u8) -> u8
+{
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    let get_distance = |(r, g, b): (u8, u8, u8)| {
+        let channel_distance = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+        channel_distance(r, red) + channel_distance(g, green) + channel_distance(b, blue)
+    };
+    let get_nearest_level = |channel: u8| {
+        (0..CUBE_LEVELS.len()).min_by_key(|index| (i32::from(CUBE_LEVELS[*index]) - i32::from(channel)).abs()).unwrap_or(0)
+    };
+
+    let cube_index = [get_nearest_level(red), get_nearest_level(green), get_nearest_level(blue)];
+    let cube_color = (CUBE_LEVELS[cube_index[0]], CUBE_LEVELS[cube_index[1]], CUBE_LEVELS[cube_index[2]]);
+
+    let average = (u16::from(red) + u16::from(green) + u16::from(blue)) / 3;
+    let gray_index = u8::try_from((average.saturating_sub(3) / 10).min(23)).unwrap_or(23);
+    let gray_level = 8 + gray_index * 10;
+
+    if get_distance((gray_level, gray_level, gray_level)) < get_distance(cube_color)
+    {
+        return 232 + gray_index;
+    }
+
+    let [red_index, green_index, blue_index] = cube_index.map(|index| u8::try_from(index).unwrap_or(0));
+
+    16 + 36 * red_index + 6 * green_index + blue_index
+}
+
+/// The shipped themes followed by the `.toml` files in the themes folder, if any. A file named like a shipped theme
+/// replaces it. Files that fail to parse are skipped and reported in the returned errors.
+pub fn load_themes(themes_path: Option<&Path>) -> (Vec<Theme>, Vec<String>)
+{
+    let mut themes: Vec<Theme> = Vec::new();
+    let mut errors = Vec::new();
+
+    let mut sources = BUILT_IN_THEMES.iter().map(|(name, content)| ((*name).to_string(), (*content).to_string())).collect::<Vec<_>>();
+
+    if let Some(Ok(entries)) = themes_path.map(fs::read_dir)
+    {
+        let mut files = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+            .collect::<Vec<_>>();
+
+        files.sort();
+
+        for file in files
+        {
+            let Some(name) = file.file_stem().and_then(|name| name.to_str())
+            else
+            {
+                continue;
+            };
+
+            if let Ok(content) = fs::read_to_string(&file)
+            {
+                sources.retain(|(source_name, _)| source_name != name);
+                sources.push((name.to_string(), content));
+            }
+        }
+    }
+
+    for (name, content) in sources
+    {
+        match Theme::parse(&name, &content, &Theme::default())
+        {
+            Ok(theme) => themes.push(theme),
+            Err(error) => errors.push(format!("{}: {}", name.to_uppercase(), error)),
+        }
+    }
+
+    (themes, errors)
+}
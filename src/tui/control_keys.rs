@@ -0,0 +1,490 @@
+use crossterm::event::KeyCode;
+use crate::app_state::{
+    CommandState, ConfirmOpen, ExportState, ImportState, RunningEditField, SelectState, SessionEditState, SessionFieldEditState,
+    SessionInputState, SessionModifyState, TagInputState, WorkspaceState,
+};
+use crate::tui::io::Key;
+
+pub const KEY_NEW: Key = Key::new(KeyCode::Char('n'));
+pub const KEY_ADD: Key = Key::new(KeyCode::Char('a'));
+pub const KEY_DELETE: Key = Key::new(KeyCode::Char('d'));
+pub const KEY_END: Key = Key::new(KeyCode::Char(' '));
+pub const KEY_DISCARD: Key = Key::new(KeyCode::Char('k'));
+pub const KEY_EDIT: Key = Key::new(KeyCode::Char('e'));
+pub const KEY_EDIT_RUNNING: Key = Key::new(KeyCode::Char('r'));
+pub const KEY_COPY: Key = Key::new(KeyCode::Char('c'));
+pub const KEY_CONTINUE_LAST: Key = Key::new(KeyCode::Char('l'));
+pub const KEY_DUPLICATE: Key = Key::new(KeyCode::Char('p'));
+pub const KEY_SELECT: Key = Key::new(KeyCode::Char('v'));
+pub const KEY_MARK: Key = Key::new(KeyCode::Char(' '));
+pub const KEY_RETAG: Key = Key::new(KeyCode::Char('t'));
+pub const KEY_BACKUPS: Key = Key::new(KeyCode::Char('b'));
+pub const KEY_AUDIT_LOG: Key = Key::new(KeyCode::Char('h'));
+pub const KEY_IMPORT: Key = Key::new(KeyCode::Char('i'));
+pub const KEY_EXPORT: Key = Key::new(KeyCode::Char('x'));
+pub const KEY_COLLAPSE: Key = Key::new(KeyCode::Char('z'));
+pub const KEY_CALENDAR: Key = Key::new(KeyCode::Char('m'));
+pub const KEY_STATS: Key = Key::new(KeyCode::Char('s'));
+pub const KEY_DATABASE_STATS: Key = Key::new(KeyCode::Char('S'));
+pub const KEY_GOALS: Key = Key::new(KeyCode::Char('g'));
+pub const KEY_TEMPLATES: Key = Key::new(KeyCode::Char('T'));
+/// Marks the selected calendar day as vacation, holiday or sick, one after another.
+pub const KEY_DAY_OFF: Key = Key::new(KeyCode::Char('o'));
+pub const KEY_WEEK_REPORT: Key = Key::new(KeyCode::Char('w'));
+pub const KEY_GAPS: Key = Key::new(KeyCode::Char('u'));
+pub const KEY_SETTINGS: Key = Key::new(KeyCode::Char('o'));
+pub const KEY_WORKSPACES: Key = Key::new(KeyCode::Char('W'));
+pub const KEY_HELP: Key = Key::new(KeyCode::Char('?'));
+pub const KEY_HELP_ALT: Key = Key::new(KeyCode::F(1));
+pub const KEY_DEBUG_LOG: Key = Key::new(KeyCode::F(12));
+pub const KEY_QUIT: Key = Key::new(KeyCode::Char('q'));
+/// Quits the way Ctrl+C is expected to in a terminal, rather than copying like a plain `c`.
+pub const KEY_QUIT_ALT: Key = Key::ctrl(KeyCode::Char('c'));
+pub const KEY_READ_ONLY: Key = Key::new(KeyCode::Char('r'));
+pub const KEY_ENTER: Key = Key::new(KeyCode::Enter);
+pub const KEY_TAB: Key = Key::new(KeyCode::Tab);
+pub const KEY_BACK_TAB: Key = Key::shift(KeyCode::BackTab);
+pub const KEY_YES: Key = Key::new(KeyCode::Char('y'));
+/// Answers yes and stops asking about that kind of action.
+pub const KEY_YES_ALWAYS: Key = Key::new(KeyCode::Char('Y'));
+pub const KEY_NO: Key = Key::new(KeyCode::Char('n'));
+pub const KEY_ADJUST: Key = Key::new(KeyCode::Char('a'));
+pub const KEY_UP: Key = Key::new(KeyCode::Up);
+pub const KEY_DOWN: Key = Key::new(KeyCode::Down);
+pub const KEY_LEFT: Key = Key::new(KeyCode::Left);
+pub const KEY_RIGHT: Key = Key::new(KeyCode::Right);
+pub const KEY_PAGE_UP: Key = Key::new(KeyCode::PageUp);
+pub const KEY_PAGE_DOWN: Key = Key::new(KeyCode::PageDown);
+pub const KEY_BACKSPACE: Key = Key::new(KeyCode::Backspace);
+pub const KEY_DELETE_FORWARD: Key = Key::new(KeyCode::Delete);
+pub const KEY_LINE_START: Key = Key::new(KeyCode::Home);
+pub const KEY_LINE_END: Key = Key::new(KeyCode::End);
+pub const KEY_DELETE_WORD: Key = Key::ctrl(KeyCode::Char('w'));
+pub const KEY_DELETE_WORD_ALT: Key = Key::alt(KeyCode::Backspace);
+pub const KEY_NOTES: Key = Key::new(KeyCode::Char('N'));
+pub const KEY_EXTERNAL_EDITOR: Key = Key::new(KeyCode::Char('E'));
+pub const KEY_CLIPBOARD: Key = Key::new(KeyCode::Char('y'));
+pub const KEY_FOCUS: Key = Key::new(KeyCode::Char('f'));
+pub const KEY_ESCAPE: Key = Key::new(KeyCode::Esc);
+
+/// Idle commands that modify the database and are therefore unavailable in read-only mode.
+pub const READ_ONLY_BLOCKED_KEYS: [Key; 14] = [
+    KEY_NEW,
+    KEY_ADD,
+    KEY_EDIT,
+    KEY_EDIT_RUNNING,
+    KEY_DELETE,
+    KEY_COPY,
+    KEY_CONTINUE_LAST,
+    KEY_DUPLICATE,
+    KEY_SELECT,
+    KEY_BACKUPS,
+    KEY_IMPORT,
+    KEY_END,
+    KEY_DISCARD,
+    KEY_TEMPLATES,
+];
+
+/// Blocked idle commands a read-only instance hands to the daemon, which owns the running session.
+pub const DAEMON_KEYS: [Key; 3] = [KEY_NEW, KEY_CONTINUE_LAST, KEY_END];
+
+pub type Controls = Vec<Control>;
+
+/// Idle commands, most important first. The bottom bar drops them from the end when space runs out.
+#[must_use]
+pub fn get_controls() -> Vec<Control>
+{
+    vec![
+        Control::new(KEY_NEW, "new"),
+        Control::new(KEY_ADD, "add"),
+        Control::new(KEY_EDIT, "edit"),
+        Control::new(KEY_DELETE, "delete"),
+        Control::new(KEY_COPY, "copy"),
+        Control::new(KEY_CONTINUE_LAST, "continue last"),
+        Control::new(KEY_DUPLICATE, "duplicate"),
+        Control::new(KEY_SELECT, "select"),
+        Control::new(KEY_END, "end"),
+        Control::new(KEY_DISCARD, "discard"),
+        Control::new(KEY_EDIT_RUNNING, "edit running"),
+        Control::new(KEY_FOCUS, "focus"),
+        Control::new(KEY_QUIT, "quit"),
+        Control::new(KEY_BACKUPS, "backups"),
+        Control::new(KEY_AUDIT_LOG, "history"),
+        Control::new(KEY_IMPORT, "import"),
+        Control::new(KEY_EXPORT, "export"),
+        Control::new(KEY_CALENDAR, "month"),
+        Control::new(KEY_STATS, "stats"),
+        Control::new(KEY_DATABASE_STATS, "database"),
+        Control::new(KEY_GOALS, "goals"),
+        Control::new(KEY_TEMPLATES, "templates"),
+        Control::new(KEY_WEEK_REPORT, "weeks"),
+        Control::new(KEY_GAPS, "gaps"),
+        Control::new(KEY_SETTINGS, "settings"),
+        Control::new(KEY_WORKSPACES, "workspaces"),
+        Control::new(KEY_COLLAPSE, "fold"),
+        Control::new(KEY_CLIPBOARD, "copy list"),
+        Control::new(KEY_DEBUG_LOG, "debug log"),
+        Control::new(KEY_HELP, "help"),
+    ]
+}
+
+/// Every key the given state reacts to, in the order they are listed in the help overlay. Keys sharing a description
+/// are shown on one line.
+#[allow(clippy::too_many_lines)]
+#[must_use]
+pub fn get_state_controls(state: &CommandState) -> Controls
+{
+    let confirm = vec![Control::new(KEY_YES, "yes"), Control::new(KEY_NO, "no"), Control::new(KEY_ESCAPE, "no")];
+    // Questions that can be turned off in `[confirmations]`.
+    let skippable_confirm = vec![
+        Control::new(KEY_YES, "yes"),
+        Control::new(KEY_YES_ALWAYS, "yes, don't ask again"),
+        Control::new(KEY_NO, "no"),
+        Control::new(KEY_ESCAPE, "no"),
+    ];
+    // Moving the cursor and deleting in a line of text.
+    let text_editing = || {
+        vec![
+            Control::new(KEY_LEFT, "move cursor"),
+            Control::new(KEY_RIGHT, "move cursor"),
+            Control::new(KEY_LINE_START, "line start"),
+            Control::new(KEY_LINE_END, "line end"),
+            Control::new(KEY_BACKSPACE, "delete character"),
+            Control::new(KEY_DELETE_FORWARD, "delete next character"),
+            Control::new(KEY_DELETE_WORD, "delete word"),
+            Control::new(KEY_DELETE_WORD_ALT, "delete word"),
+        ]
+    };
+    let list_selection = |action: &str| {
+        vec![
+            Control::new(KEY_UP, "select session"),
+            Control::new(KEY_DOWN, "select session"),
+            Control::new(KEY_COLLAPSE, "fold day"),
+            Control::new(KEY_ENTER, action),
+            Control::new(KEY_ESCAPE, "cancel"),
+        ]
+    };
+
+    match state
+    {
+        CommandState::Idle =>
+        {
+            let mut controls = get_controls();
+            controls.push(Control::new(KEY_QUIT_ALT, "quit"));
+            controls
+        }
+        CommandState::New(
+            SessionInputState::Description(ConfirmOpen::Yes) | SessionInputState::Tag(TagInputState::Delete(ConfirmOpen::Yes)),
+        )
+        | CommandState::Modify(SessionModifyState::Continue(ConfirmOpen::Yes))
+        | CommandState::AutoStop
+        | CommandState::DiscardShort
+        | CommandState::RestoreEdit => confirm,
+        CommandState::Backups(ConfirmOpen::Yes)
+        | CommandState::Modify(
+            SessionModifyState::Delete(ConfirmOpen::Yes)
+            | SessionModifyState::Select(SelectState::Delete | SelectState::Retag(ConfirmOpen::Yes)),
+        )
+        | CommandState::End
+        | CommandState::Discard
+        | CommandState::Quitting => skippable_confirm,
+        CommandState::New(SessionInputState::Description(ConfirmOpen::No)) =>
+        {
+            let mut controls = vec![
+                Control::new(KEY_UP, "select suggestion"),
+                Control::new(KEY_DOWN, "select suggestion"),
+                Control::new(KEY_ENTER, "start session or use suggestion"),
+                Control::new(KEY_TAB, "next field"),
+                Control::new(KEY_BACK_TAB, "previous field"),
+            ];
+
+            controls.extend(text_editing());
+            controls.push(Control::new(KEY_ESCAPE, "cancel"));
+            controls
+        }
+        CommandState::New(SessionInputState::Tag(TagInputState::Select)) => vec![
+            Control::new(KEY_UP, "select tag"),
+            Control::new(KEY_DOWN, "select tag"),
+            Control::new(KEY_ENTER, "use tag or create typed one"),
+            Control::new(KEY_BACKSPACE, "delete filter character"),
+            Control::new(KEY_TAB, "next field"),
+            Control::new(KEY_BACK_TAB, "previous field"),
+            Control::new(KEY_ESCAPE, "clear filter or back"),
+        ],
+        CommandState::New(SessionInputState::Tag(TagInputState::New)) =>
+        {
+            let mut controls = vec![Control::new(KEY_ENTER, "save tag")];
+
+            controls.extend(text_editing());
+            controls.push(Control::new(KEY_ESCAPE, "back"));
+            controls
+        }
+        CommandState::New(SessionInputState::Tag(TagInputState::Delete(ConfirmOpen::No))) =>
+        {
+            vec![Control::new(KEY_ESCAPE, "back")]
+        }
+        CommandState::New(SessionInputState::Start) => vec![
+            Control::new(KEY_ENTER, "confirm start time"),
+            Control::new(KEY_TAB, "next field"),
+            Control::new(KEY_BACK_TAB, "previous field"),
+            Control::new(KEY_BACKSPACE, "delete character"),
+            Control::new(KEY_ESCAPE, "back"),
+        ],
+        CommandState::New(SessionInputState::Timer) => vec![
+            Control::new(KEY_ENTER, "confirm timer"),
+            Control::new(KEY_TAB, "next field"),
+            Control::new(KEY_BACK_TAB, "previous field"),
+            Control::new(KEY_BACKSPACE, "delete character"),
+            Control::new(KEY_ESCAPE, "no timer"),
+        ],
+        CommandState::Add(_) => vec![
+            Control::new(KEY_TAB, "next field"),
+            Control::new(KEY_DOWN, "next field"),
+            Control::new(KEY_UP, "previous field"),
+            Control::new(KEY_BACK_TAB, "previous field"),
+            Control::new(KEY_LEFT, "previous tag"),
+            Control::new(KEY_RIGHT, "next tag"),
+            Control::new(KEY_BACKSPACE, "delete character"),
+            Control::new(KEY_ENTER, "save"),
+            Control::new(KEY_ESCAPE, "cancel"),
+        ],
+        CommandState::EditRunning(field) =>
+        {
+            let mut controls = vec![
+                Control::new(KEY_TAB, "next field"),
+                Control::new(KEY_BACK_TAB, "previous field"),
+                Control::new(KEY_DOWN, "next field"),
+            ];
+
+            if *field == RunningEditField::Tag
+            {
+                controls.push(Control::new(KEY_LEFT, "previous tag"));
+                controls.push(Control::new(KEY_RIGHT, "next tag"));
+            }
+            else
+            {
+                controls.push(Control::new(KEY_BACKSPACE, "delete character"));
+            }
+
+            controls.push(Control::new(KEY_ENTER, "save"));
+            controls.push(Control::new(KEY_ESCAPE, "cancel"));
+            controls
+        }
+        CommandState::Overlap(_) => vec![
+            Control::new(KEY_YES, "save anyway"),
+            Control::new(KEY_ADJUST, "adjust to fit"),
+            Control::new(KEY_NO, "back"),
+            Control::new(KEY_ESCAPE, "back"),
+        ],
+        CommandState::Backups(ConfirmOpen::No) => vec![
+            Control::new(KEY_UP, "select backup"),
+            Control::new(KEY_DOWN, "select backup"),
+            Control::new(KEY_ENTER, "restore"),
+            Control::new(KEY_ESCAPE, "close"),
+        ],
+        CommandState::AuditLog => vec![
+            Control::new(KEY_UP, "select change"),
+            Control::new(KEY_DOWN, "select change"),
+            Control::new(KEY_ESCAPE, "close"),
+            Control::new(KEY_AUDIT_LOG, "close"),
+        ],
+        CommandState::Workspaces(WorkspaceState::Browse) => vec![
+            Control::new(KEY_UP, "select workspace"),
+            Control::new(KEY_DOWN, "select workspace"),
+            Control::new(KEY_ENTER, "open"),
+            Control::new(KEY_NEW, "new workspace"),
+            Control::new(KEY_ESCAPE, "close"),
+        ],
+        CommandState::Workspaces(WorkspaceState::New) => vec![
+            Control::new(KEY_ENTER, "create and open"),
+            Control::new(KEY_BACKSPACE, "delete character"),
+            Control::new(KEY_ESCAPE, "back"),
+        ],
+        CommandState::ImportReport | CommandState::Import(ImportState::Summary) =>
+        {
+            vec![Control::new(KEY_ENTER, "close"), Control::new(KEY_ESCAPE, "close")]
+        }
+        CommandState::Import(ImportState::Path) => vec![
+            Control::new(KEY_ENTER, "import"),
+            Control::new(KEY_BACKSPACE, "delete character"),
+            Control::new(KEY_ESCAPE, "cancel"),
+        ],
+        CommandState::Import(ImportState::Mapping(_)) => vec![
+            Control::new(KEY_TAB, "next field"),
+            Control::new(KEY_DOWN, "next field"),
+            Control::new(KEY_UP, "previous field"),
+            Control::new(KEY_BACK_TAB, "previous field"),
+            Control::new(KEY_LEFT, "change column"),
+            Control::new(KEY_RIGHT, "change column"),
+            Control::new(KEY_ENTER, "import"),
+            Control::new(KEY_ESCAPE, "back"),
+        ],
+        CommandState::Export(ExportState::Form(_)) => vec![
+            Control::new(KEY_TAB, "next field"),
+            Control::new(KEY_DOWN, "next field"),
+            Control::new(KEY_UP, "previous field"),
+            Control::new(KEY_BACK_TAB, "previous field"),
+            Control::new(KEY_BACKSPACE, "delete character"),
+            Control::new(KEY_ENTER, "export"),
+            Control::new(KEY_ESCAPE, "cancel"),
+        ],
+        CommandState::Calendar => vec![
+            Control::new(KEY_LEFT, "previous day"),
+            Control::new(KEY_RIGHT, "next day"),
+            Control::new(KEY_UP, "previous week"),
+            Control::new(KEY_DOWN, "next week"),
+            Control::new(KEY_PAGE_UP, "previous month"),
+            Control::new(KEY_PAGE_DOWN, "next month"),
+            Control::new(KEY_DAY_OFF, "mark day off"),
+            Control::new(KEY_ENTER, "open day"),
+            Control::new(KEY_ESCAPE, "close"),
+            Control::new(KEY_CALENDAR, "close"),
+        ],
+        CommandState::Stats => vec![
+            Control::new(KEY_LEFT, "previous period type"),
+            Control::new(KEY_RIGHT, "next period type"),
+            Control::new(KEY_PAGE_UP, "earlier period"),
+            Control::new(KEY_PAGE_DOWN, "later period"),
+            Control::new(KEY_EXPORT, "write daily notes"),
+            Control::new(KEY_ESCAPE, "close"),
+            Control::new(KEY_STATS, "close"),
+        ],
+        CommandState::DatabaseStats => vec![Control::new(KEY_ESCAPE, "close"), Control::new(KEY_DATABASE_STATS, "close")],
+        CommandState::Goals =>
+        {
+            vec![Control::new(KEY_ENTER, "close"), Control::new(KEY_ESCAPE, "close"), Control::new(KEY_GOALS, "close")]
+        }
+        CommandState::Templates => vec![
+            Control::new(KEY_UP, "select template"),
+            Control::new(KEY_DOWN, "select template"),
+            Control::new(KEY_ENTER, "start"),
+            Control::new(Key::new(KeyCode::Char('1')), "start template by number"),
+            Control::new(KEY_ESCAPE, "close"),
+            Control::new(KEY_TEMPLATES, "close"),
+        ],
+        CommandState::Focus => vec![Control::new(KEY_FOCUS, "leave focus"), Control::new(KEY_ESCAPE, "leave focus")],
+        CommandState::WeekReport => vec![
+            Control::new(KEY_LEFT, "previous week"),
+            Control::new(KEY_PAGE_UP, "previous week"),
+            Control::new(KEY_RIGHT, "next week"),
+            Control::new(KEY_PAGE_DOWN, "next week"),
+            Control::new(KEY_CLIPBOARD, "copy report"),
+            Control::new(KEY_ESCAPE, "close"),
+            Control::new(KEY_WEEK_REPORT, "close"),
+        ],
+        CommandState::Gaps => vec![
+            Control::new(KEY_LEFT, "previous day"),
+            Control::new(KEY_RIGHT, "next day"),
+            Control::new(KEY_UP, "select gap"),
+            Control::new(KEY_DOWN, "select gap"),
+            Control::new(KEY_ENTER, "fill gap"),
+            Control::new(KEY_ESCAPE, "close"),
+            Control::new(KEY_GAPS, "close"),
+        ],
+        CommandState::Settings(_) => vec![
+            Control::new(KEY_UP, "select setting"),
+            Control::new(KEY_DOWN, "select setting"),
+            Control::new(KEY_LEFT, "previous value"),
+            Control::new(KEY_RIGHT, "next value"),
+            Control::new(KEY_ENTER, "save"),
+            Control::new(KEY_ESCAPE, "cancel"),
+        ],
+        CommandState::Locked =>
+        {
+            vec![
+                Control::new(KEY_READ_ONLY, "open read-only"),
+                Control::new(KEY_WORKSPACES, "other workspace"),
+                Control::new(KEY_QUIT, "quit"),
+                Control::new(KEY_QUIT_ALT, "quit"),
+                Control::new(KEY_ESCAPE, "quit"),
+            ]
+        }
+        CommandState::Modify(SessionModifyState::Edit(SessionEditState::Browse)) => list_selection("show details"),
+        CommandState::Modify(SessionModifyState::Edit(SessionEditState::Details)) =>
+        {
+            vec![
+                Control::new(KEY_ENTER, "edit session"),
+                Control::new(KEY_NOTES, "edit notes"),
+                Control::new(KEY_EXTERNAL_EDITOR, "open in $EDITOR"),
+                Control::new(KEY_CLIPBOARD, "copy to clipboard"),
+                Control::new(KEY_ESCAPE, "back"),
+            ]
+        }
+        CommandState::Modify(SessionModifyState::Edit(SessionEditState::Notes(ConfirmOpen::No))) => vec![
+            Control::new(KEY_UP, "move cursor"),
+            Control::new(KEY_DOWN, "move cursor"),
+            Control::new(KEY_LEFT, "move cursor"),
+            Control::new(KEY_RIGHT, "move cursor"),
+            Control::new(KEY_LINE_START, "line start"),
+            Control::new(KEY_LINE_END, "line end"),
+            Control::new(KEY_ENTER, "new line"),
+            Control::new(KEY_BACKSPACE, "delete character"),
+            Control::new(KEY_DELETE_FORWARD, "delete next character"),
+            Control::new(KEY_DELETE_WORD, "delete word"),
+            Control::new(KEY_DELETE_WORD_ALT, "delete word"),
+            Control::new(KEY_ESCAPE, "done"),
+        ],
+        CommandState::Modify(SessionModifyState::Edit(SessionEditState::Notes(ConfirmOpen::Yes))) =>
+        {
+            vec![Control::new(KEY_YES, "save notes"), Control::new(KEY_NO, "discard notes"), Control::new(KEY_ESCAPE, "back")]
+        }
+        CommandState::Modify(SessionModifyState::Continue(ConfirmOpen::No)) => list_selection("continue session"),
+        CommandState::Modify(SessionModifyState::Duplicate) => list_selection("duplicate session"),
+        CommandState::Modify(SessionModifyState::Delete(ConfirmOpen::No)) => list_selection("delete session"),
+        CommandState::Modify(SessionModifyState::Select(SelectState::Browse)) => vec![
+            Control::new(KEY_UP, "select session"),
+            Control::new(KEY_DOWN, "select session"),
+            Control::new(KEY_MARK, "mark session"),
+            Control::new(KEY_COLLAPSE, "fold day"),
+            Control::new(KEY_DELETE, "delete marked"),
+            Control::new(KEY_RETAG, "retag marked"),
+            Control::new(KEY_EXPORT, "export marked"),
+            Control::new(KEY_ESCAPE, "cancel"),
+        ],
+        CommandState::Modify(SessionModifyState::Select(SelectState::Retag(ConfirmOpen::No))) => vec![
+            Control::new(KEY_UP, "select tag"),
+            Control::new(KEY_DOWN, "select tag"),
+            Control::new(KEY_ENTER, "use tag"),
+            Control::new(KEY_ESCAPE, "back"),
+        ],
+        CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(SessionFieldEditState::Browse))) => vec![
+            Control::new(KEY_LEFT, "previous field"),
+            Control::new(KEY_RIGHT, "next field"),
+            Control::new(KEY_TAB, "next field"),
+            Control::new(KEY_BACK_TAB, "previous field"),
+            Control::new(KEY_ENTER, "edit field"),
+            Control::new(KEY_ESCAPE, "finish editing"),
+        ],
+        CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(SessionFieldEditState::Editing))) => vec![
+            Control::new(KEY_UP, "change value"),
+            Control::new(KEY_DOWN, "change value"),
+            Control::new(KEY_LEFT, "previous segment"),
+            Control::new(KEY_RIGHT, "next segment"),
+            Control::new(KEY_BACKSPACE, "delete character"),
+            Control::new(KEY_ENTER, "confirm"),
+            Control::new(KEY_ESCAPE, "cancel"),
+        ],
+        CommandState::Modify(SessionModifyState::Edit(SessionEditState::Confirm)) =>
+        {
+            vec![Control::new(KEY_YES, "save changes"), Control::new(KEY_NO, "discard changes"), Control::new(KEY_ESCAPE, "back")]
+        }
+    }
+}
+
+pub struct Control
+{
+    pub key: Key,
+    pub description: String,
+}
+
+impl Control
+{
+    #[must_use]
+    pub fn new(key: Key, description: &str) -> Self
+    {
+        Control {
+            key,
+            description: description.to_string(),
+        }
+    }
+}
@@ -0,0 +1,2226 @@
+//! Key presses, clicks and pastes turned into changes of the app state, the state machine behind every screen.
+
+use crate::app_manager::AppManager;
+use crate::app_state::*;
+use crate::config::ConfirmAction;
+use crate::report::get_week_report_text;
+use crate::text::get_pasted_line;
+use crate::text_input::TextInput;
+use crate::time_input::{edit_date, edit_time, Step};
+use crate::tui::control_keys::*;
+use crate::tui::io::Key;
+use chrono::TimeDelta;
+use crossterm::event::{Event, KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use std::cmp;
+
+/// Applies `event` to the app: key presses and clicks move the open screen along, pastes are typed into the field being
+/// edited.
+#[allow(clippy::too_many_lines)]
+pub fn update(app_manager: &mut AppManager, event: &Event)
+{
+    if let Event::Paste(text) = event
+    {
+        paste(app_manager, text);
+        return;
+    }
+
+    if let Some(key) = get_user_key(app_manager, event)
+    {
+        let value_separator = app_manager.get_value_separator();
+        app_manager.clipboard_message = None;
+
+        if key == KEY_DEBUG_LOG
+        {
+            app_manager.debug_log_open = !app_manager.debug_log_open;
+            return;
+        }
+
+        if app_manager.help_open
+        {
+            if key == KEY_ESCAPE || key == KEY_ENTER || key == KEY_HELP || key == KEY_HELP_ALT
+            {
+                app_manager.help_open = false;
+            }
+
+            return;
+        }
+
+        if key == KEY_HELP_ALT || (key == KEY_HELP && !app_manager.state.is_text_input())
+        {
+            app_manager.help_open = true;
+            return;
+        }
+
+        match app_manager.state.clone()
+        {
+            CommandState::Idle
+                if app_manager.is_read_only()
+                    && READ_ONLY_BLOCKED_KEYS.contains(&key)
+                    && !(app_manager.daemon_connected && DAEMON_KEYS.contains(&key)) =>
+            {
+                app_manager.refuse_read_only_change();
+            }
+            CommandState::Idle => match key
+            {
+                KEY_NEW =>
+                {
+                    app_manager.detect_git_branch();
+                    app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
+                }
+                KEY_ADD =>
+                {
+                    app_manager.open_manual_entry();
+                    app_manager.state = CommandState::Add(ManualEntryField::Description);
+                }
+                KEY_EDIT =>
+                {
+                    if let Some(last_session_index) = app_manager.sessions.len().checked_sub(1)
+                    {
+                        app_manager.selected_session_index = last_session_index;
+                        app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Browse));
+                    }
+                }
+                KEY_COPY =>
+                {
+                    if let Some(last_session_index) = app_manager.sessions.len().checked_sub(1)
+                    {
+                        app_manager.selected_session_index = last_session_index;
+                        app_manager.state = CommandState::Modify(SessionModifyState::Continue(ConfirmOpen::No));
+                    }
+                }
+                KEY_DELETE =>
+                {
+                    if let Some(last_session_index) = app_manager.sessions.len().checked_sub(1)
+                    {
+                        app_manager.selected_session_index = last_session_index;
+                        app_manager.state = CommandState::Modify(SessionModifyState::Delete(ConfirmOpen::No));
+                    }
+                }
+                KEY_CONTINUE_LAST =>
+                {
+                    if app_manager.select_last_finished_session()
+                    {
+                        app_manager.state = CommandState::Modify(SessionModifyState::Continue(ConfirmOpen::Yes));
+                    }
+                }
+                KEY_DUPLICATE =>
+                {
+                    if let Some(last_session_index) = app_manager.sessions.len().checked_sub(1)
+                    {
+                        app_manager.selected_session_index = last_session_index;
+                        app_manager.state = CommandState::Modify(SessionModifyState::Duplicate);
+                    }
+                }
+                KEY_SELECT =>
+                {
+                    if app_manager.open_selection()
+                    {
+                        app_manager.state = CommandState::Modify(SessionModifyState::Select(SelectState::Browse));
+                    }
+                }
+                KEY_EXPORT =>
+                {
+                    app_manager.open_export_form();
+                    app_manager.state = CommandState::Export(ExportState::Form(ExportField::From));
+                }
+                KEY_IMPORT =>
+                {
+                    app_manager.import_error = None;
+                    app_manager.state = CommandState::Import(ImportState::Path);
+                }
+                KEY_COLLAPSE =>
+                {
+                    app_manager.toggle_all_days_collapsed();
+                }
+                KEY_CLIPBOARD =>
+                {
+                    let list_text = app_manager.get_list_text();
+                    app_manager.copy_to_clipboard(&list_text);
+                }
+                KEY_CALENDAR =>
+                {
+                    app_manager.open_calendar();
+                    app_manager.state = CommandState::Calendar;
+                }
+                KEY_STATS =>
+                {
+                    app_manager.open_stats();
+                    app_manager.state = CommandState::Stats;
+                }
+                KEY_DATABASE_STATS =>
+                {
+                    app_manager.open_database_stats();
+                    app_manager.state = CommandState::DatabaseStats;
+                }
+                KEY_GOALS =>
+                {
+                    app_manager.load_all_sessions();
+                    app_manager.state = CommandState::Goals;
+                }
+                KEY_TEMPLATES =>
+                {
+                    app_manager.selected_template_index = 0;
+                    app_manager.state = CommandState::Templates;
+                }
+                KEY_WEEK_REPORT =>
+                {
+                    app_manager.open_week_report();
+                    app_manager.state = CommandState::WeekReport;
+                }
+                KEY_FOCUS =>
+                {
+                    app_manager.renderer.clear_screen();
+                    app_manager.state = CommandState::Focus;
+                }
+                KEY_GAPS =>
+                {
+                    app_manager.open_gaps();
+                    app_manager.state = CommandState::Gaps;
+                }
+                KEY_SETTINGS =>
+                {
+                    app_manager.open_settings();
+                    app_manager.state = CommandState::Settings(SettingsField::Theme);
+                }
+                KEY_BACKUPS =>
+                {
+                    app_manager.refresh_backups();
+                    app_manager.state = CommandState::Backups(ConfirmOpen::No);
+                }
+                KEY_AUDIT_LOG =>
+                {
+                    app_manager.open_audit_log();
+                    app_manager.state = CommandState::AuditLog;
+                }
+                KEY_WORKSPACES =>
+                {
+                    app_manager.open_workspaces();
+                    app_manager.state = CommandState::Workspaces(WorkspaceState::Browse);
+                }
+                KEY_EDIT_RUNNING =>
+                {
+                    if app_manager.open_running_session_edit()
+                    {
+                        app_manager.state = CommandState::EditRunning(RunningEditField::Description);
+                    }
+                }
+                KEY_END =>
+                {
+                    if app_manager.is_last_session_still_running() && app_manager.config.asks_to(ConfirmAction::End)
+                    {
+                        app_manager.state = CommandState::End;
+                    }
+                    else if app_manager.is_last_session_still_running()
+                    {
+                        end_session(app_manager);
+                    }
+                }
+                KEY_DISCARD =>
+                {
+                    if app_manager.is_last_session_still_running() && app_manager.config.asks_to(ConfirmAction::Discard)
+                    {
+                        app_manager.state = CommandState::Discard;
+                    }
+                    else if app_manager.is_last_session_still_running()
+                    {
+                        app_manager.discard_running_session();
+                    }
+                }
+                KEY_QUIT | KEY_QUIT_ALT =>
+                {
+                    if app_manager.config.asks_to(ConfirmAction::Quit)
+                    {
+                        app_manager.state = CommandState::Quitting;
+                    }
+                    else
+                    {
+                        quit(app_manager);
+                    }
+                }
+                _ =>
+                {}
+            },
+            CommandState::New(input_field) => match input_field
+            {
+                SessionInputState::Description(confirm_end_previous) => match confirm_end_previous
+                {
+                    ConfirmOpen::Yes =>
+                    {
+                        if key == KEY_YES
+                        {
+                            if let Some(start) = app_manager.get_new_session_start()
+                            {
+                                app_manager.end_running_session_at(start);
+                            }
+
+                            app_manager.try_start_new_session();
+                            app_manager.state = CommandState::Idle;
+                        }
+                        else if key == KEY_NO || key == KEY_ESCAPE
+                        {
+                            app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
+                        }
+                    }
+                    ConfirmOpen::No => match key
+                    {
+                        KEY_ESCAPE =>
+                        {
+                            app_manager.selected_suggestion_index = None;
+                            app_manager.state = CommandState::Idle;
+                        }
+                        KEY_UP =>
+                        {
+                            app_manager.move_suggestion_selection(true);
+                        }
+                        KEY_DOWN =>
+                        {
+                            app_manager.move_suggestion_selection(false);
+                        }
+                        KEY_ENTER if app_manager.apply_selected_suggestion() =>
+                        {}
+                        KEY_ENTER =>
+                        {
+                            start_new_session(app_manager);
+                        }
+                        KEY_TAB | KEY_BACK_TAB =>
+                        {
+                            cycle_new_session_field(app_manager, key == KEY_TAB);
+                        }
+                        _ if edit_text_input(&mut app_manager.description_buffer, key, value_separator) =>
+                        {
+                            app_manager.selected_suggestion_index = None;
+                        }
+                        _ =>
+                        {}
+                    },
+                },
+                SessionInputState::Tag(edit_state) => match edit_state
+                {
+                    TagInputState::Select => match key
+                    {
+                        KEY_ESCAPE =>
+                        {
+                            if app_manager.tag_filter.is_empty()
+                            {
+                                app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
+                            }
+
+                            app_manager.tag_filter.clear();
+                        }
+                        KEY_UP =>
+                        {
+                            app_manager.move_tag_selection(true);
+                        }
+                        KEY_DOWN =>
+                        {
+                            app_manager.move_tag_selection(false);
+                        }
+                        KEY_ENTER =>
+                        {
+                            if app_manager.snap_tag_selection_to_filter()
+                            {
+                                app_manager.set_selected_tag_index(app_manager.temp_tag_index);
+                                app_manager.apply_tag_template();
+                                app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
+                            }
+                            else
+                            {
+                                app_manager.tag_buffer.clone_from(&app_manager.tag_filter);
+                                app_manager.state = CommandState::New(SessionInputState::Tag(TagInputState::New));
+                            }
+
+                            app_manager.tag_filter.clear();
+                        }
+                        KEY_TAB | KEY_BACK_TAB =>
+                        {
+                            cycle_new_session_field(app_manager, key == KEY_TAB);
+                        }
+                        _ if edit_text_input(&mut app_manager.tag_filter, key, value_separator) =>
+                        {
+                            app_manager.snap_tag_selection_to_filter();
+                        }
+                        _ =>
+                        {}
+                    },
+                    TagInputState::New => match key
+                    {
+                        KEY_ESCAPE =>
+                        {
+                            app_manager.state = CommandState::New(SessionInputState::Tag(TagInputState::Select));
+                        }
+                        KEY_ENTER =>
+                        {
+                            app_manager.try_store_tag();
+                            app_manager.apply_tag_template();
+                            app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
+                        }
+                        _ =>
+                        {
+                            edit_text_input(&mut app_manager.tag_buffer, key, value_separator);
+                        }
+                    },
+                    TagInputState::Delete(_) =>
+                    {}
+                },
+                SessionInputState::Start => match key
+                {
+                    KEY_ESCAPE =>
+                    {
+                        app_manager.start_buffer.clear();
+                        app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
+                    }
+                    KEY_BACKSPACE =>
+                    {
+                        app_manager.start_buffer.pop();
+                    }
+                    KEY_ENTER =>
+                    {
+                        if app_manager.get_new_session_start().is_some()
+                        {
+                            app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
+                        }
+                    }
+                    KEY_TAB | KEY_BACK_TAB =>
+                    {
+                        cycle_new_session_field(app_manager, key == KEY_TAB);
+                    }
+                    _ if let Some(character) = key.get_typed_char() =>
+                    {
+                        app_manager.start_buffer.push(character);
+                    }
+                    _ =>
+                    {}
+                },
+                SessionInputState::Timer => match key
+                {
+                    KEY_ESCAPE =>
+                    {
+                        app_manager.timer_buffer.clear();
+                        app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
+                    }
+                    KEY_BACKSPACE =>
+                    {
+                        app_manager.timer_buffer.pop();
+                    }
+                    KEY_ENTER =>
+                    {
+                        if app_manager.is_new_session_timer_valid()
+                        {
+                            app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
+                        }
+                    }
+                    KEY_TAB | KEY_BACK_TAB =>
+                    {
+                        cycle_new_session_field(app_manager, key == KEY_TAB);
+                    }
+                    _ if let Some(character) = key.get_typed_char() =>
+                    {
+                        app_manager.timer_buffer.push(character);
+                    }
+                    _ =>
+                    {}
+                },
+            },
+            CommandState::Add(selected_field) => match key
+            {
+                KEY_ESCAPE =>
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+                KEY_ENTER =>
+                {
+                    if app_manager.stage_manual_session()
+                    {
+                        if app_manager.pending_session_has_overlaps()
+                        {
+                            app_manager.state = CommandState::Overlap(OverlapOrigin::Add);
+                        }
+                        else
+                        {
+                            app_manager.add_pending_session();
+                            app_manager.state = CommandState::Idle;
+                        }
+                    }
+                }
+                KEY_TAB | KEY_DOWN =>
+                {
+                    app_manager.state = CommandState::Add(selected_field.next());
+                }
+                KEY_UP | KEY_BACK_TAB =>
+                {
+                    app_manager.state = CommandState::Add(selected_field.previous());
+                }
+                KEY_LEFT =>
+                {
+                    if selected_field == ManualEntryField::Tag && app_manager.manual_entry.tag_index > 0
+                    {
+                        app_manager.manual_entry.tag_index -= 1;
+                    }
+                }
+                KEY_RIGHT =>
+                {
+                    if selected_field == ManualEntryField::Tag && app_manager.manual_entry.tag_index + 1 < app_manager.tags.len()
+                    {
+                        app_manager.manual_entry.tag_index += 1;
+                    }
+                }
+                KEY_BACKSPACE =>
+                {
+                    if let Some(text) = app_manager.manual_entry.get_text_field_mut(selected_field)
+                    {
+                        text.pop();
+                    }
+                }
+                _ if let Some(character) = key.get_typed_char() =>
+                {
+                    if let Some(text) = app_manager.manual_entry.get_text_field_mut(selected_field)
+                    {
+                        text.push(character);
+                    }
+                }
+                _ =>
+                {}
+            },
+            CommandState::EditRunning(selected_field) => match key
+            {
+                KEY_ESCAPE =>
+                {
+                    app_manager.session_edit_buffer = None;
+                    app_manager.state = CommandState::Idle;
+                }
+                KEY_ENTER =>
+                {
+                    app_manager.apply_running_session_edit();
+                    app_manager.state = CommandState::Idle;
+                }
+                KEY_TAB | KEY_BACK_TAB | KEY_DOWN | KEY_UP =>
+                {
+                    app_manager.state = CommandState::EditRunning(selected_field.next());
+                }
+                KEY_LEFT if selected_field == RunningEditField::Tag =>
+                {
+                    app_manager.temp_tag_index = app_manager.temp_tag_index.saturating_sub(1);
+                }
+                KEY_RIGHT if selected_field == RunningEditField::Tag =>
+                {
+                    if app_manager.temp_tag_index + 1 < app_manager.tags.len()
+                    {
+                        app_manager.temp_tag_index += 1;
+                    }
+                }
+                KEY_BACKSPACE if selected_field == RunningEditField::Description =>
+                {
+                    if let Some(session) = app_manager.session_edit_buffer.as_mut()
+                    {
+                        session.description.pop();
+                    }
+                }
+                _ if let Some(character) = key.get_typed_char()
+                    && selected_field == RunningEditField::Description =>
+                {
+                    if let Some(session) = app_manager.session_edit_buffer.as_mut()
+                    {
+                        session.description.push(character);
+                    }
+                }
+                _ =>
+                {}
+            },
+            CommandState::Overlap(origin) => match key
+            {
+                KEY_YES | KEY_ADJUST =>
+                {
+                    if key == KEY_ADJUST && !app_manager.adjust_pending_session()
+                    {
+                        return;
+                    }
+
+                    match origin
+                    {
+                        OverlapOrigin::Add =>
+                        {
+                            app_manager.add_pending_session();
+                        }
+                        OverlapOrigin::Edit =>
+                        {
+                            app_manager.apply_pending_session_to_edit_buffer();
+
+                            if !app_manager.apply_changes_to_session()
+                            {
+                                app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(
+                                    SessionFieldEditState::Browse,
+                                )));
+
+                                return;
+                            }
+
+                            app_manager.clear_session_edit_buffer();
+                            app_manager.selected_session_field = SessionField::None;
+                        }
+                    }
+
+                    app_manager.state = CommandState::Idle;
+                }
+                KEY_NO | KEY_ESCAPE =>
+                {
+                    app_manager.pending_session = None;
+
+                    app_manager.state = match origin
+                    {
+                        OverlapOrigin::Add => CommandState::Add(ManualEntryField::Description),
+                        OverlapOrigin::Edit =>
+                        {
+                            CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(SessionFieldEditState::Browse)))
+                        }
+                    };
+                }
+                _ =>
+                {}
+            },
+            CommandState::Export(export_state) => match export_state
+            {
+                ExportState::Form(selected_field) => match key
+                {
+                    KEY_ESCAPE =>
+                    {
+                        app_manager.state = CommandState::Idle;
+                    }
+                    KEY_ENTER =>
+                    {
+                        if app_manager.try_export_sessions()
+                        {
+                            app_manager.state = CommandState::Idle;
+                        }
+                    }
+                    KEY_TAB | KEY_DOWN =>
+                    {
+                        app_manager.state = CommandState::Export(ExportState::Form(selected_field.next()));
+                    }
+                    KEY_UP | KEY_BACK_TAB =>
+                    {
+                        app_manager.state = CommandState::Export(ExportState::Form(selected_field.previous()));
+                    }
+                    KEY_BACKSPACE =>
+                    {
+                        app_manager.export_form.get_text_field_mut(selected_field).pop();
+                    }
+                    _ if let Some(character) = key.get_typed_char() =>
+                    {
+                        app_manager.export_form.get_text_field_mut(selected_field).push(character);
+                    }
+                    _ =>
+                    {}
+                },
+            },
+            CommandState::Import(import_state) => match import_state
+            {
+                ImportState::Path => match key
+                {
+                    KEY_ESCAPE =>
+                    {
+                        app_manager.state = CommandState::Idle;
+                    }
+                    KEY_BACKSPACE =>
+                    {
+                        app_manager.import_path_buffer.pop();
+                    }
+                    KEY_ENTER =>
+                    {
+                        import_file(app_manager);
+                    }
+                    _ if let Some(character) = key.get_typed_char() =>
+                    {
+                        app_manager.import_path_buffer.push(character);
+                    }
+                    _ =>
+                    {}
+                },
+                ImportState::Mapping(selected_field) => match key
+                {
+                    KEY_ESCAPE =>
+                    {
+                        app_manager.csv_mapping = None;
+                        app_manager.state = CommandState::Import(ImportState::Path);
+                    }
+                    KEY_ENTER =>
+                    {
+                        app_manager.import_mapped_csv();
+                        app_manager.state = CommandState::Import(ImportState::Summary);
+                    }
+                    KEY_TAB | KEY_DOWN =>
+                    {
+                        app_manager.state = CommandState::Import(ImportState::Mapping(selected_field.next()));
+                    }
+                    KEY_UP | KEY_BACK_TAB =>
+                    {
+                        app_manager.state = CommandState::Import(ImportState::Mapping(selected_field.previous()));
+                    }
+                    KEY_LEFT | KEY_RIGHT =>
+                    {
+                        if let Some(csv_mapping) = &mut app_manager.csv_mapping
+                        {
+                            csv_mapping.cycle(selected_field, key == KEY_RIGHT);
+                        }
+                    }
+                    _ =>
+                    {}
+                },
+                ImportState::Summary =>
+                {
+                    if key == KEY_ENTER || key == KEY_ESCAPE
+                    {
+                        app_manager.import_summary = None;
+                        app_manager.state = CommandState::Idle;
+                    }
+                }
+            },
+            CommandState::Settings(selected_field) => match key
+            {
+                KEY_ESCAPE =>
+                {
+                    app_manager.cancel_settings();
+                    app_manager.state = CommandState::Idle;
+                }
+                KEY_UP | KEY_DOWN =>
+                {
+                    let field = if key == KEY_DOWN
+                    {
+                        selected_field.next()
+                    }
+                    else
+                    {
+                        selected_field.previous()
+                    };
+                    app_manager.state = CommandState::Settings(field);
+                }
+                KEY_LEFT | KEY_RIGHT =>
+                {
+                    app_manager.change_setting(selected_field, key == KEY_RIGHT);
+                }
+                KEY_ENTER =>
+                {
+                    if app_manager.save_settings()
+                    {
+                        app_manager.show_status("Settings saved");
+                        app_manager.state = CommandState::Idle;
+                    }
+                    else
+                    {
+                        app_manager.sync_message = Some(String::from("SETTINGS NOT SAVED"));
+                    }
+                }
+                _ =>
+                {}
+            },
+            CommandState::AuditLog => match key
+            {
+                KEY_ESCAPE | KEY_AUDIT_LOG =>
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+                KEY_UP =>
+                {
+                    app_manager.selected_audit_index = app_manager.selected_audit_index.saturating_sub(1);
+                }
+                KEY_DOWN =>
+                {
+                    if app_manager.selected_audit_index + 1 < app_manager.audit_entries.len()
+                    {
+                        app_manager.selected_audit_index += 1;
+                    }
+                }
+                _ =>
+                {}
+            },
+            CommandState::Gaps => match key
+            {
+                KEY_ESCAPE | KEY_GAPS =>
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+                KEY_LEFT =>
+                {
+                    app_manager.move_gaps_date(-1);
+                }
+                KEY_RIGHT =>
+                {
+                    app_manager.move_gaps_date(1);
+                }
+                KEY_UP =>
+                {
+                    app_manager.selected_gap_index = app_manager.selected_gap_index.saturating_sub(1);
+                }
+                KEY_DOWN =>
+                {
+                    if app_manager.selected_gap_index + 1 < app_manager.get_gaps().len()
+                    {
+                        app_manager.selected_gap_index += 1;
+                    }
+                }
+                KEY_ENTER =>
+                {
+                    if app_manager.open_manual_entry_for_selected_gap()
+                    {
+                        app_manager.state = CommandState::Add(ManualEntryField::Description);
+                    }
+                }
+                _ =>
+                {}
+            },
+            CommandState::Focus =>
+            {
+                if key == KEY_ESCAPE || key == KEY_FOCUS
+                {
+                    app_manager.renderer.clear_screen();
+                    app_manager.state = CommandState::Idle;
+                }
+            }
+            CommandState::WeekReport => match key
+            {
+                KEY_ESCAPE | KEY_WEEK_REPORT =>
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+                KEY_PAGE_UP | KEY_LEFT =>
+                {
+                    app_manager.report_week_start -= TimeDelta::days(7);
+                }
+                KEY_PAGE_DOWN | KEY_RIGHT =>
+                {
+                    app_manager.report_week_start += TimeDelta::days(7);
+                }
+                KEY_CLIPBOARD =>
+                {
+                    let report_text = get_week_report_text(
+                        app_manager.report_week_start,
+                        app_manager.get_week_comparison(),
+                        app_manager.get_week_flex_time(),
+                    );
+                    app_manager.copy_to_clipboard(&report_text);
+                }
+                _ =>
+                {}
+            },
+            CommandState::Goals =>
+            {
+                if key == KEY_ESCAPE || key == KEY_GOALS || key == KEY_ENTER
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+            }
+            CommandState::Templates => match key
+            {
+                KEY_ESCAPE | KEY_TEMPLATES =>
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+                KEY_UP =>
+                {
+                    app_manager.selected_template_index = app_manager.selected_template_index.saturating_sub(1);
+                }
+                KEY_DOWN =>
+                {
+                    let last_index = app_manager.config.templates.len().saturating_sub(1);
+                    app_manager.selected_template_index = cmp::min(app_manager.selected_template_index + 1, last_index);
+                }
+                KEY_ENTER if app_manager.apply_template(app_manager.selected_template_index) =>
+                {
+                    start_new_session(app_manager);
+                }
+                _ if let Some(index) = key.get_typed_char().and_then(|character| character.to_digit(10))
+                    && index > 0
+                    && app_manager.apply_template(index as usize - 1) =>
+                {
+                    start_new_session(app_manager);
+                }
+                _ =>
+                {}
+            },
+            CommandState::DatabaseStats =>
+            {
+                if key == KEY_ESCAPE || key == KEY_DATABASE_STATS
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+            }
+            CommandState::Stats => match key
+            {
+                KEY_ESCAPE | KEY_STATS =>
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+                KEY_LEFT =>
+                {
+                    app_manager.stats_period = app_manager.stats_period.previous();
+                }
+                KEY_RIGHT =>
+                {
+                    app_manager.stats_period = app_manager.stats_period.next();
+                }
+                KEY_PAGE_UP =>
+                {
+                    app_manager.move_stats_period(false);
+                }
+                KEY_PAGE_DOWN =>
+                {
+                    app_manager.move_stats_period(true);
+                }
+                KEY_EXPORT =>
+                {
+                    app_manager.write_daily_notes();
+                }
+                _ =>
+                {}
+            },
+            CommandState::Calendar => match key
+            {
+                KEY_ESCAPE | KEY_CALENDAR =>
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+                KEY_LEFT =>
+                {
+                    app_manager.move_calendar_selection(-1);
+                }
+                KEY_RIGHT =>
+                {
+                    app_manager.move_calendar_selection(1);
+                }
+                KEY_UP =>
+                {
+                    app_manager.move_calendar_selection(-7);
+                }
+                KEY_DOWN =>
+                {
+                    app_manager.move_calendar_selection(7);
+                }
+                KEY_PAGE_UP =>
+                {
+                    app_manager.move_calendar_month(false);
+                }
+                KEY_PAGE_DOWN =>
+                {
+                    app_manager.move_calendar_month(true);
+                }
+                KEY_DAY_OFF =>
+                {
+                    app_manager.cycle_selected_calendar_day_off();
+                }
+                KEY_ENTER =>
+                {
+                    if app_manager.open_selected_calendar_day()
+                    {
+                        app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Browse));
+                    }
+                }
+                _ =>
+                {}
+            },
+            CommandState::Locked =>
+            {
+                if key == KEY_READ_ONLY
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+                else if key == KEY_WORKSPACES
+                {
+                    app_manager.open_workspaces();
+                    app_manager.state = CommandState::Workspaces(WorkspaceState::Browse);
+                }
+                else if key == KEY_QUIT || key == KEY_QUIT_ALT || key == KEY_ESCAPE
+                {
+                    app_manager.running = false;
+                }
+            }
+            CommandState::ImportReport =>
+            {
+                if key == KEY_ENTER || key == KEY_ESCAPE
+                {
+                    app_manager.rejected_lines.clear();
+                    app_manager.state = CommandState::Idle;
+                }
+            }
+            CommandState::Backups(confirm_open) => match confirm_open
+            {
+                ConfirmOpen::Yes =>
+                {
+                    if is_confirmed(app_manager, key, ConfirmAction::Restore)
+                    {
+                        restore_backup(app_manager);
+                    }
+                    else if key == KEY_NO || key == KEY_ESCAPE
+                    {
+                        app_manager.state = CommandState::Backups(ConfirmOpen::No);
+                    }
+                }
+                ConfirmOpen::No => match key
+                {
+                    KEY_ESCAPE =>
+                    {
+                        app_manager.state = CommandState::Idle;
+                    }
+                    KEY_UP =>
+                    {
+                        if app_manager.selected_backup_index > 0
+                        {
+                            app_manager.selected_backup_index -= 1;
+                        }
+                    }
+                    KEY_DOWN =>
+                    {
+                        if app_manager.selected_backup_index + 1 < app_manager.backups.len()
+                        {
+                            app_manager.selected_backup_index += 1;
+                        }
+                    }
+                    KEY_ENTER if !app_manager.backups.is_empty() && !app_manager.config.asks_to(ConfirmAction::Restore) =>
+                    {
+                        restore_backup(app_manager);
+                    }
+                    KEY_ENTER =>
+                    {
+                        if !app_manager.backups.is_empty()
+                        {
+                            app_manager.state = CommandState::Backups(ConfirmOpen::Yes);
+                        }
+                    }
+                    _ =>
+                    {}
+                },
+            },
+            CommandState::Workspaces(workspace_state) => match workspace_state
+            {
+                WorkspaceState::Browse => match key
+                {
+                    KEY_ESCAPE =>
+                    {
+                        app_manager.state = if app_manager.is_read_only()
+                        {
+                            CommandState::Locked
+                        }
+                        else
+                        {
+                            CommandState::Idle
+                        };
+                    }
+                    KEY_UP =>
+                    {
+                        app_manager.selected_workspace_index = app_manager.selected_workspace_index.saturating_sub(1);
+                    }
+                    KEY_DOWN =>
+                    {
+                        if app_manager.selected_workspace_index + 1 < app_manager.workspaces.len()
+                        {
+                            app_manager.selected_workspace_index += 1;
+                        }
+                    }
+                    KEY_ENTER =>
+                    {
+                        app_manager.switch_to_selected_workspace();
+                    }
+                    KEY_NEW =>
+                    {
+                        app_manager.workspace_error = None;
+                        app_manager.state = CommandState::Workspaces(WorkspaceState::New);
+                    }
+                    _ =>
+                    {}
+                },
+                WorkspaceState::New => match key
+                {
+                    KEY_ESCAPE =>
+                    {
+                        app_manager.workspace_name_buffer.clear();
+                        app_manager.workspace_error = None;
+                        app_manager.state = CommandState::Workspaces(WorkspaceState::Browse);
+                    }
+                    KEY_BACKSPACE =>
+                    {
+                        app_manager.workspace_name_buffer.pop();
+                    }
+                    KEY_ENTER =>
+                    {
+                        app_manager.create_workspace();
+                    }
+                    _ if let Some(character) = key.get_typed_char() =>
+                    {
+                        app_manager.workspace_name_buffer.push(character);
+                    }
+                    _ =>
+                    {}
+                },
+            },
+            CommandState::Modify(session_modify_state) => match session_modify_state
+            {
+                SessionModifyState::Edit(edit_state) => match edit_state
+                {
+                    SessionEditState::Browse => match key
+                    {
+                        KEY_ESCAPE =>
+                        {
+                            app_manager.state = CommandState::Idle;
+                        }
+                        KEY_UP =>
+                        {
+                            app_manager.move_session_selection(true);
+                        }
+                        KEY_DOWN =>
+                        {
+                            app_manager.move_session_selection(false);
+                        }
+                        KEY_COLLAPSE =>
+                        {
+                            app_manager.toggle_selected_day_collapsed();
+                        }
+                        KEY_ENTER if app_manager.is_selected_day_collapsed() =>
+                        {
+                            app_manager.toggle_selected_day_collapsed();
+                        }
+                        KEY_ENTER =>
+                        {
+                            app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Details));
+                        }
+                        _ =>
+                        {}
+                    },
+                    SessionEditState::Details => match key
+                    {
+                        KEY_ESCAPE =>
+                        {
+                            app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Browse));
+                        }
+                        KEY_ENTER =>
+                        {
+                            app_manager.copy_selected_session_to_buffer();
+                            app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(
+                                SessionFieldEditState::Browse,
+                            )));
+                        }
+                        KEY_EXTERNAL_EDITOR =>
+                        {
+                            app_manager.edit_selected_session_in_editor();
+                        }
+                        KEY_CLIPBOARD =>
+                        {
+                            if let Some(session_text) = app_manager.get_selected_session_text()
+                            {
+                                app_manager.copy_to_clipboard(&session_text);
+                            }
+                        }
+                        KEY_NOTES =>
+                        {
+                            app_manager.open_notes_editor();
+                            app_manager.state =
+                                CommandState::Modify(SessionModifyState::Edit(SessionEditState::Notes(ConfirmOpen::No)));
+                        }
+                        _ =>
+                        {}
+                    },
+                    SessionEditState::Notes(ConfirmOpen::No) =>
+                    {
+                        let Some(notes_editor) = app_manager.notes_editor.as_mut()
+                        else
+                        {
+                            app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Details));
+                            return;
+                        };
+
+                        match key
+                        {
+                            KEY_ESCAPE if notes_editor.has_changes() =>
+                            {
+                                app_manager.state =
+                                    CommandState::Modify(SessionModifyState::Edit(SessionEditState::Notes(ConfirmOpen::Yes)));
+                            }
+                            KEY_ESCAPE =>
+                            {
+                                app_manager.notes_editor = None;
+                                app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Details));
+                            }
+                            KEY_ENTER => notes_editor.insert_line_break(),
+                            KEY_BACKSPACE => notes_editor.delete_before(),
+                            KEY_DELETE_FORWARD => notes_editor.delete_after(),
+                            KEY_LEFT => notes_editor.move_left(),
+                            KEY_RIGHT => notes_editor.move_right(),
+                            KEY_UP => notes_editor.move_vertically(true),
+                            KEY_DOWN => notes_editor.move_vertically(false),
+                            KEY_LINE_START => notes_editor.move_to_line_start(),
+                            KEY_LINE_END => notes_editor.move_to_line_end(),
+                            KEY_DELETE_WORD | KEY_DELETE_WORD_ALT => notes_editor.delete_word_before(),
+                            _ if let Some(character) = key.get_typed_char() => notes_editor.insert(character),
+                            _ =>
+                            {}
+                        }
+                    }
+                    SessionEditState::Notes(ConfirmOpen::Yes) => match key
+                    {
+                        KEY_YES =>
+                        {
+                            app_manager.save_notes();
+                            app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Details));
+                        }
+                        KEY_NO =>
+                        {
+                            app_manager.notes_editor = None;
+                            app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Details));
+                        }
+                        KEY_ESCAPE =>
+                        {
+                            app_manager.state =
+                                CommandState::Modify(SessionModifyState::Edit(SessionEditState::Notes(ConfirmOpen::No)));
+                        }
+                        _ =>
+                        {}
+                    },
+                    SessionEditState::EditFields(state) => match state
+                    {
+                        SessionFieldEditState::Browse => match key
+                        {
+                            KEY_ESCAPE =>
+                            {
+                                if app_manager.session_buffer_has_pending_changes()
+                                {
+                                    app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Confirm));
+                                }
+                                else
+                                {
+                                    app_manager.clear_session_edit_buffer();
+                                    app_manager.selected_session_field = SessionField::None;
+                                    app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Browse));
+                                }
+                            }
+                            KEY_LEFT =>
+                            {
+                                app_manager.decrement_selected_session_field();
+                            }
+                            KEY_RIGHT =>
+                            {
+                                app_manager.increment_selected_session_field();
+                            }
+                            KEY_TAB | KEY_BACK_TAB =>
+                            {
+                                app_manager.cycle_selected_session_field(key == KEY_TAB);
+                            }
+                            KEY_ENTER =>
+                            {
+                                app_manager.selected_datetime_segment = 0;
+
+                                let can_edit = if let SessionField::End(_) = app_manager.selected_session_field
+                                    && app_manager.is_last_session_still_running()
+                                {
+                                    false
+                                }
+                                else
+                                {
+                                    true
+                                };
+
+                                if can_edit
+                                {
+                                    app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(
+                                        SessionFieldEditState::Editing,
+                                    )));
+                                }
+                            }
+                            _ =>
+                            {}
+                        },
+                        SessionFieldEditState::Editing =>
+                        {
+                            match key
+                            {
+                                KEY_ESCAPE =>
+                                {
+                                    app_manager.session_edit_error = None;
+                                    app_manager.tag_filter.clear();
+
+                                    let Some(session_edit_buffer) = &app_manager.session_edit_buffer
+                                    else
+                                    {
+                                        return;
+                                    };
+
+                                    app_manager.temp_tag_index = app_manager.get_index_of_tag(&session_edit_buffer.tag);
+
+                                    match &mut app_manager.selected_session_field
+                                    {
+                                        SessionField::Date(date_buffer) =>
+                                        {
+                                            *date_buffer = session_edit_buffer.start;
+                                        }
+                                        SessionField::Description(description_buffer) =>
+                                        {
+                                            description_buffer.set(&session_edit_buffer.description);
+                                        }
+                                        SessionField::Tag(tag_buffer) =>
+                                        {
+                                            tag_buffer.clone_from(&session_edit_buffer.tag);
+                                        }
+                                        SessionField::Start(start_time_buffer) =>
+                                        {
+                                            *start_time_buffer = session_edit_buffer.start;
+                                        }
+                                        SessionField::End(end_time_buffer) =>
+                                        {
+                                            *end_time_buffer = session_edit_buffer.end;
+                                        }
+                                        SessionField::None =>
+                                        {}
+                                    }
+
+                                    app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(
+                                        SessionFieldEditState::Browse,
+                                    )));
+                                }
+                                KEY_ENTER
+                                    if matches!(app_manager.selected_session_field, SessionField::Tag(_))
+                                        && !app_manager.snap_tag_selection_to_filter() =>
+                                {}
+                                KEY_ENTER =>
+                                {
+                                    app_manager.tag_filter.clear();
+
+                                    if app_manager.store_modified_field_to_session_buffer()
+                                    {
+                                        app_manager.state = CommandState::Modify(SessionModifyState::Edit(
+                                            SessionEditState::EditFields(SessionFieldEditState::Browse),
+                                        ));
+                                    }
+                                }
+                                _ =>
+                                {}
+                            }
+
+                            let last_time_segment = app_manager.get_time_segment_count() - 1;
+
+                            if let SessionField::Tag(_) = app_manager.selected_session_field
+                            {
+                                match key
+                                {
+                                    KEY_UP | KEY_DOWN =>
+                                    {
+                                        app_manager.move_tag_selection(key == KEY_UP);
+                                    }
+                                    _ =>
+                                    {
+                                        edit_text_input(&mut app_manager.tag_filter, key, value_separator);
+                                    }
+                                }
+
+                                app_manager.snap_tag_selection_to_filter();
+                            }
+
+                            match &mut app_manager.selected_session_field
+                            {
+                                SessionField::Date(date_buffer) =>
+                                {
+                                    if let Some(step) = get_step(key)
+                                        && let Some(new_date) = edit_date(step, app_manager.selected_datetime_segment, *date_buffer)
+                                    {
+                                        *date_buffer = new_date;
+                                    }
+
+                                    match key
+                                    {
+                                        KEY_LEFT =>
+                                        {
+                                            if app_manager.selected_datetime_segment > 0
+                                            {
+                                                app_manager.selected_datetime_segment -= 1;
+                                            }
+                                        }
+                                        KEY_RIGHT =>
+                                        {
+                                            if app_manager.selected_datetime_segment < 2
+                                            {
+                                                app_manager.selected_datetime_segment += 1;
+                                            }
+                                        }
+                                        _ =>
+                                        {}
+                                    }
+                                }
+                                SessionField::Description(description_buffer) =>
+                                {
+                                    edit_text_input(description_buffer, key, value_separator);
+                                }
+
+                                SessionField::Tag(tag_buffer) =>
+                                {
+                                    if let Some(tag) = app_manager.tags.get(app_manager.temp_tag_index)
+                                    {
+                                        tag_buffer.clone_from(tag);
+                                    }
+                                }
+                                SessionField::Start(start_buffer) =>
+                                {
+                                    if let Some(step) = get_step(key)
+                                        && let Some(new_date) = edit_time(step, app_manager.selected_datetime_segment, *start_buffer)
+                                    {
+                                        *start_buffer = new_date;
+                                    }
+
+                                    match key
+                                    {
+                                        KEY_LEFT =>
+                                        {
+                                            if app_manager.selected_datetime_segment > 0
+                                            {
+                                                app_manager.selected_datetime_segment -= 1;
+                                            }
+                                        }
+                                        KEY_RIGHT =>
+                                        {
+                                            if app_manager.selected_datetime_segment < last_time_segment
+                                            {
+                                                app_manager.selected_datetime_segment += 1;
+                                            }
+                                        }
+                                        _ =>
+                                        {}
+                                    }
+                                }
+                                SessionField::End(end_buffer) =>
+                                {
+                                    if let Some(end_buffer) = end_buffer
+                                        && let Some(step) = get_step(key)
+                                        && let Some(new_date) = edit_time(step, app_manager.selected_datetime_segment, *end_buffer)
+                                    {
+                                        *end_buffer = new_date;
+                                    }
+
+                                    match key
+                                    {
+                                        KEY_LEFT =>
+                                        {
+                                            if app_manager.selected_datetime_segment > 0
+                                            {
+                                                app_manager.selected_datetime_segment -= 1;
+                                            }
+                                        }
+                                        KEY_RIGHT =>
+                                        {
+                                            if app_manager.selected_datetime_segment < last_time_segment
+                                            {
+                                                app_manager.selected_datetime_segment += 1;
+                                            }
+                                        }
+                                        _ =>
+                                        {}
+                                    }
+                                }
+                                SessionField::None =>
+                                {}
+                            }
+                        }
+                    },
+                    SessionEditState::Confirm => match key
+                    {
+                        KEY_YES =>
+                        {
+                            app_manager.stage_edited_session();
+
+                            if app_manager.pending_session_has_overlaps()
+                            {
+                                app_manager.state = CommandState::Overlap(OverlapOrigin::Edit);
+                            }
+                            else
+                            {
+                                app_manager.pending_session = None;
+
+                                if app_manager.apply_changes_to_session()
+                                {
+                                    app_manager.clear_session_edit_buffer();
+                                    app_manager.selected_session_field = SessionField::None;
+                                    app_manager.state = CommandState::Idle;
+                                }
+                                else
+                                {
+                                    app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(
+                                        SessionFieldEditState::Browse,
+                                    )));
+                                }
+                            }
+                        }
+                        KEY_NO =>
+                        {
+                            app_manager.clear_session_edit_buffer();
+                            app_manager.selected_session_field = SessionField::None;
+                            app_manager.state = CommandState::Idle;
+                        }
+                        KEY_ESCAPE =>
+                        {
+                            app_manager.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(
+                                SessionFieldEditState::Browse,
+                            )));
+                        }
+                        _ =>
+                        {}
+                    },
+                },
+                SessionModifyState::Continue(confirm_open) => match confirm_open
+                {
+                    ConfirmOpen::Yes =>
+                    {
+                        if key == KEY_YES
+                        {
+                            app_manager.start_new_session_based_on_selected();
+                            app_manager.state = CommandState::Idle;
+                        }
+                        else if key == KEY_NO || key == KEY_ESCAPE
+                        {
+                            app_manager.state = CommandState::Idle;
+                        }
+                    }
+                    ConfirmOpen::No => match key
+                    {
+                        KEY_ESCAPE =>
+                        {
+                            app_manager.state = CommandState::Idle;
+                        }
+                        KEY_UP =>
+                        {
+                            app_manager.move_session_selection(true);
+                        }
+                        KEY_DOWN =>
+                        {
+                            app_manager.move_session_selection(false);
+                        }
+                        KEY_COLLAPSE =>
+                        {
+                            app_manager.toggle_selected_day_collapsed();
+                        }
+                        KEY_ENTER if app_manager.is_selected_day_collapsed() =>
+                        {
+                            app_manager.toggle_selected_day_collapsed();
+                        }
+                        KEY_ENTER =>
+                        {
+                            app_manager.state = CommandState::Modify(SessionModifyState::Continue(ConfirmOpen::Yes));
+                        }
+                        _ =>
+                        {}
+                    },
+                },
+                SessionModifyState::Duplicate => match key
+                {
+                    KEY_ESCAPE =>
+                    {
+                        app_manager.state = CommandState::Idle;
+                    }
+                    KEY_UP =>
+                    {
+                        app_manager.move_session_selection(true);
+                    }
+                    KEY_DOWN =>
+                    {
+                        app_manager.move_session_selection(false);
+                    }
+                    KEY_COLLAPSE =>
+                    {
+                        app_manager.toggle_selected_day_collapsed();
+                    }
+                    KEY_ENTER if app_manager.is_selected_day_collapsed() =>
+                    {
+                        app_manager.toggle_selected_day_collapsed();
+                    }
+                    KEY_ENTER =>
+                    {
+                        app_manager.open_manual_entry_for_selected_session();
+                        app_manager.state = CommandState::Add(ManualEntryField::Description);
+                    }
+                    _ =>
+                    {}
+                },
+                SessionModifyState::Select(select_state) => match select_state
+                {
+                    SelectState::Browse => match key
+                    {
+                        KEY_ESCAPE =>
+                        {
+                            app_manager.marked_session_ids.clear();
+                            app_manager.state = CommandState::Idle;
+                        }
+                        KEY_UP =>
+                        {
+                            app_manager.move_session_selection(true);
+                        }
+                        KEY_DOWN =>
+                        {
+                            app_manager.move_session_selection(false);
+                        }
+                        KEY_COLLAPSE =>
+                        {
+                            app_manager.toggle_selected_day_collapsed();
+                        }
+                        KEY_ENTER if app_manager.is_selected_day_collapsed() =>
+                        {
+                            app_manager.toggle_selected_day_collapsed();
+                        }
+                        KEY_MARK if !app_manager.is_selected_day_collapsed() =>
+                        {
+                            app_manager.toggle_selected_session_mark();
+                        }
+                        KEY_DELETE
+                            if !app_manager.marked_session_ids.is_empty() && !app_manager.config.asks_to(ConfirmAction::Delete) =>
+                        {
+                            app_manager.delete_marked_sessions();
+                            app_manager.state = CommandState::Idle;
+                        }
+                        KEY_DELETE if !app_manager.marked_session_ids.is_empty() =>
+                        {
+                            app_manager.state = CommandState::Modify(SessionModifyState::Select(SelectState::Delete));
+                        }
+                        KEY_RETAG if !app_manager.marked_session_ids.is_empty() && !app_manager.tags.is_empty() =>
+                        {
+                            app_manager.temp_tag_index = 0;
+                            app_manager.state = CommandState::Modify(SessionModifyState::Select(SelectState::Retag(ConfirmOpen::No)));
+                        }
+                        KEY_EXPORT if !app_manager.marked_session_ids.is_empty() =>
+                        {
+                            app_manager.open_export_form_for_marked();
+                            app_manager.state = CommandState::Export(ExportState::Form(ExportField::Path));
+                        }
+                        _ =>
+                        {}
+                    },
+                    SelectState::Delete =>
+                    {
+                        if is_confirmed(app_manager, key, ConfirmAction::Delete)
+                        {
+                            app_manager.delete_marked_sessions();
+                            app_manager.state = CommandState::Idle;
+                        }
+                        else if key == KEY_NO || key == KEY_ESCAPE
+                        {
+                            app_manager.state = CommandState::Modify(SessionModifyState::Select(SelectState::Browse));
+                        }
+                    }
+                    SelectState::Retag(ConfirmOpen::No) => match key
+                    {
+                        KEY_UP =>
+                        {
+                            app_manager.temp_tag_index = app_manager.temp_tag_index.saturating_sub(1);
+                        }
+                        KEY_DOWN =>
+                        {
+                            app_manager.temp_tag_index = cmp::min(app_manager.temp_tag_index + 1, app_manager.tags.len() - 1);
+                        }
+                        KEY_ENTER if !app_manager.config.asks_to(ConfirmAction::Retag) =>
+                        {
+                            let tag = app_manager.tags[app_manager.temp_tag_index].clone();
+                            app_manager.retag_marked_sessions(&tag);
+                            app_manager.state = CommandState::Idle;
+                        }
+                        KEY_ENTER =>
+                        {
+                            app_manager.state = CommandState::Modify(SessionModifyState::Select(SelectState::Retag(ConfirmOpen::Yes)));
+                        }
+                        KEY_ESCAPE =>
+                        {
+                            app_manager.state = CommandState::Modify(SessionModifyState::Select(SelectState::Browse));
+                        }
+                        _ =>
+                        {}
+                    },
+                    SelectState::Retag(ConfirmOpen::Yes) =>
+                    {
+                        if is_confirmed(app_manager, key, ConfirmAction::Retag)
+                        {
+                            let tag = app_manager.tags[app_manager.temp_tag_index].clone();
+                            app_manager.retag_marked_sessions(&tag);
+                            app_manager.state = CommandState::Idle;
+                        }
+                        else if key == KEY_NO || key == KEY_ESCAPE
+                        {
+                            app_manager.state = CommandState::Modify(SessionModifyState::Select(SelectState::Retag(ConfirmOpen::No)));
+                        }
+                    }
+                },
+                SessionModifyState::Delete(confirm_open) => match confirm_open
+                {
+                    ConfirmOpen::Yes =>
+                    {
+                        if is_confirmed(app_manager, key, ConfirmAction::Delete)
+                        {
+                            app_manager.delete_selected_session();
+                            app_manager.state = CommandState::Idle;
+                        }
+                        else if key == KEY_NO || key == KEY_ESCAPE
+                        {
+                            app_manager.state = CommandState::Idle;
+                        }
+                    }
+                    ConfirmOpen::No => match key
+                    {
+                        KEY_ESCAPE =>
+                        {
+                            app_manager.state = CommandState::Idle;
+                        }
+                        KEY_UP =>
+                        {
+                            app_manager.move_session_selection(true);
+                        }
+                        KEY_DOWN =>
+                        {
+                            app_manager.move_session_selection(false);
+                        }
+                        KEY_COLLAPSE =>
+                        {
+                            app_manager.toggle_selected_day_collapsed();
+                        }
+                        KEY_ENTER if app_manager.is_selected_day_collapsed() =>
+                        {
+                            app_manager.toggle_selected_day_collapsed();
+                        }
+                        KEY_ENTER if !app_manager.config.asks_to(ConfirmAction::Delete) =>
+                        {
+                            app_manager.delete_selected_session();
+                            app_manager.state = CommandState::Idle;
+                        }
+                        KEY_ENTER =>
+                        {
+                            app_manager.state = CommandState::Modify(SessionModifyState::Delete(ConfirmOpen::Yes));
+                        }
+                        _ =>
+                        {}
+                    },
+                },
+            },
+            CommandState::End =>
+            {
+                if is_confirmed(app_manager, key, ConfirmAction::End)
+                {
+                    end_session(app_manager);
+                }
+                else if key == KEY_NO || key == KEY_ESCAPE
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+            }
+            CommandState::AutoStop =>
+            {
+                if key == KEY_YES
+                {
+                    if let Some(auto_stop_end) = app_manager.get_auto_stop_end()
+                    {
+                        app_manager.end_running_session_at(auto_stop_end);
+                    }
+
+                    app_manager.state = CommandState::Idle;
+                }
+                else if key == KEY_NO || key == KEY_ESCAPE
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+            }
+            CommandState::Discard =>
+            {
+                if is_confirmed(app_manager, key, ConfirmAction::Discard)
+                {
+                    app_manager.discard_running_session();
+                    app_manager.state = CommandState::Idle;
+                }
+                else if key == KEY_NO || key == KEY_ESCAPE
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+            }
+            CommandState::DiscardShort =>
+            {
+                if key == KEY_YES
+                {
+                    app_manager.discard_running_session();
+                    app_manager.state = CommandState::Idle;
+                }
+                else if key == KEY_NO
+                {
+                    app_manager.end_running_session();
+                    app_manager.state = CommandState::Idle;
+                }
+                else if key == KEY_ESCAPE
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+            }
+            CommandState::RestoreEdit =>
+            {
+                if key == KEY_YES && !app_manager.restore_unsaved_edit()
+                {
+                    app_manager.show_status("The edited session is gone");
+                }
+                else if key == KEY_NO || key == KEY_ESCAPE
+                {
+                    app_manager.discard_unsaved_edit();
+                }
+            }
+            CommandState::Quitting =>
+            {
+                if is_confirmed(app_manager, key, ConfirmAction::Quit)
+                {
+                    quit(app_manager);
+                }
+                else if key == KEY_NO || key == KEY_ESCAPE
+                {
+                    app_manager.state = CommandState::Idle;
+                }
+            }
+        }
+    }
+}
+
+/// Work that doesn't wait for input: outside changes to the database, the daemon and notifications.
+pub fn tick(app_manager: &mut AppManager)
+{
+    app_manager.reload_on_external_changes();
+    app_manager.export_running_session_changes();
+    app_manager.refresh_from_daemon();
+    app_manager.send_due_notifications();
+    app_manager.journal_unsaved_edit();
+
+    // Only where nothing is being typed or looked at that ending the session would pull away.
+    if matches!(app_manager.state, CommandState::Idle | CommandState::Focus) && app_manager.apply_auto_stop()
+    {
+        app_manager.state = CommandState::AutoStop;
+    }
+}
+
+/// Resizing clears the screen so nothing of the old layout is left behind, the next render lays everything out again.
+/// Left clicks are resolved by the app manager and may come back as the key of a clicked button.
+fn get_user_key(app_manager: &mut AppManager, event: &Event) -> Option<Key>
+{
+    match event
+    {
+        Event::Resize(_, _) =>
+        {
+            app_manager.renderer.clear_screen();
+            return None;
+        }
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            ..
+        }) =>
+        {
+            return app_manager.handle_click(*column, *row);
+        }
+        _ =>
+        {}
+    }
+
+    if let Some(key_event) = event.as_key_press_event()
+    {
+        return Some(Key::from(key_event));
+    }
+
+    None
+}
+
+/// Types pasted text into the field being edited one character at a time, so a line break in it cannot confirm the field
+/// halfway through. Notes keep their line breaks. Outside text fields a paste is dropped rather than run as commands.
+fn paste(app_manager: &mut AppManager, text: &str)
+{
+    if !app_manager.state.is_text_input()
+    {
+        return;
+    }
+
+    let is_notes = matches!(app_manager.state, CommandState::Modify(SessionModifyState::Edit(SessionEditState::Notes(_))));
+    let mut press = |key: Key| update(app_manager, &Event::Key(KeyEvent::new(key.code, key.modifiers)));
+
+    if is_notes
+    {
+        for (index, line) in text.lines().enumerate()
+        {
+            if index > 0
+            {
+                press(KEY_ENTER);
+            }
+
+            line.replace('\t', " ").chars().filter(|character| !character.is_control()).for_each(|character| {
+                press(Key::new(KeyCode::Char(character)));
+            });
+        }
+    }
+    else
+    {
+        get_pasted_line(text).chars().for_each(|character| press(Key::new(KeyCode::Char(character))));
+    }
+}
+
+/// Imports the file named in the import popup, going on to the column mapping for CSV files in an unknown layout.
+/// Errors are shown in the popup.
+pub fn import_file(app_manager: &mut AppManager)
+{
+    if app_manager.try_import_file()
+    {
+        app_manager.state = if app_manager.csv_mapping.is_some()
+        {
+            CommandState::Import(ImportState::Mapping(CsvMappingField::Description))
+        }
+        else
+        {
+            CommandState::Import(ImportState::Summary)
+        };
+    }
+}
+
+/// Edits `input` with `key`: typing, deleting around the cursor or the word before it, and moving the cursor. A typed
+/// `value_separator` is stored as a comma so the session line stays readable. False for keys that do not edit text,
+/// which are left to the field.
+fn edit_text_input(input: &mut TextInput, key: Key, value_separator: char) -> bool
+{
+    match key
+    {
+        KEY_BACKSPACE => input.delete_before(),
+        KEY_DELETE_FORWARD => input.delete_after(),
+        KEY_DELETE_WORD | KEY_DELETE_WORD_ALT => input.delete_word_before(),
+        KEY_LEFT => input.move_left(),
+        KEY_RIGHT => input.move_right(),
+        KEY_LINE_START => input.move_to_start(),
+        KEY_LINE_END => input.move_to_end(),
+        _ if key.get_typed_char() == Some(value_separator) => input.insert(','),
+        _ if let Some(character) = key.get_typed_char() => input.insert(character),
+        _ => return false,
+    }
+
+    true
+}
+
+/// Which way the up and down keys move the selected segment of a date or time, `None` for other keys.
+fn get_step(key: Key) -> Option<Step>
+{
+    match key
+    {
+        KEY_UP => Some(Step::Forward),
+        KEY_DOWN => Some(Step::Back),
+        _ => None,
+    }
+}
+
+/// True if a question about `action` is answered with yes. "Yes, don't ask again" also stops asking about it.
+fn is_confirmed(app_manager: &mut AppManager, key: Key, action: ConfirmAction) -> bool
+{
+    if key == KEY_YES_ALWAYS
+    {
+        app_manager.stop_confirming(action);
+    }
+
+    key == KEY_YES || key == KEY_YES_ALWAYS
+}
+
+/// Starts the session filled in in the new session popup, or selects the field keeping it from starting. Asks first when
+/// another session is still running.
+fn start_new_session(app_manager: &mut AppManager)
+{
+    if app_manager.get_new_session_start().is_none()
+    {
+        app_manager.state = CommandState::New(SessionInputState::Start);
+    }
+    else if !app_manager.is_new_session_timer_valid()
+    {
+        app_manager.state = CommandState::New(SessionInputState::Timer);
+    }
+    else if app_manager.is_last_session_still_running()
+    {
+        app_manager.state = CommandState::New(SessionInputState::Description(ConfirmOpen::Yes));
+    }
+    else
+    {
+        app_manager.try_start_new_session();
+        app_manager.state = CommandState::Idle;
+    }
+}
+
+/// Moves through the fields of the new session popup, forward with Tab and back with Shift+Tab. A start time or timer
+/// that cannot be read keeps the field selected.
+fn cycle_new_session_field(app_manager: &mut AppManager, forward: bool)
+{
+    let CommandState::New(field) = app_manager.state
+    else
+    {
+        return;
+    };
+
+    match field
+    {
+        SessionInputState::Start if app_manager.get_new_session_start().is_none() => return,
+        SessionInputState::Timer if !app_manager.is_new_session_timer_valid() => return,
+        SessionInputState::Tag(_) => app_manager.tag_filter.clear(),
+        _ =>
+        {}
+    }
+
+    let field = if forward
+    {
+        field.next()
+    }
+    else
+    {
+        field.previous()
+    };
+
+    if let SessionInputState::Tag(_) = field
+    {
+        app_manager.temp_tag_index = app_manager.get_selected_tag_index();
+    }
+
+    app_manager.state = CommandState::New(field);
+}
+
+/// Ends the running session, asking whether to discard it instead if it is shorter than the minimum duration.
+fn end_session(app_manager: &mut AppManager)
+{
+    if app_manager.is_running_session_too_short()
+    {
+        app_manager.state = CommandState::DiscardShort;
+    }
+    else
+    {
+        app_manager.end_running_session();
+        app_manager.state = CommandState::Idle;
+    }
+}
+
+/// Restores the selected backup, listing the lines it couldn't read if there are any.
+fn restore_backup(app_manager: &mut AppManager)
+{
+    app_manager.restore_selected_backup();
+    app_manager.state = if app_manager.rejected_lines.is_empty()
+    {
+        CommandState::Idle
+    }
+    else
+    {
+        CommandState::ImportReport
+    };
+}
+
+/// Ends the running session and leaves the loop.
+fn quit(app_manager: &mut AppManager)
+{
+    // The daemon keeps it running.
+    if app_manager.is_last_session_still_running() && !app_manager.daemon_connected
+    {
+        app_manager.end_running_session();
+    }
+
+    app_manager.running = false;
+    app_manager.journal_unsaved_edit();
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::config::SessionTemplate;
+    use crate::database_handler::{DatabaseHandler, DATE_FORMAT, VALUE_SEPARATOR};
+    use crate::tui::io::{BufferBackend, Out, Vector2};
+    use chrono::NaiveDateTime;
+
+    /// The app at 10:30 on Wednesday 14 January 2026, on an in-memory workspace holding `session_lines` as stored in the
+    /// sessions file.
+    fn open_app(workspace: &str, session_lines: &[&str]) -> AppManager
+    {
+        let database_handler = DatabaseHandler::new_ephemeral(workspace);
+
+        for session_line in session_lines
+        {
+            database_handler.export_session(session_line, VALUE_SEPARATOR, DATE_FORMAT).expect("Storing session failed.");
+        }
+
+        let clock = MockClock::new(NaiveDateTime::parse_from_str("14-01-2026 10:30:00", DATE_FORMAT).expect("Invalid test time."));
+        let renderer = Out::with_backend(Box::new(BufferBackend::new(Vector2::new(100, 30))));
+
+        AppManager::with_parts(database_handler, None, renderer, Box::new(clock))
+    }
+
+    fn press(app_manager: &mut AppManager, key: Key)
+    {
+        update(app_manager, &Event::Key(KeyEvent::new(key.code, key.modifiers)));
+    }
+
+    #[test]
+    fn only_up_and_down_step_dates_and_times()
+    {
+        assert_eq!(get_step(KEY_UP), Some(Step::Forward));
+        assert_eq!(get_step(KEY_DOWN), Some(Step::Back));
+        assert_eq!(get_step(KEY_LEFT), None);
+    }
+
+    #[test]
+    fn deleting_stops_asking_after_yes_always()
+    {
+        let mut app_manager = open_app(
+            "events-delete",
+            &[
+                "12-01-2026;Plan the sprint;meeting;09:00:00;10:15:00;12-01-2026;1;",
+                "12-01-2026;Fix login redirect;code;10:30:00;12:45:30;12-01-2026;2;",
+            ],
+        );
+
+        press(&mut app_manager, KEY_DELETE);
+        press(&mut app_manager, KEY_ENTER);
+        assert!(matches!(app_manager.state, CommandState::Modify(SessionModifyState::Delete(ConfirmOpen::Yes))));
+
+        press(&mut app_manager, KEY_YES_ALWAYS);
+        assert_eq!(app_manager.sessions.len(), 1);
+        assert!(!app_manager.config.asks_to(ConfirmAction::Delete));
+
+        press(&mut app_manager, KEY_DELETE);
+        press(&mut app_manager, KEY_ENTER);
+        assert!(app_manager.sessions.is_empty());
+        assert!(matches!(app_manager.state, CommandState::Idle));
+    }
+
+    #[test]
+    fn ctrl_c_asks_to_quit_instead_of_copying()
+    {
+        let mut app_manager = open_app("events-ctrl-c", &["12-01-2026;Plan the sprint;meeting;09:00:00;10:15:00;12-01-2026;1;"]);
+
+        press(&mut app_manager, Key::ctrl(KeyCode::Char('c')));
+
+        assert!(matches!(app_manager.state, CommandState::Quitting));
+    }
+
+    #[test]
+    fn tab_and_shift_tab_cycle_through_the_new_session_fields()
+    {
+        let mut app_manager = open_app("events-tab", &[]);
+
+        press(&mut app_manager, KEY_NEW);
+        press(&mut app_manager, KEY_BACK_TAB);
+        assert!(matches!(app_manager.state, CommandState::New(SessionInputState::Timer)));
+
+        press(&mut app_manager, KEY_BACK_TAB);
+        press(&mut app_manager, KEY_BACK_TAB);
+        assert!(matches!(app_manager.state, CommandState::New(SessionInputState::Tag(TagInputState::Select))));
+
+        press(&mut app_manager, KEY_TAB);
+        press(&mut app_manager, KEY_TAB);
+        press(&mut app_manager, KEY_TAB);
+        assert!(matches!(app_manager.state, CommandState::New(SessionInputState::Description(ConfirmOpen::No))));
+    }
+
+    #[test]
+    fn pasted_text_lands_in_the_description_on_one_line()
+    {
+        let mut app_manager = open_app("events-paste", &[]);
+
+        update(&mut app_manager, &Event::Paste("quit".to_string()));
+        assert!(matches!(app_manager.state, CommandState::Idle));
+
+        press(&mut app_manager, KEY_NEW);
+        update(&mut app_manager, &Event::Paste("Fix login\r\n\tredirect\n".to_string()));
+
+        assert_eq!(app_manager.description_buffer, "Fix login redirect");
+        assert!(matches!(app_manager.state, CommandState::New(SessionInputState::Description(ConfirmOpen::No))));
+    }
+
+    #[test]
+    fn typed_value_separator_is_stored_as_a_comma()
+    {
+        let mut app_manager = open_app("events-typed-separator", &[]);
+
+        press(&mut app_manager, KEY_NEW);
+        "Plan; review".chars().for_each(|character| press(&mut app_manager, Key::new(KeyCode::Char(character))));
+
+        assert_eq!(app_manager.description_buffer, "Plan, review");
+    }
+
+    fn get_standup_template() -> SessionTemplate
+    {
+        SessionTemplate {
+            name: String::from("standup"),
+            description: String::from("Daily standup"),
+            tag: String::from("meetings"),
+            target: Some(TimeDelta::minutes(15)),
+        }
+    }
+
+    #[test]
+    fn template_number_starts_its_session_with_a_timer()
+    {
+        let mut app_manager = open_app("events-templates", &[]);
+        app_manager.config.templates.push(get_standup_template());
+
+        press(&mut app_manager, KEY_TEMPLATES);
+        press(&mut app_manager, Key::new(KeyCode::Char('2')));
+        assert!(matches!(app_manager.state, CommandState::Templates));
+
+        press(&mut app_manager, Key::new(KeyCode::Char('1')));
+
+        let session = app_manager.sessions.last().expect("The template should have started a session.");
+        assert!(matches!(app_manager.state, CommandState::Idle));
+        assert_eq!((session.description.as_str(), session.tag.as_str()), ("Daily standup", "meetings"));
+        assert_eq!(app_manager.get_running_session_time_left(), Some(TimeDelta::minutes(15)));
+    }
+
+    #[test]
+    fn choosing_the_tag_of_a_template_fills_in_its_description()
+    {
+        let mut app_manager = open_app("events-tag-template", &[]);
+        app_manager.config.templates.push(get_standup_template());
+
+        press(&mut app_manager, KEY_NEW);
+        press(&mut app_manager, KEY_TAB);
+        "meetings".chars().for_each(|character| press(&mut app_manager, Key::new(KeyCode::Char(character))));
+        press(&mut app_manager, KEY_ENTER);
+        press(&mut app_manager, KEY_ENTER);
+
+        assert_eq!(app_manager.description_buffer, "Daily standup");
+        assert_eq!(app_manager.timer_buffer, "15m");
+    }
+
+    #[test]
+    fn bulk_delete_keeps_the_running_session()
+    {
+        let mut app_manager = open_app(
+            "events-bulk-delete",
+            &[
+                "12-01-2026;Plan the sprint;meeting;09:00:00;10:15:00;12-01-2026;1;",
+                "12-01-2026;Fix login redirect;code;10:30:00;12:45:30;12-01-2026;2;",
+            ],
+        );
+        app_manager.start_session("Review", Some("code"), None).expect("Starting the session failed.");
+
+        press(&mut app_manager, KEY_SELECT);
+        press(&mut app_manager, KEY_MARK);
+        assert!(app_manager.marked_session_ids.is_empty());
+        assert_eq!(app_manager.get_status_message(), Some("THE RUNNING SESSION CAN'T BE MARKED"));
+
+        press(&mut app_manager, KEY_DOWN);
+        press(&mut app_manager, KEY_MARK);
+        press(&mut app_manager, KEY_DELETE);
+        press(&mut app_manager, KEY_YES);
+
+        let descriptions = app_manager.sessions.iter().map(|session| session.description.as_str()).collect::<Vec<&str>>();
+        assert_eq!(descriptions, ["Plan the sprint", "Review"]);
+        assert!(app_manager.is_last_session_still_running());
+    }
+
+    #[test]
+    fn selecting_without_sessions_does_nothing()
+    {
+        let mut app_manager = open_app("events-select-empty", &[]);
+
+        press(&mut app_manager, KEY_SELECT);
+
+        assert!(matches!(app_manager.state, CommandState::Idle));
+    }
+
+    #[test]
+    fn session_keys_without_sessions_do_nothing()
+    {
+        let mut app_manager = open_app("events-session-keys-empty", &[]);
+
+        for key in [KEY_EDIT, KEY_COPY, KEY_DELETE, KEY_DUPLICATE]
+        {
+            press(&mut app_manager, key);
+
+            assert!(matches!(app_manager.state, CommandState::Idle));
+        }
+    }
+}
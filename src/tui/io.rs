@@ -0,0 +1,821 @@
+use crate::app_state::SortColumn;
+use crate::sprites::to_ascii;
+use crate::text::get_display_width;
+use crate::tui::colors::{ColorValue, Theme, ThemeColor};
+use crossterm::cursor;
+use crossterm::event;
+use crossterm::event::{
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, KeyCode, KeyEvent, KeyModifiers,
+};
+use crossterm::style;
+use crossterm::style::{SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use crossterm::{terminal, QueueableCommand};
+use ratatui::backend::CrosstermBackend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::{StatefulWidget, Widget};
+use ratatui::Terminal;
+use std::cell::RefCell;
+use std::env;
+use std::fmt::{Display, Formatter};
+use std::io::{self, stdout, IsTerminal, Stdout, Write};
+use std::rc::Rc;
+
+/// Asks for a passphrase without echoing it, before the TUI takes over the terminal. `None` if cancelled with Escape
+/// or Ctrl+C.
+///
+/// # Errors
+///
+/// If there is no terminal to type it in, or the terminal can't be switched to raw mode and back.
+pub fn read_passphrase(prompt: &str) -> io::Result<Option<String>>
+{
+    if !io::stdin().is_terminal()
+    {
+        return Err(io::Error::other("There is no terminal to type the passphrase in."));
+    }
+
+    print!("{prompt}");
+    stdout().flush()?;
+    enable_raw_mode()?;
+
+    let passphrase = read_typed_passphrase();
+
+    disable_raw_mode()?;
+    println!();
+
+    passphrase
+}
+
+/// The keys typed until Enter, with raw mode on.
+fn read_typed_passphrase() -> io::Result<Option<String>>
+{
+    let mut passphrase = Some(String::new());
+
+    while let Some(typed) = &mut passphrase
+    {
+        let Some(key_event) = event::read()?.as_key_press_event()
+        else
+        {
+            continue;
+        };
+
+        match key_event.code
+        {
+            KeyCode::Enter => break,
+            KeyCode::Esc => passphrase = None,
+            KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => passphrase = None,
+            KeyCode::Backspace =>
+            {
+                typed.pop();
+            }
+            KeyCode::Char(character) => typed.push(character),
+            _ =>
+            {}
+        }
+    }
+
+    Ok(passphrase)
+}
+
+/// A pressed key together with the modifiers held, so a binding can tell `z` from Ctrl+Z.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Key
+{
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key
+{
+    #[must_use]
+    pub const fn new(code: KeyCode) -> Self
+    {
+        Key {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[must_use]
+    pub const fn ctrl(code: KeyCode) -> Self
+    {
+        Key {
+            code,
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    #[must_use]
+    pub const fn alt(code: KeyCode) -> Self
+    {
+        Key {
+            code,
+            modifiers: KeyModifiers::ALT,
+        }
+    }
+
+    #[must_use]
+    pub const fn shift(code: KeyCode) -> Self
+    {
+        Key {
+            code,
+            modifiers: KeyModifiers::SHIFT,
+        }
+    }
+
+    /// The character typed with this key, `None` for keys that are not text or are held with Ctrl or Alt.
+    #[must_use]
+    pub fn get_typed_char(&self) -> Option<char>
+    {
+        match (self.code, self.modifiers)
+        {
+            (KeyCode::Char(character), KeyModifiers::NONE) => Some(character),
+            _ => None,
+        }
+    }
+}
+
+impl From<KeyEvent> for Key
+{
+    /// Shift already shows in the character typed, and terminals disagree on whether they report it along, so it is
+    /// dropped for characters. Shift+Tab keeps it, some terminals send it as a plain back tab.
+    fn from(key_event: KeyEvent) -> Self
+    {
+        match key_event.code
+        {
+            KeyCode::Char(_) => Key {
+                code: key_event.code,
+                modifiers: key_event.modifiers - KeyModifiers::SHIFT,
+            },
+            KeyCode::BackTab => Key::shift(KeyCode::BackTab),
+            code => Key {
+                code,
+                modifiers: key_event.modifiers,
+            },
+        }
+    }
+}
+
+impl Display for Key
+{
+    /// The key as it is offered on screen, e.g. `CTRL+Z`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    {
+        for (modifier, name) in [(KeyModifiers::CONTROL, "CTRL"), (KeyModifiers::ALT, "ALT"), (KeyModifiers::SHIFT, "SHIFT")]
+        {
+            if self.modifiers.contains(modifier)
+            {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        let name = match self.code
+        {
+            KeyCode::Char(' ') => "SPACE".to_string(),
+            KeyCode::Char(character) if self.modifiers.is_empty() => character.to_string(),
+            KeyCode::Char(character) => character.to_uppercase().to_string(),
+            KeyCode::F(number) => format!("F{number}"),
+            KeyCode::Enter => "ENTER".to_string(),
+            KeyCode::Tab | KeyCode::BackTab => "TAB".to_string(),
+            KeyCode::Esc => "ESC".to_string(),
+            KeyCode::Backspace => "BACKSPACE".to_string(),
+            KeyCode::Up => "UP".to_string(),
+            KeyCode::Down => "DOWN".to_string(),
+            KeyCode::Left => "LEFT".to_string(),
+            KeyCode::Right => "RIGHT".to_string(),
+            KeyCode::PageUp => "PGUP".to_string(),
+            KeyCode::PageDown => "PGDN".to_string(),
+            KeyCode::Delete => "DEL".to_string(),
+            KeyCode::Home => "HOME".to_string(),
+            KeyCode::End => "END".to_string(),
+            code => format!("{code}").to_uppercase(),
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// What a click on a screen area does.
+#[derive(PartialEq, Copy, Clone)]
+pub enum ClickTarget
+{
+    Key(Key),
+    Row(usize),
+    Column(Option<SortColumn>),
+}
+
+/// A clickable line segment registered while rendering.
+#[derive(PartialEq, Copy, Clone)]
+pub struct ClickArea
+{
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub target: ClickTarget,
+}
+
+/// Either a slot of the active theme or a fixed color.
+#[derive(Copy, Clone)]
+pub enum DrawColor
+{
+    Theme(ThemeColor),
+    Value(ColorValue),
+}
+
+impl From<ThemeColor> for DrawColor
+{
+    fn from(color: ThemeColor) -> Self
+    {
+        DrawColor::Theme(color)
+    }
+}
+
+impl From<ColorValue> for DrawColor
+{
+    fn from(color: ColorValue) -> Self
+    {
+        DrawColor::Value(color)
+    }
+}
+
+#[derive(Copy, Clone)]
+pub enum ColorType
+{
+    Foreground,
+    Background,
+}
+#[derive(Debug)]
+pub struct Vector2
+{
+    pub x: u16,
+    pub y: u16,
+}
+
+impl Vector2
+{
+    #[must_use]
+    pub fn new(x: u16, y: u16) -> Self
+    {
+        Vector2 {
+            x,
+            y,
+        }
+    }
+}
+
+impl From<(u16, u16)> for Vector2
+{
+    fn from(value: (u16, u16)) -> Self
+    {
+        Self::new(value.0, value.1)
+    }
+}
+impl Display for Vector2
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    {
+        write!(f, "( {}, {} )", self.x, self.y)
+    }
+}
+
+/// Where `Out` sends the frames it draws.
+pub trait Backend
+{
+    fn get_size(&self) -> Vector2;
+
+    /// Shows `frame`, only sending the cells that changed since the last one where the backend can tell.
+    fn draw(&mut self, frame: &Buffer);
+
+    /// Empties the screen, so the next frame is sent in full.
+    fn clear(&mut self);
+
+    /// Sets the title of the terminal window or tab.
+    fn set_title(&mut self, _title: &str) {}
+
+    /// Hands the terminal back for another program to use, until `resume`.
+    fn suspend(&mut self) {}
+
+    fn resume(&mut self) {}
+}
+
+/// Saves the terminal title on a stack, for terminals that support it, so the one before the app can be restored.
+const PUSH_TITLE: &str = "\x1b[22;0t";
+const POP_TITLE: &str = "\x1b[23;0t";
+
+/// Draws to the terminal through ratatui, which keeps the last frame to send only what changed. The terminal is in raw
+/// mode for as long as the backend lives.
+pub struct TerminalBackend
+{
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl Default for TerminalBackend
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl TerminalBackend
+{
+    /// Takes over the terminal, switching it to raw mode.
+    ///
+    /// # Panics
+    ///
+    /// If the terminal can't be switched to raw mode or written to.
+    #[must_use]
+    pub fn new() -> Self
+    {
+        enable_raw_mode().expect("enable_raw_mode() failed.");
+
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout())).expect("Opening terminal failed.");
+        terminal.backend_mut().queue(style::Print(PUSH_TITLE)).expect("Saving title failed.");
+
+        TerminalBackend {
+            terminal,
+        }
+    }
+
+    /// Leaves the terminal as it was before the app started.
+    fn restore_terminal(&mut self)
+    {
+        self.terminal
+            .backend_mut()
+            .queue(SetForegroundColor(style::Color::Reset))
+            .expect("clean_up() failed.")
+            .queue(SetBackgroundColor(style::Color::Reset))
+            .expect("clean_up() failed.")
+            .queue(cursor::Show)
+            .expect("clean_up() failed.")
+            .queue(DisableMouseCapture)
+            .expect("Disable mouse capture failed.")
+            .queue(DisableBracketedPaste)
+            .expect("Disable bracketed paste failed.")
+            .queue(terminal::Clear(terminal::ClearType::All))
+            .expect("Clear all failed.")
+            .queue(terminal::EnableLineWrap)
+            .expect("Disable line wrap failed.")
+            .queue(cursor::MoveTo(0, 0))
+            .expect("Cursor move failed.")
+            .queue(style::Print(POP_TITLE))
+            .expect("Restoring title failed.");
+
+        disable_raw_mode().expect("Disable raw mode failed.");
+
+        self.terminal.backend_mut().flush().expect("Flushing terminal failed.");
+    }
+}
+
+impl Backend for TerminalBackend
+{
+    fn get_size(&self) -> Vector2
+    {
+        Vector2::from(terminal::size().expect("get_terminal_size() failed."))
+    }
+
+    fn draw(&mut self, frame: &Buffer)
+    {
+        self.terminal
+            .draw(|terminal_frame| copy_cells(frame, terminal_frame.buffer_mut()))
+            .expect("Drawing frame failed.");
+    }
+
+    fn clear(&mut self)
+    {
+        self.terminal.clear().expect("Clear all failed.");
+        self.terminal
+            .backend_mut()
+            .queue(cursor::Hide)
+            .expect("Hiding cursor failed.")
+            .queue(terminal::DisableLineWrap)
+            .expect("Disable line wrap failed.")
+            .queue(EnableMouseCapture)
+            .expect("Enable mouse capture failed.")
+            .queue(EnableBracketedPaste)
+            .expect("Enable bracketed paste failed.");
+        self.terminal.backend_mut().flush().expect("Flushing terminal failed.");
+    }
+
+    fn set_title(&mut self, title: &str)
+    {
+        self.terminal.backend_mut().queue(terminal::SetTitle(title)).expect("set_title() failed.");
+    }
+
+    fn suspend(&mut self)
+    {
+        self.restore_terminal();
+    }
+
+    fn resume(&mut self)
+    {
+        enable_raw_mode().expect("enable_raw_mode() failed.");
+        self.terminal.backend_mut().queue(style::Print(PUSH_TITLE)).expect("Saving title failed.");
+        self.clear();
+    }
+}
+
+impl Drop for TerminalBackend
+{
+    fn drop(&mut self)
+    {
+        self.restore_terminal();
+    }
+}
+
+/// Copies the cells `from` and `to` both cover, leaving the rest of `to` as it is.
+fn copy_cells(from: &Buffer, to: &mut Buffer)
+{
+    let area = from.area.intersection(to.area);
+
+    for position in area.positions()
+    {
+        to[position] = from[position].clone();
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Cell
+{
+    pub character: char,
+    pub foreground: Color,
+    pub background: Color,
+}
+
+/// What a `BufferBackend` shows.
+struct Screen
+{
+    buffer: Buffer,
+    title: String,
+}
+
+/// Keeps the frames in memory instead of showing them in the terminal, so screens can be looked at in tests. Clones
+/// share the screen, one can be kept to read what was drawn through the `Out` owning the other.
+#[derive(Clone)]
+pub struct BufferBackend
+{
+    screen: Rc<RefCell<Screen>>,
+}
+
+impl BufferBackend
+{
+    #[allow(clippy::needless_pass_by_value)]
+    #[must_use]
+    pub fn new(size: Vector2) -> Self
+    {
+        BufferBackend {
+            screen: Rc::new(RefCell::new(Screen {
+                buffer: Buffer::empty(Rect::new(0, 0, size.x, size.y)),
+                title: String::new(),
+            })),
+        }
+    }
+
+    /// `None` for the second half of a wide character and outside the screen.
+    #[must_use]
+    pub fn get_cell(&self, position: &Vector2) -> Option<Cell>
+    {
+        let screen = self.screen.borrow();
+        let area = screen.buffer.area;
+
+        if position.x >= area.width || position.y >= area.height
+        {
+            return None;
+        }
+
+        let mut x = 0;
+
+        while x < position.x
+        {
+            x += get_display_width(screen.buffer[(x, position.y)].symbol()).max(1) as u16;
+        }
+
+        if x != position.x
+        {
+            return None;
+        }
+
+        let cell = &screen.buffer[(x, position.y)];
+
+        Some(Cell {
+            character: cell.symbol().chars().next().unwrap_or(' '),
+            foreground: cell.fg,
+            background: cell.bg,
+        })
+    }
+
+    /// The characters of the screen, one line per row without trailing spaces.
+    #[must_use]
+    pub fn get_text(&self) -> String
+    {
+        let screen = self.screen.borrow();
+        let area = screen.buffer.area;
+        let mut lines = Vec::with_capacity(area.height as usize);
+
+        for y in 0..area.height
+        {
+            let mut line = String::new();
+            let mut x = 0;
+
+            while x < area.width
+            {
+                let symbol = screen.buffer[(x, y)].symbol();
+                line.push_str(symbol);
+                x += get_display_width(symbol).max(1) as u16;
+            }
+
+            lines.push(line.trim_end().to_string());
+        }
+
+        lines.join("\n")
+    }
+
+    /// The last title set, empty if none was.
+    #[must_use]
+    pub fn get_title(&self) -> String
+    {
+        self.screen.borrow().title.clone()
+    }
+}
+
+impl Backend for BufferBackend
+{
+    fn get_size(&self) -> Vector2
+    {
+        let area = self.screen.borrow().buffer.area;
+
+        Vector2::new(area.width, area.height)
+    }
+
+    fn draw(&mut self, frame: &Buffer)
+    {
+        copy_cells(frame, &mut self.screen.borrow_mut().buffer);
+    }
+
+    fn clear(&mut self)
+    {
+        self.screen.borrow_mut().buffer.reset();
+    }
+
+    fn set_title(&mut self, title: &str)
+    {
+        self.screen.borrow_mut().title = title.to_string();
+    }
+}
+
+/// Draws a frame at a time into a buffer, in the colors on top of its color stacks, and hands it to the backend on
+/// `render`.
+pub struct Out
+{
+    backend: Box<dyn Backend>,
+    frame: Buffer,
+    cursor: Vector2,
+    foreground_color_stack: Vec<ColorValue>,
+    background_color_stack: Vec<ColorValue>,
+    theme: Theme,
+    colors_enabled: bool,
+    true_color: bool,
+    ascii: bool,
+    /// The title last sent, so it is only sent again when it changes.
+    title: Option<String>,
+}
+
+impl Default for Out
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl Out
+{
+    #[must_use]
+    pub fn new() -> Out
+    {
+        Self::with_backend(Box::new(TerminalBackend::new()))
+    }
+
+    #[must_use]
+    pub fn with_backend(backend: Box<dyn Backend>) -> Out
+    {
+        let size = backend.get_size();
+
+        Out {
+            backend,
+            frame: Buffer::empty(Rect::new(0, 0, size.x, size.y)),
+            cursor: Vector2::new(0, 0),
+            foreground_color_stack: vec![],
+            background_color_stack: vec![],
+            theme: Theme::default(),
+            colors_enabled: true,
+            true_color: env::var("COLORTERM").is_ok_and(|color_term| color_term == "truecolor" || color_term == "24bit"),
+            ascii: false,
+            title: None,
+        }
+    }
+
+    /// Runs `run` with the terminal handed back, e.g. for an editor, and takes it over again afterwards.
+    pub fn suspend_while<T>(&mut self, run: impl FnOnce() -> T) -> T
+    {
+        self.backend.suspend();
+        let result = run();
+        self.backend.resume();
+        self.title = None;
+
+        self.clear_screen();
+
+        result
+    }
+
+    /// Empties the screen and starts a new frame.
+    pub fn clear_screen(&mut self)
+    {
+        self.backend.clear();
+        self.start_frame();
+    }
+
+    #[must_use]
+    pub fn get_terminal_size(&self) -> Vector2
+    {
+        self.backend.get_size()
+    }
+
+    /// Shows what was drawn since the last call and starts a new, empty frame sized to the terminal.
+    pub fn render(&mut self)
+    {
+        self.backend.draw(&self.frame);
+        self.start_frame();
+    }
+
+    fn start_frame(&mut self)
+    {
+        let size = self.backend.get_size();
+
+        self.frame.resize(Rect::new(0, 0, size.x, size.y));
+        self.frame.reset();
+    }
+
+    #[must_use]
+    pub fn get_theme(&self) -> &Theme
+    {
+        &self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: Theme)
+    {
+        self.theme = theme;
+    }
+
+    /// With colors disabled the color stacks are still kept, but everything is drawn in the terminal's own colors.
+    pub fn set_colors_enabled(&mut self, colors_enabled: bool)
+    {
+        self.colors_enabled = colors_enabled;
+    }
+
+    pub fn set_ascii(&mut self, ascii: bool)
+    {
+        self.ascii = ascii;
+    }
+
+    #[must_use]
+    pub fn is_ascii(&self) -> bool
+    {
+        self.ascii
+    }
+
+    pub fn set_title(&mut self, title: &str)
+    {
+        if self.title.as_deref() != Some(title)
+        {
+            self.backend.set_title(title);
+            self.title = Some(title.to_string());
+        }
+    }
+
+    pub fn push_color<T: Into<DrawColor>>(&mut self, color_type: ColorType, color: T)
+    {
+        let color = match color.into()
+        {
+            DrawColor::Theme(theme_color) => self.theme.get(theme_color),
+            DrawColor::Value(color_value) => color_value,
+        };
+
+        match color_type
+        {
+            ColorType::Foreground => self.foreground_color_stack.push(color),
+            ColorType::Background => self.background_color_stack.push(color),
+        }
+    }
+
+    /// Goes back to the color pushed before the one on top of the stack of `color_type`.
+    ///
+    /// # Panics
+    ///
+    /// If nothing is left on the stack, as every pop needs a push before it.
+    pub fn pop_color(&mut self, color_type: ColorType)
+    {
+        let color_stack = match color_type
+        {
+            ColorType::Foreground => &mut self.foreground_color_stack,
+            ColorType::Background => &mut self.background_color_stack,
+        };
+
+        assert!(!color_stack.is_empty());
+        color_stack.pop();
+    }
+
+    /// The colors on top of the stacks, as ratatui widgets are styled.
+    #[must_use]
+    pub fn get_style(&self) -> Style
+    {
+        if !self.colors_enabled
+        {
+            return Style::default();
+        }
+
+        let get_color = |color_stack: &Vec<ColorValue>| color_stack.last().map_or(Color::Reset, |color| color.to_color(self.true_color));
+
+        Style::default().fg(get_color(&self.foreground_color_stack)).bg(get_color(&self.background_color_stack))
+    }
+
+    /// The theme colors as a ratatui style, for widgets with parts in colors of their own.
+    #[must_use]
+    pub fn get_theme_style(&self, foreground: ThemeColor, background: ThemeColor) -> Style
+    {
+        if !self.colors_enabled
+        {
+            return Style::default();
+        }
+
+        Style::default()
+            .fg(self.theme.get(foreground).to_color(self.true_color))
+            .bg(self.theme.get(background).to_color(self.true_color))
+    }
+
+    /// `text` as it is drawn, with the sprites replaced where only ASCII can be shown.
+    pub fn get_printable(&self, text: &str) -> String
+    {
+        if self.ascii
+        {
+            return text.chars().map(to_ascii).collect();
+        }
+
+        text.to_string()
+    }
+
+    /// Checks that every pushed color was popped again once a frame is drawn.
+    ///
+    /// # Panics
+    ///
+    /// If a color is still on a stack.
+    pub fn check_color_stacks(&self)
+    {
+        assert!(self.foreground_color_stack.is_empty() && self.background_color_stack.is_empty());
+    }
+
+    pub fn go_to_position(&mut self, position: &Vector2) -> &mut Self
+    {
+        self.cursor = Vector2::new(position.x, position.y);
+
+        self
+    }
+
+    /// Like the terminal with line wrap disabled, what doesn't fit the row is cut off.
+    pub fn draw<T: Display>(&mut self, sprite: T) -> &mut Self
+    {
+        let sprite = self.get_printable(&sprite.to_string());
+        let area = self.frame.area;
+
+        if self.cursor.x < area.width && self.cursor.y < area.height
+        {
+            let style = self.get_style();
+            self.frame.set_stringn(self.cursor.x, self.cursor.y, &sprite, usize::MAX, style);
+        }
+
+        self.cursor.x = self.cursor.x.saturating_add(get_display_width(&sprite) as u16);
+
+        self
+    }
+
+    pub fn draw_at<T: Display>(&mut self, sprite: T, position: &Vector2) -> &mut Self
+    {
+        self.go_to_position(position).draw(sprite);
+
+        self
+    }
+
+    /// Renders a ratatui widget into `area` of the frame, cut off where it leaves the screen.
+    pub fn draw_widget(&mut self, widget: impl Widget, area: Rect)
+    {
+        widget.render(area.intersection(self.frame.area), &mut self.frame);
+    }
+
+    /// Renders a ratatui widget that keeps state between frames, like the scroll position of a list.
+    pub fn draw_stateful_widget<W: StatefulWidget>(&mut self, widget: W, area: Rect, state: &mut W::State)
+    {
+        widget.render(area.intersection(self.frame.area), &mut self.frame, state);
+    }
+}
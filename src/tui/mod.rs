@@ -0,0 +1,8 @@
+//! The terminal frontend: drawing to the terminal, reading its keys and mouse, and turning them into changes of the
+//! state kept by [`crate::app_manager::AppManager`].
+
+pub mod colors;
+pub mod control_keys;
+pub mod events;
+pub mod io;
+pub mod widgets;
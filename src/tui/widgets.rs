@@ -0,0 +1,299 @@
+//! Building blocks the screens are put together from, on top of ratatui: areas to lay things out in, framed windows,
+//! popups, scrolling lists and key hints. They only draw into an [`Out`], so what a view shows and where stays in the
+//! app.
+
+use crate::sprites::{ARROW, ARROW_DOWN, ARROW_UP, CURSOR, DIVIDER_H, INTERSECT_L, INTERSECT_R};
+use crate::text::{get_display_width, get_tail_within_width, truncate_to_width};
+use crate::text_input::TextInput;
+use crate::tui::colors::{COL_BG_POPUP, COL_OUTLINE_POPUP, COL_TEXT_BLACK, COL_TEXT_HIGHLIGHT, COL_TEXT_RED_DARK, COL_WINDOW_SHADOW};
+use crate::tui::io::{ColorType, Out, Vector2};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::symbols::border;
+use ratatui::widgets::{Block, Clear, HighlightSpacing, List, ListState};
+
+pub use ratatui::layout::Rect;
+
+/// Cells from the left edge of a window to its title.
+const TITLE_OFFSET: u16 = 2;
+
+/// The window frame where only ASCII can be drawn, matching `sprites::to_ascii`.
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "=",
+    horizontal_bottom: "=",
+};
+
+/// Positions inside an area on screen.
+pub trait Area
+{
+    fn get_position(&self) -> Vector2;
+
+    /// The cell `column` cells right of the left edge on row `row`, both counted from the top left corner.
+    fn at(&self, column: u16, row: u16) -> Vector2;
+}
+
+impl Area for Rect
+{
+    fn get_position(&self) -> Vector2
+    {
+        Vector2::new(self.x, self.y)
+    }
+
+    fn at(&self, column: u16, row: u16) -> Vector2
+    {
+        Vector2::new(self.x + column, self.y + row)
+    }
+}
+
+/// `size` in the middle of a screen of `screen_size`, pushed to the top left corner when it does not fit.
+#[must_use]
+pub fn get_centered_area(size: &Vector2, screen_size: &Vector2) -> Rect
+{
+    Rect::new(screen_size.x.saturating_sub(size.x) / 2, screen_size.y.saturating_sub(size.y) / 2, size.x, size.y)
+}
+
+/// Splits `area` into columns of `widths` with `gap` cells between them. A width of 0 takes what the other columns
+/// leave over.
+#[must_use]
+pub fn layout_columns(area: Rect, widths: &[u16], gap: u16) -> Vec<Rect>
+{
+    let constraints = widths.iter().map(|width| {
+        if *width == 0
+        {
+            Constraint::Fill(1)
+        }
+        else
+        {
+            Constraint::Length(*width)
+        }
+    });
+
+    Layout::horizontal(constraints).spacing(gap).split(area).to_vec()
+}
+
+/// Draws the frame of a window over `area` and clears its inside, in the current colors.
+pub fn draw_frame(renderer: &mut Out, area: &Rect)
+{
+    let border_set = if renderer.is_ascii()
+    {
+        ASCII_BORDER
+    }
+    else
+    {
+        border::DOUBLE
+    };
+    let frame = Block::bordered().border_set(border_set).style(renderer.get_style());
+
+    renderer.draw_widget(Clear, *area);
+    renderer.draw_widget(frame, *area);
+}
+
+/// Darkens the cells right of and below `area`, so a window looks lifted off what is under it.
+pub fn draw_shadow(renderer: &mut Out, area: &Rect)
+{
+    renderer.push_color(ColorType::Background, COL_WINDOW_SHADOW);
+    renderer.draw_at(" ".repeat(area.width as usize), &Vector2::new(area.x + 1, area.bottom()));
+
+    for row in 1..=area.height
+    {
+        renderer.draw_at("  ", &area.at(area.width, row));
+    }
+    renderer.pop_color(ColorType::Background);
+}
+
+/// Writes `title` into the top edge of the window over `area`, in the current colors.
+pub fn draw_title(renderer: &mut Out, title: &str, area: &Rect)
+{
+    renderer.draw_at(format!(" {} ", title), &area.at(TITLE_OFFSET, 0));
+}
+
+/// Writes `title` into the top edge of the window over `area` inverted, the way popups and dropdowns are labeled.
+pub fn draw_inverted_title(renderer: &mut Out, title: &str, area: &Rect)
+{
+    renderer.push_color(ColorType::Background, COL_TEXT_BLACK);
+    renderer.push_color(ColorType::Foreground, COL_BG_POPUP);
+    draw_title(renderer, title, area);
+    renderer.pop_color(ColorType::Background);
+    renderer.pop_color(ColorType::Foreground);
+}
+
+/// A window lifted off the screen by a shadow, with an inverted title when `title` is given. Used for input fields
+/// and dropdowns inside a popup, in its colors.
+pub fn draw_window(renderer: &mut Out, area: &Rect, title: Option<&str>)
+{
+    draw_frame(renderer, area);
+    draw_shadow(renderer, area);
+
+    if let Some(title) = title
+    {
+        draw_inverted_title(renderer, title, area);
+    }
+}
+
+/// Opens a popup over `area`: pushes the popup colors and draws its window with `title`. The colors stay pushed for the
+/// content until [`close_popup`].
+pub fn open_popup(renderer: &mut Out, area: &Rect, title: &str)
+{
+    renderer.push_color(ColorType::Background, COL_BG_POPUP);
+    renderer.push_color(ColorType::Foreground, COL_OUTLINE_POPUP);
+    draw_window(renderer, area, Some(title));
+}
+
+/// Pops the colors pushed by [`open_popup`].
+pub fn close_popup(renderer: &mut Out)
+{
+    renderer.pop_color(ColorType::Foreground);
+    renderer.pop_color(ColorType::Background);
+}
+
+/// Splits the window over `area` with a line across it on screen row `y`, joined to the frame on both sides.
+pub fn draw_divider(renderer: &mut Out, area: &Rect, y: u16)
+{
+    renderer.draw_at(INTERSECT_L, &Vector2::new(area.x, y));
+    renderer.draw(DIVIDER_H.to_string().repeat(area.width.saturating_sub(2) as usize));
+    renderer.draw(INTERSECT_R);
+}
+
+/// Draws the label of a form field padded to `width`, inverted when the field is `selected` and highlighted otherwise.
+/// The cursor is left behind it for the value.
+pub fn draw_field_label(renderer: &mut Out, label: &str, width: usize, position: &Vector2, selected: bool)
+{
+    if selected
+    {
+        renderer.push_color(ColorType::Background, COL_TEXT_BLACK);
+        renderer.push_color(ColorType::Foreground, COL_BG_POPUP);
+    }
+    else
+    {
+        renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+    }
+
+    renderer.draw_at(format!("{:<width$}", label), position);
+
+    if selected
+    {
+        renderer.pop_color(ColorType::Background);
+    }
+    renderer.pop_color(ColorType::Foreground);
+}
+
+/// Runs `draw` inverted when `selected`, the way the selected row of a list stands out.
+pub fn draw_selectable(renderer: &mut Out, selected: bool, draw: impl FnOnce(&mut Out))
+{
+    if selected
+    {
+        renderer.push_color(ColorType::Background, COL_TEXT_BLACK);
+        renderer.push_color(ColorType::Foreground, COL_BG_POPUP);
+    }
+
+    draw(renderer);
+
+    if selected
+    {
+        renderer.pop_color(ColorType::Background);
+        renderer.pop_color(ColorType::Foreground);
+    }
+}
+
+/// The arrow in front of the selected row of a list, blank for the others.
+#[must_use]
+pub fn get_selection_arrow(selected: bool) -> char
+{
+    if selected
+    {
+        ARROW
+    }
+    else
+    {
+        ' '
+    }
+}
+
+/// Draws `text` as a row of a list at `position`, behind an arrow and inverted when `selected`.
+pub fn draw_list_row(renderer: &mut Out, text: &str, position: &Vector2, selected: bool)
+{
+    draw_selectable(renderer, selected, |renderer| {
+        renderer.draw_at(format!(" {} {}", get_selection_arrow(selected), text), position);
+    });
+}
+
+/// Draws `rows` as a list filling `area`, the selected one behind an arrow and inverted, scrolled so it stays in view.
+/// Returns the index of the first row shown.
+pub fn draw_list(renderer: &mut Out, rows: &[String], selected_index: Option<usize>, area: Rect) -> usize
+{
+    let rows = rows.iter().map(|row| renderer.get_printable(row)).collect::<Vec<String>>();
+    let list = List::new(rows)
+        .style(renderer.get_style())
+        .highlight_style(renderer.get_theme_style(COL_BG_POPUP, COL_TEXT_BLACK))
+        .highlight_symbol(renderer.get_printable(&format!(" {} ", ARROW)))
+        .highlight_spacing(HighlightSpacing::Always);
+    let mut state = ListState::default().with_selected(selected_index);
+
+    renderer.draw_stateful_widget(list, area, &mut state);
+
+    state.offset()
+}
+
+/// The first of `visible_count` rows to show so the selected row stays in view, scrolling only once it would fall off
+/// the bottom.
+#[must_use]
+pub fn get_first_visible_row(selected_index: usize, visible_count: usize) -> usize
+{
+    selected_index.saturating_sub(visible_count.saturating_sub(1))
+}
+
+/// Marks the frame of the window over `area` with arrows where a list of `total_count` rows, showing `visible_count`
+/// from `first_row`, continues above or below.
+pub fn draw_scroll_markers(renderer: &mut Out, area: &Rect, first_row: usize, visible_count: usize, total_count: usize)
+{
+    let marker_column = area.width.saturating_sub(3);
+
+    if first_row > 0
+    {
+        renderer.draw_at(ARROW_UP, &area.at(marker_column, 0));
+    }
+
+    if first_row + visible_count < total_count
+    {
+        renderer.draw_at(ARROW_DOWN, &area.at(marker_column, area.height.saturating_sub(1)));
+    }
+}
+
+/// Draws `input` at `position` within `width` cells, scrolled so its cursor stays in view. The cursor is a block behind
+/// the text and inverts the character it is on inside it.
+pub fn draw_text_input(renderer: &mut Out, input: &TextInput, position: &Vector2, width: usize)
+{
+    let before_cursor = get_tail_within_width(input.get_text_before_cursor(), width.saturating_sub(1));
+    let cursor_x = position.x + get_display_width(&before_cursor) as u16;
+    let after_cursor = truncate_to_width(input.get_text_after_cursor(), width.saturating_sub(get_display_width(&before_cursor)));
+
+    renderer.draw_at(format!("{}{}", before_cursor, after_cursor), position);
+
+    if let Some(character) = input.get_char_at_cursor()
+    {
+        renderer.push_color(ColorType::Background, COL_TEXT_HIGHLIGHT);
+        renderer.push_color(ColorType::Foreground, COL_TEXT_BLACK);
+        renderer.draw_at(character, &Vector2::new(cursor_x, position.y));
+        renderer.pop_color(ColorType::Foreground);
+        renderer.pop_color(ColorType::Background);
+    }
+    else
+    {
+        renderer.draw_at(CURSOR, &Vector2::new(cursor_x, position.y));
+    }
+}
+
+/// Draws `[k]label` at the cursor with the key highlighted, the way keys are offered on screen.
+pub fn draw_key_hint(renderer: &mut Out, key: char, label: &str)
+{
+    renderer.draw('[');
+    renderer.push_color(ColorType::Foreground, COL_TEXT_RED_DARK);
+    renderer.draw(key);
+    renderer.pop_color(ColorType::Foreground);
+    renderer.draw(format!("]{}", label));
+}
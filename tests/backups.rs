@@ -0,0 +1,102 @@
+//! Backups of the sessions, taken before every rewrite of them and once a day when the journal is compacted.
+
+use chrono::{NaiveDateTime, TimeDelta};
+use time_tracker_core::database_handler::{DatabaseHandler, DATE_FORMAT, VALUE_SEPARATOR};
+use time_tracker_core::session::Session;
+
+fn get_session(id: u64, start: NaiveDateTime) -> Session
+{
+    let mut session = Session::from("Review", "work", start, Some(start + TimeDelta::minutes(30)));
+    session.id = id;
+
+    session
+}
+
+fn store_sessions(database_handler: &DatabaseHandler, count: u64)
+{
+    let start = NaiveDateTime::parse_from_str("05-01-2026 09:00:00", DATE_FORMAT).expect("Invalid test time.");
+
+    for index in 0..count
+    {
+        let session_start = start + TimeDelta::hours(i64::try_from(index).expect("Too many test sessions."));
+        let session = get_session(index + 1, session_start);
+
+        database_handler
+            .export_session(&session.construct_db_string(VALUE_SEPARATOR, DATE_FORMAT), VALUE_SEPARATOR, DATE_FORMAT)
+            .expect("Storing session failed.");
+    }
+}
+
+#[test]
+fn compacting_the_journal_backs_up_once_a_day()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("backups-compaction");
+
+    store_sessions(&database_handler, 250);
+
+    let backups = database_handler.list_backups();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(database_handler.count_backup_sessions(&backups[0]), 100);
+}
+
+#[test]
+fn rewriting_all_sessions_backs_them_up_every_time()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("backups-rewrite");
+    store_sessions(&database_handler, 3);
+
+    let start = NaiveDateTime::parse_from_str("06-01-2026 09:00:00", DATE_FORMAT).expect("Invalid test time.");
+    let sessions = [get_session(1, start), get_session(2, start + TimeDelta::hours(1))];
+    database_handler.export_all_sessions(&sessions, VALUE_SEPARATOR, DATE_FORMAT).expect("Rewriting failed.");
+    database_handler.export_all_sessions(&sessions[..1], VALUE_SEPARATOR, DATE_FORMAT).expect("Rewriting failed.");
+
+    let backups = database_handler.list_backups();
+    let session_counts = backups.iter().map(|backup| database_handler.count_backup_sessions(backup)).collect::<Vec<usize>>();
+    assert_eq!(session_counts, [2, 3]);
+}
+
+#[test]
+fn daily_backup_is_taken_once_a_day()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("backups-daily");
+
+    database_handler.create_daily_backup(VALUE_SEPARATOR, DATE_FORMAT);
+    assert!(database_handler.list_backups().is_empty());
+
+    store_sessions(&database_handler, 3);
+    database_handler.create_daily_backup(VALUE_SEPARATOR, DATE_FORMAT);
+    database_handler.create_daily_backup(VALUE_SEPARATOR, DATE_FORMAT);
+
+    let backups = database_handler.list_backups();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(database_handler.count_backup_sessions(&backups[0]), 3);
+}
+
+#[test]
+fn backups_taken_back_to_back_are_all_kept()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("backups-back-to-back");
+
+    store_sessions(&database_handler, 3);
+    database_handler.create_daily_backup(VALUE_SEPARATOR, DATE_FORMAT);
+    let first_backup = database_handler.list_backups().remove(0);
+
+    // Restoring backs up the sessions it replaces first.
+    store_sessions(&database_handler, 5);
+    database_handler.restore_backup(&first_backup, VALUE_SEPARATOR, DATE_FORMAT).expect("Restoring failed.");
+    database_handler.restore_backup(&first_backup, VALUE_SEPARATOR, DATE_FORMAT).expect("Restoring failed.");
+
+    let backups = database_handler.list_backups();
+    let session_counts = backups.iter().map(|backup| database_handler.count_backup_sessions(backup)).collect::<Vec<usize>>();
+    assert_eq!(session_counts, [3, 5, 3]);
+}
+
+#[test]
+fn backup_names_of_older_versions_are_still_read()
+{
+    let time = |time: &str| NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S%.3f").ok();
+
+    assert_eq!(DatabaseHandler::get_backup_timestamp("sessions-20260114-103000.txt"), time("2026-01-14 10:30:00.000"));
+    assert_eq!(DatabaseHandler::get_backup_timestamp("sessions-20260114-103000250.txt"), time("2026-01-14 10:30:00.250"));
+    assert_eq!(DatabaseHandler::get_backup_timestamp("sessions-2026.txt"), None);
+}
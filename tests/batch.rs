@@ -0,0 +1,16 @@
+//! Command lines read by `time-tracker batch`, split into arguments the way a shell would.
+
+use time_tracker_core::text::split_arguments;
+
+#[test]
+fn arguments_split_at_spaces_outside_quotes()
+{
+    let split = |line| split_arguments(line).map(|arguments| arguments.join("|"));
+
+    assert_eq!(split("start \"Code review\" --tag work"), Some(String::from("start|Code review|--tag|work")));
+    assert_eq!(split("  add 'It''s done'   --start 09:00 "), Some(String::from("add|Its done|--start|09:00")));
+    assert_eq!(split(r#"amend "Say \"hi\"" --tag a\ b"#), Some(String::from("amend|Say \"hi\"|--tag|a b")));
+    assert_eq!(split("amend \"\" --end 10:00"), Some(String::from("amend||--end|10:00")));
+    assert_eq!(split("start \"Review --tag work"), None);
+    assert_eq!(split("stop \\"), None);
+}
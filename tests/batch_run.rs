@@ -0,0 +1,44 @@
+//! Batches run by the binary on a database in a temp folder.
+#![cfg(all(unix, feature = "tui"))]
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use time_tracker_core::exit_code;
+
+/// Runs the binary with `arguments` and `input` on stdin, keeping its database in `root_path`.
+fn run(root_path: &Path, arguments: &[&str], input: &str) -> Output
+{
+    let mut process = Command::new(env!("CARGO_BIN_EXE_time-tracker"))
+        .args(arguments)
+        .env("XDG_DATA_HOME", root_path.join("data"))
+        .env("XDG_CONFIG_HOME", root_path.join("config"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Running the command failed.");
+    process.stdin.take().expect("No stdin.").write_all(input.as_bytes()).expect("Writing stdin failed.");
+
+    process.wait_with_output().expect("Running the command failed.")
+}
+
+#[test]
+fn session_left_running_fails_the_batch_and_is_not_stored()
+{
+    let root_path = env::temp_dir().join(format!("time-tracker-batch-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root_path);
+
+    let batch = run(&root_path, &["batch"], "add \"Plan\" --tag work --start 09:00 --end 10:00\nstart \"Review\" --tag work\n");
+    let error = String::from_utf8_lossy(&batch.stderr);
+    assert_eq!(batch.status.code(), Some(exit_code::USAGE), "{error}");
+    assert!(error.starts_with("Line 2: the session is still running"), "{error}");
+
+    let log = String::from_utf8_lossy(&run(&root_path, &["log"], "").stdout).into_owned();
+    assert!(log.contains("Plan") && !log.contains("Review"), "{log}");
+    assert!(!String::from_utf8_lossy(&run(&root_path, &["status"], "").stdout).contains("Review"));
+
+    let _ = fs::remove_dir_all(&root_path);
+}
@@ -0,0 +1,40 @@
+//! Text the app puts on the clipboard, which is pasted into standups and emails as it is.
+#![cfg(feature = "tui")]
+
+use chrono::NaiveDateTime;
+use time_tracker_core::app_manager::AppManager;
+use time_tracker_core::clock::MockClock;
+use time_tracker_core::database_handler::{DatabaseHandler, DATE_FORMAT, VALUE_SEPARATOR};
+use time_tracker_core::text_input::TextInput;
+use time_tracker_core::tui::io::{BufferBackend, Out, Vector2};
+
+#[test]
+fn list_text_follows_the_list_and_leaves_out_collapsed_days()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("clipboard");
+    for session_line in [
+        "13-01-2026;Standup;meetings;09:00:00;09:15:00;13-01-2026;1;",
+        "14-01-2026;Review;work;09:00:00;10:30:00;14-01-2026;2;Parser\\nTests;",
+    ]
+    {
+        database_handler.export_session(session_line, VALUE_SEPARATOR, DATE_FORMAT).expect("Storing session failed.");
+    }
+
+    let now = NaiveDateTime::parse_from_str("14-01-2026 11:00:00", DATE_FORMAT).expect("Invalid test time.");
+    let renderer = Out::with_backend(Box::new(BufferBackend::new(Vector2::new(100, 30))));
+    let mut app_manager = AppManager::with_parts(database_handler, None, renderer, Box::new(MockClock::new(now)));
+
+    app_manager.tag_buffer = TextInput::from("work");
+    app_manager.try_store_tag();
+    app_manager.description_buffer = TextInput::from("Release");
+    app_manager.try_start_new_session();
+
+    assert_eq!(
+        app_manager.get_list_text(),
+        "Wed 14 Jan \u{2014} 1h 30m\n  11:00-now  0h 00m  work  Release\n  09:00-10:30  1h 30m  work  Review\n    Parser\n    Tests\n\
+         Tue 13 Jan \u{2014} 0h 15m\n  09:00-09:15  0h 15m  meetings  Standup"
+    );
+
+    app_manager.toggle_all_days_collapsed();
+    assert_eq!(app_manager.get_list_text(), "Wed 14 Jan \u{2014} 1h 30m\n  11:00-now  0h 00m  work  Release\n  09:00-10:30  1h 30m  work  Review\n    Parser\n    Tests\nTue 13 Jan \u{2014} 0h 15m");
+}
@@ -0,0 +1,257 @@
+//! Time math of the app against a clock that only moves when told to.
+#![cfg(feature = "tui")]
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Weekday};
+use time_tracker_core::app_manager::AppManager;
+use time_tracker_core::clock::MockClock;
+use time_tracker_core::config::{DateLabels, WeekNumbers};
+use time_tracker_core::database_handler::DatabaseHandler;
+use time_tracker_core::text_input::TextInput;
+use time_tracker_core::tui::io::{BufferBackend, Out, Vector2};
+
+fn get_time(text: &str) -> NaiveDateTime
+{
+    NaiveDateTime::parse_from_str(text, "%d-%m-%Y %H:%M:%S").expect("Invalid test time.")
+}
+
+/// The app on an empty in-memory workspace at `now`, with a "work" tag to start sessions with.
+fn open_app(workspace: &str, now: &str) -> (AppManager, MockClock)
+{
+    let clock = MockClock::new(get_time(now));
+    let renderer = Out::with_backend(Box::new(BufferBackend::new(Vector2::new(100, 30))));
+    let mut app_manager = AppManager::with_parts(DatabaseHandler::new_ephemeral(workspace), None, renderer, Box::new(clock.clone()));
+
+    app_manager.tag_buffer = TextInput::from("work");
+    app_manager.try_store_tag();
+
+    (app_manager, clock)
+}
+
+fn start_session(app_manager: &mut AppManager, description: &str)
+{
+    app_manager.description_buffer = TextInput::from(description);
+    app_manager.try_start_new_session();
+}
+
+#[test]
+fn running_session_follows_the_clock()
+{
+    let (mut app_manager, clock) = open_app("clock-running", "14-01-2026 10:00:00");
+
+    start_session(&mut app_manager, "Write tests");
+    assert_eq!(app_manager.get_running_session_elapsed(), Some(TimeDelta::zero()));
+
+    clock.advance(TimeDelta::seconds(5405));
+    assert_eq!(app_manager.get_running_session_elapsed(), Some(TimeDelta::seconds(5405)));
+
+    app_manager.end_running_session();
+
+    let session = app_manager.sessions.last().expect("Session was not stored.");
+    assert_eq!(session.end, Some(get_time("14-01-2026 11:30:05")));
+    assert_eq!(app_manager.get_running_session_elapsed(), None);
+    assert_eq!(app_manager.get_today_total(), TimeDelta::seconds(5405));
+}
+
+#[test]
+fn sessions_over_midnight_count_towards_both_days()
+{
+    let (mut app_manager, clock) = open_app("clock-midnight", "13-01-2026 23:00:00");
+
+    start_session(&mut app_manager, "Release");
+    clock.set(get_time("14-01-2026 01:30:00"));
+
+    let first_day = NaiveDate::from_ymd_opt(2026, 1, 13).expect("Invalid test date.");
+
+    assert_eq!(app_manager.get_today(), first_day.succ_opt().expect("Invalid test date."));
+    assert_eq!(app_manager.get_day_total(first_day), TimeDelta::hours(1));
+    assert_eq!(app_manager.get_today_total(), TimeDelta::minutes(90));
+
+    app_manager.end_running_session();
+
+    assert_eq!(app_manager.get_day_total(first_day), TimeDelta::hours(1));
+    assert_eq!(app_manager.get_today_total(), TimeDelta::minutes(90));
+}
+
+#[test]
+fn idle_time_starts_with_working_hours_or_the_last_session()
+{
+    let (mut app_manager, clock) = open_app("clock-idle", "14-01-2026 08:00:00");
+
+    // Before working hours nobody is idle.
+    assert_eq!(app_manager.get_idle_start(), None);
+
+    clock.set(get_time("14-01-2026 10:00:00"));
+    assert_eq!(app_manager.get_idle_start(), Some(get_time("14-01-2026 09:00:00")));
+
+    start_session(&mut app_manager, "Standup");
+    assert_eq!(app_manager.get_idle_start(), None);
+
+    clock.advance(TimeDelta::minutes(15));
+    app_manager.end_running_session();
+    clock.advance(TimeDelta::minutes(40));
+    assert_eq!(app_manager.get_idle_start(), Some(get_time("14-01-2026 10:15:00")));
+
+    clock.set(get_time("14-01-2026 18:00:00"));
+    assert_eq!(app_manager.get_idle_start(), None);
+}
+
+#[test]
+fn timer_counts_down_past_zero()
+{
+    let (mut app_manager, clock) = open_app("clock-timer", "14-01-2026 10:00:00");
+
+    app_manager.timer_buffer = String::from("45m");
+    start_session(&mut app_manager, "Deep work");
+    assert_eq!(app_manager.get_running_session_time_left(), Some(TimeDelta::minutes(45)));
+
+    clock.advance(TimeDelta::minutes(50));
+    assert_eq!(app_manager.get_running_session_time_left(), Some(TimeDelta::minutes(-5)));
+
+    app_manager.end_running_session();
+    start_session(&mut app_manager, "Open-ended");
+    assert_eq!(app_manager.get_running_session_time_left(), None);
+}
+
+#[test]
+fn auto_stop_ends_sessions_as_of_the_stop_time()
+{
+    let (mut app_manager, clock) = open_app("clock-auto-stop", "14-01-2026 17:00:00");
+    app_manager.config.auto_stop = NaiveTime::from_hms_opt(20, 0, 0);
+
+    start_session(&mut app_manager, "Forgot to stop");
+    clock.set(get_time("14-01-2026 19:59:00"));
+    assert!(!app_manager.apply_auto_stop());
+    assert!(app_manager.is_last_session_still_running());
+
+    clock.set(get_time("15-01-2026 08:30:00"));
+    assert!(!app_manager.apply_auto_stop());
+    assert_eq!(app_manager.sessions.last().and_then(|session| session.end), Some(get_time("14-01-2026 20:00:00")));
+
+    app_manager.config.auto_stop_ask = true;
+    start_session(&mut app_manager, "Late night");
+    clock.set(get_time("15-01-2026 21:00:00"));
+    assert!(app_manager.apply_auto_stop());
+    assert!(!app_manager.apply_auto_stop());
+    assert!(app_manager.is_last_session_still_running());
+}
+
+#[test]
+fn terminal_title_shows_the_running_session()
+{
+    let (mut app_manager, clock) = open_app("clock-title", "14-01-2026 10:00:00");
+    assert_eq!(app_manager.get_terminal_title().as_deref(), Some("time-tracker"));
+
+    start_session(&mut app_manager, "Write tests");
+    clock.advance(TimeDelta::seconds(3725));
+    assert_eq!(app_manager.get_terminal_title().as_deref(), Some("01:02:05 Write tests"));
+
+    app_manager.config.terminal_title = Some(String::from("[{tag}] {elapsed}"));
+    assert_eq!(app_manager.get_terminal_title().as_deref(), Some("[work] 01:02:05"));
+
+    app_manager.config.terminal_title = None;
+    assert_eq!(app_manager.get_terminal_title(), None);
+}
+
+#[test]
+fn sessions_under_the_minimum_duration_are_too_short()
+{
+    let (mut app_manager, clock) = open_app("clock-short", "14-01-2026 10:00:00");
+
+    start_session(&mut app_manager, "Wrong tag");
+    clock.advance(TimeDelta::seconds(59));
+    assert!(app_manager.is_running_session_too_short());
+
+    clock.advance(TimeDelta::seconds(1));
+    assert!(!app_manager.is_running_session_too_short());
+
+    app_manager.config.min_session_duration = None;
+    clock.set(get_time("14-01-2026 10:00:00"));
+    assert!(!app_manager.is_running_session_too_short());
+}
+
+#[test]
+fn date_labels_are_relative_to_today()
+{
+    let (mut app_manager, _) = open_app("clock-dates", "14-01-2026 10:00:00");
+    let date = |day| NaiveDate::from_ymd_opt(2026, 1, day).expect("Invalid test date.");
+
+    assert_eq!(app_manager.get_date_label(date(14)), "14 Jan 26");
+
+    app_manager.config.date_labels = DateLabels::Relative;
+    assert_eq!(app_manager.get_date_label(date(14)), "Today");
+    assert_eq!(app_manager.get_date_label(date(13)), "Yesterday");
+    assert_eq!(app_manager.get_date_label(date(12)), "12 Jan 26");
+
+    app_manager.config.date_labels = DateLabels::Weekdays;
+    assert_eq!(app_manager.get_date_label(date(12)), "Monday");
+    assert_eq!(app_manager.get_date_label(date(11)), "11 Jan 26");
+}
+
+#[test]
+fn weeks_start_on_the_configured_day()
+{
+    let (mut app_manager, _) = open_app("clock-weeks", "14-01-2026 10:00:00");
+    let date = |day| NaiveDate::from_ymd_opt(2026, 1, day).expect("Invalid test date.");
+
+    app_manager.open_week_report();
+    assert_eq!(app_manager.report_week_start, date(12));
+    assert_eq!(app_manager.get_week_number(date(12)), None);
+
+    app_manager.config.week_start = Weekday::Sun;
+    app_manager.config.week_numbers = WeekNumbers::Iso;
+    app_manager.open_week_report();
+    assert_eq!(app_manager.report_week_start, date(11));
+    assert_eq!(app_manager.get_week_number(date(11)), Some(3));
+}
+
+#[test]
+fn sessions_added_later_are_checked_like_manual_entries()
+{
+    let (mut app_manager, _) = open_app("clock-add", "14-01-2026 18:00:00");
+    let yesterday = NaiveDate::from_ymd_opt(2026, 1, 13).expect("Invalid test date.");
+
+    let session = app_manager.add_session("Review", None, yesterday, "09:00", "10:30").expect("Adding session failed.");
+    assert_eq!(
+        (session.tag.as_str(), session.start, session.end),
+        ("work", get_time("13-01-2026 09:00:00"), Some(get_time("13-01-2026 10:30:00")))
+    );
+
+    assert_eq!(
+        app_manager.add_session("Standup", Some("meetings"), yesterday, "10:00", "10:15").map(|_| ()),
+        Err(String::from("OVERLAPS EXISTING SESSIONS"))
+    );
+    assert_eq!(
+        app_manager.add_session("Standup", Some("meetings"), yesterday, "10:30", "").map(|_| ()),
+        Err(String::from("INVALID END TIME (HH:MM)"))
+    );
+
+    let session =
+        app_manager.amend_last_session(Some("Code review"), Some("meetings"), Some("11:00")).expect("Amending session failed.");
+    assert_eq!(
+        (session.description.as_str(), session.tag.as_str(), session.end),
+        ("Code review", "meetings", Some(get_time("13-01-2026 11:00:00")))
+    );
+    assert!(app_manager.tags.contains(&String::from("meetings")));
+    assert_eq!(app_manager.sessions.len(), 1);
+}
+
+#[test]
+fn sessions_started_from_a_batch_end_the_running_one()
+{
+    let (mut app_manager, clock) = open_app("clock-batch", "14-01-2026 09:00:00");
+
+    app_manager.start_session("Review", None, None).expect("Starting session failed.");
+    clock.advance(TimeDelta::minutes(45));
+
+    assert_eq!(
+        app_manager.start_session("Standup", Some("meetings"), Some("08:30")).map(|_| ()),
+        Err(String::from("START IS BEFORE THE RUNNING SESSION"))
+    );
+    assert_eq!(app_manager.start_session(" ", None, None).map(|_| ()), Err(String::from("DESCRIPTION IS EMPTY")));
+    assert!(!app_manager.tags.contains(&String::from("meetings")));
+
+    let session = app_manager.start_session("Standup", Some("meetings"), Some("-15m")).expect("Starting session failed.");
+    assert_eq!((session.tag.as_str(), session.start), ("meetings", get_time("14-01-2026 09:30:00")));
+    assert_eq!(app_manager.sessions[0].end, Some(get_time("14-01-2026 09:30:00")));
+    assert!(app_manager.is_last_session_still_running());
+}
@@ -0,0 +1,27 @@
+//! Fixtures shared by the integration tests, each test binary using only some of them.
+#![allow(dead_code)]
+
+use chrono::NaiveDateTime;
+use time_tracker_core::app_manager::AppManager;
+use time_tracker_core::clock::MockClock;
+use time_tracker_core::database_handler::{DatabaseHandler, DATE_FORMAT};
+use time_tracker_core::tui::io::{BufferBackend, Out, Vector2};
+
+/// The clock of runs that add sessions later in the day than [`open_app`] would allow.
+pub const LATE_AFTERNOON: &str = "14-01-2026 18:00:00";
+
+/// Another run of the app on the in-memory database of `database_handler`, at 10:00 on Wednesday 14 January 2026.
+pub fn open_app(database_handler: &DatabaseHandler) -> AppManager
+{
+    open_app_at(database_handler, "14-01-2026 10:00:00")
+}
+
+/// Another run of the app on the in-memory database of `database_handler`, its clock standing at `now`.
+pub fn open_app_at(database_handler: &DatabaseHandler, now: &str) -> AppManager
+{
+    let clock = MockClock::new(NaiveDateTime::parse_from_str(now, DATE_FORMAT).expect("Invalid test time."));
+    let renderer = Out::with_backend(Box::new(BufferBackend::new(Vector2::new(100, 30))));
+    let workspace = database_handler.get_workspace();
+
+    AppManager::with_parts(database_handler.open_workspace(workspace, None), None, renderer, Box::new(clock))
+}
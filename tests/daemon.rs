@@ -0,0 +1,101 @@
+//! The app next to a running daemon, which hands new sessions to it and refuses the changes it can't hand over.
+#![cfg(all(unix, feature = "tui"))]
+
+use chrono::Local;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+use time_tracker_core::app_manager::AppManager;
+use time_tracker_core::clock::MockClock;
+use time_tracker_core::daemon::request_daemon;
+use time_tracker_core::database_handler::{DatabaseHandler, DEFAULT_WORKSPACE};
+use time_tracker_core::tui::io::{BufferBackend, Out, Vector2};
+
+/// A daemon run of the binary on a database in a temp folder, killed with the folder removed when dropped.
+struct Daemon
+{
+    process: Child,
+    root_path: PathBuf,
+}
+
+impl Daemon
+{
+    fn start(root_path: PathBuf) -> Self
+    {
+        let process = Command::new(env!("CARGO_BIN_EXE_time-tracker"))
+            .arg("--daemon")
+            .stdout(Stdio::null())
+            .spawn()
+            .expect("Starting the daemon failed.");
+        let daemon = Daemon {
+            process,
+            root_path,
+        };
+
+        let socket_path = DatabaseHandler::get_daemon_socket_path(DEFAULT_WORKSPACE);
+
+        for _ in 0..100
+        {
+            if socket_path.exists() && request_daemon(DEFAULT_WORKSPACE, "GET", "/status", None).is_some()
+            {
+                return daemon;
+            }
+
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        panic!("The daemon didn't start listening.");
+    }
+}
+
+impl Drop for Daemon
+{
+    fn drop(&mut self)
+    {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        let _ = fs::remove_dir_all(&self.root_path);
+    }
+}
+
+#[test]
+fn changes_the_daemon_cannot_take_are_refused_with_a_status()
+{
+    let root_path = env::temp_dir().join(format!("time-tracker-daemon-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root_path);
+    fs::create_dir_all(&root_path).expect("Creating the test folder failed.");
+
+    // SAFETY: this is the only test of its binary, no other thread reads the environment. The daemon inherits it, so
+    // both keep the database in the temp folder.
+    unsafe {
+        env::set_var("XDG_DATA_HOME", root_path.join("data"));
+        env::set_var("XDG_CONFIG_HOME", root_path.join("config"));
+    }
+
+    let _daemon = Daemon::start(root_path);
+
+    let clock = MockClock::new(Local::now().naive_local());
+    let renderer = Out::with_backend(Box::new(BufferBackend::new(Vector2::new(100, 30))));
+    let mut app_manager = AppManager::with_parts(DatabaseHandler::new(DEFAULT_WORKSPACE, None), None, renderer, Box::new(clock));
+
+    assert!(app_manager.is_read_only());
+    assert!(app_manager.daemon_connected);
+
+    let session = app_manager.start_session("Review", Some("work"), None).expect("Starting through the daemon failed.");
+    assert_eq!(session.description, "Review");
+
+    let (_, status) = request_daemon(DEFAULT_WORKSPACE, "GET", "/status", None).expect("The daemon stopped answering.");
+    let running_description = status.get("running").and_then(|running| running.get("description")?.as_str());
+    assert_eq!(running_description, Some("Review"));
+
+    app_manager.open_calendar();
+    app_manager.cycle_selected_calendar_day_off();
+    assert_eq!(app_manager.get_status_message(), Some("READ-ONLY WHILE THE DAEMON RUNS"));
+    assert!(app_manager.days_off.is_empty());
+
+    assert!(!app_manager.open_selected_calendar_day());
+    assert!(!app_manager.open_manual_entry_for_selected_gap());
+}
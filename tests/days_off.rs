@@ -0,0 +1,68 @@
+//! Days marked as vacation, holiday or sick, which the gap report and goals leave out.
+#![cfg(feature = "tui")]
+
+use chrono::{NaiveDate, TimeDelta};
+use time_tracker_core::config::{Goal, GoalPeriod};
+use time_tracker_core::database_handler::DatabaseHandler;
+use time_tracker_core::day_off::DayOff;
+
+mod common;
+use common::open_app;
+
+fn get_date(day: u32) -> NaiveDate
+{
+    NaiveDate::from_ymd_opt(2026, 1, day).expect("Invalid test date.")
+}
+
+#[test]
+fn marking_a_day_again_goes_through_every_kind_and_is_kept()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("days-off-marking");
+
+    let mut app_manager = open_app(&database_handler);
+    app_manager.open_calendar();
+    app_manager.move_calendar_selection(-2);
+    app_manager.cycle_selected_calendar_day_off();
+    app_manager.cycle_selected_calendar_day_off();
+
+    let mut app_manager = open_app(&database_handler);
+    assert_eq!(app_manager.get_day_off(get_date(12)), Some(DayOff::Holiday));
+    assert_eq!(app_manager.get_day_off(get_date(13)), None);
+
+    app_manager.calendar_date = get_date(12);
+    app_manager.cycle_selected_calendar_day_off();
+    assert_eq!(app_manager.get_day_off(get_date(12)), Some(DayOff::Sick));
+
+    app_manager.cycle_selected_calendar_day_off();
+    assert!(open_app(&database_handler).days_off.is_empty());
+}
+
+#[test]
+fn day_off_has_no_gaps_and_lowers_goal_targets()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("days-off-targets");
+
+    let mut app_manager = open_app(&database_handler);
+    app_manager.config.goals = vec![
+        Goal {
+            tag: String::from("work"),
+            target: TimeDelta::hours(8),
+            period: GoalPeriod::Day,
+        },
+        Goal {
+            tag: String::from("work"),
+            target: TimeDelta::hours(40),
+            period: GoalPeriod::Week,
+        },
+    ];
+    app_manager.open_gaps();
+    assert!(!app_manager.get_gaps().is_empty());
+
+    app_manager.open_calendar();
+    app_manager.cycle_selected_calendar_day_off();
+
+    let targets = app_manager.get_goal_progress().into_iter().map(|(.., target)| target).collect::<Vec<TimeDelta>>();
+    assert_eq!(targets, vec![TimeDelta::zero(), TimeDelta::hours(32)]);
+    assert!(app_manager.get_gaps().is_empty());
+    assert_eq!(app_manager.get_idle_start(), None);
+}
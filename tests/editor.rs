@@ -0,0 +1,38 @@
+//! Sessions edited in the user's editor, through a file nobody else can read that is gone once the editor closes.
+#![cfg(unix)]
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use time_tracker_core::editor::edit_in_editor;
+
+#[test]
+fn the_edited_file_is_private_and_removed_afterwards()
+{
+    let log_path = env::temp_dir().join(format!("time-tracker-editor-test-{}.log", std::process::id()));
+    let _ = fs::remove_file(&log_path);
+
+    // An editor that notes the path and permissions of the file it got, then adds a line to it.
+    let editor = format!(
+        "edit() {{ echo \"$1\" > '{0}'; ls -ld \"$1\" \"$(dirname \"$1\")\" >> '{0}'; echo Reviewed >> \"$1\"; }}; edit",
+        log_path.display()
+    );
+    // SAFETY: this is the only test of its binary, no other thread reads the environment.
+    unsafe {
+        env::set_var("VISUAL", editor);
+    }
+
+    let edited = edit_in_editor("Review\n\nNotes\n").expect("Editing failed.");
+    assert_eq!(edited, "Review\n\nNotes\nReviewed\n");
+
+    let log = fs::read_to_string(&log_path).expect("The editor didn't run.");
+    let _ = fs::remove_file(&log_path);
+    let mut lines = log.lines();
+    let edited_path = Path::new(lines.next().expect("No path logged."));
+
+    let permissions = lines.map(|line| line.split_whitespace().next().unwrap_or_default()).collect::<Vec<&str>>();
+    assert!(permissions.contains(&"-rw-------"), "{log}");
+    assert!(permissions.contains(&"drwx------"), "{log}");
+    assert!(!edited_path.exists());
+    assert!(!edited_path.parent().expect("The file has no folder.").exists());
+}
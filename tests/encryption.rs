@@ -0,0 +1,46 @@
+//! Encrypting a workspace and storing it as plain text again, which has to take every file of the workspace along.
+
+use chrono::{NaiveDateTime, TimeDelta};
+use time_tracker_core::database_handler::{DatabaseHandler, DATE_FORMAT, VALUE_SEPARATOR};
+use time_tracker_core::session::Session;
+
+fn get_time(time: &str) -> NaiveDateTime
+{
+    NaiveDateTime::parse_from_str(time, DATE_FORMAT).expect("Invalid test time.")
+}
+
+#[test]
+fn running_session_and_unsaved_edit_are_converted_too()
+{
+    let mut database_handler = DatabaseHandler::new_ephemeral("encryption-side-files");
+    let workspace = database_handler.get_workspace().to_string();
+
+    let start = get_time("14-01-2026 09:00:00");
+    let mut session = Session::from("Plan the sprint", "meeting", start, Some(start + TimeDelta::hours(1)));
+    session.id = 1;
+    database_handler
+        .export_session(&session.construct_db_string(VALUE_SEPARATOR, DATE_FORMAT), VALUE_SEPARATOR, DATE_FORMAT)
+        .expect("Storing session failed.");
+
+    let running_session = Session::from("Review", "work", get_time("14-01-2026 11:00:00"), None);
+    database_handler.export_running_session(Some(&running_session), VALUE_SEPARATOR, DATE_FORMAT);
+    database_handler.export_unsaved_edit(&[String::from("Review notes")]).expect("Storing the edit failed.");
+
+    database_handler.set_passphrase(Some("correct horse")).expect("Encrypting failed.");
+
+    // Without the key nothing can be read, but nothing panics either.
+    let locked_handler = database_handler.open_workspace(&workspace, None);
+    assert!(locked_handler.import_own_running_session(VALUE_SEPARATOR, DATE_FORMAT).is_none());
+    assert!(locked_handler.import_unsaved_edit().is_empty());
+    assert!(locked_handler.import_sessions(VALUE_SEPARATOR, DATE_FORMAT).0.unwrap_or_default().is_empty());
+    drop(locked_handler);
+
+    database_handler.set_passphrase(None).expect("Decrypting failed.");
+
+    let plain_handler = database_handler.open_workspace(&workspace, None);
+    let stored_running_session =
+        plain_handler.import_own_running_session(VALUE_SEPARATOR, DATE_FORMAT).expect("Running session was lost.");
+    assert_eq!(stored_running_session.description, "Review");
+    assert_eq!(plain_handler.import_unsaved_edit(), ["Review notes"]);
+    assert_eq!(plain_handler.import_sessions(VALUE_SEPARATOR, DATE_FORMAT).0.unwrap_or_default().len(), 1);
+}
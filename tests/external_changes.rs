@@ -0,0 +1,43 @@
+//! Changes another program makes to the database files while the app has them open.
+#![cfg(feature = "tui")]
+
+use time_tracker_core::database_handler::{DatabaseHandler, DATE_FORMAT, VALUE_SEPARATOR};
+
+mod common;
+use common::open_app;
+
+fn store_session(database_handler: &DatabaseHandler, session_string: &str)
+{
+    database_handler.export_session(session_string, VALUE_SEPARATOR, DATE_FORMAT).expect("Storing session failed.");
+}
+
+#[test]
+fn bulk_delete_keeps_sessions_added_from_outside()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("external-bulk-delete");
+    store_session(&database_handler, "12-01-2026;Plan the sprint;meeting;09:00:00;10:15:00;12-01-2026;1;");
+    store_session(&database_handler, "12-01-2026;Review;meeting;11:00:00;11:30:00;12-01-2026;2;");
+
+    let mut app_manager = open_app(&database_handler);
+    store_session(&database_handler, "13-01-2026;Write the report;docs;09:00:00;12:00:00;13-01-2026;3;");
+
+    app_manager.marked_session_ids.push(1);
+    app_manager.delete_marked_sessions();
+
+    let descriptions = open_app(&database_handler).sessions.into_iter().map(|session| session.description).collect::<Vec<_>>();
+    assert_eq!(descriptions, ["Review", "Write the report"]);
+}
+
+#[test]
+fn own_write_does_not_hide_a_change_from_outside()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("external-own-write");
+    store_session(&database_handler, "12-01-2026;Plan the sprint;meeting;09:00:00;10:15:00;12-01-2026;1;");
+
+    let own_handler = database_handler.open_workspace(database_handler.get_workspace(), None);
+    own_handler.import_sessions(VALUE_SEPARATOR, DATE_FORMAT);
+    store_session(&database_handler, "13-01-2026;Write the report;docs;09:00:00;12:00:00;13-01-2026;2;");
+    own_handler.export_tag(&String::from("meeting")).expect("Storing tag failed.");
+
+    assert!(own_handler.has_external_changes());
+}
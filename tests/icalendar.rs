@@ -0,0 +1,75 @@
+//! Sessions written as an iCalendar file, compared with tests/fixtures/export/sessions.ics.
+#![cfg(unix)]
+
+use chrono::{NaiveDate, NaiveDateTime};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use time_tracker_core::config::{Rounding, RoundingMode};
+use time_tracker_core::database_handler::DATE_FORMAT;
+use time_tracker_core::exporter::export_icalendar;
+use time_tracker_core::issues::{IssueProvider, IssueTracker};
+use time_tracker_core::session::Session;
+
+fn get_session(session_id: u64, description: &str, tag: &str, start: &str, end: &str) -> Session
+{
+    let parse = |datetime: &str| NaiveDateTime::parse_from_str(datetime, DATE_FORMAT).expect("Invalid test time.");
+    let mut session = Session::from(description, tag, parse(start), Some(parse(end)));
+    session.id = session_id;
+
+    session
+}
+
+#[test]
+fn sessions_are_exported_in_utc_with_escaped_and_folded_text()
+{
+    // SAFETY: this is the only test of its binary, no other thread reads the environment. Central European time puts
+    // the winter and the summer session an hour apart in UTC.
+    unsafe {
+        env::set_var("TZ", "CET-1CEST,M3.5.0,M10.5.0/3");
+    }
+
+    let mut notes_session = get_session(2, "Review #12", "meeting", "14-07-2026 09:00:00", "14-07-2026 09:20:00");
+    notes_session.notes = String::from("Went through the comments, backslashes \\ included\nNext: the release");
+
+    let mut running_session = get_session(3, "Still running", "work", "15-07-2026 09:00:00", "15-07-2026 09:00:00");
+    running_session.end = None;
+
+    let sessions = [
+        get_session(1, "Plan; the sprint, part 1", "work", "14-01-2026 09:00:00", "14-01-2026 10:14:00"),
+        notes_session,
+        running_session,
+        get_session(4, "Outside of the range", "work", "01-08-2026 09:00:00", "01-08-2026 10:00:00"),
+    ];
+    let rounding = Rounding {
+        mode: RoundingMode::Up,
+        minutes: 15,
+    };
+    let issue_tracker = IssueTracker {
+        provider: IssueProvider::GitHub,
+        url: None,
+        repository: Some(String::from("iamandver/time-tracker")),
+        token: None,
+    };
+
+    let path = env::temp_dir().join(format!("time-tracker-icalendar-test-{}.ics", std::process::id()));
+    let from = NaiveDate::from_ymd_opt(2026, 1, 1).expect("Invalid test date.");
+    let to = NaiveDate::from_ymd_opt(2026, 7, 31).expect("Invalid test date.");
+
+    let exported = export_icalendar(&sessions, from, to, path.to_str().expect("Invalid temp path."), rounding, &issue_tracker);
+    let content = fs::read_to_string(&path).expect("Reading the export failed.");
+    let _ = fs::remove_file(&path);
+
+    assert_eq!(exported, Ok(2));
+
+    // The time of the export changes with every run.
+    let content = content.split("\r\n").filter(|line| !line.starts_with("DTSTAMP:")).collect::<Vec<&str>>().join("\n");
+    let fixture_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join("export").join("sessions.ics");
+
+    if env::var("UPDATE_SNAPSHOTS").is_ok()
+    {
+        fs::write(&fixture_path, &content).expect("Writing the fixture failed.");
+    }
+
+    assert_eq!(content, fs::read_to_string(&fixture_path).expect("Reading the fixture failed."));
+}
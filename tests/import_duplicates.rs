@@ -0,0 +1,56 @@
+//! Imported sessions merged into the tracked ones, skipping those that are tracked already.
+#![cfg(feature = "tui")]
+
+use std::path::PathBuf;
+use time_tracker_core::app_manager::AppManager;
+use time_tracker_core::database_handler::DatabaseHandler;
+use time_tracker_core::importer::ImportSummary;
+
+mod common;
+use common::{open_app_at, LATE_AFTERNOON};
+
+/// Imports the fixture `name` from tests/fixtures/import, returning how many sessions were imported, skipped as
+/// duplicates and not readable.
+fn import_fixture(app_manager: &mut AppManager, name: &str) -> [usize; 3]
+{
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join("import").join(name);
+    app_manager.import_path_buffer = path.to_str().expect("Invalid fixture path.").to_string();
+
+    assert!(app_manager.try_import_file(), "{:?}", app_manager.import_error);
+
+    let ImportSummary {
+        imported,
+        duplicates,
+        invalid,
+    } = app_manager.import_summary.take().expect("Nothing was imported.");
+
+    [imported, duplicates, invalid]
+}
+
+#[test]
+fn sessions_imported_twice_are_stored_once()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("import-duplicates");
+    let mut app_manager = open_app_at(&database_handler, LATE_AFTERNOON);
+
+    // The export repeats its first entry.
+    assert_eq!(import_fixture(&mut app_manager, "toggl.csv"), [3, 1, 2]);
+    assert_eq!(import_fixture(&mut app_manager, "toggl.csv"), [0, 4, 2]);
+
+    let sessions = open_app_at(&database_handler, LATE_AFTERNOON).sessions;
+    let descriptions = sessions.into_iter().map(|session| session.description).collect::<Vec<_>>();
+    assert_eq!(descriptions, ["Fix \"login\" bug, part 1", "Write the docs", "(no description)"]);
+}
+
+#[test]
+fn value_separator_in_imported_text_is_stored_as_a_comma()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("import-separator");
+    let mut app_manager = open_app_at(&database_handler, LATE_AFTERNOON);
+
+    assert_eq!(import_fixture(&mut app_manager, "timewarrior.json"), [3, 0, 2]);
+    assert_eq!(import_fixture(&mut app_manager, "timewarrior.json"), [0, 3, 2]);
+
+    let app_manager = open_app_at(&database_handler, LATE_AFTERNOON);
+    assert!(app_manager.sessions.iter().any(|session| session.description == "Standup, \"daily\", remote"));
+}
@@ -0,0 +1,166 @@
+//! Files exported by other time trackers, read from the fixtures in tests/fixtures/import.
+
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use std::path::PathBuf;
+use time_tracker_core::importer::{import_file, CsvMapping, ImportedFile, ImportedSessions};
+
+const FIXTURE_DATE_FORMAT: &str = "%d-%m-%Y %H:%M";
+
+fn get_fixture_path(name: &str) -> String
+{
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join("import").join(name);
+
+    path.to_str().expect("Invalid fixture path.").to_string()
+}
+
+fn import_sessions(name: &str) -> ImportedSessions
+{
+    match import_file(&get_fixture_path(name))
+    {
+        Ok(ImportedFile::Sessions(imported_sessions)) => imported_sessions,
+        Ok(ImportedFile::Csv(_)) => panic!("{name} was taken for a CSV file to map."),
+        Err(error) => panic!("{name} couldn't be imported: {error}"),
+    }
+}
+
+/// Description, tag, start and end of every imported session, the times formatted like `14-01-2026 09:00`.
+fn describe(imported_sessions: &ImportedSessions) -> Vec<(String, String, String, String)>
+{
+    imported_sessions
+        .sessions
+        .iter()
+        .map(|session| {
+            (
+                session.description.clone(),
+                session.tag.clone(),
+                session.start.format(FIXTURE_DATE_FORMAT).to_string(),
+                session.end.map(|end| end.format(FIXTURE_DATE_FORMAT).to_string()).unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+fn session(description: &str, tag: &str, start: &str, end: &str) -> (String, String, String, String)
+{
+    (description.to_string(), tag.to_string(), start.to_string(), end.to_string())
+}
+
+/// `utc`, given like `14-01-2026 08:00`, in the local time sessions are stored in.
+fn to_local(utc: &str) -> String
+{
+    let utc = NaiveDateTime::parse_from_str(utc, FIXTURE_DATE_FORMAT).expect("Invalid test time.");
+
+    Utc.from_utc_datetime(&utc).with_timezone(&Local).naive_local().format(FIXTURE_DATE_FORMAT).to_string()
+}
+
+#[test]
+fn toggl_export_keeps_quoted_fields_and_skips_malformed_rows()
+{
+    let imported_sessions = import_sessions("toggl.csv");
+
+    assert_eq!(
+        describe(&imported_sessions),
+        [
+            session("Fix \"login\" bug, part 1", "Website", "14-01-2026 09:00", "14-01-2026 10:30"),
+            session("Write the docs", "Docs", "14-01-2026 11:00", "14-01-2026 11:45"),
+            session("(no description)", "imported", "14-01-2026 13:00", "14-01-2026 13:15"),
+            session("Fix \"login\" bug, part 1", "Website", "14-01-2026 09:00", "14-01-2026 10:30"),
+        ]
+    );
+    assert_eq!(imported_sessions.invalid_entries, 2);
+}
+
+#[test]
+fn timewarrior_intervals_are_moved_from_utc_to_local_time()
+{
+    let imported_sessions = import_sessions("timewarrior.json");
+
+    assert_eq!(
+        describe(&imported_sessions),
+        [
+            session("planning, sprint", "work", &to_local("14-01-2026 08:00"), &to_local("14-01-2026 09:30")),
+            session("Standup, \"daily\"; remote", "meeting", &to_local("14-01-2026 10:00"), &to_local("14-01-2026 10:30")),
+            session("(no description)", "imported", &to_local("14-01-2026 11:00"), &to_local("14-01-2026 11:30")),
+        ]
+    );
+    // One interval is still running, another starts at a time that can't be read.
+    assert_eq!(imported_sessions.invalid_entries, 2);
+}
+
+#[test]
+fn timeclock_pairs_clock_ins_with_the_next_clock_out()
+{
+    let imported_sessions = import_sessions("timeclock.txt");
+
+    assert_eq!(
+        describe(&imported_sessions),
+        [
+            session("Write the report", "work:website", "14-01-2026 09:00", "14-01-2026 10:30"),
+            session("(no description)", "meeting", "14-01-2026 11:00", "14-01-2026 11:45"),
+            session("Review again", "work", "14-01-2026 14:00", "14-01-2026 15:00"),
+        ]
+    );
+    // A clock-out without a clock-in, a clock-in followed by another, an unknown code and a clock-in left open.
+    assert_eq!(imported_sessions.invalid_entries, 4);
+}
+
+#[test]
+fn generic_csv_is_mapped_by_its_header()
+{
+    let Ok(ImportedFile::Csv(csv_mapping)) = import_file(&get_fixture_path("generic.csv"))
+    else
+    {
+        panic!("generic.csv wasn't offered for mapping.");
+    };
+
+    assert_eq!(
+        [csv_mapping.description_column, csv_mapping.start_column, csv_mapping.end_column],
+        [0, 2, 3],
+        "{:?}",
+        csv_mapping.rows[0]
+    );
+    assert_eq!(csv_mapping.tag_column, Some(1));
+    assert_eq!(csv_mapping.get_date_format_label(), "DD.MM.YYYY");
+    assert_eq!(csv_mapping.get_sample(0), "Call; follow up \"Acme\"");
+
+    let imported_sessions = csv_mapping.to_imported_sessions();
+
+    assert_eq!(
+        describe(&imported_sessions),
+        [
+            session("Call; follow up \"Acme\"", "sales", "14-01-2026 09:00", "14-01-2026 09:30"),
+            session("Plan the sprint", "imported", "14-01-2026 10:00", "14-01-2026 11:15"),
+            session("Call; follow up \"Acme\"", "sales", "14-01-2026 09:00", "14-01-2026 09:30"),
+        ]
+    );
+    // One row has no end, another dates in a format other than the one of the first row.
+    assert_eq!(imported_sessions.invalid_entries, 2);
+}
+
+#[test]
+fn generic_csv_columns_can_be_mapped_by_hand()
+{
+    let rows = [["When", "What", "Until"], ["2026/01/14 09:00", "Review", "2026/01/14 09:45"]];
+    let mut csv_mapping = CsvMapping::new(rows.iter().map(|row| row.map(String::from).to_vec()).collect());
+
+    // Without known header names the first columns are taken in order.
+    assert_eq!([csv_mapping.description_column, csv_mapping.start_column, csv_mapping.end_column], [0, 1, 2]);
+    assert_eq!(csv_mapping.get_date_format_label(), "YYYY-MM-DD");
+
+    csv_mapping.description_column = 1;
+    csv_mapping.start_column = 0;
+    assert_eq!(csv_mapping.to_imported_sessions().invalid_entries, 1);
+
+    csv_mapping.date_format_index = 5;
+    assert_eq!(describe(&csv_mapping.to_imported_sessions()), [session("Review", "imported", "14-01-2026 09:00", "14-01-2026 09:45")]);
+}
+
+#[test]
+fn unreadable_files_and_unknown_formats_are_refused()
+{
+    let error = import_file(&get_fixture_path("missing.csv")).err().expect("A missing file was imported.");
+    assert!(error.starts_with("CANNOT READ FILE"), "{error}");
+
+    let error = import_file(&get_fixture_path("notes.txt")).err().expect("A list of notes was imported.");
+    assert_eq!(error, "UNKNOWN FILE FORMAT");
+}
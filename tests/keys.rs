@@ -0,0 +1,31 @@
+//! Telling keys apart by the modifiers held with them.
+#![cfg(feature = "tui")]
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use time_tracker_core::tui::io::Key;
+
+#[test]
+fn shift_is_dropped_for_characters_but_other_modifiers_are_kept()
+{
+    assert_eq!(Key::from(KeyEvent::new(KeyCode::Char('S'), KeyModifiers::SHIFT)), Key::new(KeyCode::Char('S')));
+    assert_eq!(Key::from(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL)), Key::ctrl(KeyCode::Char('z')));
+    assert_eq!(Key::from(KeyEvent::new(KeyCode::BackTab, KeyModifiers::NONE)), Key::shift(KeyCode::BackTab));
+    assert_ne!(Key::ctrl(KeyCode::Char('z')), Key::new(KeyCode::Char('z')));
+}
+
+#[test]
+fn only_unmodified_characters_are_typed()
+{
+    assert_eq!(Key::new(KeyCode::Char('a')).get_typed_char(), Some('a'));
+    assert_eq!(Key::alt(KeyCode::Char('a')).get_typed_char(), None);
+    assert_eq!(Key::new(KeyCode::Enter).get_typed_char(), None);
+}
+
+#[test]
+fn keys_are_named_with_their_modifiers()
+{
+    assert_eq!(Key::new(KeyCode::Char('n')).to_string(), "n");
+    assert_eq!(Key::new(KeyCode::Char(' ')).to_string(), "SPACE");
+    assert_eq!(Key::ctrl(KeyCode::Char('z')).to_string(), "CTRL+Z");
+    assert_eq!(Key::shift(KeyCode::BackTab).to_string(), "SHIFT+TAB");
+}
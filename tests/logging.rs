@@ -0,0 +1,27 @@
+//! Records reaching the log file and the debug overlay, filtered by the level from the config.
+
+use log::{info, warn, LevelFilter};
+use std::fs;
+use std::path::PathBuf;
+use time_tracker_core::logger::{get_recent_records, init_logging, set_log_level};
+
+#[test]
+fn records_below_the_level_are_left_out()
+{
+    let log_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("logging").join("time-tracker.log");
+    let _ = fs::remove_file(&log_path);
+
+    init_logging(Some(&log_path));
+    set_log_level(LevelFilter::Warn);
+
+    info!("Loaded 3 sessions");
+    warn!("Pushing to the sync remote failed: PUSH FAILED");
+    log::logger().flush();
+
+    let records = get_recent_records();
+    assert_eq!(records.len(), 1);
+    assert!(records[0].ends_with("WARN  logging: Pushing to the sync remote failed: PUSH FAILED"), "{}", records[0]);
+
+    let log_file = fs::read_to_string(&log_path).expect("Log file was not written.");
+    assert_eq!(log_file, records[0].clone() + "\n");
+}
@@ -0,0 +1,54 @@
+//! The questions of the first run wizard, answered from a script instead of a terminal.
+
+use std::env;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use time_tracker_core::onboarding::ask_onboarding;
+
+#[test]
+fn empty_answers_take_the_defaults()
+{
+    let default_path = env::temp_dir().join("time-tracker-onboarding-defaults");
+    let mut output = Vec::new();
+
+    let onboarding = ask_onboarding(&mut Cursor::new("\n\n\n\n\n"), &mut output, &default_path).expect("Onboarding ended early.");
+
+    assert_eq!(onboarding.database_path, default_path);
+    assert_eq!(onboarding.clock_format.as_deref(), Some("24h"));
+    assert_eq!(onboarding.date_order.as_deref(), Some("dmy"));
+    assert!(onboarding.tags.is_empty());
+    assert!(onboarding.import_path.is_none());
+}
+
+#[test]
+fn invalid_answers_are_asked_again()
+{
+    let database_path = env::temp_dir().join("time-tracker-onboarding-answers");
+    let input = format!("{}\n13h\n12h\nymd\n work, ,meetings \n/no/such/export.csv\n\n", database_path.display());
+    let mut output = Vec::new();
+
+    let onboarding = ask_onboarding(&mut Cursor::new(input), &mut output, Path::new("/unused")).expect("Onboarding ended early.");
+
+    assert_eq!(onboarding.database_path, database_path);
+    assert_eq!(onboarding.clock_format.as_deref(), Some("12h"));
+    assert_eq!(onboarding.date_order.as_deref(), Some("ymd"));
+    assert_eq!(onboarding.tags, ["work", "meetings"]);
+    assert!(onboarding.import_path.is_none());
+    assert_eq!(String::from_utf8_lossy(&output).matches("Invalid answer.").count(), 2);
+
+    assert!(ask_onboarding(&mut Cursor::new("\n24h\n"), &mut Vec::new(), &database_path).is_none());
+}
+
+#[test]
+fn existing_databases_keep_their_settings()
+{
+    let database_path = env::temp_dir().join("time-tracker-onboarding-existing");
+    fs::create_dir_all(&database_path).expect("Creating test folder failed.");
+    fs::write(database_path.join("config.toml"), "").expect("Writing test config failed.");
+
+    let onboarding = ask_onboarding(&mut Cursor::new("\n"), &mut Vec::new(), &database_path).expect("Onboarding ended early.");
+
+    assert_eq!(onboarding.database_path, database_path);
+    assert!(onboarding.clock_format.is_none());
+}
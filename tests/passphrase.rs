@@ -0,0 +1,43 @@
+//! Commands on an encrypted workspace run without a terminal, where no passphrase can be typed.
+#![cfg(all(unix, feature = "tui"))]
+
+use std::env;
+use std::fs;
+use std::process::{Command, Stdio};
+use time_tracker_core::database_handler::{DatabaseHandler, DEFAULT_WORKSPACE};
+use time_tracker_core::exit_code;
+
+#[test]
+fn encrypted_workspace_without_a_terminal_is_a_database_error()
+{
+    let root_path = env::temp_dir().join(format!("time-tracker-passphrase-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root_path);
+    fs::create_dir_all(&root_path).expect("Creating the test folder failed.");
+
+    // SAFETY: this is the only test of its binary, no other thread reads the environment. The commands inherit it, so
+    // all of them use the database in the temp folder.
+    unsafe {
+        env::set_var("XDG_DATA_HOME", root_path.join("data"));
+        env::set_var("XDG_CONFIG_HOME", root_path.join("config"));
+    }
+
+    let mut database_handler = DatabaseHandler::new(DEFAULT_WORKSPACE, None);
+    database_handler.set_passphrase(Some("correct horse")).expect("Encrypting failed.");
+    drop(database_handler);
+
+    for command in ["status", "report"]
+    {
+        let output = Command::new(env!("CARGO_BIN_EXE_time-tracker"))
+            .arg(command)
+            .stdin(Stdio::null())
+            .output()
+            .expect("Running the command failed.");
+        let error = String::from_utf8_lossy(&output.stderr);
+
+        assert_eq!(output.status.code(), Some(exit_code::DATABASE_ERROR), "{command}: {error}");
+        assert!(error.contains("no terminal"), "{command}: {error}");
+        assert!(!error.contains("panicked"), "{command}: {error}");
+    }
+
+    let _ = fs::remove_dir_all(&root_path);
+}
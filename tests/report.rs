@@ -0,0 +1,135 @@
+//! Reports and logs printed by `time-tracker report` and `time-tracker log`, read by scripts and mailed around as they are.
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use time_tracker_core::config::{Config, Rounding, RoundingMode, WorkingDay};
+use time_tracker_core::database_handler::DATE_FORMAT;
+use time_tracker_core::day_off::DayOff;
+use time_tracker_core::report::{
+    format_log, format_log_json, format_report, get_flex_balance, get_flex_time, get_log_sessions, get_report_rows, ReportFormat,
+    ReportGrouping,
+};
+use time_tracker_core::session::Session;
+
+fn get_time(text: &str) -> NaiveDateTime
+{
+    NaiveDateTime::parse_from_str(text, DATE_FORMAT).expect("Invalid test time.")
+}
+
+fn get_sessions() -> Vec<Session>
+{
+    let mut sessions = vec![
+        Session::from("Late fix", "acme:support", get_time("12-01-2026 23:00:00"), Some(get_time("13-01-2026 01:00:00"))),
+        Session::from("Standup", "meetings", get_time("13-01-2026 09:00:00"), Some(get_time("13-01-2026 09:10:00"))),
+        Session::from("Review", "acme:dev", get_time("14-01-2026 09:00:00"), None),
+    ];
+
+    for (id, session) in sessions.iter_mut().enumerate()
+    {
+        session.id = id as u64 + 8;
+    }
+
+    sessions
+}
+
+#[test]
+fn rows_group_the_time_within_the_range()
+{
+    let sessions = get_sessions();
+    let date = |day| NaiveDate::from_ymd_opt(2026, 1, day).expect("Invalid test date.");
+    let now = get_time("14-01-2026 10:30:00");
+    let no_rounding = Rounding {
+        mode: RoundingMode::None,
+        minutes: 0,
+    };
+    let rows = |grouping, rounding| {
+        get_report_rows(&sessions, date(13), date(14), grouping, rounding, now)
+            .into_iter()
+            .map(|(name, duration)| (name, duration.num_minutes()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        rows(ReportGrouping::Tag, no_rounding),
+        [(String::from("acme:dev"), 90), (String::from("acme:support"), 60), (String::from("meetings"), 10)]
+    );
+    assert_eq!(rows(ReportGrouping::Project, no_rounding), [(String::from("acme"), 150), (String::from("-"), 10)]);
+    assert_eq!(rows(ReportGrouping::Day, no_rounding), [(String::from("2026-01-13"), 70), (String::from("2026-01-14"), 90)]);
+
+    let rounding = Rounding {
+        mode: RoundingMode::Up,
+        minutes: 15,
+    };
+    assert_eq!(rows(ReportGrouping::Project, rounding), [(String::from("acme"), 150), (String::from("-"), 15)]);
+}
+
+#[test]
+fn formats_end_with_the_total()
+{
+    let date = NaiveDate::from_ymd_opt(2026, 1, 13).expect("Invalid test date.");
+    let rows = [(String::from("acme, inc"), TimeDelta::minutes(90)), (String::from("meetings"), TimeDelta::minutes(10))];
+    let report = |format| format_report(&rows, date, date, ReportGrouping::Tag, format, None);
+
+    assert_eq!(report(ReportFormat::Table), "TAG            TIME\nacme, inc  01:30:00\nmeetings   00:10:00\nTOTAL      01:40:00\n");
+    assert_eq!(report(ReportFormat::Csv), "tag,duration,seconds\n\"acme, inc\",01:30:00,5400\nmeetings,00:10:00,600\n");
+    assert_eq!(
+        report(ReportFormat::Markdown),
+        "| Tag | Time |\n| --- | ---: |\n| acme, inc | 01:30:00 |\n| meetings | 00:10:00 |\n| **Total** | **01:40:00** |\n"
+    );
+    assert_eq!(
+        report(ReportFormat::Json),
+        "{\"from\":\"2026-01-13\",\"to\":\"2026-01-13\",\"group_by\":\"tag\",\"total_seconds\":6000,\"rows\":[{\"tag\":\"acme, inc\",\"seconds\":5400},{\"tag\":\"meetings\",\"seconds\":600}]}\n"
+    );
+}
+
+#[test]
+fn balance_follows_the_total()
+{
+    let date = NaiveDate::from_ymd_opt(2026, 1, 13).expect("Invalid test date.");
+    let rows = [(String::from("meetings"), TimeDelta::minutes(10))];
+    let report = |format| format_report(&rows, date, date, ReportGrouping::Tag, format, Some(TimeDelta::minutes(-90)));
+
+    assert_eq!(report(ReportFormat::Table), "TAG            TIME\nmeetings   00:10:00\nTOTAL      00:10:00\nBALANCE   -01:30:00\n");
+    assert_eq!(report(ReportFormat::Csv), "tag,duration,seconds\nmeetings,00:10:00,600\n");
+    assert!(report(ReportFormat::Markdown).ends_with("| **Total** | **00:10:00** |\n| **Balance** | **-01:30:00** |\n"));
+    assert!(report(ReportFormat::Json).contains("\"total_seconds\":600,\"balance_seconds\":-5400,"));
+}
+
+#[test]
+fn flex_time_is_the_time_tracked_beyond_the_schedule()
+{
+    let sessions = get_sessions();
+    let date = |day| NaiveDate::from_ymd_opt(2026, 1, day).expect("Invalid test date.");
+    let now = get_time("14-01-2026 10:30:00");
+    let mut config = Config::new();
+    config.schedule = [Some(WorkingDay::Hours(TimeDelta::hours(1))); 7];
+
+    // Monday has the hour of the late fix before midnight, Tuesday the hour after it and the standup.
+    assert_eq!(get_flex_time(&sessions, &config, &[], date(12), date(13), now), TimeDelta::minutes(10));
+    assert_eq!(get_flex_time(&sessions, &config, &[(date(13), DayOff::Sick)], date(12), date(13), now), TimeDelta::minutes(70));
+    assert_eq!(get_flex_balance(&sessions, &config, &[], date(14), now), None);
+
+    // The running day is left out of the balance.
+    config.balance_start = Some(date(11));
+    assert_eq!(get_flex_balance(&sessions, &config, &[], date(14), now), Some(TimeDelta::minutes(-50)));
+}
+
+#[test]
+fn log_lists_the_latest_sessions_first()
+{
+    let sessions = get_sessions();
+    let date = |day| NaiveDate::from_ymd_opt(2026, 1, day).expect("Invalid test date.");
+    let now = get_time("14-01-2026 10:30:00");
+
+    assert_eq!(
+        format_log(&get_log_sessions(&sessions, None, None, None, 2), now),
+        " * 2026-01-14 09:00-now    1h 30m acme:dev Review\n 9 2026-01-13 09:00-09:10  0h 10m meetings Standup\n"
+    );
+    assert_eq!(
+        format_log(&get_log_sessions(&sessions, Some("acme:support"), None, None, 20), now),
+        "8 2026-01-12 23:00-01:00  2h 00m acme:support Late fix\n"
+    );
+    assert_eq!(
+        format_log_json(&get_log_sessions(&sessions, None, Some(date(13)), Some(date(13)), 20), now),
+        "[{\"id\":9,\"description\":\"Standup\",\"tag\":\"meetings\",\"notes\":\"\",\"start\":\"2026-01-13T09:00:00\",\"end\":\"2026-01-13T09:10:00\",\"duration_seconds\":600}]\n"
+    );
+}
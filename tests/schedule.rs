@@ -0,0 +1,73 @@
+//! Working hours per weekday from `[schedule]`, which decide the gaps and what goals expect.
+#![cfg(feature = "tui")]
+
+use chrono::{NaiveDateTime, NaiveTime, TimeDelta, Weekday};
+use std::fs;
+use std::path::PathBuf;
+use time_tracker_core::app_manager::AppManager;
+use time_tracker_core::clock::MockClock;
+use time_tracker_core::config::Config;
+use time_tracker_core::database_handler::{DatabaseHandler, DATE_FORMAT};
+use time_tracker_core::tui::io::{BufferBackend, Out, Vector2};
+
+/// Loads `content` as the config file named `name`.
+fn load_config(name: &str, content: &str) -> Config
+{
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.toml"));
+    fs::write(&path, content).expect("Writing the config failed.");
+
+    Config::load(&path, None)
+}
+
+fn get_time(hour: u32, minute: u32) -> NaiveTime
+{
+    NaiveTime::from_hms_opt(hour, minute, 0).expect("Invalid test time.")
+}
+
+#[test]
+fn days_left_out_of_the_schedule_keep_the_working_hours_on_weekdays()
+{
+    let config = load_config(
+        "schedule",
+        "[working_hours]\nstart = \"08:30\"\nend = \"17:00\"\n\n[schedule]\nfriday = \"6h\"\nsat = \"10:00-12:00\"\nmonday = \"off\"\n",
+    );
+
+    assert_eq!(config.error, None);
+    assert_eq!(config.get_working_hours(Weekday::Mon), None);
+    assert_eq!(config.get_working_hours(Weekday::Tue), Some((get_time(8, 30), get_time(17, 0))));
+    assert_eq!(config.get_working_hours(Weekday::Fri), Some((get_time(8, 30), get_time(14, 30))));
+    assert_eq!(config.get_scheduled_time(Weekday::Sat), TimeDelta::hours(2));
+    assert_eq!(config.get_scheduled_time(Weekday::Sun), TimeDelta::zero());
+}
+
+#[test]
+fn unreadable_schedule_is_reported()
+{
+    let config = load_config("schedule-invalid", "[schedule]\nfriday = \"14:00-10:00\"\n");
+
+    assert_eq!(config.error.as_deref(), Some("INVALID SCHEDULE FOR FRIDAY"));
+}
+
+#[test]
+fn short_friday_ends_its_gaps_early_and_counts_less_for_weekly_goals()
+{
+    let clock = MockClock::new(NaiveDateTime::parse_from_str("16-01-2026 18:00:00", DATE_FORMAT).expect("Invalid test time."));
+    let renderer = Out::with_backend(Box::new(BufferBackend::new(Vector2::new(100, 30))));
+    let mut app_manager =
+        AppManager::with_parts(DatabaseHandler::new_ephemeral("schedule-friday"), None, renderer, Box::new(clock));
+
+    app_manager.config =
+        load_config("schedule-friday", "[goals]\nwork = \"36h/week\"\n\n[schedule]\nmonday = \"8h\"\nfriday = \"6h\"\n");
+    app_manager.open_gaps();
+
+    let gap_end = app_manager.get_gaps().last().map(|(_, end)| end.time());
+    assert_eq!(gap_end, Some(get_time(15, 0)));
+
+    // Tuesday to Thursday keep the 9h of the working hours, so a day off on Monday leaves 36h * (41h - 8h) / 41h.
+    app_manager.open_calendar();
+    app_manager.move_calendar_selection(-4);
+    app_manager.cycle_selected_calendar_day_off();
+
+    let targets = app_manager.get_goal_progress().into_iter().map(|(.., target)| target).collect::<Vec<TimeDelta>>();
+    assert_eq!(targets, vec![TimeDelta::seconds(36 * 3600 * 33 / 41)]);
+}
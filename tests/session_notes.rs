@@ -0,0 +1,51 @@
+//! Notes surviving the trip through the sessions files and the external editor, whatever characters they contain.
+
+use chrono::{NaiveDateTime, TimeDelta};
+use time_tracker_core::database_handler::{DatabaseHandler, DATE_FORMAT, VALUE_SEPARATOR};
+use time_tracker_core::editor::{format_session_text, parse_session_text};
+use time_tracker_core::session::Session;
+
+#[test]
+fn notes_keep_line_breaks_separators_and_backslashes()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("notes");
+    let start = NaiveDateTime::parse_from_str("14-01-2026 09:00:00", DATE_FORMAT).expect("Invalid test time.");
+
+    let mut session = Session::from("Plan the sprint", "meeting", start, Some(start + TimeDelta::hours(1)));
+    session.id = 1;
+    session.notes = String::from("Agenda:\n- estimate; then split\n- C:\\path\\n is not a line break");
+
+    database_handler
+        .export_session(&session.construct_db_string(VALUE_SEPARATOR, DATE_FORMAT), VALUE_SEPARATOR, DATE_FORMAT)
+        .expect("Storing session failed.");
+
+    let (sessions, rejected_lines) = database_handler.import_sessions(VALUE_SEPARATOR, DATE_FORMAT);
+    assert!(rejected_lines.is_empty());
+    let stored_session = sessions.expect("Sessions were not read.").pop().expect("Session was not stored.");
+    assert_eq!(stored_session.notes, session.notes);
+    assert!(stored_session == session);
+}
+
+#[test]
+fn sessions_without_notes_are_stored_as_before()
+{
+    let start = NaiveDateTime::parse_from_str("14-01-2026 09:00:00", DATE_FORMAT).expect("Invalid test time.");
+    let mut session = Session::from("Plan the sprint", "meeting", start, Some(start + TimeDelta::hours(1)));
+    session.id = 1;
+
+    assert_eq!(
+        session.construct_db_string(VALUE_SEPARATOR, DATE_FORMAT),
+        "14-01-2026;Plan the sprint;meeting;09:00:00;10:00:00;14-01-2026;1;"
+    );
+}
+
+#[test]
+fn description_and_notes_are_read_back_from_the_editor_text()
+{
+    let text = format_session_text("Review", "Checked the parser\n  - tests left");
+    assert_eq!(parse_session_text(&text), Some((String::from("Review"), String::from("Checked the parser\n  - tests left"))));
+
+    assert_eq!(parse_session_text("\nRenamed\n"), Some((String::from("Renamed"), String::new())));
+    assert_eq!(parse_session_text("\n\nOnly notes"), Some((String::from("Only notes"), String::new())));
+    assert_eq!(parse_session_text("  \n"), None);
+}
@@ -0,0 +1,69 @@
+//! Editing typed text around a cursor instead of only at its end.
+
+use time_tracker_core::notes_editor::NotesEditor;
+use time_tracker_core::text_input::TextInput;
+
+#[test]
+fn characters_go_in_and_out_at_the_cursor()
+{
+    let mut input = TextInput::from("Fix redirect");
+
+    input.move_to_start();
+    input.move_right();
+    input.move_right();
+    input.move_right();
+    input.insert(' ');
+    "login".chars().for_each(|character| input.insert(character));
+    assert_eq!(input, "Fix login redirect");
+
+    input.delete_after();
+    input.delete_before();
+    assert_eq!(input, "Fix logiredirect");
+    assert_eq!(input.get_text_before_cursor(), "Fix logi");
+    assert_eq!(input.get_char_at_cursor(), Some('r'));
+
+    input.move_to_end();
+    assert_eq!(input.get_char_at_cursor(), None);
+}
+
+#[test]
+fn cursor_counts_characters_not_bytes()
+{
+    let mut input = TextInput::from("Café 東京");
+
+    input.move_left();
+    input.move_left();
+    input.delete_before();
+    assert_eq!(input, "Café東京");
+    assert_eq!(input.get_cursor(), 4);
+}
+
+#[test]
+fn word_deletion_takes_the_spaces_before_the_cursor_along()
+{
+    let mut input = TextInput::from("Write release  notes  ");
+
+    input.delete_word_before();
+    assert_eq!(input, "Write release  ");
+
+    input.move_left();
+    input.delete_word_before();
+    assert_eq!(input, "Write  ");
+
+    input.move_to_start();
+    input.delete_word_before();
+    assert_eq!(input, "Write  ");
+}
+
+#[test]
+fn notes_delete_words_within_the_line_and_join_lines_at_its_start()
+{
+    let mut notes_editor = NotesEditor::new("Agenda\nestimate stories");
+
+    notes_editor.delete_word_before();
+    assert_eq!(notes_editor.get_text(), "Agenda\nestimate");
+
+    notes_editor.move_to_line_start();
+    notes_editor.delete_word_before();
+    assert_eq!(notes_editor.get_text(), "Agendaestimate");
+}
@@ -0,0 +1,64 @@
+//! Durations and session starts typed into the START and TIMER fields.
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use time_tracker_core::time_input::{parse_duration, parse_start_input};
+
+fn now() -> NaiveDateTime
+{
+    NaiveDate::from_ymd_opt(2026, 3, 10).unwrap().and_hms_opt(12, 0, 0).unwrap()
+}
+
+#[test]
+fn durations_add_up_their_units()
+{
+    assert_eq!(parse_duration("1h30m"), Some(TimeDelta::minutes(90)));
+    assert_eq!(parse_duration("45m10s"), Some(TimeDelta::seconds(45 * 60 + 10)));
+    assert_eq!(parse_duration("90"), Some(TimeDelta::minutes(90)));
+    assert_eq!(parse_duration("0m"), None);
+    assert_eq!(parse_duration("1x"), None);
+}
+
+#[test]
+fn oversized_durations_are_rejected()
+{
+    assert_eq!(parse_duration("99999999999999999h"), None);
+    assert_eq!(parse_duration("99999999999999999999m"), None);
+    assert_eq!(parse_duration("9223372036854776s"), None);
+    assert_eq!(parse_duration("2562047788015h2562047788015h"), None);
+}
+
+#[test]
+fn starts_are_offsets_or_clock_times()
+{
+    assert_eq!(parse_start_input("-15m", now()), Some(now() - TimeDelta::minutes(15)));
+    assert_eq!(parse_start_input("1h", now()), Some(now() - TimeDelta::hours(1)));
+    assert_eq!(parse_start_input("09:40", now()), now().date().and_hms_opt(9, 40, 0));
+}
+
+#[test]
+fn later_clock_time_starts_yesterday()
+{
+    let just_after_midnight = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap().and_hms_opt(0, 10, 0).unwrap();
+    let yesterday = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
+
+    assert_eq!(parse_start_input("23:50", just_after_midnight), yesterday.and_hms_opt(23, 50, 0));
+    assert_eq!(parse_start_input("13:00", now()), yesterday.and_hms_opt(13, 0, 0));
+}
+
+#[test]
+fn starts_with_a_day_are_rejected_in_the_future()
+{
+    let yesterday = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
+
+    assert_eq!(parse_start_input("yesterday 23:50", now()), yesterday.and_hms_opt(23, 50, 0));
+    assert_eq!(parse_start_input("2026-03-10 09:40", now()), now().date().and_hms_opt(9, 40, 0));
+    assert_eq!(parse_start_input("today 13:00", now()), None);
+    assert_eq!(parse_start_input("2026-03-11 09:00", now()), None);
+}
+
+#[test]
+fn oversized_start_offsets_are_rejected()
+{
+    assert_eq!(parse_start_input("999999999999", now()), None);
+    assert_eq!(parse_start_input("-99999999999999999h", now()), None);
+}
@@ -0,0 +1,39 @@
+//! Which sessions a Toggl sync sends, going by the links between sessions and Toggl entries.
+#![cfg(feature = "tui")]
+
+use chrono::NaiveDate;
+use time_tracker_core::database_handler::{DatabaseHandler, VALUE_SEPARATOR};
+use time_tracker_core::toggl::get_unsent_sessions;
+
+mod common;
+use common::{open_app_at, LATE_AFTERNOON};
+
+#[test]
+fn session_after_deleting_the_newest_one_is_still_sent()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("toggl-deleted-newest");
+    let date = NaiveDate::from_ymd_opt(2026, 1, 14).expect("Invalid test date.");
+    let since = date.and_hms_opt(0, 0, 0).expect("Invalid test time.");
+
+    let mut app_manager = open_app_at(&database_handler, LATE_AFTERNOON);
+    app_manager.add_session("Standup", Some("meeting"), date, "09:00", "09:15").expect("Adding session failed.");
+    let sent_session = app_manager.add_session("Review", Some("work"), date, "10:00", "11:00").expect("Adding session failed.");
+    database_handler.export_toggl_links(&[(sent_session.id, 900)], VALUE_SEPARATOR).expect("Storing links failed.");
+
+    app_manager.selected_session_index =
+        app_manager.sessions.iter().position(|session| session.id == sent_session.id).expect("Session was not added.");
+    app_manager.delete_selected_session();
+
+    let mut app_manager = open_app_at(&database_handler, LATE_AFTERNOON);
+    let new_session = app_manager.add_session("Write docs", Some("work"), date, "13:00", "14:00").expect("Adding session failed.");
+    assert!(new_session.id > sent_session.id);
+
+    let sessions = open_app_at(&database_handler, LATE_AFTERNOON).sessions;
+    let links = database_handler.import_toggl_links(VALUE_SEPARATOR);
+    let unsent_sessions = get_unsent_sessions(&sessions, &links, since);
+
+    assert_eq!(
+        unsent_sessions.iter().map(|session| session.description.as_str()).collect::<Vec<&str>>(),
+        ["Standup", "Write docs"]
+    );
+}
@@ -0,0 +1,53 @@
+//! Edits a run was making when it stopped, picked up again by the next run on the same database.
+#![cfg(feature = "tui")]
+
+use time_tracker_core::app_state::{CommandState, ConfirmOpen, SessionEditState, SessionInputState, SessionModifyState};
+use time_tracker_core::database_handler::{DatabaseHandler, DATE_FORMAT, VALUE_SEPARATOR};
+use time_tracker_core::text_input::TextInput;
+
+mod common;
+use common::open_app;
+
+#[test]
+fn typed_description_is_offered_after_a_crash()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("unsaved-description");
+
+    let mut crashed_app = open_app(&database_handler);
+    crashed_app.state = CommandState::New(SessionInputState::Description(ConfirmOpen::No));
+    crashed_app.description_buffer = TextInput::from("Write the changelog");
+    crashed_app.journal_unsaved_edit();
+
+    let mut app_manager = open_app(&database_handler);
+    assert!(matches!(app_manager.state, CommandState::RestoreEdit));
+    assert!(app_manager.restore_unsaved_edit());
+    assert!(matches!(app_manager.state, CommandState::New(SessionInputState::Description(ConfirmOpen::No))));
+    assert_eq!(app_manager.description_buffer, "Write the changelog");
+
+    app_manager.discard_unsaved_edit();
+    assert!(matches!(open_app(&database_handler).state, CommandState::Idle));
+}
+
+#[test]
+fn session_changes_are_offered_after_a_crash()
+{
+    let database_handler = DatabaseHandler::new_ephemeral("unsaved-session");
+    database_handler
+        .export_session("12-01-2026;Plan the sprint;meeting;09:00:00;10:15:00;12-01-2026;1;", VALUE_SEPARATOR, DATE_FORMAT)
+        .expect("Storing session failed.");
+    database_handler.export_tag(&String::from("meeting")).expect("Storing tag failed.");
+
+    let mut crashed_app = open_app(&database_handler);
+    crashed_app.selected_session_index = 0;
+    crashed_app.copy_selected_session_to_buffer();
+    crashed_app.session_edit_buffer.as_mut().expect("Session was not copied.").description = String::from("Plan the release");
+    crashed_app.state = CommandState::Modify(SessionModifyState::Edit(SessionEditState::Confirm));
+    crashed_app.journal_unsaved_edit();
+
+    let mut app_manager = open_app(&database_handler);
+    assert!(matches!(app_manager.state, CommandState::RestoreEdit));
+    assert!(app_manager.restore_unsaved_edit());
+    assert!(matches!(app_manager.state, CommandState::Modify(SessionModifyState::Edit(SessionEditState::EditFields(_)))));
+    assert_eq!(app_manager.session_edit_buffer.as_ref().map(|session| session.description.as_str()), Some("Plan the release"));
+    assert_eq!(app_manager.sessions[0].description, "Plan the sprint");
+}
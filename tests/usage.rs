@@ -0,0 +1,29 @@
+//! Command lines naming a command the binary doesn't know, which are answered with the usage instead of opening the app.
+#![cfg(feature = "tui")]
+
+use std::process::{Command, Stdio};
+use time_tracker_core::exit_code;
+
+#[test]
+fn unknown_command_prints_the_usage()
+{
+    let command_lines = [
+        (&["stauts"][..], "stauts"),
+        (&["--workspace", "work", "reprot", "--json"], "reprot"),
+        (&["--serve", "--port", "8080", "now"], "now"),
+    ];
+
+    for (arguments, command) in command_lines
+    {
+        let output = Command::new(env!("CARGO_BIN_EXE_time-tracker"))
+            .args(arguments)
+            .stdin(Stdio::null())
+            .output()
+            .expect("Running the command failed.");
+        let error = String::from_utf8_lossy(&output.stderr);
+
+        assert_eq!(output.status.code(), Some(exit_code::USAGE), "{arguments:?}: {error}");
+        assert!(error.starts_with(&format!("Unknown command {command}.")), "{arguments:?}: {error}");
+        assert!(error.contains("Usage: time-tracker"), "{arguments:?}: {error}");
+    }
+}
@@ -0,0 +1,65 @@
+//! Layout and drawing of the building blocks screens are put together from.
+#![cfg(feature = "tui")]
+
+use time_tracker_core::tui::io::{BufferBackend, Out, Vector2};
+use time_tracker_core::tui::widgets::*;
+
+fn open_buffer(width: u16, height: u16) -> (Out, BufferBackend)
+{
+    let backend = BufferBackend::new(Vector2::new(width, height));
+    let renderer = Out::with_backend(Box::new(backend.clone()));
+
+    (renderer, backend)
+}
+
+#[test]
+fn popups_are_centered_and_stay_on_screen_when_too_large()
+{
+    assert_eq!(get_centered_area(&Vector2::new(10, 4), &Vector2::new(30, 10)), Rect::new(10, 3, 10, 4));
+    assert_eq!(get_centered_area(&Vector2::new(40, 12), &Vector2::new(30, 10)), Rect::new(0, 0, 40, 12));
+}
+
+#[test]
+fn flexible_columns_take_the_width_left_over()
+{
+    let columns = layout_columns(Rect::new(0, 0, 40, 1), &[5, 0, 10], 1);
+
+    assert_eq!(columns, vec![Rect::new(0, 0, 5, 1), Rect::new(6, 0, 23, 1), Rect::new(30, 0, 10, 1)]);
+}
+
+#[test]
+fn popup_draws_a_titled_frame_and_restores_the_colors()
+{
+    let (mut renderer, backend) = open_buffer(20, 8);
+    let popup = Rect::new(2, 1, 12, 4);
+
+    open_popup(&mut renderer, &popup, "HI");
+    draw_list_row(&mut renderer, "one", &popup.at(1, 1), true);
+    draw_list_row(&mut renderer, "two", &popup.at(1, 2), false);
+    close_popup(&mut renderer);
+    renderer.render();
+
+    renderer.check_color_stacks();
+    assert_eq!(backend.get_text(), "\n  ╔═ HI ═════╗\n  ║ ▶ one    ║\n  ║   two    ║\n  ╚══════════╝\n\n\n");
+}
+
+#[test]
+fn list_scrolls_to_keep_the_selected_row_in_view()
+{
+    let (mut renderer, backend) = open_buffer(12, 3);
+    let rows = ["a", "b", "c", "d", "e"].map(String::from);
+
+    let first_row = draw_list(&mut renderer, &rows, Some(3), Rect::new(0, 0, 6, 3));
+    renderer.render();
+
+    assert_eq!(first_row, 1);
+    assert_eq!(backend.get_text(), "   b\n   c\n ▶ d");
+}
+
+#[test]
+fn scroll_keeps_the_selected_row_at_the_bottom_once_it_passes_the_view()
+{
+    assert_eq!(get_first_visible_row(2, 5), 0);
+    assert_eq!(get_first_visible_row(7, 5), 3);
+    assert_eq!(get_first_visible_row(7, 0), 7);
+}